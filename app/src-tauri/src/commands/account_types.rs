@@ -0,0 +1,153 @@
+use sqlx::{SqliteExecutor, SqlitePool};
+use tauri::State;
+
+use super::now;
+use crate::error::{Error, Result};
+use crate::models::{
+    AccountCategory, AccountType, CreateCustomAccountTypeInput, CustomAccountType,
+    UpdateCustomAccountTypeInput,
+};
+
+async fn fetch_custom_type<'e>(
+    executor: impl SqliteExecutor<'e>,
+    slug: &str,
+) -> Result<CustomAccountType> {
+    sqlx::query_as::<_, CustomAccountType>("SELECT * FROM custom_account_types WHERE slug = ?")
+        .bind(slug)
+        .fetch_optional(executor)
+        .await?
+        .ok_or_else(|| Error::not_found("account type", slug))
+}
+
+/// The category an account of `account_type` belongs to, looking custom types up
+/// in `custom_account_types`.
+pub(crate) async fn resolve_category<'e>(
+    executor: impl SqliteExecutor<'e>,
+    account_type: &AccountType,
+) -> Result<AccountCategory> {
+    match account_type {
+        AccountType::Custom(slug) => Ok(fetch_custom_type(executor, slug).await?.category),
+        builtin => Ok(builtin
+            .category()
+            .expect("built-in account types have a fixed category")),
+    }
+}
+
+#[tauri::command]
+pub async fn get_custom_account_types(
+    pool: State<'_, SqlitePool>,
+) -> Result<Vec<CustomAccountType>> {
+    let types =
+        sqlx::query_as::<_, CustomAccountType>("SELECT * FROM custom_account_types ORDER BY label")
+            .fetch_all(pool.inner())
+            .await?;
+    Ok(types)
+}
+
+#[tauri::command]
+pub async fn create_custom_account_type(
+    pool: State<'_, SqlitePool>,
+    input: CreateCustomAccountTypeInput,
+) -> Result<CustomAccountType> {
+    input.validate()?;
+
+    let exists: bool =
+        sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM custom_account_types WHERE slug = ?)")
+            .bind(&input.slug)
+            .fetch_one(pool.inner())
+            .await?;
+    if exists {
+        return Err(Error::Conflict(format!(
+            "account type '{}' already exists",
+            input.slug
+        )));
+    }
+
+    let custom_type = CustomAccountType {
+        slug: input.slug,
+        label: input.label.trim().to_string(),
+        category: input.category,
+        icon: input.icon,
+        created_at: now(),
+    };
+    sqlx::query(
+        "INSERT INTO custom_account_types (slug, label, category, icon, created_at)
+         VALUES (?, ?, ?, ?, ?)",
+    )
+    .bind(&custom_type.slug)
+    .bind(&custom_type.label)
+    .bind(custom_type.category.as_str())
+    .bind(&custom_type.icon)
+    .bind(&custom_type.created_at)
+    .execute(pool.inner())
+    .await?;
+
+    Ok(custom_type)
+}
+
+/// Changing a type's category moves every account of that type with it.
+#[tauri::command]
+pub async fn update_custom_account_type(
+    pool: State<'_, SqlitePool>,
+    slug: String,
+    input: UpdateCustomAccountTypeInput,
+) -> Result<CustomAccountType> {
+    input.validate()?;
+
+    let mut tx = pool.begin().await?;
+    let mut custom_type = fetch_custom_type(&mut *tx, &slug).await?;
+    if let Some(label) = input.label {
+        custom_type.label = label.trim().to_string();
+    }
+    if input.icon.is_some() {
+        custom_type.icon = input.icon;
+    }
+    if let Some(category) = input.category {
+        custom_type.category = category;
+    }
+
+    sqlx::query("UPDATE custom_account_types SET label = ?, category = ?, icon = ? WHERE slug = ?")
+        .bind(&custom_type.label)
+        .bind(custom_type.category.as_str())
+        .bind(&custom_type.icon)
+        .bind(&slug)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query(
+        "UPDATE accounts SET category = ?, updated_at = ? WHERE account_type = ? AND category != ?",
+    )
+    .bind(custom_type.category.as_str())
+    .bind(now())
+    .bind(&slug)
+    .bind(custom_type.category.as_str())
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(custom_type)
+}
+
+/// Only unused types can be deleted; accounts must be moved to another type first.
+#[tauri::command]
+pub async fn delete_custom_account_type(pool: State<'_, SqlitePool>, slug: String) -> Result<()> {
+    let mut tx = pool.begin().await?;
+    fetch_custom_type(&mut *tx, &slug).await?;
+
+    let in_use: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM accounts WHERE account_type = ?")
+        .bind(&slug)
+        .fetch_one(&mut *tx)
+        .await?;
+    if in_use > 0 {
+        return Err(Error::Conflict(format!(
+            "account type '{slug}' is used by {in_use} account(s)"
+        )));
+    }
+
+    sqlx::query("DELETE FROM custom_account_types WHERE slug = ?")
+        .bind(&slug)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(())
+}
@@ -0,0 +1,1182 @@
+use std::collections::{HashMap, HashSet};
+
+use sqlx::{SqliteConnection, SqliteExecutor, SqlitePool};
+use tauri::{AppHandle, State};
+use uuid::Uuid;
+
+use super::account_types::resolve_category;
+use super::attachments::{remove_unreferenced_files, stored_names_for_account};
+use super::balances::{
+    fetch_entries, fetch_latest_entry, find_duplicates, insert_balance_entry, new_balance_entry,
+    upsert_entry,
+};
+use super::history::record_change;
+use super::tags::fetch_account_tags;
+use super::{non_empty, non_empty_patch, normalize_date, now, today, validate_ordering};
+use crate::error::{Error, Result};
+use crate::models::{
+    validate_currency, validate_ownership_percent, Account, AccountCategory, AccountIssue,
+    AccountType, AccountWithBalance, BalanceEntry, CreateAccountInput, MergeStrategy, MergeSummary,
+    UpdateAccountInput, DEFAULT_CURRENCY,
+};
+use crate::search;
+
+pub(crate) async fn fetch_account<'e>(
+    executor: impl SqliteExecutor<'e>,
+    id: &str,
+) -> Result<Account> {
+    sqlx::query_as::<_, Account>("SELECT * FROM accounts WHERE id = ?")
+        .bind(id)
+        .fetch_optional(executor)
+        .await?
+        .ok_or_else(|| Error::not_found("account", id))
+}
+
+/// Selects `accounts` as `a` joined to each account's most recent balance entry,
+/// ready for a `WHERE` clause and decoding as `AccountWithBalance`.
+pub(crate) const ACCOUNT_WITH_BALANCE_SELECT: &str = "
+    SELECT a.*,
+        COALESCE(b.balance, 0.0) AS current_balance,
+        COALESCE(b.balance, 0.0) * a.ownership_percent / 100.0 AS owned_balance,
+        b.date AS balance_date
+    FROM accounts a
+    LEFT JOIN balance_entries b ON b.id = (
+        SELECT id FROM balance_entries
+        WHERE account_id = a.id
+        ORDER BY date DESC
+        LIMIT 1
+    )";
+
+/// Like `ACCOUNT_WITH_BALANCE_SELECT`, but joined to the latest entry on or before
+/// a date, which must be bound first.
+pub(crate) const ACCOUNT_WITH_BALANCE_AS_OF_SELECT: &str = "
+    SELECT a.*,
+        COALESCE(b.balance, 0.0) AS current_balance,
+        COALESCE(b.balance, 0.0) * a.ownership_percent / 100.0 AS owned_balance,
+        b.date AS balance_date
+    FROM accounts a
+    LEFT JOIN balance_entries b ON b.id = (
+        SELECT id FROM balance_entries
+        WHERE account_id = a.id AND date <= ?
+        ORDER BY date DESC
+        LIMIT 1
+    )";
+
+/// Active, unarchived accounts paired with their most recent balance entry.
+pub(crate) async fn fetch_accounts_with_balance<'e>(
+    executor: impl SqliteExecutor<'e>,
+) -> Result<Vec<AccountWithBalance>> {
+    let sql = format!(
+        "{ACCOUNT_WITH_BALANCE_SELECT}
+         WHERE a.is_active = 1 AND a.archived_at IS NULL
+         ORDER BY a.sort_order, a.name"
+    );
+    let accounts = sqlx::query_as::<_, AccountWithBalance>(&sql)
+        .fetch_all(executor)
+        .await?;
+    Ok(accounts)
+}
+
+/// A fresh, active account; callers fill in the optional details before inserting.
+pub(crate) fn new_account(
+    name: &str,
+    account_type: AccountType,
+    category: AccountCategory,
+    currency: String,
+) -> Account {
+    let timestamp = now();
+    let liquidity = account_type.default_liquidity();
+    Account {
+        id: Uuid::new_v4().to_string(),
+        name: name.trim().to_string(),
+        account_type,
+        category,
+        institution: None,
+        description: None,
+        currency,
+        is_active: true,
+        created_at: timestamp.clone(),
+        updated_at: timestamp,
+        archived_at: None,
+        group_id: None,
+        sort_order: 0,
+        linked_account_id: None,
+        ownership_percent: 100.0,
+        color: None,
+        icon: None,
+        interest_rate: None,
+        opened_at: None,
+        closed_at: None,
+        exclude_from_net_worth: false,
+        credit_limit: None,
+        reminder_frequency: None,
+        external_ref: None,
+        statement_day: None,
+        liquidity,
+        is_emergency_fund: false,
+        tags: None,
+    }
+}
+
+/// Fails with a conflict naming the other account when `external_ref` is already
+/// taken by an account other than `account_id`.
+pub(crate) async fn check_external_ref<'e>(
+    executor: impl SqliteExecutor<'e>,
+    account_id: &str,
+    external_ref: Option<&str>,
+) -> Result<()> {
+    let Some(external_ref) = external_ref else {
+        return Ok(());
+    };
+    let taken_by: Option<String> =
+        sqlx::query_scalar("SELECT name FROM accounts WHERE external_ref = ? AND id != ?")
+            .bind(external_ref)
+            .bind(account_id)
+            .fetch_optional(executor)
+            .await?;
+    match taken_by {
+        Some(name) => Err(Error::Conflict(format!(
+            "external reference '{external_ref}' is already used by account '{name}'"
+        ))),
+        None => Ok(()),
+    }
+}
+
+fn check_credit_limit(account: &Account) -> Result<()> {
+    if account.credit_limit.is_some() && account.account_type != AccountType::CreditCard {
+        return Err(Error::validation(
+            "credit_limit",
+            "only credit card accounts have a credit limit",
+        ));
+    }
+    Ok(())
+}
+
+/// Inserts `account` at the end of the manual order, updating its `sort_order`.
+pub(crate) async fn insert_account(
+    conn: &mut SqliteConnection,
+    account: &mut Account,
+) -> Result<()> {
+    account.sort_order =
+        sqlx::query_scalar("SELECT COALESCE(MAX(sort_order) + 1, 0) FROM accounts")
+            .fetch_one(&mut *conn)
+            .await?;
+    insert_account_row(conn, account).await
+}
+
+/// Inserts `account` exactly as given, including its `sort_order`.
+pub(crate) async fn insert_account_row<'e>(
+    executor: impl SqliteExecutor<'e>,
+    account: &Account,
+) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO accounts (id, name, account_type, category, institution, description, currency, is_active, created_at, updated_at, archived_at, group_id, sort_order, linked_account_id, ownership_percent, color, icon, interest_rate, opened_at, closed_at, exclude_from_net_worth, credit_limit, reminder_frequency, external_ref, statement_day, liquidity, is_emergency_fund)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(&account.id)
+    .bind(&account.name)
+    .bind(account.account_type.as_str())
+    .bind(account.category.as_str())
+    .bind(&account.institution)
+    .bind(&account.description)
+    .bind(&account.currency)
+    .bind(account.is_active)
+    .bind(&account.created_at)
+    .bind(&account.updated_at)
+    .bind(&account.archived_at)
+    .bind(&account.group_id)
+    .bind(account.sort_order)
+    .bind(&account.linked_account_id)
+    .bind(account.ownership_percent)
+    .bind(&account.color)
+    .bind(&account.icon)
+    .bind(account.interest_rate)
+    .bind(&account.opened_at)
+    .bind(&account.closed_at)
+    .bind(account.exclude_from_net_worth)
+    .bind(account.credit_limit)
+    .bind(account.reminder_frequency)
+    .bind(&account.external_ref)
+    .bind(account.statement_day)
+    .bind(account.liquidity)
+    .bind(account.is_emergency_fund)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+/// Creates the account and, when `opening_balance` is given, its first balance
+/// entry in the same transaction.
+#[tauri::command]
+pub async fn create_account(
+    pool: State<'_, SqlitePool>,
+    input: CreateAccountInput,
+) -> Result<Account> {
+    input.validate()?;
+    let opened_at = match &input.opened_at {
+        Some(value) => {
+            let date = normalize_date("opened_at", value)?;
+            if date > today() {
+                return Err(Error::validation("opened_at", "must not be in the future"));
+            }
+            Some(date)
+        }
+        None => None,
+    };
+
+    let currency = input
+        .currency
+        .unwrap_or_else(|| DEFAULT_CURRENCY.to_string());
+    let mut tx = pool.begin().await?;
+    let category = resolve_category(&mut *tx, &input.account_type).await?;
+    let mut account = new_account(&input.name, input.account_type, category, currency);
+    account.institution = non_empty(input.institution);
+    account.description = non_empty(input.description);
+    if let Some(percent) = input.ownership_percent {
+        account.ownership_percent = percent;
+    }
+    account.color = input.color;
+    account.icon = non_empty(input.icon);
+    account.interest_rate = input.interest_rate;
+    account.opened_at = opened_at;
+    account.exclude_from_net_worth = input.exclude_from_net_worth;
+    account.credit_limit = input.credit_limit;
+    check_credit_limit(&account)?;
+    account.reminder_frequency = input.reminder_frequency;
+    account.external_ref = non_empty(input.external_ref);
+    account.statement_day = input.statement_day;
+    if let Some(liquidity) = input.liquidity {
+        account.liquidity = liquidity;
+    }
+    account.is_emergency_fund = input.is_emergency_fund;
+    check_external_ref(&mut *tx, &account.id, account.external_ref.as_deref()).await?;
+
+    insert_account(&mut tx, &mut account).await?;
+    if let Some(balance) = input.opening_balance {
+        let date = account.opened_at.clone().unwrap_or_else(today);
+        let entry = new_balance_entry(&account, date, balance, None);
+        insert_balance_entry(&mut *tx, &entry).await?;
+    }
+
+    tx.commit().await?;
+    Ok(account)
+}
+
+/// Starts a new account from an existing one's type, institution, currency and
+/// description. History and archive status are not copied; with
+/// `copy_latest_balance` the new account opens today at the source's latest balance.
+#[tauri::command]
+pub async fn duplicate_account(
+    pool: State<'_, SqlitePool>,
+    account_id: String,
+    new_name: String,
+    copy_latest_balance: bool,
+) -> Result<Account> {
+    let mut tx = pool.begin().await?;
+    let source = fetch_account(&mut *tx, &account_id).await?;
+
+    if new_name.trim().is_empty() {
+        return Err(Error::validation("name", "must not be empty"));
+    }
+    let mut account = new_account(
+        &new_name,
+        source.account_type,
+        source.category,
+        source.currency,
+    );
+    account.institution = source.institution;
+    account.description = source.description;
+    account.liquidity = source.liquidity;
+    if copy_latest_balance {
+        account.opened_at = Some(today());
+    }
+    insert_account(&mut tx, &mut account).await?;
+
+    if copy_latest_balance {
+        let balance = fetch_latest_entry(&mut *tx, &account_id)
+            .await?
+            .map_or(0.0, |entry| entry.balance);
+        let entry = new_balance_entry(&account, today(), balance, None);
+        insert_balance_entry(&mut *tx, &entry).await?;
+    }
+
+    tx.commit().await?;
+    Ok(account)
+}
+
+/// Active, unarchived accounts with their balance as of `as_of` (default today):
+/// the latest entry on or before that date, or zero when there is none yet.
+#[tauri::command]
+pub async fn get_accounts_with_balance(
+    pool: State<'_, SqlitePool>,
+    as_of: Option<String>,
+) -> Result<Vec<AccountWithBalance>> {
+    let as_of = match as_of {
+        Some(date) => normalize_date("as_of", &date)?,
+        None => today(),
+    };
+    let sql = format!(
+        "{ACCOUNT_WITH_BALANCE_AS_OF_SELECT}
+         WHERE a.is_active = 1 AND a.archived_at IS NULL
+         ORDER BY a.sort_order, a.name"
+    );
+    let accounts = sqlx::query_as::<_, AccountWithBalance>(&sql)
+        .bind(&as_of)
+        .fetch_all(pool.inner())
+        .await?;
+    Ok(accounts)
+}
+
+/// Matches `query` loosely against name, institution and description, ignoring
+/// case and accents. Results are ordered by how well they match, then by most
+/// recent balance entry; an empty query returns every account in the usual order.
+#[tauri::command]
+pub async fn search_accounts(
+    pool: State<'_, SqlitePool>,
+    query: String,
+    include_archived: bool,
+) -> Result<Vec<AccountWithBalance>> {
+    let sql = format!(
+        "{ACCOUNT_WITH_BALANCE_SELECT}
+         WHERE a.is_active = 1 AND (? OR a.archived_at IS NULL)
+         ORDER BY a.sort_order, a.name"
+    );
+    let accounts = sqlx::query_as::<_, AccountWithBalance>(&sql)
+        .bind(include_archived)
+        .fetch_all(pool.inner())
+        .await?;
+
+    let query = search::fold(query.trim());
+    if query.is_empty() {
+        return Ok(accounts);
+    }
+
+    let mut scored: Vec<(f64, AccountWithBalance)> = accounts
+        .into_iter()
+        .filter_map(|account| {
+            let fields = [
+                (Some(&account.account.name), 1.0),
+                (account.account.institution.as_ref(), 0.8),
+                (account.account.description.as_ref(), 0.6),
+            ];
+            let best = fields
+                .into_iter()
+                .filter_map(|(text, weight)| {
+                    search::score(&query, &search::fold(text?)).map(|score| score * weight)
+                })
+                .reduce(f64::max)?;
+            Some((best, account))
+        })
+        .collect();
+    scored.sort_by(|(a_score, a), (b_score, b)| {
+        b_score
+            .total_cmp(a_score)
+            .then_with(|| b.balance_date.cmp(&a.balance_date))
+    });
+
+    Ok(scored.into_iter().map(|(_, account)| account).collect())
+}
+
+#[tauri::command]
+pub async fn get_accounts(
+    pool: State<'_, SqlitePool>,
+    include_archived: bool,
+) -> Result<Vec<Account>> {
+    let accounts = sqlx::query_as::<_, Account>(
+        "SELECT * FROM accounts
+         WHERE is_active = 1 AND (? OR archived_at IS NULL)
+         ORDER BY sort_order, name",
+    )
+    .bind(include_archived)
+    .fetch_all(pool.inner())
+    .await?;
+    Ok(accounts)
+}
+
+#[tauri::command]
+pub async fn get_account(
+    pool: State<'_, SqlitePool>,
+    id: String,
+    include_tags: bool,
+) -> Result<Account> {
+    let mut account = fetch_account(pool.inner(), &id).await?;
+    if include_tags {
+        account.tags = Some(fetch_account_tags(pool.inner(), &id).await?);
+    }
+    Ok(account)
+}
+
+/// Applies `input`, recording any change of name or institution in the account's
+/// field history.
+#[tauri::command]
+pub async fn update_account(
+    pool: State<'_, SqlitePool>,
+    id: String,
+    input: UpdateAccountInput,
+) -> Result<Account> {
+    input.validate()?;
+
+    let mut tx = pool.begin().await?;
+    let mut account = fetch_account(&mut *tx, &id).await?;
+    let previous = account.clone();
+    account.category = resolve_category(&mut *tx, &account.account_type).await?;
+    if let Some(name) = input.name {
+        account.name = name.trim().to_string();
+    }
+    non_empty_patch(input.institution).apply(&mut account.institution);
+    non_empty_patch(input.description).apply(&mut account.description);
+    if let Some(currency) = input.currency {
+        account.currency = currency;
+    }
+    if let Some(is_active) = input.is_active {
+        account.is_active = is_active;
+    }
+    if input.color.is_some() {
+        account.color = input.color;
+    }
+    if input.icon.is_some() {
+        account.icon = non_empty(input.icon);
+    }
+    if input.interest_rate.is_some() {
+        account.interest_rate = input.interest_rate;
+    }
+    if let Some(exclude) = input.exclude_from_net_worth {
+        account.exclude_from_net_worth = exclude;
+    }
+    input.credit_limit.apply(&mut account.credit_limit);
+    check_credit_limit(&account)?;
+    input
+        .reminder_frequency
+        .apply(&mut account.reminder_frequency);
+    non_empty_patch(input.external_ref).apply(&mut account.external_ref);
+    check_external_ref(&mut *tx, &account.id, account.external_ref.as_deref()).await?;
+    input.statement_day.apply(&mut account.statement_day);
+    if let Some(liquidity) = input.liquidity {
+        account.liquidity = liquidity;
+    }
+    if let Some(is_emergency_fund) = input.is_emergency_fund {
+        account.is_emergency_fund = is_emergency_fund;
+    }
+    account.updated_at = now();
+
+    sqlx::query(
+        "UPDATE accounts
+         SET name = ?, category = ?, institution = ?, description = ?, currency = ?, is_active = ?, color = ?, icon = ?, interest_rate = ?, exclude_from_net_worth = ?, credit_limit = ?, reminder_frequency = ?, external_ref = ?, statement_day = ?, liquidity = ?, is_emergency_fund = ?, updated_at = ?
+         WHERE id = ?",
+    )
+    .bind(&account.name)
+    .bind(account.category.as_str())
+    .bind(&account.institution)
+    .bind(&account.description)
+    .bind(&account.currency)
+    .bind(account.is_active)
+    .bind(&account.color)
+    .bind(&account.icon)
+    .bind(account.interest_rate)
+    .bind(account.exclude_from_net_worth)
+    .bind(account.credit_limit)
+    .bind(account.reminder_frequency)
+    .bind(&account.external_ref)
+    .bind(account.statement_day)
+    .bind(account.liquidity)
+    .bind(account.is_emergency_fund)
+    .bind(&account.updated_at)
+    .bind(&account.id)
+    .execute(&mut *tx)
+    .await?;
+
+    record_change(
+        &mut *tx,
+        &id,
+        "name",
+        Some(&previous.name),
+        Some(&account.name),
+        &account.updated_at,
+    )
+    .await?;
+    record_change(
+        &mut *tx,
+        &id,
+        "institution",
+        previous.institution.as_deref(),
+        account.institution.as_deref(),
+        &account.updated_at,
+    )
+    .await?;
+
+    tx.commit().await?;
+    Ok(account)
+}
+
+/// Moves the account to `new_type`, recomputing its category. A change between
+/// asset and liability is refused unless `negate_balances` is set, in which case
+/// every balance entry's sign is flipped to suit the new category and any links
+/// between this account and others are removed, since they only run from a
+/// liability to an asset.
+#[tauri::command]
+pub async fn change_account_type(
+    pool: State<'_, SqlitePool>,
+    account_id: String,
+    new_type: AccountType,
+    negate_balances: bool,
+) -> Result<Account> {
+    let mut tx = pool.begin().await?;
+    let mut account = fetch_account(&mut *tx, &account_id).await?;
+    if account.account_type == new_type {
+        return Ok(account);
+    }
+
+    let category = resolve_category(&mut *tx, &new_type).await?;
+    let flips = category != account.category;
+    if flips && !negate_balances {
+        return Err(Error::Conflict(format!(
+            "'{}' would move from {} to {}; pass negate_balances to flip its balances",
+            account.name, account.category, category
+        )));
+    }
+
+    let previous_type = account.account_type.clone();
+    // A liquidity chosen by hand is kept; one left at the old type's default follows the type.
+    if account.liquidity == previous_type.default_liquidity() {
+        account.liquidity = new_type.default_liquidity();
+    }
+    account.account_type = new_type;
+    account.category = category;
+    if account.account_type != AccountType::CreditCard {
+        account.credit_limit = None;
+    }
+    if flips {
+        account.linked_account_id = None;
+    }
+    account.updated_at = now();
+
+    sqlx::query(
+        "UPDATE accounts
+         SET account_type = ?, category = ?, credit_limit = ?, linked_account_id = ?, liquidity = ?, updated_at = ?
+         WHERE id = ?",
+    )
+    .bind(account.account_type.as_str())
+    .bind(account.category.as_str())
+    .bind(account.credit_limit)
+    .bind(&account.linked_account_id)
+    .bind(account.liquidity)
+    .bind(&account.updated_at)
+    .bind(&account_id)
+    .execute(&mut *tx)
+    .await?;
+
+    if flips {
+        sqlx::query("UPDATE balance_entries
+             SET balance = -balance, balance_minor = -balance_minor, original_amount = -original_amount
+             WHERE account_id = ?")
+            .bind(&account_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("UPDATE accounts SET linked_account_id = NULL WHERE linked_account_id = ?")
+            .bind(&account_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    record_change(
+        &mut *tx,
+        &account_id,
+        "account_type",
+        Some(previous_type.as_str()),
+        Some(account.account_type.as_str()),
+        &account.updated_at,
+    )
+    .await?;
+
+    tx.commit().await?;
+    Ok(account)
+}
+
+/// Scans every stored account for a type, category or currency that doesn't
+/// validate, e.g. after hand-editing the database or an old import, and for
+/// entries stored twice under different spellings of a date. Rows are read as
+/// raw text so one bad row can't hide the rest.
+/// What is wrong with `currency`, with the nearest known codes; `None` for an
+/// ISO 4217 code.
+pub(crate) fn currency_problem(currency: &str) -> Option<String> {
+    let error = validate_currency(currency).err()?;
+    Some(match &error {
+        Error::UnknownCurrency { suggestions, .. } if !suggestions.is_empty() => {
+            format!("{error}; did you mean {}?", suggestions.join(", "))
+        }
+        _ => error.to_string(),
+    })
+}
+
+#[tauri::command]
+pub async fn validate_accounts(pool: State<'_, SqlitePool>) -> Result<Vec<AccountIssue>> {
+    let rows: Vec<(String, String, String, String, String)> = sqlx::query_as(
+        "SELECT id, name, account_type, category, currency FROM accounts ORDER BY sort_order, name",
+    )
+    .fetch_all(pool.inner())
+    .await?;
+    let custom_types: HashMap<String, String> =
+        sqlx::query_as("SELECT slug, category FROM custom_account_types")
+            .fetch_all(pool.inner())
+            .await?
+            .into_iter()
+            .collect();
+    let duplicates = find_duplicates(pool.inner(), None).await?;
+
+    let mut issues = Vec::new();
+    for (id, name, account_type, category, currency) in rows {
+        let mut issue = |field: &str, value: &str, message: String| {
+            issues.push(AccountIssue {
+                account_id: id.clone(),
+                name: name.clone(),
+                field: field.to_string(),
+                value: value.to_string(),
+                message,
+            });
+        };
+
+        let expected = match account_type.parse::<AccountType>() {
+            Ok(AccountType::Custom(slug)) => match custom_types.get(&slug) {
+                Some(category) => category.parse::<AccountCategory>().ok(),
+                None => {
+                    issue(
+                        "account_type",
+                        &account_type,
+                        format!("no custom account type '{slug}' exists"),
+                    );
+                    None
+                }
+            },
+            Ok(builtin) => builtin.category(),
+            Err(error) => {
+                issue("account_type", &account_type, error.to_string());
+                None
+            }
+        };
+        match category.parse::<AccountCategory>() {
+            Ok(actual) => {
+                if let Some(expected) = expected.filter(|expected| *expected != actual) {
+                    issue(
+                        "category",
+                        &category,
+                        format!("'{account_type}' accounts should be '{expected}'"),
+                    );
+                }
+            }
+            Err(error) => issue("category", &category, error.to_string()),
+        }
+        if let Some(message) = currency_problem(&currency) {
+            issue("currency", &currency, message);
+        }
+        for group in duplicates.iter().filter(|group| group.account_id == id) {
+            let spellings: Vec<String> = group
+                .entries
+                .iter()
+                .map(|entry| format!("'{}'", entry.date))
+                .collect();
+            issue(
+                "date",
+                &group.date,
+                format!(
+                    "the day has {} entries, dated {}; merge_duplicate_entries keeps one",
+                    group.entries.len(),
+                    spellings.join(", ")
+                ),
+            );
+        }
+    }
+
+    Ok(issues)
+}
+
+/// Clears the account's custom colour and icon so the type defaults apply again.
+#[tauri::command]
+pub async fn reset_account_appearance(
+    pool: State<'_, SqlitePool>,
+    account_id: String,
+) -> Result<Account> {
+    let mut account = fetch_account(pool.inner(), &account_id).await?;
+    account.color = None;
+    account.icon = None;
+    account.updated_at = now();
+    sqlx::query("UPDATE accounts SET color = NULL, icon = NULL, updated_at = ? WHERE id = ?")
+        .bind(&account.updated_at)
+        .bind(&account.id)
+        .execute(pool.inner())
+        .await?;
+
+    Ok(account)
+}
+
+/// Sets how much of a jointly held account counts towards net worth.
+#[tauri::command]
+pub async fn set_ownership(
+    pool: State<'_, SqlitePool>,
+    account_id: String,
+    ownership_percent: f64,
+) -> Result<Account> {
+    validate_ownership_percent(ownership_percent)?;
+
+    let mut account = fetch_account(pool.inner(), &account_id).await?;
+    account.ownership_percent = ownership_percent;
+    account.updated_at = now();
+    sqlx::query("UPDATE accounts SET ownership_percent = ?, updated_at = ? WHERE id = ?")
+        .bind(account.ownership_percent)
+        .bind(&account.updated_at)
+        .bind(&account.id)
+        .execute(pool.inner())
+        .await?;
+
+    Ok(account)
+}
+
+/// The account carrying `external_ref`, archived or not, if any.
+#[tauri::command]
+pub async fn find_account_by_external_ref(
+    pool: State<'_, SqlitePool>,
+    external_ref: String,
+) -> Result<Option<Account>> {
+    let account = sqlx::query_as::<_, Account>("SELECT * FROM accounts WHERE external_ref = ?")
+        .bind(&external_ref)
+        .fetch_optional(pool.inner())
+        .await?;
+    Ok(account)
+}
+
+/// Sets or, with `None` or a blank value, clears the account's external reference.
+/// Fails if another account already uses it.
+#[tauri::command]
+pub async fn set_account_external_ref(
+    pool: State<'_, SqlitePool>,
+    account_id: String,
+    external_ref: Option<String>,
+) -> Result<Account> {
+    let mut tx = pool.begin().await?;
+    let mut account = fetch_account(&mut *tx, &account_id).await?;
+    account.external_ref = non_empty(external_ref);
+    check_external_ref(&mut *tx, &account.id, account.external_ref.as_deref()).await?;
+    account.updated_at = now();
+    sqlx::query("UPDATE accounts SET external_ref = ?, updated_at = ? WHERE id = ?")
+        .bind(&account.external_ref)
+        .bind(&account.updated_at)
+        .bind(&account.id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(account)
+}
+
+/// Rewrites the manual order of the active, unarchived accounts. `ordered_ids`
+/// must list each of them exactly once. Inactive and archived accounts keep
+/// their order among themselves, numbered after the rest, so no two accounts
+/// share a position.
+#[tauri::command]
+pub async fn reorder_accounts(
+    pool: State<'_, SqlitePool>,
+    ordered_ids: Vec<String>,
+) -> Result<Vec<Account>> {
+    let mut tx = pool.begin().await?;
+
+    let existing: HashSet<String> =
+        sqlx::query_scalar("SELECT id FROM accounts WHERE is_active = 1 AND archived_at IS NULL")
+            .fetch_all(&mut *tx)
+            .await?
+            .into_iter()
+            .collect();
+    validate_ordering(&ordered_ids, &existing, "account")?;
+
+    let others: Vec<String> = sqlx::query_scalar(
+        "SELECT id FROM accounts WHERE is_active = 0 OR archived_at IS NOT NULL
+         ORDER BY sort_order, name",
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+    for (position, id) in ordered_ids.iter().chain(&others).enumerate() {
+        sqlx::query("UPDATE accounts SET sort_order = ? WHERE id = ?")
+            .bind(position as i64)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    let accounts = sqlx::query_as::<_, Account>(
+        "SELECT * FROM accounts
+         WHERE is_active = 1 AND archived_at IS NULL
+         ORDER BY sort_order, name",
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(accounts)
+}
+
+/// Hides the account from listings and aggregates while keeping its balance history.
+#[tauri::command]
+pub async fn archive_account(pool: State<'_, SqlitePool>, id: String) -> Result<Account> {
+    let mut account = fetch_account(pool.inner(), &id).await?;
+    if account.is_archived() {
+        return Ok(account);
+    }
+
+    let timestamp = now();
+    sqlx::query("UPDATE accounts SET archived_at = ?, updated_at = ? WHERE id = ?")
+        .bind(&timestamp)
+        .bind(&timestamp)
+        .bind(&id)
+        .execute(pool.inner())
+        .await?;
+
+    account.archived_at = Some(timestamp.clone());
+    account.updated_at = timestamp;
+    Ok(account)
+}
+
+#[tauri::command]
+pub async fn unarchive_account(pool: State<'_, SqlitePool>, id: String) -> Result<Account> {
+    let mut account = fetch_account(pool.inner(), &id).await?;
+    if !account.is_archived() {
+        return Ok(account);
+    }
+
+    let timestamp = now();
+    sqlx::query("UPDATE accounts SET archived_at = NULL, updated_at = ? WHERE id = ?")
+        .bind(&timestamp)
+        .bind(&id)
+        .execute(pool.inner())
+        .await?;
+
+    account.archived_at = None;
+    account.updated_at = timestamp;
+    Ok(account)
+}
+
+/// Marks the account closed on `closed_at` and inactive, recording a final
+/// balance entry on that date (zero unless `final_balance` is given). The account
+/// stops contributing to totals after that date.
+#[tauri::command]
+pub async fn close_account(
+    pool: State<'_, SqlitePool>,
+    account_id: String,
+    closed_at: String,
+    final_balance: Option<f64>,
+) -> Result<Account> {
+    let closed_at = normalize_date("closed_at", &closed_at)?;
+    if closed_at > today() {
+        return Err(Error::validation("closed_at", "must not be in the future"));
+    }
+    let final_balance = final_balance.unwrap_or(0.0);
+    if !final_balance.is_finite() {
+        return Err(Error::validation("final_balance", "must be a number"));
+    }
+
+    let mut tx = pool.begin().await?;
+    let mut account = fetch_account(&mut *tx, &account_id).await?;
+    if account
+        .opened_at
+        .as_deref()
+        .is_some_and(|opened| closed_at.as_str() < opened)
+    {
+        return Err(Error::validation(
+            "closed_at",
+            "must not be before the account was opened",
+        ));
+    }
+    let later: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM balance_entries WHERE account_id = ? AND date > ?",
+    )
+    .bind(&account_id)
+    .bind(&closed_at)
+    .fetch_one(&mut *tx)
+    .await?;
+    if later > 0 {
+        return Err(Error::Conflict(format!(
+            "'{}' has {later} balance entries after {closed_at}",
+            account.name
+        )));
+    }
+
+    let entry = new_balance_entry(&account, closed_at.clone(), final_balance, None);
+    upsert_entry(&mut *tx, &entry).await?;
+
+    account.closed_at = Some(closed_at);
+    account.is_active = false;
+    account.updated_at = now();
+    sqlx::query("UPDATE accounts SET closed_at = ?, is_active = 0, updated_at = ? WHERE id = ?")
+        .bind(&account.closed_at)
+        .bind(&account.updated_at)
+        .bind(&account_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(account)
+}
+
+/// Clears `closed_at` and reactivates the account. The final balance entry is kept.
+#[tauri::command]
+pub async fn reopen_account(pool: State<'_, SqlitePool>, account_id: String) -> Result<Account> {
+    let mut account = fetch_account(pool.inner(), &account_id).await?;
+
+    account.closed_at = None;
+    account.is_active = true;
+    account.updated_at = now();
+    sqlx::query("UPDATE accounts SET closed_at = NULL, is_active = 1, updated_at = ? WHERE id = ?")
+        .bind(&account.updated_at)
+        .bind(&account_id)
+        .execute(pool.inner())
+        .await?;
+
+    Ok(account)
+}
+
+/// Removes the account; its balance entries and their attachments go with it via
+/// `ON DELETE CASCADE`, and attachment files no other account shares are deleted.
+/// Only archived accounts can be deleted unless `force` is set, so history is
+/// never lost by a single click.
+#[tauri::command]
+pub async fn delete_account(
+    app: AppHandle,
+    pool: State<'_, SqlitePool>,
+    id: String,
+    force: bool,
+) -> Result<()> {
+    let account = fetch_account(pool.inner(), &id).await?;
+    if !account.is_archived() && !force {
+        return Err(Error::Conflict(format!(
+            "account '{}' must be archived before it can be deleted",
+            account.name
+        )));
+    }
+
+    let stored_names = stored_names_for_account(pool.inner(), &id).await?;
+    sqlx::query("DELETE FROM accounts WHERE id = ?")
+        .bind(&id)
+        .execute(pool.inner())
+        .await?;
+    remove_unreferenced_files(&app, pool.inner(), stored_names).await
+}
+
+/// Folds `source_id` into `target_id`: balance history, flows and milestones move to the
+/// target, date collisions are settled by `strategy`, and the source is deleted.
+/// Accounts of different categories are only merged with `force`; accounts in
+/// different currencies never are, as their entries would be moved unconverted.
+#[tauri::command]
+pub async fn merge_accounts(
+    pool: State<'_, SqlitePool>,
+    source_id: String,
+    target_id: String,
+    strategy: MergeStrategy,
+    force: bool,
+) -> Result<MergeSummary> {
+    if source_id == target_id {
+        return Err(Error::validation(
+            "target_id",
+            "cannot merge an account into itself",
+        ));
+    }
+
+    let mut tx = pool.begin().await?;
+    let source = fetch_account(&mut *tx, &source_id).await?;
+    let target = fetch_account(&mut *tx, &target_id).await?;
+
+    if !force && source.category != target.category {
+        return Err(Error::Conflict(format!(
+            "cannot merge {} '{}' into {} '{}'",
+            source.category.as_str(),
+            source.name,
+            target.category.as_str(),
+            target.name
+        )));
+    }
+    if source.currency != target.currency {
+        return Err(Error::Conflict(format!(
+            "'{}' is in {} but '{}' is in {}",
+            source.name, source.currency, target.name, target.currency
+        )));
+    }
+
+    let source_entries = fetch_entries(&mut *tx, &source_id).await?;
+
+    let mut summary = MergeSummary::default();
+    for entry in source_entries {
+        let existing = sqlx::query_as::<_, BalanceEntry>(
+            "SELECT * FROM balance_entries WHERE account_id = ? AND date = ?",
+        )
+        .bind(&target_id)
+        .bind(&entry.date)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(existing) = existing else {
+            sqlx::query("UPDATE balance_entries SET account_id = ? WHERE id = ?")
+                .bind(&target_id)
+                .bind(&entry.id)
+                .execute(&mut *tx)
+                .await?;
+            summary.moved += 1;
+            continue;
+        };
+
+        let keep_source = match strategy {
+            MergeStrategy::KeepTarget => false,
+            MergeStrategy::KeepSource => true,
+            MergeStrategy::KeepLarger => entry.balance > existing.balance,
+        };
+        if keep_source {
+            sqlx::query(
+                "UPDATE balance_entries
+                 SET balance = ?, balance_minor = ?, minor_units = ?, notes = ?, original_currency = ?, original_amount = ?
+                 WHERE id = ?",
+            )
+            .bind(entry.balance)
+            .bind(entry.balance_minor)
+            .bind(entry.minor_units)
+            .bind(&entry.notes)
+            .bind(&entry.original_currency)
+            .bind(entry.original_amount)
+                .bind(&existing.id)
+                .execute(&mut *tx)
+                .await?;
+            summary.overwritten += 1;
+        } else {
+            summary.skipped += 1;
+        }
+        sqlx::query(
+            "UPDATE OR IGNORE attachments SET balance_entry_id = ? WHERE balance_entry_id = ?",
+        )
+        .bind(&existing.id)
+        .bind(&entry.id)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query("DELETE FROM balance_entries WHERE id = ?")
+            .bind(&entry.id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    // Milestones already linked to both keep their link to the target alone.
+    sqlx::query("UPDATE OR IGNORE milestone_accounts SET account_id = ? WHERE account_id = ?")
+        .bind(&target_id)
+        .bind(&source_id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("DELETE FROM milestone_accounts WHERE account_id = ?")
+        .bind(&source_id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("UPDATE flows SET account_id = ? WHERE account_id = ?")
+        .bind(&target_id)
+        .bind(&source_id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query(
+        "INSERT OR IGNORE INTO account_tags (account_id, tag_id)
+         SELECT ?, tag_id FROM account_tags WHERE account_id = ?",
+    )
+    .bind(&target_id)
+    .bind(&source_id)
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query("DELETE FROM accounts WHERE id = ?")
+        .bind(&source_id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("UPDATE accounts SET updated_at = ? WHERE id = ?")
+        .bind(now())
+        .bind(&target_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::commands::parse_date;
+    use crate::testing::{block_on, input, seed_daily_entries, TestDb};
+
+    #[test]
+    fn balances_of_thousands_of_entries_come_back_quickly() {
+        let db = TestDb::new();
+        block_on(async {
+            let last = parse_date("date", "2025-12-31").unwrap();
+            for index in 0..40 {
+                let account = create_account(
+                    db.state(),
+                    input(
+                        json!({"name": format!("Account {index:02}"), "account_type": "savings"}),
+                    ),
+                )
+                .await
+                .unwrap();
+                seed_daily_entries(&db, &account, last, 250).await;
+            }
+            create_account(
+                db.state(),
+                input(json!({"name": "Empty", "account_type": "savings"})),
+            )
+            .await
+            .unwrap();
+
+            let started = Instant::now();
+            let accounts = get_accounts_with_balance(db.state(), Some("2025-12-01".into()))
+                .await
+                .unwrap();
+            let elapsed = started.elapsed();
+
+            assert_eq!(accounts.len(), 41);
+            for account in &accounts {
+                if account.account.name == "Empty" {
+                    assert_eq!(
+                        (account.current_balance, account.balance_date.as_deref()),
+                        (0.0, None)
+                    );
+                } else {
+                    // Thirty days short of the last and largest entry.
+                    assert_eq!(account.current_balance, 220.0);
+                    assert_eq!(account.balance_date.as_deref(), Some("2025-12-01"));
+                }
+            }
+            assert!(elapsed < Duration::from_millis(100), "{elapsed:?}");
+        });
+    }
+
+    #[test]
+    fn accounts_in_different_currencies_are_not_merged_even_with_force() {
+        let db = TestDb::new();
+        let (result, source_entries) = block_on(async {
+            let account = |name: &str, currency: &str| {
+                let value = json!({
+                    "name": name,
+                    "account_type": "savings",
+                    "currency": currency,
+                    "opening_balance": 1000,
+                    "opened_at": "2025-01-01",
+                });
+                async { create_account(db.state(), input(value)).await.unwrap() }
+            };
+            let source = account("Dollar ISA", "USD").await;
+            let target = account("ISA", "GBP").await;
+            let result = merge_accounts(
+                db.state(),
+                source.id.clone(),
+                target.id,
+                MergeStrategy::KeepTarget,
+                true,
+            )
+            .await;
+            (result, fetch_entries(db.pool(), &source.id).await.unwrap())
+        });
+        assert!(matches!(result, Err(Error::Conflict(_))), "{result:?}");
+        assert_eq!(source_entries.len(), 1);
+    }
+}
@@ -0,0 +1,367 @@
+use std::collections::HashSet;
+
+use chrono::{Days, NaiveDate};
+use sqlx::{SqliteExecutor, SqlitePool};
+use tauri::State;
+use uuid::Uuid;
+
+use super::accounts::fetch_account;
+use super::balances::{
+    entry_exists, fetch_latest_entry, insert_balance_entry, latest_allowed, new_balance_entry,
+};
+use super::settings::load_settings;
+use super::undo::record_undo;
+use super::{normalize_date, now, parse_date, today_date};
+use crate::dates;
+use crate::error::{Error, Result};
+use crate::models::{
+    from_minor, minor_units_for, to_minor, Account, AdjustmentKind, AppliedAdjustments,
+    BalanceEntry, BulkAdjustment, BulkAdjustmentResult, CreateAdjustmentInput, EntrySource, Patch,
+    RecurringAdjustment, SkippedAdjustment, SkippedBulkAdjustment, UndoOperation,
+    UpdateAdjustmentInput,
+};
+use crate::series::monthly_on_day;
+
+async fn fetch_adjustment<'e>(
+    executor: impl SqliteExecutor<'e>,
+    id: &str,
+) -> Result<RecurringAdjustment> {
+    sqlx::query_as::<_, RecurringAdjustment>("SELECT * FROM recurring_adjustments WHERE id = ?")
+        .bind(id)
+        .fetch_optional(executor)
+        .await?
+        .ok_or_else(|| Error::not_found("recurring adjustment", id))
+}
+
+/// Checks the rule's fields, rounding `amount` to the account currency.
+fn check_adjustment(account: &Account, adjustment: &mut RecurringAdjustment) -> Result<()> {
+    if !adjustment.amount.is_finite() {
+        return Err(Error::validation("amount", "must be a number"));
+    }
+    let minor_units = minor_units_for(&account.currency);
+    adjustment.amount = from_minor(to_minor(adjustment.amount, minor_units), minor_units);
+    if !(1..=31).contains(&adjustment.day_of_month) {
+        return Err(Error::validation(
+            "day_of_month",
+            "must be between 1 and 31",
+        ));
+    }
+    if let Some(end_date) = &adjustment.end_date {
+        if *end_date < adjustment.start_date {
+            return Err(Error::validation(
+                "end_date",
+                "must not be before start_date",
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn create_recurring_adjustment(
+    pool: State<'_, SqlitePool>,
+    input: CreateAdjustmentInput,
+) -> Result<RecurringAdjustment> {
+    let account = fetch_account(pool.inner(), &input.account_id).await?;
+    let mut adjustment = RecurringAdjustment {
+        id: Uuid::new_v4().to_string(),
+        account_id: account.id.clone(),
+        amount: input.amount,
+        day_of_month: input.day_of_month,
+        start_date: normalize_date("start_date", &input.start_date)?,
+        end_date: input
+            .end_date
+            .map(|date| normalize_date("end_date", &date))
+            .transpose()?,
+        kind: input.kind,
+        last_applied: None,
+        created_at: now(),
+    };
+    check_adjustment(&account, &mut adjustment)?;
+
+    sqlx::query(
+        "INSERT INTO recurring_adjustments (id, account_id, amount, day_of_month, start_date, end_date, kind, last_applied, created_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(&adjustment.id)
+    .bind(&adjustment.account_id)
+    .bind(adjustment.amount)
+    .bind(adjustment.day_of_month)
+    .bind(&adjustment.start_date)
+    .bind(&adjustment.end_date)
+    .bind(adjustment.kind)
+    .bind(&adjustment.last_applied)
+    .bind(&adjustment.created_at)
+    .execute(pool.inner())
+    .await?;
+
+    Ok(adjustment)
+}
+
+/// Every rule, or only the account's when `account_id` is given.
+#[tauri::command]
+pub async fn get_recurring_adjustments(
+    pool: State<'_, SqlitePool>,
+    account_id: Option<String>,
+) -> Result<Vec<RecurringAdjustment>> {
+    let adjustments = sqlx::query_as::<_, RecurringAdjustment>(
+        "SELECT * FROM recurring_adjustments
+         WHERE ?1 IS NULL OR account_id = ?1
+         ORDER BY start_date, created_at",
+    )
+    .bind(account_id)
+    .fetch_all(pool.inner())
+    .await?;
+    Ok(adjustments)
+}
+
+/// Changes the rule from now on. Entries it has already generated are left as
+/// they are.
+#[tauri::command]
+pub async fn update_recurring_adjustment(
+    pool: State<'_, SqlitePool>,
+    id: String,
+    input: UpdateAdjustmentInput,
+) -> Result<RecurringAdjustment> {
+    let mut adjustment = fetch_adjustment(pool.inner(), &id).await?;
+    let account = fetch_account(pool.inner(), &adjustment.account_id).await?;
+    if let Some(amount) = input.amount {
+        adjustment.amount = amount;
+    }
+    if let Some(day_of_month) = input.day_of_month {
+        adjustment.day_of_month = day_of_month;
+    }
+    if let Some(start_date) = input.start_date {
+        adjustment.start_date = normalize_date("start_date", &start_date)?;
+    }
+    match input.end_date {
+        Patch::Keep => {}
+        Patch::Clear => adjustment.end_date = None,
+        Patch::Set(date) => adjustment.end_date = Some(normalize_date("end_date", &date)?),
+    }
+    if let Some(kind) = input.kind {
+        adjustment.kind = kind;
+    }
+    check_adjustment(&account, &mut adjustment)?;
+
+    sqlx::query(
+        "UPDATE recurring_adjustments
+         SET amount = ?, day_of_month = ?, start_date = ?, end_date = ?, kind = ?
+         WHERE id = ?",
+    )
+    .bind(adjustment.amount)
+    .bind(adjustment.day_of_month)
+    .bind(&adjustment.start_date)
+    .bind(&adjustment.end_date)
+    .bind(adjustment.kind)
+    .bind(&id)
+    .execute(pool.inner())
+    .await?;
+
+    Ok(adjustment)
+}
+
+/// Removes the rule. The entries it generated stay.
+#[tauri::command]
+pub async fn delete_recurring_adjustment(pool: State<'_, SqlitePool>, id: String) -> Result<()> {
+    let deleted = sqlx::query("DELETE FROM recurring_adjustments WHERE id = ?")
+        .bind(&id)
+        .execute(pool.inner())
+        .await?;
+    if deleted.rows_affected() == 0 {
+        return Err(Error::not_found("recurring adjustment", id));
+    }
+    Ok(())
+}
+
+/// The dates `adjustment` is due on up to `as_of` that it hasn't yet been
+/// applied for, stopping at the account's closure.
+pub(crate) fn due_dates(
+    adjustment: &RecurringAdjustment,
+    account: &Account,
+    as_of: NaiveDate,
+) -> Result<Vec<NaiveDate>> {
+    let mut from = parse_date("start_date", &adjustment.start_date)?;
+    if let Some(last_applied) = &adjustment.last_applied {
+        from = from.max(parse_date("last_applied", last_applied)? + Days::new(1));
+    }
+    let mut to = as_of;
+    for limit in [&adjustment.end_date, &account.closed_at]
+        .into_iter()
+        .flatten()
+    {
+        to = to.min(parse_date("end_date", limit)?);
+    }
+    Ok(monthly_on_day(from, to, adjustment.day_of_month))
+}
+
+/// Generates every entry the rules are due for up to `as_of` (today by
+/// default). Dates already covered by an entry are skipped rather than
+/// overwritten, and each rule remembers how far it got, so running this again
+/// creates nothing new.
+#[tauri::command]
+pub async fn apply_due_adjustments(
+    pool: State<'_, SqlitePool>,
+    as_of: Option<String>,
+) -> Result<AppliedAdjustments> {
+    let as_of = match as_of {
+        Some(date) => parse_date("as_of", &date)?,
+        None => today_date(),
+    };
+
+    let mut tx = pool.begin().await?;
+    let adjustments = sqlx::query_as::<_, RecurringAdjustment>(
+        "SELECT * FROM recurring_adjustments ORDER BY start_date, created_at",
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let mut applied = AppliedAdjustments::default();
+    for adjustment in adjustments {
+        let account = fetch_account(&mut *tx, &adjustment.account_id).await?;
+        let due = due_dates(&adjustment, &account, as_of)?;
+        let Some(last_due) = due.last().copied() else {
+            continue;
+        };
+
+        for date in due.iter().copied().map(dates::format) {
+            let skip = |reason: &str| SkippedAdjustment {
+                adjustment_id: adjustment.id.clone(),
+                account_id: account.id.clone(),
+                date: date.clone(),
+                reason: reason.to_string(),
+            };
+            if entry_exists(&mut tx, &account.id, &date).await? {
+                applied
+                    .skipped
+                    .push(skip("the account already has an entry on this date"));
+                continue;
+            }
+            let balance = match adjustment.kind {
+                AdjustmentKind::Absolute => adjustment.amount,
+                AdjustmentKind::Delta => {
+                    let previous = sqlx::query_as::<_, BalanceEntry>(
+                        "SELECT * FROM balance_entries WHERE account_id = ? AND date < ?
+                         ORDER BY date DESC LIMIT 1",
+                    )
+                    .bind(&account.id)
+                    .bind(&date)
+                    .fetch_optional(&mut *tx)
+                    .await?;
+                    let Some(previous) = previous else {
+                        applied
+                            .skipped
+                            .push(skip("there is no earlier balance to adjust"));
+                        continue;
+                    };
+                    previous.balance + adjustment.amount
+                }
+            };
+
+            let mut entry = new_balance_entry(&account, date, balance, None);
+            entry.source = EntrySource::Scheduled;
+            insert_balance_entry(&mut *tx, &entry).await?;
+            applied.created.push(entry);
+        }
+
+        sqlx::query("UPDATE recurring_adjustments SET last_applied = ? WHERE id = ?")
+            .bind(dates::format(last_due))
+            .bind(&adjustment.id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    record_undo(
+        &mut tx,
+        UndoOperation::Adjust,
+        format!(
+            "Apply recurring adjustments to {} balances",
+            applied.created.len()
+        ),
+        &[],
+        &applied.created,
+    )
+    .await?;
+
+    tx.commit().await?;
+    Ok(applied)
+}
+
+/// Records a balance on `date` for each account, worked out from its latest
+/// entry by `adjustment`. Accounts without an entry before `date`, or whose
+/// latest entry is already on or after it, are skipped and reported.
+#[tauri::command]
+pub async fn apply_bulk_adjustment(
+    pool: State<'_, SqlitePool>,
+    account_ids: Vec<String>,
+    date: String,
+    adjustment: BulkAdjustment,
+) -> Result<BulkAdjustmentResult> {
+    let mut tx = pool.begin().await?;
+    let date = normalize_date("date", &date)?;
+    let latest_allowed = latest_allowed(&load_settings(&mut *tx).await?);
+    if date > latest_allowed {
+        return Err(Error::validation(
+            "date",
+            format!("{date} is too far in the future; the latest allowed is {latest_allowed}"),
+        ));
+    }
+    let (BulkAdjustment::Percent(amount) | BulkAdjustment::Delta(amount)) = adjustment;
+    if !amount.is_finite() {
+        return Err(Error::validation("adjustment", "must be a number"));
+    }
+
+    let mut result = BulkAdjustmentResult::default();
+    let mut seen = HashSet::new();
+    for account_id in account_ids {
+        if !seen.insert(account_id.clone()) {
+            continue;
+        }
+        let account = fetch_account(&mut *tx, &account_id).await?;
+        let skip = |latest_date: Option<String>, reason: &str| SkippedBulkAdjustment {
+            account_id: account.id.clone(),
+            latest_date,
+            reason: reason.to_string(),
+        };
+        let Some(latest) = fetch_latest_entry(&mut *tx, &account.id).await? else {
+            result
+                .skipped
+                .push(skip(None, "the account has no balance to adjust"));
+            continue;
+        };
+        if latest.date >= date {
+            result.skipped.push(skip(
+                Some(latest.date),
+                "the account already has a balance on or after this date",
+            ));
+            continue;
+        }
+
+        let balance = match adjustment {
+            BulkAdjustment::Percent(percent) => latest.balance * (1.0 + percent / 100.0),
+            BulkAdjustment::Delta(delta) => latest.balance + delta,
+        };
+        let entry = new_balance_entry(&account, date.clone(), balance, None);
+        insert_balance_entry(&mut *tx, &entry).await?;
+        result.created.push(entry);
+    }
+
+    let change = match adjustment {
+        BulkAdjustment::Percent(percent) => format!("{percent:+}%"),
+        BulkAdjustment::Delta(delta) => format!("{delta:+}"),
+    };
+    record_undo(
+        &mut tx,
+        UndoOperation::Adjust,
+        format!(
+            "Adjust {} balances by {change} on {date}",
+            result.created.len()
+        ),
+        &[],
+        &result.created,
+    )
+    .await?;
+
+    tx.commit().await?;
+    Ok(result)
+}
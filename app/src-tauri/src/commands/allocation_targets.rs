@@ -0,0 +1,435 @@
+use std::collections::{BTreeMap, HashMap};
+
+use sqlx::{SqliteConnection, SqliteExecutor, SqlitePool};
+use tauri::State;
+use uuid::Uuid;
+
+use super::account_types::resolve_category;
+use super::analytics::{account_values, AccountValues};
+use super::settings::load_settings;
+use super::{now, parse_date, today_date};
+use crate::dates;
+use crate::error::{Error, Result};
+use crate::models::{
+    minor_units_for, round_amount, sum_amounts, AccountCategory, AllocationBucket, AllocationDrift,
+    AllocationDriftBucket, AllocationTarget, CreateAllocationTargetInput, FillMethod,
+    UnallocatedHolding, UpdateAllocationTargetInput,
+};
+
+/// How far from 100% the targets may add up to, in percentage points.
+const TARGET_TOTAL_TOLERANCE: f64 = 0.01;
+
+#[derive(sqlx::FromRow)]
+struct AllocationTargetRow {
+    id: String,
+    account_type: Option<String>,
+    tag_id: Option<String>,
+    target_percent: f64,
+    created_at: String,
+    updated_at: String,
+}
+
+impl TryFrom<AllocationTargetRow> for AllocationTarget {
+    type Error = Error;
+
+    fn try_from(row: AllocationTargetRow) -> Result<Self> {
+        let bucket = match (row.account_type, row.tag_id) {
+            (Some(account_type), None) => AllocationBucket::AccountType {
+                account_type: account_type.parse()?,
+            },
+            (None, Some(tag_id)) => AllocationBucket::Tag { tag_id },
+            _ => {
+                return Err(Error::validation(
+                    "allocation_targets",
+                    "a target needs exactly one of account_type and tag_id",
+                ))
+            }
+        };
+        Ok(AllocationTarget {
+            id: row.id,
+            bucket,
+            target_percent: row.target_percent,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        })
+    }
+}
+
+/// The `account_type` and `tag_id` columns for `bucket`.
+pub(crate) fn bucket_columns(bucket: &AllocationBucket) -> (Option<&str>, Option<&str>) {
+    match bucket {
+        AllocationBucket::AccountType { account_type } => (Some(account_type.as_str()), None),
+        AllocationBucket::Tag { tag_id } => (None, Some(tag_id)),
+    }
+}
+
+async fn fetch_allocation_target<'e>(
+    executor: impl SqliteExecutor<'e>,
+    id: &str,
+) -> Result<AllocationTarget> {
+    sqlx::query_as::<_, AllocationTargetRow>("SELECT * FROM allocation_targets WHERE id = ?")
+        .bind(id)
+        .fetch_optional(executor)
+        .await?
+        .ok_or_else(|| Error::not_found("allocation target", id))?
+        .try_into()
+}
+
+pub(crate) async fn fetch_allocation_targets<'e>(
+    executor: impl SqliteExecutor<'e>,
+) -> Result<Vec<AllocationTarget>> {
+    sqlx::query_as::<_, AllocationTargetRow>(
+        "SELECT * FROM allocation_targets ORDER BY target_percent DESC, created_at",
+    )
+    .fetch_all(executor)
+    .await?
+    .into_iter()
+    .map(AllocationTarget::try_from)
+    .collect()
+}
+
+fn check_percent(percent: f64) -> Result<f64> {
+    if !percent.is_finite() || percent <= 0.0 || percent > 100.0 {
+        return Err(Error::validation(
+            "target_percent",
+            "must be a percentage above 0 and at most 100",
+        ));
+    }
+    Ok(percent)
+}
+
+/// Fails when `targets` would add up to more than 100%.
+fn check_total<'a>(targets: impl IntoIterator<Item = &'a f64>) -> Result<()> {
+    let total: f64 = targets.into_iter().sum();
+    if total > 100.0 + TARGET_TOTAL_TOLERANCE {
+        return Err(Error::validation(
+            "target_percent",
+            format!(
+                "would bring the targets to {}%, over 100%",
+                round_amount(total, 2)
+            ),
+        ));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_allocation_targets(pool: State<'_, SqlitePool>) -> Result<Vec<AllocationTarget>> {
+    fetch_allocation_targets(pool.inner()).await
+}
+
+/// Targets for account types must name asset types, and every target must be
+/// of the same kind as those already set. Targets are built up one at a time,
+/// so they may add up to less than 100% until `get_allocation_drift`, but never
+/// more.
+#[tauri::command]
+pub async fn create_allocation_target(
+    pool: State<'_, SqlitePool>,
+    input: CreateAllocationTargetInput,
+) -> Result<AllocationTarget> {
+    let target_percent = check_percent(input.target_percent)?;
+    let mut tx = pool.begin().await?;
+    match &input.bucket {
+        AllocationBucket::AccountType { account_type } => {
+            if resolve_category(&mut *tx, account_type).await? != AccountCategory::Asset {
+                return Err(Error::validation("account_type", "must be an asset type"));
+            }
+        }
+        AllocationBucket::Tag { tag_id } => {
+            let exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM tags WHERE id = ?)")
+                .bind(tag_id)
+                .fetch_one(&mut *tx)
+                .await?;
+            if !exists {
+                return Err(Error::not_found("tag", tag_id));
+            }
+        }
+    }
+
+    let existing = fetch_allocation_targets(&mut *tx).await?;
+    if let Some(other) = existing
+        .iter()
+        .find(|target| !target.bucket.same_kind(&input.bucket))
+    {
+        let kind = match other.bucket {
+            AllocationBucket::AccountType { .. } => "account type",
+            AllocationBucket::Tag { .. } => "tag",
+        };
+        return Err(Error::Conflict(format!(
+            "the targets already set are by {kind}; delete them to set targets another way"
+        )));
+    }
+    if existing.iter().any(|target| target.bucket == input.bucket) {
+        return Err(Error::Conflict(
+            "there is already a target for that bucket".to_string(),
+        ));
+    }
+    check_total(
+        existing
+            .iter()
+            .map(|target| &target.target_percent)
+            .chain([&target_percent]),
+    )?;
+
+    let created_at = now();
+    let target = AllocationTarget {
+        id: Uuid::new_v4().to_string(),
+        bucket: input.bucket,
+        target_percent,
+        created_at: created_at.clone(),
+        updated_at: created_at,
+    };
+    let (account_type, tag_id) = bucket_columns(&target.bucket);
+    sqlx::query(
+        "INSERT INTO allocation_targets (id, account_type, tag_id, target_percent, created_at, updated_at)
+         VALUES (?, ?, ?, ?, ?, ?)",
+    )
+    .bind(&target.id)
+    .bind(account_type)
+    .bind(tag_id)
+    .bind(target.target_percent)
+    .bind(&target.created_at)
+    .bind(&target.updated_at)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(target)
+}
+
+#[tauri::command]
+pub async fn update_allocation_target(
+    pool: State<'_, SqlitePool>,
+    id: String,
+    input: UpdateAllocationTargetInput,
+) -> Result<AllocationTarget> {
+    let target_percent = check_percent(input.target_percent)?;
+    let mut tx = pool.begin().await?;
+    fetch_allocation_target(&mut *tx, &id).await?;
+    let existing = fetch_allocation_targets(&mut *tx).await?;
+    check_total(
+        existing
+            .iter()
+            .filter(|target| target.id != id)
+            .map(|target| &target.target_percent)
+            .chain([&target_percent]),
+    )?;
+
+    sqlx::query("UPDATE allocation_targets SET target_percent = ?, updated_at = ? WHERE id = ?")
+        .bind(target_percent)
+        .bind(now())
+        .bind(&id)
+        .execute(&mut *tx)
+        .await?;
+    let target = fetch_allocation_target(&mut *tx, &id).await?;
+    tx.commit().await?;
+    Ok(target)
+}
+
+#[tauri::command]
+pub async fn delete_allocation_target(pool: State<'_, SqlitePool>, id: String) -> Result<()> {
+    let result = sqlx::query("DELETE FROM allocation_targets WHERE id = ?")
+        .bind(&id)
+        .execute(pool.inner())
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(Error::not_found("allocation target", id));
+    }
+    Ok(())
+}
+
+#[derive(Default)]
+struct Holding {
+    name: String,
+    amounts: Vec<f64>,
+    account_ids: Vec<String>,
+}
+
+/// The tag with a target on each account that has one. An account with two
+/// can't be put in either bucket.
+async fn targeted_tags(
+    conn: &mut SqliteConnection,
+    accounts: &[AccountValues],
+) -> Result<HashMap<String, String>> {
+    let rows: Vec<(String, String)> = sqlx::query_as(
+        "SELECT at.account_id, at.tag_id FROM account_tags at
+         JOIN allocation_targets t ON t.tag_id = at.tag_id",
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    let mut tags = HashMap::new();
+    for (account_id, tag_id) in rows {
+        if tags.insert(account_id.clone(), tag_id).is_some() {
+            if let Some(valued) = accounts
+                .iter()
+                .find(|valued| valued.account.id == account_id)
+            {
+                return Err(Error::Conflict(format!(
+                    "account '{}' has more than one tag with a target",
+                    valued.account.name
+                )));
+            }
+        }
+    }
+    Ok(tags)
+}
+
+/// What each targeted bucket held on `as_of` (default today) against its
+/// target, valued as in `get_allocation`. Only assets count; targets are shares
+/// of all of them, taking in what no target covers, which is listed apart.
+/// Fails unless the targets add up to 100%.
+#[tauri::command]
+pub async fn get_allocation_drift(
+    pool: State<'_, SqlitePool>,
+    as_of: Option<String>,
+) -> Result<AllocationDrift> {
+    let as_of = match as_of {
+        Some(date) => parse_date("as_of", &date)?,
+        None => today_date(),
+    };
+    let mut conn = pool.acquire().await?;
+    let targets = fetch_allocation_targets(&mut *conn).await?;
+    if targets.is_empty() {
+        return Err(Error::NoData("no allocation targets are set".to_string()));
+    }
+    let total_percent: f64 = targets.iter().map(|target| target.target_percent).sum();
+    if (total_percent - 100.0).abs() > TARGET_TOTAL_TOLERANCE {
+        return Err(Error::validation(
+            "target_percent",
+            format!(
+                "the targets add up to {}%, not 100%",
+                round_amount(total_percent, 2)
+            ),
+        ));
+    }
+    let by_tag = matches!(targets[0].bucket, AllocationBucket::Tag { .. });
+
+    let base_currency = load_settings(&mut *conn).await?.base_currency;
+    let units = minor_units_for(&base_currency);
+    let accounts = account_values(
+        &mut conn,
+        &[as_of],
+        FillMethod::CarryForward,
+        false,
+        &base_currency,
+    )
+    .await?;
+    let tags = if by_tag {
+        targeted_tags(&mut conn, &accounts).await?
+    } else {
+        HashMap::new()
+    };
+    let tag_names: HashMap<String, String> = sqlx::query_as("SELECT id, name FROM tags")
+        .fetch_all(&mut *conn)
+        .await?
+        .into_iter()
+        .collect();
+
+    // Keyed by account type slug or tag id.
+    let target_keys: Vec<&str> = targets
+        .iter()
+        .map(|target| match &target.bucket {
+            AllocationBucket::AccountType { account_type } => account_type.as_str(),
+            AllocationBucket::Tag { tag_id } => tag_id.as_str(),
+        })
+        .collect();
+    let mut targeted: HashMap<&str, Holding> = HashMap::new();
+    let mut unallocated: BTreeMap<String, Holding> = BTreeMap::new();
+    let mut all = Vec::new();
+    for AccountValues {
+        account, values, ..
+    } in &accounts
+    {
+        let Some(&(_, value, _)) = values.first() else {
+            continue;
+        };
+        if account.category != AccountCategory::Asset {
+            continue;
+        }
+        all.push(value);
+        let key = if by_tag {
+            tags.get(&account.id).map(String::as_str)
+        } else {
+            Some(account.account_type.as_str())
+        };
+        let holding = match key.and_then(|key| target_keys.iter().find(|target| **target == key)) {
+            Some(key) => targeted.entry(key).or_default(),
+            None if by_tag => unallocated
+                .entry(account.id.clone())
+                .or_insert_with(|| Holding {
+                    name: account.name.clone(),
+                    ..Holding::default()
+                }),
+            None => {
+                let kind = account.account_type.as_str().to_string();
+                unallocated.entry(kind.clone()).or_insert_with(|| Holding {
+                    name: kind,
+                    ..Holding::default()
+                })
+            }
+        };
+        holding.amounts.push(value);
+        holding.account_ids.push(account.id.clone());
+    }
+
+    let total_assets = round_amount(sum_amounts(all), units);
+    let share = |amount: f64| {
+        if total_assets == 0.0 {
+            0.0
+        } else {
+            amount / total_assets * 100.0
+        }
+    };
+    let buckets = targets
+        .iter()
+        .zip(&target_keys)
+        .map(|(target, key)| {
+            let holding = targeted.remove(key).unwrap_or_default();
+            let actual_amount = round_amount(sum_amounts(holding.amounts), units);
+            let target_amount = round_amount(total_assets * target.target_percent / 100.0, units);
+            let name = match &target.bucket {
+                AllocationBucket::AccountType { account_type } => account_type.as_str().to_string(),
+                AllocationBucket::Tag { tag_id } => {
+                    tag_names.get(tag_id).cloned().unwrap_or_default()
+                }
+            };
+            AllocationDriftBucket {
+                target_id: target.id.clone(),
+                bucket: target.bucket.clone(),
+                name,
+                account_ids: holding.account_ids,
+                actual_amount,
+                actual_percent: round_amount(share(actual_amount), 2),
+                target_amount,
+                target_percent: target.target_percent,
+                drift_percent: round_amount(share(actual_amount) - target.target_percent, 2),
+                drift_amount: sum_amounts([actual_amount, -target_amount]),
+                to_move: sum_amounts([target_amount, -actual_amount]),
+            }
+        })
+        .collect();
+    let mut unallocated: Vec<UnallocatedHolding> = unallocated
+        .into_iter()
+        .map(|(key, holding)| {
+            let amount = round_amount(sum_amounts(holding.amounts), units);
+            UnallocatedHolding {
+                key,
+                name: holding.name,
+                account_ids: holding.account_ids,
+                amount,
+                percent: round_amount(share(amount), 2),
+            }
+        })
+        .collect();
+    unallocated.sort_by(|a, b| {
+        b.amount
+            .total_cmp(&a.amount)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+
+    Ok(AllocationDrift {
+        as_of: dates::format(as_of),
+        total_assets,
+        buckets,
+        unallocated,
+    })
+}
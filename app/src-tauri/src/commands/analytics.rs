@@ -0,0 +1,2084 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use chrono::{Datelike, Days, Months, NaiveDate};
+use sqlx::{SqliteConnection, SqlitePool};
+use tauri::State;
+
+use super::accounts::fetch_account;
+use super::balances::fetch_points;
+use super::exchange_rates::convert;
+use super::income::{format_month, parse_month};
+use super::inflation::{load_deflator, Deflator};
+use super::milestones::fetch_milestones;
+use super::net_worth_cache::cached_points;
+use super::settings::load_settings;
+use super::{parse_date, today_date};
+use crate::dates;
+use crate::error::{Error, Result};
+use crate::models::{
+    check_fire_rates, fiscal_year_start_in, minor_units_for, round_amount, sum_amounts, Account,
+    AccountCategory, AccountType, Allocation, AllocationGroupBy, AllocationSlice, BreakdownGroupBy,
+    Cagr, CagrEndpoint, CagrScope, CagrUnavailable, CurrentDrawdown, DateRange, DebtRatioPoint,
+    Drawdown, DrawdownAnalysis, FillMethod, FireBasis, FireParams, FireProjection, FireStatus,
+    Flow, FlowKind, ForecastFit, ForecastModel, ForecastPoint, Granularity, GrowthSummary,
+    GrowthWindow, GrowthWindowKind, InflationOptions, Liquidity, LiquidityAccount,
+    LiquidityBreakdown, LiquidityPoint, LiquidityTotals, MilestoneMarker, MonteCarloBand,
+    MonteCarloParams, MonteCarloProjection, NetWorthBreakdownPoint, NetWorthForecast,
+    NetWorthPoint, NetWorthSeriesOptions, PeriodChange, PeriodStats, SavingsRate, SavingsRateMonth,
+    SavingsSource, Streak, StreakDirection, TrendMethod,
+};
+use crate::series::{
+    linear_fit, month_end, month_ends, period_dates, r_squared, resample, smooth, LinearFit,
+    DAYS_PER_MONTH,
+};
+use crate::simulation::{self, Simulation};
+
+/// One account's part of net worth on each date it was held: its owned share
+/// in the base currency, unrounded so that totals are rounded once after
+/// summing, with what is owed as a positive amount, and whether it was
+/// estimated.
+pub(crate) struct AccountValues {
+    pub account: Account,
+    pub values: Vec<(NaiveDate, f64, bool)>,
+    /// The first day it has a value: its first entry or opening date, whichever
+    /// is later. `None` without entries.
+    held_from: Option<NaiveDate>,
+    held_until: Option<NaiveDate>,
+}
+
+/// Every account not archived or excluded from net worth.
+pub(crate) async fn net_worth_accounts(conn: &mut SqliteConnection) -> Result<Vec<Account>> {
+    let accounts = sqlx::query_as::<_, Account>(
+        "SELECT * FROM accounts
+         WHERE archived_at IS NULL AND exclude_from_net_worth = 0
+         ORDER BY sort_order, name",
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    Ok(accounts)
+}
+
+/// The accounts counted towards net worth, each valued on `period_ends`: every
+/// account not archived or excluded from net worth, from its first entry or
+/// opening date until it closed, converted at the nearest recorded rate.
+pub(crate) async fn account_values(
+    conn: &mut SqliteConnection,
+    period_ends: &[NaiveDate],
+    method: FillMethod,
+    exclude_estimated: bool,
+    base_currency: &str,
+) -> Result<Vec<AccountValues>> {
+    let accounts = net_worth_accounts(conn).await?;
+    value_accounts(
+        conn,
+        accounts,
+        period_ends,
+        method,
+        exclude_estimated,
+        base_currency,
+    )
+    .await
+}
+
+/// `accounts` valued on `period_ends` as in `account_values`.
+pub(crate) async fn value_accounts(
+    conn: &mut SqliteConnection,
+    accounts: Vec<Account>,
+    period_ends: &[NaiveDate],
+    method: FillMethod,
+    exclude_estimated: bool,
+    base_currency: &str,
+) -> Result<Vec<AccountValues>> {
+    let mut valued = Vec::with_capacity(accounts.len());
+    // Rates are looked up once per currency and date, however many accounts use them.
+    let mut rates: HashMap<(String, NaiveDate), f64> = HashMap::new();
+    for account in accounts {
+        let bound = |date: &Option<String>, field| {
+            date.as_deref()
+                .map(|date| parse_date(field, date))
+                .transpose()
+        };
+        let opened_at = bound(&account.opened_at, "opened_at")?;
+        let closed_at = bound(&account.closed_at, "closed_at")?;
+        let points = fetch_points(&mut *conn, &account.id, exclude_estimated).await?;
+        let share = account.ownership_percent / 100.0;
+
+        let mut values = Vec::new();
+        for sample in resample(&points, period_ends, method) {
+            let held = opened_at.is_none_or(|opened| sample.date >= opened)
+                && closed_at.is_none_or(|closed| sample.date <= closed);
+            if !held {
+                continue;
+            }
+            let mut value = sample.balance * share;
+            if account.currency != base_currency {
+                let key = (account.currency.clone(), sample.date);
+                let rate = match rates.get(&key) {
+                    Some(rate) => *rate,
+                    None => {
+                        let date = dates::format(sample.date);
+                        let rate =
+                            convert(&mut *conn, 1.0, &account.currency, base_currency, &date)
+                                .await?;
+                        *rates.entry(key).or_insert(rate)
+                    }
+                };
+                value *= rate;
+            }
+            if account.category == AccountCategory::Liability {
+                value = value.abs();
+            }
+            values.push((sample.date, value, sample.estimated));
+        }
+        let held_from = points
+            .first()
+            .map(|first| opened_at.map_or(first.date, |opened| opened.max(first.date)));
+        valued.push(AccountValues {
+            account,
+            values,
+            held_from,
+            held_until: closed_at,
+        });
+    }
+    Ok(valued)
+}
+
+pub(crate) fn check_range(start: &str, end: &str) -> Result<(NaiveDate, NaiveDate)> {
+    let start = parse_date("start", start)?;
+    let end = parse_date("end", end)?;
+    if end < start {
+        return Err(Error::validation("end", "must not be before start"));
+    }
+    Ok((start, end))
+}
+
+#[derive(Default)]
+struct Totals {
+    assets: Vec<f64>,
+    liabilities: Vec<f64>,
+    estimated: bool,
+}
+
+/// Puts each milestone between `start` and `end` on the first point on or after
+/// its date, which closes the period it falls in. Ones before the first point
+/// go on that one.
+async fn attach_milestones(
+    conn: &mut SqliteConnection,
+    series: &mut [NetWorthPoint],
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<()> {
+    let Some(last) = series.len().checked_sub(1) else {
+        return Ok(());
+    };
+    let milestones = fetch_milestones(
+        conn,
+        Some(dates::format(start)),
+        Some(dates::format(end)),
+        None,
+    )
+    .await?;
+    let mut point = 0;
+    for milestone in milestones {
+        while point < last && series[point].date < milestone.date {
+            point += 1;
+        }
+        series[point].milestones.push(MilestoneMarker {
+            achieved: milestone.achieved_at.is_some(),
+            milestone_id: milestone.id,
+            date: milestone.date,
+            label: milestone.label,
+            category: milestone.category,
+            icon: milestone.icon,
+        });
+    }
+    Ok(())
+}
+
+/// Net worth on each day, week or month between `start` and `end`, from the
+/// accounts of `account_values`, each rounded to the base currency before they
+/// are added up. Balances between entries are estimated as in
+/// `get_account_series`. Dates before any account has a balance have no point.
+/// Served from the snapshots of `refresh_snapshots` when they cover the range.
+#[tauri::command]
+pub async fn get_net_worth_series(
+    pool: State<'_, SqlitePool>,
+    start: String,
+    end: String,
+    granularity: Granularity,
+    options: Option<NetWorthSeriesOptions>,
+) -> Result<Vec<NetWorthPoint>> {
+    let options = options.unwrap_or_default();
+    let (start, end) = check_range(&start, &end)?;
+    let mut conn = pool.acquire().await?;
+    let mut series = match cached_points(&mut conn, start, end, granularity, &options).await? {
+        Some(series) => series,
+        None => net_worth_points(&mut conn, start, end, granularity, &options).await?,
+    };
+    if let Some(spec) = options.smoothing {
+        let net: Vec<f64> = series.iter().map(|point| point.net).collect();
+        for (point, smoothed) in series.iter_mut().zip(smooth(&net, spec)?) {
+            point.smoothed = Some(smoothed);
+        }
+    }
+    if options.include_milestones {
+        attach_milestones(&mut conn, &mut series, start, end).await?;
+    }
+    Ok(series)
+}
+
+/// The points of `get_net_worth_series`, without milestones.
+pub(crate) async fn net_worth_points(
+    conn: &mut SqliteConnection,
+    start: NaiveDate,
+    end: NaiveDate,
+    granularity: Granularity,
+    options: &NetWorthSeriesOptions,
+) -> Result<Vec<NetWorthPoint>> {
+    let base_currency = load_settings(&mut *conn).await?.base_currency;
+    let deflator = load_deflator(conn, &options.inflation).await?;
+    let totals = net_worth_totals(
+        conn,
+        &period_dates(start, end, granularity),
+        options.method.unwrap_or(FillMethod::Linear),
+        options.exclude_estimated,
+        &base_currency,
+    )
+    .await?;
+    Ok(points_from_totals(
+        totals,
+        deflator.as_ref(),
+        minor_units_for(&base_currency),
+    ))
+}
+
+/// Assets, liabilities and whether either was estimated on each of
+/// `period_ends` that some account has a value on, before any inflation
+/// adjustment.
+pub(crate) async fn net_worth_totals(
+    conn: &mut SqliteConnection,
+    period_ends: &[NaiveDate],
+    method: FillMethod,
+    exclude_estimated: bool,
+    base_currency: &str,
+) -> Result<BTreeMap<NaiveDate, (f64, f64, bool)>> {
+    let units = minor_units_for(base_currency);
+    let accounts =
+        account_values(conn, period_ends, method, exclude_estimated, base_currency).await?;
+
+    let mut totals: BTreeMap<NaiveDate, Totals> = BTreeMap::new();
+    for AccountValues {
+        account, values, ..
+    } in accounts
+    {
+        for (date, value, estimated) in values {
+            let totals = totals.entry(date).or_default();
+            match account.category {
+                AccountCategory::Asset => totals.assets.push(value),
+                AccountCategory::Liability => totals.liabilities.push(value),
+            }
+            totals.estimated |= estimated;
+        }
+    }
+
+    Ok(totals
+        .into_iter()
+        .map(|(date, totals)| {
+            let assets = round_amount(sum_amounts(totals.assets), units);
+            let liabilities = round_amount(sum_amounts(totals.liabilities), units);
+            (date, (assets, liabilities, totals.estimated))
+        })
+        .collect())
+}
+
+/// The points for `totals`, in the prices of `deflator`'s month when there is
+/// one, rounded to `units`.
+pub(crate) fn points_from_totals(
+    totals: BTreeMap<NaiveDate, (f64, f64, bool)>,
+    deflator: Option<&Deflator>,
+    units: u8,
+) -> Vec<NetWorthPoint> {
+    totals
+        .into_iter()
+        .map(|(date, (mut assets, mut liabilities, mut estimated))| {
+            if let Some(deflator) = deflator {
+                let (factor, cpi_estimated) = deflator.factor(date);
+                assets = round_amount(assets * factor, units);
+                liabilities = round_amount(liabilities * factor, units);
+                estimated |= cpi_estimated;
+            }
+            NetWorthPoint {
+                date: dates::format(date),
+                assets,
+                liabilities,
+                net: sum_amounts([assets, -liabilities]),
+                is_estimated: estimated,
+                smoothed: None,
+                milestones: Vec::new(),
+            }
+        })
+        .collect()
+}
+
+/// Liabilities against assets and against net worth on each date of
+/// `get_net_worth_series`, from the same figures.
+#[tauri::command]
+pub async fn get_debt_ratio_series(
+    pool: State<'_, SqlitePool>,
+    start: String,
+    end: String,
+    granularity: Granularity,
+) -> Result<Vec<DebtRatioPoint>> {
+    let (start, end) = check_range(&start, &end)?;
+    let mut conn = pool.acquire().await?;
+    let points = net_worth_points(
+        &mut conn,
+        start,
+        end,
+        granularity,
+        &NetWorthSeriesOptions::default(),
+    )
+    .await?;
+    Ok(points
+        .into_iter()
+        .map(|point| DebtRatioPoint {
+            debt_to_assets: (point.assets > 0.0).then(|| point.liabilities / point.assets),
+            debt_to_net_worth: (point.net > 0.0).then(|| point.liabilities / point.net),
+            date: point.date,
+            assets: point.assets,
+            liabilities: point.liabilities,
+            net: point.net,
+            is_estimated: point.is_estimated,
+        })
+        .collect())
+}
+
+/// `get_net_worth_series` split by `group_by`: on each date, what each account
+/// category or type amounts to, with what is owed as a positive amount. Groups
+/// that are zero throughout are left out; the others appear on every date. The
+/// groups on a date add up to that date's assets and liabilities.
+#[tauri::command]
+pub async fn get_net_worth_breakdown(
+    pool: State<'_, SqlitePool>,
+    start: String,
+    end: String,
+    granularity: Granularity,
+    group_by: BreakdownGroupBy,
+    options: Option<NetWorthSeriesOptions>,
+) -> Result<Vec<NetWorthBreakdownPoint>> {
+    let options = options.unwrap_or_default();
+    let (start, end) = check_range(&start, &end)?;
+    let mut conn = pool.acquire().await?;
+    let base_currency = load_settings(&mut *conn).await?.base_currency;
+    let units = minor_units_for(&base_currency);
+    let accounts = account_values(
+        &mut conn,
+        &period_dates(start, end, granularity),
+        options.method.unwrap_or(FillMethod::Linear),
+        options.exclude_estimated,
+        &base_currency,
+    )
+    .await?;
+
+    let mut by_date: BTreeMap<NaiveDate, (BTreeMap<String, Vec<f64>>, bool)> = BTreeMap::new();
+    let mut nonzero = HashSet::new();
+    for AccountValues {
+        account, values, ..
+    } in &accounts
+    {
+        let group = match group_by {
+            BreakdownGroupBy::Category => account.category.as_str(),
+            BreakdownGroupBy::AccountType => account.account_type.as_str(),
+        };
+        for &(date, value, estimated) in values {
+            let (groups, any_estimated) = by_date.entry(date).or_default();
+            groups.entry(group.to_string()).or_default().push(value);
+            *any_estimated |= estimated;
+            if value != 0.0 {
+                nonzero.insert(group);
+            }
+        }
+    }
+
+    Ok(by_date
+        .into_iter()
+        .map(|(date, (groups, estimated))| {
+            let mut amounts: BTreeMap<String, f64> = nonzero
+                .iter()
+                .map(|group| (group.to_string(), 0.0))
+                .collect();
+            for (group, values) in groups {
+                if let Some(amount) = amounts.get_mut(&group) {
+                    *amount = round_amount(sum_amounts(values), units);
+                }
+            }
+            NetWorthBreakdownPoint {
+                date: dates::format(date),
+                groups: amounts,
+                is_estimated: estimated,
+            }
+        })
+        .collect())
+}
+
+/// The accounts in one slice, before it is sized.
+#[derive(Default)]
+struct Group {
+    name: String,
+    amounts: Vec<f64>,
+    account_ids: Vec<String>,
+}
+
+/// The total of `groups` and their slices of it, largest first. Slices under
+/// `other_threshold` percent are merged into one named "Other" when there are
+/// at least two of them.
+fn slices(
+    groups: BTreeMap<String, Group>,
+    other_threshold: f64,
+    units: u8,
+) -> (f64, Vec<AllocationSlice>) {
+    let groups: Vec<(String, Group, f64)> = groups
+        .into_iter()
+        .map(|(key, group)| {
+            let amount = round_amount(sum_amounts(group.amounts.iter().copied()), units);
+            (key, group, amount)
+        })
+        .collect();
+    let total = sum_amounts(groups.iter().map(|(_, _, amount)| *amount));
+    let percent = |amount: f64| {
+        if total == 0.0 {
+            0.0
+        } else {
+            round_amount(amount / total * 100.0, 2)
+        }
+    };
+    let mut slices: Vec<AllocationSlice> = groups
+        .into_iter()
+        .filter(|(_, _, amount)| *amount != 0.0)
+        .map(|(key, group, amount)| AllocationSlice {
+            key,
+            name: group.name,
+            amount,
+            percent: percent(amount),
+            account_ids: group.account_ids,
+            is_other: false,
+        })
+        .collect();
+    slices.sort_by(|a, b| {
+        b.amount
+            .total_cmp(&a.amount)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+
+    let small = slices
+        .iter()
+        .filter(|slice| slice.percent < other_threshold)
+        .count();
+    if small >= 2 {
+        let (kept, collapsed): (Vec<_>, Vec<_>) = slices
+            .into_iter()
+            .partition(|slice| slice.percent >= other_threshold);
+        let amount = sum_amounts(collapsed.iter().map(|slice| slice.amount));
+        slices = kept;
+        slices.push(AllocationSlice {
+            key: String::new(),
+            name: "Other".to_string(),
+            amount,
+            percent: percent(amount),
+            account_ids: collapsed
+                .into_iter()
+                .flat_map(|slice| slice.account_ids)
+                .collect(),
+            is_other: true,
+        });
+    }
+    (total, slices)
+}
+
+/// What each account, account type or institution held on `as_of` (default
+/// today), from the latest entry on or before it, valued as in
+/// `get_net_worth_series`. Assets and liabilities are split separately, each
+/// slice with its share of its side. With `other_threshold`, a percentage,
+/// smaller slices are collapsed into one.
+#[tauri::command]
+pub async fn get_allocation(
+    pool: State<'_, SqlitePool>,
+    as_of: Option<String>,
+    group_by: AllocationGroupBy,
+    other_threshold: Option<f64>,
+) -> Result<Allocation> {
+    let as_of = match as_of {
+        Some(date) => parse_date("as_of", &date)?,
+        None => today_date(),
+    };
+    let other_threshold = other_threshold.unwrap_or(0.0);
+    if !(0.0..=100.0).contains(&other_threshold) {
+        return Err(Error::validation(
+            "other_threshold",
+            "must be a percentage between 0 and 100",
+        ));
+    }
+
+    let mut conn = pool.acquire().await?;
+    let base_currency = load_settings(&mut *conn).await?.base_currency;
+    let accounts = account_values(
+        &mut conn,
+        &[as_of],
+        FillMethod::CarryForward,
+        false,
+        &base_currency,
+    )
+    .await?;
+
+    // Assets, then liabilities, by group key.
+    let mut sides: [BTreeMap<String, Group>; 2] = Default::default();
+    for AccountValues {
+        account, values, ..
+    } in accounts
+    {
+        let Some(&(_, value, _)) = values.first() else {
+            continue;
+        };
+        let (key, name) = match group_by {
+            AllocationGroupBy::Account => (account.id.clone(), account.name.clone()),
+            AllocationGroupBy::AccountType => {
+                let kind = account.account_type.as_str().to_string();
+                (kind.clone(), kind)
+            }
+            AllocationGroupBy::Institution => match &account.institution {
+                Some(institution) => (institution.clone(), institution.clone()),
+                None => (String::new(), "No institution".to_string()),
+            },
+        };
+        let side = match account.category {
+            AccountCategory::Asset => 0,
+            AccountCategory::Liability => 1,
+        };
+        let group = sides[side].entry(key).or_insert_with(|| Group {
+            name,
+            ..Group::default()
+        });
+        group.amounts.push(value);
+        group.account_ids.push(account.id);
+    }
+
+    let units = minor_units_for(&base_currency);
+    let [assets, liabilities] = sides.map(|groups| slices(groups, other_threshold, units));
+    Ok(Allocation {
+        as_of: dates::format(as_of),
+        group_by,
+        total_assets: assets.0,
+        total_liabilities: liabilities.0,
+        assets: assets.1,
+        liabilities: liabilities.1,
+    })
+}
+
+/// Net worth on each of `dates`, ascending, as in `get_net_worth_series`;
+/// `None` on dates before any account has a balance.
+pub(crate) async fn net_worth_on(
+    conn: &mut SqliteConnection,
+    dates: &[NaiveDate],
+    base_currency: &str,
+) -> Result<Vec<Option<f64>>> {
+    let accounts = account_values(conn, dates, FillMethod::Linear, false, base_currency).await?;
+    Ok(totals_on(&accounts, dates, minor_units_for(base_currency)))
+}
+
+/// What `accounts` add up to on each of `dates`, with what is owed counted
+/// against them, rounded to `units`; `None` on dates none of them has a value.
+pub(crate) fn totals_on(
+    accounts: &[AccountValues],
+    dates: &[NaiveDate],
+    units: u8,
+) -> Vec<Option<f64>> {
+    let mut totals: BTreeMap<NaiveDate, Vec<f64>> = BTreeMap::new();
+    for AccountValues {
+        account, values, ..
+    } in accounts
+    {
+        for (date, value, _) in values {
+            let value = match account.category {
+                AccountCategory::Asset => *value,
+                AccountCategory::Liability => -value,
+            };
+            totals.entry(*date).or_default().push(value);
+        }
+    }
+    dates
+        .iter()
+        .map(|date| {
+            totals
+                .remove(date)
+                .map(|values| round_amount(sum_amounts(values), units))
+        })
+        .collect()
+}
+
+/// Net worth on `as_of` (default today) against 1, 3 and 12 months earlier and
+/// the start of the financial year, each from the interpolated series. A window
+/// starting before the first balance is marked unavailable rather than
+/// compared against nothing. With `inflation`, every figure is first restated
+/// in the prices of its reference month.
+#[tauri::command]
+pub async fn get_growth_summary(
+    pool: State<'_, SqlitePool>,
+    as_of: Option<String>,
+    inflation: Option<InflationOptions>,
+) -> Result<GrowthSummary> {
+    let as_of = match as_of {
+        Some(date) => parse_date("as_of", &date)?,
+        None => today_date(),
+    };
+    let mut conn = pool.acquire().await?;
+    let settings = load_settings(&mut *conn).await?;
+
+    let months_back = |months| as_of - Months::new(months);
+    let year_start =
+        fiscal_year_start_in(&settings.fiscal_year_start, as_of.year()).ok_or_else(|| {
+            Error::validation("fiscal_year_start", "must be a month and day as MM-DD")
+        })?;
+    let year_start = if year_start > as_of {
+        fiscal_year_start_in(&settings.fiscal_year_start, as_of.year() - 1).unwrap_or(year_start)
+    } else {
+        year_start
+    };
+    let windows = [
+        (GrowthWindowKind::OneMonth, months_back(1)),
+        (GrowthWindowKind::ThreeMonths, months_back(3)),
+        (GrowthWindowKind::TwelveMonths, months_back(12)),
+        (GrowthWindowKind::YearToDate, year_start),
+    ];
+
+    let mut dates: Vec<NaiveDate> = windows.iter().map(|(_, date)| *date).collect();
+    dates.push(as_of);
+    dates.sort();
+    dates.dedup();
+    let mut values = net_worth_on(&mut conn, &dates, &settings.base_currency).await?;
+    let deflator = load_deflator(&mut conn, &inflation.unwrap_or_default()).await?;
+    let mut cpi_estimated = false;
+    if let Some(deflator) = &deflator {
+        let units = minor_units_for(&settings.base_currency);
+        for (date, value) in dates.iter().zip(&mut values) {
+            if let Some(value) = value {
+                let (factor, estimated) = deflator.factor(*date);
+                *value = round_amount(*value * factor, units);
+                cpi_estimated |= estimated;
+            }
+        }
+    }
+    let value_on = |date: NaiveDate| {
+        dates
+            .binary_search(&date)
+            .ok()
+            .and_then(|index| values[index])
+    };
+
+    let net_worth = value_on(as_of);
+    Ok(GrowthSummary {
+        as_of: dates::format(as_of),
+        net_worth,
+        windows: windows
+            .into_iter()
+            .map(|(window, start)| {
+                let start_net_worth = value_on(start);
+                let change = start_net_worth
+                    .zip(net_worth)
+                    .map(|(from, to)| sum_amounts([to, -from]));
+                GrowthWindow {
+                    window,
+                    start: dates::format(start),
+                    available: change.is_some(),
+                    start_net_worth,
+                    change,
+                    change_percent: start_net_worth
+                        .zip(change)
+                        .filter(|(from, _)| *from != 0.0)
+                        .map(|(from, change)| change / from.abs() * 100.0),
+                }
+            })
+            .collect(),
+        reference_month: deflator.map(|deflator| deflator.reference_month),
+        cpi_estimated,
+    })
+}
+
+/// Compound annual growth of `scope` from `start` to `end`. Dates outside the
+/// span the scope has values for are moved to its first or last day, and the
+/// dates and values actually used are returned. Windows under a year are still
+/// annualised, and flagged. With `inflation`, the growth is real: both
+/// endpoints are in the prices of the reference month.
+#[tauri::command]
+pub async fn get_cagr(
+    pool: State<'_, SqlitePool>,
+    scope: CagrScope,
+    start: String,
+    end: String,
+    inflation: Option<InflationOptions>,
+) -> Result<Cagr> {
+    let (requested_start, requested_end) = check_range(&start, &end)?;
+    let mut conn = pool.acquire().await?;
+    let base_currency = load_settings(&mut *conn).await?.base_currency;
+    let accounts = match &scope {
+        CagrScope::NetWorth => net_worth_accounts(&mut conn).await?,
+        CagrScope::Category { category } => net_worth_accounts(&mut conn)
+            .await?
+            .into_iter()
+            .filter(|account| account.category == *category)
+            .collect(),
+        CagrScope::Account { account_id } => vec![fetch_account(&mut *conn, account_id).await?],
+    };
+
+    // Valued once to find the span the scope is held over, then on the dates used.
+    let spans = value_accounts(
+        &mut conn,
+        accounts.clone(),
+        &[],
+        FillMethod::Linear,
+        false,
+        &base_currency,
+    )
+    .await?;
+    let held: Vec<&AccountValues> = spans
+        .iter()
+        .filter(|account| account.held_from.is_some())
+        .collect();
+    let first = held.iter().filter_map(|account| account.held_from).min();
+    // Only bounded while every account in the scope has closed.
+    let last = held
+        .iter()
+        .map(|account| account.held_until)
+        .collect::<Option<Vec<_>>>()
+        .and_then(|closed| closed.into_iter().max());
+    let start = first.map_or(requested_start, |first| requested_start.max(first));
+    let end = last.map_or(requested_end, |last| requested_end.min(last));
+
+    let mut result = Cagr {
+        scope,
+        requested_start: dates::format(requested_start),
+        requested_end: dates::format(requested_end),
+        start: None,
+        end: None,
+        years: 0.0,
+        short_window: false,
+        cagr: None,
+        unavailable: None,
+        reference_month: None,
+    };
+    if first.is_none() || start > end {
+        result.unavailable = Some(CagrUnavailable::NoData);
+        return Ok(result);
+    }
+
+    let dates = if start == end {
+        vec![start]
+    } else {
+        vec![start, end]
+    };
+    let valued = value_accounts(
+        &mut conn,
+        accounts,
+        &dates,
+        FillMethod::Linear,
+        false,
+        &base_currency,
+    )
+    .await?;
+    let deflator = load_deflator(&mut conn, &inflation.unwrap_or_default()).await?;
+    let units = minor_units_for(&base_currency);
+    let endpoint = |date: NaiveDate| {
+        let mut amounts = Vec::new();
+        let mut estimated = false;
+        for AccountValues {
+            account, values, ..
+        } in &valued
+        {
+            if let Some((_, value, is_estimated)) = values.iter().find(|(on, ..)| *on == date) {
+                let signed = matches!(result.scope, CagrScope::NetWorth)
+                    && account.category == AccountCategory::Liability;
+                amounts.push(if signed { -value } else { *value });
+                estimated |= is_estimated;
+            }
+        }
+        let mut value = round_amount(sum_amounts(amounts), units);
+        if let Some(deflator) = &deflator {
+            let (factor, cpi_estimated) = deflator.factor(date);
+            value = round_amount(value * factor, units);
+            estimated |= cpi_estimated;
+        }
+        CagrEndpoint {
+            date: dates::format(date),
+            value,
+            estimated,
+        }
+    };
+    let (from, to) = (endpoint(start), endpoint(end));
+    let days = (end - start).num_days();
+    result.years = days as f64 / 365.25;
+    result.short_window = days < 365;
+    result.unavailable = if days == 0 {
+        Some(CagrUnavailable::EmptyWindow)
+    } else if from.value <= 0.0 {
+        Some(CagrUnavailable::NonPositiveStart)
+    } else if to.value < 0.0 {
+        Some(CagrUnavailable::NegativeEnd)
+    } else {
+        None
+    };
+    if result.unavailable.is_none() {
+        result.cagr = Some(((to.value / from.value).powf(1.0 / result.years) - 1.0) * 100.0);
+    }
+    result.start = Some(from);
+    result.end = Some(to);
+    result.reference_month = deflator.map(|deflator| deflator.reference_month);
+    Ok(result)
+}
+
+/// Every flow from `start` to `end` into `accounts`, at their owned share in
+/// the base currency, with withdrawals negative.
+pub(crate) async fn net_flows(
+    conn: &mut SqliteConnection,
+    accounts: &[Account],
+    start: NaiveDate,
+    end: NaiveDate,
+    base_currency: &str,
+) -> Result<Vec<(String, f64)>> {
+    let mut flows = Vec::new();
+    for account in accounts {
+        let account_flows = sqlx::query_as::<_, Flow>(
+            "SELECT * FROM flows WHERE account_id = ? AND date >= ? AND date <= ?",
+        )
+        .bind(&account.id)
+        .bind(dates::format(start))
+        .bind(dates::format(end))
+        .fetch_all(&mut *conn)
+        .await?;
+        for flow in account_flows {
+            let mut amount = flow.amount * account.ownership_percent / 100.0;
+            if account.currency != base_currency {
+                amount = convert(
+                    &mut *conn,
+                    amount,
+                    &account.currency,
+                    base_currency,
+                    &flow.date,
+                )
+                .await?;
+            }
+            if flow.kind == FlowKind::Withdrawal {
+                amount = -amount;
+            }
+            flows.push((flow.date, amount));
+        }
+    }
+    Ok(flows)
+}
+
+/// Total saved over total income across `months`, skipping those missing either.
+fn pooled_rate<'a>(months: impl IntoIterator<Item = &'a SavingsRateMonth>) -> Option<f64> {
+    let (mut saved, mut income) = (Vec::new(), Vec::new());
+    for month in months {
+        if let (Some(month_saved), Some(month_income)) = (month.saved, month.income) {
+            saved.push(month_saved);
+            income.push(month_income);
+        }
+    }
+    let income = sum_amounts(income);
+    (income > 0.0).then(|| sum_amounts(saved) / income * 100.0)
+}
+
+/// What was saved each month from `start` to `end` (as `YYYY-MM`) against the
+/// income recorded for it. Saving is the net flow into accounts counted
+/// towards net worth, at their owned share; a month without flows falls back
+/// to its change in net worth. Months without income are flagged and left out
+/// of the rates that span several months.
+#[tauri::command]
+pub async fn get_savings_rate(
+    pool: State<'_, SqlitePool>,
+    start: String,
+    end: String,
+) -> Result<SavingsRate> {
+    let first = parse_month("start", &start)?;
+    let last = parse_month("end", &end)?;
+    if last < first {
+        return Err(Error::validation("end", "must not be before start"));
+    }
+    // The eleven months before `start` are worked out too, for the trailing rates.
+    let from = first - Months::new(11);
+    let mut months = Vec::new();
+    let mut month = from;
+    while month <= last {
+        months.push(month);
+        month = month + Months::new(1);
+    }
+
+    let mut conn = pool.acquire().await?;
+    let base_currency = load_settings(&mut *conn).await?.base_currency;
+    let units = minor_units_for(&base_currency);
+    let incomes: HashMap<String, f64> = sqlx::query_as::<_, (String, f64)>(
+        "SELECT month, net_income FROM income_records WHERE month >= ? AND month <= ?",
+    )
+    .bind(format_month(from))
+    .bind(format_month(last))
+    .fetch_all(&mut *conn)
+    .await?
+    .into_iter()
+    .collect();
+
+    // Net worth at the end of each month, after that of the month before the first.
+    let ends: Vec<NaiveDate> = std::iter::once(from - Days::new(1))
+        .chain(months.iter().map(|month| month_end(*month)))
+        .collect();
+    let net_worth = net_worth_on(&mut conn, &ends, &base_currency).await?;
+
+    let mut flows: HashMap<String, Vec<f64>> = HashMap::new();
+    let accounts = net_worth_accounts(&mut conn).await?;
+    for (date, amount) in
+        net_flows(&mut conn, &accounts, from, month_end(last), &base_currency).await?
+    {
+        flows.entry(date[..7].to_string()).or_default().push(amount);
+    }
+
+    let mut rows: Vec<SavingsRateMonth> = Vec::with_capacity(months.len());
+    for (index, month) in months.iter().enumerate() {
+        let month = format_month(*month);
+        let (saved, source) = match flows.remove(&month) {
+            Some(amounts) => (
+                Some(round_amount(sum_amounts(amounts), units)),
+                SavingsSource::Flows,
+            ),
+            None => (
+                net_worth[index]
+                    .zip(net_worth[index + 1])
+                    .map(|(before, after)| sum_amounts([after, -before])),
+                SavingsSource::NetWorthChange,
+            ),
+        };
+        let income = incomes.get(&month).copied();
+        let mut row = SavingsRateMonth {
+            month,
+            income,
+            saved,
+            source,
+            rate: None,
+            missing_income: income.is_none(),
+            trailing_12_month_rate: None,
+        };
+        row.rate = pooled_rate([&row]);
+        row.trailing_12_month_rate =
+            pooled_rate(rows[index.saturating_sub(11)..].iter().chain([&row]));
+        rows.push(row);
+    }
+    let months = rows.split_off(11);
+
+    Ok(SavingsRate {
+        start: format_month(first),
+        end: format_month(last),
+        average_rate: pooled_rate(&months),
+        months,
+    })
+}
+
+/// How far ahead `get_fire_projection` looks before calling a target out of reach.
+const MAX_FIRE_MONTHS: u32 = 1200;
+
+/// When compounding today's investable net worth, plus a monthly contribution,
+/// reaches annual expenses divided by the safe withdrawal rate. Every liability
+/// counts against the starting figure, whichever assets are counted.
+#[tauri::command]
+pub async fn get_fire_projection(
+    pool: State<'_, SqlitePool>,
+    params: FireParams,
+) -> Result<FireProjection> {
+    if !params.annual_expenses.is_finite() || params.annual_expenses <= 0.0 {
+        return Err(Error::validation(
+            "annual_expenses",
+            "must be a positive number",
+        ));
+    }
+    check_fire_rates(params.safe_withdrawal_rate, params.real_growth)?;
+    if params
+        .monthly_contribution
+        .is_some_and(|amount| !amount.is_finite())
+    {
+        return Err(Error::validation(
+            "monthly_contribution",
+            "must be a number",
+        ));
+    }
+
+    let mut conn = pool.acquire().await?;
+    let settings = load_settings(&mut *conn).await?;
+    let units = minor_units_for(&settings.base_currency);
+    let today = today_date();
+    let safe_withdrawal_rate = params
+        .safe_withdrawal_rate
+        .unwrap_or(settings.safe_withdrawal_rate);
+    let real_growth = params.real_growth.unwrap_or(settings.expected_real_growth);
+    let monthly_contribution = match params.monthly_contribution {
+        Some(amount) => amount,
+        None => {
+            let accounts = net_worth_accounts(&mut conn).await?;
+            let flows = net_flows(
+                &mut conn,
+                &accounts,
+                today - Months::new(12) + Days::new(1),
+                today,
+                &settings.base_currency,
+            )
+            .await?;
+            round_amount(
+                sum_amounts(flows.into_iter().map(|(_, amount)| amount)) / 12.0,
+                units,
+            )
+        }
+    };
+
+    let accounts = account_values(
+        &mut conn,
+        &[today],
+        FillMethod::CarryForward,
+        false,
+        &settings.base_currency,
+    )
+    .await?;
+    let (mut assets, mut liabilities) = (Vec::new(), Vec::new());
+    for AccountValues {
+        account, values, ..
+    } in accounts
+    {
+        let Some((_, value, _)) = values.first() else {
+            continue;
+        };
+        match account.category {
+            AccountCategory::Liability => liabilities.push(*value),
+            AccountCategory::Asset => {
+                let counted = match params.basis {
+                    FireBasis::AllAssets => true,
+                    FireBasis::Investable => matches!(
+                        account.account_type,
+                        AccountType::Investment | AccountType::Pension | AccountType::Savings
+                    ),
+                };
+                if counted {
+                    assets.push(*value);
+                }
+            }
+        }
+    }
+    let assets = round_amount(sum_amounts(assets), units);
+    let liabilities = round_amount(sum_amounts(liabilities), units);
+    let starting_value = sum_amounts([assets, -liabilities]);
+    let fi_number = round_amount(
+        params.annual_expenses / (safe_withdrawal_rate / 100.0),
+        units,
+    );
+
+    // Compounded monthly at the rate that gives `real_growth` over a year.
+    let monthly_growth = (1.0 + real_growth / 100.0).powf(1.0 / 12.0) - 1.0;
+    let months_to_fi = if starting_value >= fi_number {
+        Some(0)
+    } else if monthly_contribution <= 0.0 && (monthly_growth <= 0.0 || starting_value <= 0.0) {
+        None
+    } else {
+        let mut value = starting_value;
+        (1..=MAX_FIRE_MONTHS).find(|_| {
+            value = value * (1.0 + monthly_growth) + monthly_contribution;
+            value >= fi_number
+        })
+    };
+
+    Ok(FireProjection {
+        fi_number,
+        starting_value,
+        assets,
+        liabilities,
+        progress_percent: (starting_value / fi_number * 100.0).clamp(0.0, 100.0),
+        status: match months_to_fi {
+            Some(0) => FireStatus::Reached,
+            Some(_) => FireStatus::Projected,
+            None => FireStatus::Never,
+        },
+        fi_date: months_to_fi.map(|months| dates::format(today + Months::new(months))),
+        months_to_fi,
+        safe_withdrawal_rate,
+        real_growth,
+        monthly_contribution,
+        basis: params.basis,
+    })
+}
+
+const DEFAULT_SIMULATION_PATHS: u32 = 1000;
+const MAX_SIMULATION_PATHS: u32 = 100_000;
+const MAX_SIMULATION_MONTHS: u32 = 600;
+
+/// The mean and standard deviation of monthly returns in `values`, one per
+/// month end, net of each month's `flows`. Months starting at or below zero
+/// have no return and are skipped.
+fn monthly_returns(values: &[Option<f64>], flows: &[f64]) -> Option<(f64, f64)> {
+    let returns: Vec<f64> = values
+        .windows(2)
+        .zip(flows)
+        .filter_map(|(pair, flow)| match pair {
+            [Some(before), Some(after)] if *before > 0.0 => Some((after - before - flow) / before),
+            _ => None,
+        })
+        .collect();
+    if returns.len() < 2 {
+        return None;
+    }
+    let count = returns.len() as f64;
+    let mean = returns.iter().sum::<f64>() / count;
+    let variance = returns
+        .iter()
+        .map(|value| (value - mean).powi(2))
+        .sum::<f64>()
+        / (count - 1.0);
+    Some((mean, variance.sqrt()))
+}
+
+/// Simulates `params.paths` months-long paths of net worth, or of one asset
+/// account, from today's value and reports the 10th to 90th percentiles at
+/// each month end. Returns are drawn from a normal distribution each month and
+/// the contribution added after; the paths run on a blocking thread.
+#[tauri::command]
+pub async fn run_monte_carlo(
+    pool: State<'_, SqlitePool>,
+    params: MonteCarloParams,
+) -> Result<MonteCarloProjection> {
+    if !(1..=MAX_SIMULATION_MONTHS).contains(&params.months) {
+        return Err(Error::validation(
+            "months",
+            format!("must be between 1 and {MAX_SIMULATION_MONTHS}"),
+        ));
+    }
+    let paths = params.paths.unwrap_or(DEFAULT_SIMULATION_PATHS);
+    if !(1..=MAX_SIMULATION_PATHS).contains(&paths) {
+        return Err(Error::validation(
+            "paths",
+            format!("must be between 1 and {MAX_SIMULATION_PATHS}"),
+        ));
+    }
+    if params
+        .annual_return
+        .is_some_and(|rate| !rate.is_finite() || rate <= -100.0)
+    {
+        return Err(Error::validation("annual_return", "must be more than -100"));
+    }
+    if params
+        .annual_volatility
+        .is_some_and(|volatility| !volatility.is_finite() || volatility < 0.0)
+    {
+        return Err(Error::validation(
+            "annual_volatility",
+            "must be zero or more",
+        ));
+    }
+    if params
+        .monthly_contribution
+        .is_some_and(|amount| !amount.is_finite())
+    {
+        return Err(Error::validation(
+            "monthly_contribution",
+            "must be a number",
+        ));
+    }
+
+    let mut conn = pool.acquire().await?;
+    let base_currency = load_settings(&mut *conn).await?.base_currency;
+    let accounts = match &params.account_id {
+        None => net_worth_accounts(&mut conn).await?,
+        Some(account_id) => {
+            let account = fetch_account(&mut *conn, account_id).await?;
+            if account.category == AccountCategory::Liability {
+                return Err(Error::validation(
+                    "account_id",
+                    format!(
+                        "'{}' is a liability, which has no returns to simulate",
+                        account.name
+                    ),
+                ));
+            }
+            vec![account]
+        }
+    };
+    let today = today_date();
+    let spans = value_accounts(
+        &mut conn,
+        accounts.clone(),
+        &[],
+        FillMethod::Linear,
+        false,
+        &base_currency,
+    )
+    .await?;
+    let Some(first) = spans.iter().filter_map(|account| account.held_from).min() else {
+        return Err(Error::NoData(
+            "there are no balances to simulate from".to_string(),
+        ));
+    };
+    let mut month_ends: Vec<NaiveDate> = month_ends(first, today)
+        .into_iter()
+        .filter(|date| *date < today)
+        .collect();
+    month_ends.push(today);
+    let valued = value_accounts(
+        &mut conn,
+        accounts.clone(),
+        &month_ends,
+        FillMethod::Linear,
+        false,
+        &base_currency,
+    )
+    .await?;
+    let values = totals_on(&valued, &month_ends, minor_units_for(&base_currency));
+    let start_value = values.last().copied().flatten().unwrap_or(0.0);
+
+    let flows = net_flows(&mut conn, &accounts, first, today, &base_currency).await?;
+    let flow_in = |start: NaiveDate, end: NaiveDate| {
+        let (start, end) = (dates::format(start), dates::format(end));
+        sum_amounts(
+            flows
+                .iter()
+                .filter(|(date, _)| *date > start && *date <= end)
+                .map(|(_, amount)| *amount),
+        )
+    };
+    let monthly_contribution = params.monthly_contribution.unwrap_or_else(|| {
+        round_amount(
+            flow_in(today - Months::new(12), today) / 12.0,
+            minor_units_for(&base_currency),
+        )
+    });
+
+    let estimated = params.annual_return.is_none() || params.annual_volatility.is_none();
+    let (annual_return, annual_volatility) = match (params.annual_return, params.annual_volatility)
+    {
+        (Some(annual_return), Some(annual_volatility)) => (annual_return, annual_volatility),
+        (annual_return, annual_volatility) => {
+            let month_flows: Vec<f64> = month_ends
+                .windows(2)
+                .map(|pair| flow_in(pair[0], pair[1]))
+                .collect();
+            let (mean, deviation) = monthly_returns(&values, &month_flows).ok_or_else(|| {
+                Error::NoData(
+                    "there are too few month ends to estimate returns from; give annual_return and annual_volatility"
+                        .to_string(),
+                )
+            })?;
+            (
+                annual_return.unwrap_or(((1.0 + mean).powi(12) - 1.0) * 100.0),
+                annual_volatility.unwrap_or(deviation * 12f64.sqrt() * 100.0),
+            )
+        }
+    };
+
+    let seed = params.seed.unwrap_or_else(|| {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos() as u64)
+    });
+    let run = Simulation {
+        start: start_value,
+        monthly_mean: (1.0 + annual_return / 100.0).powf(1.0 / 12.0) - 1.0,
+        monthly_volatility: annual_volatility / 100.0 / 12f64.sqrt(),
+        monthly_contribution,
+        months: params.months,
+        paths,
+        seed,
+    };
+    let bands = tauri::async_runtime::spawn_blocking(move || simulation::run(&run)).await?;
+
+    Ok(MonteCarloProjection {
+        start_date: dates::format(today),
+        start_value,
+        annual_return,
+        annual_volatility,
+        estimated,
+        monthly_contribution,
+        paths,
+        seed,
+        bands: bands
+            .into_iter()
+            .zip(1..)
+            .map(|([p10, p25, p50, p75, p90], month)| MonteCarloBand {
+                date: dates::format(today + Months::new(month)),
+                p10,
+                p25,
+                p50,
+                p75,
+                p90,
+            })
+            .collect(),
+    })
+}
+
+const DEFAULT_FORECAST_WINDOW_MONTHS: u32 = 24;
+
+/// The value of a trend `days` after the window starts, from the line fitted
+/// to the figures themselves or, for an exponential trend, to their logs.
+fn trend_value(method: TrendMethod, line: &LinearFit, days: f64) -> f64 {
+    match method {
+        TrendMethod::Linear => line.value_after(days),
+        TrendMethod::Exponential => line.value_after(days).exp(),
+    }
+}
+
+/// `method` fitted to `window`, with the line behind it, or `None` for an
+/// exponential trend through a figure at or below zero.
+fn fit_trend(method: TrendMethod, window: &[(NaiveDate, f64)]) -> Option<(ForecastFit, LinearFit)> {
+    let first = window.first()?.0;
+    let line = match method {
+        TrendMethod::Linear => linear_fit(window),
+        TrendMethod::Exponential => {
+            if window.iter().any(|(_, value)| *value <= 0.0) {
+                return None;
+            }
+            let logs: Vec<(NaiveDate, f64)> = window
+                .iter()
+                .map(|(date, value)| (*date, value.ln()))
+                .collect();
+            linear_fit(&logs)
+        }
+    };
+    let r_squared = r_squared(window.iter().map(|(date, actual)| {
+        let days = (*date - first).num_days() as f64;
+        (*actual, trend_value(method, &line, days))
+    }));
+    let monthly_rate = match method {
+        TrendMethod::Linear => line.slope * DAYS_PER_MONTH,
+        TrendMethod::Exponential => ((line.slope * DAYS_PER_MONTH).exp() - 1.0) * 100.0,
+    };
+    Some((
+        ForecastFit {
+            method,
+            intercept: trend_value(method, &line, 0.0),
+            monthly_rate,
+            r_squared,
+        },
+        line,
+    ))
+}
+
+/// Carries a trend fitted to month-end net worth over the trailing
+/// `window_months` (24 by default) on for `horizon_months`. A window reaching
+/// back before the first balance is shortened, and the notes say so.
+#[tauri::command]
+pub async fn forecast_net_worth(
+    pool: State<'_, SqlitePool>,
+    horizon_months: u32,
+    model: ForecastModel,
+    window_months: Option<u32>,
+) -> Result<NetWorthForecast> {
+    if !(1..=MAX_SIMULATION_MONTHS).contains(&horizon_months) {
+        return Err(Error::validation(
+            "horizon_months",
+            format!("must be between 1 and {MAX_SIMULATION_MONTHS}"),
+        ));
+    }
+    let requested_window_months = window_months.unwrap_or(DEFAULT_FORECAST_WINDOW_MONTHS);
+    if requested_window_months < 2 {
+        return Err(Error::validation("window_months", "must be at least 2"));
+    }
+
+    let mut conn = pool.acquire().await?;
+    let base_currency = load_settings(&mut *conn).await?.base_currency;
+    let today = today_date();
+    let ends: Vec<NaiveDate> = month_ends(today - Months::new(requested_window_months), today);
+    let values = net_worth_on(&mut conn, &ends, &base_currency).await?;
+    let window: Vec<(NaiveDate, f64)> = ends
+        .into_iter()
+        .zip(values)
+        .filter_map(|(date, value)| Some((date, value?)))
+        .collect();
+    let (Some(&(window_start, _)), Some(&(window_end, _))) = (window.first(), window.last()) else {
+        return Err(Error::NoData(
+            "there is no net worth to forecast from".to_string(),
+        ));
+    };
+    if window.len() < 3 {
+        return Err(Error::NoData(
+            "at least three month ends of net worth are needed for a trend".to_string(),
+        ));
+    }
+
+    let mut notes = Vec::new();
+    let months_covered = (window_end.year() - window_start.year()) * 12 + window_end.month() as i32
+        - window_start.month() as i32;
+    let window_months = (months_covered.max(0) as u32).min(requested_window_months);
+    if window_months < requested_window_months {
+        notes.push(format!(
+            "the history only covers {window_months} of the {requested_window_months} months asked for, so the window was shortened"
+        ));
+    }
+
+    let methods: &[TrendMethod] = match model {
+        ForecastModel::Linear => &[TrendMethod::Linear],
+        ForecastModel::Exponential => &[TrendMethod::Exponential],
+        ForecastModel::Auto => &[TrendMethod::Linear, TrendMethod::Exponential],
+    };
+    let mut fits = Vec::new();
+    for method in methods {
+        match fit_trend(*method, &window) {
+            Some(fit) => fits.push(fit),
+            None if model == ForecastModel::Auto => notes.push(
+                "an exponential trend was left out because net worth was not above zero throughout"
+                    .to_string(),
+            ),
+            None => {
+                return Err(Error::validation(
+                    "model",
+                    "an exponential trend needs net worth above zero throughout the window",
+                ))
+            }
+        }
+    }
+    let (fit, line) = fits
+        .iter()
+        .max_by(|(a, _), (b, _)| a.r_squared.total_cmp(&b.r_squared))
+        .cloned()
+        .expect("a linear trend always fits");
+    if model == ForecastModel::Auto && fits.len() > 1 {
+        notes.push(format!(
+            "picked the {} trend, which fits better by R²",
+            match fit.method {
+                TrendMethod::Linear => "linear",
+                TrendMethod::Exponential => "exponential",
+            }
+        ));
+    }
+
+    let units = minor_units_for(&base_currency);
+    let points = (1..=horizon_months)
+        .map(|month| {
+            let date = today + Months::new(month);
+            let days = (date - window_start).num_days() as f64;
+            ForecastPoint {
+                date: dates::format(date),
+                net_worth: round_amount(trend_value(fit.method, &line, days), units),
+            }
+        })
+        .collect();
+
+    Ok(NetWorthForecast {
+        requested_model: model,
+        fit,
+        candidates: fits.into_iter().map(|(fit, _)| fit).collect(),
+        window_start: dates::format(window_start),
+        window_end: dates::format(window_end),
+        requested_window_months,
+        window_months,
+        data_points: window.len() as u32,
+        notes,
+        points,
+    })
+}
+
+/// The accounts' values on each date they have one, summed by category and
+/// liquidity and rounded to `units`.
+fn liquidity_points(accounts: &[AccountValues], units: u8) -> Vec<LiquidityPoint> {
+    #[derive(Default)]
+    struct Sums {
+        // Liquid, semi and illiquid, for assets then liabilities.
+        sides: [[Vec<f64>; 3]; 2],
+        estimated: bool,
+    }
+    let mut sums: BTreeMap<NaiveDate, Sums> = BTreeMap::new();
+    for AccountValues {
+        account, values, ..
+    } in accounts
+    {
+        let side = match account.category {
+            AccountCategory::Asset => 0,
+            AccountCategory::Liability => 1,
+        };
+        let band = match account.liquidity {
+            Liquidity::Liquid => 0,
+            Liquidity::Semi => 1,
+            Liquidity::Illiquid => 2,
+        };
+        for &(date, value, estimated) in values {
+            let sums = sums.entry(date).or_default();
+            sums.sides[side][band].push(value);
+            sums.estimated |= estimated;
+        }
+    }
+
+    let totals = |bands: [Vec<f64>; 3]| {
+        let [liquid, semi, illiquid] = bands.map(|values| round_amount(sum_amounts(values), units));
+        LiquidityTotals {
+            liquid,
+            semi,
+            illiquid,
+            total: sum_amounts([liquid, semi, illiquid]),
+        }
+    };
+    sums.into_iter()
+        .map(|(date, sums)| {
+            let [assets, liabilities] = sums.sides.map(totals);
+            LiquidityPoint {
+                date: dates::format(date),
+                accessible_cash: sum_amounts([assets.liquid, -liabilities.liquid]),
+                assets,
+                liabilities,
+                is_estimated: sums.estimated,
+            }
+        })
+        .collect()
+}
+
+/// Assets and liabilities on `as_of` (default today) split by each account's
+/// liquidity, valued as in `get_allocation`, with the accounts behind them.
+#[tauri::command]
+pub async fn get_liquidity_breakdown(
+    pool: State<'_, SqlitePool>,
+    as_of: Option<String>,
+) -> Result<LiquidityBreakdown> {
+    let as_of = match as_of {
+        Some(date) => parse_date("as_of", &date)?,
+        None => today_date(),
+    };
+    let mut conn = pool.acquire().await?;
+    let base_currency = load_settings(&mut *conn).await?.base_currency;
+    let accounts = account_values(
+        &mut conn,
+        &[as_of],
+        FillMethod::CarryForward,
+        false,
+        &base_currency,
+    )
+    .await?;
+
+    let units = minor_units_for(&base_currency);
+    let totals = liquidity_points(&accounts, units)
+        .pop()
+        .unwrap_or_else(|| LiquidityPoint {
+            date: dates::format(as_of),
+            assets: LiquidityTotals::default(),
+            liabilities: LiquidityTotals::default(),
+            accessible_cash: 0.0,
+            is_estimated: false,
+        });
+    let mut listed: Vec<LiquidityAccount> = accounts
+        .into_iter()
+        .filter_map(
+            |AccountValues {
+                 account, values, ..
+             }| {
+                let &(_, amount, _) = values.first()?;
+                Some(LiquidityAccount {
+                    account_id: account.id,
+                    name: account.name,
+                    category: account.category,
+                    liquidity: account.liquidity,
+                    amount: round_amount(amount, units),
+                })
+            },
+        )
+        .collect();
+    listed.sort_by(|a, b| {
+        (a.category == AccountCategory::Liability)
+            .cmp(&(b.category == AccountCategory::Liability))
+            .then(b.amount.total_cmp(&a.amount))
+    });
+    Ok(LiquidityBreakdown {
+        totals,
+        accounts: listed,
+    })
+}
+
+/// `get_liquidity_breakdown` on each date of `get_net_worth_series`, monthly by
+/// default.
+#[tauri::command]
+pub async fn get_liquidity_series(
+    pool: State<'_, SqlitePool>,
+    start: String,
+    end: String,
+    granularity: Option<Granularity>,
+) -> Result<Vec<LiquidityPoint>> {
+    let (start, end) = check_range(&start, &end)?;
+    let mut conn = pool.acquire().await?;
+    let base_currency = load_settings(&mut *conn).await?.base_currency;
+    let accounts = account_values(
+        &mut conn,
+        &period_dates(start, end, granularity.unwrap_or(Granularity::Month)),
+        FillMethod::Linear,
+        false,
+        &base_currency,
+    )
+    .await?;
+    Ok(liquidity_points(&accounts, minor_units_for(&base_currency)))
+}
+
+/// Net worth on each date the accounts have values, rounded to `units`, with
+/// the share of what was held or owed, by value, that was estimated.
+fn estimated_totals(accounts: &[AccountValues], units: u8) -> BTreeMap<NaiveDate, (f64, f64)> {
+    let mut sums: BTreeMap<NaiveDate, (Vec<f64>, f64, f64)> = BTreeMap::new();
+    for AccountValues {
+        account, values, ..
+    } in accounts
+    {
+        for &(date, value, estimated) in values {
+            let (net, gross, guessed) = sums.entry(date).or_default();
+            net.push(match account.category {
+                AccountCategory::Asset => value,
+                AccountCategory::Liability => -value,
+            });
+            *gross += value.abs();
+            if estimated {
+                *guessed += value.abs();
+            }
+        }
+    }
+    sums.into_iter()
+        .map(|(date, (net, gross, guessed))| {
+            let share = if gross > 0.0 { guessed / gross } else { 0.0 };
+            (date, (round_amount(sum_amounts(net), units), share))
+        })
+        .collect()
+}
+
+/// The change between each pair of consecutive `dates` that both have a value.
+fn period_changes(
+    totals: &BTreeMap<NaiveDate, (f64, f64)>,
+    dates: &[NaiveDate],
+) -> Vec<PeriodChange> {
+    dates
+        .windows(2)
+        .filter_map(|pair| {
+            let &(from, _) = totals.get(&pair[0])?;
+            let &(to, estimated_share) = totals.get(&pair[1])?;
+            Some(PeriodChange {
+                start: dates::format(pair[0]),
+                end: dates::format(pair[1]),
+                start_net_worth: from,
+                end_net_worth: to,
+                change: sum_amounts([to, -from]),
+                change_percent: (from != 0.0).then(|| (to - from) / from.abs() * 100.0),
+                mostly_estimated: estimated_share > 0.5,
+            })
+        })
+        .collect()
+}
+
+/// The largest and smallest change, the later one winning a tie.
+fn extremes(changes: &[PeriodChange]) -> (Option<PeriodChange>, Option<PeriodChange>) {
+    let mut best: Option<&PeriodChange> = None;
+    let mut worst: Option<&PeriodChange> = None;
+    for change in changes {
+        if best.is_none_or(|best| change.change >= best.change) {
+            best = Some(change);
+        }
+        if worst.is_none_or(|worst| change.change <= worst.change) {
+            worst = Some(change);
+        }
+    }
+    (best.cloned(), worst.cloned())
+}
+
+fn direction(change: f64) -> StreakDirection {
+    if change > 0.0 {
+        StreakDirection::Up
+    } else if change < 0.0 {
+        StreakDirection::Down
+    } else {
+        StreakDirection::Flat
+    }
+}
+
+/// Every run of consecutive changes in the same direction, oldest first. A gap
+/// in the series ends a run.
+fn streaks(changes: &[PeriodChange]) -> Vec<Streak> {
+    let mut runs: Vec<Streak> = Vec::new();
+    for change in changes {
+        let direction = direction(change.change);
+        match runs.last_mut() {
+            Some(run) if run.direction == direction && run.end == change.start => {
+                run.end = change.end.clone();
+                run.periods += 1;
+            }
+            _ => runs.push(Streak {
+                direction,
+                start: change.start.clone(),
+                end: change.end.clone(),
+                periods: 1,
+            }),
+        }
+    }
+    runs
+}
+
+/// Best and worst periods and years, streaks and the spread of changes in net
+/// worth over `range`, from the series of `get_net_worth_series` at
+/// `granularity` (monthly by default). The range defaults to the first entry
+/// of a counted account through today. Ties go to the more recent period.
+#[tauri::command]
+pub async fn get_period_stats(
+    pool: State<'_, SqlitePool>,
+    granularity: Option<Granularity>,
+    range: Option<DateRange>,
+) -> Result<PeriodStats> {
+    let granularity = granularity.unwrap_or(Granularity::Month);
+    let range = range.unwrap_or_default();
+    let mut conn = pool.acquire().await?;
+    let start = match range.start {
+        Some(date) => parse_date("start", &date)?,
+        None => {
+            let first: Option<String> = sqlx::query_scalar(
+                "SELECT MIN(e.date) FROM balance_entries e
+                 JOIN accounts a ON a.id = e.account_id
+                 WHERE a.archived_at IS NULL AND a.exclude_from_net_worth = 0",
+            )
+            .fetch_one(&mut *conn)
+            .await?;
+            let first = first.ok_or_else(|| Error::NoData("no balances recorded".to_string()))?;
+            parse_date("start", &first)?
+        }
+    };
+    let end = match range.end {
+        Some(date) => parse_date("end", &date)?,
+        None => today_date(),
+    };
+    if end < start {
+        return Err(Error::validation("end", "must not be before start"));
+    }
+
+    let periods = period_dates(start, end, granularity);
+    // The start, each year end in between and the end.
+    let mut years = vec![start];
+    years.extend(
+        (start.year()..end.year())
+            .filter_map(|year| NaiveDate::from_ymd_opt(year, 12, 31))
+            .filter(|date| *date > start),
+    );
+    if years.last() != Some(&end) {
+        years.push(end);
+    }
+    let mut dates: Vec<NaiveDate> = periods.iter().chain(&years).copied().collect();
+    dates.sort();
+    dates.dedup();
+
+    let base_currency = load_settings(&mut *conn).await?.base_currency;
+    let accounts =
+        account_values(&mut conn, &dates, FillMethod::Linear, false, &base_currency).await?;
+    let totals = estimated_totals(&accounts, minor_units_for(&base_currency));
+
+    let changes = period_changes(&totals, &periods);
+    let (best_period, worst_period) = extremes(&changes);
+    let (best_year, worst_year) = extremes(&period_changes(&totals, &years));
+    let runs = streaks(&changes);
+    let mut longest_rise: Option<&Streak> = None;
+    for run in runs
+        .iter()
+        .filter(|run| run.direction == StreakDirection::Up)
+    {
+        if longest_rise.is_none_or(|longest| run.periods >= longest.periods) {
+            longest_rise = Some(run);
+        }
+    }
+
+    let units = minor_units_for(&base_currency);
+    let count = changes.len() as f64;
+    let mean = (!changes.is_empty())
+        .then(|| changes.iter().map(|change| change.change).sum::<f64>() / count);
+    let std_dev = mean.map(|mean| {
+        let variance = changes
+            .iter()
+            .map(|change| (change.change - mean).powi(2))
+            .sum::<f64>()
+            / count;
+        round_amount(variance.sqrt(), units)
+    });
+
+    Ok(PeriodStats {
+        granularity,
+        start: dates::format(start),
+        end: dates::format(end),
+        best_period,
+        worst_period,
+        best_year,
+        worst_year,
+        longest_rise: longest_rise.cloned(),
+        current_streak: runs.last().cloned(),
+        average_change: mean.map(|mean| round_amount(mean, units)),
+        change_std_dev: std_dev,
+        changes,
+    })
+}
+
+/// Drawdowns shallower than this percentage are left out of the list by default.
+const DEFAULT_DRAWDOWN_THRESHOLD: f64 = 5.0;
+
+/// A drawdown from `peak` to `trough`.
+fn drawdown(
+    peak: &NetWorthPoint,
+    trough: &NetWorthPoint,
+    recovery_date: Option<String>,
+) -> Drawdown {
+    let depth = sum_amounts([peak.net, -trough.net]);
+    Drawdown {
+        peak_date: peak.date.clone(),
+        peak: peak.net,
+        trough_date: trough.date.clone(),
+        trough: trough.net,
+        recovery_date,
+        depth,
+        depth_percent: (peak.net > 0.0).then(|| depth / peak.net * 100.0),
+    }
+}
+
+/// Every fall below a running high in `points`, each ending when net worth is
+/// back at or above that high or, unrecovered, at the last point.
+fn find_drawdowns(points: &[NetWorthPoint]) -> Vec<Drawdown> {
+    let mut drawdowns = Vec::new();
+    let Some(first) = points.first() else {
+        return drawdowns;
+    };
+    let mut peak = first;
+    let mut trough: Option<&NetWorthPoint> = None;
+    for point in &points[1..] {
+        if point.net >= peak.net {
+            if let Some(low) = trough.take() {
+                drawdowns.push(drawdown(peak, low, Some(point.date.clone())));
+            }
+            peak = point;
+        } else if trough.is_none_or(|low| point.net < low.net) {
+            trough = Some(point);
+        }
+    }
+    if let Some(low) = trough {
+        drawdowns.push(drawdown(peak, low, None));
+    }
+    drawdowns
+}
+
+/// The falls in net worth from its highs between `start` and `end`, from the
+/// series of `get_net_worth_series` at `granularity` (monthly by default): the
+/// deepest, how far below the range's high it ends, and every one at least
+/// `threshold_percent` deep (5 by default). A series that never falls has none,
+/// and one still below its high at `end` has no recovery date.
+#[tauri::command]
+pub async fn get_drawdowns(
+    pool: State<'_, SqlitePool>,
+    start: String,
+    end: String,
+    granularity: Option<Granularity>,
+    threshold_percent: Option<f64>,
+) -> Result<DrawdownAnalysis> {
+    let (start, end) = check_range(&start, &end)?;
+    let threshold_percent = threshold_percent.unwrap_or(DEFAULT_DRAWDOWN_THRESHOLD);
+    if !threshold_percent.is_finite() || threshold_percent < 0.0 {
+        return Err(Error::validation(
+            "threshold_percent",
+            "must be zero or a positive percentage",
+        ));
+    }
+    let mut conn = pool.acquire().await?;
+    let points = net_worth_points(
+        &mut conn,
+        start,
+        end,
+        granularity.unwrap_or(Granularity::Month),
+        &NetWorthSeriesOptions::default(),
+    )
+    .await?;
+
+    let all = find_drawdowns(&points);
+    let max_drawdown = all
+        .iter()
+        .max_by(|a, b| {
+            let percent = |drawdown: &Drawdown| drawdown.depth_percent.unwrap_or(f64::NEG_INFINITY);
+            percent(a)
+                .total_cmp(&percent(b))
+                .then(a.depth.total_cmp(&b.depth))
+        })
+        .cloned();
+    let current = points
+        .iter()
+        .reduce(|high, point| if point.net >= high.net { point } else { high })
+        .zip(points.last())
+        .map(|(high, last)| {
+            let depth = sum_amounts([high.net, -last.net]);
+            CurrentDrawdown {
+                high_date: high.date.clone(),
+                high: high.net,
+                value: last.net,
+                depth,
+                depth_percent: (high.net > 0.0).then(|| depth / high.net * 100.0),
+            }
+        });
+    let drawdowns = all
+        .into_iter()
+        .filter(|drawdown| {
+            drawdown
+                .depth_percent
+                .is_some_and(|percent| percent >= threshold_percent)
+        })
+        .collect();
+
+    Ok(DrawdownAnalysis {
+        start: dates::format(start),
+        end: dates::format(end),
+        max_drawdown,
+        current,
+        drawdowns,
+        threshold_percent,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::commands::accounts::create_account;
+    use crate::testing::{block_on, input, seed_household, TestDb};
+
+    #[test]
+    fn net_worth_counts_each_account_while_it_is_held() {
+        let db = TestDb::new();
+        let series = block_on(async {
+            seed_household(&db).await;
+            get_net_worth_series(
+                db.state(),
+                "2025-01-01".into(),
+                "2025-05-31".into(),
+                Granularity::Month,
+                None,
+            )
+            .await
+            .unwrap()
+        });
+        let totals: Vec<(&str, f64, f64, f64)> = series
+            .iter()
+            .map(|point| {
+                (
+                    point.date.as_str(),
+                    point.assets,
+                    point.liabilities,
+                    point.net,
+                )
+            })
+            .collect();
+        // Current interpolated to 1,600 by March; the brokerage counted from
+        // March at 0.8 to the dollar; half the mortgage; old savings until April.
+        assert_eq!(
+            totals,
+            [
+                ("2025-01-31", 1202.25 + 500.0, 99_000.0, -97_297.75),
+                ("2025-02-28", 1391.01 + 500.0, 98_066.67, -96_175.66),
+                (
+                    "2025-03-31",
+                    1600.0 + 1683.12 + 500.0,
+                    97_033.33,
+                    -93_250.21
+                ),
+                (
+                    "2025-04-30",
+                    1600.0 + 1838.96 + 500.0,
+                    96_033.33,
+                    -92_094.37
+                ),
+                ("2025-05-31", 1600.0 + 2000.0, 95_000.0, -91_400.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn net_worth_is_rounded_once_after_summing() {
+        let db = TestDb::new();
+        let series = block_on(async {
+            // Half of 10.01 is 5.005 each: 5.00 apiece rounded, 10.01 together.
+            for name in ["Joint current", "Joint savings"] {
+                create_account(
+                    db.state(),
+                    input(json!({
+                        "name": name,
+                        "account_type": "savings",
+                        "opening_balance": 10.01,
+                        "opened_at": "2025-01-01",
+                        "ownership_percent": 50,
+                    })),
+                )
+                .await
+                .unwrap();
+            }
+            get_net_worth_series(
+                db.state(),
+                "2025-01-31".into(),
+                "2025-01-31".into(),
+                Granularity::Month,
+                None,
+            )
+            .await
+            .unwrap()
+        });
+        assert_eq!(series.len(), 1);
+        assert_eq!(series[0].assets, 10.01);
+        assert_eq!(series[0].net, 10.01);
+    }
+
+    #[test]
+    fn breakdown_groups_add_up_to_the_series() {
+        let db = TestDb::new();
+        let (series, by_category, by_type) = block_on(async {
+            seed_household(&db).await;
+            create_account(
+                db.state(),
+                input(json!({
+                    "name": "Unused card",
+                    "account_type": "credit_card",
+                    "opening_balance": 0,
+                    "opened_at": "2025-01-01",
+                })),
+            )
+            .await
+            .unwrap();
+            let range = || ("2025-01-01".to_string(), "2025-05-31".to_string());
+            let (start, end) = range();
+            let series = get_net_worth_series(db.state(), start, end, Granularity::Month, None)
+                .await
+                .unwrap();
+            let mut breakdowns = Vec::new();
+            for group_by in [BreakdownGroupBy::Category, BreakdownGroupBy::AccountType] {
+                let (start, end) = range();
+                breakdowns.push(
+                    get_net_worth_breakdown(
+                        db.state(),
+                        start,
+                        end,
+                        Granularity::Month,
+                        group_by,
+                        None,
+                    )
+                    .await
+                    .unwrap(),
+                );
+            }
+            let by_type = breakdowns.pop().unwrap();
+            (series, breakdowns.pop().unwrap(), by_type)
+        });
+
+        assert_eq!(by_category.len(), series.len());
+        assert_eq!(by_type.len(), series.len());
+        let total = |point: &NetWorthBreakdownPoint, groups: &[&str]| {
+            sum_amounts(groups.iter().map(|group| point.groups[*group]))
+        };
+        for ((point, category), account_type) in series.iter().zip(&by_category).zip(&by_type) {
+            assert_eq!(category.date, point.date);
+            assert_eq!(account_type.date, point.date);
+            let categories: Vec<&str> = category.groups.keys().map(String::as_str).collect();
+            assert_eq!(categories, ["asset", "liability"]);
+            assert_eq!(total(category, &["asset"]), point.assets);
+            assert_eq!(total(category, &["liability"]), point.liabilities);
+            // The card is zero throughout, so it has no group.
+            let types: Vec<&str> = account_type.groups.keys().map(String::as_str).collect();
+            assert_eq!(types, ["investment", "mortgage", "savings"]);
+            assert_eq!(
+                total(account_type, &["investment", "savings"]),
+                point.assets
+            );
+            assert_eq!(total(account_type, &["mortgage"]), point.liabilities);
+        }
+    }
+}
@@ -0,0 +1,282 @@
+use std::collections::HashSet;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+use sqlx::{SqliteExecutor, SqlitePool};
+use tauri::{AppHandle, Manager, State};
+use tauri_plugin_opener::OpenerExt;
+use uuid::Uuid;
+
+use super::now;
+use crate::error::{Error, Result};
+use crate::models::{Attachment, OrphanedAttachments};
+
+/// Where attachment files live: `attachments/` in the app data directory.
+fn attachments_dir(app: &AppHandle) -> Result<PathBuf> {
+    Ok(app.path().app_data_dir()?.join("attachments"))
+}
+
+fn mime_type_for(extension: &str) -> &'static str {
+    match extension {
+        "pdf" => "application/pdf",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "heic" => "image/heic",
+        "csv" => "text/csv",
+        "txt" => "text/plain",
+        "html" | "htm" => "text/html",
+        _ => "application/octet-stream",
+    }
+}
+
+/// The lower-cased extension of `path`, if it is a plain alphanumeric one and so
+/// safe to reuse in a stored file name.
+fn extension_of(path: &Path) -> Option<String> {
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    (!extension.is_empty() && extension.chars().all(|c| c.is_ascii_alphanumeric()))
+        .then_some(extension)
+}
+
+async fn fetch_attachment<'e>(executor: impl SqliteExecutor<'e>, id: &str) -> Result<Attachment> {
+    sqlx::query_as::<_, Attachment>("SELECT * FROM attachments WHERE id = ?")
+        .bind(id)
+        .fetch_optional(executor)
+        .await?
+        .ok_or_else(|| Error::not_found("attachment", id))
+}
+
+/// Stored names of every attachment on the account's entries, for cleaning up
+/// after the account is deleted.
+pub(crate) async fn stored_names_for_account<'e>(
+    executor: impl SqliteExecutor<'e>,
+    account_id: &str,
+) -> Result<Vec<String>> {
+    let names = sqlx::query_scalar(
+        "SELECT DISTINCT a.stored_name FROM attachments a
+         JOIN balance_entries b ON b.id = a.balance_entry_id
+         WHERE b.account_id = ?",
+    )
+    .bind(account_id)
+    .fetch_all(executor)
+    .await?;
+    Ok(names)
+}
+
+pub(crate) async fn stored_names_for_entry<'e>(
+    executor: impl SqliteExecutor<'e>,
+    entry_id: &str,
+) -> Result<Vec<String>> {
+    let names =
+        sqlx::query_scalar("SELECT stored_name FROM attachments WHERE balance_entry_id = ?")
+            .bind(entry_id)
+            .fetch_all(executor)
+            .await?;
+    Ok(names)
+}
+
+/// Deletes the files among `stored_names` that no attachment row refers to any
+/// more. Files shared with other entries are kept.
+pub(crate) async fn remove_unreferenced_files(
+    app: &AppHandle,
+    pool: &SqlitePool,
+    stored_names: Vec<String>,
+) -> Result<()> {
+    let dir = attachments_dir(app)?;
+    for stored_name in stored_names {
+        let in_use: bool =
+            sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM attachments WHERE stored_name = ?)")
+                .bind(&stored_name)
+                .fetch_one(pool)
+                .await?;
+        if in_use {
+            continue;
+        }
+        match std::fs::remove_file(dir.join(&stored_name)) {
+            Err(error) if error.kind() != ErrorKind::NotFound => return Err(error.into()),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Copies the file at `source_path` into the attachments directory and links
+/// it to the entry. Identical files share one copy.
+#[tauri::command]
+pub async fn add_attachment(
+    app: AppHandle,
+    pool: State<'_, SqlitePool>,
+    entry_id: String,
+    source_path: String,
+) -> Result<Attachment> {
+    let entry_exists: bool =
+        sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM balance_entries WHERE id = ?)")
+            .bind(&entry_id)
+            .fetch_one(pool.inner())
+            .await?;
+    if !entry_exists {
+        return Err(Error::not_found("balance entry", entry_id));
+    }
+
+    let source = Path::new(&source_path);
+    let filename = source
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| Error::validation("source_path", "is not a file"))?
+        .to_string();
+    let contents = std::fs::read(source)?;
+
+    let extension = extension_of(source);
+    let hash = format!("{:x}", Sha256::digest(&contents));
+    let stored_name = match &extension {
+        Some(extension) => format!("{hash}.{extension}"),
+        None => hash,
+    };
+
+    let already_attached: bool = sqlx::query_scalar(
+        "SELECT EXISTS(SELECT 1 FROM attachments WHERE balance_entry_id = ? AND stored_name = ?)",
+    )
+    .bind(&entry_id)
+    .bind(&stored_name)
+    .fetch_one(pool.inner())
+    .await?;
+    if already_attached {
+        return Err(Error::Conflict(format!(
+            "'{filename}' is already attached to this entry"
+        )));
+    }
+
+    let dir = attachments_dir(&app)?;
+    std::fs::create_dir_all(&dir)?;
+    let stored_path = dir.join(&stored_name);
+    if !stored_path.exists() {
+        std::fs::write(&stored_path, &contents)?;
+    }
+
+    let attachment = Attachment {
+        id: Uuid::new_v4().to_string(),
+        balance_entry_id: entry_id,
+        filename,
+        stored_name,
+        mime_type: mime_type_for(extension.as_deref().unwrap_or_default()).to_string(),
+        size_bytes: contents.len() as i64,
+        added_at: now(),
+    };
+    sqlx::query(
+        "INSERT INTO attachments (id, balance_entry_id, filename, stored_name, mime_type, size_bytes, added_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(&attachment.id)
+    .bind(&attachment.balance_entry_id)
+    .bind(&attachment.filename)
+    .bind(&attachment.stored_name)
+    .bind(&attachment.mime_type)
+    .bind(attachment.size_bytes)
+    .bind(&attachment.added_at)
+    .execute(pool.inner())
+    .await?;
+
+    Ok(attachment)
+}
+
+#[tauri::command]
+pub async fn get_attachments(
+    pool: State<'_, SqlitePool>,
+    entry_id: String,
+) -> Result<Vec<Attachment>> {
+    let attachments = sqlx::query_as::<_, Attachment>(
+        "SELECT * FROM attachments WHERE balance_entry_id = ? ORDER BY added_at",
+    )
+    .bind(&entry_id)
+    .fetch_all(pool.inner())
+    .await?;
+    Ok(attachments)
+}
+
+/// Opens the attachment with the system's default app for its type.
+#[tauri::command]
+pub async fn open_attachment(
+    app: AppHandle,
+    pool: State<'_, SqlitePool>,
+    id: String,
+) -> Result<()> {
+    let attachment = fetch_attachment(pool.inner(), &id).await?;
+    let path = attachments_dir(&app)?.join(&attachment.stored_name);
+    std::fs::metadata(&path)?;
+    app.opener()
+        .open_path(path.to_string_lossy(), None::<&str>)?;
+    Ok(())
+}
+
+/// Unlinks the attachment, deleting its file unless another entry shares it.
+#[tauri::command]
+pub async fn remove_attachment(
+    app: AppHandle,
+    pool: State<'_, SqlitePool>,
+    id: String,
+) -> Result<()> {
+    let attachment = fetch_attachment(pool.inner(), &id).await?;
+    sqlx::query("DELETE FROM attachments WHERE id = ?")
+        .bind(&id)
+        .execute(pool.inner())
+        .await?;
+    remove_unreferenced_files(&app, pool.inner(), vec![attachment.stored_name]).await
+}
+
+/// Finds files no attachment refers to, such as those left behind when entries
+/// were deleted outside the app, and deletes them unless `dry_run` is set.
+#[tauri::command]
+pub async fn find_orphaned_attachments(
+    app: AppHandle,
+    pool: State<'_, SqlitePool>,
+    dry_run: bool,
+) -> Result<OrphanedAttachments> {
+    let attachments =
+        sqlx::query_as::<_, Attachment>("SELECT * FROM attachments ORDER BY added_at")
+            .fetch_all(pool.inner())
+            .await?;
+    let referenced: HashSet<&str> = attachments
+        .iter()
+        .map(|attachment| attachment.stored_name.as_str())
+        .collect();
+
+    let dir = attachments_dir(&app)?;
+    let mut orphaned_files = Vec::new();
+    match std::fs::read_dir(&dir) {
+        Ok(files) => {
+            for file in files {
+                let file = file?;
+                if !file.file_type()?.is_file() {
+                    continue;
+                }
+                let name = file.file_name().to_string_lossy().into_owned();
+                if !referenced.contains(name.as_str()) {
+                    orphaned_files.push(name);
+                }
+            }
+        }
+        Err(error) if error.kind() == ErrorKind::NotFound => {}
+        Err(error) => return Err(error.into()),
+    }
+    orphaned_files.sort();
+
+    if !dry_run {
+        for name in &orphaned_files {
+            std::fs::remove_file(dir.join(name))?;
+        }
+    }
+
+    let missing_files = attachments
+        .iter()
+        .filter(|attachment| !dir.join(&attachment.stored_name).is_file())
+        .cloned()
+        .collect();
+
+    Ok(OrphanedAttachments {
+        dry_run,
+        orphaned_files,
+        missing_files,
+    })
+}
@@ -0,0 +1,513 @@
+use std::collections::HashMap;
+
+use sqlx::SqlitePool;
+use tauri::State;
+
+use super::accounts::insert_account_row;
+use super::allocation_targets::{bucket_columns, fetch_allocation_targets};
+use super::balances::insert_balance_entry;
+use super::benchmarks::{encode_parameters, fetch_benchmarks};
+use super::inflation::fetch_cpi_rates;
+use super::milestones::{load_account_ids, write_account_ids};
+use super::now;
+use super::scenarios::{encode_operations, fetch_scenarios};
+use crate::error::{Error, Result};
+use crate::models::{
+    from_minor, minor_units_for, to_minor, Account, AccountFieldChange, AccountGroup, AccountTag,
+    Attachment, Backup, BalanceEntry, BalanceEntryChange, CustomAccountType, ExchangeRate, Flow,
+    ImportSummary, IncomeRecord, Milestone, PensionDetails, PropertyDetails, RecurringAdjustment,
+    Setting, Tag, BACKUP_VERSION,
+};
+
+/// Snapshots every table; the frontend saves the result as JSON.
+#[tauri::command]
+pub async fn export_backup(pool: State<'_, SqlitePool>) -> Result<Backup> {
+    let mut tx = pool.begin().await?;
+
+    let mut backup = Backup {
+        version: BACKUP_VERSION,
+        exported_at: now(),
+        custom_account_types: sqlx::query_as::<_, CustomAccountType>(
+            "SELECT * FROM custom_account_types ORDER BY slug",
+        )
+        .fetch_all(&mut *tx)
+        .await?,
+        groups: sqlx::query_as::<_, AccountGroup>(
+            "SELECT * FROM account_groups ORDER BY sort_order",
+        )
+        .fetch_all(&mut *tx)
+        .await?,
+        accounts: sqlx::query_as::<_, Account>("SELECT * FROM accounts ORDER BY sort_order")
+            .fetch_all(&mut *tx)
+            .await?,
+        balance_entries: sqlx::query_as::<_, BalanceEntry>(
+            "SELECT * FROM balance_entries ORDER BY account_id, date",
+        )
+        .fetch_all(&mut *tx)
+        .await?,
+        milestones: sqlx::query_as::<_, Milestone>(
+            // Templates before the instances that reference them.
+            "SELECT * FROM milestones ORDER BY parent_id IS NOT NULL, date",
+        )
+        .fetch_all(&mut *tx)
+        .await?,
+        tags: sqlx::query_as::<_, Tag>("SELECT * FROM tags ORDER BY name")
+            .fetch_all(&mut *tx)
+            .await?,
+        account_tags: sqlx::query_as::<_, AccountTag>("SELECT * FROM account_tags")
+            .fetch_all(&mut *tx)
+            .await?,
+        account_field_history: sqlx::query_as::<_, AccountFieldChange>(
+            "SELECT * FROM account_field_history ORDER BY changed_at",
+        )
+        .fetch_all(&mut *tx)
+        .await?,
+        pension_details: sqlx::query_as::<_, PensionDetails>("SELECT * FROM pension_details")
+            .fetch_all(&mut *tx)
+            .await?,
+        property_details: sqlx::query_as::<_, PropertyDetails>("SELECT * FROM property_details")
+            .fetch_all(&mut *tx)
+            .await?,
+        settings: sqlx::query_as::<_, Setting>("SELECT * FROM settings ORDER BY key")
+            .fetch_all(&mut *tx)
+            .await?,
+        exchange_rates: sqlx::query_as::<_, ExchangeRate>(
+            "SELECT * FROM exchange_rates ORDER BY date, from_currency, to_currency",
+        )
+        .fetch_all(&mut *tx)
+        .await?,
+        flows: sqlx::query_as::<_, Flow>("SELECT * FROM flows ORDER BY date, created_at")
+            .fetch_all(&mut *tx)
+            .await?,
+        recurring_adjustments: sqlx::query_as::<_, RecurringAdjustment>(
+            "SELECT * FROM recurring_adjustments ORDER BY start_date, created_at",
+        )
+        .fetch_all(&mut *tx)
+        .await?,
+        income_records: sqlx::query_as::<_, IncomeRecord>(
+            "SELECT * FROM income_records ORDER BY month",
+        )
+        .fetch_all(&mut *tx)
+        .await?,
+        benchmarks: fetch_benchmarks(&mut *tx).await?,
+        cpi_rates: fetch_cpi_rates(&mut *tx).await?,
+        scenarios: fetch_scenarios(&mut *tx).await?,
+        allocation_targets: fetch_allocation_targets(&mut *tx).await?,
+        attachments: sqlx::query_as::<_, Attachment>("SELECT * FROM attachments ORDER BY added_at")
+            .fetch_all(&mut *tx)
+            .await?,
+        balance_entry_history: sqlx::query_as::<_, BalanceEntryChange>(
+            "SELECT * FROM balance_entry_history ORDER BY changed_at, rowid",
+        )
+        .fetch_all(&mut *tx)
+        .await?,
+    };
+
+    load_account_ids(&mut tx, &mut backup.milestones).await?;
+
+    tx.commit().await?;
+    Ok(backup)
+}
+
+/// Replaces everything in the database with `backup`, in one transaction, so a
+/// backup that fails to load leaves the existing data untouched.
+#[tauri::command]
+pub async fn import_backup(pool: State<'_, SqlitePool>, backup: Backup) -> Result<ImportSummary> {
+    if backup.version > BACKUP_VERSION {
+        return Err(Error::validation(
+            "version",
+            format!(
+                "backup version {} is newer than this app supports ({BACKUP_VERSION})",
+                backup.version
+            ),
+        ));
+    }
+
+    let mut tx = pool.begin().await?;
+
+    for table in [
+        "account_field_history",
+        "pension_details",
+        "property_details",
+        "account_tags",
+        "attachments",
+        "balance_entries",
+        "flows",
+        "recurring_adjustments",
+        "milestone_accounts",
+        "milestones",
+        "allocation_targets",
+        "accounts",
+        // Deleting the entries above records history, so this is cleared after them.
+        "balance_entry_history",
+        "tags",
+        "account_groups",
+        "custom_account_types",
+        "exchange_rates",
+        "income_records",
+        "benchmarks",
+        "cpi_rates",
+        "scenarios",
+        "settings",
+        // Steps recorded against the replaced entries can't be undone afterwards.
+        "undo_log",
+        "net_worth_snapshots",
+    ] {
+        sqlx::query(&format!("DELETE FROM {table}"))
+            .execute(&mut *tx)
+            .await?;
+    }
+    // Nothing cached is valid for the new data, whatever the triggers made of it.
+    sqlx::query("UPDATE net_worth_snapshot_state SET valid_until = NULL")
+        .execute(&mut *tx)
+        .await?;
+
+    for custom_type in &backup.custom_account_types {
+        sqlx::query(
+            "INSERT INTO custom_account_types (slug, label, category, icon, created_at)
+             VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(&custom_type.slug)
+        .bind(&custom_type.label)
+        .bind(custom_type.category.as_str())
+        .bind(&custom_type.icon)
+        .bind(&custom_type.created_at)
+        .execute(&mut *tx)
+        .await?;
+    }
+    for group in &backup.groups {
+        sqlx::query(
+            "INSERT INTO account_groups (id, name, sort_order, created_at) VALUES (?, ?, ?, ?)",
+        )
+        .bind(&group.id)
+        .bind(&group.name)
+        .bind(group.sort_order)
+        .bind(&group.created_at)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    // Links point at other accounts, so they are restored once every account exists.
+    for account in &backup.accounts {
+        let unlinked = Account {
+            linked_account_id: None,
+            ..account.clone()
+        };
+        insert_account_row(&mut *tx, &unlinked).await?;
+    }
+    for account in &backup.accounts {
+        if let Some(linked) = &account.linked_account_id {
+            sqlx::query("UPDATE accounts SET linked_account_id = ? WHERE id = ?")
+                .bind(linked)
+                .bind(&account.id)
+                .execute(&mut *tx)
+                .await?;
+        }
+    }
+
+    for tag in &backup.tags {
+        sqlx::query("INSERT INTO tags (id, name, created_at) VALUES (?, ?, ?)")
+            .bind(&tag.id)
+            .bind(&tag.name)
+            .bind(&tag.created_at)
+            .execute(&mut *tx)
+            .await?;
+    }
+    for account_tag in &backup.account_tags {
+        sqlx::query("INSERT INTO account_tags (account_id, tag_id) VALUES (?, ?)")
+            .bind(&account_tag.account_id)
+            .bind(&account_tag.tag_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+    // Backups before version 2 carry only the float balance.
+    let currencies: HashMap<&str, &str> = backup
+        .accounts
+        .iter()
+        .map(|account| (account.id.as_str(), account.currency.as_str()))
+        .collect();
+    for entry in &backup.balance_entries {
+        if backup.version < 2 {
+            let currency = currencies
+                .get(entry.account_id.as_str())
+                .copied()
+                .unwrap_or_default();
+            let minor_units = minor_units_for(currency);
+            let balance_minor = to_minor(entry.balance, minor_units);
+            let entry = BalanceEntry {
+                balance: from_minor(balance_minor, minor_units),
+                balance_minor,
+                minor_units,
+                ..entry.clone()
+            };
+            insert_balance_entry(&mut *tx, &entry).await?;
+        } else {
+            insert_balance_entry(&mut *tx, entry).await?;
+        }
+    }
+    for rate in &backup.exchange_rates {
+        sqlx::query(
+            "INSERT INTO exchange_rates (id, date, from_currency, to_currency, rate, created_at)
+             VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&rate.id)
+        .bind(&rate.date)
+        .bind(&rate.from_currency)
+        .bind(&rate.to_currency)
+        .bind(rate.rate)
+        .bind(&rate.created_at)
+        .execute(&mut *tx)
+        .await?;
+    }
+    for flow in &backup.flows {
+        sqlx::query(
+            "INSERT INTO flows (id, account_id, date, amount, kind, notes, created_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&flow.id)
+        .bind(&flow.account_id)
+        .bind(&flow.date)
+        .bind(flow.amount)
+        .bind(flow.kind)
+        .bind(&flow.notes)
+        .bind(&flow.created_at)
+        .execute(&mut *tx)
+        .await?;
+    }
+    for record in &backup.income_records {
+        sqlx::query(
+            "INSERT INTO income_records (id, month, net_income, notes, created_at)
+             VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(&record.id)
+        .bind(&record.month)
+        .bind(record.net_income)
+        .bind(&record.notes)
+        .bind(&record.created_at)
+        .execute(&mut *tx)
+        .await?;
+    }
+    for benchmark in &backup.benchmarks {
+        sqlx::query(
+            "INSERT INTO benchmarks (id, name, kind, parameters, created_at) VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(&benchmark.id)
+        .bind(&benchmark.name)
+        .bind(benchmark.spec.kind())
+        .bind(encode_parameters(&benchmark.spec))
+        .bind(&benchmark.created_at)
+        .execute(&mut *tx)
+        .await?;
+    }
+    for rate in &backup.cpi_rates {
+        sqlx::query("INSERT INTO cpi_rates (month, index_value, created_at) VALUES (?, ?, ?)")
+            .bind(&rate.month)
+            .bind(rate.index_value)
+            .bind(&rate.created_at)
+            .execute(&mut *tx)
+            .await?;
+    }
+    for scenario in &backup.scenarios {
+        sqlx::query(
+            "INSERT INTO scenarios (id, name, description, horizon_months, operations, created_at, updated_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&scenario.id)
+        .bind(&scenario.name)
+        .bind(&scenario.description)
+        .bind(scenario.definition.horizon_months)
+        .bind(encode_operations(&scenario.definition))
+        .bind(&scenario.created_at)
+        .bind(&scenario.updated_at)
+        .execute(&mut *tx)
+        .await?;
+    }
+    for target in &backup.allocation_targets {
+        let (account_type, tag_id) = bucket_columns(&target.bucket);
+        sqlx::query(
+            "INSERT INTO allocation_targets (id, account_type, tag_id, target_percent, created_at, updated_at)
+             VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&target.id)
+        .bind(account_type)
+        .bind(tag_id)
+        .bind(target.target_percent)
+        .bind(&target.created_at)
+        .bind(&target.updated_at)
+        .execute(&mut *tx)
+        .await?;
+    }
+    for adjustment in &backup.recurring_adjustments {
+        sqlx::query(
+            "INSERT INTO recurring_adjustments (id, account_id, amount, day_of_month, start_date, end_date, kind, last_applied, created_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&adjustment.id)
+        .bind(&adjustment.account_id)
+        .bind(adjustment.amount)
+        .bind(adjustment.day_of_month)
+        .bind(&adjustment.start_date)
+        .bind(&adjustment.end_date)
+        .bind(adjustment.kind)
+        .bind(&adjustment.last_applied)
+        .bind(&adjustment.created_at)
+        .execute(&mut *tx)
+        .await?;
+    }
+    for attachment in &backup.attachments {
+        sqlx::query(
+            "INSERT INTO attachments (id, balance_entry_id, filename, stored_name, mime_type, size_bytes, added_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&attachment.id)
+        .bind(&attachment.balance_entry_id)
+        .bind(&attachment.filename)
+        .bind(&attachment.stored_name)
+        .bind(&attachment.mime_type)
+        .bind(attachment.size_bytes)
+        .bind(&attachment.added_at)
+        .execute(&mut *tx)
+        .await?;
+    }
+    for milestone in &backup.milestones {
+        sqlx::query(
+            "INSERT INTO milestones (id, date, label, created_at, target_amount, target_scope, achieved_at, recurrence, parent_id, materialised_until, category, icon, sort_order)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&milestone.id)
+        .bind(&milestone.date)
+        .bind(&milestone.label)
+        .bind(&milestone.created_at)
+        .bind(milestone.target_amount)
+        .bind(milestone.target_scope)
+        .bind(&milestone.achieved_at)
+        .bind(milestone.recurrence)
+        .bind(&milestone.parent_id)
+        .bind(&milestone.materialised_until)
+        .bind(milestone.category)
+        .bind(&milestone.icon)
+        .bind(milestone.sort_order)
+        .execute(&mut *tx)
+        .await?;
+    }
+    // Backups before version 3 link a milestone to at most one account.
+    for milestone in &backup.milestones {
+        let account_ids = if backup.version < 3 {
+            milestone.legacy_account_id.iter().cloned().collect()
+        } else {
+            milestone.account_ids.clone()
+        };
+        write_account_ids(&mut tx, &milestone.id, &account_ids).await?;
+    }
+    for change in &backup.account_field_history {
+        sqlx::query(
+            "INSERT INTO account_field_history (id, account_id, field, old_value, new_value, changed_at)
+             VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&change.id)
+        .bind(&change.account_id)
+        .bind(&change.field)
+        .bind(&change.old_value)
+        .bind(&change.new_value)
+        .bind(&change.changed_at)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    for details in &backup.pension_details {
+        sqlx::query(
+            "INSERT INTO pension_details (account_id, employer, employee_contribution_percent, employer_contribution_percent, annual_salary, retirement_date, updated_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&details.account_id)
+        .bind(&details.employer)
+        .bind(details.employee_contribution_percent)
+        .bind(details.employer_contribution_percent)
+        .bind(details.annual_salary)
+        .bind(&details.retirement_date)
+        .bind(&details.updated_at)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    for details in &backup.property_details {
+        sqlx::query(
+            "INSERT INTO property_details (account_id, purchase_price, purchase_date, deposit_paid, updated_at)
+             VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(&details.account_id)
+        .bind(details.purchase_price)
+        .bind(&details.purchase_date)
+        .bind(details.deposit_paid)
+        .bind(&details.updated_at)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    for change in &backup.balance_entry_history {
+        sqlx::query(
+            "INSERT INTO balance_entry_history (id, entry_id, account_id, operation, old_date, old_balance, old_notes, new_date, new_balance, new_notes, old_verified_at, new_verified_at, changed_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&change.id)
+        .bind(&change.entry_id)
+        .bind(&change.account_id)
+        .bind(&change.operation)
+        .bind(&change.old_date)
+        .bind(change.old_balance)
+        .bind(&change.old_notes)
+        .bind(&change.new_date)
+        .bind(change.new_balance)
+        .bind(&change.new_notes)
+        .bind(&change.old_verified_at)
+        .bind(&change.new_verified_at)
+        .bind(&change.changed_at)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    for setting in &backup.settings {
+        sqlx::query("INSERT INTO settings (key, value, updated_at) VALUES (?, ?, ?)")
+            .bind(&setting.key)
+            .bind(&setting.value)
+            .bind(&setting.updated_at)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    tx.commit().await?;
+    Ok(ImportSummary {
+        accounts: backup.accounts.len() as u32,
+        balance_entries: backup.balance_entries.len() as u32,
+        milestones: backup.milestones.len() as u32,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::net_worth_cache::refresh_snapshots;
+    use crate::testing::{block_on, seed_household, TestDb};
+
+    #[test]
+    fn importing_a_backup_starts_the_snapshots_over() {
+        let db = TestDb::new();
+        block_on(async {
+            seed_household(&db).await;
+            refresh_snapshots(db.state()).await.unwrap();
+            let backup = export_backup(db.state()).await.unwrap();
+            import_backup(db.state(), backup).await.unwrap();
+
+            let snapshots: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM net_worth_snapshots")
+                .fetch_one(db.pool())
+                .await
+                .unwrap();
+            let valid_until: Option<String> =
+                sqlx::query_scalar("SELECT valid_until FROM net_worth_snapshot_state")
+                    .fetch_one(db.pool())
+                    .await
+                    .unwrap();
+            assert_eq!((snapshots, valid_until), (0, None));
+        });
+    }
+}
@@ -0,0 +1,1646 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::{Days, NaiveDate};
+use sqlx::{SqliteConnection, SqliteExecutor, SqlitePool};
+use tauri::{AppHandle, State};
+use uuid::Uuid;
+
+use super::accounts::fetch_account;
+use super::attachments::{remove_unreferenced_files, stored_names_for_entry};
+use super::exchange_rates::{convert, currency_code};
+use super::settings::load_settings;
+use super::undo::record_undo;
+use super::{non_empty, non_empty_patch, normalize_date, now, parse_date, today_date};
+use crate::dates;
+use crate::error::{Error, Result};
+use crate::models::{
+    from_minor, minor_units_for, sum_amounts, to_minor, Account, AccountCategory, AccountSignFix,
+    BalanceEntry, BalanceEntryDeletion, BalanceEntryFilter, BalanceEntryPage,
+    BalanceEntryWithDelta, BatchEntryError, CreateBalanceInput, CreatedBalanceEntry, DateRepair,
+    DateRepairReport, DatetimeRepairReport, DuplicateEntryGroup, DuplicateMergeReport,
+    DuplicateMergeStrategy, EntrySource, MergedEntries, OutlierWarning, ReconciliationStatus,
+    Settings, SignNormalisationReport, SignPolicy, SignWarning, SortDirection, UndoOperation,
+    UpdateBalanceInput, UpsertBalanceResult,
+};
+use crate::series::Point;
+
+/// An entry for `account`, with `balance` rounded to its currency's minor units.
+pub(crate) fn new_balance_entry(
+    account: &Account,
+    date: String,
+    balance: f64,
+    notes: Option<String>,
+) -> BalanceEntry {
+    let minor_units = minor_units_for(&account.currency);
+    let balance_minor = to_minor(balance, minor_units);
+    BalanceEntry {
+        id: Uuid::new_v4().to_string(),
+        account_id: account.id.clone(),
+        date,
+        balance: from_minor(balance_minor, minor_units),
+        balance_minor,
+        minor_units,
+        notes,
+        created_at: now(),
+        original_currency: None,
+        original_amount: None,
+        source: EntrySource::Manual,
+        verified_at: None,
+    }
+}
+
+pub(crate) async fn insert_balance_entry<'e>(
+    executor: impl SqliteExecutor<'e>,
+    entry: &BalanceEntry,
+) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO balance_entries (id, account_id, date, balance, balance_minor, minor_units, notes, created_at, original_currency, original_amount, source, verified_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(&entry.id)
+    .bind(&entry.account_id)
+    .bind(&entry.date)
+    .bind(entry.balance)
+    .bind(entry.balance_minor)
+    .bind(entry.minor_units)
+    .bind(&entry.notes)
+    .bind(&entry.created_at)
+    .bind(&entry.original_currency)
+    .bind(entry.original_amount)
+    .bind(entry.source)
+    .bind(&entry.verified_at)
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+/// Inserts `entry`, or overwrites the balance and notes of the entry already on
+/// that date. An overwritten entry stays verified only if its amount is unchanged.
+pub(crate) async fn upsert_entry<'e>(
+    executor: impl SqliteExecutor<'e>,
+    entry: &BalanceEntry,
+) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO balance_entries (id, account_id, date, balance, balance_minor, minor_units, notes, created_at, original_currency, original_amount, source)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+         ON CONFLICT(account_id, date) DO UPDATE SET
+            verified_at = CASE
+                WHEN balance_minor IS excluded.balance_minor AND minor_units IS excluded.minor_units
+                THEN verified_at
+            END,
+            balance = excluded.balance,
+            balance_minor = excluded.balance_minor,
+            minor_units = excluded.minor_units,
+            notes = excluded.notes,
+            original_currency = excluded.original_currency,
+            original_amount = excluded.original_amount,
+            source = excluded.source",
+    )
+    .bind(&entry.id)
+    .bind(&entry.account_id)
+    .bind(&entry.date)
+    .bind(entry.balance)
+    .bind(entry.balance_minor)
+    .bind(entry.minor_units)
+    .bind(&entry.notes)
+    .bind(&entry.created_at)
+    .bind(&entry.original_currency)
+    .bind(entry.original_amount)
+    .bind(entry.source)
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+pub(crate) async fn fetch_latest_entry<'e>(
+    executor: impl SqliteExecutor<'e>,
+    account_id: &str,
+) -> Result<Option<BalanceEntry>> {
+    let entry = sqlx::query_as::<_, BalanceEntry>(
+        "SELECT * FROM balance_entries WHERE account_id = ? ORDER BY date DESC LIMIT 1",
+    )
+    .bind(account_id)
+    .fetch_optional(executor)
+    .await?;
+    Ok(entry)
+}
+
+pub(crate) async fn fetch_entries<'e>(
+    executor: impl SqliteExecutor<'e>,
+    account_id: &str,
+) -> Result<Vec<BalanceEntry>> {
+    let entries = sqlx::query_as::<_, BalanceEntry>(
+        "SELECT * FROM balance_entries WHERE account_id = ? ORDER BY date",
+    )
+    .bind(account_id)
+    .fetch_all(executor)
+    .await?;
+    Ok(entries)
+}
+
+/// An account's entries as dated points, oldest first, leaving out estimated
+/// ones when `exclude_estimated` is set.
+pub(crate) async fn fetch_points<'e>(
+    executor: impl SqliteExecutor<'e>,
+    account_id: &str,
+    exclude_estimated: bool,
+) -> Result<Vec<Point>> {
+    fetch_entries(executor, account_id)
+        .await?
+        .iter()
+        .filter(|entry| !exclude_estimated || entry.source != EntrySource::Estimated)
+        .map(|entry| {
+            Ok(Point {
+                date: parse_date("date", &entry.date)?,
+                balance: entry.balance,
+            })
+        })
+        .collect()
+}
+
+/// The furthest-ahead date a new entry may have, from the `max_future_days` setting.
+pub(crate) fn latest_allowed(settings: &Settings) -> String {
+    dates::format(today_date() + Days::new(settings.max_future_days.into()))
+}
+
+/// A warning when `balance` is both `outlier_percent` and `outlier_min_change`
+/// away from `previous`. Without a previous balance to compare against, as when
+/// it was zero, only the absolute change counts.
+fn detect_outlier(
+    previous: &BalanceEntry,
+    balance: f64,
+    settings: &Settings,
+) -> Option<OutlierWarning> {
+    let change = sum_amounts([balance, -previous.balance]);
+    let change_percent = (previous.balance != 0.0).then(|| change / previous.balance.abs() * 100.0);
+    let flagged = change.abs() >= settings.outlier_min_change
+        && change_percent.is_none_or(|percent| percent.abs() >= settings.outlier_percent);
+    flagged.then(|| OutlierWarning {
+        previous_date: previous.date.clone(),
+        previous_balance: previous.balance,
+        balance,
+        change,
+        change_percent,
+        sign_changed: previous.balance * balance < 0.0,
+    })
+}
+
+/// The account's latest stored entry before `entry`, other than `entry` itself.
+async fn previous_entry(
+    conn: &mut SqliteConnection,
+    entry: &BalanceEntry,
+) -> Result<Option<BalanceEntry>> {
+    let previous = sqlx::query_as::<_, BalanceEntry>(
+        "SELECT * FROM balance_entries WHERE account_id = ? AND date < ? AND id != ? ORDER BY date DESC LIMIT 1",
+    )
+    .bind(&entry.account_id)
+    .bind(&entry.date)
+    .bind(&entry.id)
+    .fetch_optional(conn)
+    .await?;
+    Ok(previous)
+}
+
+/// Compares `entry` with the latest entry before it. A flagged balance is
+/// returned as a warning, or refused when `confirm_outliers` is on and the
+/// caller hasn't confirmed it. An account's first entry is never flagged.
+async fn check_outlier(
+    conn: &mut SqliteConnection,
+    entry: &BalanceEntry,
+    settings: &Settings,
+    confirmed: bool,
+) -> Result<Option<OutlierWarning>> {
+    let previous = previous_entry(conn, entry).await?;
+    judge_outlier(previous.as_ref(), entry, settings, confirmed)
+}
+
+/// `check_outlier` against a `previous` entry already found.
+fn judge_outlier(
+    previous: Option<&BalanceEntry>,
+    entry: &BalanceEntry,
+    settings: &Settings,
+    confirmed: bool,
+) -> Result<Option<OutlierWarning>> {
+    let warning = previous.and_then(|previous| detect_outlier(previous, entry.balance, settings));
+    match warning {
+        Some(warning) if settings.confirm_outliers && !confirmed => {
+            Err(Error::UnconfirmedOutlier { warning })
+        }
+        warning => Ok(warning),
+    }
+}
+
+/// Applies the `sign_policy` setting to a new balance for `account`. Liabilities
+/// are stored as the positive amount owed, so a negative balance on one is
+/// flagged, and made positive under `normalise`. Assets can legitimately go
+/// negative, as an overdrawn current account does, and are left alone.
+pub(crate) fn check_sign(
+    account: &Account,
+    entry: &mut BalanceEntry,
+    settings: &Settings,
+) -> Option<SignWarning> {
+    if account.category != AccountCategory::Liability
+        || entry.balance >= 0.0
+        || settings.sign_policy == SignPolicy::Allow
+    {
+        return None;
+    }
+    let entered = entry.balance;
+    let normalised = settings.sign_policy == SignPolicy::Normalise;
+    if normalised {
+        flip_sign(entry);
+    }
+    Some(SignWarning {
+        entered,
+        normalised,
+    })
+}
+
+fn flip_sign(entry: &mut BalanceEntry) {
+    entry.balance_minor = -entry.balance_minor;
+    entry.balance = from_minor(entry.balance_minor, entry.minor_units);
+    entry.original_amount = entry.original_amount.map(|amount| -amount);
+}
+
+/// `date` normalised to `YYYY-MM-DD`, refused if it's after `latest_allowed`.
+fn check_date(date: &str, latest_allowed: &str) -> Result<String> {
+    let date = normalize_date("date", date)?;
+    if date.as_str() > latest_allowed {
+        return Err(Error::validation(
+            "date",
+            format!("{date} is too far in the future; the latest allowed is {latest_allowed}"),
+        ));
+    }
+    Ok(date)
+}
+
+/// Checks what can be checked without the database, returning the date
+/// normalised to `YYYY-MM-DD`.
+fn check_input(input: &CreateBalanceInput, latest_allowed: &str) -> Result<String> {
+    let date = check_date(&input.date, latest_allowed)?;
+    if !input.balance.is_finite() {
+        return Err(Error::validation("balance", "must be a number"));
+    }
+    if input
+        .original_amount
+        .is_some_and(|amount| !amount.is_finite())
+    {
+        return Err(Error::validation("original_amount", "must be a number"));
+    }
+    Ok(date)
+}
+
+/// The entry `input` describes once checked. A balance reported in another
+/// currency is converted into the account's at the rate nearest `date`, and
+/// the reported figure is kept alongside it.
+async fn entry_from_input(
+    conn: &mut SqliteConnection,
+    account: &Account,
+    date: String,
+    input: CreateBalanceInput,
+) -> Result<BalanceEntry> {
+    let notes = non_empty(input.notes);
+    let Some(currency) = input.original_currency.as_deref() else {
+        return Ok(new_balance_entry(account, date, input.balance, notes));
+    };
+    let currency = currency_code(currency)?;
+    let original_amount = input.original_amount.unwrap_or(input.balance);
+    if currency == account.currency {
+        return Ok(new_balance_entry(account, date, original_amount, notes));
+    }
+
+    let converted = convert(conn, original_amount, &currency, &account.currency, &date).await?;
+    let mut entry = new_balance_entry(account, date, converted, notes);
+    let minor_units = minor_units_for(&currency);
+    entry.original_amount = Some(from_minor(
+        to_minor(original_amount, minor_units),
+        minor_units,
+    ));
+    entry.original_currency = Some(currency);
+    Ok(entry)
+}
+
+pub(crate) async fn entry_exists(
+    conn: &mut SqliteConnection,
+    account_id: &str,
+    date: &str,
+) -> Result<bool> {
+    let exists = sqlx::query_scalar(
+        "SELECT EXISTS(SELECT 1 FROM balance_entries WHERE account_id = ? AND date = ?)",
+    )
+    .bind(account_id)
+    .bind(date)
+    .fetch_one(conn)
+    .await?;
+    Ok(exists)
+}
+
+async fn fetch_entry_on(
+    conn: &mut SqliteConnection,
+    account_id: &str,
+    date: &str,
+) -> Result<Option<BalanceEntry>> {
+    let entry = sqlx::query_as::<_, BalanceEntry>(
+        "SELECT * FROM balance_entries WHERE account_id = ? AND date = ?",
+    )
+    .bind(account_id)
+    .bind(date)
+    .fetch_optional(conn)
+    .await?;
+    Ok(entry)
+}
+
+async fn fetch_balance_entry(conn: &mut SqliteConnection, id: &str) -> Result<BalanceEntry> {
+    sqlx::query_as::<_, BalanceEntry>("SELECT * FROM balance_entries WHERE id = ?")
+        .bind(id)
+        .fetch_optional(conn)
+        .await?
+        .ok_or_else(|| Error::not_found("balance entry", id))
+}
+
+/// Records a balance. Fails if the account already has an entry on that date;
+/// `upsert_balance_entry` is the way to overwrite one.
+#[tauri::command]
+pub async fn create_balance_entry(
+    pool: State<'_, SqlitePool>,
+    input: CreateBalanceInput,
+) -> Result<CreatedBalanceEntry> {
+    let mut tx = pool.begin().await?;
+    let settings = load_settings(&mut *tx).await?;
+    let date = check_input(&input, &latest_allowed(&settings))?;
+    let confirmed = input.confirm_outlier;
+    let account = fetch_account(&mut *tx, &input.account_id).await?;
+    let mut entry = entry_from_input(&mut tx, &account, date, input).await?;
+    let sign = check_sign(&account, &mut entry, &settings);
+    if entry_exists(&mut tx, &entry.account_id, &entry.date).await? {
+        return Err(Error::Conflict(format!(
+            "the account already has a balance entry on {}",
+            entry.date
+        )));
+    }
+    let outlier = check_outlier(&mut tx, &entry, &settings, confirmed).await?;
+    insert_balance_entry(&mut *tx, &entry).await?;
+    record_undo(
+        &mut tx,
+        UndoOperation::Create,
+        format!("Add the {} balance of {}", entry.date, account.name),
+        &[],
+        std::slice::from_ref(&entry),
+    )
+    .await?;
+
+    tx.commit().await?;
+    Ok(CreatedBalanceEntry {
+        entry,
+        outlier,
+        sign,
+    })
+}
+
+/// Records a balance, or overwrites the balance and notes of the account's entry
+/// on that date. An overwritten entry keeps its id and `created_at`.
+#[tauri::command]
+pub async fn upsert_balance_entry(
+    pool: State<'_, SqlitePool>,
+    input: CreateBalanceInput,
+) -> Result<UpsertBalanceResult> {
+    let mut tx = pool.begin().await?;
+    let result = upsert_balance(&mut tx, input, EntrySource::Manual).await?;
+    tx.commit().await?;
+    Ok(result)
+}
+
+/// `upsert_balance_entry` within the caller's transaction, recording the entry
+/// as coming from `source`.
+pub(crate) async fn upsert_balance(
+    conn: &mut SqliteConnection,
+    input: CreateBalanceInput,
+    source: EntrySource,
+) -> Result<UpsertBalanceResult> {
+    let settings = load_settings(&mut *conn).await?;
+    let date = check_input(&input, &latest_allowed(&settings))?;
+    let confirmed = input.confirm_outlier;
+    let account = fetch_account(&mut *conn, &input.account_id).await?;
+    let mut entry = entry_from_input(conn, &account, date, input).await?;
+    entry.source = source;
+    let sign = check_sign(&account, &mut entry, &settings);
+    let previous = fetch_entry_on(conn, &entry.account_id, &entry.date).await?;
+    let created = previous.is_none();
+    let outlier = check_outlier(conn, &entry, &settings, confirmed).await?;
+    upsert_entry(&mut *conn, &entry).await?;
+    let entry = fetch_entry_on(conn, &entry.account_id, &entry.date)
+        .await?
+        .expect("the entry was just written");
+    let (operation, verb) = if created {
+        (UndoOperation::Create, "Add")
+    } else {
+        (UndoOperation::Update, "Change")
+    };
+    record_undo(
+        conn,
+        operation,
+        format!("{verb} the {} balance of {}", entry.date, account.name),
+        previous.as_slice(),
+        std::slice::from_ref(&entry),
+    )
+    .await?;
+
+    Ok(UpsertBalanceResult {
+        entry,
+        created,
+        outlier,
+        sign,
+    })
+}
+
+/// Records many balances at once. Every entry is checked first; if any fail,
+/// nothing is inserted and the error lists each bad entry by its index. Under
+/// the `normalise` sign policy, negative liability balances are saved positive.
+/// With `confirm_outliers` on, an unconfirmed outlier is one of the failures,
+/// judged against the entry before it whether stored or earlier in the batch.
+#[tauri::command]
+pub async fn create_balance_entries(
+    pool: State<'_, SqlitePool>,
+    entries: Vec<CreateBalanceInput>,
+) -> Result<Vec<BalanceEntry>> {
+    let mut tx = pool.begin().await?;
+    let settings = load_settings(&mut *tx).await?;
+    let latest_allowed = latest_allowed(&settings);
+    let accounts: HashMap<String, Account> = sqlx::query_as::<_, Account>("SELECT * FROM accounts")
+        .fetch_all(&mut *tx)
+        .await?
+        .into_iter()
+        .map(|account| (account.id.clone(), account))
+        .collect();
+
+    let mut errors = Vec::new();
+    let mut reject = |index: usize, field: &str, message: String| {
+        errors.push(BatchEntryError {
+            index,
+            field: field.to_string(),
+            message,
+        })
+    };
+    let mut first_index: HashMap<(String, String), usize> = HashMap::new();
+    let mut checked = Vec::with_capacity(entries.len());
+
+    for (index, input) in entries.into_iter().enumerate() {
+        let date = match check_input(&input, &latest_allowed) {
+            Ok(date) => date,
+            Err(Error::Validation { field, message }) => {
+                reject(index, field, message);
+                continue;
+            }
+            Err(error) => return Err(error),
+        };
+        let Some(account) = accounts.get(&input.account_id) else {
+            reject(
+                index,
+                "account_id",
+                format!("no account with id '{}'", input.account_id),
+            );
+            continue;
+        };
+        let confirmed = input.confirm_outlier;
+        let mut entry = match entry_from_input(&mut tx, account, date, input).await {
+            Ok(entry) => entry,
+            Err(Error::Validation { field, message }) => {
+                reject(index, field, message);
+                continue;
+            }
+            Err(error @ (Error::UnknownCurrency { .. } | Error::MissingExchangeRate { .. })) => {
+                reject(index, "original_currency", error.to_string());
+                continue;
+            }
+            Err(error) => return Err(error),
+        };
+        check_sign(account, &mut entry, &settings);
+        let key = (entry.account_id.clone(), entry.date.clone());
+        if let Some(first) = first_index.get(&key) {
+            reject(
+                index,
+                "date",
+                format!("entry {first} is for the same account and date"),
+            );
+            continue;
+        }
+        first_index.insert(key, index);
+        if entry_exists(&mut tx, &entry.account_id, &entry.date).await? {
+            reject(
+                index,
+                "date",
+                format!("the account already has a balance entry on {}", entry.date),
+            );
+            continue;
+        }
+        checked.push((index, confirmed, entry));
+    }
+
+    for (index, confirmed, entry) in &checked {
+        let stored = previous_entry(&mut tx, entry).await?;
+        let batched = checked
+            .iter()
+            .map(|(_, _, other)| other)
+            .filter(|other| other.account_id == entry.account_id && other.date < entry.date)
+            .max_by(|a, b| a.date.cmp(&b.date));
+        let previous = stored
+            .iter()
+            .chain(batched)
+            .max_by(|a, b| a.date.cmp(&b.date));
+        if let Err(error @ Error::UnconfirmedOutlier { .. }) =
+            judge_outlier(previous, entry, &settings, *confirmed)
+        {
+            reject(*index, "balance", error.to_string());
+        }
+    }
+    if !errors.is_empty() {
+        errors.sort_by_key(|error| error.index);
+        return Err(Error::InvalidBatch { errors });
+    }
+    let created: Vec<BalanceEntry> = checked.into_iter().map(|(_, _, entry)| entry).collect();
+    for entry in &created {
+        insert_balance_entry(&mut *tx, entry).await?;
+    }
+    record_undo(
+        &mut tx,
+        UndoOperation::Create,
+        format!("Add {} balances", created.len()),
+        &[],
+        &created,
+    )
+    .await?;
+
+    tx.commit().await?;
+    Ok(created)
+}
+
+pub(crate) const DEFAULT_PAGE_SIZE: u32 = 50;
+pub(crate) const MAX_PAGE_SIZE: u32 = 500;
+
+/// `pattern` for a `LIKE ... ESCAPE '\'` that matches it literally anywhere.
+pub(crate) fn contains_pattern(pattern: &str) -> String {
+    let mut escaped = String::with_capacity(pattern.len() + 2);
+    escaped.push('%');
+    for c in pattern.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped.push('%');
+    escaped
+}
+
+/// One page of the account's entries. The date filters and cursor become
+/// bounds on the `(account_id, date)` index, so a page costs about the same
+/// however long the history is.
+#[tauri::command]
+pub async fn list_balance_entries(
+    pool: State<'_, SqlitePool>,
+    account_id: String,
+    filter: BalanceEntryFilter,
+) -> Result<BalanceEntryPage> {
+    let limit = filter.limit.unwrap_or(DEFAULT_PAGE_SIZE);
+    if !(1..=MAX_PAGE_SIZE).contains(&limit) {
+        return Err(Error::validation(
+            "limit",
+            format!("must be between 1 and {MAX_PAGE_SIZE}"),
+        ));
+    }
+    let offset = filter.offset.unwrap_or(0);
+    let mut lower = filter
+        .start_date
+        .map(|date| parse_date("start_date", &date))
+        .transpose()?;
+    let mut upper = filter
+        .end_date
+        .map(|date| parse_date("end_date", &date))
+        .transpose()?;
+    let search = filter.search.as_deref().and_then(|search| {
+        let search = search.trim();
+        (!search.is_empty()).then(|| contains_pattern(search))
+    });
+
+    fetch_account(pool.inner(), &account_id).await?;
+    let count_query = "SELECT COUNT(*) FROM balance_entries
+         WHERE account_id = ?1 AND date >= ?2 AND date <= ?3
+           AND (?4 IS NULL OR notes LIKE ?4 ESCAPE '\\')
+           AND (?5 IS NULL OR (verified_at IS NOT NULL) = ?5)
+           AND (?6 IS NULL OR source = ?6)";
+    let bound = |date: Option<NaiveDate>, default: &'static str| {
+        date.map_or_else(|| default.to_string(), dates::format)
+    };
+    let total: u32 = sqlx::query_scalar(count_query)
+        .bind(&account_id)
+        .bind(bound(lower, ""))
+        .bind(bound(upper, "9999-12-31"))
+        .bind(&search)
+        .bind(filter.verified)
+        .bind(filter.source)
+        .fetch_one(pool.inner())
+        .await?;
+
+    // Dates are whole days, so "after" a date is from the next one on.
+    if let Some(after) = filter.after {
+        let after = parse_date("after", &after)?;
+        match filter.sort {
+            SortDirection::Asc => lower = lower.max(after.succ_opt()),
+            SortDirection::Desc => {
+                let before = after.pred_opt();
+                upper = upper.map_or(before, |upper| before.map(|before| upper.min(before)));
+            }
+        }
+    }
+    let direction = match filter.sort {
+        SortDirection::Asc => "ASC",
+        SortDirection::Desc => "DESC",
+    };
+    let entries = sqlx::query_as::<_, BalanceEntry>(&format!(
+        "SELECT * FROM balance_entries
+         WHERE account_id = ?1 AND date >= ?2 AND date <= ?3
+           AND (?4 IS NULL OR notes LIKE ?4 ESCAPE '\\')
+           AND (?5 IS NULL OR (verified_at IS NOT NULL) = ?5)
+           AND (?6 IS NULL OR source = ?6)
+         ORDER BY date {direction}
+         LIMIT ?7 OFFSET ?8"
+    ))
+    .bind(&account_id)
+    .bind(bound(lower, ""))
+    .bind(bound(upper, "9999-12-31"))
+    .bind(&search)
+    .bind(filter.verified)
+    .bind(filter.source)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(pool.inner())
+    .await?;
+
+    let next_cursor = (entries.len() == limit as usize)
+        .then(|| entries.last().map(|entry| entry.date.clone()))
+        .flatten();
+    Ok(BalanceEntryPage {
+        entries,
+        total,
+        limit,
+        offset,
+        next_cursor,
+    })
+}
+
+/// The latest entry of every active account, on or before `as_of` when given,
+/// keyed by account id, in one query. Accounts without one are left out.
+#[tauri::command]
+pub async fn get_latest_balances(
+    pool: State<'_, SqlitePool>,
+    as_of: Option<String>,
+) -> Result<HashMap<String, BalanceEntry>> {
+    let as_of = as_of
+        .map(|date| normalize_date("as_of", &date))
+        .transpose()?;
+    let entries = sqlx::query_as::<_, BalanceEntry>(
+        "SELECT b.* FROM accounts a
+         JOIN balance_entries b ON b.id = (
+            SELECT id FROM balance_entries
+            WHERE account_id = a.id AND (?1 IS NULL OR date <= ?1)
+            ORDER BY date DESC
+            LIMIT 1
+         )
+         WHERE a.is_active = 1 AND a.archived_at IS NULL",
+    )
+    .bind(as_of)
+    .fetch_all(pool.inner())
+    .await?;
+    Ok(entries
+        .into_iter()
+        .map(|entry| (entry.account_id.clone(), entry))
+        .collect())
+}
+
+/// The account's entries between `start` and `end` (both optional and
+/// inclusive), newest first, each with its change since the entry before it.
+/// That entry may fall before `start`.
+#[tauri::command]
+pub async fn get_balance_entries_with_delta(
+    pool: State<'_, SqlitePool>,
+    account_id: String,
+    start: Option<String>,
+    end: Option<String>,
+) -> Result<Vec<BalanceEntryWithDelta>> {
+    let start = start
+        .map(|date| normalize_date("start", &date))
+        .transpose()?;
+    let end = end.map(|date| normalize_date("end", &date)).transpose()?;
+    fetch_account(pool.inner(), &account_id).await?;
+    let entries = fetch_entries(pool.inner(), &account_id).await?;
+
+    let mut with_delta = Vec::new();
+    let mut previous: Option<&BalanceEntry> = None;
+    for entry in &entries {
+        let in_range = start.as_ref().is_none_or(|start| &entry.date >= start)
+            && end.as_ref().is_none_or(|end| &entry.date <= end);
+        if in_range {
+            with_delta.push(delta_from(entry, previous)?);
+        }
+        previous = Some(entry);
+    }
+    with_delta.reverse();
+    Ok(with_delta)
+}
+
+fn delta_from(
+    entry: &BalanceEntry,
+    previous: Option<&BalanceEntry>,
+) -> Result<BalanceEntryWithDelta> {
+    let mut with_delta = BalanceEntryWithDelta {
+        entry: entry.clone(),
+        previous_balance: None,
+        change_amount: None,
+        change_percent: None,
+        days_since_previous: None,
+    };
+    let Some(previous) = previous else {
+        return Ok(with_delta);
+    };
+
+    // Subtracting minor units keeps the change exact when both share a scale.
+    let change = if entry.minor_units == previous.minor_units {
+        from_minor(
+            entry.balance_minor - previous.balance_minor,
+            entry.minor_units,
+        )
+    } else {
+        entry.balance - previous.balance
+    };
+    with_delta.previous_balance = Some(previous.balance);
+    with_delta.change_amount = Some(change);
+    with_delta.change_percent =
+        (previous.balance != 0.0).then(|| change / previous.balance.abs() * 100.0);
+    with_delta.days_since_previous =
+        Some((parse_date("date", &entry.date)? - parse_date("date", &previous.date)?).num_days());
+    Ok(with_delta)
+}
+
+/// Changes an entry's date, balance or notes. A new date is held to the same
+/// limits as a new entry's. A new balance replaces any figure it was reported
+/// as in another currency, turns an estimate into a manual entry, and goes
+/// through the `sign_policy` and outlier settings as a new one would.
+#[tauri::command]
+pub async fn update_balance_entry(
+    pool: State<'_, SqlitePool>,
+    id: String,
+    input: UpdateBalanceInput,
+) -> Result<CreatedBalanceEntry> {
+    let mut tx = pool.begin().await?;
+    let settings = load_settings(&mut *tx).await?;
+    let previous = fetch_balance_entry(&mut tx, &id).await?;
+    let account = fetch_account(&mut *tx, &previous.account_id).await?;
+    let mut entry = previous.clone();
+    if let Some(date) = &input.date {
+        let date = check_date(date, &latest_allowed(&settings))?;
+        if date != entry.date && entry_exists(&mut tx, &entry.account_id, &date).await? {
+            return Err(Error::Conflict(format!(
+                "the account already has a balance entry on {date}"
+            )));
+        }
+        entry.date = date;
+    }
+    if let Some(balance) = input.balance {
+        if !balance.is_finite() {
+            return Err(Error::validation("balance", "must be a number"));
+        }
+        let updated = new_balance_entry(&account, entry.date.clone(), balance, None);
+        entry.balance = updated.balance;
+        entry.balance_minor = updated.balance_minor;
+        entry.minor_units = updated.minor_units;
+        entry.original_currency = None;
+        entry.original_amount = None;
+        if entry.source == EntrySource::Estimated {
+            entry.source = EntrySource::Manual;
+        }
+    }
+    let sign = input
+        .balance
+        .and_then(|_| check_sign(&account, &mut entry, &settings));
+    let outlier = if entry.date != previous.date || entry.balance_minor != previous.balance_minor {
+        check_outlier(&mut tx, &entry, &settings, input.confirm_outlier).await?
+    } else {
+        None
+    };
+    non_empty_patch(input.notes).apply(&mut entry.notes);
+
+    sqlx::query(
+        "UPDATE balance_entries
+         SET date = ?, balance = ?, balance_minor = ?, minor_units = ?, notes = ?, original_currency = ?, original_amount = ?, source = ?
+         WHERE id = ?",
+    )
+    .bind(&entry.date)
+    .bind(entry.balance)
+    .bind(entry.balance_minor)
+    .bind(entry.minor_units)
+    .bind(&entry.notes)
+    .bind(&entry.original_currency)
+    .bind(entry.original_amount)
+    .bind(entry.source)
+    .bind(&id)
+    .execute(&mut *tx)
+    .await?;
+    // Read back for the verification a new balance clears.
+    let entry = fetch_balance_entry(&mut tx, &id).await?;
+    record_undo(
+        &mut tx,
+        UndoOperation::Update,
+        format!("Change the {} balance of {}", entry.date, account.name),
+        std::slice::from_ref(&previous),
+        std::slice::from_ref(&entry),
+    )
+    .await?;
+
+    tx.commit().await?;
+    Ok(CreatedBalanceEntry {
+        entry,
+        outlier,
+        sign,
+    })
+}
+
+async fn set_verified_at(
+    pool: &SqlitePool,
+    id: &str,
+    verified_at: Option<String>,
+) -> Result<BalanceEntry> {
+    let mut conn = pool.acquire().await?;
+    let entry = fetch_balance_entry(&mut conn, id).await?;
+    sqlx::query("UPDATE balance_entries SET verified_at = ? WHERE id = ?")
+        .bind(&verified_at)
+        .bind(id)
+        .execute(&mut *conn)
+        .await?;
+    Ok(BalanceEntry {
+        verified_at,
+        ..entry
+    })
+}
+
+/// Marks the entry's balance as checked against a statement. Marking it again
+/// moves the time it was verified on.
+#[tauri::command]
+pub async fn mark_entry_verified(
+    pool: State<'_, SqlitePool>,
+    entry_id: String,
+) -> Result<BalanceEntry> {
+    set_verified_at(pool.inner(), &entry_id, Some(now())).await
+}
+
+#[tauri::command]
+pub async fn unmark_entry_verified(
+    pool: State<'_, SqlitePool>,
+    entry_id: String,
+) -> Result<BalanceEntry> {
+    set_verified_at(pool.inner(), &entry_id, None).await
+}
+
+/// The account's latest verified entry and how many entries since still need
+/// checking.
+#[tauri::command]
+pub async fn get_reconciliation_status(
+    pool: State<'_, SqlitePool>,
+    account_id: String,
+) -> Result<ReconciliationStatus> {
+    fetch_account(pool.inner(), &account_id).await?;
+    let latest: Option<(String, String)> = sqlx::query_as(
+        "SELECT date, verified_at FROM balance_entries
+         WHERE account_id = ? AND verified_at IS NOT NULL
+         ORDER BY date DESC
+         LIMIT 1",
+    )
+    .bind(&account_id)
+    .fetch_optional(pool.inner())
+    .await?;
+    let (last_verified_date, last_verified_at) = latest.unzip();
+    let unverified_since: u32 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM balance_entries
+         WHERE account_id = ? AND verified_at IS NULL AND date > ?",
+    )
+    .bind(&account_id)
+    .bind(last_verified_date.as_deref().unwrap_or(""))
+    .fetch_one(pool.inner())
+    .await?;
+    Ok(ReconciliationStatus {
+        account_id,
+        last_verified_date,
+        last_verified_at,
+        unverified_since,
+    })
+}
+
+/// Deletes the entry along with its attachments, removing attachment files no
+/// other entry shares.
+#[tauri::command]
+pub async fn delete_balance_entry(
+    app: AppHandle,
+    pool: State<'_, SqlitePool>,
+    id: String,
+) -> Result<()> {
+    let mut tx = pool.begin().await?;
+    let entry = fetch_balance_entry(&mut tx, &id).await?;
+    let account = fetch_account(&mut *tx, &entry.account_id).await?;
+    let stored_names = stored_names_for_entry(&mut *tx, &id).await?;
+    sqlx::query("DELETE FROM balance_entries WHERE id = ?")
+        .bind(&id)
+        .execute(&mut *tx)
+        .await?;
+    record_undo(
+        &mut tx,
+        UndoOperation::Delete,
+        format!("Delete the {} balance of {}", entry.date, account.name),
+        std::slice::from_ref(&entry),
+        &[],
+    )
+    .await?;
+
+    tx.commit().await?;
+    remove_unreferenced_files(&app, pool.inner(), stored_names).await
+}
+
+/// The account's entries from `start_date` to `end_date` inclusive, optionally
+/// only those from `source`. Unless `dry_run` is set they are all deleted in
+/// one go, along with their attachments.
+#[tauri::command]
+pub async fn delete_balance_entries(
+    app: AppHandle,
+    pool: State<'_, SqlitePool>,
+    account_id: String,
+    start_date: String,
+    end_date: String,
+    source: Option<EntrySource>,
+    dry_run: bool,
+) -> Result<BalanceEntryDeletion> {
+    let start_date = normalize_date("start_date", &start_date)?;
+    let end_date = normalize_date("end_date", &end_date)?;
+    if end_date < start_date {
+        return Err(Error::validation(
+            "end_date",
+            "must not be before start_date",
+        ));
+    }
+
+    let mut tx = pool.begin().await?;
+    let account = fetch_account(&mut *tx, &account_id).await?;
+    let entries = sqlx::query_as::<_, BalanceEntry>(
+        "SELECT * FROM balance_entries
+         WHERE account_id = ?1 AND date >= ?2 AND date <= ?3 AND (?4 IS NULL OR source = ?4)
+         ORDER BY date",
+    )
+    .bind(&account_id)
+    .bind(&start_date)
+    .bind(&end_date)
+    .bind(source)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let mut stored_names = Vec::new();
+    if !dry_run {
+        for entry in &entries {
+            stored_names.extend(stored_names_for_entry(&mut *tx, &entry.id).await?);
+            sqlx::query("DELETE FROM balance_entries WHERE id = ?")
+                .bind(&entry.id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        record_undo(
+            &mut tx,
+            UndoOperation::Delete,
+            format!(
+                "Delete {} balances of {} from {start_date} to {end_date}",
+                entries.len(),
+                account.name
+            ),
+            &entries,
+            &[],
+        )
+        .await?;
+    }
+    tx.commit().await?;
+    remove_unreferenced_files(&app, pool.inner(), stored_names).await?;
+
+    Ok(BalanceEntryDeletion {
+        dry_run,
+        count: entries.len(),
+        dates: entries.iter().map(|entry| entry.date.clone()).collect(),
+        entries,
+    })
+}
+
+/// Deletes every entry `fill_missing_months` generated for the account, and
+/// nothing else, returning what was removed.
+#[tauri::command]
+pub async fn delete_estimated_entries(
+    app: AppHandle,
+    pool: State<'_, SqlitePool>,
+    account_id: String,
+) -> Result<Vec<BalanceEntry>> {
+    let mut tx = pool.begin().await?;
+    let account = fetch_account(&mut *tx, &account_id).await?;
+    let entries = sqlx::query_as::<_, BalanceEntry>(
+        "SELECT * FROM balance_entries WHERE account_id = ? AND source = ? ORDER BY date",
+    )
+    .bind(&account_id)
+    .bind(EntrySource::Estimated)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let mut stored_names = Vec::new();
+    for entry in &entries {
+        stored_names.extend(stored_names_for_entry(&mut *tx, &entry.id).await?);
+        sqlx::query("DELETE FROM balance_entries WHERE id = ?")
+            .bind(&entry.id)
+            .execute(&mut *tx)
+            .await?;
+    }
+    record_undo(
+        &mut tx,
+        UndoOperation::Delete,
+        format!(
+            "Delete {} estimated balances of {}",
+            entries.len(),
+            account.name
+        ),
+        &entries,
+        &[],
+    )
+    .await?;
+
+    tx.commit().await?;
+    remove_unreferenced_files(&app, pool.inner(), stored_names).await?;
+    Ok(entries)
+}
+
+/// Finds entries whose date isn't stored as `YYYY-MM-DD`. Those that parse are
+/// rewritten in that shape unless `dry_run` is set, or another entry for the
+/// account already has the corrected date; the rest are only reported.
+#[tauri::command]
+pub async fn repair_entry_dates(
+    pool: State<'_, SqlitePool>,
+    dry_run: bool,
+) -> Result<DateRepairReport> {
+    let mut tx = pool.begin().await?;
+    let rows: Vec<(String, String, String)> = sqlx::query_as(
+        "SELECT id, account_id, date FROM balance_entries ORDER BY account_id, date",
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+    let mut taken: HashSet<(String, String)> = rows
+        .iter()
+        .map(|(_, account_id, date)| (account_id.clone(), date.clone()))
+        .collect();
+
+    let mut report = DateRepairReport {
+        dry_run,
+        scanned: rows.len() as u32,
+        repaired: Vec::new(),
+        conflicts: Vec::new(),
+        unparseable: Vec::new(),
+    };
+    for (id, account_id, date) in rows {
+        let repaired = dates::normalize(&date);
+        if repaired.as_deref() == Some(date.as_str()) {
+            continue;
+        }
+        let mut repair = DateRepair {
+            entry_id: id,
+            account_id,
+            date,
+            repaired_date: repaired,
+        };
+        let Some(repaired) = repair.repaired_date.clone() else {
+            report.unparseable.push(repair);
+            continue;
+        };
+        if !taken.insert((repair.account_id.clone(), repaired.clone())) {
+            report.conflicts.push(repair);
+            continue;
+        }
+        if !dry_run {
+            sqlx::query("UPDATE balance_entries SET date = ? WHERE id = ?")
+                .bind(&repaired)
+                .bind(&repair.entry_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        repair.repaired_date = Some(repaired);
+        report.repaired.push(repair);
+    }
+
+    if dry_run {
+        tx.rollback().await?;
+    } else {
+        tx.commit().await?;
+    }
+    Ok(report)
+}
+
+/// Finds liabilities with negative balances in their history, whether entered
+/// the other way round throughout or mixed with positive ones, and makes them
+/// positive unless `dry_run` is set. Totals treat owed amounts as positive
+/// either way; this keeps the stored history consistent with new entries.
+#[tauri::command]
+pub async fn normalise_balance_signs(
+    pool: State<'_, SqlitePool>,
+    dry_run: bool,
+) -> Result<SignNormalisationReport> {
+    let mut tx = pool.begin().await?;
+    let accounts = sqlx::query_as::<_, Account>(
+        "SELECT * FROM accounts WHERE category = 'liability' ORDER BY sort_order, name",
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let mut report = SignNormalisationReport {
+        dry_run,
+        accounts: Vec::new(),
+        fixed: 0,
+    };
+    let (mut before, mut after) = (Vec::new(), Vec::new());
+    for account in accounts {
+        let entries = fetch_entries(&mut *tx, &account.id).await?;
+        let (negative, positive): (Vec<BalanceEntry>, Vec<BalanceEntry>) =
+            entries.into_iter().partition(|entry| entry.balance < 0.0);
+        if negative.is_empty() {
+            continue;
+        }
+        for entry in &negative {
+            let mut fixed = entry.clone();
+            flip_sign(&mut fixed);
+            fixed.verified_at = None;
+            if !dry_run {
+                sqlx::query(
+                    "UPDATE balance_entries
+                     SET balance = ?, balance_minor = ?, original_amount = ?
+                     WHERE id = ?",
+                )
+                .bind(fixed.balance)
+                .bind(fixed.balance_minor)
+                .bind(fixed.original_amount)
+                .bind(&fixed.id)
+                .execute(&mut *tx)
+                .await?;
+            }
+            after.push(fixed);
+        }
+        report.fixed += negative.len();
+        report.accounts.push(AccountSignFix {
+            account_id: account.id,
+            name: account.name,
+            positive_entries: positive.len(),
+            dates: negative.iter().map(|entry| entry.date.clone()).collect(),
+        });
+        before.extend(negative);
+    }
+
+    if dry_run {
+        tx.rollback().await?;
+        return Ok(report);
+    }
+    record_undo(
+        &mut tx,
+        UndoOperation::Update,
+        format!("Make {} liability balances positive", report.fixed),
+        &before,
+        &after,
+    )
+    .await?;
+    tx.commit().await?;
+    Ok(report)
+}
+
+/// Groups entries whose stored dates are the same day once written as
+/// `YYYY-MM-DD`, which the unique index on the raw text can't catch.
+pub(crate) async fn find_duplicates<'e>(
+    executor: impl SqliteExecutor<'e>,
+    account_id: Option<&str>,
+) -> Result<Vec<DuplicateEntryGroup>> {
+    let entries = sqlx::query_as::<_, BalanceEntry>(
+        "SELECT * FROM balance_entries
+         WHERE ?1 IS NULL OR account_id = ?1
+         ORDER BY account_id, created_at",
+    )
+    .bind(account_id)
+    .fetch_all(executor)
+    .await?;
+
+    let mut groups: Vec<DuplicateEntryGroup> = Vec::new();
+    let mut index: HashMap<(String, String), usize> = HashMap::new();
+    for entry in entries {
+        let date = dates::normalize(&entry.date).unwrap_or_else(|| entry.date.clone());
+        let key = (entry.account_id.clone(), date.clone());
+        match index.get(&key) {
+            Some(&at) => groups[at].entries.push(entry),
+            None => {
+                index.insert(key, groups.len());
+                groups.push(DuplicateEntryGroup {
+                    account_id: entry.account_id.clone(),
+                    date,
+                    entries: vec![entry],
+                });
+            }
+        }
+    }
+    groups.retain(|group| group.entries.len() > 1);
+    groups.sort_by(|a, b| (&a.account_id, &a.date).cmp(&(&b.account_id, &b.date)));
+    Ok(groups)
+}
+
+/// Entries that fall on the same day under different date spellings, for one
+/// account or all of them.
+#[tauri::command]
+pub async fn find_duplicate_entries(
+    pool: State<'_, SqlitePool>,
+    account_id: Option<String>,
+) -> Result<Vec<DuplicateEntryGroup>> {
+    if let Some(account_id) = &account_id {
+        fetch_account(pool.inner(), account_id).await?;
+    }
+    find_duplicates(pool.inner(), account_id.as_deref()).await
+}
+
+/// How strongly `strategy` prefers to keep `entry`, higher first.
+fn merge_rank(entry: &BalanceEntry, strategy: DuplicateMergeStrategy) -> (i64, &str) {
+    let preference = match strategy {
+        DuplicateMergeStrategy::LatestCreated => 0,
+        DuplicateMergeStrategy::Largest => entry.balance_minor,
+        DuplicateMergeStrategy::ManualOverEstimated => match entry.source {
+            EntrySource::Manual => 2,
+            EntrySource::Import | EntrySource::Scheduled => 1,
+            EntrySource::Estimated => 0,
+        },
+    };
+    (preference, entry.created_at.as_str())
+}
+
+/// Keeps the entry of `group` that `strategy` prefers, under the group's
+/// `YYYY-MM-DD` date, and deletes the rest after moving their attachments onto
+/// it. The entries as they were and as they are go on `before` and `after`.
+async fn merge_group(
+    conn: &mut SqliteConnection,
+    group: DuplicateEntryGroup,
+    strategy: DuplicateMergeStrategy,
+    before: &mut Vec<BalanceEntry>,
+    after: &mut Vec<BalanceEntry>,
+) -> Result<MergedEntries> {
+    let mut entries = group.entries;
+    let keep = entries
+        .iter()
+        .enumerate()
+        .max_by(|(_, a), (_, b)| merge_rank(a, strategy).cmp(&merge_rank(b, strategy)))
+        .map(|(index, _)| index)
+        .expect("a group holds at least two entries");
+    before.extend(entries.iter().cloned());
+    let mut kept = entries.remove(keep);
+
+    for entry in &entries {
+        // A file already attached to the kept entry stays attached once.
+        sqlx::query(
+            "UPDATE OR IGNORE attachments SET balance_entry_id = ? WHERE balance_entry_id = ?",
+        )
+        .bind(&kept.id)
+        .bind(&entry.id)
+        .execute(&mut *conn)
+        .await?;
+        sqlx::query("DELETE FROM balance_entries WHERE id = ?")
+            .bind(&entry.id)
+            .execute(&mut *conn)
+            .await?;
+    }
+    if kept.date != group.date {
+        sqlx::query("UPDATE balance_entries SET date = ? WHERE id = ?")
+            .bind(&group.date)
+            .bind(&kept.id)
+            .execute(&mut *conn)
+            .await?;
+        kept.date = group.date.clone();
+    }
+
+    after.push(kept.clone());
+    Ok(MergedEntries {
+        account_id: group.account_id,
+        date: group.date,
+        kept,
+        deleted: entries,
+    })
+}
+
+/// Keeps one entry per duplicated day, chosen by `strategy`, and deletes the
+/// rest. The kept entry takes the `YYYY-MM-DD` date and the attachments of
+/// those deleted.
+#[tauri::command]
+pub async fn merge_duplicate_entries(
+    pool: State<'_, SqlitePool>,
+    strategy: DuplicateMergeStrategy,
+) -> Result<DuplicateMergeReport> {
+    let mut tx = pool.begin().await?;
+    let groups = find_duplicates(&mut *tx, None).await?;
+
+    let mut report = DuplicateMergeReport {
+        strategy,
+        merged: Vec::with_capacity(groups.len()),
+        deleted: 0,
+    };
+    let (mut before, mut after) = (Vec::new(), Vec::new());
+    for group in groups {
+        let merged = merge_group(&mut tx, group, strategy, &mut before, &mut after).await?;
+        report.deleted += merged.deleted.len();
+        report.merged.push(merged);
+    }
+
+    record_undo(
+        &mut tx,
+        UndoOperation::Delete,
+        format!("Merge {} duplicated balance dates", report.merged.len()),
+        &before,
+        &after,
+    )
+    .await?;
+    tx.commit().await?;
+    Ok(report)
+}
+
+/// Strips the time from entries dated like `2024-03-01T00:00:00` or
+/// `2024-03-01 00:00:00`, unless `dry_run` is set. An entry whose day the
+/// account already has an entry for is a collision: merged by `strategy` when
+/// one is given, otherwise only reported.
+#[tauri::command]
+pub async fn repair_datetime_dates(
+    pool: State<'_, SqlitePool>,
+    dry_run: bool,
+    strategy: Option<DuplicateMergeStrategy>,
+) -> Result<DatetimeRepairReport> {
+    let mut tx = pool.begin().await?;
+    let collisions: Vec<DuplicateEntryGroup> = find_duplicates(&mut *tx, None)
+        .await?
+        .into_iter()
+        .filter(|group| {
+            group
+                .entries
+                .iter()
+                .any(|entry| dates::has_time(&entry.date))
+        })
+        .collect();
+    let colliding: HashSet<&str> = collisions
+        .iter()
+        .flat_map(|group| group.entries.iter().map(|entry| entry.id.as_str()))
+        .collect();
+    let timestamped: Vec<BalanceEntry> = sqlx::query_as::<_, BalanceEntry>(
+        "SELECT * FROM balance_entries ORDER BY account_id, date",
+    )
+    .fetch_all(&mut *tx)
+    .await?
+    .into_iter()
+    .filter(|entry| dates::has_time(&entry.date) && !colliding.contains(entry.id.as_str()))
+    .collect();
+
+    let mut report = DatetimeRepairReport {
+        dry_run,
+        repaired: Vec::new(),
+        collisions: Vec::new(),
+        merged: Vec::new(),
+    };
+    let (mut before, mut after) = (Vec::new(), Vec::new());
+    for entry in timestamped {
+        let Some(repaired) = dates::normalize(&entry.date) else {
+            continue;
+        };
+        sqlx::query("UPDATE balance_entries SET date = ? WHERE id = ?")
+            .bind(&repaired)
+            .bind(&entry.id)
+            .execute(&mut *tx)
+            .await?;
+        report.repaired.push(DateRepair {
+            entry_id: entry.id.clone(),
+            account_id: entry.account_id.clone(),
+            date: entry.date.clone(),
+            repaired_date: Some(repaired.clone()),
+        });
+        before.push(entry.clone());
+        after.push(BalanceEntry {
+            date: repaired,
+            ..entry
+        });
+    }
+    match strategy {
+        Some(strategy) => {
+            for group in collisions {
+                let merged = merge_group(&mut tx, group, strategy, &mut before, &mut after).await?;
+                report.merged.push(merged);
+            }
+        }
+        None => report.collisions = collisions,
+    }
+
+    // The changes are made either way so a dry run reports exactly what would happen.
+    if dry_run {
+        tx.rollback().await?;
+        return Ok(report);
+    }
+    record_undo(
+        &mut tx,
+        UndoOperation::Update,
+        format!(
+            "Remove the time from {} balance dates",
+            report.repaired.len() + report.merged.len()
+        ),
+        &before,
+        &after,
+    )
+    .await?;
+    tx.commit().await?;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::commands::accounts::create_account;
+    use crate::testing::{block_on, input, seed_daily_entries, TestDb};
+
+    const HISTORY: u64 = 50_000;
+
+    /// Follows `next_cursor` from the first page to the last, returning the
+    /// dates in the order they came.
+    async fn page_through(db: &TestDb, account_id: &str, sort: SortDirection) -> Vec<NaiveDate> {
+        let mut dates = Vec::new();
+        let mut after = None;
+        loop {
+            let filter = BalanceEntryFilter {
+                sort,
+                limit: Some(MAX_PAGE_SIZE),
+                after,
+                ..Default::default()
+            };
+            let page = list_balance_entries(db.state(), account_id.to_string(), filter)
+                .await
+                .unwrap();
+            assert_eq!(page.total as u64, HISTORY);
+            for entry in &page.entries {
+                dates.push(parse_date("date", &entry.date).unwrap());
+            }
+            match page.next_cursor {
+                Some(cursor) => after = Some(cursor),
+                None => return dates,
+            }
+        }
+    }
+
+    #[test]
+    fn keyset_pages_cover_a_long_history_exactly_once() {
+        let db = TestDb::new();
+        block_on(async {
+            let last = parse_date("date", "2025-12-31").unwrap();
+            let mut accounts = Vec::new();
+            // Another account's entries on the same days must not leak in.
+            for (name, count) in [("Long", HISTORY), ("Other", 1000)] {
+                let account = create_account(
+                    db.state(),
+                    input(json!({"name": name, "account_type": "savings"})),
+                )
+                .await
+                .unwrap();
+                seed_daily_entries(&db, &account, last, count).await;
+                accounts.push(account);
+            }
+            let account_id = &accounts[0].id;
+
+            let started = Instant::now();
+            let filter = BalanceEntryFilter {
+                limit: Some(DEFAULT_PAGE_SIZE),
+                ..Default::default()
+            };
+            let first = list_balance_entries(db.state(), account_id.clone(), filter)
+                .await
+                .unwrap();
+            let elapsed = started.elapsed();
+            assert_eq!(first.entries[0].date, "2025-12-31");
+            assert!(elapsed < Duration::from_millis(100), "{elapsed:?}");
+
+            for sort in [SortDirection::Desc, SortDirection::Asc] {
+                let dates = page_through(&db, account_id, sort).await;
+                assert_eq!(dates.len() as u64, HISTORY, "{sort:?}");
+                // Consecutive days throughout: nothing repeated or skipped.
+                for pair in dates.windows(2) {
+                    let step = match sort {
+                        SortDirection::Asc => pair[1] - pair[0],
+                        SortDirection::Desc => pair[0] - pair[1],
+                    };
+                    assert_eq!(step.num_days(), 1, "{sort:?} {pair:?}");
+                }
+            }
+        });
+    }
+
+    #[test]
+    fn latest_balances_skip_inactive_and_empty_accounts() {
+        let db = TestDb::new();
+        block_on(async {
+            let last = parse_date("date", "2025-12-31").unwrap();
+            let mut accounts = Vec::new();
+            for name in ["Kept", "Closed", "Empty"] {
+                accounts.push(
+                    create_account(
+                        db.state(),
+                        input(json!({"name": name, "account_type": "savings"})),
+                    )
+                    .await
+                    .unwrap(),
+                );
+            }
+            seed_daily_entries(&db, &accounts[0], last, 10).await;
+            seed_daily_entries(&db, &accounts[1], last, 10).await;
+            sqlx::query("UPDATE accounts SET is_active = 0 WHERE id = ?")
+                .bind(&accounts[1].id)
+                .execute(db.pool())
+                .await
+                .unwrap();
+
+            let latest = get_latest_balances(db.state(), None).await.unwrap();
+            let ids: Vec<&String> = latest.keys().collect();
+            assert_eq!(ids, [&accounts[0].id]);
+            let entry = &latest[&accounts[0].id];
+            assert_eq!((entry.date.as_str(), entry.balance), ("2025-12-31", 10.0));
+
+            let earlier = get_latest_balances(db.state(), Some("2025-12-25".into()))
+                .await
+                .unwrap();
+            let entry = &earlier[&accounts[0].id];
+            assert_eq!((entry.date.as_str(), entry.balance), ("2025-12-25", 4.0));
+        });
+    }
+
+    /// Seeding a hundred thousand entries takes a while in a debug build; run
+    /// with `cargo test -- --ignored`.
+    #[test]
+    #[ignore]
+    fn latest_balances_of_a_hundred_long_histories_come_back_quickly() {
+        let db = TestDb::new();
+        block_on(async {
+            let last = parse_date("date", "2025-12-31").unwrap();
+            for index in 0..100 {
+                let account = create_account(
+                    db.state(),
+                    input(
+                        json!({"name": format!("Account {index:03}"), "account_type": "savings"}),
+                    ),
+                )
+                .await
+                .unwrap();
+                seed_daily_entries(&db, &account, last, 1000).await;
+            }
+
+            let started = Instant::now();
+            let latest = get_latest_balances(db.state(), Some("2025-06-30".into()))
+                .await
+                .unwrap();
+            let elapsed = started.elapsed();
+
+            assert_eq!(latest.len(), 100);
+            assert!(latest.values().all(|entry| entry.date == "2025-06-30"));
+            assert!(elapsed < Duration::from_millis(100), "{elapsed:?}");
+        });
+    }
+
+    #[test]
+    fn overwriting_an_amount_clears_its_verification() {
+        let db = TestDb::new();
+        let (renoted, changed) = block_on(async {
+            let account = create_account(
+                db.state(),
+                input(json!({"name": "Savings", "account_type": "savings", "opened_at": "2025-01-01"})),
+            )
+            .await
+            .unwrap();
+            let upsert = |balance: f64, notes: &str| {
+                let value = json!({
+                    "account_id": account.id,
+                    "date": "2025-03-31",
+                    "balance": balance,
+                    "notes": notes,
+                });
+                async {
+                    upsert_balance_entry(db.state(), input(value))
+                        .await
+                        .unwrap()
+                        .entry
+                }
+            };
+            let entry = upsert(1000.0, "statement").await;
+            mark_entry_verified(db.state(), entry.id).await.unwrap();
+            let renoted = upsert(1000.0, "checked against the statement").await;
+            let changed = upsert(1250.0, "checked against the statement").await;
+            (renoted, changed)
+        });
+        assert!(renoted.verified_at.is_some());
+        assert_eq!(changed.verified_at, None);
+    }
+}
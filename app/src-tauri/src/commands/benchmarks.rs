@@ -0,0 +1,283 @@
+use std::collections::HashMap;
+
+use sqlx::{SqliteExecutor, SqlitePool};
+use tauri::State;
+use uuid::Uuid;
+
+use super::analytics::{check_range, net_worth_on, net_worth_points};
+use super::settings::load_settings;
+use super::{normalize_date, now, parse_date};
+use crate::dates;
+use crate::error::{Error, Result};
+use crate::models::{
+    minor_units_for, round_amount, Benchmark, BenchmarkPoint, BenchmarkSpec, BenchmarkValue,
+    CreateBenchmarkInput, Granularity, NetWorthSeriesOptions, UpdateBenchmarkInput,
+};
+use crate::series::{interpolate_until, period_dates, Point, DAYS_PER_MONTH};
+
+#[derive(sqlx::FromRow)]
+struct BenchmarkRow {
+    id: String,
+    name: String,
+    kind: String,
+    parameters: String,
+    created_at: String,
+}
+
+impl TryFrom<BenchmarkRow> for Benchmark {
+    type Error = Error;
+
+    fn try_from(row: BenchmarkRow) -> Result<Self> {
+        let mut parameters: serde_json::Map<String, serde_json::Value> =
+            serde_json::from_str(&row.parameters)
+                .map_err(|error| Error::validation("parameters", error.to_string()))?;
+        parameters.insert("kind".to_string(), row.kind.into());
+        let spec = serde_json::from_value(parameters.into())
+            .map_err(|error| Error::validation("parameters", error.to_string()))?;
+        Ok(Benchmark {
+            id: row.id,
+            name: row.name,
+            spec,
+            created_at: row.created_at,
+        })
+    }
+}
+
+/// The spec's parameters as stored, without the kind, which has its own column.
+pub(crate) fn encode_parameters(spec: &BenchmarkSpec) -> String {
+    let mut value = serde_json::to_value(spec).expect("benchmark specs serialize");
+    if let Some(parameters) = value.as_object_mut() {
+        parameters.remove("kind");
+    }
+    value.to_string()
+}
+
+async fn fetch_benchmark<'e>(executor: impl SqliteExecutor<'e>, id: &str) -> Result<Benchmark> {
+    sqlx::query_as::<_, BenchmarkRow>("SELECT * FROM benchmarks WHERE id = ?")
+        .bind(id)
+        .fetch_optional(executor)
+        .await?
+        .ok_or_else(|| Error::not_found("benchmark", id))?
+        .try_into()
+}
+
+fn check_name(name: &str) -> Result<String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(Error::validation("name", "must not be empty"));
+    }
+    Ok(name.to_string())
+}
+
+/// `spec` with its points' dates normalized and sorted, provided the rate or
+/// values make sense.
+fn check_spec(spec: BenchmarkSpec) -> Result<BenchmarkSpec> {
+    match spec {
+        BenchmarkSpec::FixedRate { annual_rate } => {
+            if !annual_rate.is_finite() || annual_rate <= -100.0 {
+                return Err(Error::validation(
+                    "annual_rate",
+                    "must be a percentage above -100",
+                ));
+            }
+            Ok(BenchmarkSpec::FixedRate { annual_rate })
+        }
+        BenchmarkSpec::CustomPoints { points } => {
+            if points.is_empty() {
+                return Err(Error::validation("points", "must not be empty"));
+            }
+            let mut checked = Vec::with_capacity(points.len());
+            for point in points {
+                if !point.value.is_finite() || point.value <= 0.0 {
+                    return Err(Error::validation(
+                        "points",
+                        format!("the value on {} must be a positive number", point.date),
+                    ));
+                }
+                checked.push(BenchmarkValue {
+                    date: normalize_date("points", &point.date)?,
+                    value: point.value,
+                });
+            }
+            checked.sort_by(|a, b| a.date.cmp(&b.date));
+            if let Some(pair) = checked.windows(2).find(|pair| pair[0].date == pair[1].date) {
+                return Err(Error::validation(
+                    "points",
+                    format!("there is more than one value on {}", pair[0].date),
+                ));
+            }
+            Ok(BenchmarkSpec::CustomPoints { points: checked })
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn create_benchmark(
+    pool: State<'_, SqlitePool>,
+    input: CreateBenchmarkInput,
+) -> Result<Benchmark> {
+    let benchmark = Benchmark {
+        id: Uuid::new_v4().to_string(),
+        name: check_name(&input.name)?,
+        spec: check_spec(input.spec)?,
+        created_at: now(),
+    };
+
+    sqlx::query(
+        "INSERT INTO benchmarks (id, name, kind, parameters, created_at) VALUES (?, ?, ?, ?, ?)",
+    )
+    .bind(&benchmark.id)
+    .bind(&benchmark.name)
+    .bind(benchmark.spec.kind())
+    .bind(encode_parameters(&benchmark.spec))
+    .bind(&benchmark.created_at)
+    .execute(pool.inner())
+    .await?;
+
+    Ok(benchmark)
+}
+
+pub(crate) async fn fetch_benchmarks<'e>(
+    executor: impl SqliteExecutor<'e>,
+) -> Result<Vec<Benchmark>> {
+    sqlx::query_as::<_, BenchmarkRow>("SELECT * FROM benchmarks ORDER BY name, created_at")
+        .fetch_all(executor)
+        .await?
+        .into_iter()
+        .map(Benchmark::try_from)
+        .collect()
+}
+
+#[tauri::command]
+pub async fn get_benchmarks(pool: State<'_, SqlitePool>) -> Result<Vec<Benchmark>> {
+    fetch_benchmarks(pool.inner()).await
+}
+
+#[tauri::command]
+pub async fn update_benchmark(
+    pool: State<'_, SqlitePool>,
+    id: String,
+    input: UpdateBenchmarkInput,
+) -> Result<Benchmark> {
+    let mut benchmark = fetch_benchmark(pool.inner(), &id).await?;
+    if let Some(name) = input.name {
+        benchmark.name = check_name(&name)?;
+    }
+    if let Some(spec) = input.spec {
+        benchmark.spec = check_spec(spec)?;
+    }
+
+    sqlx::query("UPDATE benchmarks SET name = ?, kind = ?, parameters = ? WHERE id = ?")
+        .bind(&benchmark.name)
+        .bind(benchmark.spec.kind())
+        .bind(encode_parameters(&benchmark.spec))
+        .bind(&id)
+        .execute(pool.inner())
+        .await?;
+
+    Ok(benchmark)
+}
+
+#[tauri::command]
+pub async fn delete_benchmark(pool: State<'_, SqlitePool>, id: String) -> Result<()> {
+    let deleted = sqlx::query("DELETE FROM benchmarks WHERE id = ?")
+        .bind(&id)
+        .execute(pool.inner())
+        .await?;
+    if deleted.rows_affected() == 0 {
+        return Err(Error::not_found("benchmark", id));
+    }
+    Ok(())
+}
+
+/// The benchmark on each date of `get_net_worth_series` for the same range and
+/// granularity, starting from net worth on `start` and moving as the benchmark
+/// does: a fixed rate compounds monthly, pro rata between month ends, and
+/// custom points are interpolated linearly with no value outside their span.
+/// Net worth comes alongside for comparison.
+#[tauri::command]
+pub async fn get_benchmark_series(
+    pool: State<'_, SqlitePool>,
+    benchmark_id: String,
+    start: String,
+    end: String,
+    granularity: Granularity,
+) -> Result<Vec<BenchmarkPoint>> {
+    let (start, end) = check_range(&start, &end)?;
+    let mut conn = pool.acquire().await?;
+    let benchmark = fetch_benchmark(&mut *conn, &benchmark_id).await?;
+    let base_currency = load_settings(&mut *conn).await?.base_currency;
+    let units = minor_units_for(&base_currency);
+
+    let anchor = net_worth_on(&mut conn, &[start], &base_currency)
+        .await?
+        .pop()
+        .flatten()
+        .ok_or_else(|| {
+            Error::NoData(format!(
+                "there is no net worth on {} to start the benchmark from",
+                dates::format(start)
+            ))
+        })?;
+    let net_worth = net_worth_points(
+        &mut conn,
+        start,
+        end,
+        granularity,
+        &NetWorthSeriesOptions::default(),
+    )
+    .await?
+    .into_iter()
+    .map(|point| (point.date, point.net))
+    .collect::<HashMap<String, f64>>();
+
+    let period_ends = period_dates(start, end, granularity);
+    // How far the benchmark has moved since `start` on each date, as a multiple.
+    let growth: Vec<Option<f64>> = match benchmark.spec {
+        BenchmarkSpec::FixedRate { annual_rate } => {
+            let monthly = 1.0 + annual_rate / 100.0 / 12.0;
+            period_ends
+                .iter()
+                .map(|date| {
+                    let months = (*date - start).num_days() as f64 / DAYS_PER_MONTH;
+                    Some(monthly.powf(months))
+                })
+                .collect()
+        }
+        BenchmarkSpec::CustomPoints { points } => {
+            let points = points
+                .iter()
+                .map(|point| {
+                    Ok(Point {
+                        date: parse_date("points", &point.date)?,
+                        balance: point.value,
+                    })
+                })
+                .collect::<Result<Vec<Point>>>()?;
+            let last = points.last().map(|point| point.date);
+            let at_start = interpolate_until(&points, start, last).ok_or_else(|| {
+                Error::NoData(format!(
+                    "the benchmark's points don't cover {}",
+                    dates::format(start)
+                ))
+            })?;
+            period_ends
+                .iter()
+                .map(|date| interpolate_until(&points, *date, last).map(|value| value / at_start))
+                .collect()
+        }
+    };
+
+    Ok(period_ends
+        .into_iter()
+        .zip(growth)
+        .map(|(date, growth)| {
+            let date_text = dates::format(date);
+            BenchmarkPoint {
+                value: growth.map(|growth| round_amount(anchor * growth, units)),
+                net_worth: net_worth.get(&date_text).copied(),
+                date: date_text,
+            }
+        })
+        .collect())
+}
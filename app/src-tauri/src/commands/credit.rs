@@ -0,0 +1,167 @@
+use sqlx::SqlitePool;
+use tauri::State;
+
+use super::accounts::ACCOUNT_WITH_BALANCE_SELECT;
+use super::balances::fetch_points;
+use super::exchange_rates::convert_balance;
+use super::parse_date;
+use super::settings::load_settings;
+use crate::dates;
+use crate::error::Result;
+use crate::models::{
+    minor_units_for, round_amount, sum_amounts, AccountWithBalance, CardUtilisation,
+    CardWithoutLimit, CreditUtilisationReport, UtilisationPoint,
+};
+use crate::series::{interpolate_until, month_ends, Point};
+
+/// Utilisation of every open credit card, individually and combined. Cards
+/// without a limit are listed separately and left out of the totals.
+#[tauri::command]
+pub async fn get_credit_utilisation(
+    pool: State<'_, SqlitePool>,
+) -> Result<CreditUtilisationReport> {
+    let sql = format!(
+        "{ACCOUNT_WITH_BALANCE_SELECT}
+         WHERE a.account_type = 'credit_card' AND a.is_active = 1 AND a.archived_at IS NULL
+         ORDER BY a.sort_order, a.name"
+    );
+    let accounts = sqlx::query_as::<_, AccountWithBalance>(&sql)
+        .fetch_all(pool.inner())
+        .await?;
+
+    let base_currency = load_settings(pool.inner()).await?.base_currency;
+    let mut cards = Vec::new();
+    let mut cards_without_limit = Vec::new();
+    let mut history = Vec::new();
+    // The totals add cards in the base currency, whatever each is held in.
+    let (mut balances, mut limits) = (Vec::new(), Vec::new());
+    for card in accounts {
+        let balance = card.current_balance.abs();
+        let Some(credit_limit) = card.account.credit_limit else {
+            cards_without_limit.push(CardWithoutLimit {
+                account_id: card.account.id,
+                name: card.account.name,
+                balance,
+                balance_date: card.balance_date,
+            });
+            continue;
+        };
+
+        let points: Vec<Point> = fetch_points(pool.inner(), &card.account.id, false)
+            .await?
+            .into_iter()
+            .map(|p| Point {
+                balance: p.balance.abs(),
+                ..p
+            })
+            .collect();
+        let closed_at = card
+            .account
+            .closed_at
+            .as_deref()
+            .map(|date| parse_date("closed_at", date))
+            .transpose()?;
+        history.push((credit_limit, closed_at, points));
+        balances.push(convert_balance(pool.inner(), &card, balance, &base_currency).await?);
+        limits.push(convert_balance(pool.inner(), &card, credit_limit, &base_currency).await?);
+
+        cards.push(CardUtilisation {
+            account_id: card.account.id,
+            name: card.account.name,
+            balance,
+            balance_date: card.balance_date,
+            credit_limit,
+            utilisation_percent: balance / credit_limit * 100.0,
+        });
+    }
+
+    let units = minor_units_for(&base_currency);
+    let total_balance = round_amount(sum_amounts(balances), units);
+    let total_limit = round_amount(sum_amounts(limits), units);
+    let utilisation_percent = (total_limit > 0.0).then(|| total_balance / total_limit * 100.0);
+
+    let mut series = Vec::new();
+    let start = history
+        .iter()
+        .filter_map(|(_, _, points)| points.first().map(|p| p.date))
+        .min();
+    let end = history
+        .iter()
+        .filter_map(|(_, _, points)| points.last().map(|p| p.date))
+        .max();
+    if let (Some(start), Some(end)) = (start, end) {
+        for date in month_ends(start, end) {
+            let mut balance = 0.0;
+            let mut credit_limit = 0.0;
+            for (limit, closed_at, points) in &history {
+                if let Some(owed) = interpolate_until(points, date, *closed_at) {
+                    balance += owed;
+                    credit_limit += limit;
+                }
+            }
+            if credit_limit > 0.0 {
+                series.push(UtilisationPoint {
+                    date: dates::format(date),
+                    balance,
+                    credit_limit,
+                    utilisation_percent: balance / credit_limit * 100.0,
+                });
+            }
+        }
+    }
+
+    Ok(CreditUtilisationReport {
+        cards,
+        cards_without_limit,
+        total_balance,
+        total_limit,
+        utilisation_percent,
+        series,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::commands::accounts::create_account;
+    use crate::commands::exchange_rates::set_exchange_rate;
+    use crate::testing::{block_on, input, TestDb};
+
+    #[test]
+    fn totals_convert_each_card_to_the_base_currency() {
+        let db = TestDb::new();
+        let report = block_on(async {
+            set_exchange_rate(
+                db.state(),
+                "2025-01-01".into(),
+                "USD".into(),
+                "GBP".into(),
+                0.8,
+            )
+            .await
+            .unwrap();
+            for (name, currency) in [("Card", "GBP"), ("Dollar card", "USD")] {
+                create_account(
+                    db.state(),
+                    input(json!({
+                        "name": name,
+                        "account_type": "credit_card",
+                        "currency": currency,
+                        "credit_limit": 1000,
+                        "opening_balance": 500,
+                        "opened_at": "2025-01-01",
+                    })),
+                )
+                .await
+                .unwrap();
+            }
+            get_credit_utilisation(db.state()).await.unwrap()
+        });
+        assert_eq!(report.cards.len(), 2);
+        assert_eq!(report.total_balance, 500.0 + 400.0);
+        assert_eq!(report.total_limit, 1000.0 + 800.0);
+        assert_eq!(report.utilisation_percent, Some(50.0));
+    }
+}
@@ -0,0 +1,17 @@
+use super::exchange_rates::currency_code;
+use crate::error::Result;
+use crate::models::{find_currency, Currency, CurrencyFormat, CURRENCIES};
+
+/// Every currency an account can be held in, sorted by code.
+#[tauri::command]
+pub fn get_supported_currencies() -> Vec<Currency> {
+    CURRENCIES.to_vec()
+}
+
+/// How to show amounts in `currency`: its symbol, decimal places and separators.
+#[tauri::command]
+pub fn get_currency_format(currency: String) -> Result<CurrencyFormat> {
+    let code = currency_code(&currency)?;
+    let currency = find_currency(&code).expect("checked by currency_code");
+    Ok(currency.format())
+}
@@ -0,0 +1,100 @@
+use std::collections::BTreeMap;
+
+use chrono::Months;
+use sqlx::SqlitePool;
+use tauri::State;
+
+use super::analytics::{value_accounts, AccountValues};
+use super::settings::load_settings;
+use super::{parse_date, today_date};
+use crate::dates;
+use crate::error::Result;
+use crate::models::{
+    minor_units_for, round_amount, sum_amounts, Account, EmergencyCoverage, EmergencyCoveragePoint,
+    EmergencyCoverageStatus, FillMethod,
+};
+use crate::series::month_ends;
+
+/// How far back the coverage series reaches.
+const COVERAGE_HISTORY_MONTHS: u32 = 24;
+
+/// The asset accounts marked as emergency funds on `as_of` (default today),
+/// valued from their latest entry on or before each date as in
+/// `get_allocation`, against the `monthly_expenses` setting. Whether or not
+/// they count towards net worth, archived ones are left out.
+#[tauri::command]
+pub async fn get_emergency_coverage(
+    pool: State<'_, SqlitePool>,
+    as_of: Option<String>,
+) -> Result<EmergencyCoverage> {
+    let as_of = match as_of {
+        Some(date) => parse_date("as_of", &date)?,
+        None => today_date(),
+    };
+    let mut conn = pool.acquire().await?;
+    let settings = load_settings(&mut *conn).await?;
+    let accounts = sqlx::query_as::<_, Account>(
+        "SELECT * FROM accounts
+         WHERE is_emergency_fund = 1 AND category = 'asset' AND archived_at IS NULL
+         ORDER BY sort_order, name",
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    let account_ids = accounts.iter().map(|account| account.id.clone()).collect();
+    let status = if accounts.is_empty() {
+        EmergencyCoverageStatus::NoAccounts
+    } else if settings.monthly_expenses > 0.0 {
+        EmergencyCoverageStatus::Available
+    } else {
+        EmergencyCoverageStatus::ConfigureExpenses
+    };
+
+    let period_ends = month_ends(as_of - Months::new(COVERAGE_HISTORY_MONTHS), as_of);
+    let valued = value_accounts(
+        &mut conn,
+        accounts,
+        &period_ends,
+        FillMethod::CarryForward,
+        false,
+        &settings.base_currency,
+    )
+    .await?;
+    let units = minor_units_for(&settings.base_currency);
+    let mut totals: BTreeMap<_, Vec<f64>> = BTreeMap::new();
+    for AccountValues { values, .. } in valued {
+        for (date, value, _) in values {
+            totals.entry(date).or_default().push(value);
+        }
+    }
+
+    let months = |total: f64| {
+        (status == EmergencyCoverageStatus::Available)
+            .then(|| round_amount(total / settings.monthly_expenses, 2))
+    };
+    let series: Vec<EmergencyCoveragePoint> = totals
+        .into_iter()
+        .map(|(date, values)| {
+            let total = round_amount(sum_amounts(values), units);
+            EmergencyCoveragePoint {
+                date: dates::format(date),
+                total,
+                months_covered: months(total),
+            }
+        })
+        .collect();
+    let as_of = dates::format(as_of);
+    let total = series
+        .last()
+        .filter(|point| point.date == as_of)
+        .map_or(0.0, |point| point.total);
+
+    Ok(EmergencyCoverage {
+        as_of,
+        status,
+        monthly_expenses: settings.monthly_expenses,
+        account_ids,
+        total,
+        months_covered: months(total),
+        series,
+    })
+}
@@ -0,0 +1,169 @@
+use sqlx::{SqliteExecutor, SqlitePool};
+use tauri::State;
+use uuid::Uuid;
+
+use super::{normalize_date, now, today_date};
+use crate::dates;
+use crate::error::{Error, Result};
+use crate::models::{validate_currency, AccountWithBalance, ExchangeRate};
+
+/// `code` upper-cased, provided it is a supported currency.
+pub(crate) fn currency_code(code: &str) -> Result<String> {
+    let code = code.trim().to_ascii_uppercase();
+    validate_currency(&code)?;
+    Ok(code)
+}
+
+/// The rate from `from` to `to` recorded closest to `date`, on either side of
+/// it; the earlier wins a tie. A rate stored the other way round is inverted.
+pub(crate) async fn find_rate<'e>(
+    executor: impl SqliteExecutor<'e>,
+    from: &str,
+    to: &str,
+    date: &str,
+) -> Result<Option<f64>> {
+    let rate = sqlx::query_scalar(
+        "SELECT rate FROM (
+            SELECT date, rate FROM exchange_rates WHERE from_currency = ?1 AND to_currency = ?2
+            UNION ALL
+            SELECT date, 1.0 / rate FROM exchange_rates WHERE from_currency = ?2 AND to_currency = ?1
+         )
+         ORDER BY abs(julianday(date) - julianday(?3)), date
+         LIMIT 1",
+    )
+    .bind(from)
+    .bind(to)
+    .bind(date)
+    .fetch_optional(executor)
+    .await?;
+    Ok(rate)
+}
+
+/// `amount` in `from` converted into `to` at the nearest recorded rate.
+pub(crate) async fn convert<'e>(
+    executor: impl SqliteExecutor<'e>,
+    amount: f64,
+    from: &str,
+    to: &str,
+    date: &str,
+) -> Result<f64> {
+    let rate =
+        find_rate(executor, from, to, date)
+            .await?
+            .ok_or_else(|| Error::MissingExchangeRate {
+                from_currency: from.to_string(),
+                to_currency: to.to_string(),
+                date: date.to_string(),
+            })?;
+    Ok(amount * rate)
+}
+
+/// `amount` in `account`'s currency converted into `to` at the rate nearest
+/// its latest balance, or today without one.
+pub(crate) async fn convert_balance<'e>(
+    executor: impl SqliteExecutor<'e>,
+    account: &AccountWithBalance,
+    amount: f64,
+    to: &str,
+) -> Result<f64> {
+    if account.account.currency == to {
+        return Ok(amount);
+    }
+    let date = match &account.balance_date {
+        Some(date) => date.clone(),
+        None => dates::format(today_date()),
+    };
+    convert(executor, amount, &account.account.currency, to, &date).await
+}
+
+/// Records what one `from_currency` was worth in `to_currency` on `date`,
+/// replacing any rate already stored for that pair and date.
+#[tauri::command]
+pub async fn set_exchange_rate(
+    pool: State<'_, SqlitePool>,
+    date: String,
+    from_currency: String,
+    to_currency: String,
+    rate: f64,
+) -> Result<ExchangeRate> {
+    let date = normalize_date("date", &date)?;
+    let from_currency = currency_code(&from_currency)?;
+    let to_currency = currency_code(&to_currency)?;
+    if from_currency == to_currency {
+        return Err(Error::validation(
+            "to_currency",
+            "must differ from from_currency",
+        ));
+    }
+    if !rate.is_finite() || rate <= 0.0 {
+        return Err(Error::validation("rate", "must be a positive number"));
+    }
+
+    let exchange_rate = ExchangeRate {
+        id: Uuid::new_v4().to_string(),
+        date,
+        from_currency,
+        to_currency,
+        rate,
+        created_at: now(),
+    };
+    sqlx::query(
+        "INSERT INTO exchange_rates (id, date, from_currency, to_currency, rate, created_at)
+         VALUES (?, ?, ?, ?, ?, ?)
+         ON CONFLICT(from_currency, to_currency, date) DO UPDATE SET
+            rate = excluded.rate,
+            created_at = excluded.created_at",
+    )
+    .bind(&exchange_rate.id)
+    .bind(&exchange_rate.date)
+    .bind(&exchange_rate.from_currency)
+    .bind(&exchange_rate.to_currency)
+    .bind(exchange_rate.rate)
+    .bind(&exchange_rate.created_at)
+    .execute(pool.inner())
+    .await?;
+
+    let stored = sqlx::query_as::<_, ExchangeRate>(
+        "SELECT * FROM exchange_rates WHERE from_currency = ? AND to_currency = ? AND date = ?",
+    )
+    .bind(&exchange_rate.from_currency)
+    .bind(&exchange_rate.to_currency)
+    .bind(&exchange_rate.date)
+    .fetch_one(pool.inner())
+    .await?;
+    Ok(stored)
+}
+
+/// Stored rates, newest first, optionally only those for one pair of currencies
+/// in the direction given.
+#[tauri::command]
+pub async fn get_exchange_rates(
+    pool: State<'_, SqlitePool>,
+    from_currency: Option<String>,
+    to_currency: Option<String>,
+) -> Result<Vec<ExchangeRate>> {
+    let from_currency = from_currency.as_deref().map(currency_code).transpose()?;
+    let to_currency = to_currency.as_deref().map(currency_code).transpose()?;
+    let rates = sqlx::query_as::<_, ExchangeRate>(
+        "SELECT * FROM exchange_rates
+         WHERE (?1 IS NULL OR from_currency = ?1) AND (?2 IS NULL OR to_currency = ?2)
+         ORDER BY date DESC, from_currency, to_currency",
+    )
+    .bind(from_currency)
+    .bind(to_currency)
+    .fetch_all(pool.inner())
+    .await?;
+    Ok(rates)
+}
+
+#[tauri::command]
+pub async fn delete_exchange_rate(pool: State<'_, SqlitePool>, id: String) -> Result<()> {
+    let deleted = sqlx::query("DELETE FROM exchange_rates WHERE id = ?")
+        .bind(&id)
+        .execute(pool.inner())
+        .await?;
+    if deleted.rows_affected() == 0 {
+        return Err(Error::not_found("exchange rate", id));
+    }
+    Ok(())
+}
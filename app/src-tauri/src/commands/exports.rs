@@ -0,0 +1,401 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use std::path::Path;
+
+use chrono::{Datelike, NaiveDate, Utc};
+use sqlx::SqlitePool;
+use tauri::State;
+
+use super::accounts::fetch_account;
+use super::balances::fetch_entries;
+use super::milestones::{describe_target, fetch_milestones};
+use super::settings::load_settings;
+use super::{normalize_date, parse_date};
+use crate::error::{Error, Result};
+use crate::models::{
+    minor_units_for, round_amount, Account, BalanceCsvExportOptions, DateRange, EntrySource,
+    Milestone, MilestoneRecurrence,
+};
+use crate::series::{interpolate_linear, month_ends, monthly_on_day, yearly_on_day, Point};
+use crate::{csv, dates, ics};
+
+struct ExportRow {
+    date: NaiveDate,
+    balance: f64,
+    notes: Option<String>,
+    source: EntrySource,
+    verified: bool,
+}
+
+/// Writes one account's balance history to a CSV at `path`, oldest first, with
+/// date, balance, notes, source and verified columns. Returns the number of
+/// rows written, not counting the header.
+#[tauri::command]
+pub async fn export_balances_csv(
+    pool: State<'_, SqlitePool>,
+    account_id: String,
+    path: String,
+    options: Option<BalanceCsvExportOptions>,
+) -> Result<u32> {
+    let options = options.unwrap_or_default();
+    // Patterns chrono can't apply to a date, such as `%H`, only fail when used.
+    let mut sample = String::new();
+    if write!(sample, "{}", NaiveDate::MIN.format(&options.date_format)).is_err() {
+        return Err(Error::validation(
+            "date_format",
+            format!("'{}' is not a valid date format", options.date_format),
+        ));
+    }
+    let delimiter = match options.decimal_separator {
+        '.' => ',',
+        ',' => ';',
+        _ => return Err(Error::validation("decimal_separator", "must be '.' or ','")),
+    };
+    if !options.overwrite && Path::new(&path).exists() {
+        return Err(Error::Conflict(format!(
+            "{path} already exists; pass overwrite to replace it"
+        )));
+    }
+
+    let account = fetch_account(pool.inner(), &account_id).await?;
+    let units = minor_units_for(&account.currency);
+    let mut rows = Vec::new();
+    for entry in fetch_entries(pool.inner(), &account_id).await? {
+        rows.push(ExportRow {
+            date: parse_date("date", &entry.date)?,
+            balance: entry.balance,
+            notes: entry.notes,
+            source: entry.source,
+            verified: entry.verified_at.is_some(),
+        });
+    }
+
+    if options.include_interpolated {
+        let points: Vec<Point> = rows
+            .iter()
+            .map(|row| Point {
+                date: row.date,
+                balance: row.balance,
+            })
+            .collect();
+        let taken: HashSet<NaiveDate> = points.iter().map(|point| point.date).collect();
+        if let (Some(first), Some(last)) = (points.first(), points.last()) {
+            for date in month_ends(first.date, last.date) {
+                if taken.contains(&date) {
+                    continue;
+                }
+                let balance = interpolate_linear(&points, date).expect("within the entries");
+                rows.push(ExportRow {
+                    date,
+                    balance: round_amount(balance, units),
+                    notes: None,
+                    source: EntrySource::Estimated,
+                    verified: false,
+                });
+            }
+        }
+        rows.sort_by_key(|row| row.date);
+    }
+
+    let mut out = String::new();
+    csv::write_record(
+        &mut out,
+        &["date", "balance", "notes", "source", "verified"],
+        delimiter,
+    );
+    for row in &rows {
+        let balance = format!("{:.*}", usize::from(units), row.balance);
+        csv::write_record(
+            &mut out,
+            &[
+                row.date.format(&options.date_format).to_string(),
+                balance.replace('.', &options.decimal_separator.to_string()),
+                row.notes.clone().unwrap_or_default(),
+                row.source.as_str().to_string(),
+                row.verified.to_string(),
+            ],
+            delimiter,
+        );
+    }
+    std::fs::write(&path, out)?;
+    Ok(rows.len() as u32)
+}
+
+/// The RRULE that repeats a milestone dated `date` the way
+/// `materialise_recurring_milestones` does, taking the last day of months
+/// too short for its day.
+fn recurrence_rule(recurrence: MilestoneRecurrence, date: NaiveDate) -> Option<String> {
+    let short_months = |day: u32| {
+        let days: Vec<String> = (28..=day).map(|day| day.to_string()).collect();
+        format!("BYMONTHDAY={};BYSETPOS=-1", days.join(","))
+    };
+    match recurrence {
+        MilestoneRecurrence::None => None,
+        MilestoneRecurrence::Monthly if date.day() > 28 => {
+            Some(format!("FREQ=MONTHLY;{}", short_months(date.day())))
+        }
+        MilestoneRecurrence::Monthly => Some(format!("FREQ=MONTHLY;BYMONTHDAY={}", date.day())),
+        MilestoneRecurrence::Yearly if date.month() == 2 && date.day() == 29 => {
+            Some(format!("FREQ=YEARLY;BYMONTH=2;{}", short_months(29)))
+        }
+        MilestoneRecurrence::Yearly => Some("FREQ=YEARLY".to_string()),
+    }
+}
+
+/// True when `instance` is what its template's rule produces on its date, so
+/// the template's repeating event already shows it.
+fn follows_template(
+    instance: &Milestone,
+    template: &Milestone,
+    rule_dates: &HashSet<String>,
+) -> bool {
+    rule_dates.contains(&instance.date)
+        && instance.label == template.label
+        && instance.account_ids == template.account_ids
+        && instance.category == template.category
+        && instance.icon == template.icon
+}
+
+fn ics_date(date: NaiveDate) -> String {
+    date.format("%Y%m%d").to_string()
+}
+
+/// Writes milestones dated within `range` to an iCalendar file at `path`,
+/// replacing any file there, as one all-day event each. Event UIDs come from
+/// milestone ids, so importing a later export updates the events rather than
+/// duplicating them. A recurring template is one repeating event covering its
+/// instances, and an instance changed on its own is written separately with
+/// its date taken out of the rule. Returns the number of events written.
+#[tauri::command]
+pub async fn export_milestones_ics(
+    pool: State<'_, SqlitePool>,
+    path: String,
+    range: Option<DateRange>,
+) -> Result<u32> {
+    let range = range.unwrap_or_default();
+    let start = range
+        .start
+        .map(|date| normalize_date("start", &date))
+        .transpose()?;
+    let end = range
+        .end
+        .map(|date| normalize_date("end", &date))
+        .transpose()?;
+    if let (Some(start), Some(end)) = (&start, &end) {
+        if end < start {
+            return Err(Error::validation("end", "must not be before start"));
+        }
+    }
+
+    let mut conn = pool.acquire().await?;
+    // Everything is read, not just the range: templates dated before `start` can
+    // still recur into it, and instances past `end` decide the rule's exceptions.
+    let milestones = fetch_milestones(&mut conn, None, None, None).await?;
+    let accounts: HashMap<String, Account> = sqlx::query_as::<_, Account>("SELECT * FROM accounts")
+        .fetch_all(&mut *conn)
+        .await?
+        .into_iter()
+        .map(|account| (account.id.clone(), account))
+        .collect();
+    let base_currency = load_settings(&mut *conn).await?.base_currency;
+
+    // The dates each recurring template has generated instances for.
+    let mut rule_dates: HashMap<&str, HashSet<String>> = HashMap::new();
+    for template in milestones
+        .iter()
+        .filter(|milestone| milestone.recurrence != MilestoneRecurrence::None)
+    {
+        let first = parse_date("date", &template.date)?;
+        let until = template
+            .materialised_until
+            .as_deref()
+            .map(|until| parse_date("materialised_until", until))
+            .transpose()?
+            .unwrap_or(first);
+        let dates = match template.recurrence {
+            MilestoneRecurrence::Yearly => yearly_on_day(first, until),
+            _ => monthly_on_day(first, until, first.day()),
+        };
+        rule_dates.insert(
+            &template.id,
+            dates
+                .into_iter()
+                .filter(|date| *date > first)
+                .map(dates::format)
+                .collect(),
+        );
+    }
+    let templates: HashMap<&str, &Milestone> = milestones
+        .iter()
+        .filter(|milestone| rule_dates.contains_key(milestone.id.as_str()))
+        .map(|milestone| (milestone.id.as_str(), milestone))
+        .collect();
+    let shown: HashSet<&str> = milestones
+        .iter()
+        .filter(|milestone| {
+            let template = milestone
+                .parent_id
+                .as_deref()
+                .and_then(|parent_id| templates.get(parent_id));
+            template.is_some_and(|template| {
+                follows_template(milestone, template, &rule_dates[template.id.as_str()])
+            })
+        })
+        .map(|milestone| milestone.id.as_str())
+        .collect();
+
+    let mut out = String::new();
+    for line in [
+        "BEGIN:VCALENDAR",
+        "VERSION:2.0",
+        "PRODID:-//Tally//Milestones//EN",
+        "CALSCALE:GREGORIAN",
+        "METHOD:PUBLISH",
+    ] {
+        ics::write_line(&mut out, line);
+    }
+    let stamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let mut count = 0;
+    for milestone in &milestones {
+        let recurring = templates.contains_key(milestone.id.as_str());
+        let before_range = start.as_ref().is_some_and(|start| milestone.date < *start);
+        let after_range = end.as_ref().is_some_and(|end| milestone.date > *end);
+        if shown.contains(milestone.id.as_str()) || after_range || (before_range && !recurring) {
+            continue;
+        }
+        let date = parse_date("date", &milestone.date)?;
+        let next_day = date.succ_opt().unwrap_or(date);
+
+        let names: Vec<&str> = milestone
+            .account_ids
+            .iter()
+            .filter_map(|id| accounts.get(id))
+            .map(|account| account.name.as_str())
+            .collect();
+        let mut description = Vec::new();
+        match names.as_slice() {
+            [] => {}
+            [name] => description.push(format!("Account: {name}")),
+            names => description.push(format!("Accounts: {}", names.join(", "))),
+        }
+        if let Some(target) = describe_target(milestone, &accounts, &base_currency) {
+            description.push(format!("Target: {target}"));
+        }
+        if let Some(achieved_at) = &milestone.achieved_at {
+            description.push(format!("Achieved on {achieved_at}"));
+        }
+
+        ics::write_line(&mut out, "BEGIN:VEVENT");
+        ics::write_line(&mut out, &format!("UID:{}@tally", milestone.id));
+        ics::write_line(&mut out, &format!("DTSTAMP:{stamp}"));
+        ics::write_line(&mut out, &format!("DTSTART;VALUE=DATE:{}", ics_date(date)));
+        ics::write_line(
+            &mut out,
+            &format!("DTEND;VALUE=DATE:{}", ics_date(next_day)),
+        );
+        ics::write_line(
+            &mut out,
+            &format!("SUMMARY:{}", ics::escape(&milestone.label)),
+        );
+        if !description.is_empty() {
+            let description = ics::escape(&description.join("\n"));
+            ics::write_line(&mut out, &format!("DESCRIPTION:{description}"));
+        }
+        if recurring {
+            if let Some(rule) = recurrence_rule(milestone.recurrence, date) {
+                ics::write_line(&mut out, &format!("RRULE:{rule}"));
+            }
+            let covered: HashSet<&str> = milestones
+                .iter()
+                .filter(|instance| {
+                    instance.parent_id.as_deref() == Some(milestone.id.as_str())
+                        && shown.contains(instance.id.as_str())
+                })
+                .map(|instance| instance.date.as_str())
+                .collect();
+            let mut excluded: Vec<String> = rule_dates[milestone.id.as_str()]
+                .iter()
+                .filter(|date| !covered.contains(date.as_str()))
+                .map(|date| parse_date("date", date).map(ics_date))
+                .collect::<Result<_>>()?;
+            if !excluded.is_empty() {
+                excluded.sort();
+                ics::write_line(
+                    &mut out,
+                    &format!("EXDATE;VALUE=DATE:{}", excluded.join(",")),
+                );
+            }
+        }
+        ics::write_line(&mut out, "END:VEVENT");
+        count += 1;
+    }
+    ics::write_line(&mut out, "END:VCALENDAR");
+
+    std::fs::write(&path, out)?;
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::commands::accounts::create_account;
+    use crate::commands::milestones::create_milestone;
+    use crate::ics::tests::assert_valid;
+    use crate::testing::{block_on, input, temp_path, TestDb};
+
+    #[test]
+    fn milestones_export_as_a_valid_calendar() {
+        let db = TestDb::new();
+        block_on(async {
+            let account = create_account(
+                db.state(),
+                input(json!({"name": "Rainy day, mostly; savings", "account_type": "savings"})),
+            )
+            .await
+            .unwrap();
+            let label =
+                "Emergency fund: six months of rent, bills; food and a long enough label to fold";
+            let one_off = create_milestone(
+                db.state(),
+                input(json!({
+                    "date": "2025-06-30",
+                    "label": label,
+                    "account_ids": [account.id],
+                    "target_amount": 12000,
+                    "target_scope": "account",
+                })),
+            )
+            .await
+            .unwrap();
+            let monthly = create_milestone(
+                db.state(),
+                input(json!({"date": "2025-01-31", "label": "Pay day", "recurrence": "monthly"})),
+            )
+            .await
+            .unwrap();
+
+            let path = temp_path("ics");
+            let count = export_milestones_ics(db.state(), path.to_string_lossy().into(), None)
+                .await
+                .unwrap();
+            let calendar = std::fs::read_to_string(&path).unwrap();
+            std::fs::remove_file(&path).unwrap();
+
+            assert_eq!(count, 2);
+            let lines = assert_valid(&calendar);
+            assert!(lines.contains(&format!("UID:{}@tally", one_off.id)));
+            assert!(lines.contains(&format!("UID:{}@tally", monthly.id)));
+            assert!(lines.contains(&format!("SUMMARY:{}", ics::escape(label))));
+            assert!(lines.contains(&"DTSTART;VALUE=DATE:20250630".to_string()));
+            assert!(lines.contains(&"DTEND;VALUE=DATE:20250701".to_string()));
+            assert!(lines.iter().any(
+                |line| line.starts_with(r"DESCRIPTION:Account: Rainy day\, mostly\; savings\n")
+            ));
+            assert!(lines
+                .iter()
+                .any(|line| line.starts_with("RRULE:FREQ=MONTHLY")));
+        });
+    }
+}
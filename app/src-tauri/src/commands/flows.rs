@@ -0,0 +1,627 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{Days, NaiveDate};
+use sqlx::{SqliteExecutor, SqlitePool};
+use tauri::State;
+use uuid::Uuid;
+
+use super::accounts::fetch_account;
+use super::analytics::{net_flows, net_worth_accounts, value_accounts, AccountValues};
+use super::balances::fetch_points;
+use super::settings::load_settings;
+use super::{non_empty, non_empty_patch, normalize_date, now, parse_date};
+use crate::dates;
+use crate::error::{Error, Result};
+use crate::models::{
+    from_minor, minor_units_for, round_amount, sum_amounts, to_minor, Account, AccountCategory,
+    AccountGrowth, CreateFlowInput, FillMethod, Flow, FlowKind, NetWorthGrowth, ReturnUnavailable,
+    SubPeriodReturn, TimeWeightedReturn, UpdateFlowInput,
+};
+use crate::series::{interpolate_linear, Point};
+
+async fn fetch_flow<'e>(executor: impl SqliteExecutor<'e>, id: &str) -> Result<Flow> {
+    sqlx::query_as::<_, Flow>("SELECT * FROM flows WHERE id = ?")
+        .bind(id)
+        .fetch_optional(executor)
+        .await?
+        .ok_or_else(|| Error::not_found("flow", id))
+}
+
+/// `amount` rounded to the account currency, provided it is positive.
+fn check_amount(account: &Account, amount: f64) -> Result<f64> {
+    if !amount.is_finite() || amount <= 0.0 {
+        return Err(Error::validation(
+            "amount",
+            "must be a positive number; use kind to record a withdrawal",
+        ));
+    }
+    let minor_units = minor_units_for(&account.currency);
+    Ok(from_minor(to_minor(amount, minor_units), minor_units))
+}
+
+fn check_period(start: &str, end: &str) -> Result<(NaiveDate, NaiveDate)> {
+    let start = parse_date("start", start)?;
+    let end = parse_date("end", end)?;
+    if end < start {
+        return Err(Error::validation("end", "must not be before start"));
+    }
+    Ok((start, end))
+}
+
+/// The latest balance on or before `date`, or 0 before the first entry.
+pub(crate) fn balance_on(points: &[Point], date: NaiveDate) -> f64 {
+    let after = points.partition_point(|point| point.date <= date);
+    after.checked_sub(1).map_or(0.0, |i| points[i].balance)
+}
+
+/// The growth of `account` between `start` and `end` from its entries as
+/// points and every flow it has, in any order.
+fn account_growth(
+    account: &Account,
+    points: &[Point],
+    flows: &[Flow],
+    start: NaiveDate,
+    end: NaiveDate,
+) -> AccountGrowth {
+    let owed = account.category == AccountCategory::Liability;
+    let units = minor_units_for(&account.currency);
+    let measure = |balance: f64| round_amount(if owed { balance.abs() } else { balance }, units);
+    let start_balance = measure(balance_on(points, start));
+    let end_balance = measure(balance_on(points, end));
+
+    let (start, end) = (dates::format(start), dates::format(end));
+    let in_period: Vec<&Flow> = flows
+        .iter()
+        .filter(|flow| flow.date > start && flow.date <= end)
+        .collect();
+    let total = |kind| {
+        let amounts = in_period.iter().filter(|flow| flow.kind == kind);
+        round_amount(sum_amounts(amounts.map(|flow| flow.amount)), units)
+    };
+    let contributions = total(FlowKind::Contribution);
+    let withdrawals = total(FlowKind::Withdrawal);
+    let net_flow = sum_amounts([contributions, -withdrawals]);
+    let change = sum_amounts([end_balance, -start_balance]);
+    // Paying into a liability lowers what is owed, so its flows count the other way.
+    let growth = if owed {
+        sum_amounts([change, net_flow])
+    } else {
+        sum_amounts([change, -net_flow])
+    };
+
+    AccountGrowth {
+        account_id: account.id.clone(),
+        start,
+        end,
+        start_balance,
+        end_balance,
+        change,
+        contributions,
+        withdrawals,
+        net_flow,
+        growth,
+        has_flows: !flows.is_empty(),
+    }
+}
+
+#[tauri::command]
+pub async fn create_flow(pool: State<'_, SqlitePool>, input: CreateFlowInput) -> Result<Flow> {
+    let account = fetch_account(pool.inner(), &input.account_id).await?;
+    let flow = Flow {
+        id: Uuid::new_v4().to_string(),
+        account_id: account.id.clone(),
+        date: normalize_date("date", &input.date)?,
+        amount: check_amount(&account, input.amount)?,
+        kind: input.kind,
+        notes: non_empty(input.notes),
+        created_at: now(),
+    };
+
+    sqlx::query(
+        "INSERT INTO flows (id, account_id, date, amount, kind, notes, created_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(&flow.id)
+    .bind(&flow.account_id)
+    .bind(&flow.date)
+    .bind(flow.amount)
+    .bind(flow.kind)
+    .bind(&flow.notes)
+    .bind(&flow.created_at)
+    .execute(pool.inner())
+    .await?;
+
+    Ok(flow)
+}
+
+/// The account's flows between `start` and `end` (both optional and inclusive),
+/// oldest first.
+#[tauri::command]
+pub async fn get_flows(
+    pool: State<'_, SqlitePool>,
+    account_id: String,
+    start: Option<String>,
+    end: Option<String>,
+) -> Result<Vec<Flow>> {
+    let start = start
+        .map(|date| normalize_date("start", &date))
+        .transpose()?;
+    let end = end.map(|date| normalize_date("end", &date)).transpose()?;
+    let flows = sqlx::query_as::<_, Flow>(
+        "SELECT * FROM flows
+         WHERE account_id = ?1 AND (?2 IS NULL OR date >= ?2) AND (?3 IS NULL OR date <= ?3)
+         ORDER BY date, created_at",
+    )
+    .bind(&account_id)
+    .bind(start)
+    .bind(end)
+    .fetch_all(pool.inner())
+    .await?;
+    Ok(flows)
+}
+
+#[tauri::command]
+pub async fn update_flow(
+    pool: State<'_, SqlitePool>,
+    id: String,
+    input: UpdateFlowInput,
+) -> Result<Flow> {
+    let mut flow = fetch_flow(pool.inner(), &id).await?;
+    if let Some(date) = input.date {
+        flow.date = normalize_date("date", &date)?;
+    }
+    if let Some(amount) = input.amount {
+        let account = fetch_account(pool.inner(), &flow.account_id).await?;
+        flow.amount = check_amount(&account, amount)?;
+    }
+    if let Some(kind) = input.kind {
+        flow.kind = kind;
+    }
+    non_empty_patch(input.notes).apply(&mut flow.notes);
+
+    sqlx::query("UPDATE flows SET date = ?, amount = ?, kind = ?, notes = ? WHERE id = ?")
+        .bind(&flow.date)
+        .bind(flow.amount)
+        .bind(flow.kind)
+        .bind(&flow.notes)
+        .bind(&id)
+        .execute(pool.inner())
+        .await?;
+
+    Ok(flow)
+}
+
+#[tauri::command]
+pub async fn delete_flow(pool: State<'_, SqlitePool>, id: String) -> Result<()> {
+    let deleted = sqlx::query("DELETE FROM flows WHERE id = ?")
+        .bind(&id)
+        .execute(pool.inner())
+        .await?;
+    if deleted.rows_affected() == 0 {
+        return Err(Error::not_found("flow", id));
+    }
+    Ok(())
+}
+
+/// Splits the account's balance change between `start` and `end` into net
+/// flows and market growth, optionally ignoring estimated entries.
+#[tauri::command]
+pub async fn get_account_growth(
+    pool: State<'_, SqlitePool>,
+    account_id: String,
+    start: String,
+    end: String,
+    exclude_estimated: Option<bool>,
+) -> Result<AccountGrowth> {
+    let (start, end) = check_period(&start, &end)?;
+    let account = fetch_account(pool.inner(), &account_id).await?;
+    let points = fetch_points(
+        pool.inner(),
+        &account_id,
+        exclude_estimated.unwrap_or(false),
+    )
+    .await?;
+    let flows = sqlx::query_as::<_, Flow>("SELECT * FROM flows WHERE account_id = ?")
+        .bind(&account_id)
+        .fetch_all(pool.inner())
+        .await?;
+    Ok(account_growth(&account, &points, &flows, start, end))
+}
+
+/// Splits the change in net worth between `start` and `end` into what was
+/// saved and what grew, treating accounts without flows as all growth and
+/// optionally ignoring estimated entries. Net worth counts the accounts
+/// `get_net_worth_series` does, in the base currency.
+#[tauri::command]
+pub async fn get_net_worth_growth(
+    pool: State<'_, SqlitePool>,
+    start: String,
+    end: String,
+    exclude_estimated: Option<bool>,
+) -> Result<NetWorthGrowth> {
+    let (start, end) = check_period(&start, &end)?;
+    let exclude_estimated = exclude_estimated.unwrap_or(false);
+    let mut conn = pool.acquire().await?;
+    let base_currency = load_settings(&mut *conn).await?.base_currency;
+    let accounts = net_worth_accounts(&mut conn).await?;
+    let mut flows: HashMap<String, Vec<Flow>> = HashMap::new();
+    for flow in sqlx::query_as::<_, Flow>("SELECT * FROM flows")
+        .fetch_all(&mut *conn)
+        .await?
+    {
+        flows.entry(flow.account_id.clone()).or_default().push(flow);
+    }
+
+    let mut growths = Vec::with_capacity(accounts.len());
+    for account in &accounts {
+        let points = fetch_points(&mut *conn, &account.id, exclude_estimated).await?;
+        let account_flows = flows.get(&account.id).map_or(&[][..], Vec::as_slice);
+        growths.push(account_growth(account, &points, account_flows, start, end));
+    }
+
+    // Valued and converted as the series is, and each total rounded once.
+    let units = minor_units_for(&base_currency);
+    let (mut start_values, mut end_values) = (Vec::new(), Vec::new());
+    for AccountValues {
+        account, values, ..
+    } in value_accounts(
+        &mut conn,
+        accounts.clone(),
+        &[start, end],
+        FillMethod::Linear,
+        exclude_estimated,
+        &base_currency,
+    )
+    .await?
+    {
+        let sign = match account.category {
+            AccountCategory::Asset => 1.0,
+            AccountCategory::Liability => -1.0,
+        };
+        for (date, value, _) in values {
+            if date == start {
+                start_values.push(sign * value);
+            }
+            if date == end {
+                end_values.push(sign * value);
+            }
+        }
+    }
+    let start_net_worth = round_amount(sum_amounts(start_values), units);
+    let end_net_worth = round_amount(sum_amounts(end_values), units);
+    let change = sum_amounts([end_net_worth, -start_net_worth]);
+    // Flows on `start` are already in its balance.
+    let flows = net_flows(
+        &mut conn,
+        &accounts,
+        start + Days::new(1),
+        end,
+        &base_currency,
+    )
+    .await?;
+    let saved = round_amount(
+        sum_amounts(flows.into_iter().map(|(_, amount)| amount)),
+        units,
+    );
+    Ok(NetWorthGrowth {
+        start: dates::format(start),
+        end: dates::format(end),
+        start_net_worth,
+        end_net_worth,
+        change,
+        saved,
+        grown: sum_amounts([change, -saved]),
+        accounts: growths,
+    })
+}
+
+/// Newton's method gives up after this many steps.
+const MAX_XIRR_ITERATIONS: u32 = 100;
+
+/// The yearly rate, as a fraction, at which `cash_flows` have no net present
+/// value, by Newton's method from 10%. Each is days from the first and an amount
+/// with money paid in negative; years are 365 days, as spreadsheets count them.
+/// `None` when the amounts never change sign or the rate doesn't settle.
+fn xirr(cash_flows: &[(f64, f64)]) -> Option<f64> {
+    let paid_in = cash_flows.iter().any(|(_, amount)| *amount < 0.0);
+    let paid_out = cash_flows.iter().any(|(_, amount)| *amount > 0.0);
+    if !(paid_in && paid_out) {
+        return None;
+    }
+    let mut rate: f64 = 0.1;
+    for _ in 0..MAX_XIRR_ITERATIONS {
+        let (mut value, mut slope) = (0.0, 0.0);
+        for &(days, amount) in cash_flows {
+            let years = days / 365.0;
+            let discount = (1.0 + rate).powf(years);
+            value += amount / discount;
+            slope -= years * amount / (discount * (1.0 + rate));
+        }
+        if !slope.is_finite() || slope == 0.0 {
+            return None;
+        }
+        let mut next = rate - value / slope;
+        if !next.is_finite() {
+            return None;
+        }
+        // Rates at or below -100% are meaningless; step halfway towards it instead.
+        if next <= -1.0 {
+            next = (rate - 1.0) / 2.0;
+        }
+        if (next - rate).abs() < 1e-10 {
+            return Some(next);
+        }
+        rate = next;
+    }
+    None
+}
+
+/// The time-weighted return of an asset account from `start` to `end`: the
+/// period is broken at each day with flows, the return of each part taken net
+/// of the flows that end it, and the parts chained. Balances are interpolated
+/// between entries as end-of-day values that include the day's flows. Comes
+/// with the money-weighted XIRR for comparison; without flows both reduce to
+/// the simple change.
+#[tauri::command]
+pub async fn get_time_weighted_return(
+    pool: State<'_, SqlitePool>,
+    account_id: String,
+    start: String,
+    end: String,
+) -> Result<TimeWeightedReturn> {
+    let (start, end) = check_period(&start, &end)?;
+    if start == end {
+        return Err(Error::validation("end", "must be after start"));
+    }
+    let account = fetch_account(pool.inner(), &account_id).await?;
+    if account.category == AccountCategory::Liability {
+        return Err(Error::validation(
+            "account_id",
+            "returns are only measured for assets",
+        ));
+    }
+    let points = fetch_points(pool.inner(), &account_id, false).await?;
+    let flows = sqlx::query_as::<_, Flow>(
+        "SELECT * FROM flows WHERE account_id = ? AND date > ? AND date <= ? ORDER BY date",
+    )
+    .bind(&account_id)
+    .bind(dates::format(start))
+    .bind(dates::format(end))
+    .fetch_all(pool.inner())
+    .await?;
+    let units = minor_units_for(&account.currency);
+
+    let mut by_day: BTreeMap<NaiveDate, Vec<f64>> = BTreeMap::new();
+    for flow in &flows {
+        let amount = match flow.kind {
+            FlowKind::Contribution => flow.amount,
+            FlowKind::Withdrawal => -flow.amount,
+        };
+        by_day
+            .entry(parse_date("date", &flow.date)?)
+            .or_default()
+            .push(amount);
+    }
+    let mut breaks: Vec<(NaiveDate, f64)> = by_day
+        .into_iter()
+        .map(|(date, amounts)| (date, sum_amounts(amounts)))
+        .collect();
+    if breaks.last().map(|(date, _)| *date) != Some(end) {
+        breaks.push((end, 0.0));
+    }
+
+    let value_on = |date: NaiveDate| {
+        let value = interpolate_linear(&points, date)?;
+        let estimated = points
+            .binary_search_by_key(&date, |point| point.date)
+            .is_err();
+        Some((round_amount(value, units), estimated))
+    };
+    let mut result = TimeWeightedReturn {
+        account_id,
+        start: dates::format(start),
+        end: dates::format(end),
+        periods: Vec::new(),
+        twr: None,
+        twr_annualised: None,
+        twr_unavailable: None,
+        xirr: None,
+        xirr_unavailable: None,
+        has_flows: !flows.is_empty(),
+    };
+    let Some((start_value, start_estimated)) = value_on(start) else {
+        result.twr_unavailable = Some(ReturnUnavailable::NoData);
+        result.xirr_unavailable = Some(ReturnUnavailable::NoData);
+        return Ok(result);
+    };
+
+    let (mut from, mut from_value, mut from_estimated) = (start, start_value, start_estimated);
+    let mut growth = Some(1.0);
+    let mut cash_flows = vec![(0.0, -start_value)];
+    for (date, net_flow) in breaks {
+        // Every date after one with a balance has one too.
+        let (value, estimated) = value_on(date).expect("balances carry forward");
+        let ratio = (from_value > 0.0).then(|| (value - net_flow) / from_value);
+        growth = growth.zip(ratio).map(|(growth, ratio)| growth * ratio);
+        result.periods.push(SubPeriodReturn {
+            start: dates::format(from),
+            end: dates::format(date),
+            start_value: from_value,
+            end_value: value,
+            net_flow,
+            return_percent: ratio.map(|ratio| (ratio - 1.0) * 100.0),
+            estimated: estimated || from_estimated,
+        });
+        let days = (date - start).num_days() as f64;
+        if net_flow != 0.0 {
+            cash_flows.push((days, -net_flow));
+        }
+        if date == end {
+            cash_flows.push((days, value));
+        }
+        (from, from_value, from_estimated) = (date, value, estimated);
+    }
+
+    match growth {
+        Some(growth) => {
+            let years = (end - start).num_days() as f64 / 365.25;
+            result.twr = Some((growth - 1.0) * 100.0);
+            result.twr_annualised =
+                (growth > 0.0).then(|| (growth.powf(1.0 / years) - 1.0) * 100.0);
+        }
+        None => result.twr_unavailable = Some(ReturnUnavailable::NonPositiveValue),
+    }
+    match xirr(&cash_flows) {
+        Some(rate) => result.xirr = Some(rate * 100.0),
+        None => result.xirr_unavailable = Some(ReturnUnavailable::NoConvergence),
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::commands::accounts::{archive_account, create_account};
+    use crate::commands::balances::create_balance_entry;
+    use crate::commands::exchange_rates::set_exchange_rate;
+    use crate::testing::{block_on, input, TestDb};
+
+    #[test]
+    fn xirr_matches_the_spreadsheet_example() {
+        // Excel's XIRR help: -10,000 on 2008-01-01 and four repayments, 37.34%.
+        let cash_flows = [
+            (0.0, -10_000.0),
+            (60.0, 2_750.0),
+            (303.0, 4_250.0),
+            (411.0, 3_250.0),
+            (456.0, 2_750.0),
+        ];
+        let rate = xirr(&cash_flows).unwrap();
+        assert!((rate - 0.373_362_535).abs() < 1e-8, "{rate}");
+    }
+
+    #[test]
+    fn xirr_gives_up_without_a_root() {
+        // -100 + 100x - 100x² is negative for every discount factor x.
+        assert_eq!(
+            xirr(&[(0.0, -100.0), (365.0, 100.0), (730.0, -100.0)]),
+            None
+        );
+        assert_eq!(xirr(&[(0.0, -100.0), (365.0, -50.0)]), None);
+    }
+
+    #[test]
+    fn without_flows_the_return_is_the_simple_change() {
+        let db = TestDb::new();
+        let result = block_on(async {
+            let account = create_account(
+                db.state(),
+                input(json!({
+                    "name": "ISA",
+                    "account_type": "investment",
+                    "opening_balance": 10_000,
+                    "opened_at": "2023-01-01",
+                })),
+            )
+            .await
+            .unwrap();
+            create_balance_entry(
+                db.state(),
+                input(json!({"account_id": account.id, "date": "2024-01-01", "balance": 11_000})),
+            )
+            .await
+            .unwrap();
+            get_time_weighted_return(
+                db.state(),
+                account.id,
+                "2023-01-01".into(),
+                "2024-01-01".into(),
+            )
+            .await
+            .unwrap()
+        });
+        assert!(!result.has_flows);
+        assert_eq!(result.periods.len(), 1);
+        assert!((result.twr.unwrap() - 10.0).abs() < 1e-9);
+        // 365 days is a spreadsheet year, so the money-weighted rate agrees.
+        assert!((result.xirr.unwrap() - 10.0).abs() < 1e-6);
+        assert_eq!(result.xirr_unavailable, None);
+    }
+
+    #[test]
+    fn net_worth_growth_skips_archived_accounts_and_converts_currencies() {
+        let db = TestDb::new();
+        let growth = block_on(async {
+            set_exchange_rate(
+                db.state(),
+                "2025-01-01".into(),
+                "USD".into(),
+                "GBP".into(),
+                0.8,
+            )
+            .await
+            .unwrap();
+            let account = |value: serde_json::Value| async {
+                create_account(db.state(), input(value)).await.unwrap()
+            };
+            let entry = |account_id: &str, balance: f64| {
+                let value =
+                    json!({"account_id": account_id, "date": "2025-06-30", "balance": balance});
+                async {
+                    create_balance_entry(db.state(), input(value))
+                        .await
+                        .unwrap()
+                }
+            };
+            let savings = account(json!({
+                "name": "Savings",
+                "account_type": "savings",
+                "opening_balance": 1000,
+                "opened_at": "2025-01-01",
+            }))
+            .await;
+            entry(&savings.id, 1200.0).await;
+            let brokerage = account(json!({
+                "name": "Brokerage",
+                "account_type": "investment",
+                "currency": "USD",
+                "opening_balance": 1000,
+                "opened_at": "2025-01-01",
+            }))
+            .await;
+            entry(&brokerage.id, 1500.0).await;
+            create_flow(
+                db.state(),
+                input(json!({
+                    "account_id": brokerage.id,
+                    "date": "2025-03-01",
+                    "amount": 250,
+                    "kind": "contribution",
+                })),
+            )
+            .await
+            .unwrap();
+            let old = account(json!({
+                "name": "Old savings",
+                "account_type": "savings",
+                "opening_balance": 5000,
+                "opened_at": "2025-01-01",
+            }))
+            .await;
+            archive_account(db.state(), old.id).await.unwrap();
+
+            get_net_worth_growth(db.state(), "2025-01-01".into(), "2025-06-30".into(), None)
+                .await
+                .unwrap()
+        });
+        // The brokerage at 0.8 to the pound; the archived savings not at all.
+        assert_eq!(growth.accounts.len(), 2);
+        assert_eq!(growth.start_net_worth, 1000.0 + 800.0);
+        assert_eq!(growth.end_net_worth, 1200.0 + 1200.0);
+        assert_eq!(growth.change, 600.0);
+        assert_eq!(growth.saved, 200.0);
+        assert_eq!(growth.grown, 400.0);
+    }
+}
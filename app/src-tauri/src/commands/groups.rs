@@ -0,0 +1,260 @@
+use std::collections::HashSet;
+
+use sqlx::{SqliteExecutor, SqlitePool};
+use tauri::State;
+use uuid::Uuid;
+
+use super::accounts::{fetch_account, fetch_accounts_with_balance};
+use super::exchange_rates::convert_balance;
+use super::settings::load_settings;
+use super::{now, validate_ordering};
+use crate::error::{Error, Result};
+use crate::models::{
+    minor_units_for, round_amount, sum_amounts, Account, AccountGroup, AccountGroupWithAccounts,
+    AccountWithBalance, CreateGroupInput, GroupedAccounts, UpdateGroupInput,
+};
+
+async fn fetch_group<'e>(executor: impl SqliteExecutor<'e>, id: &str) -> Result<AccountGroup> {
+    sqlx::query_as::<_, AccountGroup>("SELECT * FROM account_groups WHERE id = ?")
+        .bind(id)
+        .fetch_optional(executor)
+        .await?
+        .ok_or_else(|| Error::not_found("group", id))
+}
+
+#[tauri::command]
+pub async fn create_group(
+    pool: State<'_, SqlitePool>,
+    input: CreateGroupInput,
+) -> Result<AccountGroup> {
+    input.validate()?;
+
+    let sort_order: i64 =
+        sqlx::query_scalar("SELECT COALESCE(MAX(sort_order) + 1, 0) FROM account_groups")
+            .fetch_one(pool.inner())
+            .await?;
+    let group = AccountGroup {
+        id: Uuid::new_v4().to_string(),
+        name: input.name.trim().to_string(),
+        sort_order,
+        created_at: now(),
+    };
+
+    sqlx::query(
+        "INSERT INTO account_groups (id, name, sort_order, created_at) VALUES (?, ?, ?, ?)",
+    )
+    .bind(&group.id)
+    .bind(&group.name)
+    .bind(group.sort_order)
+    .bind(&group.created_at)
+    .execute(pool.inner())
+    .await?;
+
+    Ok(group)
+}
+
+#[tauri::command]
+pub async fn rename_group(
+    pool: State<'_, SqlitePool>,
+    id: String,
+    input: UpdateGroupInput,
+) -> Result<AccountGroup> {
+    input.validate()?;
+
+    let mut group = fetch_group(pool.inner(), &id).await?;
+    group.name = input.name.trim().to_string();
+
+    sqlx::query("UPDATE account_groups SET name = ? WHERE id = ?")
+        .bind(&group.name)
+        .bind(&id)
+        .execute(pool.inner())
+        .await?;
+
+    Ok(group)
+}
+
+/// Removes the group only; its member accounts become ungrouped.
+#[tauri::command]
+pub async fn delete_group(pool: State<'_, SqlitePool>, id: String) -> Result<()> {
+    let mut tx = pool.begin().await?;
+    fetch_group(&mut *tx, &id).await?;
+
+    sqlx::query("UPDATE accounts SET group_id = NULL WHERE group_id = ?")
+        .bind(&id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("DELETE FROM account_groups WHERE id = ?")
+        .bind(&id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Moves an account into a group, or out of any group when `group_id` is `None`.
+#[tauri::command]
+pub async fn assign_account_to_group(
+    pool: State<'_, SqlitePool>,
+    account_id: String,
+    group_id: Option<String>,
+) -> Result<Account> {
+    let mut account = fetch_account(pool.inner(), &account_id).await?;
+    if let Some(group_id) = &group_id {
+        fetch_group(pool.inner(), group_id).await?;
+    }
+
+    account.group_id = group_id;
+    account.updated_at = now();
+    sqlx::query("UPDATE accounts SET group_id = ?, updated_at = ? WHERE id = ?")
+        .bind(&account.group_id)
+        .bind(&account.updated_at)
+        .bind(&account.id)
+        .execute(pool.inner())
+        .await?;
+
+    Ok(account)
+}
+
+/// Rewrites every group's position from `ordered_ids`, which must list each
+/// existing group exactly once.
+#[tauri::command]
+pub async fn reorder_groups(
+    pool: State<'_, SqlitePool>,
+    ordered_ids: Vec<String>,
+) -> Result<Vec<AccountGroup>> {
+    let mut tx = pool.begin().await?;
+
+    let existing: HashSet<String> = sqlx::query_scalar("SELECT id FROM account_groups")
+        .fetch_all(&mut *tx)
+        .await?
+        .into_iter()
+        .collect();
+    validate_ordering(&ordered_ids, &existing, "group")?;
+
+    for (position, id) in ordered_ids.iter().enumerate() {
+        sqlx::query("UPDATE account_groups SET sort_order = ? WHERE id = ?")
+            .bind(position as i64)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    let groups =
+        sqlx::query_as::<_, AccountGroup>("SELECT * FROM account_groups ORDER BY sort_order, name")
+            .fetch_all(&mut *tx)
+            .await?;
+
+    tx.commit().await?;
+    Ok(groups)
+}
+
+/// What `accounts` add to net worth together, each converted into
+/// `base_currency` before they are added.
+async fn subtotal(
+    pool: &SqlitePool,
+    accounts: &[AccountWithBalance],
+    base_currency: &str,
+) -> Result<f64> {
+    let mut contributions = Vec::with_capacity(accounts.len());
+    for account in accounts {
+        contributions
+            .push(convert_balance(pool, account, account.net_contribution(), base_currency).await?);
+    }
+    Ok(round_amount(
+        sum_amounts(contributions),
+        minor_units_for(base_currency),
+    ))
+}
+
+#[tauri::command]
+pub async fn get_accounts_grouped(pool: State<'_, SqlitePool>) -> Result<GroupedAccounts> {
+    let groups =
+        sqlx::query_as::<_, AccountGroup>("SELECT * FROM account_groups ORDER BY sort_order, name")
+            .fetch_all(pool.inner())
+            .await?;
+    let accounts = fetch_accounts_with_balance(pool.inner()).await?;
+
+    let mut grouped: Vec<AccountGroupWithAccounts> = groups
+        .into_iter()
+        .map(|group| AccountGroupWithAccounts {
+            group,
+            accounts: Vec::new(),
+            subtotal: 0.0,
+        })
+        .collect();
+    let mut ungrouped = Vec::new();
+
+    for account in accounts {
+        let slot = account
+            .account
+            .group_id
+            .as_deref()
+            .and_then(|group_id| grouped.iter_mut().find(|g| g.group.id == group_id));
+        match slot {
+            Some(group) => group.accounts.push(account),
+            None => ungrouped.push(account),
+        }
+    }
+    let base_currency = load_settings(pool.inner()).await?.base_currency;
+    for group in &mut grouped {
+        group.subtotal = subtotal(pool.inner(), &group.accounts, &base_currency).await?;
+    }
+    let ungrouped_subtotal = subtotal(pool.inner(), &ungrouped, &base_currency).await?;
+
+    Ok(GroupedAccounts {
+        groups: grouped,
+        ungrouped,
+        ungrouped_subtotal,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::commands::accounts::create_account;
+    use crate::commands::exchange_rates::set_exchange_rate;
+    use crate::testing::{block_on, input, TestDb};
+
+    #[test]
+    fn subtotals_convert_each_account_to_the_base_currency() {
+        let db = TestDb::new();
+        let grouped = block_on(async {
+            set_exchange_rate(
+                db.state(),
+                "2025-01-01".into(),
+                "USD".into(),
+                "GBP".into(),
+                0.8,
+            )
+            .await
+            .unwrap();
+            let group = create_group(db.state(), input(json!({"name": "Savings"})))
+                .await
+                .unwrap();
+            for (name, currency) in [("Easy access", "GBP"), ("Dollar savings", "USD")] {
+                let account = create_account(
+                    db.state(),
+                    input(json!({
+                        "name": name,
+                        "account_type": "savings",
+                        "currency": currency,
+                        "opening_balance": 1000,
+                        "opened_at": "2025-01-01",
+                    })),
+                )
+                .await
+                .unwrap();
+                assign_account_to_group(db.state(), account.id, Some(group.id.clone()))
+                    .await
+                    .unwrap();
+            }
+            get_accounts_grouped(db.state()).await.unwrap()
+        });
+        assert_eq!(grouped.groups.len(), 1);
+        assert_eq!(grouped.groups[0].subtotal, 1000.0 + 800.0);
+        assert_eq!(grouped.ungrouped_subtotal, 0.0);
+    }
+}
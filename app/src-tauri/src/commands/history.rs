@@ -0,0 +1,94 @@
+use sqlx::{SqliteExecutor, SqlitePool};
+use tauri::State;
+use uuid::Uuid;
+
+use super::accounts::fetch_account;
+use crate::error::{Error, Result};
+use crate::models::{AccountFieldChange, BalanceEntryChange};
+
+const MAX_RECENT_CHANGES: u32 = 500;
+
+/// Appends a history row for `field` when its value actually changed.
+pub(crate) async fn record_change<'e>(
+    executor: impl SqliteExecutor<'e>,
+    account_id: &str,
+    field: &str,
+    old_value: Option<&str>,
+    new_value: Option<&str>,
+    changed_at: &str,
+) -> Result<()> {
+    if old_value == new_value {
+        return Ok(());
+    }
+    sqlx::query(
+        "INSERT INTO account_field_history (id, account_id, field, old_value, new_value, changed_at)
+         VALUES (?, ?, ?, ?, ?, ?)",
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(account_id)
+    .bind(field)
+    .bind(old_value)
+    .bind(new_value)
+    .bind(changed_at)
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+/// Changes to the account's name, institution and type, newest first.
+#[tauri::command]
+pub async fn get_account_history(
+    pool: State<'_, SqlitePool>,
+    account_id: String,
+) -> Result<Vec<AccountFieldChange>> {
+    fetch_account(pool.inner(), &account_id).await?;
+
+    let history = sqlx::query_as::<_, AccountFieldChange>(
+        "SELECT * FROM account_field_history
+         WHERE account_id = ?
+         ORDER BY changed_at DESC, rowid DESC",
+    )
+    .bind(&account_id)
+    .fetch_all(pool.inner())
+    .await?;
+    Ok(history)
+}
+
+/// Every recorded edit to one balance entry, newest first. Still available
+/// after the entry itself has been deleted.
+#[tauri::command]
+pub async fn get_balance_entry_history(
+    pool: State<'_, SqlitePool>,
+    entry_id: String,
+) -> Result<Vec<BalanceEntryChange>> {
+    let history = sqlx::query_as::<_, BalanceEntryChange>(
+        "SELECT * FROM balance_entry_history
+         WHERE entry_id = ?
+         ORDER BY changed_at DESC, rowid DESC",
+    )
+    .bind(&entry_id)
+    .fetch_all(pool.inner())
+    .await?;
+    Ok(history)
+}
+
+/// The latest `limit` balance entry edits across all accounts, newest first.
+#[tauri::command]
+pub async fn get_recent_changes(
+    pool: State<'_, SqlitePool>,
+    limit: u32,
+) -> Result<Vec<BalanceEntryChange>> {
+    if !(1..=MAX_RECENT_CHANGES).contains(&limit) {
+        return Err(Error::validation(
+            "limit",
+            format!("must be between 1 and {MAX_RECENT_CHANGES}"),
+        ));
+    }
+    let changes = sqlx::query_as::<_, BalanceEntryChange>(
+        "SELECT * FROM balance_entry_history ORDER BY changed_at DESC, rowid DESC LIMIT ?",
+    )
+    .bind(limit)
+    .fetch_all(pool.inner())
+    .await?;
+    Ok(changes)
+}
@@ -0,0 +1,867 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::{Days, NaiveDate};
+use sqlx::{SqliteConnection, SqlitePool};
+use tauri::State;
+
+use super::account_types::resolve_category;
+use super::accounts::{fetch_account, insert_account, new_account};
+use super::balances::{
+    check_sign, fetch_entries, insert_balance_entry, latest_allowed, new_balance_entry,
+    upsert_balance, upsert_entry,
+};
+use super::settings::load_settings;
+use super::undo::record_undo;
+use super::{non_empty, today_date};
+use crate::csv::{self, Record};
+use crate::dates;
+use crate::error::{Error, Result};
+use crate::models::{
+    from_minor, minor_units_for, to_minor, validate_currency, Account, AccountType,
+    BalanceCsvMapping, BalanceCsvReport, BalanceCsvRow, BalanceCsvRowStatus, BalanceEntry,
+    CreateBalanceInput, CsvImportReport, CsvRowReport, CsvRowStatus, DuplicatePolicy, EntrySource,
+    OfxImportReport, QifDateOrder, QifImportMode, QifImportOptions, UndoOperation,
+    DEFAULT_CURRENCY,
+};
+use crate::ofx;
+use crate::qif::{self, Transaction};
+use crate::series::month_end;
+
+/// Where each known column sits in the file; only `name` and `type` are required.
+struct AccountColumns {
+    name: usize,
+    account_type: usize,
+    institution: Option<usize>,
+    currency: Option<usize>,
+    description: Option<usize>,
+    external_ref: Option<usize>,
+}
+
+impl AccountColumns {
+    fn from_header(header: &[String]) -> Result<Self> {
+        let find = |column: &str| {
+            header
+                .iter()
+                .position(|h| h.trim().eq_ignore_ascii_case(column))
+        };
+        let required = |column: &str| {
+            find(column).ok_or_else(|| {
+                Error::validation("path", format!("the CSV has no '{column}' column"))
+            })
+        };
+        Ok(AccountColumns {
+            name: required("name")?,
+            account_type: required("type")?,
+            institution: find("institution"),
+            currency: find("currency"),
+            description: find("description"),
+            external_ref: find("external_ref"),
+        })
+    }
+}
+
+fn field(record: &Record, index: Option<usize>) -> Option<String> {
+    let value = record.fields.get(index?)?.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// Account names and institutions compare ignoring case.
+fn duplicate_key(name: &str, institution: Option<&str>) -> (String, String) {
+    (
+        name.trim().to_lowercase(),
+        institution.unwrap_or_default().trim().to_lowercase(),
+    )
+}
+
+async fn parse_account_row(
+    conn: &mut SqliteConnection,
+    columns: &AccountColumns,
+    record: &Record,
+) -> Result<Account> {
+    let name = field(record, Some(columns.name))
+        .ok_or_else(|| Error::validation("name", "must not be empty"))?;
+    let account_type: AccountType = field(record, Some(columns.account_type))
+        .ok_or_else(|| Error::validation("type", "must not be empty"))?
+        .parse()?;
+    let category = resolve_category(&mut *conn, &account_type).await?;
+    let currency = match field(record, columns.currency) {
+        Some(currency) => {
+            let currency = currency.to_uppercase();
+            validate_currency(&currency)?;
+            currency
+        }
+        None => DEFAULT_CURRENCY.to_string(),
+    };
+
+    let mut account = new_account(&name, account_type, category, currency);
+    account.institution = non_empty(field(record, columns.institution));
+    account.description = non_empty(field(record, columns.description));
+    account.external_ref = field(record, columns.external_ref);
+    Ok(account)
+}
+
+/// Creates accounts from a CSV with a header row naming `name`, `type` and
+/// optionally `institution`, `currency`, `description` and `external_ref`. Every
+/// row is reported; bad rows and duplicates are skipped and the rest inserted in
+/// one transaction. A row is a duplicate when its `external_ref` is already in
+/// use, or failing that, when its name and institution are. With `dry_run`
+/// nothing is written.
+#[tauri::command]
+pub async fn import_accounts_csv(
+    pool: State<'_, SqlitePool>,
+    path: String,
+    dry_run: bool,
+) -> Result<CsvImportReport> {
+    let text = std::fs::read_to_string(&path)?;
+    let records = csv::parse(&text).map_err(|message| Error::validation("path", message))?;
+
+    let mut report = CsvImportReport {
+        dry_run,
+        created: 0,
+        skipped: 0,
+        errors: 0,
+        rows: Vec::new(),
+    };
+    let Some((header, rows)) = records.split_first() else {
+        return Ok(report);
+    };
+    let columns = AccountColumns::from_header(&header.fields)?;
+
+    let mut tx = pool.begin().await?;
+    let existing: Vec<(String, String, Option<String>, Option<String>)> =
+        sqlx::query_as("SELECT id, name, institution, external_ref FROM accounts")
+            .fetch_all(&mut *tx)
+            .await?;
+    let mut seen: HashSet<(String, String)> = existing
+        .iter()
+        .map(|(_, name, institution, _)| duplicate_key(name, institution.as_deref()))
+        .collect();
+    let mut refs: HashMap<String, (Option<String>, String)> = existing
+        .into_iter()
+        .filter_map(|(id, name, _, external_ref)| Some((external_ref?, (Some(id), name))))
+        .collect();
+
+    for record in rows {
+        let name = field(record, Some(columns.name)).unwrap_or_default();
+        let mut row = CsvRowReport {
+            line: record.line,
+            name,
+            status: CsvRowStatus::Created,
+            message: None,
+            account_id: None,
+        };
+
+        let parsed = parse_account_row(&mut tx, &columns, record).await;
+        match parsed {
+            Err(error) => {
+                row.status = CsvRowStatus::Error;
+                row.message = Some(error.to_string());
+            }
+            Ok(mut account) => {
+                let key = duplicate_key(&account.name, account.institution.as_deref());
+                let matched = account
+                    .external_ref
+                    .as_ref()
+                    .and_then(|external_ref| refs.get(external_ref));
+                if let Some((id, name)) = matched {
+                    row.status = CsvRowStatus::SkippedDuplicate;
+                    row.message = Some(format!("matches account '{name}' by external_ref"));
+                    row.account_id = id.clone();
+                } else if !seen.insert(key) {
+                    row.status = CsvRowStatus::SkippedDuplicate;
+                } else if !dry_run {
+                    match insert_account(&mut tx, &mut account).await {
+                        Ok(()) => row.account_id = Some(account.id.clone()),
+                        Err(error) => {
+                            row.status = CsvRowStatus::Error;
+                            row.message = Some(error.to_string());
+                        }
+                    }
+                }
+                if row.status == CsvRowStatus::Created {
+                    if let Some(external_ref) = account.external_ref {
+                        refs.insert(external_ref, (row.account_id.clone(), account.name));
+                    }
+                }
+            }
+        }
+
+        match row.status {
+            CsvRowStatus::Created => report.created += 1,
+            CsvRowStatus::SkippedDuplicate => report.skipped += 1,
+            CsvRowStatus::Error => report.errors += 1,
+        }
+        report.rows.push(row);
+    }
+
+    if dry_run {
+        tx.rollback().await?;
+    } else {
+        tx.commit().await?;
+    }
+    Ok(report)
+}
+
+/// Reads an amount such as `1,234.56`, `-£20` or `(20.00)`, the last being negative.
+fn parse_amount(text: &str, decimal_separator: char) -> Result<f64> {
+    let invalid = || Error::validation("balance", format!("'{text}' is not an amount"));
+    let trimmed = text.trim();
+    let (negative, trimmed) = match trimmed
+        .strip_prefix('(')
+        .and_then(|rest| rest.strip_suffix(')'))
+    {
+        Some(inner) => (true, inner),
+        None => (false, trimmed),
+    };
+    let thousands = if decimal_separator == ',' { '.' } else { ',' };
+    let cleaned: String = trimmed
+        .chars()
+        .filter(|c| *c != thousands && !c.is_whitespace() && !"£$€¥".contains(*c))
+        .map(|c| if c == decimal_separator { '.' } else { c })
+        .collect();
+    let amount: f64 = cleaned.parse().map_err(|_| invalid())?;
+    if !amount.is_finite() {
+        return Err(invalid());
+    }
+    Ok(if negative { -amount } else { amount })
+}
+
+fn parse_row_date(text: &str, format: Option<&str>) -> Result<String> {
+    let date = match format {
+        Some(format) => NaiveDate::parse_from_str(text.trim(), format).ok(),
+        None => dates::parse(text),
+    };
+    date.map(dates::format).ok_or_else(|| match format {
+        Some(format) => Error::validation("date", format!("'{text}' doesn't match '{format}'")),
+        None => Error::validation("date", format!("'{text}' is not a YYYY-MM-DD date")),
+    })
+}
+
+/// A balance row as read from the input, before it is checked against the account.
+struct BalanceRow {
+    line: usize,
+    parsed: Result<(String, f64)>,
+    notes: Option<String>,
+    /// Reported with the row even when it is written, e.g. an ambiguous date.
+    warning: Option<String>,
+}
+
+fn check_duplicate_policy(dry_run: bool, on_duplicate: Option<DuplicatePolicy>) -> Result<()> {
+    if !dry_run && on_duplicate.is_none() {
+        return Err(Error::validation(
+            "on_duplicate",
+            "choose whether to skip or overwrite existing entries",
+        ));
+    }
+    Ok(())
+}
+
+/// Checks `rows` against the account's existing entries and each other, and
+/// writes the good ones. The caller commits, or rolls back for a dry run.
+async fn write_balance_rows(
+    conn: &mut SqliteConnection,
+    account: &Account,
+    rows: Vec<BalanceRow>,
+    dry_run: bool,
+    on_duplicate: Option<DuplicatePolicy>,
+) -> Result<BalanceCsvReport> {
+    let settings = load_settings(&mut *conn).await?;
+    let latest_allowed = latest_allowed(&settings);
+    let existing: HashMap<String, BalanceEntry> = fetch_entries(&mut *conn, &account.id)
+        .await?
+        .into_iter()
+        .map(|entry| (entry.date.clone(), entry))
+        .collect();
+    let (mut before, mut after) = (Vec::new(), Vec::new());
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    let mut report = empty_report(dry_run);
+
+    for input in rows {
+        let mut row = BalanceCsvRow {
+            line: input.line,
+            date: None,
+            balance: None,
+            notes: input.notes,
+            status: BalanceCsvRowStatus::Created,
+            message: input.warning,
+        };
+
+        match input.parsed {
+            Err(error) => {
+                row.status = BalanceCsvRowStatus::Error;
+                row.message = Some(error.to_string());
+            }
+            Ok((date, _)) if date > latest_allowed => {
+                row.status = BalanceCsvRowStatus::Error;
+                row.message = Some(format!("{date} is too far in the future"));
+                row.date = Some(date);
+            }
+            Ok((date, _)) if seen.contains_key(&date) => {
+                row.status = BalanceCsvRowStatus::Error;
+                row.message = Some(format!("{date} already appears on line {}", seen[&date]));
+                row.date = Some(date);
+            }
+            Ok((date, balance)) => {
+                seen.insert(date.clone(), input.line);
+                let mut entry = new_balance_entry(account, date, balance, row.notes.clone());
+                entry.source = EntrySource::Import;
+                if let Some(sign) = check_sign(account, &mut entry, &settings) {
+                    let warning = if sign.normalised {
+                        "saved as positive, since liabilities are stored as the amount owed"
+                    } else {
+                        "negative, but liabilities are stored as the amount owed"
+                    };
+                    row.message = Some(match row.message.take() {
+                        Some(message) => format!("{message}; {warning}"),
+                        None => warning.to_string(),
+                    });
+                }
+                row.date = Some(entry.date.clone());
+                row.balance = Some(entry.balance);
+                let duplicate = existing.get(&entry.date);
+                if duplicate.is_some() {
+                    report.duplicates += 1;
+                }
+                row.status = match (duplicate, on_duplicate) {
+                    (None, _) => BalanceCsvRowStatus::Created,
+                    (Some(_), None) => BalanceCsvRowStatus::Duplicate,
+                    (Some(_), Some(DuplicatePolicy::Skip)) => BalanceCsvRowStatus::SkippedDuplicate,
+                    (Some(_), Some(DuplicatePolicy::Overwrite)) => BalanceCsvRowStatus::Overwritten,
+                };
+                match (row.status, duplicate) {
+                    (BalanceCsvRowStatus::Created, _) => {
+                        insert_balance_entry(&mut *conn, &entry).await?;
+                        after.push(entry);
+                    }
+                    (BalanceCsvRowStatus::Overwritten, Some(previous)) => {
+                        upsert_entry(&mut *conn, &entry).await?;
+                        // The upsert keeps the overwritten entry's id and creation time,
+                        // and its verification while the balance is unchanged.
+                        entry.id = previous.id.clone();
+                        entry.created_at = previous.created_at.clone();
+                        if entry.balance == previous.balance {
+                            entry.verified_at = previous.verified_at.clone();
+                        }
+                        before.push(previous.clone());
+                        after.push(entry);
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        match row.status {
+            BalanceCsvRowStatus::Created => report.created += 1,
+            BalanceCsvRowStatus::Overwritten => report.overwritten += 1,
+            BalanceCsvRowStatus::SkippedDuplicate => report.skipped += 1,
+            BalanceCsvRowStatus::Error => report.errors += 1,
+            BalanceCsvRowStatus::Duplicate => {}
+        }
+        report.rows.push(row);
+    }
+
+    if !dry_run {
+        record_undo(
+            conn,
+            UndoOperation::Import,
+            format!("Import {} balances into {}", after.len(), account.name),
+            &before,
+            &after,
+        )
+        .await?;
+    }
+    Ok(report)
+}
+
+fn empty_report(dry_run: bool) -> BalanceCsvReport {
+    BalanceCsvReport {
+        dry_run,
+        created: 0,
+        overwritten: 0,
+        skipped: 0,
+        duplicates: 0,
+        errors: 0,
+        rows: Vec::new(),
+    }
+}
+
+/// Reads an account's balance history from a CSV whose header row names the
+/// columns in `mapping`. Every row is reported; rows that don't parse are
+/// skipped and the rest written in one transaction. Writing needs
+/// `on_duplicate`, which decides what happens to rows dated the same as an
+/// existing entry; a dry run without it just flags them.
+#[tauri::command]
+pub async fn import_balances_csv(
+    pool: State<'_, SqlitePool>,
+    account_id: String,
+    path: String,
+    mapping: BalanceCsvMapping,
+    dry_run: bool,
+    on_duplicate: Option<DuplicatePolicy>,
+) -> Result<BalanceCsvReport> {
+    if !matches!(mapping.decimal_separator, '.' | ',') {
+        return Err(Error::validation("decimal_separator", "must be '.' or ','"));
+    }
+    check_duplicate_policy(dry_run, on_duplicate)?;
+
+    let text = std::fs::read_to_string(&path)?;
+    let records = csv::parse(&text).map_err(|message| Error::validation("path", message))?;
+    let Some((header, records)) = records.split_first() else {
+        return Ok(empty_report(dry_run));
+    };
+    let find = |column: &str| {
+        header
+            .fields
+            .iter()
+            .position(|h| h.trim().eq_ignore_ascii_case(column.trim()))
+            .ok_or_else(|| {
+                Error::validation("mapping", format!("the CSV has no '{column}' column"))
+            })
+    };
+    let date_column = find(&mapping.date_column)?;
+    let balance_column = find(&mapping.balance_column)?;
+    let notes_column = mapping.notes_column.as_deref().map(find).transpose()?;
+
+    let rows = records
+        .iter()
+        .map(|record| {
+            let text = |column: usize| record.fields.get(column).map_or("", String::as_str);
+            let parsed = parse_row_date(text(date_column), mapping.date_format.as_deref())
+                .and_then(|date| {
+                    let amount = parse_amount(text(balance_column), mapping.decimal_separator)?;
+                    Ok((date, if mapping.flip_sign { -amount } else { amount }))
+                });
+            BalanceRow {
+                line: record.line,
+                parsed,
+                notes: field(record, notes_column),
+                warning: None,
+            }
+        })
+        .collect();
+
+    let mut tx = pool.begin().await?;
+    let account = fetch_account(&mut *tx, &account_id).await?;
+    let report = write_balance_rows(&mut tx, &account, rows, dry_run, on_duplicate).await?;
+    if dry_run {
+        tx.rollback().await?;
+    } else {
+        tx.commit().await?;
+    }
+    Ok(report)
+}
+
+/// Numeric layouts that read differently day-first and month-first.
+const DAY_FIRST_FORMATS: &[&str] = &["%d/%m/%Y", "%d-%m-%Y", "%d.%m.%Y", "%d/%m/%y"];
+const MONTH_FIRST_FORMATS: &[&str] = &["%m/%d/%Y", "%m-%d-%Y", "%m.%d.%Y", "%m/%d/%y"];
+/// Layouts with the month spelled out, which can't be misread.
+const NAMED_MONTH_FORMATS: &[&str] = &[
+    "%d %b %Y",
+    "%d %B %Y",
+    "%d-%b-%Y",
+    "%d-%b-%y",
+    "%b %d %Y",
+    "%B %d %Y",
+    "%b %d, %Y",
+    "%B %d, %Y",
+];
+
+/// The ways a pasted date can be read.
+#[derive(Debug, Clone, Copy)]
+enum DateReading {
+    Exact(NaiveDate),
+    /// Only valid with the day first, e.g. `25/03/2024`.
+    DayFirst(NaiveDate),
+    /// Only valid with the month first, e.g. `03/25/2024`.
+    MonthFirst(NaiveDate),
+    /// Valid either way round, e.g. `03/04/2024`.
+    Ambiguous {
+        day_first: NaiveDate,
+        month_first: NaiveDate,
+    },
+}
+
+fn first_match(text: &str, formats: &[&str]) -> Option<NaiveDate> {
+    formats
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(text, format).ok())
+}
+
+fn read_pasted_date(text: &str) -> Option<DateReading> {
+    let text = text.trim();
+    if let Some(date) = dates::parse(text).or_else(|| first_match(text, NAMED_MONTH_FORMATS)) {
+        return Some(DateReading::Exact(date));
+    }
+    match (
+        first_match(text, DAY_FIRST_FORMATS),
+        first_match(text, MONTH_FIRST_FORMATS),
+    ) {
+        (Some(day_first), Some(month_first)) if day_first == month_first => {
+            Some(DateReading::Exact(day_first))
+        }
+        (Some(day_first), Some(month_first)) => Some(DateReading::Ambiguous {
+            day_first,
+            month_first,
+        }),
+        (Some(date), None) => Some(DateReading::DayFirst(date)),
+        (None, Some(date)) => Some(DateReading::MonthFirst(date)),
+        (None, None) => None,
+    }
+}
+
+/// Spreadsheets paste `1,234.56` or `1.234,56`; whichever separator comes last
+/// is the decimal point, and a lone comma is one only before one or two digits.
+fn guess_decimal_separator(text: &str) -> char {
+    match (text.rfind('.'), text.rfind(',')) {
+        (Some(dot), Some(comma)) if comma > dot => ',',
+        (None, Some(comma)) => {
+            let decimals = text[comma + 1..].trim_end_matches(')').trim().len();
+            if text.matches(',').count() == 1 && (1..=2).contains(&decimals) {
+                ','
+            } else {
+                '.'
+            }
+        }
+        _ => '.',
+    }
+}
+
+/// Reads balances pasted from a spreadsheet: a date column, a balance column
+/// and optionally notes, separated by tabs or commas, with or without a header
+/// row. Numeric dates are read day-first unless some row only makes sense
+/// month-first; rows that could be either are flagged with how they were read.
+/// The same report and duplicate handling as `import_balances_csv` apply.
+#[tauri::command]
+pub async fn import_balances_from_text(
+    pool: State<'_, SqlitePool>,
+    account_id: String,
+    text: String,
+    dry_run: bool,
+    on_duplicate: Option<DuplicatePolicy>,
+) -> Result<BalanceCsvReport> {
+    check_duplicate_policy(dry_run, on_duplicate)?;
+
+    let delimiter = if text.contains('\t') { '\t' } else { ',' };
+    let mut records =
+        csv::parse_with(&text, delimiter).map_err(|message| Error::validation("text", message))?;
+    let cell = |record: &Record, column: usize| {
+        record
+            .fields
+            .get(column)
+            .map_or_else(String::new, |value| value.trim().to_string())
+    };
+    if records
+        .first()
+        .is_some_and(|first| read_pasted_date(&cell(first, 0)).is_none())
+    {
+        records.remove(0);
+    }
+
+    let readings: Vec<Option<DateReading>> = records
+        .iter()
+        .map(|record| read_pasted_date(&cell(record, 0)))
+        .collect();
+    let month_first = readings
+        .iter()
+        .any(|reading| matches!(reading, Some(DateReading::MonthFirst(_))))
+        && !readings
+            .iter()
+            .any(|reading| matches!(reading, Some(DateReading::DayFirst(_))));
+
+    let rows = records
+        .iter()
+        .zip(readings)
+        .map(|(record, reading)| {
+            let date_text = cell(record, 0);
+            let mut warning = None;
+            let date = match reading {
+                Some(DateReading::Exact(date)) => Ok(date),
+                Some(DateReading::DayFirst(date)) if !month_first => Ok(date),
+                Some(DateReading::MonthFirst(date)) if month_first => Ok(date),
+                Some(DateReading::Ambiguous {
+                    day_first,
+                    month_first: other,
+                }) => {
+                    let (read, alternative) = if month_first {
+                        (other, day_first)
+                    } else {
+                        (day_first, other)
+                    };
+                    warning = Some(format!(
+                        "'{date_text}' read as {}; it could also be {}",
+                        read.format("%-d %B %Y"),
+                        alternative.format("%-d %B %Y")
+                    ));
+                    Ok(read)
+                }
+                Some(_) => Err(Error::validation(
+                    "date",
+                    format!("'{date_text}' doesn't match the day/month order of the other rows"),
+                )),
+                None => Err(Error::validation(
+                    "date",
+                    format!("'{date_text}' is not a date"),
+                )),
+            };
+            let balance_text = cell(record, 1);
+            let parsed = date.and_then(|date| {
+                let balance = parse_amount(&balance_text, guess_decimal_separator(&balance_text))?;
+                Ok((dates::format(date), balance))
+            });
+            BalanceRow {
+                line: record.line,
+                parsed,
+                notes: field(record, Some(2)),
+                warning,
+            }
+        })
+        .collect();
+
+    let mut tx = pool.begin().await?;
+    let account = fetch_account(&mut *tx, &account_id).await?;
+    let report = write_balance_rows(&mut tx, &account, rows, dry_run, on_duplicate).await?;
+    if dry_run {
+        tx.rollback().await?;
+    } else {
+        tx.commit().await?;
+    }
+    Ok(report)
+}
+
+/// Takes the closing balance from an OFX or QFX statement and records it on
+/// the statement date, overwriting any entry already there. The ledger balance
+/// is used, or the available balance when the statement has none. When the
+/// file holds several statements, `statement_account` picks one by its
+/// `ACCTID`; without it nothing is written and the report lists them. A
+/// statement in another currency is converted into the account's.
+#[tauri::command]
+pub async fn import_ofx(
+    pool: State<'_, SqlitePool>,
+    account_id: String,
+    path: String,
+    dry_run: bool,
+    statement_account: Option<String>,
+) -> Result<OfxImportReport> {
+    let text = String::from_utf8_lossy(&std::fs::read(&path)?).into_owned();
+    let statements = ofx::parse(&text)?;
+    let mut report = OfxImportReport {
+        dry_run,
+        statements,
+        selected: None,
+        balance: None,
+        date: None,
+        currency_mismatch: None,
+        result: None,
+    };
+    let statement = match (&statement_account, report.statements.as_slice()) {
+        (None, []) => return Err(Error::validation("path", "the file has no statements")),
+        (None, [only]) => only,
+        (None, _) => return Ok(report),
+        (Some(wanted), statements) => statements
+            .iter()
+            .find(|statement| statement.account_id == wanted.trim())
+            .ok_or_else(|| {
+                Error::validation(
+                    "statement_account",
+                    format!("the file has no statement for account '{wanted}'"),
+                )
+            })?,
+    };
+    let (balance, date) = match (statement.ledger_balance, statement.available_balance) {
+        (Some(balance), _) => (balance, statement.ledger_date.clone()),
+        (None, Some(balance)) => (balance, statement.available_date.clone()),
+        (None, None) => unreachable!("the parser rejects statements without a balance"),
+    };
+    let Some(date) = date else {
+        return Err(Error::validation(
+            "path",
+            "the statement balance has no DTASOF date",
+        ));
+    };
+
+    let mut tx = pool.begin().await?;
+    let account = fetch_account(&mut *tx, &account_id).await?;
+    let original_currency = statement
+        .currency
+        .clone()
+        .filter(|currency| *currency != account.currency);
+    if let Some(currency) = &original_currency {
+        report.currency_mismatch = Some(format!(
+            "the statement is in {currency} but the account is in {}; the balance was converted",
+            account.currency
+        ));
+    }
+    let input = CreateBalanceInput {
+        account_id,
+        date: date.clone(),
+        balance,
+        notes: None,
+        original_currency,
+        original_amount: None,
+        confirm_outlier: true,
+    };
+    let selected = statement.account_id.clone();
+    let result = upsert_balance(&mut tx, input, EntrySource::Import).await?;
+    if dry_run {
+        tx.rollback().await?;
+    } else {
+        tx.commit().await?;
+    }
+
+    report.selected = Some(selected);
+    report.balance = Some(balance);
+    report.date = Some(date);
+    report.result = Some(result);
+    Ok(report)
+}
+
+/// Reads balance history from a QIF file, in either `mode`. Numeric dates are
+/// read the way round the options say, or failing that the way the file's
+/// dates show they must be; a file whose dates all read both ways needs
+/// `date_order`. The generated balances go through the same checks, report and
+/// duplicate handling as `import_balances_csv`.
+#[tauri::command]
+pub async fn import_qif(
+    pool: State<'_, SqlitePool>,
+    account_id: String,
+    path: String,
+    mode: QifImportMode,
+    dry_run: bool,
+    on_duplicate: Option<DuplicatePolicy>,
+    options: Option<QifImportOptions>,
+) -> Result<BalanceCsvReport> {
+    check_duplicate_policy(dry_run, on_duplicate)?;
+    let QifImportOptions {
+        opening_balance,
+        date_order,
+    } = options.unwrap_or_default();
+    if opening_balance.is_some_and(|balance| !balance.is_finite()) {
+        return Err(Error::validation("opening_balance", "must be a number"));
+    }
+
+    let text = std::fs::read_to_string(&path)?;
+    let transactions = qif::parse(&text).map_err(|message| Error::validation("path", message))?;
+    if transactions.is_empty() {
+        return Ok(empty_report(dry_run));
+    }
+    let order = match date_order {
+        Some(order) => order,
+        None => qif::detect_date_order(transactions.iter().filter_map(|t| t.date.as_deref()))
+            .map_err(|message| Error::validation("date_order", message))?
+            .ok_or_else(|| {
+                Error::validation(
+                    "date_order",
+                    "every date in the file could be day-first or month-first; say which it uses",
+                )
+            })?,
+    };
+    let order_name = match order {
+        QifDateOrder::DayFirst => "day-first",
+        QifDateOrder::MonthFirst => "month-first",
+    };
+    let read = |transaction: &Transaction| -> Result<(NaiveDate, f64)> {
+        let text = transaction
+            .date
+            .as_deref()
+            .ok_or_else(|| Error::validation("date", "the record has no D line"))?;
+        let date = qif::parse_date(text, order).ok_or_else(|| {
+            Error::validation("date", format!("'{text}' is not a {order_name} date"))
+        })?;
+        let amount = transaction
+            .amount
+            .as_deref()
+            .ok_or_else(|| Error::validation("balance", "the record has no T line"))?;
+        Ok((date, parse_amount(amount, '.')?))
+    };
+
+    let mut tx = pool.begin().await?;
+    let account = fetch_account(&mut *tx, &account_id).await?;
+    let rows = match mode {
+        QifImportMode::BalanceRecordsOnly => transactions
+            .iter()
+            .map(|transaction| BalanceRow {
+                line: transaction.line,
+                parsed: read(transaction).map(|(date, amount)| (dates::format(date), amount)),
+                notes: non_empty(transaction.memo.clone().or(transaction.payee.clone())),
+                warning: None,
+            })
+            .collect(),
+        QifImportMode::ClosingBalancePerMonth => {
+            let mut readable = Vec::new();
+            let mut unreadable = Vec::new();
+            for transaction in &transactions {
+                match read(transaction) {
+                    Ok((date, amount)) => readable.push((date, amount, transaction.line)),
+                    Err(error) => unreadable.push(BalanceRow {
+                        line: transaction.line,
+                        parsed: Err(error),
+                        notes: None,
+                        warning: None,
+                    }),
+                }
+            }
+            readable.sort_by_key(|(date, _, _)| *date);
+            let warning = (!unreadable.is_empty()).then(|| {
+                format!(
+                    "{} transactions couldn't be read, so this balance may be off",
+                    unreadable.len()
+                )
+            });
+            let mut rows =
+                monthly_closing_rows(&account, opening_balance.unwrap_or(0.0), &readable);
+            for row in &mut rows {
+                row.warning = warning.clone();
+            }
+            rows.extend(unreadable);
+            rows
+        }
+    };
+
+    let report = write_balance_rows(&mut tx, &account, rows, dry_run, on_duplicate).await?;
+    if dry_run {
+        tx.rollback().await?;
+    } else {
+        tx.commit().await?;
+    }
+    Ok(report)
+}
+
+/// The balance each month closed on, from `opening` plus the `transactions`
+/// (date, amount and line, in date order) up to its end, for every month from
+/// the first transaction's to the last's. The current month is dated today.
+fn monthly_closing_rows(
+    account: &Account,
+    opening: f64,
+    transactions: &[(NaiveDate, f64, usize)],
+) -> Vec<BalanceRow> {
+    let (Some(first), Some(last)) = (transactions.first(), transactions.last()) else {
+        return Vec::new();
+    };
+    let minor_units = minor_units_for(&account.currency);
+    let mut total = to_minor(opening, minor_units);
+    let mut line = first.2;
+    let mut next = 0;
+    let mut rows = Vec::new();
+    let mut month = month_end(first.0);
+    while month <= month_end(last.0) {
+        while let Some((date, amount, transaction_line)) = transactions.get(next) {
+            if *date > month {
+                break;
+            }
+            total += to_minor(*amount, minor_units);
+            line = *transaction_line;
+            next += 1;
+        }
+        rows.push(BalanceRow {
+            line,
+            parsed: Ok((
+                dates::format(month.min(today_date())),
+                from_minor(total, minor_units),
+            )),
+            notes: None,
+            warning: None,
+        });
+        month = month_end(month + Days::new(1));
+    }
+    rows
+}
@@ -0,0 +1,159 @@
+use chrono::NaiveDate;
+use sqlx::{SqliteExecutor, SqlitePool};
+use tauri::State;
+use uuid::Uuid;
+
+use super::settings::load_settings;
+use super::{non_empty, non_empty_patch, now};
+use crate::error::{Error, Result};
+use crate::models::{
+    minor_units_for, round_amount, CreateIncomeRecordInput, IncomeRecord, UpdateIncomeRecordInput,
+};
+
+/// The first day of `value`'s month, given as `YYYY-MM`.
+pub(crate) fn parse_month(field: &'static str, value: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(&format!("{}-01", value.trim()), "%Y-%m-%d")
+        .map_err(|_| Error::validation(field, format!("'{value}' is not a month as YYYY-MM")))
+}
+
+pub(crate) fn format_month(month: NaiveDate) -> String {
+    month.format("%Y-%m").to_string()
+}
+
+async fn fetch_income_record<'e>(
+    executor: impl SqliteExecutor<'e>,
+    id: &str,
+) -> Result<IncomeRecord> {
+    sqlx::query_as::<_, IncomeRecord>("SELECT * FROM income_records WHERE id = ?")
+        .bind(id)
+        .fetch_optional(executor)
+        .await?
+        .ok_or_else(|| Error::not_found("income record", id))
+}
+
+/// Fails when another record than `id` already covers `month`.
+async fn check_month_free<'e>(
+    executor: impl SqliteExecutor<'e>,
+    month: &str,
+    id: Option<&str>,
+) -> Result<()> {
+    let taken: bool = sqlx::query_scalar(
+        "SELECT EXISTS(SELECT 1 FROM income_records WHERE month = ? AND id IS NOT ?)",
+    )
+    .bind(month)
+    .bind(id)
+    .fetch_one(executor)
+    .await?;
+    if taken {
+        return Err(Error::Conflict(format!(
+            "income is already recorded for {month}"
+        )));
+    }
+    Ok(())
+}
+
+/// `amount` rounded to the base currency, provided it isn't negative.
+async fn check_income(pool: &SqlitePool, amount: f64) -> Result<f64> {
+    if !amount.is_finite() || amount < 0.0 {
+        return Err(Error::validation(
+            "net_income",
+            "must be zero or a positive number",
+        ));
+    }
+    let base_currency = load_settings(pool).await?.base_currency;
+    Ok(round_amount(amount, minor_units_for(&base_currency)))
+}
+
+#[tauri::command]
+pub async fn create_income_record(
+    pool: State<'_, SqlitePool>,
+    input: CreateIncomeRecordInput,
+) -> Result<IncomeRecord> {
+    let month = format_month(parse_month("month", &input.month)?);
+    check_month_free(pool.inner(), &month, None).await?;
+    let record = IncomeRecord {
+        id: Uuid::new_v4().to_string(),
+        month,
+        net_income: check_income(pool.inner(), input.net_income).await?,
+        notes: non_empty(input.notes),
+        created_at: now(),
+    };
+
+    sqlx::query(
+        "INSERT INTO income_records (id, month, net_income, notes, created_at)
+         VALUES (?, ?, ?, ?, ?)",
+    )
+    .bind(&record.id)
+    .bind(&record.month)
+    .bind(record.net_income)
+    .bind(&record.notes)
+    .bind(&record.created_at)
+    .execute(pool.inner())
+    .await?;
+
+    Ok(record)
+}
+
+/// The records from month `start` to month `end` (both optional and
+/// inclusive, as `YYYY-MM`), oldest first.
+#[tauri::command]
+pub async fn get_income_records(
+    pool: State<'_, SqlitePool>,
+    start: Option<String>,
+    end: Option<String>,
+) -> Result<Vec<IncomeRecord>> {
+    let month = |field, value: Option<String>| {
+        value
+            .map(|value| parse_month(field, &value).map(format_month))
+            .transpose()
+    };
+    let records = sqlx::query_as::<_, IncomeRecord>(
+        "SELECT * FROM income_records
+         WHERE (?1 IS NULL OR month >= ?1) AND (?2 IS NULL OR month <= ?2)
+         ORDER BY month",
+    )
+    .bind(month("start", start)?)
+    .bind(month("end", end)?)
+    .fetch_all(pool.inner())
+    .await?;
+    Ok(records)
+}
+
+#[tauri::command]
+pub async fn update_income_record(
+    pool: State<'_, SqlitePool>,
+    id: String,
+    input: UpdateIncomeRecordInput,
+) -> Result<IncomeRecord> {
+    let mut record = fetch_income_record(pool.inner(), &id).await?;
+    if let Some(month) = input.month {
+        record.month = format_month(parse_month("month", &month)?);
+        check_month_free(pool.inner(), &record.month, Some(&id)).await?;
+    }
+    if let Some(net_income) = input.net_income {
+        record.net_income = check_income(pool.inner(), net_income).await?;
+    }
+    non_empty_patch(input.notes).apply(&mut record.notes);
+
+    sqlx::query("UPDATE income_records SET month = ?, net_income = ?, notes = ? WHERE id = ?")
+        .bind(&record.month)
+        .bind(record.net_income)
+        .bind(&record.notes)
+        .bind(&id)
+        .execute(pool.inner())
+        .await?;
+
+    Ok(record)
+}
+
+#[tauri::command]
+pub async fn delete_income_record(pool: State<'_, SqlitePool>, id: String) -> Result<()> {
+    let deleted = sqlx::query("DELETE FROM income_records WHERE id = ?")
+        .bind(&id)
+        .execute(pool.inner())
+        .await?;
+    if deleted.rows_affected() == 0 {
+        return Err(Error::not_found("income record", id));
+    }
+    Ok(())
+}
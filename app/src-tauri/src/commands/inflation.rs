@@ -0,0 +1,204 @@
+use chrono::{Datelike, NaiveDate};
+use sqlx::{SqliteConnection, SqliteExecutor, SqlitePool};
+use tauri::State;
+
+use super::income::{format_month, parse_month};
+use super::now;
+use crate::csv;
+use crate::dates;
+use crate::error::{Error, Result};
+use crate::models::{CpiImportReport, CpiRate, CpiRowError, InflationOptions};
+
+/// Turns amounts into the prices of a reference month.
+pub(crate) struct Deflator {
+    pub reference_month: String,
+    reference: f64,
+    reference_estimated: bool,
+    /// The first day of each month with a value, ascending.
+    rates: Vec<(NaiveDate, f64)>,
+}
+
+impl Deflator {
+    /// The CPI for `date`'s month, or the nearest earlier month's with `true`.
+    /// Months before the first value take that one, also with `true`.
+    fn index_on(rates: &[(NaiveDate, f64)], date: NaiveDate) -> (f64, bool) {
+        let month = date.with_day(1).expect("every month has a first day");
+        match rates.partition_point(|(on, _)| *on <= month) {
+            0 => (rates[0].1, true),
+            after => {
+                let (on, index) = rates[after - 1];
+                (index, on != month)
+            }
+        }
+    }
+
+    /// What one unit on `date` is worth in the reference month's prices, and
+    /// whether either CPI value was borrowed from another month.
+    pub fn factor(&self, date: NaiveDate) -> (f64, bool) {
+        let (index, estimated) = Self::index_on(&self.rates, date);
+        (
+            self.reference / index,
+            estimated || self.reference_estimated,
+        )
+    }
+}
+
+/// A deflator for `options`, or `None` when they don't ask for real terms.
+pub(crate) async fn load_deflator(
+    conn: &mut SqliteConnection,
+    options: &InflationOptions,
+) -> Result<Option<Deflator>> {
+    if !options.inflation_adjusted {
+        return Ok(None);
+    }
+    let rates = fetch_cpi_rates(&mut *conn)
+        .await?
+        .into_iter()
+        .map(|rate| Ok((parse_month("month", &rate.month)?, rate.index_value)))
+        .collect::<Result<Vec<_>>>()?;
+    let Some(&(latest, _)) = rates.last() else {
+        return Err(Error::NoData("no CPI values are recorded".to_string()));
+    };
+    let reference_month = match &options.reference_month {
+        Some(month) => parse_month("reference_month", month)?,
+        None => latest,
+    };
+    let (reference, reference_estimated) = Deflator::index_on(&rates, reference_month);
+    Ok(Some(Deflator {
+        reference_month: format_month(reference_month),
+        reference,
+        reference_estimated,
+        rates,
+    }))
+}
+
+pub(crate) async fn fetch_cpi_rates<'e>(executor: impl SqliteExecutor<'e>) -> Result<Vec<CpiRate>> {
+    let rates = sqlx::query_as::<_, CpiRate>("SELECT * FROM cpi_rates ORDER BY month")
+        .fetch_all(executor)
+        .await?;
+    Ok(rates)
+}
+
+fn check_index(index_value: f64) -> Result<f64> {
+    if !index_value.is_finite() || index_value <= 0.0 {
+        return Err(Error::validation(
+            "index_value",
+            "must be a positive number",
+        ));
+    }
+    Ok(index_value)
+}
+
+async fn upsert_cpi_rate(
+    conn: &mut SqliteConnection,
+    month: &str,
+    index_value: f64,
+) -> Result<CpiRate> {
+    sqlx::query(
+        "INSERT INTO cpi_rates (month, index_value, created_at) VALUES (?, ?, ?)
+         ON CONFLICT(month) DO UPDATE SET
+            index_value = excluded.index_value,
+            created_at = excluded.created_at",
+    )
+    .bind(month)
+    .bind(index_value)
+    .bind(now())
+    .execute(&mut *conn)
+    .await?;
+    let stored = sqlx::query_as::<_, CpiRate>("SELECT * FROM cpi_rates WHERE month = ?")
+        .bind(month)
+        .fetch_one(&mut *conn)
+        .await?;
+    Ok(stored)
+}
+
+/// Records the CPI for `month` (as `YYYY-MM`), replacing any value already
+/// stored for it.
+#[tauri::command]
+pub async fn set_cpi_rate(
+    pool: State<'_, SqlitePool>,
+    month: String,
+    index_value: f64,
+) -> Result<CpiRate> {
+    let month = format_month(parse_month("month", &month)?);
+    let index_value = check_index(index_value)?;
+    let mut conn = pool.acquire().await?;
+    upsert_cpi_rate(&mut conn, &month, index_value).await
+}
+
+/// Every stored value, oldest first.
+#[tauri::command]
+pub async fn get_cpi_rates(pool: State<'_, SqlitePool>) -> Result<Vec<CpiRate>> {
+    fetch_cpi_rates(pool.inner()).await
+}
+
+#[tauri::command]
+pub async fn delete_cpi_rate(pool: State<'_, SqlitePool>, month: String) -> Result<()> {
+    let month = format_month(parse_month("month", &month)?);
+    let deleted = sqlx::query("DELETE FROM cpi_rates WHERE month = ?")
+        .bind(&month)
+        .execute(pool.inner())
+        .await?;
+    if deleted.rows_affected() == 0 {
+        return Err(Error::not_found("CPI value", month));
+    }
+    Ok(())
+}
+
+/// The month of one row: `YYYY-MM`, or a full date taken as its month.
+fn parse_row_month(value: &str) -> Result<String> {
+    match dates::parse(value) {
+        Some(date) => Ok(format_month(date)),
+        None => parse_month("month", value).map(format_month),
+    }
+}
+
+/// Reads CPI values from a CSV with a header row naming `month` and
+/// `index_value`, in any order and case. Months may be `YYYY-MM` or full dates.
+/// Rows that can't be read are reported and the rest saved in one
+/// transaction, replacing the values already stored for their months.
+#[tauri::command]
+pub async fn import_cpi_csv(pool: State<'_, SqlitePool>, path: String) -> Result<CpiImportReport> {
+    let text = std::fs::read_to_string(&path)?;
+    let records = csv::parse(&text).map_err(|message| Error::validation("path", message))?;
+
+    let mut report = CpiImportReport {
+        imported: 0,
+        errors: Vec::new(),
+    };
+    let Some((header, rows)) = records.split_first() else {
+        return Ok(report);
+    };
+    let column = |name: &str| {
+        header
+            .fields
+            .iter()
+            .position(|field| field.trim().eq_ignore_ascii_case(name))
+            .ok_or_else(|| Error::validation("path", format!("the CSV has no '{name}' column")))
+    };
+    let (month_column, index_column) = (column("month")?, column("index_value")?);
+
+    let mut tx = pool.begin().await?;
+    for record in rows {
+        let field = |index: usize| record.fields.get(index).map_or("", |field| field.trim());
+        let parsed = parse_row_month(field(month_column)).and_then(|month| {
+            let index = field(index_column);
+            let index_value = index.parse::<f64>().map_err(|_| {
+                Error::validation("index_value", format!("'{index}' is not a number"))
+            })?;
+            Ok((month, check_index(index_value)?))
+        });
+        match parsed {
+            Ok((month, index_value)) => {
+                upsert_cpi_rate(&mut tx, &month, index_value).await?;
+                report.imported += 1;
+            }
+            Err(error) => report.errors.push(CpiRowError {
+                line: record.line,
+                message: error.to_string(),
+            }),
+        }
+    }
+    tx.commit().await?;
+    Ok(report)
+}
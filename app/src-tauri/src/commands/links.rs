@@ -0,0 +1,170 @@
+use chrono::NaiveDate;
+use sqlx::SqlitePool;
+use tauri::State;
+
+use super::accounts::fetch_account;
+use super::balances::fetch_points;
+use super::{now, parse_date};
+use crate::dates;
+use crate::error::{Error, Result};
+use crate::models::{sum_amounts, Account, AccountCategory, BalanceUsed, LtvPoint, LtvReport};
+use crate::series::{interpolate_until, month_ends, Point};
+
+/// Records that `liability_id` (e.g. a mortgage) is secured on `asset_id`.
+#[tauri::command]
+pub async fn link_accounts(
+    pool: State<'_, SqlitePool>,
+    liability_id: String,
+    asset_id: String,
+) -> Result<Account> {
+    if liability_id == asset_id {
+        return Err(Error::validation(
+            "asset_id",
+            "an account cannot be linked to itself",
+        ));
+    }
+
+    let mut liability = fetch_account(pool.inner(), &liability_id).await?;
+    let asset = fetch_account(pool.inner(), &asset_id).await?;
+    if liability.category != AccountCategory::Liability {
+        return Err(Error::validation(
+            "liability_id",
+            format!("'{}' is not a liability", liability.name),
+        ));
+    }
+    if asset.category != AccountCategory::Asset {
+        return Err(Error::validation(
+            "asset_id",
+            format!("'{}' is not an asset", asset.name),
+        ));
+    }
+
+    liability.linked_account_id = Some(asset_id);
+    liability.updated_at = now();
+    sqlx::query("UPDATE accounts SET linked_account_id = ?, updated_at = ? WHERE id = ?")
+        .bind(&liability.linked_account_id)
+        .bind(&liability.updated_at)
+        .bind(&liability.id)
+        .execute(pool.inner())
+        .await?;
+
+    Ok(liability)
+}
+
+#[tauri::command]
+pub async fn unlink_account(pool: State<'_, SqlitePool>, account_id: String) -> Result<Account> {
+    let mut account = fetch_account(pool.inner(), &account_id).await?;
+
+    account.linked_account_id = None;
+    account.updated_at = now();
+    sqlx::query("UPDATE accounts SET linked_account_id = NULL, updated_at = ? WHERE id = ?")
+        .bind(&account.updated_at)
+        .bind(&account.id)
+        .execute(pool.inner())
+        .await?;
+
+    Ok(account)
+}
+
+fn closed_date(closed_at: Option<&str>) -> Result<Option<NaiveDate>> {
+    closed_at
+        .map(|value| parse_date("closed_at", value))
+        .transpose()
+}
+
+/// Loan-to-value of an asset against every liability linked to it.
+#[tauri::command]
+pub async fn get_ltv(pool: State<'_, SqlitePool>, asset_id: String) -> Result<LtvReport> {
+    let asset = fetch_account(pool.inner(), &asset_id).await?;
+    if asset.category != AccountCategory::Asset {
+        return Err(Error::validation(
+            "asset_id",
+            format!("'{}' is not an asset", asset.name),
+        ));
+    }
+
+    let liabilities: Vec<(String, Option<String>)> = sqlx::query_as(
+        "SELECT id, closed_at FROM accounts WHERE linked_account_id = ? ORDER BY name",
+    )
+    .bind(&asset_id)
+    .fetch_all(pool.inner())
+    .await?;
+
+    let asset_points = fetch_points(pool.inner(), &asset_id, false).await?;
+    let asset_closed = closed_date(asset.closed_at.as_deref())?;
+    let mut liability_points = Vec::with_capacity(liabilities.len());
+    for (id, closed_at) in &liabilities {
+        let points: Vec<Point> = fetch_points(pool.inner(), id, false)
+            .await?
+            .into_iter()
+            .map(|p| Point {
+                balance: p.balance.abs(),
+                ..p
+            })
+            .collect();
+        liability_points.push((id.clone(), closed_date(closed_at.as_deref())?, points));
+    }
+
+    let used = |account_id: &str, point: &Point| BalanceUsed {
+        account_id: account_id.to_string(),
+        date: dates::format(point.date),
+        balance: point.balance,
+    };
+    let asset_balance = asset_points.last().map(|p| used(&asset_id, p));
+    let liability_balances: Vec<BalanceUsed> = liability_points
+        .iter()
+        .filter(|(_, closed_at, _)| closed_at.is_none())
+        .filter_map(|(id, _, points)| points.last().map(|p| used(id, p)))
+        .collect();
+
+    let current_ltv_percent = match &asset_balance {
+        Some(asset) if asset.balance > 0.0 && !liability_balances.is_empty() => {
+            let owed = sum_amounts(liability_balances.iter().map(|l| l.balance));
+            Some(owed / asset.balance * 100.0)
+        }
+        _ => None,
+    };
+
+    let mut series = Vec::new();
+    let first_liability = liability_points
+        .iter()
+        .filter_map(|(_, _, points)| points.first().map(|p| p.date))
+        .min();
+    let last_date = asset_points
+        .iter()
+        .chain(liability_points.iter().flat_map(|(_, _, points)| points))
+        .map(|p| p.date)
+        .max();
+    if let (Some(asset_start), Some(liability_start), Some(end)) = (
+        asset_points.first().map(|p| p.date),
+        first_liability,
+        last_date,
+    ) {
+        for date in month_ends(asset_start.max(liability_start), end) {
+            let Some(asset_balance) = interpolate_until(&asset_points, date, asset_closed) else {
+                continue;
+            };
+            let liability_balance: f64 = liability_points
+                .iter()
+                .filter_map(|(_, closed_at, points)| interpolate_until(points, date, *closed_at))
+                .sum();
+            if asset_balance <= 0.0 {
+                continue;
+            }
+            series.push(LtvPoint {
+                date: dates::format(date),
+                asset_balance,
+                liability_balance,
+                ltv_percent: liability_balance / asset_balance * 100.0,
+            });
+        }
+    }
+
+    Ok(LtvReport {
+        asset_id,
+        current_ltv_percent,
+        asset_balance,
+        liability_balances,
+        series,
+    })
+}
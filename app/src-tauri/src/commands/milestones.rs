@@ -0,0 +1,1121 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use chrono::{Datelike, Days, Months, NaiveDate};
+use sqlx::sqlite::SqliteArguments;
+use sqlx::{Sqlite, SqliteConnection, SqlitePool};
+use tauri::State;
+use uuid::Uuid;
+
+use super::accounts::fetch_account;
+use super::analytics::{net_worth_accounts, totals_on, value_accounts};
+use super::balances::{
+    contains_pattern, fetch_entries, fetch_points, DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE,
+};
+use super::flows::balance_on;
+use super::settings::load_settings;
+use super::snapshots::snapshot_balance;
+use super::{non_empty, non_empty_patch, normalize_date, now, parse_date, validate_ordering};
+use crate::dates;
+use crate::error::{Error, Result};
+use crate::models::{
+    format_amount, minor_units_for, round_amount, sum_amounts, Account, AccountCategory,
+    CreateMilestoneInput, FillMethod, Milestone, MilestoneAccountBalance, MilestoneCategory,
+    MilestoneEditScope, MilestoneFilter, MilestoneListItem, MilestoneProjection,
+    MilestoneProjectionStatus, MilestoneRecurrence, MilestoneTargetScope, MilestoneWithNetWorth,
+    TrendMethod, UpdateMilestoneInput,
+};
+use crate::series::{linear_fit, monthly_on_day, yearly_on_day, Point, DAYS_PER_MONTH};
+
+/// Fills in the linked accounts of each of `milestones`.
+pub(crate) async fn load_account_ids(
+    conn: &mut SqliteConnection,
+    milestones: &mut [Milestone],
+) -> Result<()> {
+    let links: Vec<(String, String)> = sqlx::query_as(
+        "SELECT milestone_id, account_id FROM milestone_accounts ORDER BY milestone_id, rowid",
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    let mut by_milestone: HashMap<String, Vec<String>> = HashMap::new();
+    for (milestone_id, account_id) in links {
+        by_milestone
+            .entry(milestone_id)
+            .or_default()
+            .push(account_id);
+    }
+    for milestone in milestones {
+        milestone.account_ids = by_milestone.remove(&milestone.id).unwrap_or_default();
+    }
+    Ok(())
+}
+
+/// Replaces the milestone's links with `account_ids`, each of which must exist.
+pub(crate) async fn write_account_ids(
+    conn: &mut SqliteConnection,
+    milestone_id: &str,
+    account_ids: &[String],
+) -> Result<()> {
+    sqlx::query("DELETE FROM milestone_accounts WHERE milestone_id = ?")
+        .bind(milestone_id)
+        .execute(&mut *conn)
+        .await?;
+    for account_id in account_ids {
+        fetch_account(&mut *conn, account_id).await?;
+        sqlx::query("INSERT INTO milestone_accounts (milestone_id, account_id) VALUES (?, ?)")
+            .bind(milestone_id)
+            .bind(account_id)
+            .execute(&mut *conn)
+            .await?;
+    }
+    Ok(())
+}
+
+/// `account_ids` without repeats, in their first order.
+pub(crate) fn distinct(account_ids: Vec<String>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    account_ids
+        .into_iter()
+        .filter(|id| seen.insert(id.clone()))
+        .collect()
+}
+
+async fn fetch_milestone(conn: &mut SqliteConnection, id: &str) -> Result<Milestone> {
+    let milestone = sqlx::query_as::<_, Milestone>("SELECT * FROM milestones WHERE id = ?")
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or_else(|| Error::not_found("milestone", id))?;
+    let mut milestones = [milestone];
+    load_account_ids(conn, &mut milestones).await?;
+    let [milestone] = milestones;
+    Ok(milestone)
+}
+
+/// Saves a new milestone after every other on its date, setting its `sort_order`.
+pub(crate) async fn insert_milestone(
+    conn: &mut SqliteConnection,
+    milestone: &mut Milestone,
+) -> Result<()> {
+    milestone.sort_order = next_sort_order(conn, &milestone.date).await?;
+    sqlx::query(
+        "INSERT INTO milestones (id, date, label, created_at, target_amount, target_scope, achieved_at, recurrence, category, icon, sort_order)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(&milestone.id)
+    .bind(&milestone.date)
+    .bind(&milestone.label)
+    .bind(&milestone.created_at)
+    .bind(milestone.target_amount)
+    .bind(milestone.target_scope)
+    .bind(&milestone.achieved_at)
+    .bind(milestone.recurrence)
+    .bind(milestone.category)
+    .bind(&milestone.icon)
+    .bind(milestone.sort_order)
+    .execute(&mut *conn)
+    .await?;
+    write_account_ids(conn, &milestone.id, &milestone.account_ids).await
+}
+
+#[tauri::command]
+pub async fn create_milestone(
+    pool: State<'_, SqlitePool>,
+    input: CreateMilestoneInput,
+) -> Result<Milestone> {
+    input.validate()?;
+    let mut milestone = Milestone {
+        id: Uuid::new_v4().to_string(),
+        date: normalize_date("date", &input.date)?,
+        label: input.label.trim().to_string(),
+        account_ids: distinct(input.account_ids),
+        legacy_account_id: None,
+        created_at: now(),
+        target_amount: input.target_amount,
+        target_scope: input.target_scope,
+        achieved_at: None,
+        recurrence: input.recurrence,
+        parent_id: None,
+        materialised_until: None,
+        category: input.category,
+        icon: non_empty(input.icon),
+        sort_order: 0,
+    };
+    milestone.validate()?;
+
+    let mut tx = pool.begin().await?;
+    insert_milestone(&mut tx, &mut milestone).await?;
+    tx.commit().await?;
+
+    Ok(milestone)
+}
+
+/// The milestone's target as it would be shown, e.g. `£10,000 net worth`: in the
+/// base currency for net worth, otherwise in its first account's.
+pub(crate) fn describe_target(
+    milestone: &Milestone,
+    accounts: &HashMap<String, Account>,
+    base_currency: &str,
+) -> Option<String> {
+    let target = milestone.target_amount?;
+    Some(match milestone.target_scope? {
+        MilestoneTargetScope::NetWorth => {
+            format!("{} net worth", format_amount(target, base_currency))
+        }
+        MilestoneTargetScope::Account => {
+            let currency = milestone
+                .account_ids
+                .first()
+                .and_then(|id| accounts.get(id))
+                .map_or(base_currency, |account| account.currency.as_str());
+            format_amount(target, currency)
+        }
+    })
+}
+
+pub(crate) async fn fetch_milestones(
+    conn: &mut SqliteConnection,
+    start: Option<String>,
+    end: Option<String>,
+    category: Option<MilestoneCategory>,
+) -> Result<Vec<Milestone>> {
+    let start = start
+        .map(|date| normalize_date("start", &date))
+        .transpose()?;
+    let end = end.map(|date| normalize_date("end", &date)).transpose()?;
+    let mut milestones = sqlx::query_as::<_, Milestone>(
+        "SELECT * FROM milestones
+         WHERE (?1 IS NULL OR date >= ?1) AND (?2 IS NULL OR date <= ?2)
+           AND (?3 IS NULL OR category = ?3)
+         ORDER BY date, sort_order, created_at",
+    )
+    .bind(start)
+    .bind(end)
+    .bind(category)
+    .fetch_all(&mut *conn)
+    .await?;
+    load_account_ids(conn, &mut milestones).await?;
+    Ok(milestones)
+}
+
+/// Milestones between `start` and `end` (both optional and inclusive), oldest
+/// first, whichever account they belong to.
+#[tauri::command]
+pub async fn get_milestones(
+    pool: State<'_, SqlitePool>,
+    start: Option<String>,
+    end: Option<String>,
+) -> Result<Vec<Milestone>> {
+    fetch_milestones(&mut *pool.acquire().await?, start, end, None).await
+}
+
+/// `get_milestones`, each with net worth and its accounts' balances as they
+/// stood on its date, interpolated between entries as in `compare_snapshots`.
+#[tauri::command]
+pub async fn get_milestones_with_net_worth(
+    pool: State<'_, SqlitePool>,
+    start: Option<String>,
+    end: Option<String>,
+) -> Result<Vec<MilestoneWithNetWorth>> {
+    let mut conn = pool.acquire().await?;
+    let milestones = fetch_milestones(&mut conn, start, end, None).await?;
+    let units = minor_units_for(&load_settings(&mut *conn).await?.base_currency);
+    let mut accounts = Vec::new();
+    for account in sqlx::query_as::<_, Account>("SELECT * FROM accounts ORDER BY sort_order, name")
+        .fetch_all(&mut *conn)
+        .await?
+    {
+        let entries = fetch_entries(&mut *conn, &account.id).await?;
+        accounts.push((account, entries));
+    }
+
+    let mut result = Vec::with_capacity(milestones.len());
+    for milestone in milestones {
+        let mut balances = HashMap::new();
+        for (account, entries) in &accounts {
+            if let Some(balance) = snapshot_balance(account, entries, &milestone.date)? {
+                balances.insert(account.id.as_str(), (account, balance));
+            }
+        }
+        let counted: Vec<_> = balances
+            .values()
+            .filter(|(account, _)| !account.exclude_from_net_worth)
+            .collect();
+        let net_worth = (!counted.is_empty()).then(|| {
+            let values = counted
+                .iter()
+                .map(|(account, (balance, _))| match account.category {
+                    AccountCategory::Asset => *balance,
+                    AccountCategory::Liability => -balance,
+                });
+            round_amount(sum_amounts(values), units)
+        });
+        let account_balances = milestone
+            .account_ids
+            .iter()
+            .map(|id| {
+                let balance = balances.get(id.as_str()).map(|(_, balance)| *balance);
+                MilestoneAccountBalance {
+                    account_id: id.clone(),
+                    balance: balance.map(|(balance, _)| balance),
+                    is_estimated: balance.is_some_and(|(_, estimated)| estimated),
+                }
+            })
+            .collect();
+        result.push(MilestoneWithNetWorth {
+            net_worth,
+            net_worth_estimated: counted.iter().any(|(_, (_, estimated))| *estimated),
+            account_balances,
+            milestone,
+        });
+    }
+    Ok(result)
+}
+
+/// The conditions of a `MilestoneFilter`, bound as ?1 to ?6.
+const MILESTONE_FILTER: &str = "(?1 IS NULL OR m.date >= ?1) AND (?2 IS NULL OR m.date <= ?2)
+    AND (?3 IS NULL OR m.category = ?3)
+    AND (?4 IS NULL OR (m.target_amount IS NOT NULL AND (m.achieved_at IS NOT NULL) = ?4))
+    AND (?5 IS NULL OR EXISTS (
+        SELECT 1 FROM milestone_accounts ma WHERE ma.milestone_id = m.id AND ma.account_id = ?5
+    ))
+    AND (?6 IS NULL OR m.label LIKE ?6 ESCAPE '\\')";
+
+fn bind_filter<'q, O>(
+    query: sqlx::query::QueryAs<'q, Sqlite, O, SqliteArguments<'q>>,
+    filter: &MilestoneFilter,
+) -> Result<sqlx::query::QueryAs<'q, Sqlite, O, SqliteArguments<'q>>> {
+    let start = filter
+        .start
+        .as_deref()
+        .map(|date| normalize_date("start", date))
+        .transpose()?;
+    let end = filter
+        .end
+        .as_deref()
+        .map(|date| normalize_date("end", date))
+        .transpose()?;
+    let search = filter.search.as_deref().and_then(|search| {
+        let search = search.trim();
+        (!search.is_empty()).then(|| contains_pattern(search))
+    });
+    Ok(query
+        .bind(start)
+        .bind(end)
+        .bind(filter.category)
+        .bind(filter.achieved)
+        .bind(filter.account_id.clone())
+        .bind(search))
+}
+
+/// One page of the milestones matching `filter`, oldest first, 50 at a time
+/// unless `limit` says otherwise.
+#[tauri::command]
+pub async fn list_milestones(
+    pool: State<'_, SqlitePool>,
+    filter: MilestoneFilter,
+) -> Result<Vec<MilestoneListItem>> {
+    let limit = filter.limit.unwrap_or(DEFAULT_PAGE_SIZE);
+    if !(1..=MAX_PAGE_SIZE).contains(&limit) {
+        return Err(Error::validation(
+            "limit",
+            format!("must be between 1 and {MAX_PAGE_SIZE}"),
+        ));
+    }
+    let sql = format!(
+        "SELECT m.* FROM milestones m WHERE {MILESTONE_FILTER}
+         ORDER BY m.date, m.sort_order, m.created_at
+         LIMIT ?7 OFFSET ?8"
+    );
+    let mut conn = pool.acquire().await?;
+    let mut milestones = bind_filter(sqlx::query_as::<_, Milestone>(&sql), &filter)?
+        .bind(limit)
+        .bind(filter.offset.unwrap_or(0))
+        .fetch_all(&mut *conn)
+        .await?;
+    load_account_ids(&mut conn, &mut milestones).await?;
+
+    let names: HashMap<String, String> = sqlx::query_as("SELECT id, name FROM accounts")
+        .fetch_all(&mut *conn)
+        .await?
+        .into_iter()
+        .collect();
+    Ok(milestones
+        .into_iter()
+        .map(|milestone| MilestoneListItem {
+            account_names: milestone
+                .account_ids
+                .iter()
+                .filter_map(|id| names.get(id).cloned())
+                .collect(),
+            milestone,
+        })
+        .collect())
+}
+
+/// How many milestones match `filter`, ignoring `limit` and `offset`.
+#[tauri::command]
+pub async fn count_milestones(pool: State<'_, SqlitePool>, filter: MilestoneFilter) -> Result<u32> {
+    let sql = format!("SELECT COUNT(*) FROM milestones m WHERE {MILESTONE_FILTER}");
+    let (count,): (u32,) = bind_filter(sqlx::query_as(&sql), &filter)?
+        .fetch_one(pool.inner())
+        .await?;
+    Ok(count)
+}
+
+/// `get_milestones` for one category only.
+#[tauri::command]
+pub async fn get_milestones_by_category(
+    pool: State<'_, SqlitePool>,
+    category: MilestoneCategory,
+    start: Option<String>,
+    end: Option<String>,
+) -> Result<Vec<Milestone>> {
+    fetch_milestones(&mut *pool.acquire().await?, start, end, Some(category)).await
+}
+
+/// The place after every milestone already on `date`.
+async fn next_sort_order(conn: &mut SqliteConnection, date: &str) -> Result<i64> {
+    let next = sqlx::query_scalar(
+        "SELECT COALESCE(MAX(sort_order) + 1, 0) FROM milestones WHERE date = ?",
+    )
+    .bind(date)
+    .fetch_one(&mut *conn)
+    .await?;
+    Ok(next)
+}
+
+/// Puts the milestones on `date` in the order of `ordered_ids`, which must list
+/// each of them exactly once, and returns them in that order.
+#[tauri::command]
+pub async fn reorder_milestones(
+    pool: State<'_, SqlitePool>,
+    date: String,
+    ordered_ids: Vec<String>,
+) -> Result<Vec<Milestone>> {
+    let date = normalize_date("date", &date)?;
+    let mut tx = pool.begin().await?;
+    let existing: HashSet<String> = sqlx::query_scalar("SELECT id FROM milestones WHERE date = ?")
+        .bind(&date)
+        .fetch_all(&mut *tx)
+        .await?
+        .into_iter()
+        .collect();
+    validate_ordering(&ordered_ids, &existing, "milestone")?;
+
+    for (position, id) in ordered_ids.iter().enumerate() {
+        sqlx::query("UPDATE milestones SET sort_order = ? WHERE id = ?")
+            .bind(position as i64)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+    }
+    let milestones = fetch_milestones(&mut tx, Some(date.clone()), Some(date), None).await?;
+
+    tx.commit().await?;
+    Ok(milestones)
+}
+
+async fn write_milestone(conn: &mut SqliteConnection, milestone: &Milestone) -> Result<()> {
+    sqlx::query(
+        "UPDATE milestones
+         SET date = ?, label = ?, target_amount = ?, target_scope = ?,
+             achieved_at = ?, recurrence = ?, category = ?, icon = ?, sort_order = ?
+         WHERE id = ?",
+    )
+    .bind(&milestone.date)
+    .bind(&milestone.label)
+    .bind(milestone.target_amount)
+    .bind(milestone.target_scope)
+    .bind(&milestone.achieved_at)
+    .bind(milestone.recurrence)
+    .bind(milestone.category)
+    .bind(&milestone.icon)
+    .bind(milestone.sort_order)
+    .bind(&milestone.id)
+    .execute(&mut *conn)
+    .await?;
+    write_account_ids(conn, &milestone.id, &milestone.account_ids).await
+}
+
+/// Writes the changes in `input` into `milestone`, checking the result.
+fn apply_update(milestone: &mut Milestone, input: UpdateMilestoneInput) -> Result<()> {
+    if let Some(date) = input.date {
+        milestone.date = normalize_date("date", &date)?;
+    }
+    if let Some(label) = input.label {
+        milestone.label = label.trim().to_string();
+    }
+    if let Some(recurrence) = input.recurrence {
+        milestone.recurrence = recurrence;
+    }
+    if let Some(category) = input.category {
+        milestone.category = category;
+    }
+    non_empty_patch(input.icon).apply(&mut milestone.icon);
+    let retargeted = !(input.account_ids.is_none()
+        && input.target_amount.is_keep()
+        && input.target_scope.is_keep());
+    if let Some(account_ids) = input.account_ids {
+        milestone.account_ids = distinct(account_ids);
+    }
+    input.target_amount.apply(&mut milestone.target_amount);
+    input.target_scope.apply(&mut milestone.target_scope);
+    milestone.validate()?;
+    if retargeted {
+        milestone.achieved_at = None;
+    }
+    Ok(())
+}
+
+/// The series `milestone` belongs to from its date on: its template, when it is
+/// an instance, and every instance on or after it, other than itself.
+async fn fetch_following(
+    conn: &mut SqliteConnection,
+    milestone: &Milestone,
+) -> Result<Vec<Milestone>> {
+    let template_id = milestone.parent_id.as_deref().unwrap_or(&milestone.id);
+    let mut milestones = sqlx::query_as::<_, Milestone>(
+        "SELECT * FROM milestones
+         WHERE id != ?1 AND (id = ?2 OR (parent_id = ?2 AND date >= ?3))
+         ORDER BY date, sort_order",
+    )
+    .bind(&milestone.id)
+    .bind(template_id)
+    .bind(&milestone.date)
+    .fetch_all(&mut *conn)
+    .await?;
+    load_account_ids(conn, &mut milestones).await?;
+    Ok(milestones)
+}
+
+/// Changes one milestone, or with `scope` set to `all_future` the rest of its
+/// series too. Dates can only be changed one milestone at a time, and a
+/// recurrence only on the template.
+#[tauri::command]
+pub async fn update_milestone(
+    pool: State<'_, SqlitePool>,
+    id: String,
+    input: UpdateMilestoneInput,
+    scope: Option<MilestoneEditScope>,
+) -> Result<Milestone> {
+    input.validate()?;
+    let mut tx = pool.begin().await?;
+    let mut milestone = fetch_milestone(&mut tx, &id).await?;
+    let following = match scope.unwrap_or_default() {
+        MilestoneEditScope::ThisInstance => Vec::new(),
+        MilestoneEditScope::AllFuture => {
+            if input.date.is_some() {
+                return Err(Error::validation(
+                    "date",
+                    "can only be changed for this instance",
+                ));
+            }
+            fetch_following(&mut tx, &milestone).await?
+        }
+    };
+
+    let date = milestone.date.clone();
+    apply_update(&mut milestone, input.clone())?;
+    if milestone.date != date {
+        milestone.sort_order = next_sort_order(&mut tx, &milestone.date).await?;
+    }
+    write_milestone(&mut tx, &milestone).await?;
+    for mut other in following {
+        let mut input = input.clone();
+        if other.parent_id.is_some() {
+            input.recurrence = None;
+        }
+        apply_update(&mut other, input)?;
+        write_milestone(&mut tx, &other).await?;
+    }
+    tx.commit().await?;
+
+    Ok(milestone)
+}
+
+/// Deletes one milestone; instances of a deleted template stay as one-offs.
+/// With `scope` set to `all_future`, every instance of its series on or after
+/// it goes too, and the series stops recurring.
+#[tauri::command]
+pub async fn delete_milestone(
+    pool: State<'_, SqlitePool>,
+    id: String,
+    scope: Option<MilestoneEditScope>,
+) -> Result<()> {
+    let mut tx = pool.begin().await?;
+    let milestone = fetch_milestone(&mut tx, &id).await?;
+    if scope.unwrap_or_default() == MilestoneEditScope::AllFuture {
+        for other in fetch_following(&mut tx, &milestone).await? {
+            if other.parent_id.is_some() {
+                sqlx::query("DELETE FROM milestones WHERE id = ?")
+                    .bind(&other.id)
+                    .execute(&mut *tx)
+                    .await?;
+            } else {
+                sqlx::query("UPDATE milestones SET recurrence = ? WHERE id = ?")
+                    .bind(MilestoneRecurrence::None)
+                    .bind(&other.id)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+        }
+    }
+    sqlx::query("DELETE FROM milestones WHERE id = ?")
+        .bind(&id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Generates the instances of every recurring milestone due after its date up
+/// to `until_date`, skipping dates generated before or already taken by one of
+/// its instances, and returns those created.
+#[tauri::command]
+pub async fn materialise_recurring_milestones(
+    pool: State<'_, SqlitePool>,
+    until_date: String,
+) -> Result<Vec<Milestone>> {
+    let until = parse_date("until_date", &normalize_date("until_date", &until_date)?)?;
+    let mut tx = pool.begin().await?;
+    let mut templates = sqlx::query_as::<_, Milestone>(
+        "SELECT * FROM milestones WHERE recurrence != ? ORDER BY date, sort_order",
+    )
+    .bind(MilestoneRecurrence::None)
+    .fetch_all(&mut *tx)
+    .await?;
+    load_account_ids(&mut tx, &mut templates).await?;
+
+    let mut created = Vec::new();
+    for template in templates {
+        let start = parse_date("date", &template.date)?;
+        let due = match template.recurrence {
+            MilestoneRecurrence::None => continue,
+            MilestoneRecurrence::Monthly => monthly_on_day(start, until, start.day()),
+            MilestoneRecurrence::Yearly => yearly_on_day(start, until),
+        };
+        let done = template
+            .materialised_until
+            .as_deref()
+            .unwrap_or(&template.date);
+        for date in due.into_iter().map(dates::format) {
+            if date.as_str() <= done {
+                continue;
+            }
+            let exists: bool = sqlx::query_scalar(
+                "SELECT EXISTS(SELECT 1 FROM milestones WHERE parent_id = ? AND date = ?)",
+            )
+            .bind(&template.id)
+            .bind(&date)
+            .fetch_one(&mut *tx)
+            .await?;
+            if exists {
+                continue;
+            }
+            let sort_order = next_sort_order(&mut tx, &date).await?;
+            let instance = Milestone {
+                id: Uuid::new_v4().to_string(),
+                date,
+                label: template.label.clone(),
+                account_ids: template.account_ids.clone(),
+                legacy_account_id: None,
+                created_at: now(),
+                target_amount: None,
+                target_scope: None,
+                achieved_at: None,
+                recurrence: MilestoneRecurrence::None,
+                parent_id: Some(template.id.clone()),
+                materialised_until: None,
+                category: template.category,
+                icon: template.icon.clone(),
+                sort_order,
+            };
+            sqlx::query(
+                "INSERT INTO milestones (id, date, label, created_at, parent_id, category, icon, sort_order)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(&instance.id)
+            .bind(&instance.date)
+            .bind(&instance.label)
+            .bind(&instance.created_at)
+            .bind(&instance.parent_id)
+            .bind(instance.category)
+            .bind(&instance.icon)
+            .bind(instance.sort_order)
+            .execute(&mut *tx)
+            .await?;
+            write_account_ids(&mut tx, &instance.id, &instance.account_ids).await?;
+            created.push(instance);
+        }
+        let until = dates::format(until);
+        if until.as_str() > done {
+            sqlx::query("UPDATE milestones SET materialised_until = ? WHERE id = ?")
+                .bind(&until)
+                .bind(&template.id)
+                .execute(&mut *tx)
+                .await?;
+        }
+    }
+
+    tx.commit().await?;
+    Ok(created)
+}
+
+/// The sum of `accounts`' balances after each day any of them changed, oldest
+/// first. Each is carried forward from its latest entry, counts for nothing
+/// after it closed, and is turned into its part of the total by `measure`.
+async fn combined_series(
+    conn: &mut SqliteConnection,
+    accounts: Vec<Account>,
+    measure: impl Fn(&Account, f64) -> f64,
+) -> Result<Vec<Point>> {
+    let mut dates = BTreeSet::new();
+    let mut held = Vec::with_capacity(accounts.len());
+    for account in accounts {
+        let points = fetch_points(&mut *conn, &account.id, false).await?;
+        let closed_at = account
+            .closed_at
+            .as_deref()
+            .map(|closed_at| parse_date("closed_at", closed_at))
+            .transpose()?;
+        dates.extend(points.iter().map(|point| point.date));
+        dates.extend(closed_at.and_then(|closed_at| closed_at.checked_add_days(Days::new(1))));
+        held.push((account, points, closed_at));
+    }
+
+    let units = minor_units_for(&load_settings(&mut *conn).await?.base_currency);
+    Ok(dates
+        .into_iter()
+        .map(|date| {
+            let values = held.iter().map(|(account, points, closed_at)| {
+                if closed_at.is_some_and(|closed_at| date > closed_at) {
+                    return 0.0;
+                }
+                measure(account, balance_on(points, date))
+            });
+            Point {
+                date,
+                balance: round_amount(sum_amounts(values), units),
+            }
+        })
+        .collect())
+}
+
+/// `balance` counted towards a total, with what is owed subtracted.
+fn signed(account: &Account, balance: f64) -> f64 {
+    match account.category {
+        AccountCategory::Asset => balance,
+        AccountCategory::Liability => -balance.abs(),
+    }
+}
+
+/// Net worth after each day it changed, oldest first, valued as
+/// `get_net_worth_series` values it but carried forward from each entry.
+pub(crate) async fn net_worth_series(conn: &mut SqliteConnection) -> Result<Vec<Point>> {
+    let base_currency = load_settings(&mut *conn).await?.base_currency;
+    let accounts = net_worth_accounts(conn).await?;
+    let mut dates = BTreeSet::new();
+    for account in &accounts {
+        let points = fetch_points(&mut *conn, &account.id, false).await?;
+        dates.extend(points.iter().map(|point| point.date));
+        if let Some(closed_at) = &account.closed_at {
+            let closed_at = parse_date("closed_at", closed_at)?;
+            dates.extend(closed_at.checked_add_days(Days::new(1)));
+        }
+    }
+    let dates: Vec<NaiveDate> = dates.into_iter().collect();
+
+    let valued = value_accounts(
+        conn,
+        accounts,
+        &dates,
+        FillMethod::CarryForward,
+        false,
+        &base_currency,
+    )
+    .await?;
+    let totals = totals_on(&valued, &dates, minor_units_for(&base_currency));
+    Ok(dates
+        .into_iter()
+        .zip(totals)
+        .map(|(date, total)| Point {
+            date,
+            balance: total.unwrap_or(0.0),
+        })
+        .collect())
+}
+
+/// The figure a target milestone measures, oldest first, and whether reaching
+/// the target means falling to it, as paying debts down does. Net worth counts
+/// owned shares with what is owed subtracted; linked accounts count in full,
+/// as the amount owed when they are all liabilities. `None` without a target,
+/// or for an account target whose accounts have all been deleted.
+async fn target_series(
+    conn: &mut SqliteConnection,
+    milestone: &Milestone,
+) -> Result<Option<(Vec<Point>, bool)>> {
+    match milestone.target_scope {
+        Some(MilestoneTargetScope::NetWorth) => Ok(Some((net_worth_series(conn).await?, false))),
+        Some(MilestoneTargetScope::Account) if !milestone.account_ids.is_empty() => {
+            let mut accounts = Vec::with_capacity(milestone.account_ids.len());
+            for account_id in &milestone.account_ids {
+                accounts.push(fetch_account(&mut *conn, account_id).await?);
+            }
+            let owed = accounts
+                .iter()
+                .all(|account| account.category == AccountCategory::Liability);
+            let series = if owed {
+                combined_series(conn, accounts, |_, balance| balance.abs()).await?
+            } else {
+                combined_series(conn, accounts, signed).await?
+            };
+            Ok(Some((series, owed)))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// The first date `series` reached `target`, from below or, when `falling`,
+/// from above.
+pub(crate) fn first_reached(series: &[Point], target: f64, falling: bool) -> Option<NaiveDate> {
+    series
+        .iter()
+        .find(|point| {
+            if falling {
+                point.balance <= target
+            } else {
+                point.balance >= target
+            }
+        })
+        .map(|point| point.date)
+}
+
+/// Checks every target milestone not yet achieved against the whole history of
+/// its figure, stamping each that has reached its target with the first date it
+/// did, and returns those. An achievement stands even if the figure has since
+/// fallen back.
+pub(crate) async fn detect_achievements(conn: &mut SqliteConnection) -> Result<Vec<Milestone>> {
+    let mut pending = sqlx::query_as::<_, Milestone>(
+        "SELECT * FROM milestones
+         WHERE target_amount IS NOT NULL AND target_scope IS NOT NULL AND achieved_at IS NULL
+         ORDER BY date, sort_order, created_at",
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    load_account_ids(conn, &mut pending).await?;
+
+    let mut achieved = Vec::new();
+    for mut milestone in pending {
+        let Some(target) = milestone.target_amount else {
+            continue;
+        };
+        let Some((series, falling)) = target_series(conn, &milestone).await? else {
+            continue;
+        };
+        let Some(reached) = first_reached(&series, target, falling) else {
+            continue;
+        };
+        milestone.achieved_at = Some(dates::format(reached));
+        sqlx::query("UPDATE milestones SET achieved_at = ? WHERE id = ?")
+            .bind(&milestone.achieved_at)
+            .bind(&milestone.id)
+            .execute(&mut *conn)
+            .await?;
+        achieved.push(milestone);
+    }
+
+    Ok(achieved)
+}
+
+/// Runs `detect_achievements` on its own.
+#[tauri::command]
+pub async fn check_milestone_achievements(pool: State<'_, SqlitePool>) -> Result<Vec<Milestone>> {
+    let mut tx = pool.begin().await?;
+    let achieved = detect_achievements(&mut tx).await?;
+    tx.commit().await?;
+    Ok(achieved)
+}
+
+/// Fewer balances than this make a projection a rough guide.
+const MIN_CONFIDENT_POINTS: usize = 6;
+
+/// Projections further out than this count as not on track.
+const MAX_PROJECTION_DAYS: f64 = 50.0 * 365.25;
+
+/// When a target milestone is likely to be reached: a linear or exponential
+/// trend is fitted to its figure over the 12 months to the latest balance and
+/// carried on from that balance. One already reached gives the date it was.
+#[tauri::command]
+pub async fn project_milestone(
+    pool: State<'_, SqlitePool>,
+    milestone_id: String,
+    method: TrendMethod,
+) -> Result<MilestoneProjection> {
+    let mut tx = pool.begin().await?;
+    let milestone = fetch_milestone(&mut tx, &milestone_id).await?;
+    let Some(target) = milestone.target_amount else {
+        return Err(Error::validation(
+            "milestone_id",
+            format!("'{}' has no target amount", milestone.label),
+        ));
+    };
+    let mut projection = MilestoneProjection {
+        milestone_id: milestone.id.clone(),
+        method,
+        status: MilestoneProjectionStatus::Achieved,
+        date: milestone.achieved_at.clone(),
+        monthly_rate: None,
+        data_points: 0,
+        note: None,
+    };
+    if projection.date.is_some() {
+        return Ok(projection);
+    }
+    let (series, falling) = target_series(&mut tx, &milestone)
+        .await?
+        .ok_or_else(|| Error::NoData(format!("'{}' has nothing to measure", milestone.label)))?;
+    tx.commit().await?;
+    if let Some(reached) = first_reached(&series, target, falling) {
+        projection.date = Some(dates::format(reached));
+        return Ok(projection);
+    }
+
+    let Some(latest) = series.last() else {
+        return Err(Error::NoData(format!(
+            "'{}' has no balances to project from",
+            milestone.label
+        )));
+    };
+    let window_start = latest.date - Months::new(12);
+    let window: Vec<(NaiveDate, f64)> = series
+        .iter()
+        .filter(|point| point.date >= window_start)
+        .map(|point| (point.date, point.balance))
+        .collect();
+    if window.len() < 2 {
+        return Err(Error::NoData(
+            "at least two balances from the last 12 months are needed for a trend".to_string(),
+        ));
+    }
+    projection.data_points = window.len() as u32;
+
+    // Days from the latest balance until the target, if the trend heads there.
+    let days = match method {
+        TrendMethod::Linear => {
+            let slope = linear_fit(&window).slope;
+            projection.monthly_rate = Some(slope * DAYS_PER_MONTH);
+            (target - latest.balance) / slope
+        }
+        TrendMethod::Exponential => {
+            if window.iter().any(|(_, value)| *value <= 0.0) {
+                return Err(Error::validation(
+                    "method",
+                    "an exponential trend needs every balance in the last 12 months above zero",
+                ));
+            }
+            let logs: Vec<(NaiveDate, f64)> = window
+                .iter()
+                .map(|(date, value)| (*date, value.ln()))
+                .collect();
+            let slope = linear_fit(&logs).slope;
+            projection.monthly_rate = Some(((slope * DAYS_PER_MONTH).exp() - 1.0) * 100.0);
+            if target > 0.0 {
+                (target / latest.balance).ln() / slope
+            } else {
+                f64::NAN
+            }
+        }
+    };
+
+    if !days.is_finite() || days <= 0.0 {
+        projection.status = MilestoneProjectionStatus::NotOnTrack;
+        projection.note = Some("the trend is not moving towards the target".to_string());
+    } else if days > MAX_PROJECTION_DAYS {
+        projection.status = MilestoneProjectionStatus::NotOnTrack;
+        projection.note = Some("the target is more than 50 years away at this rate".to_string());
+    } else {
+        projection.status = MilestoneProjectionStatus::OnTrack;
+        projection.date = Some(dates::format(latest.date + Days::new(days.ceil() as u64)));
+        if window.len() < MIN_CONFIDENT_POINTS {
+            projection.note = Some(format!(
+                "only {} balances from the last 12 months, so treat the date as a rough guide",
+                window.len()
+            ));
+        }
+    }
+    Ok(projection)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::commands::accounts::{archive_account, create_account};
+    use crate::testing::{block_on, input, seed_household, TestDb};
+
+    #[test]
+    fn net_worth_series_matches_the_net_worth_figures() {
+        let db = TestDb::new();
+        let series = block_on(async {
+            seed_household(&db).await;
+            let archived = create_account(
+                db.state(),
+                input(json!({
+                    "name": "Archived savings",
+                    "account_type": "savings",
+                    "opening_balance": 5000,
+                    "opened_at": "2025-01-01",
+                })),
+            )
+            .await
+            .unwrap();
+            archive_account(db.state(), archived.id).await.unwrap();
+            let mut conn = db.pool().acquire().await.unwrap();
+            net_worth_series(&mut conn).await.unwrap()
+        });
+        // As on `get_net_worth_series`'s last point: the brokerage at 0.8 to
+        // the pound, the archived savings left out.
+        let last = series.last().unwrap();
+        assert_eq!(dates::format(last.date), "2025-05-31");
+        assert_eq!(last.balance, 1600.0 + 2000.0 - 95_000.0);
+    }
+
+    #[test]
+    fn filters_combine_with_and() {
+        let db = TestDb::new();
+        block_on(async {
+            let account = |name: &str| {
+                let value = json!({"name": name, "account_type": "savings"});
+                async { create_account(db.state(), input(value)).await.unwrap().id }
+            };
+            let (a, b) = (account("Current").await, account("Joint").await);
+            let milestone =
+                |value: Value| async { create_milestone(db.state(), input(value)).await.unwrap() };
+            let car = milestone(json!({
+                "date": "2025-02-01", "label": "Buy a car", "category": "purchase",
+                "account_ids": [a], "target_amount": 5000, "target_scope": "account",
+            }))
+            .await;
+            milestone(json!({
+                "date": "2025-03-15", "label": "Pay off the car loan", "category": "payoff",
+                "account_ids": [a, b], "target_amount": 100, "target_scope": "account",
+            }))
+            .await;
+            milestone(json!({"date": "2025-06-20", "label": "Wedding", "category": "life_event"}))
+                .await;
+            milestone(
+                json!({"date": "2025-09-01", "label": "Car insurance renewal", "account_ids": [b]}),
+            )
+            .await;
+            milestone(json!({
+                "date": "2026-01-01", "label": "Half a million",
+                "target_amount": 500_000, "target_scope": "net_worth",
+            }))
+            .await;
+            sqlx::query("UPDATE milestones SET achieved_at = '2025-01-20' WHERE id = ?")
+                .bind(&car.id)
+                .execute(db.pool())
+                .await
+                .unwrap();
+
+            let all = [
+                "Buy a car",
+                "Pay off the car loan",
+                "Wedding",
+                "Car insurance renewal",
+                "Half a million",
+            ];
+            let cases: Vec<(Value, Vec<&str>, u32)> = vec![
+                (json!({}), all.to_vec(), 5),
+                (json!({"start": "2025-03-15"}), all[1..].to_vec(), 4),
+                (json!({"end": "2025-06-20"}), all[..3].to_vec(), 3),
+                (
+                    json!({"start": "2025-03-01", "end": "2025-09-01"}),
+                    all[1..4].to_vec(),
+                    3,
+                ),
+                (
+                    json!({"category": "general"}),
+                    vec!["Car insurance renewal", "Half a million"],
+                    2,
+                ),
+                (json!({"category": "purchase"}), vec!["Buy a car"], 1),
+                (json!({"achieved": true}), vec!["Buy a car"], 1),
+                // Only milestones with a target are reached or not.
+                (
+                    json!({"achieved": false}),
+                    vec!["Pay off the car loan", "Half a million"],
+                    2,
+                ),
+                (
+                    json!({"account_id": a}),
+                    vec!["Buy a car", "Pay off the car loan"],
+                    2,
+                ),
+                (
+                    json!({"account_id": b}),
+                    vec!["Pay off the car loan", "Car insurance renewal"],
+                    2,
+                ),
+                (
+                    json!({"search": "car"}),
+                    vec!["Buy a car", "Pay off the car loan", "Car insurance renewal"],
+                    3,
+                ),
+                (
+                    json!({"search": " CAR "}),
+                    vec!["Buy a car", "Pay off the car loan", "Car insurance renewal"],
+                    3,
+                ),
+                (json!({"search": "_"}), vec![], 0),
+                (
+                    json!({"search": "car", "account_id": b}),
+                    vec!["Pay off the car loan", "Car insurance renewal"],
+                    2,
+                ),
+                (
+                    json!({"search": "car", "category": "general"}),
+                    vec!["Car insurance renewal"],
+                    1,
+                ),
+                (
+                    json!({"achieved": false, "account_id": a}),
+                    vec!["Pay off the car loan"],
+                    1,
+                ),
+                (
+                    json!({"start": "2025-03-01", "search": "car", "achieved": false}),
+                    vec!["Pay off the car loan"],
+                    1,
+                ),
+                (json!({"category": "general", "achieved": true}), vec![], 0),
+                (json!({"limit": 2}), all[..2].to_vec(), 5),
+                (json!({"limit": 2, "offset": 2}), all[2..4].to_vec(), 5),
+                (json!({"offset": 4}), all[4..].to_vec(), 5),
+                (
+                    json!({"search": "car", "limit": 1, "offset": 1}),
+                    vec!["Pay off the car loan"],
+                    3,
+                ),
+            ];
+            for (filter, labels, count) in cases {
+                let listed = list_milestones(db.state(), input(filter.clone()))
+                    .await
+                    .unwrap();
+                let listed: Vec<&str> = listed
+                    .iter()
+                    .map(|item| item.milestone.label.as_str())
+                    .collect();
+                assert_eq!(listed, labels, "{filter}");
+                assert_eq!(
+                    count_milestones(db.state(), input(filter.clone()))
+                        .await
+                        .unwrap(),
+                    count,
+                    "{filter}"
+                );
+            }
+        });
+    }
+}
@@ -0,0 +1,114 @@
+pub mod account_types;
+pub mod accounts;
+pub mod adjustments;
+pub mod allocation_targets;
+pub mod analytics;
+pub mod attachments;
+pub mod backup;
+pub mod balances;
+pub mod benchmarks;
+pub mod credit;
+pub mod currencies;
+pub mod emergency_fund;
+pub mod exchange_rates;
+pub mod exports;
+pub mod flows;
+pub mod groups;
+pub mod history;
+pub mod imports;
+pub mod income;
+pub mod inflation;
+pub mod links;
+pub mod milestones;
+pub mod net_worth_cache;
+pub mod notifications;
+pub mod pensions;
+pub mod projections;
+pub mod properties;
+pub mod reminders;
+pub mod reviews;
+pub mod scenarios;
+pub mod search;
+pub mod series;
+pub mod settings;
+pub mod snapshots;
+pub mod suggestions;
+pub mod tags;
+pub mod undo;
+
+use std::collections::HashSet;
+
+use chrono::{Local, NaiveDate, SecondsFormat, Utc};
+
+use crate::dates;
+use crate::error::{Error, Result};
+use crate::models::Patch;
+
+/// Timestamps in the same shape the frontend has always written
+/// (`Date.prototype.toISOString`), so old and new rows sort together.
+pub(crate) fn now() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// The user's current calendar date.
+pub(crate) fn today_date() -> NaiveDate {
+    Local::now().date_naive()
+}
+
+/// The user's current calendar date as `YYYY-MM-DD`.
+pub(crate) fn today() -> String {
+    dates::format(today_date())
+}
+
+pub(crate) fn parse_date(field: &'static str, value: &str) -> Result<NaiveDate> {
+    dates::parse(value)
+        .ok_or_else(|| Error::validation(field, format!("'{value}' is not a YYYY-MM-DD date")))
+}
+
+/// Parses a date from the frontend into the stored `YYYY-MM-DD` shape. A time
+/// of day is refused rather than dropped, since which day it falls on depends
+/// on the time zone it was written in.
+pub(crate) fn normalize_date(field: &'static str, value: &str) -> Result<String> {
+    if dates::has_time(value) {
+        return Err(Error::validation(
+            field,
+            format!("'{value}' has a time; expected a YYYY-MM-DD date"),
+        ));
+    }
+    parse_date(field, value).map(dates::format)
+}
+
+/// Treats blank optional text the same as a missing value.
+pub(crate) fn non_empty(value: Option<String>) -> Option<String> {
+    value.filter(|v| !v.trim().is_empty())
+}
+
+/// Treats setting blank text the same as clearing it.
+pub(crate) fn non_empty_patch(patch: Patch<String>) -> Patch<String> {
+    match patch {
+        Patch::Set(value) if value.trim().is_empty() => Patch::Clear,
+        other => other,
+    }
+}
+
+/// Checks that `ordered_ids` names every id in `existing` exactly once.
+pub(crate) fn validate_ordering(
+    ordered_ids: &[String],
+    existing: &HashSet<String>,
+    entity: &'static str,
+) -> Result<()> {
+    let requested: HashSet<&String> = ordered_ids.iter().collect();
+    if requested.len() != ordered_ids.len() {
+        return Err(Error::validation("ordered_ids", "contains duplicate ids"));
+    }
+    if let Some(unknown) = ordered_ids.iter().find(|id| !existing.contains(*id)) {
+        return Err(Error::not_found(entity, unknown.clone()));
+    }
+    if ordered_ids.len() != existing.len() {
+        return Err(Error::validation(
+            "ordered_ids",
+            format!("must include every {entity}"),
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,242 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{Days, NaiveDate};
+use sqlx::{Connection, SqliteConnection, SqlitePool};
+use tauri::State;
+
+use super::analytics::{net_worth_totals, points_from_totals};
+use super::inflation::load_deflator;
+use super::settings::load_settings;
+use super::{now, parse_date, today_date};
+use crate::dates;
+use crate::error::Result;
+use crate::models::{
+    minor_units_for, sum_amounts, FillMethod, Granularity, NetWorthPoint, NetWorthSeriesOptions,
+    SnapshotRefresh,
+};
+use crate::series::period_dates;
+
+/// Drops the snapshots after the last day still valid and works out every day
+/// from then until today. Balance, account, exchange rate and base currency
+/// changes pull the valid day back through triggers, so only what they touched
+/// is done again.
+async fn refresh(conn: &mut SqliteConnection) -> Result<SnapshotRefresh> {
+    let today = today_date();
+    let mut tx = conn.begin().await?;
+    let valid_until: Option<String> =
+        sqlx::query_scalar("SELECT valid_until FROM net_worth_snapshot_state")
+            .fetch_one(&mut *tx)
+            .await?;
+    let valid_until = valid_until
+        .map(|date| parse_date("valid_until", &date))
+        .transpose()?;
+    if let Some(valid_until) = valid_until.filter(|valid_until| *valid_until >= today) {
+        return Ok(SnapshotRefresh {
+            refreshed_from: None,
+            valid_until: dates::format(valid_until),
+            snapshots: 0,
+        });
+    }
+
+    sqlx::query("DELETE FROM net_worth_snapshots WHERE ?1 IS NULL OR date > ?1")
+        .bind(valid_until.map(dates::format))
+        .execute(&mut *tx)
+        .await?;
+    let start = match valid_until {
+        Some(valid_until) => valid_until + Days::new(1),
+        // Nothing has a value before the first entry.
+        None => {
+            let first: Option<String> = sqlx::query_scalar("SELECT min(date) FROM balance_entries")
+                .fetch_one(&mut *tx)
+                .await?;
+            match first {
+                Some(first) => parse_date("date", &first)?.min(today),
+                None => today,
+            }
+        }
+    };
+    let days: Vec<NaiveDate> = start.iter_days().take_while(|day| *day <= today).collect();
+    let base_currency = load_settings(&mut *tx).await?.base_currency;
+    let totals =
+        net_worth_totals(&mut tx, &days, FillMethod::Linear, false, &base_currency).await?;
+
+    let computed_at = now();
+    for (date, (assets, liabilities, estimated)) in &totals {
+        sqlx::query(
+            "INSERT INTO net_worth_snapshots (date, assets, liabilities, net, is_estimated, computed_at)
+             VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(dates::format(*date))
+        .bind(assets)
+        .bind(liabilities)
+        .bind(sum_amounts([*assets, -liabilities]))
+        .bind(estimated)
+        .bind(&computed_at)
+        .execute(&mut *tx)
+        .await?;
+    }
+    sqlx::query("UPDATE net_worth_snapshot_state SET valid_until = ?")
+        .bind(dates::format(today))
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    Ok(SnapshotRefresh {
+        refreshed_from: Some(dates::format(start)),
+        valid_until: dates::format(today),
+        snapshots: totals.len(),
+    })
+}
+
+/// The points of `net_worth_points` read from the snapshots, after bringing
+/// them up to date. `None` when they can't answer: for other fill methods,
+/// without estimated entries, or past today.
+pub(crate) async fn cached_points(
+    conn: &mut SqliteConnection,
+    start: NaiveDate,
+    end: NaiveDate,
+    granularity: Granularity,
+    options: &NetWorthSeriesOptions,
+) -> Result<Option<Vec<NetWorthPoint>>> {
+    if options.exclude_estimated
+        || options
+            .method
+            .is_some_and(|method| method != FillMethod::Linear)
+    {
+        return Ok(None);
+    }
+    let refreshed = refresh(conn).await?;
+    if dates::format(end) > refreshed.valid_until {
+        return Ok(None);
+    }
+
+    let rows: Vec<(String, f64, f64, bool)> = sqlx::query_as(
+        "SELECT date, assets, liabilities, is_estimated FROM net_worth_snapshots
+         WHERE date >= ? AND date <= ?",
+    )
+    .bind(dates::format(start))
+    .bind(dates::format(end))
+    .fetch_all(&mut *conn)
+    .await?;
+    let mut snapshots = HashMap::with_capacity(rows.len());
+    for (date, assets, liabilities, estimated) in rows {
+        snapshots.insert(parse_date("date", &date)?, (assets, liabilities, estimated));
+    }
+    let totals: BTreeMap<NaiveDate, (f64, f64, bool)> = period_dates(start, end, granularity)
+        .into_iter()
+        .filter_map(|date| snapshots.get(&date).map(|totals| (date, *totals)))
+        .collect();
+
+    let base_currency = load_settings(&mut *conn).await?.base_currency;
+    let deflator = load_deflator(conn, &options.inflation).await?;
+    Ok(Some(points_from_totals(
+        totals,
+        deflator.as_ref(),
+        minor_units_for(&base_currency),
+    )))
+}
+
+/// Brings the net worth snapshots up to today, recomputing only the days
+/// changes since the last refresh could have moved. `get_net_worth_series`
+/// does the same before reading them.
+#[tauri::command]
+pub async fn refresh_snapshots(pool: State<'_, SqlitePool>) -> Result<SnapshotRefresh> {
+    let mut conn = pool.acquire().await?;
+    refresh(&mut conn).await
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::commands::analytics::net_worth_points;
+    use crate::commands::balances::create_balance_entry;
+    use crate::testing::{block_on, input, seed_household, TestDb};
+
+    const GRANULARITIES: [Granularity; 3] =
+        [Granularity::Day, Granularity::Week, Granularity::Month];
+
+    /// Checks the snapshots answer every granularity as working it out would.
+    async fn assert_cache_matches(conn: &mut SqliteConnection) {
+        let start = parse_date("start", "2025-01-01").unwrap();
+        let end = parse_date("end", "2025-06-30").unwrap();
+        let options = NetWorthSeriesOptions::default();
+        for granularity in GRANULARITIES {
+            let cached = cached_points(conn, start, end, granularity, &options)
+                .await
+                .unwrap()
+                .expect("the snapshots cover the range");
+            let direct = net_worth_points(conn, start, end, granularity, &options)
+                .await
+                .unwrap();
+            assert!(!direct.is_empty());
+            assert_eq!(cached.len(), direct.len(), "{granularity:?}");
+            for (cached, direct) in cached.iter().zip(&direct) {
+                assert_eq!(
+                    (
+                        &cached.date,
+                        cached.assets,
+                        cached.liabilities,
+                        cached.net,
+                        cached.is_estimated
+                    ),
+                    (
+                        &direct.date,
+                        direct.assets,
+                        direct.liabilities,
+                        direct.net,
+                        direct.is_estimated
+                    ),
+                    "{granularity:?}"
+                );
+            }
+        }
+    }
+
+    async fn valid_until(conn: &mut SqliteConnection) -> Option<String> {
+        sqlx::query_scalar("SELECT valid_until FROM net_worth_snapshot_state")
+            .fetch_one(conn)
+            .await
+            .unwrap()
+    }
+
+    #[test]
+    fn snapshots_match_the_series_before_and_after_an_edit() {
+        let db = TestDb::new();
+        block_on(async {
+            let household = seed_household(&db).await;
+            let mut conn = db.pool().acquire().await.unwrap();
+            assert_cache_matches(&mut conn).await;
+            assert_eq!(
+                valid_until(&mut conn).await,
+                Some(dates::format(today_date()))
+            );
+
+            create_balance_entry(
+                db.state(),
+                input(json!({
+                    "account_id": household.current.id,
+                    "date": "2025-02-14",
+                    "balance": 5000,
+                })),
+            )
+            .await
+            .unwrap();
+            // Days from the entry before it are worked out again.
+            assert_eq!(valid_until(&mut conn).await.as_deref(), Some("2024-12-31"));
+            assert_cache_matches(&mut conn).await;
+            let point = cached_points(
+                &mut conn,
+                parse_date("start", "2025-02-14").unwrap(),
+                parse_date("end", "2025-02-14").unwrap(),
+                Granularity::Day,
+                &NetWorthSeriesOptions::default(),
+            )
+            .await
+            .unwrap()
+            .unwrap();
+            assert_eq!(point[0].assets, 5500.0);
+        });
+    }
+}
@@ -0,0 +1,266 @@
+use std::collections::HashMap;
+
+use chrono::{Days, NaiveDate};
+use sqlx::{SqliteConnection, SqlitePool};
+use tauri::{AppHandle, State};
+use tauri_plugin_notification::NotificationExt;
+
+use super::milestones::{describe_target, detect_achievements, load_account_ids};
+use super::settings::load_settings;
+use super::{now, parse_date, today_date};
+use crate::dates;
+use crate::error::Result;
+use crate::models::{Account, Milestone, MilestoneNotification, NotificationKind};
+
+/// Runs achievement detection, then works out the notices due as of `today`:
+/// one for each achievement not yet announced, and one for each milestone not
+/// yet achieved that falls within `days_ahead` of today. Nothing is recorded as
+/// sent.
+pub(crate) async fn due_notifications(
+    conn: &mut SqliteConnection,
+    today: NaiveDate,
+    days_ahead: u32,
+    base_currency: &str,
+) -> Result<Vec<MilestoneNotification>> {
+    detect_achievements(conn).await?;
+    let mut achieved = sqlx::query_as::<_, Milestone>(
+        "SELECT * FROM milestones m
+         WHERE m.achieved_at IS NOT NULL
+           AND NOT EXISTS (
+               SELECT 1 FROM notifications_sent n
+               WHERE n.milestone_id = m.id AND n.kind = 'achieved' AND n.date = m.achieved_at
+           )
+         ORDER BY m.achieved_at, m.date, m.sort_order",
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    load_account_ids(conn, &mut achieved).await?;
+    let mut upcoming = sqlx::query_as::<_, Milestone>(
+        "SELECT * FROM milestones m
+         WHERE m.achieved_at IS NULL AND m.date >= ? AND m.date <= ?
+           AND NOT EXISTS (
+               SELECT 1 FROM notifications_sent n
+               WHERE n.milestone_id = m.id AND n.kind = 'upcoming' AND n.date = m.date
+           )
+         ORDER BY m.date, m.sort_order, m.created_at",
+    )
+    .bind(dates::format(today))
+    .bind(dates::format(today + Days::new(u64::from(days_ahead))))
+    .fetch_all(&mut *conn)
+    .await?;
+    load_account_ids(conn, &mut upcoming).await?;
+
+    let accounts: HashMap<String, Account> = sqlx::query_as::<_, Account>("SELECT * FROM accounts")
+        .fetch_all(&mut *conn)
+        .await?
+        .into_iter()
+        .map(|account| (account.id.clone(), account))
+        .collect();
+
+    let mut notifications = Vec::new();
+    for milestone in achieved {
+        let date = milestone.achieved_at.clone().unwrap_or_default();
+        let body = match describe_target(&milestone, &accounts, base_currency) {
+            Some(target) => format!("{target}, reached on {date}"),
+            None => format!("Reached on {date}"),
+        };
+        notifications.push(MilestoneNotification {
+            title: format!("Milestone reached: {}", milestone.label),
+            milestone_id: milestone.id,
+            kind: NotificationKind::Achieved,
+            date,
+            body,
+        });
+    }
+    for milestone in upcoming {
+        let days = (parse_date("date", &milestone.date)? - today).num_days();
+        let when = match days {
+            0 => "today".to_string(),
+            1 => "tomorrow".to_string(),
+            days => format!("in {days} days, on {}", milestone.date),
+        };
+        let body = match describe_target(&milestone, &accounts, base_currency) {
+            Some(target) => format!("Due {when}, with a target of {target}"),
+            None => format!("Due {when}"),
+        };
+        notifications.push(MilestoneNotification {
+            title: format!("Coming up: {}", milestone.label),
+            milestone_id: milestone.id,
+            kind: NotificationKind::Upcoming,
+            date: milestone.date,
+            body,
+        });
+    }
+    Ok(notifications)
+}
+
+async fn record_sent(
+    conn: &mut SqliteConnection,
+    notification: &MilestoneNotification,
+) -> Result<()> {
+    sqlx::query(
+        "INSERT OR IGNORE INTO notifications_sent (milestone_id, kind, date, sent_at)
+         VALUES (?, ?, ?, ?)",
+    )
+    .bind(&notification.milestone_id)
+    .bind(notification.kind)
+    .bind(&notification.date)
+    .bind(now())
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+/// Meant to run at start-up and then now and again: detects achievements, shows
+/// a desktop notification for each notice due, and returns those notices. Each
+/// notice is shown once, ever. Does nothing while notifications are turned off
+/// in settings.
+#[tauri::command]
+pub async fn check_and_notify(
+    app: AppHandle,
+    pool: State<'_, SqlitePool>,
+) -> Result<Vec<MilestoneNotification>> {
+    notify_due(pool.inner(), today_date(), |notification| {
+        app.notification()
+            .builder()
+            .title(&notification.title)
+            .body(&notification.body)
+            .show()?;
+        Ok(())
+    })
+    .await
+}
+
+/// `check_and_notify` as of `today`, with each notice handed to `show`.
+async fn notify_due(
+    pool: &SqlitePool,
+    today: NaiveDate,
+    mut show: impl FnMut(&MilestoneNotification) -> Result<()>,
+) -> Result<Vec<MilestoneNotification>> {
+    let mut tx = pool.begin().await?;
+    let settings = load_settings(&mut *tx).await?;
+    if !settings.notifications_enabled {
+        return Ok(Vec::new());
+    }
+    let notifications = due_notifications(
+        &mut tx,
+        today,
+        settings.notify_days_ahead,
+        &settings.base_currency,
+    )
+    .await?;
+    for notification in &notifications {
+        show(notification)?;
+        record_sent(&mut tx, notification).await?;
+    }
+
+    tx.commit().await?;
+    Ok(notifications)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::commands::accounts::create_account;
+    use crate::commands::milestones::create_milestone;
+    use crate::commands::settings::update_settings;
+    use crate::models::Milestone;
+    use crate::testing::{block_on, input, TestDb};
+
+    /// An account worth 1,000 today, a net worth milestone of 500 it has passed,
+    /// and milestones due in three days and in thirty.
+    async fn seed(db: &TestDb, today: NaiveDate) -> [Milestone; 3] {
+        create_account(
+            db.state(),
+            input(json!({
+                "name": "Savings",
+                "account_type": "savings",
+                "opening_balance": 1000,
+                "opened_at": dates::format(today),
+            })),
+        )
+        .await
+        .unwrap();
+        let milestone = |days: u64, label: &str, target: Option<f64>| {
+            let mut value = json!({"date": dates::format(today + Days::new(days)), "label": label});
+            if let Some(target) = target {
+                value["target_amount"] = json!(target);
+                value["target_scope"] = json!("net_worth");
+            }
+            async { create_milestone(db.state(), input(value)).await.unwrap() }
+        };
+        [
+            milestone(60, "First 500", Some(500.0)).await,
+            milestone(3, "Renew the ISA", None).await,
+            milestone(30, "Pay the tax bill", None).await,
+        ]
+    }
+
+    fn kinds(notifications: &[MilestoneNotification]) -> Vec<(&str, NotificationKind)> {
+        notifications
+            .iter()
+            .map(|notification| (notification.milestone_id.as_str(), notification.kind))
+            .collect()
+    }
+
+    #[test]
+    fn notices_are_shown_once_and_returned() {
+        let db = TestDb::new();
+        block_on(async {
+            let today = today_date();
+            let [reached, soon, _later] = seed(&db, today).await;
+
+            let mut shown = Vec::new();
+            let notified = notify_due(db.pool(), today, |notification| {
+                shown.push(notification.clone());
+                Ok(())
+            })
+            .await
+            .unwrap();
+            let expected = vec![
+                (reached.id.as_str(), NotificationKind::Achieved),
+                (soon.id.as_str(), NotificationKind::Upcoming),
+            ];
+            assert_eq!(kinds(&notified), expected);
+            assert_eq!(kinds(&shown), expected);
+            assert_eq!(
+                notified[1].body,
+                "Due in 3 days, on ".to_string() + &soon.date
+            );
+
+            let sent: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM notifications_sent")
+                .fetch_one(db.pool())
+                .await
+                .unwrap();
+            assert_eq!(sent, 2);
+            let again = notify_due(db.pool(), today, |_| panic!("already shown"))
+                .await
+                .unwrap();
+            assert!(again.is_empty());
+        });
+    }
+
+    #[test]
+    fn nothing_is_shown_while_notifications_are_off() {
+        let db = TestDb::new();
+        block_on(async {
+            let today = today_date();
+            seed(&db, today).await;
+            update_settings(db.state(), input(json!({"notifications_enabled": false})))
+                .await
+                .unwrap();
+
+            let notified = notify_due(db.pool(), today, |_| panic!("notifications are off"))
+                .await
+                .unwrap();
+            assert!(notified.is_empty());
+            let sent: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM notifications_sent")
+                .fetch_one(db.pool())
+                .await
+                .unwrap();
+            assert_eq!(sent, 0);
+        });
+    }
+}
@@ -0,0 +1,155 @@
+use chrono::Months;
+use sqlx::{SqliteExecutor, SqlitePool};
+use tauri::State;
+
+use super::accounts::fetch_account;
+use super::balances::fetch_latest_entry;
+use super::{non_empty, now, parse_date};
+use crate::dates;
+use crate::error::{Error, Result};
+use crate::models::{
+    Account, AccountType, PensionDetails, PensionProjectionPoint, SetPensionDetailsInput,
+};
+
+async fn fetch_pension<'e>(executor: impl SqliteExecutor<'e>, account_id: &str) -> Result<Account> {
+    let account = fetch_account(executor, account_id).await?;
+    if account.account_type != AccountType::Pension {
+        return Err(Error::validation(
+            "account_id",
+            format!("'{}' is not a pension", account.name),
+        ));
+    }
+    Ok(account)
+}
+
+async fn fetch_pension_details<'e>(
+    executor: impl SqliteExecutor<'e>,
+    account_id: &str,
+) -> Result<Option<PensionDetails>> {
+    let details =
+        sqlx::query_as::<_, PensionDetails>("SELECT * FROM pension_details WHERE account_id = ?")
+            .bind(account_id)
+            .fetch_optional(executor)
+            .await?;
+    Ok(details)
+}
+
+/// `None` until details have been set for the pension.
+#[tauri::command]
+pub async fn get_pension_details(
+    pool: State<'_, SqlitePool>,
+    account_id: String,
+) -> Result<Option<PensionDetails>> {
+    fetch_pension(pool.inner(), &account_id).await?;
+    fetch_pension_details(pool.inner(), &account_id).await
+}
+
+#[tauri::command]
+pub async fn set_pension_details(
+    pool: State<'_, SqlitePool>,
+    account_id: String,
+    input: SetPensionDetailsInput,
+) -> Result<PensionDetails> {
+    input.validate()?;
+    let retirement_date = input
+        .retirement_date
+        .as_deref()
+        .map(|date| parse_date("retirement_date", date))
+        .transpose()?
+        .map(dates::format);
+    fetch_pension(pool.inner(), &account_id).await?;
+
+    let details = PensionDetails {
+        account_id,
+        employer: non_empty(input.employer).map(|employer| employer.trim().to_string()),
+        employee_contribution_percent: input.employee_contribution_percent,
+        employer_contribution_percent: input.employer_contribution_percent,
+        annual_salary: input.annual_salary,
+        retirement_date,
+        updated_at: now(),
+    };
+    sqlx::query(
+        "INSERT INTO pension_details (account_id, employer, employee_contribution_percent, employer_contribution_percent, annual_salary, retirement_date, updated_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?)
+         ON CONFLICT(account_id) DO UPDATE SET
+            employer = excluded.employer,
+            employee_contribution_percent = excluded.employee_contribution_percent,
+            employer_contribution_percent = excluded.employer_contribution_percent,
+            annual_salary = excluded.annual_salary,
+            retirement_date = excluded.retirement_date,
+            updated_at = excluded.updated_at",
+    )
+    .bind(&details.account_id)
+    .bind(&details.employer)
+    .bind(details.employee_contribution_percent)
+    .bind(details.employer_contribution_percent)
+    .bind(details.annual_salary)
+    .bind(&details.retirement_date)
+    .bind(&details.updated_at)
+    .execute(pool.inner())
+    .await?;
+
+    Ok(details)
+}
+
+/// Month by month from the latest entry to the retirement date: the balance grows
+/// at `annual_growth_percent`, compounded monthly, and each month's contribution
+/// from salary and contribution rates is added on top.
+#[tauri::command]
+pub async fn project_pension(
+    pool: State<'_, SqlitePool>,
+    account_id: String,
+    annual_growth_percent: f64,
+) -> Result<Vec<PensionProjectionPoint>> {
+    if !(-100.0..=100.0).contains(&annual_growth_percent) {
+        return Err(Error::validation(
+            "annual_growth_percent",
+            "must be between -100 and 100",
+        ));
+    }
+
+    let account = fetch_pension(pool.inner(), &account_id).await?;
+    let details = fetch_pension_details(pool.inner(), &account_id)
+        .await?
+        .ok_or_else(|| Error::NoData(format!("'{}' has no pension details", account.name)))?;
+    let retirement = details
+        .retirement_date
+        .as_deref()
+        .ok_or_else(|| Error::NoData(format!("'{}' has no retirement date set", account.name)))
+        .and_then(|date| parse_date("retirement_date", date))?;
+    let latest = fetch_latest_entry(pool.inner(), &account_id)
+        .await?
+        .ok_or_else(|| Error::NoData(format!("'{}' has no balance entries", account.name)))?;
+    let start = parse_date("date", &latest.date)?;
+    if retirement <= start {
+        return Err(Error::validation(
+            "retirement_date",
+            format!("must be after the latest entry on {}", latest.date),
+        ));
+    }
+
+    let growth_rate = annual_growth_percent / 100.0 / 12.0;
+    let contribution = details.monthly_contribution();
+    let mut balance = latest.balance;
+    let mut contributions = 0.0;
+    let mut growth = 0.0;
+    let mut points = Vec::new();
+    for month in 1.. {
+        let date = start + Months::new(month);
+        if date > retirement {
+            break;
+        }
+        let monthly_growth = balance * growth_rate;
+        balance += monthly_growth + contribution;
+        growth += monthly_growth;
+        contributions += contribution;
+        points.push(PensionProjectionPoint {
+            date: dates::format(date),
+            balance,
+            contributions,
+            growth,
+        });
+    }
+
+    Ok(points)
+}
@@ -0,0 +1,264 @@
+use chrono::{Days, Months, NaiveDate};
+use sqlx::SqlitePool;
+use tauri::State;
+
+use super::accounts::fetch_account;
+use super::balances::{fetch_latest_entry, fetch_points};
+use super::parse_date;
+use crate::dates;
+use crate::error::{Error, Result};
+use crate::models::{
+    minor_units_for, round_amount, sum_amounts, validate_interest_rate, AccountCategory,
+    AccountType, AmortisationPlan, AmortisationRow, AmortisationSchedule, PayoffProjection,
+    PayoffStatus, ProjectedPoint,
+};
+use crate::series::{linear_fit, DAYS_PER_MONTH};
+
+const MAX_PROJECTION_MONTHS: u32 = 600;
+
+/// Compounds the latest balance monthly at the account's interest rate. The
+/// balance keeps its sign, so an asset grows and a liability's debt grows.
+#[tauri::command]
+pub async fn project_interest(
+    pool: State<'_, SqlitePool>,
+    account_id: String,
+    months: u32,
+) -> Result<Vec<ProjectedPoint>> {
+    if !(1..=MAX_PROJECTION_MONTHS).contains(&months) {
+        return Err(Error::validation(
+            "months",
+            format!("must be between 1 and {MAX_PROJECTION_MONTHS}"),
+        ));
+    }
+
+    let account = fetch_account(pool.inner(), &account_id).await?;
+    let rate = account.interest_rate.ok_or_else(|| {
+        Error::validation(
+            "interest_rate",
+            format!("'{}' has no interest rate set", account.name),
+        )
+    })?;
+    let latest = fetch_latest_entry(pool.inner(), &account_id)
+        .await?
+        .ok_or_else(|| Error::NoData(format!("'{}' has no balance entries", account.name)))?;
+    let start = parse_date("date", &latest.date)?;
+
+    let monthly = rate / 100.0 / 12.0;
+    let mut balance = latest.balance;
+    let mut points = Vec::with_capacity(months as usize);
+    for month in 1..=months {
+        balance *= 1.0 + monthly;
+        let date = start + Months::new(month);
+        points.push(ProjectedPoint {
+            date: dates::format(date),
+            balance,
+            interest_accrued: balance - latest.balance,
+        });
+    }
+
+    Ok(points)
+}
+
+/// Schedules longer than this are refused rather than built.
+const MAX_AMORTISATION_MONTHS: u32 = 1200;
+
+/// Pays `payment` a month off `balance` at `monthly_rate`, with interest
+/// rounded to `units` each month, until nothing is owed.
+fn amortise(
+    start: NaiveDate,
+    balance: f64,
+    monthly_rate: f64,
+    payment: f64,
+    units: u8,
+) -> Result<AmortisationPlan> {
+    let first_interest = round_amount(balance * monthly_rate, units);
+    if payment <= first_interest {
+        return Err(Error::validation(
+            "payment",
+            format!(
+                "{payment} a month never repays the loan: the first month's interest alone is {first_interest}"
+            ),
+        ));
+    }
+
+    let mut rows = Vec::new();
+    let mut owed = balance;
+    let (mut interest_paid, mut paid) = (Vec::new(), Vec::new());
+    while owed > 0.0 {
+        let month = rows.len() as u32 + 1;
+        if month > MAX_AMORTISATION_MONTHS {
+            return Err(Error::validation(
+                "payment",
+                format!("{payment} a month takes more than {MAX_AMORTISATION_MONTHS} months to repay the loan"),
+            ));
+        }
+        let interest = round_amount(owed * monthly_rate, units);
+        let due = sum_amounts([owed, interest]);
+        let this_payment = payment.min(due);
+        let closing_balance = sum_amounts([due, -this_payment]);
+        rows.push(AmortisationRow {
+            month,
+            date: dates::format(start + Months::new(month)),
+            opening_balance: owed,
+            interest,
+            principal: sum_amounts([this_payment, -interest]),
+            closing_balance,
+        });
+        interest_paid.push(interest);
+        paid.push(this_payment);
+        owed = closing_balance;
+    }
+
+    Ok(AmortisationPlan {
+        monthly_payment: payment,
+        payoff_date: rows
+            .last()
+            .map_or_else(|| dates::format(start), |row| row.date.clone()),
+        rows,
+        total_interest: sum_amounts(interest_paid),
+        total_paid: sum_amounts(paid),
+    })
+}
+
+/// The month-by-month payoff of a mortgage or loan from its latest balance,
+/// at its interest rate unless `interest_rate` overrides it. With an
+/// overpayment, a second schedule shows what paying that much more each month
+/// saves.
+#[tauri::command]
+pub async fn get_amortisation_schedule(
+    pool: State<'_, SqlitePool>,
+    account_id: String,
+    payment: f64,
+    overpayment: Option<f64>,
+    interest_rate: Option<f64>,
+) -> Result<AmortisationSchedule> {
+    if !payment.is_finite() || payment <= 0.0 {
+        return Err(Error::validation("payment", "must be a positive number"));
+    }
+    if overpayment.is_some_and(|amount| !amount.is_finite() || amount <= 0.0) {
+        return Err(Error::validation(
+            "overpayment",
+            "must be a positive number",
+        ));
+    }
+    if let Some(rate) = interest_rate {
+        validate_interest_rate(rate)?;
+    }
+
+    let account = fetch_account(pool.inner(), &account_id).await?;
+    if !matches!(
+        account.account_type,
+        AccountType::Mortgage | AccountType::Loan
+    ) {
+        return Err(Error::validation(
+            "account_id",
+            format!("'{}' is not a mortgage or loan", account.name),
+        ));
+    }
+    let rate = interest_rate.or(account.interest_rate).ok_or_else(|| {
+        Error::validation(
+            "interest_rate",
+            format!("'{}' has no interest rate set", account.name),
+        )
+    })?;
+    let latest = fetch_latest_entry(pool.inner(), &account_id)
+        .await?
+        .ok_or_else(|| Error::NoData(format!("'{}' has no balance entries", account.name)))?;
+    let start = parse_date("date", &latest.date)?;
+    let units = minor_units_for(&account.currency);
+    let balance = round_amount(latest.balance.abs(), units);
+    let monthly_rate = rate / 100.0 / 12.0;
+
+    let plan = amortise(start, balance, monthly_rate, payment, units)?;
+    let with_overpayment = overpayment
+        .map(|extra| {
+            amortise(
+                start,
+                balance,
+                monthly_rate,
+                sum_amounts([payment, extra]),
+                units,
+            )
+        })
+        .transpose()?;
+    Ok(AmortisationSchedule {
+        interest_saved: with_overpayment
+            .as_ref()
+            .map(|faster| sum_amounts([plan.total_interest, -faster.total_interest])),
+        months_saved: with_overpayment
+            .as_ref()
+            .map(|faster| (plan.rows.len() - faster.rows.len()) as u32),
+        account_id,
+        start_date: latest.date,
+        balance,
+        interest_rate: rate,
+        plan,
+        with_overpayment,
+    })
+}
+
+/// How many of the latest entries a payoff trend is fitted to, at most.
+const PAYOFF_TREND_POINTS: usize = 12;
+
+const MIN_PAYOFF_TREND_POINTS: usize = 3;
+
+/// Payoff dates further out than this count as not reducing.
+const MAX_PAYOFF_DAYS: f64 = 100.0 * 365.25;
+
+/// When a liability will reach zero if it keeps falling as it has over its last
+/// 12 entries: a straight line is fitted to what was owed and carried on from
+/// the latest entry.
+#[tauri::command]
+pub async fn project_payoff(
+    pool: State<'_, SqlitePool>,
+    account_id: String,
+) -> Result<PayoffProjection> {
+    let account = fetch_account(pool.inner(), &account_id).await?;
+    if account.category != AccountCategory::Liability {
+        return Err(Error::validation(
+            "account_id",
+            format!("'{}' is not a liability", account.name),
+        ));
+    }
+    let points = fetch_points(pool.inner(), &account_id, false).await?;
+    let recent = &points[points.len().saturating_sub(PAYOFF_TREND_POINTS)..];
+    let owed: Vec<(NaiveDate, f64)> = recent
+        .iter()
+        .map(|point| (point.date, point.balance.abs()))
+        .collect();
+    let units = minor_units_for(&account.currency);
+    let remaining = owed
+        .last()
+        .map_or(0.0, |(_, balance)| round_amount(*balance, units));
+
+    let mut projection = PayoffProjection {
+        account_id,
+        status: PayoffStatus::InsufficientData,
+        payoff_date: None,
+        average_monthly_reduction: None,
+        remaining,
+        data_points: owed.len() as u32,
+    };
+    let Some(&(latest, _)) = owed.last() else {
+        return Ok(projection);
+    };
+    if remaining == 0.0 {
+        projection.status = PayoffStatus::PaidOff;
+        projection.payoff_date = Some(dates::format(latest));
+        return Ok(projection);
+    }
+    if owed.len() < MIN_PAYOFF_TREND_POINTS {
+        return Ok(projection);
+    }
+
+    let slope = linear_fit(&owed).slope;
+    projection.average_monthly_reduction = Some(round_amount(-slope * DAYS_PER_MONTH, units));
+    let days = remaining / -slope;
+    if !days.is_finite() || days <= 0.0 || days > MAX_PAYOFF_DAYS {
+        projection.status = PayoffStatus::NotReducing;
+    } else {
+        projection.status = PayoffStatus::Projected;
+        projection.payoff_date = Some(dates::format(latest + Days::new(days.ceil() as u64)));
+    }
+    Ok(projection)
+}
@@ -0,0 +1,145 @@
+use sqlx::{SqliteExecutor, SqlitePool};
+use tauri::State;
+
+use super::accounts::fetch_account;
+use super::balances::fetch_latest_entry;
+use super::{normalize_date, now, parse_date, today};
+use crate::error::{Error, Result};
+use crate::models::{
+    Account, AccountType, PropertyDetails, PropertyPerformance, SetPropertyDetailsInput,
+};
+
+async fn fetch_property<'e>(
+    executor: impl SqliteExecutor<'e>,
+    account_id: &str,
+) -> Result<Account> {
+    let account = fetch_account(executor, account_id).await?;
+    if account.account_type != AccountType::Property {
+        return Err(Error::validation(
+            "account_id",
+            format!("'{}' is not a property", account.name),
+        ));
+    }
+    Ok(account)
+}
+
+async fn fetch_property_details<'e>(
+    executor: impl SqliteExecutor<'e>,
+    account_id: &str,
+) -> Result<Option<PropertyDetails>> {
+    let details =
+        sqlx::query_as::<_, PropertyDetails>("SELECT * FROM property_details WHERE account_id = ?")
+            .bind(account_id)
+            .fetch_optional(executor)
+            .await?;
+    Ok(details)
+}
+
+/// `None` until details have been set for the property.
+#[tauri::command]
+pub async fn get_property_details(
+    pool: State<'_, SqlitePool>,
+    account_id: String,
+) -> Result<Option<PropertyDetails>> {
+    fetch_property(pool.inner(), &account_id).await?;
+    fetch_property_details(pool.inner(), &account_id).await
+}
+
+#[tauri::command]
+pub async fn set_property_details(
+    pool: State<'_, SqlitePool>,
+    account_id: String,
+    input: SetPropertyDetailsInput,
+) -> Result<PropertyDetails> {
+    input.validate()?;
+    let purchase_date = normalize_date("purchase_date", &input.purchase_date)?;
+    if purchase_date > today() {
+        return Err(Error::validation(
+            "purchase_date",
+            "must not be in the future",
+        ));
+    }
+    fetch_property(pool.inner(), &account_id).await?;
+
+    let details = PropertyDetails {
+        account_id,
+        purchase_price: input.purchase_price,
+        purchase_date,
+        deposit_paid: input.deposit_paid,
+        updated_at: now(),
+    };
+    sqlx::query(
+        "INSERT INTO property_details (account_id, purchase_price, purchase_date, deposit_paid, updated_at)
+         VALUES (?, ?, ?, ?, ?)
+         ON CONFLICT(account_id) DO UPDATE SET
+            purchase_price = excluded.purchase_price,
+            purchase_date = excluded.purchase_date,
+            deposit_paid = excluded.deposit_paid,
+            updated_at = excluded.updated_at",
+    )
+    .bind(&details.account_id)
+    .bind(details.purchase_price)
+    .bind(&details.purchase_date)
+    .bind(details.deposit_paid)
+    .bind(&details.updated_at)
+    .execute(pool.inner())
+    .await?;
+
+    Ok(details)
+}
+
+/// Appreciation from the purchase price to the latest valuation, and equity
+/// after any open mortgages linked to the property.
+#[tauri::command]
+pub async fn get_property_performance(
+    pool: State<'_, SqlitePool>,
+    account_id: String,
+) -> Result<PropertyPerformance> {
+    let account = fetch_property(pool.inner(), &account_id).await?;
+    let details = fetch_property_details(pool.inner(), &account_id)
+        .await?
+        .ok_or_else(|| {
+            Error::NoData(format!(
+                "property details are not configured for '{}'",
+                account.name
+            ))
+        })?;
+    let latest = fetch_latest_entry(pool.inner(), &account_id)
+        .await?
+        .ok_or_else(|| Error::NoData(format!("'{}' has no balance entries", account.name)))?;
+
+    let appreciation = latest.balance - details.purchase_price;
+    let held_days = (parse_date("date", &latest.date)?
+        - parse_date("purchase_date", &details.purchase_date)?)
+    .num_days();
+    let annualised_percent = (held_days > 0).then(|| {
+        let years = held_days as f64 / 365.25;
+        ((latest.balance / details.purchase_price).powf(1.0 / years) - 1.0) * 100.0
+    });
+
+    let mortgage_ids: Vec<String> = sqlx::query_scalar(
+        "SELECT id FROM accounts WHERE linked_account_id = ? AND closed_at IS NULL",
+    )
+    .bind(&account_id)
+    .fetch_all(pool.inner())
+    .await?;
+    let mut mortgage_balance = None;
+    for id in &mortgage_ids {
+        if let Some(entry) = fetch_latest_entry(pool.inner(), id).await? {
+            *mortgage_balance.get_or_insert(0.0) += entry.balance.abs();
+        }
+    }
+
+    Ok(PropertyPerformance {
+        account_id,
+        purchase_price: details.purchase_price,
+        purchase_date: details.purchase_date,
+        current_value: latest.balance,
+        valuation_date: latest.date,
+        appreciation,
+        appreciation_percent: appreciation / details.purchase_price * 100.0,
+        annualised_percent,
+        mortgage_balance,
+        equity: mortgage_balance.map(|owed| latest.balance - owed),
+    })
+}
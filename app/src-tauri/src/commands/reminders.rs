@@ -0,0 +1,113 @@
+use chrono::{Local, NaiveDate};
+use sqlx::SqlitePool;
+use tauri::State;
+
+use super::accounts::{ACCOUNT_WITH_BALANCE_AS_OF_SELECT, ACCOUNT_WITH_BALANCE_SELECT};
+use super::parse_date;
+use crate::dates;
+use crate::error::Result;
+use crate::models::{AccountWithBalance, DueUpdate, ReminderFrequency, StaleAccount};
+
+/// Active accounts that count towards net worth and are overdue an update, most
+/// overdue first. An account is stale once a period of its reminder frequency has
+/// passed since its latest entry, or `max_age_days` when it has no frequency set.
+/// Accounts with no entries at all are always stale, unless set to `never`.
+#[tauri::command]
+pub async fn get_stale_accounts(
+    pool: State<'_, SqlitePool>,
+    max_age_days: u32,
+) -> Result<Vec<StaleAccount>> {
+    let sql = format!(
+        "{ACCOUNT_WITH_BALANCE_SELECT}
+         WHERE a.is_active = 1 AND a.archived_at IS NULL AND a.exclude_from_net_worth = 0
+         ORDER BY a.sort_order, a.name"
+    );
+    let accounts = sqlx::query_as::<_, AccountWithBalance>(&sql)
+        .fetch_all(pool.inner())
+        .await?;
+
+    let today = Local::now().date_naive();
+    let mut stale = Vec::new();
+    for account in accounts {
+        let frequency = account.account.reminder_frequency;
+        if frequency == Some(ReminderFrequency::Never) {
+            continue;
+        }
+        let last = account
+            .balance_date
+            .as_deref()
+            .map(|date| parse_date("date", date))
+            .transpose()?;
+        if let Some(last) = last {
+            let fresh = match frequency {
+                Some(frequency) => frequency.periods_between(last, today) == Some(0),
+                None => (today - last).num_days() <= i64::from(max_age_days),
+            };
+            if fresh {
+                continue;
+            }
+        }
+        stale.push(StaleAccount {
+            account: account.account,
+            last_entry_date: account.balance_date,
+            days_since: last.map(|last| (today - last).num_days()),
+        });
+    }
+    stale.sort_by_key(|account| std::cmp::Reverse(account.days_since.unwrap_or(i64::MAX)));
+
+    Ok(stale)
+}
+
+/// Active accounts with a reminder frequency whose next update was due on or
+/// before `as_of` (default today), with how many periods have been missed.
+/// Accounts without a frequency, or set to `never`, are left out.
+#[tauri::command]
+pub async fn get_due_updates(
+    pool: State<'_, SqlitePool>,
+    as_of: Option<String>,
+) -> Result<Vec<DueUpdate>> {
+    let as_of = match as_of {
+        Some(date) => parse_date("as_of", &date)?,
+        None => Local::now().date_naive(),
+    };
+    let sql = format!(
+        "{ACCOUNT_WITH_BALANCE_AS_OF_SELECT}
+         WHERE a.is_active = 1 AND a.archived_at IS NULL
+           AND a.reminder_frequency IS NOT NULL AND a.reminder_frequency != 'never'
+         ORDER BY a.sort_order, a.name"
+    );
+    let accounts = sqlx::query_as::<_, AccountWithBalance>(&sql)
+        .bind(dates::format(as_of))
+        .fetch_all(pool.inner())
+        .await?;
+
+    let mut due = Vec::new();
+    for account in accounts {
+        let Some(frequency) = account.account.reminder_frequency else {
+            continue;
+        };
+        let last: Option<NaiveDate> = account
+            .balance_date
+            .as_deref()
+            .map(|date| parse_date("date", date))
+            .transpose()?;
+        let (due_date, periods_missed) = match last {
+            Some(last) => {
+                let periods = frequency.periods_between(last, as_of);
+                if periods == Some(0) {
+                    continue;
+                }
+                (frequency.next_due(last), periods)
+            }
+            None => (None, None),
+        };
+        due.push(DueUpdate {
+            account: account.account,
+            last_entry_date: account.balance_date,
+            due_date: due_date.map(dates::format),
+            periods_missed,
+        });
+    }
+
+    Ok(due)
+}
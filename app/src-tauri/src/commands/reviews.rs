@@ -0,0 +1,224 @@
+use chrono::{Days, NaiveDate};
+use sqlx::{SqliteConnection, SqlitePool};
+use tauri::State;
+
+use super::analytics::{
+    account_values, net_flows, net_worth_accounts, net_worth_on, net_worth_points, AccountValues,
+};
+use super::milestones::fetch_milestones;
+use super::settings::load_settings;
+use super::{parse_date, today_date};
+use crate::dates;
+use crate::error::{Error, Result};
+use crate::models::{
+    fiscal_year_start_in, minor_units_for, round_amount, sum_amounts, AccountCategory, FillMethod,
+    Granularity, NetWorthSeriesOptions, ReviewAccountChange, ReviewAccountEvent, ReviewEndpoint,
+    YearReview,
+};
+
+/// Accounts whose opening or closing date falls from `start` to `end`; an
+/// account without an opening date opens with its first entry.
+async fn account_events(
+    conn: &mut SqliteConnection,
+    column: &str,
+    start: &str,
+    end: &str,
+) -> Result<Vec<ReviewAccountEvent>> {
+    let rows: Vec<(String, String, String)> = sqlx::query_as(&format!(
+        "SELECT id, name, date FROM (
+            SELECT id, name, {column} AS date FROM accounts
+         )
+         WHERE date >= ? AND date <= ?
+         ORDER BY date, name"
+    ))
+    .bind(start)
+    .bind(end)
+    .fetch_all(&mut *conn)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(|(account_id, name, date)| ReviewAccountEvent {
+            account_id,
+            name,
+            date,
+        })
+        .collect())
+}
+
+/// The review of the financial year starting in `year`, with its boundaries
+/// from the `fiscal_year_start` setting.
+pub(crate) async fn year_review(conn: &mut SqliteConnection, year: i32) -> Result<YearReview> {
+    let settings = load_settings(&mut *conn).await?;
+    let year_start = |year| {
+        fiscal_year_start_in(&settings.fiscal_year_start, year).ok_or_else(|| {
+            Error::validation("fiscal_year_start", "must be a month and day as MM-DD")
+        })
+    };
+    let start = year_start(year)?;
+    let end = year_start(year + 1)? - Days::new(1);
+    let today = today_date();
+    if start > today {
+        return Err(Error::validation("year", format!("{year} hasn't started")));
+    }
+
+    let mut notes = Vec::new();
+    let last_day = if end > today {
+        notes.push(format!(
+            "the year runs until {}; figures are up to today",
+            dates::format(end)
+        ));
+        today
+    } else {
+        end
+    };
+    let series = net_worth_points(
+        conn,
+        start,
+        last_day,
+        Granularity::Month,
+        &NetWorthSeriesOptions::default(),
+    )
+    .await?;
+
+    let day_before = start - Days::new(1);
+    let values = net_worth_on(conn, &[day_before, last_day], &settings.base_currency).await?;
+    let opening = match (values[0], series.first()) {
+        (Some(net_worth), _) => Some((day_before, net_worth)),
+        (None, Some(first)) => {
+            notes.push(format!("nothing was recorded before {}", first.date));
+            Some((parse_date("date", &first.date)?, first.net))
+        }
+        (None, None) => {
+            notes.push("nothing was recorded by the end of the year".to_string());
+            None
+        }
+    };
+    let closing = values[1].map(|net_worth| (last_day, net_worth));
+
+    let mut review = YearReview {
+        year,
+        start: dates::format(start),
+        end: dates::format(end),
+        partial: !notes.is_empty(),
+        notes,
+        opening: None,
+        closing: None,
+        change: None,
+        contributions: None,
+        growth: None,
+        biggest_gain: None,
+        biggest_loss: None,
+        milestones: Vec::new(),
+        balance_updates: 0,
+        accounts_opened: Vec::new(),
+        accounts_closed: Vec::new(),
+        series,
+    };
+
+    let (from, to) = (dates::format(start), dates::format(last_day));
+    review.milestones = fetch_milestones(conn, None, Some(to.clone()), None)
+        .await?
+        .into_iter()
+        .filter(|milestone| match &milestone.achieved_at {
+            Some(achieved) => *achieved >= from && *achieved <= to,
+            None => milestone.target_amount.is_none() && milestone.date >= from,
+        })
+        .collect();
+    review.balance_updates =
+        sqlx::query_scalar("SELECT COUNT(*) FROM balance_entries WHERE date >= ? AND date <= ?")
+            .bind(&from)
+            .bind(&to)
+            .fetch_one(&mut *conn)
+            .await?;
+    review.accounts_opened = account_events(
+        conn,
+        "COALESCE(opened_at, (SELECT MIN(date) FROM balance_entries WHERE account_id = accounts.id))",
+        &from,
+        &to,
+    )
+    .await?;
+    review.accounts_closed = account_events(conn, "closed_at", &from, &to).await?;
+
+    let (Some((opened_on, opening)), Some((closed_on, closing))) = (opening, closing) else {
+        return Ok(review);
+    };
+    review.change = Some(sum_amounts([closing, -opening]));
+
+    let accounts = net_worth_accounts(conn).await?;
+    let flows = net_flows(
+        conn,
+        &accounts,
+        opened_on + Days::new(1),
+        closed_on,
+        &settings.base_currency,
+    )
+    .await?;
+    if !flows.is_empty() {
+        let saved = sum_amounts(flows.into_iter().map(|(_, amount)| amount));
+        review.contributions = Some(saved);
+        review.growth = review.change.map(|change| sum_amounts([change, -saved]));
+    }
+
+    let valued = account_values(
+        conn,
+        &[opened_on, closed_on],
+        FillMethod::Linear,
+        false,
+        &settings.base_currency,
+    )
+    .await?;
+    let units = minor_units_for(&settings.base_currency);
+    let mut changes: Vec<ReviewAccountChange> = valued
+        .into_iter()
+        .map(
+            |AccountValues {
+                 account, values, ..
+             }| {
+                let value_on = |date: NaiveDate| {
+                    values
+                        .iter()
+                        .find(|(on, ..)| *on == date)
+                        .map_or(0.0, |(_, value, _)| round_amount(*value, units))
+                };
+                let (start_value, end_value) = (value_on(opened_on), value_on(closed_on));
+                let change = match account.category {
+                    AccountCategory::Asset => sum_amounts([end_value, -start_value]),
+                    AccountCategory::Liability => sum_amounts([start_value, -end_value]),
+                };
+                ReviewAccountChange {
+                    account_id: account.id,
+                    name: account.name,
+                    start_value,
+                    end_value,
+                    change,
+                }
+            },
+        )
+        .collect();
+    changes.sort_by(|a, b| b.change.total_cmp(&a.change));
+    review.biggest_gain = changes
+        .first()
+        .filter(|change| change.change > 0.0)
+        .cloned();
+    review.biggest_loss = changes.last().filter(|change| change.change < 0.0).cloned();
+    review.opening = Some(ReviewEndpoint {
+        date: dates::format(opened_on),
+        net_worth: opening,
+    });
+    review.closing = Some(ReviewEndpoint {
+        date: dates::format(closed_on),
+        net_worth: closing,
+    });
+    Ok(review)
+}
+
+/// A summary of the financial year starting in `year`: net worth at either
+/// end and month by month, the change split into contributions and growth
+/// where flows are recorded, the accounts that gained and lost most, and what
+/// happened along the way. A year still running, or one that started before
+/// anything was recorded, is marked partial.
+#[tauri::command]
+pub async fn get_year_review(pool: State<'_, SqlitePool>, year: i32) -> Result<YearReview> {
+    let mut conn = pool.acquire().await?;
+    year_review(&mut conn, year).await
+}
@@ -0,0 +1,519 @@
+use std::collections::HashMap;
+
+use chrono::{Datelike, Days, Months, NaiveDate};
+use sqlx::{SqliteExecutor, SqlitePool};
+use tauri::State;
+use uuid::Uuid;
+
+use super::accounts::fetch_account;
+use super::adjustments::due_dates;
+use super::analytics::net_worth_accounts;
+use super::balances::fetch_latest_entry;
+use super::exchange_rates::convert;
+use super::settings::load_settings;
+use super::{non_empty, non_empty_patch, normalize_date, now, parse_date, today_date};
+use crate::dates;
+use crate::error::{Error, Result};
+use crate::models::{
+    minor_units_for, round_amount, sum_amounts, validate_interest_rate, AccountCategory,
+    AdjustmentKind, CreateScenarioInput, RecurringAdjustment, Scenario, ScenarioDefinition,
+    ScenarioOperation, ScenarioPoint, ScenarioResult, UpdateScenarioInput,
+};
+use crate::series::{month_end, monthly_on_day};
+
+/// Scenarios looking further ahead than this are refused.
+const MAX_SCENARIO_MONTHS: u32 = 600;
+
+#[derive(sqlx::FromRow)]
+struct ScenarioRow {
+    id: String,
+    name: String,
+    description: Option<String>,
+    horizon_months: u32,
+    operations: String,
+    created_at: String,
+    updated_at: String,
+}
+
+impl TryFrom<ScenarioRow> for Scenario {
+    type Error = Error;
+
+    fn try_from(row: ScenarioRow) -> Result<Self> {
+        let operations = serde_json::from_str(&row.operations)
+            .map_err(|error| Error::validation("operations", error.to_string()))?;
+        Ok(Scenario {
+            id: row.id,
+            name: row.name,
+            description: row.description,
+            definition: ScenarioDefinition {
+                horizon_months: row.horizon_months,
+                operations,
+            },
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        })
+    }
+}
+
+pub(crate) fn encode_operations(definition: &ScenarioDefinition) -> String {
+    serde_json::to_string(&definition.operations).expect("scenario operations serialize")
+}
+
+async fn fetch_scenario<'e>(executor: impl SqliteExecutor<'e>, id: &str) -> Result<Scenario> {
+    sqlx::query_as::<_, ScenarioRow>("SELECT * FROM scenarios WHERE id = ?")
+        .bind(id)
+        .fetch_optional(executor)
+        .await?
+        .ok_or_else(|| Error::not_found("scenario", id))?
+        .try_into()
+}
+
+pub(crate) async fn fetch_scenarios<'e>(
+    executor: impl SqliteExecutor<'e>,
+) -> Result<Vec<Scenario>> {
+    sqlx::query_as::<_, ScenarioRow>("SELECT * FROM scenarios ORDER BY name, created_at")
+        .fetch_all(executor)
+        .await?
+        .into_iter()
+        .map(Scenario::try_from)
+        .collect()
+}
+
+fn check_name(name: &str) -> Result<String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(Error::validation("name", "must not be empty"));
+    }
+    Ok(name.to_string())
+}
+
+fn check_amount(field: &'static str, amount: f64) -> Result<f64> {
+    if !amount.is_finite() {
+        return Err(Error::validation(field, "must be a number"));
+    }
+    Ok(amount)
+}
+
+fn check_horizon(months: u32) -> Result<u32> {
+    if !(1..=MAX_SCENARIO_MONTHS).contains(&months) {
+        return Err(Error::validation(
+            "horizon_months",
+            format!("must be between 1 and {MAX_SCENARIO_MONTHS}"),
+        ));
+    }
+    Ok(months)
+}
+
+/// `operation` with its dates normalized, provided its fields make sense.
+/// Accounts are only looked up when the scenario runs.
+fn check_operation(operation: ScenarioOperation) -> Result<ScenarioOperation> {
+    Ok(match operation {
+        ScenarioOperation::AddAccount {
+            name,
+            category,
+            opening_balance,
+            start_date,
+            monthly_contribution,
+            interest_rate,
+        } => {
+            if let Some(rate) = interest_rate {
+                validate_interest_rate(rate)?;
+            }
+            ScenarioOperation::AddAccount {
+                name: check_name(&name)?,
+                category,
+                opening_balance: check_amount("opening_balance", opening_balance)?,
+                start_date: normalize_date("start_date", &start_date)?,
+                monthly_contribution: check_amount("monthly_contribution", monthly_contribution)?,
+                interest_rate,
+            }
+        }
+        ScenarioOperation::MonthlyFlow {
+            account_id,
+            amount,
+            start_date,
+            end_date,
+        } => {
+            let start_date = normalize_date("start_date", &start_date)?;
+            let end_date = end_date
+                .map(|date| normalize_date("end_date", &date))
+                .transpose()?;
+            if end_date.as_ref().is_some_and(|end| *end < start_date) {
+                return Err(Error::validation(
+                    "end_date",
+                    "must not be before start_date",
+                ));
+            }
+            ScenarioOperation::MonthlyFlow {
+                account_id,
+                amount: check_amount("amount", amount)?,
+                start_date,
+                end_date,
+            }
+        }
+        ScenarioOperation::LumpSum {
+            account_id,
+            amount,
+            date,
+        } => ScenarioOperation::LumpSum {
+            account_id,
+            amount: check_amount("amount", amount)?,
+            date: normalize_date("date", &date)?,
+        },
+    })
+}
+
+fn check_definition(definition: ScenarioDefinition) -> Result<ScenarioDefinition> {
+    Ok(ScenarioDefinition {
+        horizon_months: check_horizon(definition.horizon_months)?,
+        operations: definition
+            .operations
+            .into_iter()
+            .map(check_operation)
+            .collect::<Result<_>>()?,
+    })
+}
+
+#[tauri::command]
+pub async fn create_scenario(
+    pool: State<'_, SqlitePool>,
+    input: CreateScenarioInput,
+) -> Result<Scenario> {
+    let timestamp = now();
+    let scenario = Scenario {
+        id: Uuid::new_v4().to_string(),
+        name: check_name(&input.name)?,
+        description: non_empty(input.description),
+        definition: check_definition(input.definition)?,
+        created_at: timestamp.clone(),
+        updated_at: timestamp,
+    };
+
+    sqlx::query(
+        "INSERT INTO scenarios (id, name, description, horizon_months, operations, created_at, updated_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(&scenario.id)
+    .bind(&scenario.name)
+    .bind(&scenario.description)
+    .bind(scenario.definition.horizon_months)
+    .bind(encode_operations(&scenario.definition))
+    .bind(&scenario.created_at)
+    .bind(&scenario.updated_at)
+    .execute(pool.inner())
+    .await?;
+
+    Ok(scenario)
+}
+
+#[tauri::command]
+pub async fn get_scenarios(pool: State<'_, SqlitePool>) -> Result<Vec<Scenario>> {
+    fetch_scenarios(pool.inner()).await
+}
+
+#[tauri::command]
+pub async fn get_scenario(pool: State<'_, SqlitePool>, id: String) -> Result<Scenario> {
+    fetch_scenario(pool.inner(), &id).await
+}
+
+#[tauri::command]
+pub async fn update_scenario(
+    pool: State<'_, SqlitePool>,
+    id: String,
+    input: UpdateScenarioInput,
+) -> Result<Scenario> {
+    let mut scenario = fetch_scenario(pool.inner(), &id).await?;
+    if let Some(name) = input.name {
+        scenario.name = check_name(&name)?;
+    }
+    non_empty_patch(input.description).apply(&mut scenario.description);
+    if let Some(months) = input.horizon_months {
+        scenario.definition.horizon_months = check_horizon(months)?;
+    }
+    if let Some(operations) = input.operations {
+        scenario.definition.operations = operations
+            .into_iter()
+            .map(check_operation)
+            .collect::<Result<_>>()?;
+    }
+    scenario.updated_at = now();
+
+    sqlx::query(
+        "UPDATE scenarios SET name = ?, description = ?, horizon_months = ?, operations = ?, updated_at = ?
+         WHERE id = ?",
+    )
+    .bind(&scenario.name)
+    .bind(&scenario.description)
+    .bind(scenario.definition.horizon_months)
+    .bind(encode_operations(&scenario.definition))
+    .bind(&scenario.updated_at)
+    .bind(&id)
+    .execute(pool.inner())
+    .await?;
+
+    Ok(scenario)
+}
+
+#[tauri::command]
+pub async fn delete_scenario(pool: State<'_, SqlitePool>, id: String) -> Result<()> {
+    let deleted = sqlx::query("DELETE FROM scenarios WHERE id = ?")
+        .bind(&id)
+        .execute(pool.inner())
+        .await?;
+    if deleted.rows_affected() == 0 {
+        return Err(Error::not_found("scenario", id));
+    }
+    Ok(())
+}
+
+/// A real account as the scenario sees it, in its own currency.
+struct Track {
+    category: AccountCategory,
+    /// Into the base currency at the owned share.
+    scale: f64,
+    closed_at: Option<NaiveDate>,
+    balance: f64,
+    /// What its recurring adjustments will do, in date order.
+    due: Vec<(NaiveDate, AdjustmentKind, f64)>,
+    /// What the scenario adds, growing at the account's interest rate.
+    extra: f64,
+    monthly_rate: f64,
+    /// Paid in by the scenario on each date, from its operations.
+    flows: Vec<(NaiveDate, f64)>,
+}
+
+/// A hypothetical account from an `AddAccount` operation.
+struct Added {
+    category: AccountCategory,
+    start: NaiveDate,
+    opening_balance: f64,
+    monthly_contribution: f64,
+    monthly_rate: f64,
+    value: Option<f64>,
+}
+
+/// What is paid into `flows` after `from` up to and including `to`.
+fn paid_between(flows: &[(NaiveDate, f64)], from: Option<NaiveDate>, to: NaiveDate) -> f64 {
+    sum_amounts(
+        flows
+            .iter()
+            .filter(|(date, _)| from.is_none_or(|from| *date > from) && *date <= to)
+            .map(|(_, amount)| *amount),
+    )
+}
+
+/// Net worth from today to `scenario.horizon_months` month ends ahead, as the
+/// real data has it and with the scenario's operations on top. The baseline
+/// carries each account's latest balance forward through its recurring
+/// adjustments; what the scenario pays into an account grows, or for a
+/// liability saves interest, at the account's interest rate, and a liability
+/// is never paid below zero. Operations dated before today take effect today.
+/// Only reads: nothing the scenario does is saved.
+#[tauri::command]
+pub async fn run_scenario(
+    pool: State<'_, SqlitePool>,
+    scenario: ScenarioDefinition,
+) -> Result<ScenarioResult> {
+    let scenario = check_definition(scenario)?;
+    let today = today_date();
+    let first = if month_end(today) == today {
+        month_end(today + Days::new(1))
+    } else {
+        month_end(today)
+    };
+    let mut period_ends = vec![today];
+    period_ends
+        .extend((0..scenario.horizon_months).map(|month| month_end(first + Months::new(month))));
+    let horizon = *period_ends.last().expect("the horizon is at least a month");
+
+    let mut conn = pool.acquire().await?;
+    let base_currency = load_settings(&mut *conn).await?.base_currency;
+    let units = minor_units_for(&base_currency);
+    let adjustments = sqlx::query_as::<_, RecurringAdjustment>(
+        "SELECT * FROM recurring_adjustments ORDER BY start_date, created_at",
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut tracks: HashMap<String, Track> = HashMap::new();
+    for account in net_worth_accounts(&mut conn).await? {
+        let closed_at = account
+            .closed_at
+            .as_deref()
+            .map(|date| parse_date("closed_at", date))
+            .transpose()?;
+        if closed_at.is_some_and(|closed| closed < today) {
+            continue;
+        }
+        let Some(latest) = fetch_latest_entry(&mut *conn, &account.id).await? else {
+            continue;
+        };
+        let latest_date = parse_date("date", &latest.date)?;
+        let mut due = Vec::new();
+        for adjustment in adjustments
+            .iter()
+            .filter(|adjustment| adjustment.account_id == account.id)
+        {
+            for date in due_dates(adjustment, &account, horizon)? {
+                if date > latest_date {
+                    due.push((date, adjustment.kind, adjustment.amount));
+                }
+            }
+        }
+        due.sort_by_key(|(date, ..)| *date);
+        let mut scale = account.ownership_percent / 100.0;
+        if account.currency != base_currency {
+            scale *= convert(
+                &mut *conn,
+                1.0,
+                &account.currency,
+                &base_currency,
+                &dates::format(today),
+            )
+            .await?;
+        }
+        tracks.insert(
+            account.id.clone(),
+            Track {
+                category: account.category,
+                scale,
+                closed_at,
+                balance: latest.balance,
+                due,
+                extra: 0.0,
+                monthly_rate: account.interest_rate.unwrap_or(0.0) / 100.0 / 12.0,
+                flows: Vec::new(),
+            },
+        );
+    }
+
+    let mut added = Vec::new();
+    for operation in scenario.operations {
+        let (account_id, flows) = match operation {
+            ScenarioOperation::AddAccount {
+                category,
+                opening_balance,
+                start_date,
+                monthly_contribution,
+                interest_rate,
+                ..
+            } => {
+                added.push(Added {
+                    category,
+                    start: parse_date("start_date", &start_date)?,
+                    opening_balance,
+                    monthly_contribution,
+                    monthly_rate: interest_rate.unwrap_or(0.0) / 100.0 / 12.0,
+                    value: None,
+                });
+                continue;
+            }
+            ScenarioOperation::MonthlyFlow {
+                account_id,
+                amount,
+                start_date,
+                end_date,
+            } => {
+                let start = parse_date("start_date", &start_date)?;
+                let end = match end_date {
+                    Some(end) => parse_date("end_date", &end)?.min(horizon),
+                    None => horizon,
+                };
+                let flows = monthly_on_day(start, end, start.day())
+                    .into_iter()
+                    .map(|date| (date, amount))
+                    .collect::<Vec<_>>();
+                (account_id, flows)
+            }
+            ScenarioOperation::LumpSum {
+                account_id,
+                amount,
+                date,
+            } => (account_id, vec![(parse_date("date", &date)?, amount)]),
+        };
+        let Some(track) = tracks.get_mut(&account_id) else {
+            let account = fetch_account(&mut *conn, &account_id).await?;
+            return Err(Error::validation(
+                "account_id",
+                format!(
+                    "'{}' has no current balance counted towards net worth",
+                    account.name
+                ),
+            ));
+        };
+        track.flows.extend(flows);
+    }
+
+    let mut points = Vec::with_capacity(period_ends.len());
+    let mut previous: Option<NaiveDate> = None;
+    for date in period_ends {
+        let (mut baseline, mut with_scenario) = (Vec::new(), Vec::new());
+        for track in tracks.values_mut() {
+            while let Some(&(due, kind, amount)) = track.due.first() {
+                if due > date {
+                    break;
+                }
+                track.balance = match kind {
+                    AdjustmentKind::Delta => track.balance + amount,
+                    AdjustmentKind::Absolute => amount,
+                };
+                track.due.remove(0);
+            }
+            if previous.is_some() {
+                track.extra *= 1.0 + track.monthly_rate;
+            }
+            track.extra += paid_between(&track.flows, previous, date);
+            if track.closed_at.is_some_and(|closed| date > closed) {
+                continue;
+            }
+            let (real, hypothetical) = match track.category {
+                AccountCategory::Asset => (track.balance, track.balance + track.extra),
+                AccountCategory::Liability => {
+                    let owed = track.balance.abs();
+                    (-owed, -(owed - track.extra).max(0.0))
+                }
+            };
+            baseline.push(round_amount(real * track.scale, units));
+            with_scenario.push(round_amount(hypothetical * track.scale, units));
+        }
+        for account in &mut added {
+            if date < account.start {
+                continue;
+            }
+            let value = match account.value {
+                None => account.opening_balance,
+                Some(value) => match account.category {
+                    AccountCategory::Asset => {
+                        value * (1.0 + account.monthly_rate) + account.monthly_contribution
+                    }
+                    AccountCategory::Liability => (value * (1.0 + account.monthly_rate)
+                        - account.monthly_contribution)
+                        .max(0.0),
+                },
+            };
+            account.value = Some(value);
+            with_scenario.push(round_amount(
+                match account.category {
+                    AccountCategory::Asset => value,
+                    AccountCategory::Liability => -value,
+                },
+                units,
+            ));
+        }
+
+        let baseline = sum_amounts(baseline);
+        let scenario = sum_amounts(with_scenario);
+        points.push(ScenarioPoint {
+            date: dates::format(date),
+            baseline,
+            scenario,
+            delta: sum_amounts([scenario, -baseline]),
+        });
+        previous = Some(date);
+    }
+
+    Ok(ScenarioResult {
+        delta_at_horizon: points.last().map_or(0.0, |point| point.delta),
+        points,
+    })
+}
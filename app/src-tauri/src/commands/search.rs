@@ -0,0 +1,54 @@
+use sqlx::SqlitePool;
+use tauri::State;
+
+use crate::error::{Error, Result};
+use crate::models::NoteSearchHit;
+use crate::search;
+
+const DEFAULT_SEARCH_LIMIT: u32 = 50;
+const MAX_SEARCH_LIMIT: u32 = 200;
+
+/// Balance entry notes and milestone labels matching `query`, best first. Every
+/// word must appear; `"quoted words"` must appear together and `word*` matches
+/// words starting with it. Case and accents are ignored.
+#[tauri::command]
+pub async fn search_notes(
+    pool: State<'_, SqlitePool>,
+    query: String,
+    limit: Option<u32>,
+) -> Result<Vec<NoteSearchHit>> {
+    let limit = limit.unwrap_or(DEFAULT_SEARCH_LIMIT);
+    if !(1..=MAX_SEARCH_LIMIT).contains(&limit) {
+        return Err(Error::validation(
+            "limit",
+            format!("must be between 1 and {MAX_SEARCH_LIMIT}"),
+        ));
+    }
+    let Some(query) = search::fts_query(&query) else {
+        return Ok(Vec::new());
+    };
+
+    let hits = sqlx::query_as::<_, NoteSearchHit>(
+        "SELECT f.kind, f.item_id AS id,
+            COALESCE(b.account_id, ma.account_id) AS account_id,
+            a.name AS account_name,
+            COALESCE(b.date, m.date) AS date,
+            snippet(notes_fts, 0, '<mark>', '</mark>', '…', 12) AS snippet
+         FROM notes_fts f
+         LEFT JOIN balance_entries b ON f.kind = 'entry' AND b.id = f.item_id
+         LEFT JOIN milestones m ON f.kind = 'milestone' AND m.id = f.item_id
+         -- A milestone's first linked account; SQLite takes account_id from the MIN(rowid) row.
+         LEFT JOIN (
+            SELECT milestone_id, account_id, MIN(rowid) FROM milestone_accounts GROUP BY milestone_id
+         ) ma ON ma.milestone_id = m.id
+         LEFT JOIN accounts a ON a.id = COALESCE(b.account_id, ma.account_id)
+         WHERE notes_fts MATCH ?
+         ORDER BY rank
+         LIMIT ?",
+    )
+    .bind(query)
+    .bind(limit)
+    .fetch_all(pool.inner())
+    .await?;
+    Ok(hits)
+}
@@ -0,0 +1,281 @@
+use std::collections::HashSet;
+
+use chrono::Days;
+use sqlx::SqlitePool;
+use tauri::State;
+
+use super::accounts::fetch_account;
+use super::balances::{fetch_entries, fetch_points, insert_balance_entry, new_balance_entry};
+use super::undo::record_undo;
+use super::{normalize_date, parse_date, today_date};
+use crate::dates;
+use crate::error::{Error, Result};
+use crate::models::{
+    sum_amounts, BalanceAt, BalanceAtStatus, BalanceEntry, EntryRef, EntrySource, FillMethod,
+    Granularity, MonthlyBalance, SeriesPoint, SmoothingSpec, UndoOperation,
+};
+use crate::series::{
+    month_end, month_ends, monthly_periods, period_dates, resample, smooth, Point,
+};
+
+/// One point per day, week or month between `start` and `end`, estimating the
+/// dates without an entry by `method`. Dates before the account's first entry,
+/// or after it was closed, have no point. With `exclude_estimated`, entries made
+/// up by `fill_missing_months` are ignored and estimated afresh. With
+/// `smoothing`, each point also carries its moving average.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn get_account_series(
+    pool: State<'_, SqlitePool>,
+    account_id: String,
+    start: String,
+    end: String,
+    granularity: Granularity,
+    method: FillMethod,
+    exclude_estimated: Option<bool>,
+    smoothing: Option<SmoothingSpec>,
+) -> Result<Vec<SeriesPoint>> {
+    let start = parse_date("start", &start)?;
+    let mut end = parse_date("end", &end)?;
+    if end < start {
+        return Err(Error::validation("end", "must not be before start"));
+    }
+
+    let account = fetch_account(pool.inner(), &account_id).await?;
+    if let Some(closed_at) = &account.closed_at {
+        end = end.min(parse_date("closed_at", closed_at)?);
+    }
+    let points = fetch_points(
+        pool.inner(),
+        &account_id,
+        exclude_estimated.unwrap_or(false),
+    )
+    .await?;
+
+    let samples = resample(&points, &period_dates(start, end, granularity), method);
+    let mut series: Vec<SeriesPoint> = samples
+        .into_iter()
+        .map(|sample| SeriesPoint {
+            date: dates::format(sample.date),
+            balance: sample.balance,
+            is_estimated: sample.estimated,
+            smoothed: None,
+        })
+        .collect();
+    if let Some(spec) = smoothing {
+        let balances: Vec<f64> = series.iter().map(|point| point.balance).collect();
+        for (point, smoothed) in series.iter_mut().zip(smooth(&balances, spec)?) {
+            point.smoothed = Some(smoothed);
+        }
+    }
+    Ok(series)
+}
+
+/// One balance per month overlapping `start` to `end`: the last entry in each,
+/// carried forward into months without one. Months close on the account's
+/// `statement_day` when it has one, so a card follows its billing cycle.
+/// Months before the first entry, or after the account was closed, are left out,
+/// as are estimated entries with `exclude_estimated`.
+#[tauri::command]
+pub async fn get_monthly_balances(
+    pool: State<'_, SqlitePool>,
+    account_id: String,
+    start: String,
+    end: String,
+    exclude_estimated: Option<bool>,
+) -> Result<Vec<MonthlyBalance>> {
+    let start = parse_date("start", &start)?;
+    let mut end = parse_date("end", &end)?;
+    if end < start {
+        return Err(Error::validation("end", "must not be before start"));
+    }
+
+    let account = fetch_account(pool.inner(), &account_id).await?;
+    if let Some(closed_at) = &account.closed_at {
+        end = end.min(parse_date("closed_at", closed_at)?);
+    }
+    let mut entries = fetch_entries(pool.inner(), &account_id).await?;
+    if exclude_estimated.unwrap_or(false) {
+        entries.retain(|entry| entry.source != EntrySource::Estimated);
+    }
+    let periods = monthly_periods(start, end, account.statement_day.unwrap_or(31));
+
+    let latest_by = |date: &str| {
+        let after = entries.partition_point(|entry| entry.date.as_str() <= date);
+        after.checked_sub(1).map(|i| &entries[i])
+    };
+    let mut previous = periods
+        .first()
+        .and_then(|(first, _)| latest_by(&dates::format(*first - Days::new(1))))
+        .map(|entry| entry.balance);
+    let mut months = Vec::with_capacity(periods.len());
+    for (period_start, period_end) in periods {
+        let (period_start, period_end) = (dates::format(period_start), dates::format(period_end));
+        let Some(entry) = latest_by(&period_end) else {
+            continue;
+        };
+        let change = previous.map(|previous| sum_amounts([entry.balance, -previous]));
+        let change_percent = previous
+            .zip(change)
+            .filter(|(previous, _)| *previous != 0.0)
+            .map(|(previous, change)| change / previous.abs() * 100.0);
+        months.push(MonthlyBalance {
+            month: period_end[..7].to_string(),
+            is_estimated: entry.date < period_start,
+            period_start,
+            period_end,
+            balance: entry.balance,
+            entry_date: entry.date.clone(),
+            change,
+            change_percent,
+        });
+        previous = Some(entry.balance);
+    }
+    Ok(months)
+}
+
+/// Gives every month from `start` to `end` that has no entry at all one dated
+/// its last day, estimated by `method` from the entries that aren't estimates
+/// themselves, and marked with the `estimated` source. Months before the first
+/// entry, after the account was closed or not yet over are left alone, so
+/// running this again adds nothing.
+#[tauri::command]
+pub async fn fill_missing_months(
+    pool: State<'_, SqlitePool>,
+    account_id: String,
+    start: String,
+    end: String,
+    method: FillMethod,
+) -> Result<Vec<BalanceEntry>> {
+    let start = parse_date("start", &start)?;
+    let mut end = parse_date("end", &end)?;
+    if end < start {
+        return Err(Error::validation("end", "must not be before start"));
+    }
+
+    let mut tx = pool.begin().await?;
+    let account = fetch_account(&mut *tx, &account_id).await?;
+    end = end.min(today_date());
+    if let Some(closed_at) = &account.closed_at {
+        end = end.min(parse_date("closed_at", closed_at)?);
+    }
+    let entries = fetch_entries(&mut *tx, &account_id).await?;
+    let covered: HashSet<&str> = entries.iter().map(|entry| &entry.date[..7]).collect();
+    let points = entries
+        .iter()
+        .filter(|entry| entry.source != EntrySource::Estimated)
+        .map(|entry| {
+            Ok(Point {
+                date: parse_date("date", &entry.date)?,
+                balance: entry.balance,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let missing: Vec<_> = month_ends(start, end)
+        .into_iter()
+        .filter(|date| *date == month_end(*date) && !covered.contains(&dates::format(*date)[..7]))
+        .collect();
+
+    let mut created = Vec::new();
+    for sample in resample(&points, &missing, method) {
+        let mut entry =
+            new_balance_entry(&account, dates::format(sample.date), sample.balance, None);
+        entry.source = EntrySource::Estimated;
+        insert_balance_entry(&mut *tx, &entry).await?;
+        created.push(entry);
+    }
+    record_undo(
+        &mut tx,
+        UndoOperation::Create,
+        format!(
+            "Estimate {} monthly balances of {}",
+            created.len(),
+            account.name
+        ),
+        &[],
+        &created,
+    )
+    .await?;
+
+    tx.commit().await?;
+    Ok(created)
+}
+
+fn entry_ref(entry: &BalanceEntry) -> EntryRef {
+    EntryRef {
+        id: entry.id.clone(),
+        date: entry.date.clone(),
+    }
+}
+
+/// The balance on `date` from `entries`, oldest first.
+pub(crate) fn balance_at(
+    account_id: &str,
+    entries: &[BalanceEntry],
+    date: &str,
+    method: FillMethod,
+) -> Result<BalanceAt> {
+    let mut result = BalanceAt {
+        account_id: account_id.to_string(),
+        date: date.to_string(),
+        status: BalanceAtStatus::NoData,
+        balance: None,
+        derived_from: Vec::new(),
+    };
+    let after = entries.partition_point(|entry| entry.date.as_str() <= date);
+    let Some(before) = after.checked_sub(1).map(|i| &entries[i]) else {
+        return Ok(result);
+    };
+
+    result.balance = Some(before.balance);
+    result.derived_from.push(entry_ref(before));
+    if before.date == date {
+        result.status = BalanceAtStatus::Exact;
+        return Ok(result);
+    }
+    result.status = BalanceAtStatus::Estimated;
+    if let (FillMethod::Linear, Some(next)) = (method, entries.get(after)) {
+        let before_date = parse_date("date", &before.date)?;
+        let span = (parse_date("date", &next.date)? - before_date).num_days() as f64;
+        let elapsed = (parse_date("date", date)? - before_date).num_days() as f64;
+        result.balance = Some(before.balance + (next.balance - before.balance) * elapsed / span);
+        result.derived_from.push(entry_ref(next));
+    }
+    Ok(result)
+}
+
+/// The account's balance on `date`, exact when an entry was recorded that day
+/// and otherwise estimated by `method` (carried forward after the last entry).
+/// A date before the first entry gives a `no_data` result rather than zero.
+#[tauri::command]
+pub async fn get_balance_at(
+    pool: State<'_, SqlitePool>,
+    account_id: String,
+    date: String,
+    method: FillMethod,
+) -> Result<BalanceAt> {
+    let date = normalize_date("date", &date)?;
+    fetch_account(pool.inner(), &account_id).await?;
+    let entries = fetch_entries(pool.inner(), &account_id).await?;
+    balance_at(&account_id, &entries, &date, method)
+}
+
+/// `get_balance_at` for several accounts, in the order given.
+#[tauri::command]
+pub async fn get_balances_at(
+    pool: State<'_, SqlitePool>,
+    account_ids: Vec<String>,
+    date: String,
+    method: FillMethod,
+) -> Result<Vec<BalanceAt>> {
+    let date = normalize_date("date", &date)?;
+    let mut tx = pool.begin().await?;
+    let mut results = Vec::with_capacity(account_ids.len());
+    for account_id in &account_ids {
+        fetch_account(&mut *tx, account_id).await?;
+        let entries = fetch_entries(&mut *tx, account_id).await?;
+        results.push(balance_at(account_id, &entries, &date, method)?);
+    }
+    tx.commit().await?;
+    Ok(results)
+}
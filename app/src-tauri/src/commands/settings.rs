@@ -0,0 +1,56 @@
+use serde_json::{Map, Value};
+use sqlx::{SqliteExecutor, SqlitePool};
+use tauri::State;
+
+use super::now;
+use crate::error::{Error, Result};
+use crate::models::{Settings, UpdateSettingsInput};
+
+/// The saved settings, with defaults for anything never set.
+pub(crate) async fn load_settings<'e>(executor: impl SqliteExecutor<'e>) -> Result<Settings> {
+    let rows: Vec<(String, String)> = sqlx::query_as("SELECT key, value FROM settings")
+        .fetch_all(executor)
+        .await?;
+    let mut values = Map::new();
+    for (key, value) in rows {
+        let value = serde_json::from_str(&value)
+            .map_err(|error| Error::validation("settings", format!("'{key}': {error}")))?;
+        values.insert(key, value);
+    }
+    serde_json::from_value(Value::Object(values))
+        .map_err(|error| Error::validation("settings", error.to_string()))
+}
+
+#[tauri::command]
+pub async fn get_settings(pool: State<'_, SqlitePool>) -> Result<Settings> {
+    load_settings(pool.inner()).await
+}
+
+#[tauri::command]
+pub async fn update_settings(
+    pool: State<'_, SqlitePool>,
+    mut input: UpdateSettingsInput,
+) -> Result<Settings> {
+    input.validate()?;
+    let Ok(Value::Object(changes)) = serde_json::to_value(&input) else {
+        unreachable!("UpdateSettingsInput serializes to an object");
+    };
+
+    let mut tx = pool.begin().await?;
+    let updated_at = now();
+    for (key, value) in changes {
+        sqlx::query(
+            "INSERT INTO settings (key, value, updated_at) VALUES (?, ?, ?)
+             ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
+        )
+        .bind(&key)
+        .bind(value.to_string())
+        .bind(&updated_at)
+        .execute(&mut *tx)
+        .await?;
+    }
+    let settings = load_settings(&mut *tx).await?;
+
+    tx.commit().await?;
+    Ok(settings)
+}
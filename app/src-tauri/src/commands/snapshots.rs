@@ -0,0 +1,135 @@
+use sqlx::SqlitePool;
+use tauri::State;
+
+use super::balances::fetch_entries;
+use super::normalize_date;
+use super::series::balance_at;
+use super::settings::load_settings;
+use crate::error::{Error, Result};
+use crate::models::{
+    minor_units_for, round_amount, sum_amounts, Account, AccountCategory, AccountSnapshotChange,
+    BalanceAtStatus, BalanceEntry, CategorySnapshotTotal, FillMethod, SnapshotComparison,
+    SnapshotPresence,
+};
+
+/// The owned share of the account's balance on `date`, interpolated between
+/// entries, and whether it was estimated. `None` when the account wasn't held.
+pub(crate) fn snapshot_balance(
+    account: &Account,
+    entries: &[BalanceEntry],
+    date: &str,
+) -> Result<Option<(f64, bool)>> {
+    let not_opened = account
+        .opened_at
+        .as_deref()
+        .is_some_and(|opened| opened > date);
+    let closed = account
+        .closed_at
+        .as_deref()
+        .is_some_and(|closed| closed < date);
+    if not_opened || closed {
+        return Ok(None);
+    }
+    let at = balance_at(&account.id, entries, date, FillMethod::Linear)?;
+    Ok(at.balance.map(|balance| {
+        let owed = account.category == AccountCategory::Liability;
+        let balance = if owed { balance.abs() } else { balance };
+        let share = balance * account.ownership_percent / 100.0;
+        let balance = round_amount(share, minor_units_for(&account.currency));
+        (balance, at.status == BalanceAtStatus::Estimated)
+    }))
+}
+
+fn change_percent(from: f64, change: f64) -> Option<f64> {
+    (from != 0.0).then(|| change / from.abs() * 100.0)
+}
+
+/// Every account held on `date_a` or `date_b`, with its balance on each,
+/// interpolated where no entry was recorded that day. Accounts excluded from
+/// net worth are listed but left out of the totals; shared ones count at the
+/// owned share.
+#[tauri::command]
+pub async fn compare_snapshots(
+    pool: State<'_, SqlitePool>,
+    date_a: String,
+    date_b: String,
+) -> Result<SnapshotComparison> {
+    let date_a = normalize_date("date_a", &date_a)?;
+    let date_b = normalize_date("date_b", &date_b)?;
+    if date_b < date_a {
+        return Err(Error::validation("date_b", "must not be before date_a"));
+    }
+
+    let mut tx = pool.begin().await?;
+    let settings = load_settings(&mut *tx).await?;
+    let accounts = sqlx::query_as::<_, Account>("SELECT * FROM accounts ORDER BY sort_order, name")
+        .fetch_all(&mut *tx)
+        .await?;
+    let mut rows = Vec::new();
+    for account in accounts {
+        let entries = fetch_entries(&mut *tx, &account.id).await?;
+        let a = snapshot_balance(&account, &entries, &date_a)?;
+        let b = snapshot_balance(&account, &entries, &date_b)?;
+        let presence = match (a, b) {
+            (None, None) => continue,
+            (None, Some(_)) => SnapshotPresence::Appeared,
+            (Some(_), None) => SnapshotPresence::Closed,
+            (Some(_), Some(_)) => SnapshotPresence::Both,
+        };
+        let (balance_a, balance_b) = (a.map(|(balance, _)| balance), b.map(|(balance, _)| balance));
+        let change = sum_amounts([balance_b.unwrap_or(0.0), -balance_a.unwrap_or(0.0)]);
+        rows.push(AccountSnapshotChange {
+            account_id: account.id,
+            name: account.name,
+            category: account.category,
+            currency: account.currency,
+            presence,
+            balance_a,
+            balance_b,
+            change,
+            change_percent: balance_a.and_then(|from| change_percent(from, change)),
+            is_estimated: [a, b].iter().flatten().any(|(_, estimated)| *estimated),
+            exclude_from_net_worth: account.exclude_from_net_worth,
+        });
+    }
+    tx.commit().await?;
+
+    let units = minor_units_for(&settings.base_currency);
+    let totals: Vec<CategorySnapshotTotal> = AccountCategory::ALL
+        .into_iter()
+        .map(|category| {
+            let counted = rows
+                .iter()
+                .filter(|row| row.category == category && !row.exclude_from_net_worth);
+            let total = |balance: fn(&AccountSnapshotChange) -> Option<f64>| {
+                round_amount(sum_amounts(counted.clone().filter_map(balance)), units)
+            };
+            let (total_a, total_b) = (total(|row| row.balance_a), total(|row| row.balance_b));
+            let change = sum_amounts([total_b, -total_a]);
+            CategorySnapshotTotal {
+                category,
+                total_a,
+                total_b,
+                change,
+                change_percent: change_percent(total_a, change),
+            }
+        })
+        .collect();
+    let net_worth = |total: fn(&CategorySnapshotTotal) -> f64| {
+        sum_amounts(totals.iter().map(|category| match category.category {
+            AccountCategory::Asset => total(category),
+            AccountCategory::Liability => -total(category),
+        }))
+    };
+    let (net_worth_a, net_worth_b) = (net_worth(|t| t.total_a), net_worth(|t| t.total_b));
+
+    Ok(SnapshotComparison {
+        date_a,
+        date_b,
+        accounts: rows,
+        totals,
+        net_worth_a,
+        net_worth_b,
+        net_worth_change: sum_amounts([net_worth_b, -net_worth_a]),
+    })
+}
@@ -0,0 +1,243 @@
+use std::collections::HashSet;
+
+use chrono::Days;
+use sqlx::{SqliteConnection, SqlitePool};
+use tauri::State;
+use uuid::Uuid;
+
+use super::balances::fetch_entries;
+use super::milestones::{distinct, first_reached, insert_milestone, net_worth_series};
+use super::settings::load_settings;
+use super::{normalize_date, now, parse_date, today_date};
+use crate::dates;
+use crate::error::Result;
+use crate::models::{
+    format_amount, minor_units_for, round_amount, Account, AccountCategory, AccountType, Milestone,
+    MilestoneCategory, MilestoneRecurrence, MilestoneSuggestion, MilestoneSuggestionKind,
+    MilestoneTargetScope,
+};
+
+/// Net worth is suggested as a milestone each time it first reaches a multiple of this.
+const NET_WORTH_STEP: f64 = 50_000.0;
+
+/// A property account added this many days ago or fewer counts as newly bought.
+const NEW_PROPERTY_DAYS: u64 = 30;
+
+/// A milestone this close to a suggestion's date, about the same thing, means
+/// it has already been marked.
+const NEARBY_DAYS: u64 = 30;
+
+/// The date a liability's balance reached zero and stayed there, when it once
+/// owed something and its latest balance is zero.
+async fn paid_off_on(conn: &mut SqliteConnection, account: &Account) -> Result<Option<String>> {
+    let entries = fetch_entries(&mut *conn, &account.id).await?;
+    let units = minor_units_for(&account.currency);
+    let owing = entries
+        .iter()
+        .rposition(|entry| round_amount(entry.balance, units) != 0.0);
+    match owing {
+        Some(last_owing) if last_owing + 1 < entries.len() => {
+            Ok(Some(entries[last_owing + 1].date.clone()))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// The day of `created_at`, a timestamp.
+fn added_on(account: &Account) -> &str {
+    account.created_at.get(..10).unwrap_or(&account.created_at)
+}
+
+/// When a property was bought: its opening date, else its first entry, else the
+/// day it was added.
+async fn bought_on(conn: &mut SqliteConnection, account: &Account) -> Result<String> {
+    if let Some(opened_at) = &account.opened_at {
+        return Ok(opened_at.clone());
+    }
+    let first: Option<String> =
+        sqlx::query_scalar("SELECT MIN(date) FROM balance_entries WHERE account_id = ?")
+            .bind(&account.id)
+            .fetch_one(&mut *conn)
+            .await?;
+    Ok(first.unwrap_or_else(|| added_on(account).to_string()))
+}
+
+/// Whether a milestone within `NEARBY_DAYS` of `date` already covers the
+/// suggestion: one linked to any of `account_ids`, or any milestone at all when
+/// there are none.
+async fn already_marked(
+    conn: &mut SqliteConnection,
+    date: &str,
+    account_ids: &[String],
+) -> Result<bool> {
+    let date = parse_date("date", date)?;
+    let from = dates::format(date - Days::new(NEARBY_DAYS));
+    let to = dates::format(date + Days::new(NEARBY_DAYS));
+    if account_ids.is_empty() {
+        let nearby: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM milestones WHERE date >= ? AND date <= ?)",
+        )
+        .bind(&from)
+        .bind(&to)
+        .fetch_one(&mut *conn)
+        .await?;
+        return Ok(nearby);
+    }
+    for account_id in account_ids {
+        let nearby: bool = sqlx::query_scalar(
+            "SELECT EXISTS(
+                SELECT 1 FROM milestones m
+                JOIN milestone_accounts ma ON ma.milestone_id = m.id
+                WHERE ma.account_id = ? AND m.date >= ? AND m.date <= ?
+             )",
+        )
+        .bind(account_id)
+        .bind(&from)
+        .bind(&to)
+        .fetch_one(&mut *conn)
+        .await?;
+        if nearby {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Milestones worth adding: liabilities paid off, properties added in the last
+/// `NEW_PROPERTY_DAYS`, and each multiple of 50,000 net worth first reached, in
+/// date order. Nothing is saved. Suggestions once accepted or dismissed, or
+/// with a milestone about the same thing within `NEARBY_DAYS`, are left out.
+#[tauri::command]
+pub async fn suggest_milestones(pool: State<'_, SqlitePool>) -> Result<Vec<MilestoneSuggestion>> {
+    let mut conn = pool.acquire().await?;
+    let resolved: HashSet<String> =
+        sqlx::query_scalar("SELECT key FROM milestone_suggestion_resolutions")
+            .fetch_all(&mut *conn)
+            .await?
+            .into_iter()
+            .collect();
+    let accounts = sqlx::query_as::<_, Account>(
+        "SELECT * FROM accounts WHERE archived_at IS NULL ORDER BY sort_order, name",
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut candidates = Vec::new();
+    let new_since = today_date() - Days::new(NEW_PROPERTY_DAYS);
+    for account in &accounts {
+        if account.category == AccountCategory::Liability {
+            if let Some(date) = paid_off_on(&mut conn, account).await? {
+                candidates.push(MilestoneSuggestion {
+                    key: format!("paid_off:{}", account.id),
+                    kind: MilestoneSuggestionKind::PaidOff,
+                    date,
+                    label: format!("Paid off {}", account.name),
+                    account_ids: vec![account.id.clone()],
+                    target_amount: Some(0.0),
+                    target_scope: Some(MilestoneTargetScope::Account),
+                    category: MilestoneCategory::Payoff,
+                });
+            }
+        }
+        let added = parse_date("created_at", added_on(account))?;
+        if account.account_type == AccountType::Property && added >= new_since {
+            candidates.push(MilestoneSuggestion {
+                key: format!("bought:{}", account.id),
+                kind: MilestoneSuggestionKind::Bought,
+                date: bought_on(&mut conn, account).await?,
+                label: format!("Bought {}", account.name),
+                account_ids: vec![account.id.clone()],
+                target_amount: None,
+                target_scope: None,
+                category: MilestoneCategory::Purchase,
+            });
+        }
+    }
+
+    let base_currency = load_settings(&mut *conn).await?.base_currency;
+    let series = net_worth_series(&mut conn).await?;
+    let peak = series.iter().map(|point| point.balance).fold(0.0, f64::max);
+    for step in 1..=(peak / NET_WORTH_STEP).floor() as u64 {
+        let target = step as f64 * NET_WORTH_STEP;
+        let Some(date) = first_reached(&series, target, false) else {
+            continue;
+        };
+        candidates.push(MilestoneSuggestion {
+            key: format!("net_worth:{}", step * NET_WORTH_STEP as u64),
+            kind: MilestoneSuggestionKind::NetWorthCrossed,
+            date: dates::format(date),
+            label: format!(
+                "Net worth reached {}",
+                format_amount(target, &base_currency)
+            ),
+            account_ids: Vec::new(),
+            target_amount: Some(target),
+            target_scope: Some(MilestoneTargetScope::NetWorth),
+            category: MilestoneCategory::General,
+        });
+    }
+
+    let mut suggestions = Vec::new();
+    for suggestion in candidates {
+        if resolved.contains(&suggestion.key)
+            || already_marked(&mut conn, &suggestion.date, &suggestion.account_ids).await?
+        {
+            continue;
+        }
+        suggestions.push(suggestion);
+    }
+    suggestions.sort_by(|a, b| a.date.cmp(&b.date));
+    Ok(suggestions)
+}
+
+async fn resolve(conn: &mut SqliteConnection, key: &str, resolution: &str) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO milestone_suggestion_resolutions (key, resolution, resolved_at) VALUES (?, ?, ?)
+         ON CONFLICT(key) DO UPDATE SET resolution = excluded.resolution, resolved_at = excluded.resolved_at",
+    )
+    .bind(key)
+    .bind(resolution)
+    .bind(now())
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+/// Saves the suggestion as a milestone; a target is marked achieved on its date.
+#[tauri::command]
+pub async fn accept_milestone_suggestion(
+    pool: State<'_, SqlitePool>,
+    suggestion: MilestoneSuggestion,
+) -> Result<Milestone> {
+    let date = normalize_date("date", &suggestion.date)?;
+    let mut milestone = Milestone {
+        id: Uuid::new_v4().to_string(),
+        achieved_at: suggestion.target_amount.map(|_| date.clone()),
+        date,
+        label: suggestion.label.trim().to_string(),
+        account_ids: distinct(suggestion.account_ids),
+        legacy_account_id: None,
+        created_at: now(),
+        target_amount: suggestion.target_amount,
+        target_scope: suggestion.target_scope,
+        recurrence: MilestoneRecurrence::None,
+        parent_id: None,
+        materialised_until: None,
+        category: suggestion.category,
+        icon: None,
+        sort_order: 0,
+    };
+    milestone.validate()?;
+
+    let mut tx = pool.begin().await?;
+    insert_milestone(&mut tx, &mut milestone).await?;
+    resolve(&mut tx, &suggestion.key, "accepted").await?;
+    tx.commit().await?;
+    Ok(milestone)
+}
+
+/// Stops the suggestion with `key` being offered again.
+#[tauri::command]
+pub async fn dismiss_milestone_suggestion(pool: State<'_, SqlitePool>, key: String) -> Result<()> {
+    resolve(&mut *pool.acquire().await?, &key, "dismissed").await
+}
@@ -0,0 +1,139 @@
+use std::collections::HashSet;
+
+use sqlx::{SqliteExecutor, SqlitePool};
+use tauri::State;
+use uuid::Uuid;
+
+use super::accounts::{fetch_account, ACCOUNT_WITH_BALANCE_SELECT};
+use super::now;
+use crate::error::{Error, Result};
+use crate::models::{AccountWithBalance, CreateTagInput, Tag};
+
+pub(crate) async fn fetch_account_tags<'e>(
+    executor: impl SqliteExecutor<'e>,
+    account_id: &str,
+) -> Result<Vec<Tag>> {
+    let tags = sqlx::query_as::<_, Tag>(
+        "SELECT t.* FROM tags t
+         JOIN account_tags at ON at.tag_id = t.id
+         WHERE at.account_id = ?
+         ORDER BY t.name",
+    )
+    .bind(account_id)
+    .fetch_all(executor)
+    .await?;
+    Ok(tags)
+}
+
+#[tauri::command]
+pub async fn get_tags(pool: State<'_, SqlitePool>) -> Result<Vec<Tag>> {
+    let tags = sqlx::query_as::<_, Tag>("SELECT * FROM tags ORDER BY name")
+        .fetch_all(pool.inner())
+        .await?;
+    Ok(tags)
+}
+
+/// Tag names are unique ignoring case, so "Joint" and "joint" are the same tag.
+#[tauri::command]
+pub async fn create_tag(pool: State<'_, SqlitePool>, input: CreateTagInput) -> Result<Tag> {
+    input.validate()?;
+    let name = input.name.trim().to_string();
+
+    let existing: Option<String> =
+        sqlx::query_scalar("SELECT name FROM tags WHERE lower(name) = lower(?)")
+            .bind(&name)
+            .fetch_optional(pool.inner())
+            .await?;
+    if let Some(existing) = existing {
+        return Err(Error::Conflict(format!("tag '{existing}' already exists")));
+    }
+
+    let tag = Tag {
+        id: Uuid::new_v4().to_string(),
+        name,
+        created_at: now(),
+    };
+    sqlx::query("INSERT INTO tags (id, name, created_at) VALUES (?, ?, ?)")
+        .bind(&tag.id)
+        .bind(&tag.name)
+        .bind(&tag.created_at)
+        .execute(pool.inner())
+        .await?;
+
+    Ok(tag)
+}
+
+/// Removes the tag and its assignments; the accounts themselves are untouched.
+#[tauri::command]
+pub async fn delete_tag(pool: State<'_, SqlitePool>, id: String) -> Result<()> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM account_tags WHERE tag_id = ?")
+        .bind(&id)
+        .execute(&mut *tx)
+        .await?;
+    let result = sqlx::query("DELETE FROM tags WHERE id = ?")
+        .bind(&id)
+        .execute(&mut *tx)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(Error::not_found("tag", id));
+    }
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Replaces the account's tags with exactly `tag_ids`.
+#[tauri::command]
+pub async fn set_account_tags(
+    pool: State<'_, SqlitePool>,
+    account_id: String,
+    tag_ids: Vec<String>,
+) -> Result<Vec<Tag>> {
+    let mut tx = pool.begin().await?;
+    fetch_account(&mut *tx, &account_id).await?;
+
+    let known: HashSet<String> = sqlx::query_scalar("SELECT id FROM tags")
+        .fetch_all(&mut *tx)
+        .await?
+        .into_iter()
+        .collect();
+    if let Some(unknown) = tag_ids.iter().find(|id| !known.contains(*id)) {
+        return Err(Error::not_found("tag", unknown.clone()));
+    }
+
+    sqlx::query("DELETE FROM account_tags WHERE account_id = ?")
+        .bind(&account_id)
+        .execute(&mut *tx)
+        .await?;
+    for tag_id in tag_ids.iter().collect::<HashSet<_>>() {
+        sqlx::query("INSERT INTO account_tags (account_id, tag_id) VALUES (?, ?)")
+            .bind(&account_id)
+            .bind(tag_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    let tags = fetch_account_tags(&mut *tx, &account_id).await?;
+    tx.commit().await?;
+    Ok(tags)
+}
+
+#[tauri::command]
+pub async fn get_accounts_by_tag(
+    pool: State<'_, SqlitePool>,
+    tag_id: String,
+) -> Result<Vec<AccountWithBalance>> {
+    let sql = format!(
+        "{ACCOUNT_WITH_BALANCE_SELECT}
+         WHERE a.is_active = 1 AND a.archived_at IS NULL
+           AND a.id IN (SELECT account_id FROM account_tags WHERE tag_id = ?)
+         ORDER BY a.sort_order, a.name"
+    );
+    let accounts = sqlx::query_as::<_, AccountWithBalance>(&sql)
+        .bind(&tag_id)
+        .fetch_all(pool.inner())
+        .await?;
+    Ok(accounts)
+}
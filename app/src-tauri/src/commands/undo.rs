@@ -0,0 +1,253 @@
+use std::collections::HashSet;
+
+use sqlx::{Connection, SqliteConnection, SqlitePool};
+use tauri::{AppHandle, State};
+
+use super::attachments::{remove_unreferenced_files, stored_names_for_entry};
+use super::now;
+use crate::error::{Error, Result};
+use crate::models::{from_minor, BalanceEntry, SkippedUndo, UndoOperation, UndoReport, UndoStep};
+
+/// How many steps the journal keeps; older ones are dropped as new ones arrive.
+const MAX_UNDO_STEPS: i64 = 200;
+
+#[derive(sqlx::FromRow)]
+struct UndoRow {
+    #[sqlx(flatten)]
+    step: UndoStep,
+    before_entries: String,
+    after_entries: String,
+}
+
+fn decode_entries(json: &str) -> Result<Vec<BalanceEntry>> {
+    serde_json::from_str(json).map_err(|error| Error::validation("undo_log", error.to_string()))
+}
+
+/// Journals a change that turned the entries in `before` into those in `after`:
+/// an entry only in `before` was deleted, one only in `after` was created. A new
+/// step discards whatever could have been redone.
+pub(crate) async fn record_undo(
+    conn: &mut SqliteConnection,
+    operation: UndoOperation,
+    description: String,
+    before: &[BalanceEntry],
+    after: &[BalanceEntry],
+) -> Result<()> {
+    if before.is_empty() && after.is_empty() {
+        return Ok(());
+    }
+    let encode = |entries| serde_json::to_string(entries).expect("balance entries serialize");
+
+    sqlx::query("DELETE FROM undo_log WHERE undone = 1")
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query(
+        "INSERT INTO undo_log (operation, description, before_entries, after_entries, created_at)
+         VALUES (?, ?, ?, ?, ?)",
+    )
+    .bind(operation)
+    .bind(description)
+    .bind(encode(before))
+    .bind(encode(after))
+    .bind(now())
+    .execute(&mut *conn)
+    .await?;
+    sqlx::query(
+        "DELETE FROM undo_log WHERE id NOT IN (SELECT id FROM undo_log ORDER BY id DESC LIMIT ?)",
+    )
+    .bind(MAX_UNDO_STEPS)
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+/// Puts `entry` back as it was, under its original id. Returns why it can't be
+/// when its account is gone or another entry has since taken its date.
+async fn restore_entry(
+    conn: &mut SqliteConnection,
+    entry: &BalanceEntry,
+) -> Result<Option<String>> {
+    let account_exists: bool =
+        sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM accounts WHERE id = ?)")
+            .bind(&entry.account_id)
+            .fetch_one(&mut *conn)
+            .await?;
+    if !account_exists {
+        return Ok(Some(format!(
+            "the account of the {} entry has since been deleted",
+            entry.date
+        )));
+    }
+    let taken: bool = sqlx::query_scalar(
+        "SELECT EXISTS(SELECT 1 FROM balance_entries WHERE account_id = ? AND date = ? AND id != ?)",
+    )
+    .bind(&entry.account_id)
+    .bind(&entry.date)
+    .bind(&entry.id)
+    .fetch_one(&mut *conn)
+    .await?;
+    if taken {
+        return Ok(Some(format!(
+            "another entry has since been recorded on {}",
+            entry.date
+        )));
+    }
+
+    // An upsert on the id rather than INSERT OR REPLACE, which would delete the
+    // row first and take its attachments with it.
+    sqlx::query(
+        "INSERT INTO balance_entries (id, account_id, date, balance, balance_minor, minor_units, notes, created_at, original_currency, original_amount, source, verified_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+         ON CONFLICT(id) DO UPDATE SET
+            account_id = excluded.account_id,
+            date = excluded.date,
+            balance = excluded.balance,
+            balance_minor = excluded.balance_minor,
+            minor_units = excluded.minor_units,
+            notes = excluded.notes,
+            created_at = excluded.created_at,
+            original_currency = excluded.original_currency,
+            original_amount = excluded.original_amount,
+            source = excluded.source,
+            verified_at = excluded.verified_at",
+    )
+    .bind(&entry.id)
+    .bind(&entry.account_id)
+    .bind(&entry.date)
+    .bind(from_minor(entry.balance_minor, entry.minor_units))
+    .bind(entry.balance_minor)
+    .bind(entry.minor_units)
+    .bind(&entry.notes)
+    .bind(&entry.created_at)
+    .bind(&entry.original_currency)
+    .bind(entry.original_amount)
+    .bind(entry.source)
+    .bind(&entry.verified_at)
+    .execute(&mut *conn)
+    .await?;
+    Ok(None)
+}
+
+/// Turns the entries in `from` back into those in `to`: entries only in `from`
+/// are deleted and the rest restored, and the attachment files of those deleted
+/// are added to `stored_names`. Returns why it can't be done, having changed
+/// nothing.
+async fn apply_step(
+    conn: &mut SqliteConnection,
+    from: &[BalanceEntry],
+    to: &[BalanceEntry],
+    stored_names: &mut Vec<String>,
+) -> Result<Option<String>> {
+    let mut step = conn.begin().await?;
+    let kept: HashSet<&str> = to.iter().map(|entry| entry.id.as_str()).collect();
+    let mut deleted_names = Vec::new();
+    for entry in from
+        .iter()
+        .filter(|entry| !kept.contains(entry.id.as_str()))
+    {
+        deleted_names.extend(stored_names_for_entry(&mut *step, &entry.id).await?);
+        sqlx::query("DELETE FROM balance_entries WHERE id = ?")
+            .bind(&entry.id)
+            .execute(&mut *step)
+            .await?;
+    }
+    for entry in to {
+        if let Some(reason) = restore_entry(&mut step, entry).await? {
+            step.rollback().await?;
+            return Ok(Some(reason));
+        }
+    }
+    step.commit().await?;
+    stored_names.extend(deleted_names);
+    Ok(None)
+}
+
+/// Replays up to `count` steps in one direction. A step that can no longer be
+/// applied is dropped from the journal and reported rather than failing the rest.
+/// Attachment files left unreferenced by entries it deletes are removed.
+async fn replay(app: &AppHandle, pool: &SqlitePool, count: u32, undo: bool) -> Result<UndoReport> {
+    let mut tx = pool.begin().await?;
+    let rows = sqlx::query_as::<_, UndoRow>(if undo {
+        "SELECT * FROM undo_log WHERE undone = 0 ORDER BY id DESC LIMIT ?"
+    } else {
+        "SELECT * FROM undo_log WHERE undone = 1 ORDER BY id LIMIT ?"
+    })
+    .bind(count)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let mut report = UndoReport::default();
+    let mut stored_names = Vec::new();
+    for row in rows {
+        let before = decode_entries(&row.before_entries)?;
+        let after = decode_entries(&row.after_entries)?;
+        let (from, to) = if undo {
+            (&after, &before)
+        } else {
+            (&before, &after)
+        };
+        let mut step = row.step;
+        if let Some(reason) = apply_step(&mut tx, from, to, &mut stored_names).await? {
+            sqlx::query("DELETE FROM undo_log WHERE id = ?")
+                .bind(step.id)
+                .execute(&mut *tx)
+                .await?;
+            report.skipped.push(SkippedUndo { step, reason });
+            continue;
+        }
+        step.undone = undo;
+        sqlx::query("UPDATE undo_log SET undone = ? WHERE id = ?")
+            .bind(undo)
+            .bind(step.id)
+            .execute(&mut *tx)
+            .await?;
+        report.applied.push(step);
+    }
+
+    tx.commit().await?;
+    remove_unreferenced_files(app, pool, stored_names).await?;
+    Ok(report)
+}
+
+/// Reverts the last `count` changes to balance entries (1 by default), newest
+/// first. Deleted entries come back with their ids but without attachments.
+#[tauri::command]
+pub async fn undo_last(
+    app: AppHandle,
+    pool: State<'_, SqlitePool>,
+    count: Option<u32>,
+) -> Result<UndoReport> {
+    replay(&app, pool.inner(), count.unwrap_or(1), true).await
+}
+
+/// Reapplies the last `count` undone changes (1 by default), most recently
+/// undone first. Any new change to an entry clears what can be redone.
+#[tauri::command]
+pub async fn redo_last(
+    app: AppHandle,
+    pool: State<'_, SqlitePool>,
+    count: Option<u32>,
+) -> Result<UndoReport> {
+    replay(&app, pool.inner(), count.unwrap_or(1), false).await
+}
+
+/// The journal: the steps that can be undone, newest first, then those that can
+/// be redone, next first.
+#[tauri::command]
+pub async fn get_undo_log(pool: State<'_, SqlitePool>) -> Result<Vec<UndoStep>> {
+    let steps = sqlx::query_as::<_, UndoStep>(
+        "SELECT id, operation, description, undone, created_at FROM undo_log
+         ORDER BY undone, CASE WHEN undone = 0 THEN -id ELSE id END",
+    )
+    .fetch_all(pool.inner())
+    .await?;
+    Ok(steps)
+}
+
+#[tauri::command]
+pub async fn clear_undo_log(pool: State<'_, SqlitePool>) -> Result<()> {
+    sqlx::query("DELETE FROM undo_log")
+        .execute(pool.inner())
+        .await?;
+    Ok(())
+}
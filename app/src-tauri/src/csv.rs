@@ -0,0 +1,95 @@
+//! A small RFC 4180 reader and writer: comma (or tab) separated, fields
+//! optionally wrapped in double quotes with `""` for a literal quote, and LF or
+//! CRLF line endings.
+
+/// A parsed record and the line it started on, counting from 1.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    pub line: usize,
+    pub fields: Vec<String>,
+}
+
+/// Splits `text` into records, skipping blank lines. Fails with the starting
+/// line of a quoted field that is never closed.
+pub fn parse(text: &str) -> Result<Vec<Record>, String> {
+    parse_with(text, ',')
+}
+
+/// Like [`parse`], with fields separated by `delimiter`.
+pub fn parse_with(text: &str, delimiter: char) -> Result<Vec<Record>, String> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let mut records = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut line = 1;
+    let mut record_line = 1;
+    let mut in_quotes = false;
+    let mut quote_line = 0;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                '\n' => {
+                    line += 1;
+                    field.push(c);
+                }
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' if field.is_empty() => {
+                in_quotes = true;
+                quote_line = line;
+            }
+            c if c == delimiter => fields.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                fields.push(std::mem::take(&mut field));
+                push_record(&mut records, record_line, std::mem::take(&mut fields));
+                line += 1;
+                record_line = line;
+            }
+            _ => field.push(c),
+        }
+    }
+    if in_quotes {
+        return Err(format!("unclosed quote starting on line {quote_line}"));
+    }
+    fields.push(field);
+    push_record(&mut records, record_line, fields);
+
+    Ok(records)
+}
+
+fn push_record(records: &mut Vec<Record>, line: usize, fields: Vec<String>) {
+    if fields.len() == 1 && fields[0].trim().is_empty() {
+        return;
+    }
+    records.push(Record { line, fields });
+}
+
+/// Appends `fields` to `out` as one CRLF-terminated record, quoting fields that
+/// hold the delimiter, a quote or a line break.
+pub fn write_record<S: AsRef<str>>(out: &mut String, fields: &[S], delimiter: char) {
+    for (index, field) in fields.iter().enumerate() {
+        if index > 0 {
+            out.push(delimiter);
+        }
+        let field = field.as_ref();
+        if field.contains([delimiter, '"', '\n', '\r']) {
+            out.push('"');
+            out.push_str(&field.replace('"', "\"\""));
+            out.push('"');
+        } else {
+            out.push_str(field);
+        }
+    }
+    out.push_str("\r\n");
+}
@@ -0,0 +1,38 @@
+//! Parsing and normalising the calendar dates stored as `YYYY-MM-DD` text.
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+
+/// The stored shape of every date column, which keeps string order and date order the same.
+pub const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Reads an ISO 8601 calendar date, also accepting unpadded months and days
+/// (`2024-2-3`) and full timestamps, whose date part is kept. Impossible dates
+/// such as `2024-02-30` and other layouts such as `01/02/2024` give `None`.
+pub fn parse(value: &str) -> Option<NaiveDate> {
+    let value = value.trim();
+    if let Ok(date) = NaiveDate::parse_from_str(value, DATE_FORMAT) {
+        return Some(date);
+    }
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Some(timestamp.date_naive());
+    }
+    ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .map(|timestamp| timestamp.date())
+}
+
+/// Whether `value` carries a time of day, as in `2024-03-01T00:00:00` or
+/// `2024-03-01 00:00`. Dates alone never hold a `T` or a colon.
+pub fn has_time(value: &str) -> bool {
+    value.trim().contains(['T', 't', ':'])
+}
+
+pub fn format(date: NaiveDate) -> String {
+    date.format(DATE_FORMAT).to_string()
+}
+
+/// `value` in the stored shape, if it is a date at all.
+pub fn normalize(value: &str) -> Option<String> {
+    parse(value).map(format)
+}
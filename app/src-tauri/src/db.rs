@@ -1,5 +1,122 @@
+use std::future::Future;
+use std::path::Path;
+use std::pin::Pin;
+
+use sqlx::error::BoxDynError;
+use sqlx::migrate::{Migration as SqlxMigration, MigrationSource, MigrationType, Migrator};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool};
 use tauri_plugin_sql::{Migration, MigrationKind};
 
+use crate::commands::accounts::currency_problem;
+use crate::models::{from_minor, minor_units_for, to_minor, AccountIssue};
+
+/// Feeds `get_migrations()` to sqlx the same way tauri_plugin_sql does, so the
+/// checksums recorded by either side match and each sees the other's work as
+/// already applied.
+#[derive(Debug)]
+struct MigrationList(Vec<Migration>);
+
+impl MigrationSource<'static> for MigrationList {
+    fn resolve(
+        self,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<SqlxMigration>, BoxDynError>> + Send>> {
+        Box::pin(async move {
+            Ok(self
+                .0
+                .into_iter()
+                .filter(|migration| matches!(migration.kind, MigrationKind::Up))
+                .map(|migration| {
+                    SqlxMigration::new(
+                        migration.version,
+                        migration.description.into(),
+                        MigrationType::ReversibleUp,
+                        migration.sql.into(),
+                        false,
+                    )
+                })
+                .collect())
+        })
+    }
+}
+
+/// Opens the shared pool used by the Rust commands and brings the schema up to date.
+/// Accounts older versions let through with currencies that aren't ISO 4217 are
+/// returned for the caller to report, rather than guessed at.
+pub async fn connect(path: &Path) -> Result<(SqlitePool, Vec<AccountIssue>), sqlx::Error> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let options = SqliteConnectOptions::new()
+        .filename(path)
+        .create_if_missing(true)
+        .foreign_keys(true);
+    let pool = SqlitePool::connect_with(options).await?;
+
+    Migrator::new(MigrationList(get_migrations()))
+        .await?
+        .run(&pool)
+        .await?;
+    let invalid = invalid_currencies(&pool).await?;
+    backfill_minor_units(&pool).await?;
+
+    Ok((pool, invalid))
+}
+
+/// Accounts whose currency isn't ISO 4217, as `validate_accounts` lists them.
+async fn invalid_currencies(pool: &SqlitePool) -> Result<Vec<AccountIssue>, sqlx::Error> {
+    let rows: Vec<(String, String, String)> =
+        sqlx::query_as("SELECT id, name, currency FROM accounts ORDER BY sort_order, name")
+            .fetch_all(pool)
+            .await?;
+    Ok(rows
+        .into_iter()
+        .filter_map(|(account_id, name, currency)| {
+            let message = currency_problem(&currency)?;
+            Some(AccountIssue {
+                account_id,
+                name,
+                field: "currency".to_string(),
+                value: currency,
+                message,
+            })
+        })
+        .collect())
+}
+
+/// Balances used to be stored only as floats. Entries without integer minor
+/// units get them from their account's currency, and their float balance is
+/// rewritten from the rounded value.
+async fn backfill_minor_units(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    let rows: Vec<(String, f64, String)> = sqlx::query_as(
+        "SELECT be.id, be.balance, a.currency
+         FROM balance_entries be
+         JOIN accounts a ON a.id = be.account_id
+         WHERE be.balance_minor IS NULL",
+    )
+    .fetch_all(pool)
+    .await?;
+    if rows.is_empty() {
+        return Ok(());
+    }
+
+    let mut tx = pool.begin().await?;
+    for (id, balance, currency) in rows {
+        let minor_units = minor_units_for(&currency);
+        let balance_minor = to_minor(balance, minor_units);
+        sqlx::query(
+            "UPDATE balance_entries SET balance = ?, balance_minor = ?, minor_units = ? WHERE id = ?",
+        )
+        .bind(from_minor(balance_minor, minor_units))
+        .bind(balance_minor)
+        .bind(minor_units)
+        .bind(&id)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await
+}
+
 pub fn get_migrations() -> Vec<Migration> {
     vec![
         Migration {
@@ -60,5 +177,962 @@ pub fn get_migrations() -> Vec<Migration> {
             "#,
             kind: MigrationKind::Up,
         },
+        Migration {
+            version: 4,
+            description: "add_accounts_archived_at",
+            sql: r#"
+                ALTER TABLE accounts ADD COLUMN archived_at TEXT;
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 5,
+            description: "create_account_groups",
+            sql: r#"
+                CREATE TABLE IF NOT EXISTS account_groups (
+                    id TEXT PRIMARY KEY NOT NULL,
+                    name TEXT NOT NULL,
+                    sort_order INTEGER NOT NULL DEFAULT 0,
+                    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+                );
+
+                ALTER TABLE accounts ADD COLUMN group_id TEXT REFERENCES account_groups(id) ON DELETE SET NULL;
+
+                CREATE INDEX IF NOT EXISTS idx_accounts_group
+                ON accounts(group_id);
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 6,
+            description: "create_tags_tables",
+            sql: r#"
+                CREATE TABLE IF NOT EXISTS tags (
+                    id TEXT PRIMARY KEY NOT NULL,
+                    name TEXT NOT NULL COLLATE NOCASE UNIQUE,
+                    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+                );
+
+                CREATE TABLE IF NOT EXISTS account_tags (
+                    account_id TEXT NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+                    tag_id TEXT NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+                    PRIMARY KEY (account_id, tag_id)
+                );
+
+                CREATE INDEX IF NOT EXISTS idx_account_tags_tag
+                ON account_tags(tag_id);
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 7,
+            description: "add_accounts_sort_order",
+            sql: r#"
+                ALTER TABLE accounts ADD COLUMN sort_order INTEGER NOT NULL DEFAULT 0;
+
+                UPDATE accounts SET sort_order = (
+                    SELECT COUNT(*) FROM accounts earlier
+                    WHERE earlier.created_at < accounts.created_at
+                       OR (earlier.created_at = accounts.created_at AND earlier.id < accounts.id)
+                );
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 8,
+            description: "create_custom_account_types",
+            sql: r#"
+                CREATE TABLE IF NOT EXISTS custom_account_types (
+                    slug TEXT PRIMARY KEY NOT NULL,
+                    label TEXT NOT NULL,
+                    category TEXT NOT NULL CHECK(category IN ('asset', 'liability')),
+                    icon TEXT,
+                    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+                );
+
+                -- SQLite can't drop a CHECK in place, so rebuild accounts without the closed
+                -- list of types. Dropping the old table fires its ON DELETE actions, so the
+                -- rows that reference it are set aside first and put back afterwards.
+                CREATE TABLE accounts_new (
+                    id TEXT PRIMARY KEY NOT NULL,
+                    name TEXT NOT NULL,
+                    account_type TEXT NOT NULL,
+                    category TEXT NOT NULL CHECK(category IN ('asset', 'liability')),
+                    institution TEXT,
+                    description TEXT,
+                    currency TEXT NOT NULL DEFAULT 'GBP',
+                    is_active INTEGER NOT NULL DEFAULT 1,
+                    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                    updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+                    archived_at TEXT,
+                    group_id TEXT REFERENCES account_groups(id) ON DELETE SET NULL,
+                    sort_order INTEGER NOT NULL DEFAULT 0
+                );
+
+                INSERT INTO accounts_new (id, name, account_type, category, institution, description, currency, is_active, created_at, updated_at, archived_at, group_id, sort_order)
+                SELECT id, name, account_type, category, institution, description, currency, is_active, created_at, updated_at, archived_at, group_id, sort_order
+                FROM accounts;
+
+                CREATE TEMP TABLE balance_entries_backup AS SELECT * FROM balance_entries;
+                CREATE TEMP TABLE milestone_accounts_backup AS
+                    SELECT id, account_id FROM milestones WHERE account_id IS NOT NULL;
+                CREATE TEMP TABLE account_tags_backup AS SELECT * FROM account_tags;
+
+                DROP TABLE accounts;
+                ALTER TABLE accounts_new RENAME TO accounts;
+
+                INSERT INTO balance_entries SELECT * FROM balance_entries_backup;
+                UPDATE milestones SET account_id = (
+                    SELECT account_id FROM milestone_accounts_backup WHERE milestone_accounts_backup.id = milestones.id
+                )
+                WHERE id IN (SELECT id FROM milestone_accounts_backup);
+                INSERT INTO account_tags SELECT * FROM account_tags_backup;
+
+                DROP TABLE balance_entries_backup;
+                DROP TABLE milestone_accounts_backup;
+                DROP TABLE account_tags_backup;
+
+                CREATE INDEX IF NOT EXISTS idx_accounts_group
+                ON accounts(group_id);
+
+                -- Stands in for the old CHECK: a type must be built in or defined by the user.
+                CREATE TRIGGER IF NOT EXISTS accounts_known_type_insert
+                BEFORE INSERT ON accounts
+                WHEN NEW.account_type NOT IN ('property', 'pension', 'investment', 'savings', 'mortgage', 'loan', 'credit_card')
+                 AND NOT EXISTS (SELECT 1 FROM custom_account_types WHERE slug = NEW.account_type)
+                BEGIN
+                    SELECT RAISE(ABORT, 'unknown account type');
+                END;
+
+                CREATE TRIGGER IF NOT EXISTS accounts_known_type_update
+                BEFORE UPDATE OF account_type ON accounts
+                WHEN NEW.account_type NOT IN ('property', 'pension', 'investment', 'savings', 'mortgage', 'loan', 'credit_card')
+                 AND NOT EXISTS (SELECT 1 FROM custom_account_types WHERE slug = NEW.account_type)
+                BEGIN
+                    SELECT RAISE(ABORT, 'unknown account type');
+                END;
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 9,
+            description: "add_accounts_linked_account_id",
+            sql: r#"
+                ALTER TABLE accounts ADD COLUMN linked_account_id TEXT REFERENCES accounts(id) ON DELETE SET NULL;
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 10,
+            description: "add_accounts_ownership_percent",
+            sql: r#"
+                ALTER TABLE accounts ADD COLUMN ownership_percent REAL NOT NULL DEFAULT 100
+                    CHECK(ownership_percent >= 0 AND ownership_percent <= 100);
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 11,
+            description: "add_accounts_appearance",
+            sql: r#"
+                ALTER TABLE accounts ADD COLUMN color TEXT;
+                ALTER TABLE accounts ADD COLUMN icon TEXT;
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 12,
+            description: "add_accounts_interest_rate",
+            sql: r#"
+                ALTER TABLE accounts ADD COLUMN interest_rate REAL;
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 13,
+            description: "add_accounts_opened_at",
+            sql: r#"
+                ALTER TABLE accounts ADD COLUMN opened_at TEXT;
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 14,
+            description: "add_accounts_closed_at",
+            sql: r#"
+                ALTER TABLE accounts ADD COLUMN closed_at TEXT;
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 15,
+            description: "enforce_account_category",
+            sql: r#"
+                -- Repair rows whose stored category disagrees with their type.
+                UPDATE accounts SET category = 'asset'
+                WHERE account_type IN ('property', 'pension', 'investment', 'savings') AND category != 'asset';
+                UPDATE accounts SET category = 'liability'
+                WHERE account_type IN ('mortgage', 'loan', 'credit_card') AND category != 'liability';
+                UPDATE accounts SET category = (
+                    SELECT category FROM custom_account_types WHERE slug = accounts.account_type
+                )
+                WHERE account_type IN (SELECT slug FROM custom_account_types)
+                  AND category != (SELECT category FROM custom_account_types WHERE slug = accounts.account_type);
+
+                CREATE TRIGGER IF NOT EXISTS accounts_category_matches_type_insert
+                BEFORE INSERT ON accounts
+                WHEN NEW.category IS NOT (
+                    CASE
+                        WHEN NEW.account_type IN ('property', 'pension', 'investment', 'savings') THEN 'asset'
+                        WHEN NEW.account_type IN ('mortgage', 'loan', 'credit_card') THEN 'liability'
+                        ELSE (SELECT category FROM custom_account_types WHERE slug = NEW.account_type)
+                    END
+                )
+                BEGIN
+                    SELECT RAISE(ABORT, 'category does not match account type');
+                END;
+
+                CREATE TRIGGER IF NOT EXISTS accounts_category_matches_type_update
+                BEFORE UPDATE OF account_type, category ON accounts
+                WHEN NEW.category IS NOT (
+                    CASE
+                        WHEN NEW.account_type IN ('property', 'pension', 'investment', 'savings') THEN 'asset'
+                        WHEN NEW.account_type IN ('mortgage', 'loan', 'credit_card') THEN 'liability'
+                        ELSE (SELECT category FROM custom_account_types WHERE slug = NEW.account_type)
+                    END
+                )
+                BEGIN
+                    SELECT RAISE(ABORT, 'category does not match account type');
+                END;
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 16,
+            description: "add_accounts_exclude_from_net_worth",
+            sql: r#"
+                ALTER TABLE accounts ADD COLUMN exclude_from_net_worth INTEGER NOT NULL DEFAULT 0;
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 17,
+            description: "create_account_field_history",
+            sql: r#"
+                CREATE TABLE IF NOT EXISTS account_field_history (
+                    id TEXT PRIMARY KEY NOT NULL,
+                    account_id TEXT NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+                    field TEXT NOT NULL,
+                    old_value TEXT,
+                    new_value TEXT,
+                    changed_at TEXT NOT NULL
+                );
+
+                CREATE INDEX IF NOT EXISTS idx_account_field_history_account
+                ON account_field_history(account_id, changed_at);
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 18,
+            description: "create_pension_details",
+            sql: r#"
+                CREATE TABLE IF NOT EXISTS pension_details (
+                    account_id TEXT PRIMARY KEY NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+                    employer TEXT,
+                    employee_contribution_percent REAL NOT NULL DEFAULT 0,
+                    employer_contribution_percent REAL NOT NULL DEFAULT 0,
+                    annual_salary REAL,
+                    retirement_date TEXT,
+                    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+                );
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 19,
+            description: "create_property_details",
+            sql: r#"
+                CREATE TABLE IF NOT EXISTS property_details (
+                    account_id TEXT PRIMARY KEY NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+                    purchase_price REAL NOT NULL,
+                    purchase_date TEXT NOT NULL,
+                    deposit_paid REAL,
+                    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+                );
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 20,
+            description: "add_accounts_credit_limit",
+            sql: r#"
+                ALTER TABLE accounts ADD COLUMN credit_limit REAL;
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 21,
+            description: "add_accounts_reminder_frequency",
+            sql: r#"
+                ALTER TABLE accounts ADD COLUMN reminder_frequency TEXT
+                    CHECK(reminder_frequency IN ('weekly', 'monthly', 'quarterly', 'yearly', 'never'));
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 22,
+            description: "add_accounts_external_ref",
+            sql: r#"
+                ALTER TABLE accounts ADD COLUMN external_ref TEXT;
+                CREATE UNIQUE INDEX IF NOT EXISTS idx_accounts_external_ref
+                    ON accounts(external_ref) WHERE external_ref IS NOT NULL;
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 23,
+            description: "add_balance_entries_minor_units",
+            sql: r#"
+                -- Filled in from `balance` on startup, see `db::backfill_minor_units`.
+                ALTER TABLE balance_entries ADD COLUMN balance_minor INTEGER;
+                ALTER TABLE balance_entries ADD COLUMN minor_units INTEGER;
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 24,
+            description: "create_settings_table",
+            sql: r#"
+                CREATE TABLE IF NOT EXISTS settings (
+                    key TEXT PRIMARY KEY NOT NULL,
+                    value TEXT NOT NULL,
+                    updated_at TEXT NOT NULL
+                );
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 25,
+            description: "create_balance_entry_history",
+            sql: r#"
+                CREATE TABLE IF NOT EXISTS balance_entry_history (
+                    id TEXT PRIMARY KEY NOT NULL,
+                    entry_id TEXT NOT NULL,
+                    account_id TEXT NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+                    operation TEXT NOT NULL CHECK(operation IN ('update', 'delete')),
+                    old_date TEXT NOT NULL,
+                    old_balance REAL NOT NULL,
+                    old_notes TEXT,
+                    new_date TEXT,
+                    new_balance REAL,
+                    new_notes TEXT,
+                    changed_at TEXT NOT NULL
+                );
+                CREATE INDEX IF NOT EXISTS idx_balance_entry_history_entry
+                    ON balance_entry_history(entry_id, changed_at);
+                CREATE INDEX IF NOT EXISTS idx_balance_entry_history_changed_at
+                    ON balance_entry_history(changed_at);
+
+                -- Triggers rather than command code, so edits made through the SQL plugin are
+                -- recorded too. The minor-units backfill only rounds, and isn't an edit.
+                CREATE TRIGGER IF NOT EXISTS balance_entries_history_update
+                AFTER UPDATE OF date, balance, notes ON balance_entries
+                WHEN OLD.balance_minor IS NOT NULL
+                    AND (OLD.date IS NOT NEW.date OR OLD.balance IS NOT NEW.balance OR OLD.notes IS NOT NEW.notes)
+                BEGIN
+                    INSERT INTO balance_entry_history (id, entry_id, account_id, operation, old_date, old_balance, old_notes, new_date, new_balance, new_notes, changed_at)
+                    VALUES (lower(hex(randomblob(16))), OLD.id, NEW.account_id, 'update', OLD.date, OLD.balance, OLD.notes, NEW.date, NEW.balance, NEW.notes, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+                END;
+
+                -- Entries removed by deleting their account take their history with them, so
+                -- none is written while the account is already gone.
+                CREATE TRIGGER IF NOT EXISTS balance_entries_history_delete
+                AFTER DELETE ON balance_entries
+                WHEN EXISTS (SELECT 1 FROM accounts WHERE id = OLD.account_id)
+                BEGIN
+                    INSERT INTO balance_entry_history (id, entry_id, account_id, operation, old_date, old_balance, old_notes, changed_at)
+                    VALUES (lower(hex(randomblob(16))), OLD.id, OLD.account_id, 'delete', OLD.date, OLD.balance, OLD.notes, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+                END;
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 26,
+            description: "create_attachments",
+            sql: r#"
+                CREATE TABLE attachments (
+                    id TEXT PRIMARY KEY,
+                    balance_entry_id TEXT NOT NULL REFERENCES balance_entries(id) ON DELETE CASCADE,
+                    filename TEXT NOT NULL,
+                    stored_name TEXT NOT NULL,
+                    mime_type TEXT NOT NULL,
+                    size_bytes INTEGER NOT NULL,
+                    added_at TEXT NOT NULL,
+                    UNIQUE (balance_entry_id, stored_name)
+                );
+                CREATE INDEX idx_attachments_stored_name ON attachments(stored_name);
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 27,
+            description: "add_entry_original_currency",
+            sql: r#"
+                CREATE TABLE exchange_rates (
+                    id TEXT PRIMARY KEY,
+                    date TEXT NOT NULL,
+                    from_currency TEXT NOT NULL,
+                    to_currency TEXT NOT NULL,
+                    rate REAL NOT NULL CHECK (rate > 0),
+                    created_at TEXT NOT NULL,
+                    UNIQUE (from_currency, to_currency, date)
+                );
+                ALTER TABLE balance_entries ADD COLUMN original_currency TEXT;
+                ALTER TABLE balance_entries ADD COLUMN original_amount REAL;
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 28,
+            description: "create_flows",
+            sql: r#"
+                CREATE TABLE flows (
+                    id TEXT PRIMARY KEY,
+                    account_id TEXT NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+                    date TEXT NOT NULL,
+                    amount REAL NOT NULL CHECK (amount > 0),
+                    kind TEXT NOT NULL CHECK (kind IN ('contribution', 'withdrawal')),
+                    notes TEXT,
+                    created_at TEXT NOT NULL
+                );
+                CREATE INDEX idx_flows_account_date ON flows(account_id, date);
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 29,
+            description: "create_recurring_adjustments",
+            sql: r#"
+                ALTER TABLE balance_entries ADD COLUMN source TEXT NOT NULL DEFAULT 'manual';
+                CREATE TABLE recurring_adjustments (
+                    id TEXT PRIMARY KEY,
+                    account_id TEXT NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+                    amount REAL NOT NULL,
+                    day_of_month INTEGER NOT NULL CHECK (day_of_month BETWEEN 1 AND 31),
+                    start_date TEXT NOT NULL,
+                    end_date TEXT,
+                    kind TEXT NOT NULL CHECK (kind IN ('delta', 'absolute')),
+                    last_applied TEXT,
+                    created_at TEXT NOT NULL
+                );
+                CREATE INDEX idx_recurring_adjustments_account ON recurring_adjustments(account_id);
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 30,
+            description: "create_undo_log",
+            sql: r#"
+                CREATE TABLE undo_log (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    operation TEXT NOT NULL,
+                    description TEXT NOT NULL,
+                    before_entries TEXT NOT NULL,
+                    after_entries TEXT NOT NULL,
+                    undone INTEGER NOT NULL DEFAULT 0,
+                    created_at TEXT NOT NULL
+                );
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 31,
+            description: "add_accounts_statement_day",
+            sql: r#"
+                ALTER TABLE accounts ADD COLUMN statement_day INTEGER CHECK (statement_day BETWEEN 1 AND 31);
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 32,
+            description: "add_balance_entries_verified_at",
+            sql: r#"
+                ALTER TABLE balance_entries ADD COLUMN verified_at TEXT;
+                ALTER TABLE balance_entry_history ADD COLUMN old_verified_at TEXT;
+                ALTER TABLE balance_entry_history ADD COLUMN new_verified_at TEXT;
+
+                -- Verification changes are recorded alongside edits.
+                DROP TRIGGER IF EXISTS balance_entries_history_update;
+                CREATE TRIGGER balance_entries_history_update
+                AFTER UPDATE OF date, balance, notes, verified_at ON balance_entries
+                WHEN OLD.balance_minor IS NOT NULL
+                    AND (OLD.date IS NOT NEW.date OR OLD.balance IS NOT NEW.balance OR OLD.notes IS NOT NEW.notes
+                        OR OLD.verified_at IS NOT NEW.verified_at)
+                BEGIN
+                    INSERT INTO balance_entry_history (id, entry_id, account_id, operation, old_date, old_balance, old_notes, new_date, new_balance, new_notes, old_verified_at, new_verified_at, changed_at)
+                    VALUES (lower(hex(randomblob(16))), OLD.id, NEW.account_id, 'update', OLD.date, OLD.balance, OLD.notes, NEW.date, NEW.balance, NEW.notes, OLD.verified_at, NEW.verified_at, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+                END;
+
+                -- A verified balance that changes is no longer the one checked against the
+                -- statement. An update that sets verified_at itself, as undo does, keeps it.
+                CREATE TRIGGER balance_entries_clear_verified
+                AFTER UPDATE OF balance ON balance_entries
+                WHEN NEW.verified_at IS NOT NULL
+                    AND OLD.verified_at IS NEW.verified_at
+                    AND OLD.balance IS NOT NEW.balance
+                BEGIN
+                    UPDATE balance_entries SET verified_at = NULL WHERE id = NEW.id;
+                END;
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 33,
+            description: "create_notes_fts",
+            sql: r#"
+                -- One row per non-empty entry note or milestone label. Kept in step by
+                -- triggers, like the entry history, so edits made through the SQL plugin are
+                -- indexed too.
+                CREATE VIRTUAL TABLE notes_fts USING fts5(
+                    text,
+                    kind UNINDEXED,
+                    item_id UNINDEXED,
+                    tokenize = 'unicode61 remove_diacritics 2'
+                );
+
+                INSERT INTO notes_fts (text, kind, item_id)
+                SELECT notes, 'entry', id FROM balance_entries WHERE notes IS NOT NULL AND notes != '';
+                INSERT INTO notes_fts (text, kind, item_id)
+                SELECT label, 'milestone', id FROM milestones WHERE label != '';
+
+                CREATE TRIGGER balance_entries_fts_insert
+                AFTER INSERT ON balance_entries
+                WHEN NEW.notes IS NOT NULL AND NEW.notes != ''
+                BEGIN
+                    INSERT INTO notes_fts (text, kind, item_id) VALUES (NEW.notes, 'entry', NEW.id);
+                END;
+
+                CREATE TRIGGER balance_entries_fts_update
+                AFTER UPDATE OF notes ON balance_entries
+                WHEN OLD.notes IS NOT NEW.notes
+                BEGIN
+                    DELETE FROM notes_fts WHERE kind = 'entry' AND item_id = OLD.id;
+                    INSERT INTO notes_fts (text, kind, item_id)
+                    SELECT NEW.notes, 'entry', NEW.id WHERE NEW.notes IS NOT NULL AND NEW.notes != '';
+                END;
+
+                CREATE TRIGGER balance_entries_fts_delete
+                AFTER DELETE ON balance_entries
+                BEGIN
+                    DELETE FROM notes_fts WHERE kind = 'entry' AND item_id = OLD.id;
+                END;
+
+                CREATE TRIGGER milestones_fts_insert
+                AFTER INSERT ON milestones
+                WHEN NEW.label != ''
+                BEGIN
+                    INSERT INTO notes_fts (text, kind, item_id) VALUES (NEW.label, 'milestone', NEW.id);
+                END;
+
+                CREATE TRIGGER milestones_fts_update
+                AFTER UPDATE OF label ON milestones
+                WHEN OLD.label IS NOT NEW.label
+                BEGIN
+                    DELETE FROM notes_fts WHERE kind = 'milestone' AND item_id = OLD.id;
+                    INSERT INTO notes_fts (text, kind, item_id)
+                    SELECT NEW.label, 'milestone', NEW.id WHERE NEW.label != '';
+                END;
+
+                CREATE TRIGGER milestones_fts_delete
+                AFTER DELETE ON milestones
+                BEGIN
+                    DELETE FROM notes_fts WHERE kind = 'milestone' AND item_id = OLD.id;
+                END;
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 34,
+            description: "add_milestone_targets",
+            sql: r#"
+                ALTER TABLE milestones ADD COLUMN target_amount REAL;
+                ALTER TABLE milestones ADD COLUMN target_scope TEXT CHECK (target_scope IN ('net_worth', 'account'));
+                ALTER TABLE milestones ADD COLUMN achieved_at TEXT;
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 35,
+            description: "add_recurring_milestones",
+            sql: r#"
+                ALTER TABLE milestones ADD COLUMN recurrence TEXT NOT NULL DEFAULT 'none' CHECK (recurrence IN ('none', 'monthly', 'yearly'));
+                ALTER TABLE milestones ADD COLUMN parent_id TEXT REFERENCES milestones(id) ON DELETE SET NULL;
+                -- The latest date instances were generated up to; earlier ones are never generated again.
+                ALTER TABLE milestones ADD COLUMN materialised_until TEXT;
+
+                CREATE INDEX IF NOT EXISTS idx_milestones_parent ON milestones(parent_id, date);
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 36,
+            description: "add_milestone_categories",
+            sql: r#"
+                ALTER TABLE milestones ADD COLUMN category TEXT NOT NULL DEFAULT 'general' CHECK (category IN ('general', 'purchase', 'payoff', 'life_event', 'custom'));
+                ALTER TABLE milestones ADD COLUMN icon TEXT;
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 37,
+            description: "create_milestone_accounts",
+            sql: r#"
+                CREATE TABLE IF NOT EXISTS milestone_accounts (
+                    milestone_id TEXT NOT NULL REFERENCES milestones(id) ON DELETE CASCADE,
+                    account_id TEXT NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+                    PRIMARY KEY (milestone_id, account_id)
+                );
+
+                CREATE INDEX IF NOT EXISTS idx_milestone_accounts_account ON milestone_accounts(account_id);
+
+                INSERT OR IGNORE INTO milestone_accounts (milestone_id, account_id)
+                SELECT id, account_id FROM milestones WHERE account_id IS NOT NULL;
+
+                -- Superseded by milestone_accounts; cleared so nothing reads a stale link.
+                UPDATE milestones SET account_id = NULL;
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 38,
+            description: "create_notifications_sent",
+            sql: r#"
+                -- One row per notice shown, so each is only shown once. `date` is what it
+                -- was about: the achievement date, or the milestone's date for an upcoming one.
+                CREATE TABLE IF NOT EXISTS notifications_sent (
+                    milestone_id TEXT NOT NULL REFERENCES milestones(id) ON DELETE CASCADE,
+                    kind TEXT NOT NULL CHECK (kind IN ('achieved', 'upcoming')),
+                    date TEXT NOT NULL,
+                    sent_at TEXT NOT NULL,
+                    PRIMARY KEY (milestone_id, kind, date)
+                );
+
+                -- Achievements from before notifications existed are not announced.
+                INSERT OR IGNORE INTO notifications_sent (milestone_id, kind, date, sent_at)
+                SELECT id, 'achieved', achieved_at, datetime('now')
+                FROM milestones WHERE achieved_at IS NOT NULL;
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 39,
+            description: "add_milestone_sort_order",
+            sql: r#"
+                ALTER TABLE milestones ADD COLUMN sort_order INTEGER NOT NULL DEFAULT 0;
+
+                -- Milestones sharing a date keep the order they were created in.
+                UPDATE milestones SET sort_order = (
+                    SELECT COUNT(*) FROM milestones earlier
+                    WHERE earlier.date = milestones.date
+                      AND (earlier.created_at < milestones.created_at
+                           OR (earlier.created_at = milestones.created_at AND earlier.id < milestones.id))
+                );
+
+                DROP INDEX IF EXISTS idx_milestones_date;
+                CREATE INDEX IF NOT EXISTS idx_milestones_date ON milestones(date, sort_order);
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 40,
+            description: "create_milestone_suggestion_resolutions",
+            sql: r#"
+                -- Suggestions that were accepted or dismissed, by key, so they aren't offered again.
+                CREATE TABLE IF NOT EXISTS milestone_suggestion_resolutions (
+                    key TEXT PRIMARY KEY,
+                    resolution TEXT NOT NULL CHECK (resolution IN ('accepted', 'dismissed')),
+                    resolved_at TEXT NOT NULL
+                );
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 41,
+            description: "create_income_records",
+            sql: r#"
+                CREATE TABLE IF NOT EXISTS income_records (
+                    id TEXT PRIMARY KEY,
+                    month TEXT NOT NULL UNIQUE,
+                    net_income REAL NOT NULL CHECK (net_income >= 0),
+                    notes TEXT,
+                    created_at TEXT NOT NULL
+                );
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 42,
+            description: "add_account_liquidity",
+            sql: r#"
+                ALTER TABLE accounts ADD COLUMN liquidity TEXT NOT NULL DEFAULT 'liquid'
+                    CHECK (liquidity IN ('liquid', 'semi', 'illiquid'));
+                UPDATE accounts SET liquidity = CASE account_type
+                    WHEN 'savings' THEN 'liquid'
+                    WHEN 'credit_card' THEN 'liquid'
+                    WHEN 'investment' THEN 'semi'
+                    WHEN 'loan' THEN 'semi'
+                    WHEN 'property' THEN 'illiquid'
+                    WHEN 'pension' THEN 'illiquid'
+                    WHEN 'mortgage' THEN 'illiquid'
+                    ELSE 'semi'
+                END;
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 43,
+            description: "create_benchmarks",
+            sql: r#"
+                CREATE TABLE IF NOT EXISTS benchmarks (
+                    id TEXT PRIMARY KEY,
+                    name TEXT NOT NULL,
+                    kind TEXT NOT NULL CHECK (kind IN ('fixed_rate', 'custom_points')),
+                    parameters TEXT NOT NULL,
+                    created_at TEXT NOT NULL
+                );
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 44,
+            description: "create_cpi_rates",
+            sql: r#"
+                CREATE TABLE IF NOT EXISTS cpi_rates (
+                    month TEXT PRIMARY KEY,
+                    index_value REAL NOT NULL CHECK (index_value > 0),
+                    created_at TEXT NOT NULL
+                );
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 45,
+            description: "create_scenarios",
+            sql: r#"
+                CREATE TABLE IF NOT EXISTS scenarios (
+                    id TEXT PRIMARY KEY,
+                    name TEXT NOT NULL,
+                    description TEXT,
+                    horizon_months INTEGER NOT NULL CHECK (horizon_months > 0),
+                    operations TEXT NOT NULL,
+                    created_at TEXT NOT NULL,
+                    updated_at TEXT NOT NULL
+                );
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 46,
+            description: "create_net_worth_snapshots",
+            sql: r#"
+                -- Net worth as `net_worth_points` works it out for a day, before inflation,
+                -- with balances between entries estimated linearly.
+                CREATE TABLE net_worth_snapshots (
+                    date TEXT PRIMARY KEY NOT NULL,
+                    assets REAL NOT NULL,
+                    liabilities REAL NOT NULL,
+                    net REAL NOT NULL,
+                    is_estimated INTEGER NOT NULL,
+                    computed_at TEXT NOT NULL
+                );
+
+                -- Every day up to valid_until has its snapshot, or had no net worth. The
+                -- triggers below pull it back to the day before the first date a change can
+                -- move; NULL means nothing can be trusted.
+                CREATE TABLE net_worth_snapshot_state (
+                    id INTEGER PRIMARY KEY CHECK (id = 1),
+                    valid_until TEXT
+                );
+                INSERT INTO net_worth_snapshot_state (id) VALUES (1);
+
+                -- Interpolation reaches back to the account's previous entry.
+                CREATE TRIGGER IF NOT EXISTS balance_entries_snapshots_insert
+                AFTER INSERT ON balance_entries
+                BEGIN
+                    UPDATE net_worth_snapshot_state SET valid_until = min(valid_until, date(COALESCE(
+                        (SELECT max(date) FROM balance_entries WHERE account_id = NEW.account_id AND date < NEW.date),
+                        NEW.date
+                    ), '-1 day'));
+                END;
+
+                CREATE TRIGGER IF NOT EXISTS balance_entries_snapshots_update
+                AFTER UPDATE OF account_id, date, balance, balance_minor, source ON balance_entries
+                BEGIN
+                    UPDATE net_worth_snapshot_state SET valid_until = min(valid_until, date(min(
+                        COALESCE(
+                            (SELECT max(date) FROM balance_entries WHERE account_id = OLD.account_id AND date < OLD.date),
+                            OLD.date
+                        ),
+                        COALESCE(
+                            (SELECT max(date) FROM balance_entries WHERE account_id = NEW.account_id AND date < NEW.date),
+                            NEW.date
+                        )
+                    ), '-1 day'));
+                END;
+
+                CREATE TRIGGER IF NOT EXISTS balance_entries_snapshots_delete
+                AFTER DELETE ON balance_entries
+                BEGIN
+                    UPDATE net_worth_snapshot_state SET valid_until = min(valid_until, date(COALESCE(
+                        (SELECT max(date) FROM balance_entries WHERE account_id = OLD.account_id AND date < OLD.date),
+                        OLD.date
+                    ), '-1 day'));
+                END;
+
+                -- An account without entries has no value to move.
+                CREATE TRIGGER IF NOT EXISTS accounts_snapshots_update
+                AFTER UPDATE OF account_type, category, currency, ownership_percent, opened_at, closed_at, archived_at, exclude_from_net_worth ON accounts
+                BEGIN
+                    UPDATE net_worth_snapshot_state SET valid_until = min(valid_until, date(COALESCE(
+                        (SELECT min(date) FROM balance_entries WHERE account_id = NEW.id),
+                        '9999-12-31'
+                    ), '-1 day'));
+                END;
+
+                CREATE TRIGGER IF NOT EXISTS accounts_snapshots_delete
+                BEFORE DELETE ON accounts
+                BEGIN
+                    UPDATE net_worth_snapshot_state SET valid_until = min(valid_until, date(COALESCE(
+                        (SELECT min(date) FROM balance_entries WHERE account_id = OLD.id),
+                        '9999-12-31'
+                    ), '-1 day'));
+                END;
+
+                -- Conversions use the nearest rate on either side, so a rate moves days back
+                -- to the pair's previous one, and every day when there is none.
+                CREATE TRIGGER IF NOT EXISTS exchange_rates_snapshots_insert
+                AFTER INSERT ON exchange_rates
+                BEGIN
+                    UPDATE net_worth_snapshot_state SET valid_until = min(valid_until, date(
+                        (SELECT max(date) FROM exchange_rates
+                         WHERE ((from_currency = NEW.from_currency AND to_currency = NEW.to_currency)
+                             OR (from_currency = NEW.to_currency AND to_currency = NEW.from_currency))
+                           AND date < NEW.date),
+                        '-1 day'
+                    ));
+                END;
+
+                CREATE TRIGGER IF NOT EXISTS exchange_rates_snapshots_update
+                AFTER UPDATE ON exchange_rates
+                BEGIN
+                    UPDATE net_worth_snapshot_state SET valid_until = min(valid_until, date(min(
+                        (SELECT max(date) FROM exchange_rates
+                         WHERE ((from_currency = OLD.from_currency AND to_currency = OLD.to_currency)
+                             OR (from_currency = OLD.to_currency AND to_currency = OLD.from_currency))
+                           AND date < OLD.date),
+                        (SELECT max(date) FROM exchange_rates
+                         WHERE ((from_currency = NEW.from_currency AND to_currency = NEW.to_currency)
+                             OR (from_currency = NEW.to_currency AND to_currency = NEW.from_currency))
+                           AND date < NEW.date)
+                    ), '-1 day'));
+                END;
+
+                CREATE TRIGGER IF NOT EXISTS exchange_rates_snapshots_delete
+                AFTER DELETE ON exchange_rates
+                BEGIN
+                    UPDATE net_worth_snapshot_state SET valid_until = min(valid_until, date(
+                        (SELECT max(date) FROM exchange_rates
+                         WHERE ((from_currency = OLD.from_currency AND to_currency = OLD.to_currency)
+                             OR (from_currency = OLD.to_currency AND to_currency = OLD.from_currency))
+                           AND date < OLD.date),
+                        '-1 day'
+                    ));
+                END;
+
+                CREATE TRIGGER IF NOT EXISTS settings_snapshots_base_currency
+                AFTER UPDATE ON settings
+                WHEN (NEW.key = 'base_currency' OR OLD.key = 'base_currency')
+                    AND (OLD.key IS NOT NEW.key OR OLD.value IS NOT NEW.value)
+                BEGIN
+                    UPDATE net_worth_snapshot_state SET valid_until = NULL;
+                END;
+
+                CREATE TRIGGER IF NOT EXISTS settings_snapshots_base_currency_insert
+                AFTER INSERT ON settings
+                WHEN NEW.key = 'base_currency'
+                BEGIN
+                    UPDATE net_worth_snapshot_state SET valid_until = NULL;
+                END;
+
+                CREATE TRIGGER IF NOT EXISTS settings_snapshots_base_currency_delete
+                AFTER DELETE ON settings
+                WHEN OLD.key = 'base_currency'
+                BEGIN
+                    UPDATE net_worth_snapshot_state SET valid_until = NULL;
+                END;
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 47,
+            description: "create_allocation_targets",
+            sql: r#"
+                -- Each target covers either an account type, by built-in name or custom slug,
+                -- or a tag.
+                CREATE TABLE allocation_targets (
+                    id TEXT PRIMARY KEY NOT NULL,
+                    account_type TEXT UNIQUE,
+                    tag_id TEXT UNIQUE REFERENCES tags(id) ON DELETE CASCADE,
+                    target_percent REAL NOT NULL CHECK (target_percent > 0 AND target_percent <= 100),
+                    created_at TEXT NOT NULL,
+                    updated_at TEXT NOT NULL,
+                    CHECK ((account_type IS NULL) != (tag_id IS NULL))
+                );
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 48,
+            description: "add_accounts_is_emergency_fund",
+            sql: r#"
+                ALTER TABLE accounts ADD COLUMN is_emergency_fund INTEGER NOT NULL DEFAULT 0;
+            "#,
+            kind: MigrationKind::Up,
+        },
     ]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{block_on, temp_path};
+
+    #[test]
+    fn connecting_reports_accounts_with_unknown_currencies() {
+        let path = temp_path("db");
+        let invalid = block_on(async {
+            let (pool, invalid) = connect(&path).await.unwrap();
+            assert!(invalid.is_empty());
+            sqlx::query(
+                "INSERT INTO accounts (id, name, account_type, category, currency)
+                 VALUES ('old', 'Old savings', 'savings', 'asset', 'GPB')",
+            )
+            .execute(&pool)
+            .await
+            .unwrap();
+            pool.close().await;
+
+            let (pool, invalid) = connect(&path).await.unwrap();
+            pool.close().await;
+            invalid
+        });
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(invalid.len(), 1);
+        assert_eq!(invalid[0].account_id, "old");
+        assert_eq!(invalid[0].field, "currency");
+        assert_eq!(invalid[0].value, "GPB");
+        assert!(invalid[0].message.contains("GBP"), "{}", invalid[0].message);
+    }
+}
@@ -0,0 +1,179 @@
+use serde::ser::{SerializeMap, Serializer};
+use serde::Serialize;
+
+use crate::models::{BatchEntryError, OutlierWarning, ParseAccountTypeError};
+use crate::ofx;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("database error: {0}")]
+    Database(sqlx::Error),
+
+    #[error("{entity} not found: {id}")]
+    NotFound { entity: &'static str, id: String },
+
+    #[error("invalid {field}: {message}")]
+    Validation {
+        field: &'static str,
+        message: String,
+    },
+
+    #[error("{0}")]
+    Conflict(String),
+
+    #[error("invalid colour '{0}': expected #RRGGBB")]
+    InvalidColor(String),
+
+    #[error("{0}")]
+    NoData(String),
+
+    #[error(transparent)]
+    InvalidAccountType(#[from] ParseAccountTypeError),
+
+    #[error("unknown currency '{code}'")]
+    UnknownCurrency {
+        code: String,
+        /// Known codes close to `code`, closest first.
+        suggestions: Vec<String>,
+    },
+
+    #[error("{} of the entries are invalid; nothing was saved", errors.len())]
+    InvalidBatch { errors: Vec<BatchEntryError> },
+
+    #[error("no exchange rate from {from_currency} to {to_currency} is recorded near {date}")]
+    MissingExchangeRate {
+        from_currency: String,
+        to_currency: String,
+        date: String,
+    },
+
+    #[error("the balance {} is {} away from the previous entry; resend with confirm_outlier to save it", warning.balance, warning.change.abs())]
+    UnconfirmedOutlier { warning: OutlierWarning },
+
+    #[error(transparent)]
+    InvalidOfx(#[from] ofx::ParseError),
+
+    #[error("file error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error(transparent)]
+    Tauri(#[from] tauri::Error),
+
+    #[error("could not open file: {0}")]
+    Opener(#[from] tauri_plugin_opener::Error),
+
+    #[error("could not show notification: {0}")]
+    Notification(#[from] tauri_plugin_notification::Error),
+}
+
+/// A row whose type or category column fails to decode surfaces as
+/// `InvalidAccountType` rather than as an opaque database error.
+impl From<sqlx::Error> for Error {
+    fn from(error: sqlx::Error) -> Self {
+        match error {
+            sqlx::Error::ColumnDecode { source, .. } if source.is::<ParseAccountTypeError>() => {
+                let parse = source
+                    .downcast::<ParseAccountTypeError>()
+                    .expect("checked by is()");
+                Error::InvalidAccountType(*parse)
+            }
+            error => Error::Database(error),
+        }
+    }
+}
+
+impl Error {
+    pub fn not_found(entity: &'static str, id: impl Into<String>) -> Self {
+        Error::NotFound {
+            entity,
+            id: id.into(),
+        }
+    }
+
+    pub fn validation(field: &'static str, message: impl Into<String>) -> Self {
+        Error::Validation {
+            field,
+            message: message.into(),
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Error::Database(_) => "database",
+            Error::NotFound { .. } => "not_found",
+            Error::Validation { .. } => "validation",
+            Error::Conflict(_) => "conflict",
+            Error::InvalidColor(_) => "invalid_color",
+            Error::NoData(_) => "no_data",
+            Error::InvalidAccountType(_) => "invalid_account_type",
+            Error::UnknownCurrency { .. } => "unknown_currency",
+            Error::InvalidBatch { .. } => "invalid_batch",
+            Error::MissingExchangeRate { .. } => "missing_exchange_rate",
+            Error::UnconfirmedOutlier { .. } => "unconfirmed_outlier",
+            Error::InvalidOfx(_) => "invalid_ofx",
+            Error::Io(_) => "io",
+            Error::Tauri(_) => "app",
+            Error::Opener(_) => "opener",
+            Error::Notification(_) => "notification",
+        }
+    }
+}
+
+/// Errors reach the frontend as `{ kind, message, ...details }` so callers can
+/// branch on `kind` instead of parsing the message.
+impl Serialize for Error {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("kind", self.kind())?;
+        map.serialize_entry("message", &self.to_string())?;
+        match self {
+            Error::Database(_)
+            | Error::Conflict(_)
+            | Error::NoData(_)
+            | Error::Io(_)
+            | Error::Tauri(_)
+            | Error::Opener(_)
+            | Error::Notification(_) => {}
+            Error::NotFound { entity, id } => {
+                map.serialize_entry("entity", entity)?;
+                map.serialize_entry("id", id)?;
+            }
+            Error::Validation { field, .. } => {
+                map.serialize_entry("field", field)?;
+            }
+            Error::InvalidColor(value) => {
+                map.serialize_entry("value", value)?;
+            }
+            Error::UnknownCurrency { code, suggestions } => {
+                map.serialize_entry("field", "currency")?;
+                map.serialize_entry("value", code)?;
+                map.serialize_entry("suggestions", suggestions)?;
+            }
+            Error::InvalidAccountType(error) => {
+                map.serialize_entry("field", error.field)?;
+                map.serialize_entry("value", &error.value)?;
+            }
+            Error::InvalidBatch { errors } => {
+                map.serialize_entry("errors", errors)?;
+            }
+            Error::UnconfirmedOutlier { warning } => {
+                map.serialize_entry("warning", warning)?;
+            }
+            Error::InvalidOfx(error) => {
+                map.serialize_entry("offset", &error.offset)?;
+            }
+            Error::MissingExchangeRate {
+                from_currency,
+                to_currency,
+                date,
+            } => {
+                map.serialize_entry("from_currency", from_currency)?;
+                map.serialize_entry("to_currency", to_currency)?;
+                map.serialize_entry("date", date)?;
+            }
+        }
+        map.end()
+    }
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
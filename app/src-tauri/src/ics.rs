@@ -0,0 +1,129 @@
+//! Just enough of iCalendar (RFC 5545) to write events: text values escaped,
+//! and content lines folded at 75 octets and ended with CRLF.
+
+/// The longest a content line may be before folding, in octets.
+const MAX_LINE_OCTETS: usize = 75;
+
+/// `text` as a TEXT value, with backslashes, separators and newlines escaped.
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Appends `line` to `out`, folded onto continuation lines that start with a
+/// space so none is longer than 75 octets, without splitting a character.
+pub fn write_line(out: &mut String, line: &str) {
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > MAX_LINE_OCTETS {
+            out.push_str("\r\n ");
+            length = 1;
+        }
+        out.push(c);
+        length += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Checks `calendar` against the RFC 5545 rules this module is for: CRLF
+    /// after every line, none over 75 octets, and each event with one UID and
+    /// one DTSTAMP. Returns the unfolded content lines.
+    pub(crate) fn assert_valid(calendar: &str) -> Vec<String> {
+        assert!(calendar.ends_with("\r\n"), "the last line ends with CRLF");
+        let lines: Vec<&str> = calendar[..calendar.len() - 2].split("\r\n").collect();
+        let mut unfolded: Vec<String> = Vec::new();
+        for line in lines {
+            assert!(
+                !line.contains('\n') && !line.contains('\r'),
+                "bare line break in {line:?}"
+            );
+            assert!(
+                line.len() <= MAX_LINE_OCTETS,
+                "{} octets in {line:?}",
+                line.len()
+            );
+            match line.strip_prefix(' ') {
+                Some(continued) => unfolded
+                    .last_mut()
+                    .expect("a line to continue")
+                    .push_str(continued),
+                None => unfolded.push(line.to_string()),
+            }
+        }
+        assert_eq!(
+            unfolded.first().map(String::as_str),
+            Some("BEGIN:VCALENDAR")
+        );
+        assert_eq!(unfolded.last().map(String::as_str), Some("END:VCALENDAR"));
+        for event in unfolded.split(|line| line == "BEGIN:VEVENT").skip(1) {
+            let event: Vec<&String> = event
+                .iter()
+                .take_while(|line| *line != "END:VEVENT")
+                .collect();
+            for property in ["UID:", "DTSTAMP:"] {
+                let count = event
+                    .iter()
+                    .filter(|line| line.starts_with(property))
+                    .count();
+                assert_eq!(count, 1, "{property} in {event:?}");
+            }
+        }
+        unfolded
+    }
+
+    #[test]
+    fn escape_escapes_text_separators_and_newlines() {
+        assert_eq!(
+            escape("Pay off car; then, save\\invest\r\nagain"),
+            r"Pay off car\; then\, save\\invest\nagain"
+        );
+    }
+
+    #[test]
+    fn write_line_ends_lines_with_crlf() {
+        let mut out = String::new();
+        write_line(&mut out, "BEGIN:VCALENDAR");
+        write_line(&mut out, "END:VCALENDAR");
+        assert_eq!(out, "BEGIN:VCALENDAR\r\nEND:VCALENDAR\r\n");
+    }
+
+    #[test]
+    fn write_line_folds_at_75_octets() {
+        let mut out = String::new();
+        let line = format!("SUMMARY:{}", "x".repeat(200));
+        write_line(&mut out, &line);
+        let folded: Vec<&str> = out.trim_end_matches("\r\n").split("\r\n").collect();
+        assert_eq!(folded[0].len(), 75);
+        assert!(folded[1..]
+            .iter()
+            .all(|part| part.starts_with(' ') && part.len() <= 75));
+        let unfolded: String = folded.iter().map(|part| part.trim_start()).collect();
+        assert_eq!(unfolded, line);
+    }
+
+    #[test]
+    fn write_line_never_splits_a_character() {
+        let mut out = String::new();
+        let line = format!("SUMMARY:{}", "£".repeat(100));
+        write_line(&mut out, &line);
+        for part in out.trim_end_matches("\r\n").split("\r\n") {
+            assert!(part.len() <= 75);
+        }
+        let unfolded = assert_valid(&format!("BEGIN:VCALENDAR\r\n{out}END:VCALENDAR\r\n"));
+        assert_eq!(unfolded[1], line);
+    }
+}
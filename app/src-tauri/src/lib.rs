@@ -1,10 +1,31 @@
+mod commands;
+mod csv;
+mod dates;
 mod db;
+mod error;
+mod ics;
 mod models;
+mod ofx;
+mod qif;
+mod search;
+mod series;
+mod simulation;
+#[cfg(test)]
+mod testing;
 
+use std::path::PathBuf;
+
+use tauri::Manager;
+
+pub use error::{Error, Result};
 pub use models::*;
 
 #[cfg(debug_assertions)]
-const DB_PATH: &str = concat!("sqlite:", env!("CARGO_MANIFEST_DIR"), "/../dev-data/tally.db");
+const DB_PATH: &str = concat!(
+    "sqlite:",
+    env!("CARGO_MANIFEST_DIR"),
+    "/../dev-data/tally.db"
+);
 #[cfg(not(debug_assertions))]
 const DB_PATH: &str = "sqlite:tally.db";
 
@@ -13,16 +34,212 @@ fn get_db_path() -> &'static str {
     DB_PATH
 }
 
+/// Resolves `DB_PATH` the same way tauri_plugin_sql does: relative to the app
+/// config directory unless it is already absolute.
+fn db_file(app: &tauri::App) -> tauri::Result<PathBuf> {
+    let mut path = app.path().app_config_dir()?;
+    path.push(DB_PATH.trim_start_matches("sqlite:"));
+    Ok(path)
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
+        .plugin(tauri_plugin_notification::init())
         .plugin(tauri_plugin_opener::init())
         .plugin(
             tauri_plugin_sql::Builder::default()
                 .add_migrations(DB_PATH, db::get_migrations())
                 .build(),
         )
-        .invoke_handler(tauri::generate_handler![get_db_path])
+        .setup(|app| {
+            let (pool, invalid) = tauri::async_runtime::block_on(db::connect(&db_file(app)?))?;
+            // Left as they are for `validate_accounts` to list, but reported so they are seen.
+            for issue in invalid {
+                eprintln!(
+                    "account '{}' ({}): {}",
+                    issue.name, issue.account_id, issue.message
+                );
+            }
+            app.manage(pool);
+            Ok(())
+        })
+        .invoke_handler(tauri::generate_handler![
+            get_db_path,
+            commands::accounts::create_account,
+            commands::accounts::duplicate_account,
+            commands::accounts::get_accounts,
+            commands::accounts::get_accounts_with_balance,
+            commands::accounts::search_accounts,
+            commands::accounts::get_account,
+            commands::accounts::update_account,
+            commands::accounts::change_account_type,
+            commands::accounts::validate_accounts,
+            commands::accounts::reset_account_appearance,
+            commands::accounts::set_ownership,
+            commands::accounts::find_account_by_external_ref,
+            commands::accounts::set_account_external_ref,
+            commands::accounts::reorder_accounts,
+            commands::accounts::archive_account,
+            commands::accounts::unarchive_account,
+            commands::accounts::close_account,
+            commands::accounts::reopen_account,
+            commands::accounts::delete_account,
+            commands::accounts::merge_accounts,
+            commands::account_types::get_custom_account_types,
+            commands::account_types::create_custom_account_type,
+            commands::account_types::update_custom_account_type,
+            commands::account_types::delete_custom_account_type,
+            commands::adjustments::create_recurring_adjustment,
+            commands::adjustments::get_recurring_adjustments,
+            commands::adjustments::update_recurring_adjustment,
+            commands::adjustments::delete_recurring_adjustment,
+            commands::adjustments::apply_due_adjustments,
+            commands::adjustments::apply_bulk_adjustment,
+            commands::attachments::add_attachment,
+            commands::attachments::get_attachments,
+            commands::attachments::open_attachment,
+            commands::attachments::remove_attachment,
+            commands::attachments::find_orphaned_attachments,
+            commands::flows::create_flow,
+            commands::flows::get_flows,
+            commands::flows::update_flow,
+            commands::flows::delete_flow,
+            commands::flows::get_account_growth,
+            commands::flows::get_net_worth_growth,
+            commands::flows::get_time_weighted_return,
+            commands::exchange_rates::set_exchange_rate,
+            commands::exchange_rates::get_exchange_rates,
+            commands::exchange_rates::delete_exchange_rate,
+            commands::backup::export_backup,
+            commands::backup::import_backup,
+            commands::balances::create_balance_entry,
+            commands::balances::create_balance_entries,
+            commands::balances::upsert_balance_entry,
+            commands::balances::repair_entry_dates,
+            commands::balances::normalise_balance_signs,
+            commands::balances::find_duplicate_entries,
+            commands::balances::merge_duplicate_entries,
+            commands::balances::repair_datetime_dates,
+            commands::balances::list_balance_entries,
+            commands::balances::delete_balance_entry,
+            commands::balances::delete_balance_entries,
+            commands::balances::update_balance_entry,
+            commands::balances::mark_entry_verified,
+            commands::balances::unmark_entry_verified,
+            commands::balances::get_reconciliation_status,
+            commands::balances::delete_estimated_entries,
+            commands::balances::get_latest_balances,
+            commands::balances::get_balance_entries_with_delta,
+            commands::credit::get_credit_utilisation,
+            commands::currencies::get_supported_currencies,
+            commands::currencies::get_currency_format,
+            commands::groups::create_group,
+            commands::groups::rename_group,
+            commands::groups::delete_group,
+            commands::groups::assign_account_to_group,
+            commands::groups::reorder_groups,
+            commands::groups::get_accounts_grouped,
+            commands::history::get_account_history,
+            commands::history::get_balance_entry_history,
+            commands::history::get_recent_changes,
+            commands::imports::import_accounts_csv,
+            commands::imports::import_balances_csv,
+            commands::exports::export_balances_csv,
+            commands::exports::export_milestones_ics,
+            commands::imports::import_balances_from_text,
+            commands::imports::import_ofx,
+            commands::imports::import_qif,
+            commands::links::link_accounts,
+            commands::links::unlink_account,
+            commands::links::get_ltv,
+            commands::milestones::create_milestone,
+            commands::milestones::get_milestones,
+            commands::milestones::get_milestones_by_category,
+            commands::milestones::get_milestones_with_net_worth,
+            commands::milestones::list_milestones,
+            commands::milestones::count_milestones,
+            commands::milestones::update_milestone,
+            commands::milestones::reorder_milestones,
+            commands::milestones::delete_milestone,
+            commands::milestones::check_milestone_achievements,
+            commands::notifications::check_and_notify,
+            commands::suggestions::suggest_milestones,
+            commands::suggestions::accept_milestone_suggestion,
+            commands::suggestions::dismiss_milestone_suggestion,
+            commands::milestones::materialise_recurring_milestones,
+            commands::milestones::project_milestone,
+            commands::pensions::get_pension_details,
+            commands::pensions::set_pension_details,
+            commands::pensions::project_pension,
+            commands::projections::project_interest,
+            commands::projections::get_amortisation_schedule,
+            commands::projections::project_payoff,
+            commands::properties::get_property_details,
+            commands::properties::set_property_details,
+            commands::properties::get_property_performance,
+            commands::reminders::get_stale_accounts,
+            commands::reminders::get_due_updates,
+            commands::analytics::get_net_worth_series,
+            commands::analytics::get_net_worth_breakdown,
+            commands::analytics::get_debt_ratio_series,
+            commands::analytics::get_allocation,
+            commands::analytics::get_liquidity_breakdown,
+            commands::analytics::get_liquidity_series,
+            commands::analytics::get_period_stats,
+            commands::analytics::get_drawdowns,
+            commands::scenarios::create_scenario,
+            commands::scenarios::get_scenarios,
+            commands::scenarios::get_scenario,
+            commands::scenarios::update_scenario,
+            commands::scenarios::delete_scenario,
+            commands::scenarios::run_scenario,
+            commands::reviews::get_year_review,
+            commands::net_worth_cache::refresh_snapshots,
+            commands::allocation_targets::get_allocation_targets,
+            commands::allocation_targets::create_allocation_target,
+            commands::allocation_targets::update_allocation_target,
+            commands::allocation_targets::delete_allocation_target,
+            commands::allocation_targets::get_allocation_drift,
+            commands::emergency_fund::get_emergency_coverage,
+            commands::benchmarks::create_benchmark,
+            commands::benchmarks::get_benchmarks,
+            commands::benchmarks::update_benchmark,
+            commands::benchmarks::delete_benchmark,
+            commands::benchmarks::get_benchmark_series,
+            commands::analytics::get_growth_summary,
+            commands::analytics::get_cagr,
+            commands::analytics::get_savings_rate,
+            commands::analytics::get_fire_projection,
+            commands::analytics::run_monte_carlo,
+            commands::analytics::forecast_net_worth,
+            commands::income::create_income_record,
+            commands::income::get_income_records,
+            commands::income::update_income_record,
+            commands::income::delete_income_record,
+            commands::inflation::set_cpi_rate,
+            commands::inflation::get_cpi_rates,
+            commands::inflation::delete_cpi_rate,
+            commands::inflation::import_cpi_csv,
+            commands::series::get_account_series,
+            commands::series::get_monthly_balances,
+            commands::series::fill_missing_months,
+            commands::series::get_balance_at,
+            commands::series::get_balances_at,
+            commands::snapshots::compare_snapshots,
+            commands::search::search_notes,
+            commands::settings::get_settings,
+            commands::settings::update_settings,
+            commands::tags::get_tags,
+            commands::tags::create_tag,
+            commands::tags::delete_tag,
+            commands::tags::set_account_tags,
+            commands::tags::get_accounts_by_tag,
+            commands::undo::undo_last,
+            commands::undo::redo_last,
+            commands::undo::get_undo_log,
+            commands::undo::clear_undo_log,
+        ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
@@ -1,6 +1,18 @@
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{Days, Months, NaiveDate};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+use super::currency::validate_currency;
+use super::{Patch, Tag};
+use crate::error::{Error, Result};
+
+pub const DEFAULT_CURRENCY: &str = "GBP";
+
+/// Built-in types serialize as plain strings (`"savings"`); user-defined ones as
+/// `{"custom": "<slug>"}`, keyed by `custom_account_types.slug`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "snake_case")]
 pub enum AccountType {
     Property,
@@ -10,10 +22,22 @@ pub enum AccountType {
     Mortgage,
     Loan,
     CreditCard,
+    Custom(String),
 }
 
 impl AccountType {
-    pub fn as_str(&self) -> &'static str {
+    pub const BUILTIN: [AccountType; 7] = [
+        AccountType::Property,
+        AccountType::Pension,
+        AccountType::Investment,
+        AccountType::Savings,
+        AccountType::Mortgage,
+        AccountType::Loan,
+        AccountType::CreditCard,
+    ];
+
+    /// The value stored in `accounts.account_type`: the built-in name or the custom slug.
+    pub fn as_str(&self) -> &str {
         match self {
             AccountType::Property => "property",
             AccountType::Pension => "pension",
@@ -22,24 +46,32 @@ impl AccountType {
             AccountType::Mortgage => "mortgage",
             AccountType::Loan => "loan",
             AccountType::CreditCard => "credit_card",
+            AccountType::Custom(slug) => slug,
         }
     }
 
-    pub fn from_str(s: &str) -> Option<Self> {
-        match s {
-            "property" => Some(AccountType::Property),
-            "pension" => Some(AccountType::Pension),
-            "investment" => Some(AccountType::Investment),
-            "savings" => Some(AccountType::Savings),
-            "mortgage" => Some(AccountType::Mortgage),
-            "loan" => Some(AccountType::Loan),
-            "credit_card" => Some(AccountType::CreditCard),
-            _ => None,
-        }
+    pub fn is_custom(&self) -> bool {
+        matches!(self, AccountType::Custom(_))
+    }
+
+    /// Custom slugs are lower-case ASCII letters, digits and underscores, and may
+    /// not shadow a built-in type.
+    pub fn is_valid_custom_slug(slug: &str) -> bool {
+        !slug.is_empty()
+            && slug
+                .bytes()
+                .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'_')
+            && AccountType::builtin(slug).is_none()
+    }
+
+    fn builtin(s: &str) -> Option<Self> {
+        AccountType::BUILTIN
+            .into_iter()
+            .find(|account_type| account_type.as_str() == s)
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "snake_case")]
 pub enum AccountCategory {
     Asset,
@@ -47,41 +79,200 @@ pub enum AccountCategory {
 }
 
 impl AccountCategory {
+    pub const ALL: [AccountCategory; 2] = [AccountCategory::Asset, AccountCategory::Liability];
+
+    /// The value stored in `category` columns.
     pub fn as_str(&self) -> &'static str {
         match self {
             AccountCategory::Asset => "asset",
             AccountCategory::Liability => "liability",
         }
     }
+}
+
+/// A stored or submitted type or category string that names nothing we know.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("unknown {field} '{value}'")]
+pub struct ParseAccountTypeError {
+    /// `"account_type"` or `"category"`.
+    pub field: &'static str,
+    pub value: String,
+}
+
+/// Anything that isn't a built-in name is a custom slug, if it is a valid one.
+impl FromStr for AccountType {
+    type Err = ParseAccountTypeError;
 
-    pub fn from_str(s: &str) -> Option<Self> {
-        match s {
-            "asset" => Some(AccountCategory::Asset),
-            "liability" => Some(AccountCategory::Liability),
-            _ => None,
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(account_type) = AccountType::builtin(s) {
+            return Ok(account_type);
         }
+        if AccountType::is_valid_custom_slug(s) {
+            return Ok(AccountType::Custom(s.to_string()));
+        }
+        Err(ParseAccountTypeError {
+            field: "account_type",
+            value: s.to_string(),
+        })
+    }
+}
+
+impl FromStr for AccountCategory {
+    type Err = ParseAccountTypeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        AccountCategory::ALL
+            .into_iter()
+            .find(|category| category.as_str() == s)
+            .ok_or_else(|| ParseAccountTypeError {
+                field: "category",
+                value: s.to_string(),
+            })
+    }
+}
+
+impl fmt::Display for AccountType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Display for AccountCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl TryFrom<&str> for AccountType {
+    type Error = ParseAccountTypeError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl TryFrom<&str> for AccountCategory {
+    type Error = ParseAccountTypeError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+/// Used by `#[sqlx(try_from = "String")]` to decode the stored columns.
+impl TryFrom<String> for AccountType {
+    type Error = ParseAccountTypeError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl TryFrom<String> for AccountCategory {
+    type Error = ParseAccountTypeError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
     }
 }
 
 impl AccountType {
-    pub fn category(&self) -> AccountCategory {
+    /// The fixed category of a built-in type. Custom types carry their category in
+    /// `custom_account_types` and return `None` here; see `resolve_category`.
+    pub fn category(&self) -> Option<AccountCategory> {
         match self {
             AccountType::Property
             | AccountType::Pension
             | AccountType::Investment
-            | AccountType::Savings => AccountCategory::Asset,
+            | AccountType::Savings => Some(AccountCategory::Asset),
             AccountType::Mortgage | AccountType::Loan | AccountType::CreditCard => {
-                AccountCategory::Liability
+                Some(AccountCategory::Liability)
             }
+            AccountType::Custom(_) => None,
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// How quickly an account's money can be got at, or for a liability, how soon
+/// it falls due.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(rename_all = "snake_case")]
+pub enum Liquidity {
+    /// Spendable now, such as cash savings; or due now, such as a card balance.
+    #[default]
+    Liquid,
+    /// Can be sold or drawn within weeks, such as investments.
+    Semi,
+    /// Locked away, such as property and pensions.
+    Illiquid,
+}
+
+impl AccountType {
+    /// The liquidity an account of this type starts with. Custom types are
+    /// taken to be in between.
+    pub fn default_liquidity(&self) -> Liquidity {
+        match self {
+            AccountType::Savings | AccountType::CreditCard => Liquidity::Liquid,
+            AccountType::Investment | AccountType::Loan | AccountType::Custom(_) => Liquidity::Semi,
+            AccountType::Property | AccountType::Pension | AccountType::Mortgage => {
+                Liquidity::Illiquid
+            }
+        }
+    }
+}
+
+/// How often an account's balance should be brought up to date.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(rename_all = "snake_case")]
+pub enum ReminderFrequency {
+    Weekly,
+    Monthly,
+    Quarterly,
+    Yearly,
+    Never,
+}
+
+impl ReminderFrequency {
+    /// Whole periods between `last` and `as_of`; `None` for `Never`.
+    pub fn periods_between(&self, last: NaiveDate, as_of: NaiveDate) -> Option<u32> {
+        let months = match self {
+            ReminderFrequency::Weekly => {
+                let days = (as_of - last).num_days().max(0);
+                return Some((days / 7) as u32);
+            }
+            ReminderFrequency::Monthly => 1,
+            ReminderFrequency::Quarterly => 3,
+            ReminderFrequency::Yearly => 12,
+            ReminderFrequency::Never => return None,
+        };
+        let mut periods = 0;
+        while last + Months::new(months * (periods + 1)) <= as_of {
+            periods += 1;
+        }
+        Some(periods)
+    }
+
+    /// When an entry dated `last` next needs updating; `None` for `Never`.
+    pub fn next_due(&self, last: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            ReminderFrequency::Weekly => Some(last + Days::new(7)),
+            ReminderFrequency::Monthly => Some(last + Months::new(1)),
+            ReminderFrequency::Quarterly => Some(last + Months::new(3)),
+            ReminderFrequency::Yearly => Some(last + Months::new(12)),
+            ReminderFrequency::Never => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct Account {
     pub id: String,
     pub name: String,
+    #[sqlx(try_from = "String")]
     pub account_type: AccountType,
+    #[sqlx(try_from = "String")]
     pub category: AccountCategory,
     pub institution: Option<String>,
     pub description: Option<String>,
@@ -89,6 +280,50 @@ pub struct Account {
     pub is_active: bool,
     pub created_at: String,
     pub updated_at: String,
+    pub archived_at: Option<String>,
+    pub group_id: Option<String>,
+    pub sort_order: i64,
+    /// For a liability, the asset it is secured on (a mortgage's property).
+    pub linked_account_id: Option<String>,
+    /// The share of the balance that counts towards the user's net worth.
+    pub ownership_percent: f64,
+    pub color: Option<String>,
+    pub icon: Option<String>,
+    /// Annual percentage rate, e.g. `4.5` for 4.5%.
+    pub interest_rate: Option<f64>,
+    /// The date the account was opened, when known. Before it the account didn't
+    /// exist, as opposed to simply having no entries yet.
+    pub opened_at: Option<String>,
+    /// Set when the account was paid off or closed; it counts for nothing after this date.
+    pub closed_at: Option<String>,
+    /// Still listed, but left out of every total.
+    pub exclude_from_net_worth: bool,
+    /// Only set on credit cards.
+    pub credit_limit: Option<f64>,
+    /// How often the balance should be updated; `None` leaves it to the caller.
+    pub reminder_frequency: Option<ReminderFrequency>,
+    /// A stable identifier from outside the app, such as a bank connection's
+    /// account id. Unique across accounts when set.
+    pub external_ref: Option<String>,
+    /// The day of the month a statement closes on, e.g. a credit card's billing
+    /// date. Monthly figures follow calendar months when unset.
+    pub statement_day: Option<u32>,
+    /// Starts at the type's default; see `AccountType::default_liquidity`.
+    #[serde(default)]
+    pub liquidity: Liquidity,
+    /// Counted towards `get_emergency_coverage`.
+    #[serde(default)]
+    pub is_emergency_fund: bool,
+    /// Only loaded on request, see `get_account`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[sqlx(skip)]
+    pub tags: Option<Vec<Tag>>,
+}
+
+impl Account {
+    pub fn is_archived(&self) -> bool {
+        self.archived_at.is_some()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -98,21 +333,275 @@ pub struct CreateAccountInput {
     pub institution: Option<String>,
     pub description: Option<String>,
     pub currency: Option<String>,
+    pub ownership_percent: Option<f64>,
+    pub color: Option<String>,
+    pub icon: Option<String>,
+    pub interest_rate: Option<f64>,
+    /// Recorded as the first balance entry, dated `opened_at` (or today).
+    pub opening_balance: Option<f64>,
+    pub opened_at: Option<String>,
+    #[serde(default)]
+    pub exclude_from_net_worth: bool,
+    pub credit_limit: Option<f64>,
+    pub reminder_frequency: Option<ReminderFrequency>,
+    pub external_ref: Option<String>,
+    pub statement_day: Option<u32>,
+    /// The type's default when `None`.
+    pub liquidity: Option<Liquidity>,
+    #[serde(default)]
+    pub is_emergency_fund: bool,
+}
+
+impl CreateAccountInput {
+    pub fn validate(&self) -> Result<()> {
+        validate_name(&self.name)?;
+        if let Some(currency) = &self.currency {
+            validate_currency(currency)?;
+        }
+        if let Some(percent) = self.ownership_percent {
+            validate_ownership_percent(percent)?;
+        }
+        if let Some(color) = &self.color {
+            validate_color(color)?;
+        }
+        if let Some(rate) = self.interest_rate {
+            validate_interest_rate(rate)?;
+        }
+        if let Some(limit) = self.credit_limit {
+            validate_credit_limit(limit)?;
+        }
+        if let Some(day) = self.statement_day {
+            validate_statement_day(day)?;
+        }
+        if self
+            .opening_balance
+            .is_some_and(|balance| !balance.is_finite())
+        {
+            return Err(Error::validation("opening_balance", "must be a number"));
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdateAccountInput {
     pub name: Option<String>,
-    pub institution: Option<String>,
-    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Patch::is_keep")]
+    pub institution: Patch<String>,
+    #[serde(default, skip_serializing_if = "Patch::is_keep")]
+    pub description: Patch<String>,
     pub currency: Option<String>,
     pub is_active: Option<bool>,
+    pub color: Option<String>,
+    pub icon: Option<String>,
+    pub interest_rate: Option<f64>,
+    pub exclude_from_net_worth: Option<bool>,
+    #[serde(default, skip_serializing_if = "Patch::is_keep")]
+    pub credit_limit: Patch<f64>,
+    #[serde(default, skip_serializing_if = "Patch::is_keep")]
+    pub reminder_frequency: Patch<ReminderFrequency>,
+    #[serde(default, skip_serializing_if = "Patch::is_keep")]
+    pub external_ref: Patch<String>,
+    #[serde(default, skip_serializing_if = "Patch::is_keep")]
+    pub statement_day: Patch<u32>,
+    pub liquidity: Option<Liquidity>,
+    pub is_emergency_fund: Option<bool>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl UpdateAccountInput {
+    pub fn validate(&self) -> Result<()> {
+        if let Some(name) = &self.name {
+            validate_name(name)?;
+        }
+        if let Some(currency) = &self.currency {
+            validate_currency(currency)?;
+        }
+        if let Some(color) = &self.color {
+            validate_color(color)?;
+        }
+        if let Some(rate) = self.interest_rate {
+            validate_interest_rate(rate)?;
+        }
+        if let Patch::Set(limit) = self.credit_limit {
+            validate_credit_limit(limit)?;
+        }
+        if let Patch::Set(day) = self.statement_day {
+            validate_statement_day(day)?;
+        }
+        Ok(())
+    }
+}
+
+fn validate_name(name: &str) -> Result<()> {
+    if name.trim().is_empty() {
+        return Err(Error::validation("name", "must not be empty"));
+    }
+    Ok(())
+}
+
+pub fn validate_ownership_percent(percent: f64) -> Result<()> {
+    if !(0.0..=100.0).contains(&percent) {
+        return Err(Error::validation(
+            "ownership_percent",
+            format!("{percent} is not between 0 and 100"),
+        ));
+    }
+    Ok(())
+}
+
+pub fn validate_interest_rate(rate: f64) -> Result<()> {
+    if !(0.0..=100.0).contains(&rate) {
+        return Err(Error::validation(
+            "interest_rate",
+            format!("{rate} is not an annual percentage between 0 and 100"),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_credit_limit(limit: f64) -> Result<()> {
+    if !limit.is_finite() || limit <= 0.0 {
+        return Err(Error::validation("credit_limit", "must be greater than 0"));
+    }
+    Ok(())
+}
+
+fn validate_statement_day(day: u32) -> Result<()> {
+    if !(1..=31).contains(&day) {
+        return Err(Error::validation(
+            "statement_day",
+            "must be between 1 and 31",
+        ));
+    }
+    Ok(())
+}
+
+/// Colours are stored as `#RRGGBB` hex so the frontend can use them directly in CSS.
+fn validate_color(color: &str) -> Result<()> {
+    let valid = color.len() == 7
+        && color.starts_with('#')
+        && color[1..].bytes().all(|b| b.is_ascii_hexdigit());
+    if !valid {
+        return Err(Error::InvalidColor(color.to_string()));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct AccountWithBalance {
     #[serde(flatten)]
+    #[sqlx(flatten)]
     pub account: Account,
     pub current_balance: f64,
+    /// `current_balance` scaled by the account's ownership share.
+    pub owned_balance: f64,
     pub balance_date: Option<String>,
 }
+
+impl AccountWithBalance {
+    /// The owned balance's effect on net worth: liabilities count against it
+    /// whichever sign they were entered with, and excluded accounts not at all.
+    pub fn net_contribution(&self) -> f64 {
+        if self.account.exclude_from_net_worth {
+            return 0.0;
+        }
+        match self.account.category {
+            AccountCategory::Asset => self.owned_balance,
+            AccountCategory::Liability => -self.owned_balance.abs(),
+        }
+    }
+}
+
+/// How `merge_accounts` resolves two entries on the same date.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum MergeStrategy {
+    KeepTarget,
+    KeepSource,
+    KeepLarger,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MergeSummary {
+    /// Source entries moved onto a date the target had no entry for.
+    pub moved: u32,
+    /// Target entries replaced by the source entry on the same date.
+    pub overwritten: u32,
+    /// Source entries dropped in favour of the target's entry.
+    pub skipped: u32,
+}
+
+/// A stored account row that fails validation, reported by `validate_accounts`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountIssue {
+    pub account_id: String,
+    pub name: String,
+    /// The offending column: `account_type`, `category` or `currency`.
+    pub field: String,
+    pub value: String,
+    pub message: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_types_round_trip_through_strings_and_serde() {
+        for account_type in AccountType::BUILTIN {
+            let name = account_type.to_string();
+            assert_eq!(name.parse::<AccountType>(), Ok(account_type.clone()));
+            let json = serde_json::to_string(&account_type).unwrap();
+            assert_eq!(json, format!("\"{name}\""));
+            let decoded: AccountType = serde_json::from_str(&json).unwrap();
+            assert_eq!(decoded, account_type);
+        }
+    }
+
+    #[test]
+    fn custom_types_round_trip_through_strings_and_serde() {
+        let account_type: AccountType = "crypto_wallet".parse().unwrap();
+        assert_eq!(account_type, AccountType::Custom("crypto_wallet".into()));
+        assert_eq!(account_type.to_string(), "crypto_wallet");
+        let json = serde_json::to_string(&account_type).unwrap();
+        assert_eq!(json, r#"{"custom":"crypto_wallet"}"#);
+        let decoded: AccountType = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, account_type);
+    }
+
+    #[test]
+    fn invalid_custom_slugs_are_refused() {
+        for value in ["", "Crypto", "crypto wallet", "crypto-wallet"] {
+            assert_eq!(
+                value.parse::<AccountType>(),
+                Err(ParseAccountTypeError {
+                    field: "account_type",
+                    value: value.to_string(),
+                })
+            );
+        }
+        assert!(!AccountType::is_valid_custom_slug("savings"));
+    }
+
+    #[test]
+    fn each_type_has_its_default_liquidity() {
+        let cases = [
+            (AccountType::Property, Liquidity::Illiquid),
+            (AccountType::Pension, Liquidity::Illiquid),
+            (AccountType::Investment, Liquidity::Semi),
+            (AccountType::Savings, Liquidity::Liquid),
+            (AccountType::Mortgage, Liquidity::Illiquid),
+            (AccountType::Loan, Liquidity::Semi),
+            (AccountType::CreditCard, Liquidity::Liquid),
+            (AccountType::Custom("crypto_wallet".into()), Liquidity::Semi),
+        ];
+        assert_eq!(cases.len(), AccountType::BUILTIN.len() + 1);
+        for (account_type, liquidity) in cases {
+            assert_eq!(
+                account_type.default_liquidity(),
+                liquidity,
+                "{account_type}"
+            );
+        }
+    }
+}
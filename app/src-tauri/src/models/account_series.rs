@@ -0,0 +1,105 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Granularity {
+    Day,
+    /// Every seventh day from the start.
+    Week,
+    /// Each month end.
+    Month,
+}
+
+/// How the balance between two entries is estimated.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FillMethod {
+    /// The last entered balance holds until the next entry.
+    CarryForward,
+    /// A straight line between the entries either side.
+    Linear,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SmoothingMethod {
+    /// The plain mean of the last `window` points.
+    Simple,
+    /// Weighted towards recent points, with a weight of 2 / (`window` + 1).
+    Exponential,
+}
+
+/// A moving average laid over a series, after interpolation.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct SmoothingSpec {
+    /// In points, at least 1.
+    pub window: u32,
+    pub method: SmoothingMethod,
+}
+
+/// A point's moving average.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Smoothed {
+    pub value: f64,
+    /// True for the first `window - 1` points, which average what came before.
+    pub partial: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeriesPoint {
+    pub date: String,
+    pub balance: f64,
+    /// False only when an entry was recorded on this date.
+    pub is_estimated: bool,
+    /// Only with a `SmoothingSpec`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub smoothed: Option<Smoothed>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BalanceAtStatus {
+    /// An entry was recorded on the date itself.
+    Exact,
+    /// Estimated from the entries around the date.
+    Estimated,
+    /// The date is before the account's first entry.
+    NoData,
+}
+
+/// A balance entry a value was derived from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntryRef {
+    pub id: String,
+    pub date: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BalanceAt {
+    pub account_id: String,
+    pub date: String,
+    pub status: BalanceAtStatus,
+    /// `None` only with `NoData`.
+    pub balance: Option<f64>,
+    /// One entry when exact or carried forward, the two either side when interpolated.
+    pub derived_from: Vec<EntryRef>,
+}
+
+/// The balance an account closed one month, or one statement period, on.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonthlyBalance {
+    /// The closing date's month, as `YYYY-MM`.
+    pub month: String,
+    pub period_start: String,
+    pub period_end: String,
+    /// The last entry within the period, or the latest before it when the
+    /// period has none.
+    pub balance: f64,
+    pub entry_date: String,
+    /// True when the balance was carried forward from an earlier period.
+    pub is_estimated: bool,
+    /// `None` when there is no earlier balance to compare with.
+    pub change: Option<f64>,
+    /// `None` also when the earlier balance was zero.
+    pub change_percent: Option<f64>,
+}
@@ -0,0 +1,91 @@
+use serde::{Deserialize, Serialize};
+
+use super::{BalanceEntry, Patch};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(rename_all = "snake_case")]
+pub enum AdjustmentKind {
+    /// `amount` is added to the previous balance, e.g. -450 for a mortgage payment.
+    Delta,
+    /// `amount` is the balance itself.
+    Absolute,
+}
+
+/// A rule generating one balance entry a month for an account whose balance
+/// changes predictably.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct RecurringAdjustment {
+    pub id: String,
+    pub account_id: String,
+    pub amount: f64,
+    /// Months without this day use their last day instead.
+    pub day_of_month: u32,
+    pub start_date: String,
+    pub end_date: Option<String>,
+    pub kind: AdjustmentKind,
+    /// The latest date an entry was generated for; earlier dates are never
+    /// generated again, even if their entries are deleted.
+    pub last_applied: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateAdjustmentInput {
+    pub account_id: String,
+    pub amount: f64,
+    pub day_of_month: u32,
+    pub start_date: String,
+    pub end_date: Option<String>,
+    pub kind: AdjustmentKind,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateAdjustmentInput {
+    pub amount: Option<f64>,
+    pub day_of_month: Option<u32>,
+    pub start_date: Option<String>,
+    #[serde(default, skip_serializing_if = "Patch::is_keep")]
+    pub end_date: Patch<String>,
+    pub kind: Option<AdjustmentKind>,
+}
+
+/// A due date for which no entry was generated.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedAdjustment {
+    pub adjustment_id: String,
+    pub account_id: String,
+    pub date: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AppliedAdjustments {
+    pub created: Vec<BalanceEntry>,
+    pub skipped: Vec<SkippedAdjustment>,
+}
+
+/// A one-off change applied to several accounts' balances at once.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum BulkAdjustment {
+    /// Grows each balance by this many percent, e.g. 2.3 or -1.5.
+    Percent(f64),
+    /// Added to each balance.
+    Delta(f64),
+}
+
+/// An account `apply_bulk_adjustment` left alone.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedBulkAdjustment {
+    pub account_id: String,
+    /// The date of the account's latest entry, when it has one.
+    pub latest_date: Option<String>,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BulkAdjustmentResult {
+    pub created: Vec<BalanceEntry>,
+    pub skipped: Vec<SkippedBulkAdjustment>,
+}
@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+
+use super::AccountType;
+
+/// The assets one allocation target covers. The targets in force are all of
+/// one kind, so no account is counted twice.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AllocationBucket {
+    AccountType { account_type: AccountType },
+    Tag { tag_id: String },
+}
+
+impl AllocationBucket {
+    pub fn same_kind(&self, other: &AllocationBucket) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+}
+
+/// The share of total assets a bucket should hold.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AllocationTarget {
+    pub id: String,
+    #[serde(flatten)]
+    pub bucket: AllocationBucket,
+    pub target_percent: f64,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateAllocationTargetInput {
+    #[serde(flatten)]
+    pub bucket: AllocationBucket,
+    pub target_percent: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateAllocationTargetInput {
+    pub target_percent: f64,
+}
+
+/// How far one targeted bucket is from its target. Percentages are of total
+/// assets and drifts are actual less target; `to_move` is what to pay in, or
+/// when negative take out, to hit the target.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AllocationDriftBucket {
+    pub target_id: String,
+    #[serde(flatten)]
+    pub bucket: AllocationBucket,
+    /// The account type, or the tag's name.
+    pub name: String,
+    /// Empty when nothing is held in the bucket.
+    pub account_ids: Vec<String>,
+    pub actual_amount: f64,
+    pub actual_percent: f64,
+    pub target_amount: f64,
+    pub target_percent: f64,
+    /// In percentage points.
+    pub drift_percent: f64,
+    pub drift_amount: f64,
+    pub to_move: f64,
+}
+
+/// Assets no target covers: an account type in type mode, otherwise one
+/// account. All of it would have to move for the targets to be met.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnallocatedHolding {
+    pub key: String,
+    pub name: String,
+    pub account_ids: Vec<String>,
+    pub amount: f64,
+    pub percent: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AllocationDrift {
+    pub as_of: String,
+    pub total_assets: f64,
+    /// Largest target first.
+    pub buckets: Vec<AllocationDriftBucket>,
+    /// Largest first.
+    pub unallocated: Vec<UnallocatedHolding>,
+}
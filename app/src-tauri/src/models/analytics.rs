@@ -0,0 +1,504 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    AccountCategory, FillMethod, Granularity, InflationOptions, Liquidity, MilestoneCategory,
+    Smoothed, SmoothingSpec, TrendMethod,
+};
+
+/// How `get_net_worth_series` builds its points.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NetWorthSeriesOptions {
+    /// How balances between entries are estimated; linear by default.
+    pub method: Option<FillMethod>,
+    /// Ignore entries made up by `fill_missing_months`, estimating afresh.
+    pub exclude_estimated: bool,
+    /// Attach to each point the milestones in the period it closes.
+    pub include_milestones: bool,
+    /// Give each point the moving average of its net worth.
+    pub smoothing: Option<SmoothingSpec>,
+    /// State each point in the prices of one month. Points whose month has no
+    /// CPI value of its own are marked estimated.
+    #[serde(flatten)]
+    pub inflation: InflationOptions,
+}
+
+/// A milestone as drawn on a chart.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MilestoneMarker {
+    pub milestone_id: String,
+    /// The milestone's own date, which may fall before the point it is on.
+    pub date: String,
+    pub label: String,
+    pub category: MilestoneCategory,
+    pub icon: Option<String>,
+    pub achieved: bool,
+}
+
+/// Net worth on one date, in the base currency and at the owned share of
+/// each account.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetWorthPoint {
+    pub date: String,
+    pub assets: f64,
+    /// What is owed, as a positive amount.
+    pub liabilities: f64,
+    pub net: f64,
+    /// False only when every account counted had an entry on this date.
+    pub is_estimated: bool,
+    /// Of `net`, only with `NetWorthSeriesOptions::smoothing`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub smoothed: Option<Smoothed>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub milestones: Vec<MilestoneMarker>,
+}
+
+/// How much is owed relative to what is held on one date.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DebtRatioPoint {
+    pub date: String,
+    pub assets: f64,
+    /// What is owed, as a positive amount.
+    pub liabilities: f64,
+    pub net: f64,
+    /// Liabilities over assets; `None` without assets.
+    pub debt_to_assets: Option<f64>,
+    /// Liabilities over net worth; `None` when net worth is zero or below.
+    pub debt_to_net_worth: Option<f64>,
+    pub is_estimated: bool,
+}
+
+/// What `get_net_worth_breakdown` splits accounts by.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BreakdownGroupBy {
+    /// Assets and liabilities.
+    Category,
+    /// Built-in type names and custom type slugs.
+    AccountType,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetWorthBreakdownPoint {
+    pub date: String,
+    /// Each group's total; what is owed is a positive amount.
+    pub groups: BTreeMap<String, f64>,
+    pub is_estimated: bool,
+}
+
+/// What `get_allocation` splits accounts by.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AllocationGroupBy {
+    Account,
+    AccountType,
+    Institution,
+}
+
+/// One slice of an allocation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AllocationSlice {
+    /// The account id, account type or institution; empty for accounts without
+    /// an institution and for the collapsed slice.
+    pub key: String,
+    pub name: String,
+    pub amount: f64,
+    /// Of the slice's side: total assets, or total liabilities.
+    pub percent: f64,
+    pub account_ids: Vec<String>,
+    /// True for the slice the ones under the threshold were collapsed into.
+    pub is_other: bool,
+}
+
+/// What was held on one date, split into assets and what was owed, largest
+/// slice first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Allocation {
+    pub as_of: String,
+    pub group_by: AllocationGroupBy,
+    pub total_assets: f64,
+    /// What is owed, as a positive amount.
+    pub total_liabilities: f64,
+    pub assets: Vec<AllocationSlice>,
+    pub liabilities: Vec<AllocationSlice>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GrowthWindowKind {
+    OneMonth,
+    ThreeMonths,
+    TwelveMonths,
+    /// Since the start of the financial year, per `Settings::fiscal_year_start`.
+    YearToDate,
+}
+
+/// The change in net worth over one window ending on the summary's date.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GrowthWindow {
+    pub window: GrowthWindowKind,
+    pub start: String,
+    /// False when the window starts before any balance was recorded; the
+    /// figures below are then `None`.
+    pub available: bool,
+    pub start_net_worth: Option<f64>,
+    pub change: Option<f64>,
+    /// `None` also when net worth was zero at the start.
+    pub change_percent: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GrowthSummary {
+    pub as_of: String,
+    /// `None` when nothing had been recorded by `as_of`.
+    pub net_worth: Option<f64>,
+    pub windows: Vec<GrowthWindow>,
+    /// The month whose prices the figures are in, when adjusted for inflation.
+    pub reference_month: Option<String>,
+    /// True when a date used had no CPI value for its month.
+    pub cpi_estimated: bool,
+}
+
+/// What `get_cagr` measures the growth of.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CagrScope {
+    NetWorth,
+    /// The accounts counted towards net worth in one category; what is owed is
+    /// positive, so a shrinking debt has negative growth.
+    Category {
+        category: AccountCategory,
+    },
+    /// Any account, counted or not, at its owned share.
+    Account {
+        account_id: String,
+    },
+}
+
+/// Why a growth rate has no value.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CagrUnavailable {
+    /// Nothing in the scope has a balance within the window.
+    NoData,
+    /// The window shrank to a single day once fitted to the data.
+    EmptyWindow,
+    /// Growth from zero or from below it has no rate.
+    NonPositiveStart,
+    NegativeEnd,
+}
+
+/// A value a growth rate was computed from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CagrEndpoint {
+    pub date: String,
+    pub value: f64,
+    /// Interpolated rather than read from entries on `date`.
+    pub estimated: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Cagr {
+    pub scope: CagrScope,
+    pub requested_start: String,
+    pub requested_end: String,
+    /// Where the window was moved to fit the data; `None` with `NoData`.
+    pub start: Option<CagrEndpoint>,
+    pub end: Option<CagrEndpoint>,
+    pub years: f64,
+    /// The window is under a year, so `cagr` extrapolates.
+    pub short_window: bool,
+    /// As a percentage per year.
+    pub cagr: Option<f64>,
+    pub unavailable: Option<CagrUnavailable>,
+    /// The month whose prices the endpoints are in, when adjusted for
+    /// inflation; an endpoint without a CPI value for its month is estimated.
+    pub reference_month: Option<String>,
+}
+
+/// Which assets count towards financial independence.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FireBasis {
+    AllAssets,
+    /// Investment, pension and savings accounts only.
+    #[default]
+    Investable,
+}
+
+/// What `get_fire_projection` assumes; left out, rates come from settings and
+/// the contribution from the last twelve months of flows.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FireParams {
+    /// In the base currency.
+    pub annual_expenses: f64,
+    /// In percent.
+    pub safe_withdrawal_rate: Option<f64>,
+    /// Yearly growth after inflation, in percent.
+    pub real_growth: Option<f64>,
+    pub monthly_contribution: Option<f64>,
+    pub basis: FireBasis,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FireStatus {
+    /// The starting figure is already at the FI number.
+    Reached,
+    Projected,
+    /// Neither growth nor contributions ever get there.
+    Never,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FireProjection {
+    /// Annual expenses divided by the withdrawal rate.
+    pub fi_number: f64,
+    /// The assets counted less every liability, in the base currency.
+    pub starting_value: f64,
+    pub assets: f64,
+    pub liabilities: f64,
+    /// Of the FI number, capped at 100; 0 when the starting figure is negative.
+    pub progress_percent: f64,
+    pub status: FireStatus,
+    /// `None` with `Never`.
+    pub fi_date: Option<String>,
+    pub months_to_fi: Option<u32>,
+    /// The assumptions used, with defaults filled in.
+    pub safe_withdrawal_rate: f64,
+    pub real_growth: f64,
+    pub monthly_contribution: f64,
+    pub basis: FireBasis,
+}
+
+/// What `run_monte_carlo` simulates. Rates left out are estimated from the
+/// month-end history, and the contribution from the last twelve months of flows.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MonteCarloParams {
+    /// An asset account to simulate; net worth when `None`.
+    pub account_id: Option<String>,
+    /// How many months ahead to simulate.
+    pub months: u32,
+    /// 1,000 by default.
+    pub paths: Option<u32>,
+    /// The mean yearly return, in percent.
+    pub annual_return: Option<f64>,
+    /// The standard deviation of yearly returns, in percent.
+    pub annual_volatility: Option<f64>,
+    pub monthly_contribution: Option<f64>,
+    /// The same seed and inputs give the same bands; random when `None`.
+    pub seed: Option<u64>,
+}
+
+/// The spread of simulated balances at the end of one month.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonteCarloBand {
+    pub date: String,
+    pub p10: f64,
+    pub p25: f64,
+    pub p50: f64,
+    pub p75: f64,
+    pub p90: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonteCarloProjection {
+    pub start_date: String,
+    pub start_value: f64,
+    /// The assumptions used, with defaults filled in.
+    pub annual_return: f64,
+    pub annual_volatility: f64,
+    /// True when either rate was estimated from history.
+    pub estimated: bool,
+    pub monthly_contribution: f64,
+    pub paths: u32,
+    /// Pass back to reproduce these bands.
+    pub seed: u64,
+    pub bands: Vec<MonteCarloBand>,
+}
+
+/// The trend `forecast_net_worth` fits.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ForecastModel {
+    Linear,
+    /// Needs net worth above zero throughout the window.
+    Exponential,
+    /// Whichever of the two fits the window better by R².
+    Auto,
+}
+
+/// One trend fitted to the trailing window.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForecastFit {
+    pub method: TrendMethod,
+    /// The fitted net worth at the start of the window.
+    pub intercept: f64,
+    /// The change a month: an amount for a linear trend, a percentage for an
+    /// exponential one.
+    pub monthly_rate: f64,
+    /// Against the actual month-end figures, for both shapes, so the two compare.
+    pub r_squared: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForecastPoint {
+    pub date: String,
+    pub net_worth: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetWorthForecast {
+    pub requested_model: ForecastModel,
+    /// The fit the points follow.
+    pub fit: ForecastFit,
+    /// Every trend fitted, including the one not picked with `Auto`.
+    pub candidates: Vec<ForecastFit>,
+    pub window_start: String,
+    pub window_end: String,
+    pub requested_window_months: u32,
+    /// Fewer than requested when the history is shorter.
+    pub window_months: u32,
+    /// The month ends fitted to.
+    pub data_points: u32,
+    /// Says why the window shrank or a model was picked or left out.
+    pub notes: Vec<String>,
+    pub points: Vec<ForecastPoint>,
+}
+
+/// One side of the balance sheet split by liquidity.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LiquidityTotals {
+    pub liquid: f64,
+    pub semi: f64,
+    pub illiquid: f64,
+    pub total: f64,
+}
+
+/// Assets and liabilities split by liquidity on one date, in the base currency
+/// and at each account's owned share.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiquidityPoint {
+    pub date: String,
+    pub assets: LiquidityTotals,
+    /// What is owed, as positive amounts.
+    pub liabilities: LiquidityTotals,
+    /// Liquid assets less liquid liabilities: what could be spent now once
+    /// what is due now is paid.
+    pub accessible_cash: f64,
+    pub is_estimated: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiquidityAccount {
+    pub account_id: String,
+    pub name: String,
+    pub category: AccountCategory,
+    pub liquidity: Liquidity,
+    /// What is owed is positive.
+    pub amount: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiquidityBreakdown {
+    #[serde(flatten)]
+    pub totals: LiquidityPoint,
+    /// Largest first within each category, assets first.
+    pub accounts: Vec<LiquidityAccount>,
+}
+
+/// The change in net worth from one point of the series to the next.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeriodChange {
+    pub start: String,
+    pub end: String,
+    pub start_net_worth: f64,
+    pub end_net_worth: f64,
+    pub change: f64,
+    /// `None` when net worth was zero at the start.
+    pub change_percent: Option<f64>,
+    /// True when more than half of what was held or owed at the end, by value,
+    /// was estimated rather than recorded on the day.
+    pub mostly_estimated: bool,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StreakDirection {
+    Up,
+    Down,
+    Flat,
+}
+
+/// Consecutive periods whose net worth moved the same way.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Streak {
+    pub direction: StreakDirection,
+    /// The start of the first period and the end of the last.
+    pub start: String,
+    pub end: String,
+    pub periods: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeriodStats {
+    pub granularity: Granularity,
+    pub start: String,
+    pub end: String,
+    /// Every change of the series, oldest first.
+    pub changes: Vec<PeriodChange>,
+    pub best_period: Option<PeriodChange>,
+    pub worst_period: Option<PeriodChange>,
+    /// Calendar years, the first and last cut short by the range.
+    pub best_year: Option<PeriodChange>,
+    pub worst_year: Option<PeriodChange>,
+    pub longest_rise: Option<Streak>,
+    /// The run the series ends on.
+    pub current_streak: Option<Streak>,
+    pub average_change: Option<f64>,
+    /// The population standard deviation of the changes.
+    pub change_std_dev: Option<f64>,
+}
+
+/// A fall in net worth from a high and, once regained, the recovery.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Drawdown {
+    pub peak_date: String,
+    pub peak: f64,
+    pub trough_date: String,
+    pub trough: f64,
+    /// The first date back at or above the peak; `None` while still below it.
+    pub recovery_date: Option<String>,
+    /// How far below the peak the trough is, as a positive amount.
+    pub depth: f64,
+    /// `depth` as a percentage of the peak; `None` when the peak was zero or below.
+    pub depth_percent: Option<f64>,
+}
+
+/// How far net worth is below the highest point of the range.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CurrentDrawdown {
+    pub high_date: String,
+    pub high: f64,
+    pub value: f64,
+    /// Zero at a new high.
+    pub depth: f64,
+    pub depth_percent: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DrawdownAnalysis {
+    pub start: String,
+    pub end: String,
+    /// The deepest by percentage, or by amount when no peak was above zero.
+    pub max_drawdown: Option<Drawdown>,
+    /// `None` when the range has no net worth.
+    pub current: Option<CurrentDrawdown>,
+    /// Those at least `threshold_percent` deep, oldest first.
+    pub drawdowns: Vec<Drawdown>,
+    pub threshold_percent: f64,
+}
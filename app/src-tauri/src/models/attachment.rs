@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+
+/// A file kept alongside a balance entry, such as the statement behind the figure.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Attachment {
+    pub id: String,
+    pub balance_entry_id: String,
+    /// The name of the file when it was attached.
+    pub filename: String,
+    /// The copy in the app's `attachments/` directory, named after a hash of
+    /// its contents so identical files are stored once.
+    pub stored_name: String,
+    pub mime_type: String,
+    pub size_bytes: i64,
+    pub added_at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OrphanedAttachments {
+    pub dry_run: bool,
+    /// Files in `attachments/` that no attachment refers to; deleted unless `dry_run`.
+    pub orphaned_files: Vec<String>,
+    /// Attachments whose file has gone missing. These are only reported.
+    pub missing_files: Vec<Attachment>,
+}
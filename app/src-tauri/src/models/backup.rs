@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+
+use super::{
+    Account, AccountFieldChange, AccountGroup, AllocationTarget, Attachment, BalanceEntry,
+    BalanceEntryChange, Benchmark, CpiRate, CustomAccountType, ExchangeRate, Flow, IncomeRecord,
+    Milestone, PensionDetails, PropertyDetails, RecurringAdjustment, Scenario, Setting, Tag,
+};
+
+/// Bumped whenever a backup written by this version can't be read by older ones.
+pub const BACKUP_VERSION: u32 = 3;
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct AccountTag {
+    pub account_id: String,
+    pub tag_id: String,
+}
+
+/// Everything in the database, as written by `export_backup` and read back by
+/// `import_backup`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Backup {
+    pub version: u32,
+    pub exported_at: String,
+    pub custom_account_types: Vec<CustomAccountType>,
+    pub groups: Vec<AccountGroup>,
+    pub accounts: Vec<Account>,
+    pub balance_entries: Vec<BalanceEntry>,
+    pub milestones: Vec<Milestone>,
+    pub tags: Vec<Tag>,
+    pub account_tags: Vec<AccountTag>,
+    #[serde(default)]
+    pub account_field_history: Vec<AccountFieldChange>,
+    #[serde(default)]
+    pub pension_details: Vec<PensionDetails>,
+    #[serde(default)]
+    pub property_details: Vec<PropertyDetails>,
+    #[serde(default)]
+    pub settings: Vec<Setting>,
+    #[serde(default)]
+    pub balance_entry_history: Vec<BalanceEntryChange>,
+    #[serde(default)]
+    pub exchange_rates: Vec<ExchangeRate>,
+    #[serde(default)]
+    pub flows: Vec<Flow>,
+    #[serde(default)]
+    pub recurring_adjustments: Vec<RecurringAdjustment>,
+    #[serde(default)]
+    pub income_records: Vec<IncomeRecord>,
+    #[serde(default)]
+    pub benchmarks: Vec<Benchmark>,
+    #[serde(default)]
+    pub cpi_rates: Vec<CpiRate>,
+    #[serde(default)]
+    pub scenarios: Vec<Scenario>,
+    #[serde(default)]
+    pub allocation_targets: Vec<AllocationTarget>,
+    /// Attachment rows only; the files themselves stay in the app data directory.
+    #[serde(default)]
+    pub attachments: Vec<Attachment>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportSummary {
+    pub accounts: u32,
+    pub balance_entries: u32,
+    pub milestones: u32,
+}
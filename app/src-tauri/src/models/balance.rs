@@ -1,13 +1,77 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use super::{serialize_amount, Patch};
+
+/// How an entry came to exist.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(rename_all = "snake_case")]
+pub enum EntrySource {
+    #[default]
+    Manual,
+    /// Read from an imported file or pasted text.
+    Import,
+    /// Filled in between real entries rather than observed.
+    Estimated,
+    /// Generated from a recurring adjustment.
+    Scheduled,
+}
+
+impl EntrySource {
+    /// The value stored in `source` columns.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EntrySource::Manual => "manual",
+            EntrySource::Import => "import",
+            EntrySource::Estimated => "estimated",
+            EntrySource::Scheduled => "scheduled",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct BalanceEntry {
     pub id: String,
     pub account_id: String,
     pub date: String,
+    /// Always `balance_minor` as a decimal; stored alongside it for the queries
+    /// that still read it directly.
+    #[serde(serialize_with = "serialize_amount")]
     pub balance: f64,
+    /// The balance as a whole number of the currency's minor units, e.g. pence.
+    /// Missing from backups written before version 2.
+    #[serde(default)]
+    pub balance_minor: i64,
+    /// The scale of `balance_minor`: 2 for pence, 0 for yen.
+    #[serde(default)]
+    pub minor_units: u8,
     pub notes: Option<String>,
     pub created_at: String,
+    /// Set when the balance was reported in another currency and converted into
+    /// the account's; `original_amount` is the figure as reported.
+    #[serde(default)]
+    pub original_currency: Option<String>,
+    #[serde(default)]
+    pub original_amount: Option<f64>,
+    #[serde(default)]
+    pub source: EntrySource,
+    /// When the balance was last checked against a statement. Changing the
+    /// balance clears it.
+    #[serde(default)]
+    pub verified_at: Option<String>,
+}
+
+/// An entry with its change since the account's previous entry. The deltas are
+/// `None` on the first entry, and `change_percent` also when the previous
+/// balance was zero.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BalanceEntryWithDelta {
+    #[serde(flatten)]
+    pub entry: BalanceEntry,
+    pub previous_balance: Option<f64>,
+    pub change_amount: Option<f64>,
+    pub change_percent: Option<f64>,
+    pub days_since_previous: Option<i64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,10 +80,238 @@ pub struct CreateBalanceInput {
     pub date: String,
     pub balance: f64,
     pub notes: Option<String>,
+    /// The currency the balance was reported in, when not the account's. The
+    /// stored balance is then converted using the exchange rate nearest `date`.
+    #[serde(default)]
+    pub original_currency: Option<String>,
+    /// The amount in `original_currency`; `balance` when omitted.
+    #[serde(default)]
+    pub original_amount: Option<f64>,
+    /// Acknowledges an `OutlierWarning`, needed to save a flagged balance when
+    /// the `confirm_outliers` setting is on.
+    #[serde(default)]
+    pub confirm_outlier: bool,
+}
+
+/// A balance far enough from the account's previous entry to look like a typo.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutlierWarning {
+    pub previous_date: String,
+    pub previous_balance: f64,
+    pub balance: f64,
+    pub change: f64,
+    /// Relative to the previous balance; `None` when that was zero.
+    pub change_percent: Option<f64>,
+    /// The balance crossed zero, e.g. a missing or stray minus sign.
+    pub sign_changed: bool,
+}
+
+/// A negative balance entered for a liability, which is stored as the positive
+/// amount owed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignWarning {
+    pub entered: f64,
+    /// The balance was saved as its positive amount, under the `normalise`
+    /// sign policy.
+    pub normalised: bool,
+}
+
+/// A newly created or edited entry, with a warning when its balance looks like
+/// an outlier or has the wrong sign.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatedBalanceEntry {
+    #[serde(flatten)]
+    pub entry: BalanceEntry,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outlier: Option<OutlierWarning>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sign: Option<SignWarning>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpsertBalanceResult {
+    pub entry: BalanceEntry,
+    /// False when an existing entry on the same date was overwritten.
+    pub created: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outlier: Option<OutlierWarning>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sign: Option<SignWarning>,
+}
+
+/// How far an account's entries have been checked against statements.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReconciliationStatus {
+    pub account_id: String,
+    /// The date of the latest verified entry.
+    pub last_verified_date: Option<String>,
+    /// When that entry was verified.
+    pub last_verified_at: Option<String>,
+    /// Unverified entries dated after `last_verified_date`, or all of them
+    /// when none is verified.
+    pub unverified_since: u32,
+}
+
+/// A liability whose history holds negative balances, which
+/// `normalise_balance_signs` makes positive.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountSignFix {
+    pub account_id: String,
+    pub name: String,
+    /// Entries already positive, for telling a history entered the other way
+    /// round from a few stray minus signs.
+    pub positive_entries: usize,
+    /// The dates of the negative entries.
+    pub dates: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignNormalisationReport {
+    pub dry_run: bool,
+    pub accounts: Vec<AccountSignFix>,
+    pub fixed: usize,
+}
+
+/// Entries in a date range, deleted unless `dry_run` was set. `entries` holds
+/// them in full so they can be put back.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BalanceEntryDeletion {
+    pub dry_run: bool,
+    pub count: usize,
+    pub dates: Vec<String>,
+    pub entries: Vec<BalanceEntry>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SortDirection {
+    Asc,
+    #[default]
+    Desc,
+}
+
+/// Which of an account's entries `list_balance_entries` returns. Every field is
+/// optional; dates are inclusive.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BalanceEntryFilter {
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+    /// Matched anywhere in the notes, ignoring ASCII case.
+    pub search: Option<String>,
+    pub sort: SortDirection,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+    /// Continue after the entry on this date, in the direction of `sort`; the
+    /// `next_cursor` of the previous page. Cheaper than `offset` deep into a
+    /// long history.
+    pub after: Option<String>,
+    /// Only verified entries when true, only unverified ones when false.
+    pub verified: Option<bool>,
+    /// Only entries from this source.
+    pub source: Option<EntrySource>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BalanceEntryPage {
+    pub entries: Vec<BalanceEntry>,
+    /// Entries matching the filter across all pages, ignoring `after`.
+    pub total: u32,
+    pub limit: u32,
+    pub offset: u32,
+    /// Pass as `after` for the next page; `None` on the last one.
+    pub next_cursor: Option<String>,
+}
+
+/// Why one entry of a batch was rejected, by its position in the request.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchEntryError {
+    pub index: usize,
+    pub field: String,
+    pub message: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdateBalanceInput {
+    #[serde(default)]
+    pub date: Option<String>,
     pub balance: Option<f64>,
-    pub notes: Option<String>,
+    #[serde(default, skip_serializing_if = "Patch::is_keep")]
+    pub notes: Patch<String>,
+    /// As for `CreateBalanceInput`, when the date or balance changes.
+    #[serde(default)]
+    pub confirm_outlier: bool,
+}
+
+/// An entry whose stored date isn't in `YYYY-MM-DD` form.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DateRepair {
+    pub entry_id: String,
+    pub account_id: String,
+    /// The date as stored.
+    pub date: String,
+    /// The same date as `YYYY-MM-DD`, or `None` when it couldn't be read.
+    pub repaired_date: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DateRepairReport {
+    pub dry_run: bool,
+    pub scanned: u32,
+    /// Rewritten, or in a dry run, would have been.
+    pub repaired: Vec<DateRepair>,
+    /// Left alone because the account already has an entry on the repaired date.
+    pub conflicts: Vec<DateRepair>,
+    pub unparseable: Vec<DateRepair>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatetimeRepairReport {
+    pub dry_run: bool,
+    /// Entries whose time was stripped.
+    pub repaired: Vec<DateRepair>,
+    /// Days the account already had an entry for, left alone without a merge
+    /// strategy.
+    pub collisions: Vec<DuplicateEntryGroup>,
+    pub merged: Vec<MergedEntries>,
+}
+
+/// Entries of one account stored under different spellings of the same day,
+/// e.g. `2023-1-5` and `2023-01-05`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateEntryGroup {
+    pub account_id: String,
+    /// The day as `YYYY-MM-DD`.
+    pub date: String,
+    /// Oldest first.
+    pub entries: Vec<BalanceEntry>,
+}
+
+/// Which entry of a `DuplicateEntryGroup` survives a merge.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicateMergeStrategy {
+    /// The most recently created.
+    LatestCreated,
+    /// The highest balance.
+    Largest,
+    /// A manual entry over an import or schedule, and any of those over an
+    /// estimate. Ties go to the most recently created.
+    ManualOverEstimated,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergedEntries {
+    pub account_id: String,
+    pub date: String,
+    /// As kept, under the `YYYY-MM-DD` date.
+    pub kept: BalanceEntry,
+    pub deleted: Vec<BalanceEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateMergeReport {
+    pub strategy: DuplicateMergeStrategy,
+    pub merged: Vec<MergedEntries>,
+    pub deleted: usize,
 }
@@ -0,0 +1,61 @@
+use serde::{Deserialize, Serialize};
+
+/// A value of a custom benchmark; only its movement matters, since the series
+/// is scaled to net worth on its start date.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BenchmarkValue {
+    pub date: String,
+    pub value: f64,
+}
+
+/// How a benchmark grows. Stored as `kind` with the rest as JSON.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum BenchmarkSpec {
+    /// A yearly percentage, compounded monthly at a twelfth of it.
+    FixedRate { annual_rate: f64 },
+    /// Values on given dates, interpolated linearly between them, oldest first.
+    CustomPoints { points: Vec<BenchmarkValue> },
+}
+
+impl BenchmarkSpec {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            BenchmarkSpec::FixedRate { .. } => "fixed_rate",
+            BenchmarkSpec::CustomPoints { .. } => "custom_points",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Benchmark {
+    pub id: String,
+    pub name: String,
+    #[serde(flatten)]
+    pub spec: BenchmarkSpec,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateBenchmarkInput {
+    pub name: String,
+    #[serde(flatten)]
+    pub spec: BenchmarkSpec,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateBenchmarkInput {
+    pub name: Option<String>,
+    /// Replaces the kind and its parameters together.
+    pub spec: Option<BenchmarkSpec>,
+}
+
+/// The benchmark and net worth on one date of the series.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchmarkPoint {
+    pub date: String,
+    /// `None` outside the span a custom benchmark has points for.
+    pub value: Option<f64>,
+    /// `None` before any account has a balance.
+    pub net_worth: Option<f64>,
+}
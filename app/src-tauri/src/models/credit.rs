@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CardUtilisation {
+    pub account_id: String,
+    pub name: String,
+    /// The amount owed on the latest entry, as a positive number.
+    pub balance: f64,
+    pub balance_date: Option<String>,
+    pub credit_limit: f64,
+    pub utilisation_percent: f64,
+}
+
+/// A card with no limit set, which can't be given a utilisation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CardWithoutLimit {
+    pub account_id: String,
+    pub name: String,
+    pub balance: f64,
+    pub balance_date: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UtilisationPoint {
+    pub date: String,
+    pub balance: f64,
+    pub credit_limit: f64,
+    pub utilisation_percent: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreditUtilisationReport {
+    pub cards: Vec<CardUtilisation>,
+    pub cards_without_limit: Vec<CardWithoutLimit>,
+    /// Totals over `cards` only.
+    pub total_balance: f64,
+    pub total_limit: f64,
+    /// `None` when no card has a limit.
+    pub utilisation_percent: Option<f64>,
+    /// Month-end utilisation across `cards`, from interpolated balances and
+    /// today's limits.
+    pub series: Vec<UtilisationPoint>,
+}
@@ -0,0 +1,321 @@
+use serde::Serialize;
+
+use super::money::to_minor;
+use crate::error::{Error, Result};
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+pub struct Currency {
+    pub code: &'static str,
+    /// Digits after the decimal point, e.g. 2 for pence, 0 for yen.
+    pub minor_units: u8,
+    pub name: &'static str,
+    pub symbol: &'static str,
+}
+
+const fn currency(
+    code: &'static str,
+    minor_units: u8,
+    name: &'static str,
+    symbol: &'static str,
+) -> Currency {
+    Currency {
+        code,
+        minor_units,
+        name,
+        symbol,
+    }
+}
+
+/// Active ISO 4217 currencies, sorted by code.
+pub const CURRENCIES: &[Currency] = &[
+    currency("AED", 2, "UAE Dirham", "د.إ"),
+    currency("AFN", 2, "Afghani", "؋"),
+    currency("ALL", 2, "Lek", "L"),
+    currency("AMD", 2, "Armenian Dram", "֏"),
+    currency("ANG", 2, "Netherlands Antillean Guilder", "ƒ"),
+    currency("AOA", 2, "Kwanza", "Kz"),
+    currency("ARS", 2, "Argentine Peso", "$"),
+    currency("AUD", 2, "Australian Dollar", "A$"),
+    currency("AWG", 2, "Aruban Florin", "ƒ"),
+    currency("AZN", 2, "Azerbaijan Manat", "₼"),
+    currency("BAM", 2, "Convertible Mark", "KM"),
+    currency("BBD", 2, "Barbados Dollar", "$"),
+    currency("BDT", 2, "Taka", "৳"),
+    currency("BGN", 2, "Bulgarian Lev", "лв"),
+    currency("BHD", 3, "Bahraini Dinar", "BD"),
+    currency("BIF", 0, "Burundi Franc", "FBu"),
+    currency("BMD", 2, "Bermudian Dollar", "$"),
+    currency("BND", 2, "Brunei Dollar", "$"),
+    currency("BOB", 2, "Boliviano", "Bs"),
+    currency("BRL", 2, "Brazilian Real", "R$"),
+    currency("BSD", 2, "Bahamian Dollar", "$"),
+    currency("BTN", 2, "Ngultrum", "Nu."),
+    currency("BWP", 2, "Pula", "P"),
+    currency("BYN", 2, "Belarusian Ruble", "Br"),
+    currency("BZD", 2, "Belize Dollar", "$"),
+    currency("CAD", 2, "Canadian Dollar", "C$"),
+    currency("CDF", 2, "Congolese Franc", "FC"),
+    currency("CHF", 2, "Swiss Franc", "CHF"),
+    currency("CLP", 0, "Chilean Peso", "$"),
+    currency("CNY", 2, "Yuan Renminbi", "¥"),
+    currency("COP", 2, "Colombian Peso", "$"),
+    currency("CRC", 2, "Costa Rican Colon", "₡"),
+    currency("CUP", 2, "Cuban Peso", "$"),
+    currency("CVE", 2, "Cabo Verde Escudo", "$"),
+    currency("CZK", 2, "Czech Koruna", "Kč"),
+    currency("DJF", 0, "Djibouti Franc", "Fdj"),
+    currency("DKK", 2, "Danish Krone", "kr"),
+    currency("DOP", 2, "Dominican Peso", "$"),
+    currency("DZD", 2, "Algerian Dinar", "DA"),
+    currency("EGP", 2, "Egyptian Pound", "E£"),
+    currency("ERN", 2, "Nakfa", "Nfk"),
+    currency("ETB", 2, "Ethiopian Birr", "Br"),
+    currency("EUR", 2, "Euro", "€"),
+    currency("FJD", 2, "Fiji Dollar", "$"),
+    currency("FKP", 2, "Falkland Islands Pound", "£"),
+    currency("GBP", 2, "Pound Sterling", "£"),
+    currency("GEL", 2, "Lari", "₾"),
+    currency("GHS", 2, "Ghana Cedi", "₵"),
+    currency("GIP", 2, "Gibraltar Pound", "£"),
+    currency("GMD", 2, "Dalasi", "D"),
+    currency("GNF", 0, "Guinean Franc", "FG"),
+    currency("GTQ", 2, "Quetzal", "Q"),
+    currency("GYD", 2, "Guyana Dollar", "$"),
+    currency("HKD", 2, "Hong Kong Dollar", "HK$"),
+    currency("HNL", 2, "Lempira", "L"),
+    currency("HTG", 2, "Gourde", "G"),
+    currency("HUF", 2, "Forint", "Ft"),
+    currency("IDR", 2, "Rupiah", "Rp"),
+    currency("ILS", 2, "New Israeli Sheqel", "₪"),
+    currency("INR", 2, "Indian Rupee", "₹"),
+    currency("IQD", 3, "Iraqi Dinar", "ع.د"),
+    currency("IRR", 2, "Iranian Rial", "﷼"),
+    currency("ISK", 0, "Iceland Krona", "kr"),
+    currency("JMD", 2, "Jamaican Dollar", "$"),
+    currency("JOD", 3, "Jordanian Dinar", "JD"),
+    currency("JPY", 0, "Yen", "¥"),
+    currency("KES", 2, "Kenyan Shilling", "KSh"),
+    currency("KGS", 2, "Som", "с"),
+    currency("KHR", 2, "Riel", "៛"),
+    currency("KMF", 0, "Comorian Franc", "CF"),
+    currency("KPW", 2, "North Korean Won", "₩"),
+    currency("KRW", 0, "Won", "₩"),
+    currency("KWD", 3, "Kuwaiti Dinar", "KD"),
+    currency("KYD", 2, "Cayman Islands Dollar", "$"),
+    currency("KZT", 2, "Tenge", "₸"),
+    currency("LAK", 2, "Lao Kip", "₭"),
+    currency("LBP", 2, "Lebanese Pound", "L£"),
+    currency("LKR", 2, "Sri Lanka Rupee", "Rs"),
+    currency("LRD", 2, "Liberian Dollar", "$"),
+    currency("LSL", 2, "Loti", "L"),
+    currency("LYD", 3, "Libyan Dinar", "LD"),
+    currency("MAD", 2, "Moroccan Dirham", "DH"),
+    currency("MDL", 2, "Moldovan Leu", "L"),
+    currency("MGA", 2, "Malagasy Ariary", "Ar"),
+    currency("MKD", 2, "Denar", "ден"),
+    currency("MMK", 2, "Kyat", "K"),
+    currency("MNT", 2, "Tugrik", "₮"),
+    currency("MOP", 2, "Pataca", "MOP$"),
+    currency("MRU", 2, "Ouguiya", "UM"),
+    currency("MUR", 2, "Mauritius Rupee", "Rs"),
+    currency("MVR", 2, "Rufiyaa", "Rf"),
+    currency("MWK", 2, "Malawi Kwacha", "MK"),
+    currency("MXN", 2, "Mexican Peso", "$"),
+    currency("MYR", 2, "Malaysian Ringgit", "RM"),
+    currency("MZN", 2, "Mozambique Metical", "MT"),
+    currency("NAD", 2, "Namibia Dollar", "$"),
+    currency("NGN", 2, "Naira", "₦"),
+    currency("NIO", 2, "Cordoba Oro", "C$"),
+    currency("NOK", 2, "Norwegian Krone", "kr"),
+    currency("NPR", 2, "Nepalese Rupee", "Rs"),
+    currency("NZD", 2, "New Zealand Dollar", "NZ$"),
+    currency("OMR", 3, "Rial Omani", "RO"),
+    currency("PAB", 2, "Balboa", "B/."),
+    currency("PEN", 2, "Sol", "S/"),
+    currency("PGK", 2, "Kina", "K"),
+    currency("PHP", 2, "Philippine Peso", "₱"),
+    currency("PKR", 2, "Pakistan Rupee", "Rs"),
+    currency("PLN", 2, "Zloty", "zł"),
+    currency("PYG", 0, "Guarani", "₲"),
+    currency("QAR", 2, "Qatari Rial", "QR"),
+    currency("RON", 2, "Romanian Leu", "lei"),
+    currency("RSD", 2, "Serbian Dinar", "din"),
+    currency("RUB", 2, "Russian Ruble", "₽"),
+    currency("RWF", 0, "Rwanda Franc", "FRw"),
+    currency("SAR", 2, "Saudi Riyal", "SR"),
+    currency("SBD", 2, "Solomon Islands Dollar", "$"),
+    currency("SCR", 2, "Seychelles Rupee", "Rs"),
+    currency("SDG", 2, "Sudanese Pound", "£"),
+    currency("SEK", 2, "Swedish Krona", "kr"),
+    currency("SGD", 2, "Singapore Dollar", "S$"),
+    currency("SHP", 2, "Saint Helena Pound", "£"),
+    currency("SLE", 2, "Leone", "Le"),
+    currency("SOS", 2, "Somali Shilling", "Sh"),
+    currency("SRD", 2, "Surinam Dollar", "$"),
+    currency("SSP", 2, "South Sudanese Pound", "£"),
+    currency("STN", 2, "Dobra", "Db"),
+    currency("SVC", 2, "El Salvador Colon", "₡"),
+    currency("SYP", 2, "Syrian Pound", "£"),
+    currency("SZL", 2, "Lilangeni", "E"),
+    currency("THB", 2, "Baht", "฿"),
+    currency("TJS", 2, "Somoni", "SM"),
+    currency("TMT", 2, "Turkmenistan New Manat", "m"),
+    currency("TND", 3, "Tunisian Dinar", "DT"),
+    currency("TOP", 2, "Pa'anga", "T$"),
+    currency("TRY", 2, "Turkish Lira", "₺"),
+    currency("TTD", 2, "Trinidad and Tobago Dollar", "$"),
+    currency("TWD", 2, "New Taiwan Dollar", "NT$"),
+    currency("TZS", 2, "Tanzanian Shilling", "TSh"),
+    currency("UAH", 2, "Hryvnia", "₴"),
+    currency("UGX", 0, "Uganda Shilling", "USh"),
+    currency("USD", 2, "US Dollar", "$"),
+    currency("UYU", 2, "Peso Uruguayo", "$"),
+    currency("UZS", 2, "Uzbekistan Sum", "so'm"),
+    currency("VES", 2, "Bolívar Soberano", "Bs."),
+    currency("VND", 0, "Dong", "₫"),
+    currency("VUV", 0, "Vatu", "VT"),
+    currency("WST", 2, "Tala", "WS$"),
+    currency("XAF", 0, "CFA Franc BEAC", "FCFA"),
+    currency("XCD", 2, "East Caribbean Dollar", "$"),
+    currency("XOF", 0, "CFA Franc BCEAO", "CFA"),
+    currency("XPF", 0, "CFP Franc", "₣"),
+    currency("YER", 2, "Yemeni Rial", "﷼"),
+    currency("ZAR", 2, "Rand", "R"),
+    currency("ZMW", 2, "Zambian Kwacha", "ZK"),
+    currency("ZWL", 2, "Zimbabwe Dollar", "$"),
+];
+
+pub fn find_currency(code: &str) -> Option<&'static Currency> {
+    CURRENCIES
+        .binary_search_by(|currency| currency.code.cmp(code))
+        .ok()
+        .map(|index| &CURRENCIES[index])
+}
+
+/// Accepts only codes in `CURRENCIES`. Anything else fails with the codes it was
+/// probably meant to be, e.g. "GPB" suggests "GBP".
+pub fn validate_currency(code: &str) -> Result<()> {
+    if find_currency(code).is_some() {
+        return Ok(());
+    }
+    Err(Error::UnknownCurrency {
+        code: code.to_string(),
+        suggestions: near_currencies(code),
+    })
+}
+
+/// Up to five known codes within two edits of `code`, ignoring case, closest first.
+pub fn near_currencies(code: &str) -> Vec<String> {
+    let code = code.trim().to_ascii_uppercase();
+    let mut near: Vec<(usize, &str)> = CURRENCIES
+        .iter()
+        .map(|currency| (edit_distance(&code, currency.code), currency.code))
+        .filter(|(distance, _)| *distance <= 2)
+        .collect();
+    near.sort();
+    near.into_iter()
+        .take(5)
+        .map(|(_, code)| code.to_string())
+        .collect()
+}
+
+/// How amounts in a currency are usually written. The separators are hints, since
+/// the reader's locale has the final say.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct CurrencyFormat {
+    pub code: &'static str,
+    pub symbol: &'static str,
+    pub decimal_places: u8,
+    pub decimal_separator: char,
+    pub thousands_separator: char,
+    /// 1234.5 written this way, e.g. `£1,234.50`.
+    pub example: String,
+}
+
+/// Currencies normally written with a decimal comma, such as `1.234,56`.
+const DECIMAL_COMMA: &[&str] = &[
+    "ARS", "BRL", "CLP", "COP", "DKK", "EUR", "IDR", "ISK", "NOK", "PLN", "RON", "RSD", "SEK",
+    "TRY", "UAH", "VND",
+];
+
+/// The decimal and thousands separators usually used with `code`.
+fn separators(code: &str) -> (char, char) {
+    match code {
+        "CHF" => ('.', '\''),
+        code if DECIMAL_COMMA.contains(&code) => (',', '.'),
+        _ => ('.', ','),
+    }
+}
+
+impl Currency {
+    pub fn format(&self) -> CurrencyFormat {
+        let (decimal_separator, thousands_separator) = separators(self.code);
+        CurrencyFormat {
+            code: self.code,
+            symbol: self.symbol,
+            decimal_places: self.minor_units,
+            decimal_separator,
+            thousands_separator,
+            example: format_amount(1234.5, self.code),
+        }
+    }
+}
+
+/// `amount` as it would be shown, e.g. `-£1,234.50` or `¥1,000`: rounded to the
+/// currency's minor units, halves to even, with its symbol in front. Codes not in
+/// the table are written after the number with two decimals.
+pub fn format_amount(amount: f64, currency: &str) -> String {
+    let known = find_currency(currency);
+    let (decimal_places, (decimal_separator, thousands_separator)) = match known {
+        Some(currency) => (currency.minor_units, separators(currency.code)),
+        None => (2, ('.', ',')),
+    };
+    let places = usize::from(decimal_places);
+    let minor = to_minor(amount, decimal_places);
+    let digits = format!("{:0>width$}", minor.unsigned_abs(), width = places + 1);
+    let (whole, fraction) = digits.split_at(digits.len() - places);
+
+    let mut number = String::new();
+    for (index, digit) in whole.chars().enumerate() {
+        if index > 0 && (whole.len() - index) % 3 == 0 {
+            number.push(thousands_separator);
+        }
+        number.push(digit);
+    }
+    if places > 0 {
+        number.push(decimal_separator);
+        number.push_str(fraction);
+    }
+
+    let sign = if minor < 0 { "-" } else { "" };
+    match known {
+        Some(currency) => format!("{sign}{}{number}", currency.symbol),
+        None => format!("{sign}{number} {}", currency.trim().to_ascii_uppercase()),
+    }
+}
+
+/// Optimal string alignment distance: insertions, deletions, substitutions and
+/// swaps of adjacent characters each cost one.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
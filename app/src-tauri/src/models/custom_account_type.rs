@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+
+use super::{AccountCategory, AccountType};
+use crate::error::{Error, Result};
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct CustomAccountType {
+    pub slug: String,
+    pub label: String,
+    #[sqlx(try_from = "String")]
+    pub category: AccountCategory,
+    pub icon: Option<String>,
+    pub created_at: String,
+}
+
+impl CustomAccountType {
+    pub fn account_type(&self) -> AccountType {
+        AccountType::Custom(self.slug.clone())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateCustomAccountTypeInput {
+    pub slug: String,
+    pub label: String,
+    pub category: AccountCategory,
+    pub icon: Option<String>,
+}
+
+impl CreateCustomAccountTypeInput {
+    pub fn validate(&self) -> Result<()> {
+        if !AccountType::is_valid_custom_slug(&self.slug) {
+            return Err(Error::validation(
+                "slug",
+                format!(
+                    "'{}' must be lower-case letters, digits or underscores and not a built-in type",
+                    self.slug
+                ),
+            ));
+        }
+        validate_label(&self.label)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateCustomAccountTypeInput {
+    pub label: Option<String>,
+    pub category: Option<AccountCategory>,
+    pub icon: Option<String>,
+}
+
+impl UpdateCustomAccountTypeInput {
+    pub fn validate(&self) -> Result<()> {
+        if let Some(label) = &self.label {
+            validate_label(label)?;
+        }
+        Ok(())
+    }
+}
+
+fn validate_label(label: &str) -> Result<()> {
+    if label.trim().is_empty() {
+        return Err(Error::validation("label", "must not be empty"));
+    }
+    Ok(())
+}
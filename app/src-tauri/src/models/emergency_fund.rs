@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EmergencyCoverageStatus {
+    Available,
+    /// The `monthly_expenses` setting is zero, so nothing can be measured in
+    /// months; totals are still given.
+    ConfigureExpenses,
+    /// No asset account is marked `is_emergency_fund`.
+    NoAccounts,
+}
+
+/// What the emergency funds held at one month end.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmergencyCoveragePoint {
+    pub date: String,
+    pub total: f64,
+    pub months_covered: Option<f64>,
+}
+
+/// How many months of expenses the emergency funds would pay for, on one date
+/// and at each month end over the two years before it. Months are `None`
+/// unless the status is `Available`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmergencyCoverage {
+    pub as_of: String,
+    pub status: EmergencyCoverageStatus,
+    /// Zero until configured.
+    pub monthly_expenses: f64,
+    pub account_ids: Vec<String>,
+    /// In the base currency, at the owned share of each account.
+    pub total: f64,
+    pub months_covered: Option<f64>,
+    pub series: Vec<EmergencyCoveragePoint>,
+}
@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+/// What one unit of `from_currency` was worth in `to_currency` on `date`.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ExchangeRate {
+    pub id: String,
+    pub date: String,
+    pub from_currency: String,
+    pub to_currency: String,
+    pub rate: f64,
+    pub created_at: String,
+}
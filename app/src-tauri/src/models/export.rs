@@ -0,0 +1,35 @@
+use serde::{Deserialize, Serialize};
+
+/// How `export_balances_csv` writes an account's history.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BalanceCsvExportOptions {
+    /// A `strftime` pattern such as `%d/%m/%Y`.
+    pub date_format: String,
+    /// `.` or `,`. With a decimal comma, columns are separated by `;` instead.
+    pub decimal_separator: char,
+    /// Adds a row for each month end without an entry, interpolated between the
+    /// entries either side and marked `estimated`.
+    pub include_interpolated: bool,
+    /// Replace the file at the path if there is one.
+    pub overwrite: bool,
+}
+
+impl Default for BalanceCsvExportOptions {
+    fn default() -> Self {
+        BalanceCsvExportOptions {
+            date_format: "%Y-%m-%d".to_string(),
+            decimal_separator: '.',
+            include_interpolated: false,
+            overwrite: false,
+        }
+    }
+}
+
+/// The dates an export covers, both optional and inclusive.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DateRange {
+    pub start: Option<String>,
+    pub end: Option<String>,
+}
@@ -0,0 +1,132 @@
+use serde::{Deserialize, Serialize};
+
+use super::Patch;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(rename_all = "snake_case")]
+pub enum FlowKind {
+    /// Money paid in, or for a liability, paid off.
+    Contribution,
+    Withdrawal,
+}
+
+/// Money moved into or out of an account, as opposed to a change in its value.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Flow {
+    pub id: String,
+    pub account_id: String,
+    pub date: String,
+    /// Always positive; `kind` gives the direction.
+    pub amount: f64,
+    pub kind: FlowKind,
+    pub notes: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateFlowInput {
+    pub account_id: String,
+    pub date: String,
+    pub amount: f64,
+    pub kind: FlowKind,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateFlowInput {
+    pub date: Option<String>,
+    pub amount: Option<f64>,
+    pub kind: Option<FlowKind>,
+    #[serde(default, skip_serializing_if = "Patch::is_keep")]
+    pub notes: Patch<String>,
+}
+
+/// How an account's balance changed over a period, split into money paid in
+/// or out and everything else. For a liability the balances are amounts owed,
+/// contributions are repayments, and `growth` is interest and charges.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountGrowth {
+    pub account_id: String,
+    pub start: String,
+    pub end: String,
+    /// The latest balance on or before each date; 0 before the first entry.
+    pub start_balance: f64,
+    pub end_balance: f64,
+    pub change: f64,
+    /// Flows dated after `start` up to and including `end`.
+    pub contributions: f64,
+    pub withdrawals: f64,
+    pub net_flow: f64,
+    pub growth: f64,
+    /// False when the account has no flows recorded at all, in which case the
+    /// whole change counts as growth.
+    pub has_flows: bool,
+}
+
+/// The change in net worth over a period split into money saved, meaning net
+/// flows into accounts, and money grown. Accounts excluded from net worth are
+/// left out and shared ones count at their owned share.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetWorthGrowth {
+    pub start: String,
+    pub end: String,
+    pub start_net_worth: f64,
+    pub end_net_worth: f64,
+    pub change: f64,
+    pub saved: f64,
+    pub grown: f64,
+    pub accounts: Vec<AccountGrowth>,
+}
+
+/// Why a rate of return has no value.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReturnUnavailable {
+    /// The account has no balance on `start`.
+    NoData,
+    /// A sub-period starts at zero or below, so has no return.
+    NonPositiveValue,
+    /// The money-weighted rate didn't settle, or the cash flows never change
+    /// sign so there is none.
+    NoConvergence,
+}
+
+/// The return between two flows, or the ends of the period.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubPeriodReturn {
+    pub start: String,
+    pub end: String,
+    pub start_value: f64,
+    /// Including the flows dated `end`.
+    pub end_value: f64,
+    /// Flows dated `end`, withdrawals negative.
+    pub net_flow: f64,
+    /// As a percentage; `None` when the sub-period starts at zero or below.
+    pub return_percent: Option<f64>,
+    /// Either value was interpolated rather than read from an entry.
+    pub estimated: bool,
+}
+
+/// An asset account's investment return over a period, with balances read as
+/// of the end of each day, after that day's flows.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeWeightedReturn {
+    pub account_id: String,
+    pub start: String,
+    pub end: String,
+    /// Sub-periods broken at each day with flows, oldest first.
+    pub periods: Vec<SubPeriodReturn>,
+    /// The sub-period returns chained, as a percentage over the whole period;
+    /// without flows, the simple change.
+    pub twr: Option<f64>,
+    /// `twr` as a percentage per year.
+    pub twr_annualised: Option<f64>,
+    pub twr_unavailable: Option<ReturnUnavailable>,
+    /// The money-weighted return as a percentage per year: the rate at which
+    /// the starting balance and flows grow into the end balance.
+    pub xirr: Option<f64>,
+    pub xirr_unavailable: Option<ReturnUnavailable>,
+    /// False when no flows fall within the period.
+    pub has_flows: bool,
+}
@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+
+use super::AccountWithBalance;
+use crate::error::{Error, Result};
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct AccountGroup {
+    pub id: String,
+    pub name: String,
+    pub sort_order: i64,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateGroupInput {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateGroupInput {
+    pub name: String,
+}
+
+fn validate_group_name(name: &str) -> Result<()> {
+    if name.trim().is_empty() {
+        return Err(Error::validation("name", "must not be empty"));
+    }
+    Ok(())
+}
+
+impl CreateGroupInput {
+    pub fn validate(&self) -> Result<()> {
+        validate_group_name(&self.name)
+    }
+}
+
+impl UpdateGroupInput {
+    pub fn validate(&self) -> Result<()> {
+        validate_group_name(&self.name)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountGroupWithAccounts {
+    #[serde(flatten)]
+    pub group: AccountGroup,
+    pub accounts: Vec<AccountWithBalance>,
+    /// Net of the members' latest balances (liabilities subtracted).
+    pub subtotal: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupedAccounts {
+    pub groups: Vec<AccountGroupWithAccounts>,
+    pub ungrouped: Vec<AccountWithBalance>,
+    pub ungrouped_subtotal: f64,
+}
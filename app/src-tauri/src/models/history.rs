@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+
+/// One change to a tracked account field, e.g. a pension moving provider.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct AccountFieldChange {
+    pub id: String,
+    pub account_id: String,
+    /// `name`, `institution` or `account_type`.
+    pub field: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    pub changed_at: String,
+}
+
+/// An edit or deletion of a balance entry, recorded by triggers on `balance_entries`.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct BalanceEntryChange {
+    pub id: String,
+    pub entry_id: String,
+    pub account_id: String,
+    /// `update` or `delete`.
+    pub operation: String,
+    pub old_date: String,
+    pub old_balance: f64,
+    pub old_notes: Option<String>,
+    /// Unset for deletions.
+    pub new_date: Option<String>,
+    pub new_balance: Option<f64>,
+    pub new_notes: Option<String>,
+    /// Verification before and after; a balance edit that clears it is
+    /// recorded as its own change.
+    #[serde(default)]
+    pub old_verified_at: Option<String>,
+    #[serde(default)]
+    pub new_verified_at: Option<String>,
+    pub changed_at: String,
+}
@@ -0,0 +1,166 @@
+use serde::{Deserialize, Serialize};
+
+use super::UpsertBalanceResult;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CsvRowStatus {
+    /// Inserted, or in a dry run, would have been.
+    Created,
+    /// An account with the same external_ref, or the same name and institution,
+    /// already exists in the database or earlier in the file.
+    SkippedDuplicate,
+    Error,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CsvRowReport {
+    /// The line in the file, counting the header as line 1.
+    pub line: usize,
+    pub name: String,
+    pub status: CsvRowStatus,
+    pub message: Option<String>,
+    /// Set for created rows outside a dry run, and for rows matched to an existing
+    /// account by external_ref.
+    pub account_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CsvImportReport {
+    pub dry_run: bool,
+    pub created: u32,
+    pub skipped: u32,
+    pub errors: u32,
+    pub rows: Vec<CsvRowReport>,
+}
+
+/// Which columns of a balance CSV hold what, and how to read them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BalanceCsvMapping {
+    /// Header names, matched ignoring case.
+    pub date_column: String,
+    pub balance_column: String,
+    pub notes_column: Option<String>,
+    /// A chrono format such as `%d/%m/%Y`. Without one, ISO 8601 dates are expected.
+    pub date_format: Option<String>,
+    /// `.` or `,`; the other is taken as a thousands separator.
+    #[serde(default = "default_decimal_separator")]
+    pub decimal_separator: char,
+    /// Negates every amount, for statements that show debt as positive.
+    #[serde(default)]
+    pub flip_sign: bool,
+}
+
+fn default_decimal_separator() -> char {
+    '.'
+}
+
+/// What to do with a row dated the same as an existing entry.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicatePolicy {
+    Skip,
+    Overwrite,
+}
+
+/// How the transactions in a QIF file become balances.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum QifImportMode {
+    /// Adds the transactions up from an opening balance and records where each
+    /// month closed.
+    ClosingBalancePerMonth,
+    /// Takes each record's amount as the balance on its date, for files that
+    /// hold balance history rather than transactions.
+    BalanceRecordsOnly,
+}
+
+/// Which way round the day and month of a numeric date are written.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum QifDateOrder {
+    /// `31/01/2024`, as in the UK.
+    DayFirst,
+    /// `01/31/2024`, as in the US.
+    MonthFirst,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QifImportOptions {
+    /// What the account held before the file's first transaction; 0 when
+    /// omitted, as when the file starts with an opening balance transaction.
+    /// Only used with `ClosingBalancePerMonth`.
+    #[serde(default)]
+    pub opening_balance: Option<f64>,
+    /// Needed when every date in the file reads both ways round.
+    #[serde(default)]
+    pub date_order: Option<QifDateOrder>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum BalanceCsvRowStatus {
+    /// Inserted, or in a dry run, would have been.
+    Created,
+    /// The account already has an entry on this date and no policy was given.
+    Duplicate,
+    Overwritten,
+    SkippedDuplicate,
+    Error,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BalanceCsvRow {
+    pub line: usize,
+    /// The date and balance as read, once they parse.
+    pub date: Option<String>,
+    pub balance: Option<f64>,
+    pub notes: Option<String>,
+    pub status: BalanceCsvRowStatus,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BalanceCsvReport {
+    pub dry_run: bool,
+    pub created: u32,
+    pub overwritten: u32,
+    pub skipped: u32,
+    /// Rows clashing with an existing entry; without a policy nothing is written.
+    pub duplicates: u32,
+    pub errors: u32,
+    pub rows: Vec<BalanceCsvRow>,
+}
+
+/// One bank or card statement read from an OFX file.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OfxStatement {
+    /// The bank's account number, `ACCTID`.
+    pub account_id: String,
+    pub bank_id: Option<String>,
+    /// `CHECKING`, `SAVINGS` and so on, or `CREDITCARD` for a card statement.
+    pub account_type: Option<String>,
+    pub currency: Option<String>,
+    pub ledger_balance: Option<f64>,
+    pub ledger_date: Option<String>,
+    pub available_balance: Option<f64>,
+    pub available_date: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OfxImportReport {
+    pub dry_run: bool,
+    /// Every statement in the file.
+    pub statements: Vec<OfxStatement>,
+    /// The `account_id` of the statement the balance came from; `None` when the
+    /// file holds several and none was picked.
+    pub selected: Option<String>,
+    /// The ledger balance, or the available balance when there is none.
+    pub balance: Option<f64>,
+    pub date: Option<String>,
+    /// Set when the statement's currency isn't the account's, in which case the
+    /// balance was converted.
+    pub currency_mismatch: Option<String>,
+    /// The entry written, or in a dry run, the one that would have been.
+    pub result: Option<UpsertBalanceResult>,
+}
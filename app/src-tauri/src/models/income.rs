@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+
+use super::Patch;
+
+/// Take-home pay for one month, in the base currency.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct IncomeRecord {
+    pub id: String,
+    /// As `YYYY-MM`; one record per month.
+    pub month: String,
+    pub net_income: f64,
+    pub notes: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateIncomeRecordInput {
+    pub month: String,
+    pub net_income: f64,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateIncomeRecordInput {
+    pub month: Option<String>,
+    pub net_income: Option<f64>,
+    #[serde(default, skip_serializing_if = "Patch::is_keep")]
+    pub notes: Patch<String>,
+}
+
+/// Where a month's savings figure came from.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SavingsSource {
+    /// Net flows into accounts dated within the month.
+    Flows,
+    /// The month had no flows, so the change in net worth over it stands in.
+    NetWorthChange,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavingsRateMonth {
+    pub month: String,
+    /// `None` when no income is recorded for the month.
+    pub income: Option<f64>,
+    /// `None` when the month has no flows and net worth isn't known at both
+    /// its ends.
+    pub saved: Option<f64>,
+    pub source: SavingsSource,
+    /// `saved` as a percentage of `income`; `None` without either or when
+    /// income was zero.
+    pub rate: Option<f64>,
+    /// True when the month has no income record and is left out of averages.
+    pub missing_income: bool,
+    /// Total saved over total income across the twelve months to this one,
+    /// counting only months with income.
+    pub trailing_12_month_rate: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavingsRate {
+    pub start: String,
+    pub end: String,
+    pub months: Vec<SavingsRateMonth>,
+    /// Across the months in the range that have income.
+    pub average_rate: Option<f64>,
+}
@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+
+/// The consumer price index for one month, in whatever base the source uses;
+/// only ratios between months matter.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct CpiRate {
+    /// As `YYYY-MM`.
+    pub month: String,
+    pub index_value: f64,
+    pub created_at: String,
+}
+
+/// Whether to state amounts in the prices of one month.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InflationOptions {
+    pub inflation_adjusted: bool,
+    /// As `YYYY-MM`; the latest month with a CPI value by default.
+    pub reference_month: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CpiRowError {
+    /// The line in the file, counting the header as line 1.
+    pub line: usize,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CpiImportReport {
+    /// Rows saved, including those replacing a month's earlier value.
+    pub imported: u32,
+    pub errors: Vec<CpiRowError>,
+}
@@ -0,0 +1,28 @@
+use serde::{Deserialize, Serialize};
+
+/// The entry a figure was taken from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BalanceUsed {
+    pub account_id: String,
+    pub date: String,
+    pub balance: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LtvPoint {
+    pub date: String,
+    pub asset_balance: f64,
+    pub liability_balance: f64,
+    pub ltv_percent: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LtvReport {
+    pub asset_id: String,
+    /// `None` when either side has no entries yet or the asset is valued at zero.
+    pub current_ltv_percent: Option<f64>,
+    pub asset_balance: Option<BalanceUsed>,
+    pub liability_balances: Vec<BalanceUsed>,
+    /// Month-end LTV over the period both sides have data, from interpolated balances.
+    pub series: Vec<LtvPoint>,
+}
@@ -0,0 +1,297 @@
+use serde::{Deserialize, Serialize};
+
+use super::Patch;
+use crate::error::{Error, Result};
+
+/// What a milestone's target is measured against.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(rename_all = "snake_case")]
+pub enum MilestoneTargetScope {
+    NetWorth,
+    /// The combined balance of the milestone's accounts. When they are all
+    /// liabilities, the amount owed, reached by falling to the target.
+    Account,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(rename_all = "snake_case")]
+pub enum MilestoneRecurrence {
+    #[default]
+    None,
+    /// On the milestone's day every month, or the month's last day when shorter.
+    Monthly,
+    /// On the milestone's day and month every year.
+    Yearly,
+}
+
+/// What kind of event a milestone marks, for how it is drawn.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(rename_all = "snake_case")]
+pub enum MilestoneCategory {
+    #[default]
+    General,
+    Purchase,
+    /// A debt paid off.
+    Payoff,
+    LifeEvent,
+    /// Drawn with the milestone's own icon.
+    Custom,
+}
+
+/// Which milestones of a recurring series an edit or deletion applies to.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MilestoneEditScope {
+    #[default]
+    ThisInstance,
+    /// This one and every instance of its series on or after its date, and the
+    /// template that will generate the rest.
+    AllFuture,
+}
+
+/// The shape of trend fitted when projecting a target milestone.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TrendMethod {
+    /// A steady amount a month.
+    Linear,
+    /// A steady percentage a month; needs every balance above zero.
+    Exponential,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MilestoneProjectionStatus {
+    /// Already reached; `date` is when.
+    Achieved,
+    /// `date` is when the trend reaches the target.
+    OnTrack,
+    /// The trend is flat, heads away from the target or would take too long.
+    NotOnTrack,
+}
+
+/// When a target milestone is likely to be reached, from the trend of the
+/// last 12 months of its figure.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MilestoneProjection {
+    pub milestone_id: String,
+    pub method: TrendMethod,
+    pub status: MilestoneProjectionStatus,
+    pub date: Option<String>,
+    /// The change a month assumed: an amount for a linear trend, a percentage
+    /// for an exponential one. `None` once achieved.
+    pub monthly_rate: Option<f64>,
+    /// The balances the trend was fitted to.
+    pub data_points: u32,
+    /// Set when the projection is on shaky ground or not on track, saying why.
+    pub note: Option<String>,
+}
+
+/// A dated event to mark on charts, such as a pay rise or a house move,
+/// optionally tied to accounts. One with a target marks itself achieved once
+/// the figure reaches it: rises to it, or for a liability falls to it.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Milestone {
+    pub id: String,
+    pub date: String,
+    pub label: String,
+    /// The accounts it relates to, from `milestone_accounts`, in the order linked.
+    #[sqlx(skip)]
+    #[serde(default)]
+    pub account_ids: Vec<String>,
+    /// The single account of backups before version 3; read, never written.
+    #[sqlx(skip)]
+    #[serde(default, rename = "account_id", skip_serializing)]
+    pub legacy_account_id: Option<String>,
+    pub created_at: String,
+    pub target_amount: Option<f64>,
+    pub target_scope: Option<MilestoneTargetScope>,
+    /// The first date the target was reached. Kept if the figure falls back.
+    pub achieved_at: Option<String>,
+    /// Set on a template, which `materialise_recurring_milestones` repeats from
+    /// its date as instances with no recurrence of their own.
+    #[serde(default)]
+    pub recurrence: MilestoneRecurrence,
+    /// The template an instance was generated from.
+    pub parent_id: Option<String>,
+    /// The latest date instances have been generated up to. Earlier ones are
+    /// never generated again, so deleting one instance keeps it deleted.
+    pub materialised_until: Option<String>,
+    #[serde(default)]
+    pub category: MilestoneCategory,
+    /// Overrides the category's icon.
+    pub icon: Option<String>,
+    /// Its place among the milestones on its date, from 0.
+    #[serde(default)]
+    pub sort_order: i64,
+}
+
+/// A linked account's owned share on a milestone's date, with what is owed as
+/// a positive amount.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MilestoneAccountBalance {
+    pub account_id: String,
+    /// `None` when the account wasn't held then.
+    pub balance: Option<f64>,
+    pub is_estimated: bool,
+}
+
+/// A milestone with the figures as they stood on its date.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MilestoneWithNetWorth {
+    #[serde(flatten)]
+    pub milestone: Milestone,
+    /// `None` when no account counted towards net worth had a balance yet.
+    pub net_worth: Option<f64>,
+    /// True when any balance in it was estimated rather than recorded that day.
+    pub net_worth_estimated: bool,
+    /// One per linked account, in the order linked.
+    pub account_balances: Vec<MilestoneAccountBalance>,
+}
+
+/// Which milestones `list_milestones` and `count_milestones` cover. Every field
+/// is optional, and those given must all match; dates are inclusive.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MilestoneFilter {
+    pub start: Option<String>,
+    pub end: Option<String>,
+    pub category: Option<MilestoneCategory>,
+    /// Only achieved targets when true, only targets not yet reached when false.
+    pub achieved: Option<bool>,
+    /// Only milestones linked to this account, among any others.
+    pub account_id: Option<String>,
+    /// Matched anywhere in the label, ignoring ASCII case.
+    pub search: Option<String>,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+/// A milestone with the names of its accounts, in the order of `account_ids`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MilestoneListItem {
+    #[serde(flatten)]
+    pub milestone: Milestone,
+    pub account_names: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MilestoneSuggestionKind {
+    /// A liability's latest balance is zero.
+    PaidOff,
+    /// A property account was added recently.
+    Bought,
+    /// Net worth first reached a multiple of 50,000.
+    NetWorthCrossed,
+}
+
+/// A milestone offered from what happened to the accounts. Nothing is saved
+/// until it is accepted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MilestoneSuggestion {
+    /// What it is about, such as `paid_off:<account id>` or `net_worth:100000`,
+    /// so that once accepted or dismissed it isn't offered again.
+    pub key: String,
+    pub kind: MilestoneSuggestionKind,
+    pub date: String,
+    pub label: String,
+    #[serde(default)]
+    pub account_ids: Vec<String>,
+    /// Set when the milestone would be a target already achieved on `date`.
+    pub target_amount: Option<f64>,
+    pub target_scope: Option<MilestoneTargetScope>,
+    pub category: MilestoneCategory,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateMilestoneInput {
+    pub date: String,
+    pub label: String,
+    #[serde(default)]
+    pub account_ids: Vec<String>,
+    pub target_amount: Option<f64>,
+    pub target_scope: Option<MilestoneTargetScope>,
+    #[serde(default)]
+    pub recurrence: MilestoneRecurrence,
+    #[serde(default)]
+    pub category: MilestoneCategory,
+    pub icon: Option<String>,
+}
+
+/// Changing the target clears `achieved_at`, so it is checked again.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateMilestoneInput {
+    pub date: Option<String>,
+    pub label: Option<String>,
+    /// Replaces every link; an empty list unlinks the milestone.
+    pub account_ids: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Patch::is_keep")]
+    pub target_amount: Patch<f64>,
+    #[serde(default, skip_serializing_if = "Patch::is_keep")]
+    pub target_scope: Patch<MilestoneTargetScope>,
+    /// Only for templates and one-off milestones; instances follow their template.
+    pub recurrence: Option<MilestoneRecurrence>,
+    pub category: Option<MilestoneCategory>,
+    #[serde(default, skip_serializing_if = "Patch::is_keep")]
+    pub icon: Patch<String>,
+}
+
+fn validate_label(label: &str) -> Result<()> {
+    if label.trim().is_empty() {
+        return Err(Error::validation("label", "must not be empty"));
+    }
+    Ok(())
+}
+
+impl Milestone {
+    /// A target needs a scope, and an account target an account. Recurring
+    /// milestones mark dates rather than goals, so have none.
+    pub fn validate(&self) -> Result<()> {
+        if self.recurrence != MilestoneRecurrence::None && self.target_amount.is_some() {
+            return Err(Error::validation(
+                "target_amount",
+                "a recurring milestone can't have a target",
+            ));
+        }
+        if self.recurrence != MilestoneRecurrence::None && self.parent_id.is_some() {
+            return Err(Error::validation(
+                "recurrence",
+                "an instance follows its template's recurrence",
+            ));
+        }
+        match (self.target_amount, self.target_scope) {
+            (None, None) => Ok(()),
+            (Some(amount), _) if !amount.is_finite() => {
+                Err(Error::validation("target_amount", "must be a number"))
+            }
+            (Some(_), None) => Err(Error::validation(
+                "target_scope",
+                "is required with a target_amount",
+            )),
+            (None, Some(_)) => Err(Error::validation(
+                "target_amount",
+                "is required with a target_scope",
+            )),
+            (Some(_), Some(MilestoneTargetScope::Account)) if self.account_ids.is_empty() => Err(
+                Error::validation("account_ids", "are required for an account target"),
+            ),
+            (Some(_), Some(_)) => Ok(()),
+        }
+    }
+}
+
+impl CreateMilestoneInput {
+    pub fn validate(&self) -> Result<()> {
+        validate_label(&self.label)
+    }
+}
+
+impl UpdateMilestoneInput {
+    pub fn validate(&self) -> Result<()> {
+        self.label.as_deref().map_or(Ok(()), validate_label)
+    }
+}
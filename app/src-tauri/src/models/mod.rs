@@ -1,5 +1,77 @@
 pub mod account;
+pub mod account_series;
+pub mod adjustment;
+pub mod allocation_target;
+pub mod analytics;
+pub mod attachment;
+pub mod backup;
 pub mod balance;
+pub mod benchmark;
+pub mod credit;
+pub mod currency;
+pub mod custom_account_type;
+pub mod emergency_fund;
+pub mod exchange_rate;
+pub mod export;
+pub mod flow;
+pub mod group;
+pub mod history;
+pub mod import;
+pub mod income;
+pub mod inflation;
+pub mod ltv;
+pub mod milestone;
+pub mod money;
+pub mod net_worth_cache;
+pub mod note_search;
+pub mod notification;
+pub mod patch;
+pub mod pension;
+pub mod projection;
+pub mod property;
+pub mod review;
+pub mod scenario;
+pub mod settings;
+pub mod snapshot;
+pub mod stale;
+pub mod tag;
+pub mod undo;
 
 pub use account::*;
+pub use account_series::*;
+pub use adjustment::*;
+pub use allocation_target::*;
+pub use analytics::*;
+pub use attachment::*;
+pub use backup::*;
 pub use balance::*;
+pub use benchmark::*;
+pub use credit::*;
+pub use currency::*;
+pub use custom_account_type::*;
+pub use emergency_fund::*;
+pub use exchange_rate::*;
+pub use export::*;
+pub use flow::*;
+pub use group::*;
+pub use history::*;
+pub use import::*;
+pub use income::*;
+pub use inflation::*;
+pub use ltv::*;
+pub use milestone::*;
+pub use money::*;
+pub use net_worth_cache::*;
+pub use note_search::*;
+pub use notification::*;
+pub use patch::*;
+pub use pension::*;
+pub use projection::*;
+pub use property::*;
+pub use review::*;
+pub use scenario::*;
+pub use settings::*;
+pub use snapshot::*;
+pub use stale::*;
+pub use tag::*;
+pub use undo::*;
@@ -0,0 +1,155 @@
+use serde::Serializer;
+
+use super::currency::find_currency;
+
+/// Digits after the decimal point used for sums across accounts. No ISO 4217
+/// currency has more, so converting any stored amount to this scale is exact.
+const SUM_MINOR_UNITS: u8 = 4;
+
+/// Minor-unit digits for `currency`, falling back to 2 for codes not in the table.
+pub fn minor_units_for(currency: &str) -> u8 {
+    find_currency(currency).map_or(2, |currency| currency.minor_units)
+}
+
+/// `amount` as a whole number of minor units, rounding halves to even as
+/// written in decimal: 1.015 is a tie at 2 digits even though the nearest
+/// double is just below it.
+pub fn to_minor(amount: f64, minor_units: u8) -> i64 {
+    let scaled = amount * 10f64.powi(minor_units.into());
+    // Away from a tie the product's error can't change which way it rounds.
+    let margin = scaled.abs() * f64::EPSILON * 8.0;
+    if !scaled.is_finite() || ((scaled.fract().abs() - 0.5).abs() > margin) {
+        return scaled.round() as i64;
+    }
+    decimal_half_even(amount, minor_units.into())
+}
+
+/// Rounds the shortest decimal that reads back as `amount`, digit by digit.
+fn decimal_half_even(amount: f64, minor_units: usize) -> i64 {
+    let written = format!("{}", amount.abs());
+    let (whole, fraction) = written.split_once('.').unwrap_or((&written, ""));
+    let (kept, rest) = fraction.split_at(fraction.len().min(minor_units));
+    let digits = format!("{whole}{kept:0<minor_units$}");
+    let mut minor: i128 = digits.parse().unwrap_or(i128::MAX);
+    let mut rest = rest.bytes();
+    let round_up = match rest.next() {
+        Some(b'6'..=b'9') => true,
+        Some(b'5') => rest.any(|digit| digit != b'0') || minor % 2 == 1,
+        _ => false,
+    };
+    if round_up {
+        minor += 1;
+    }
+    let minor = i64::try_from(minor).unwrap_or(i64::MAX);
+    if amount < 0.0 {
+        -minor
+    } else {
+        minor
+    }
+}
+
+/// The decimal amount for `minor` minor units.
+pub fn from_minor(minor: i64, minor_units: u8) -> f64 {
+    minor as f64 / 10f64.powi(minor_units.into())
+}
+
+/// `amount` rounded to `minor_units` digits, halves to even. Totals are summed
+/// exactly first and rounded once, so pennies lost per addend don't add up.
+pub fn round_amount(amount: f64, minor_units: u8) -> f64 {
+    from_minor(to_minor(amount, minor_units), minor_units)
+}
+
+/// Adds amounts as integers so that, for example, 0.1 + 0.2 is exactly 0.3.
+pub fn sum_amounts(amounts: impl IntoIterator<Item = f64>) -> f64 {
+    let total: i64 = amounts
+        .into_iter()
+        .map(|amount| to_minor(amount, SUM_MINOR_UNITS))
+        .sum();
+    from_minor(total, SUM_MINOR_UNITS)
+}
+
+/// Writes whole amounts as integers, so a yen balance goes out as `1000`
+/// rather than `1000.0`.
+pub fn serialize_amount<S: Serializer>(amount: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+    if amount.fract() == 0.0 && amount.abs() < i64::MAX as f64 {
+        serializer.serialize_i64(*amount as i64)
+    } else {
+        serializer.serialize_f64(*amount)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_minor_rounds_decimal_ties_to_even() {
+        assert_eq!(to_minor(1.015, 2), 102);
+        assert_eq!(to_minor(8.345, 2), 834);
+        assert_eq!(to_minor(0.125, 2), 12);
+        assert_eq!(to_minor(0.135, 2), 14);
+        assert_eq!(to_minor(2.5, 0), 2);
+        assert_eq!(to_minor(3.5, 0), 4);
+    }
+
+    #[test]
+    fn to_minor_rounds_negative_ties_to_even() {
+        assert_eq!(to_minor(-1.015, 2), -102);
+        assert_eq!(to_minor(-8.345, 2), -834);
+        assert_eq!(to_minor(-2.5, 0), -2);
+    }
+
+    #[test]
+    fn to_minor_rounds_past_a_tie_away_from_it() {
+        assert_eq!(to_minor(8.3451, 2), 835);
+        assert_eq!(to_minor(8.3449, 2), 834);
+        assert_eq!(to_minor(1.019, 2), 102);
+    }
+
+    #[test]
+    fn zero_minor_unit_amounts_round_trip() {
+        assert_eq!(minor_units_for("JPY"), 0);
+        assert_eq!(to_minor(1000.0, 0), 1000);
+        assert_eq!(from_minor(1000, 0), 1000.0);
+        assert_eq!(round_amount(1234.5, 0), 1234.0);
+        assert_eq!(round_amount(1235.5, 0), 1236.0);
+        let json = serde_json::to_string(&Amount(from_minor(to_minor(1000.0, 0), 0))).unwrap();
+        assert_eq!(json, "1000");
+    }
+
+    #[test]
+    fn sum_amounts_adds_exactly() {
+        assert_eq!(sum_amounts([0.1, 0.2]), 0.3);
+        assert_eq!(sum_amounts([1.15, -0.05, 0.1]), 1.2);
+        assert_eq!(sum_amounts(std::iter::empty()), 0.0);
+    }
+
+    #[test]
+    fn mixed_currency_totals_round_once_at_the_end() {
+        // Thirds of a pound from converting three foreign balances.
+        let converted = [0.3333, 0.3333, 0.3334];
+        assert_eq!(round_amount(sum_amounts(converted), 2), 1.0);
+        let per_addend = sum_amounts(converted.map(|amount| round_amount(amount, 2)));
+        assert_eq!(per_addend, 0.99);
+
+        // Half-pennies each round to even on their own but add up to a nickel.
+        let halves = [0.005; 10];
+        assert_eq!(round_amount(sum_amounts(halves), 2), 0.05);
+        assert_eq!(
+            sum_amounts(halves.map(|amount| round_amount(amount, 2))),
+            0.0
+        );
+    }
+
+    #[test]
+    fn mixed_currency_totals_round_to_the_base_currency() {
+        // 1,234.56 USD at 0.7915 and 150,000 JPY at 0.0053 into yen and pounds.
+        let in_gbp = [1234.56 * 0.7915, 150_000.0 * 0.0053];
+        assert_eq!(round_amount(sum_amounts(in_gbp), 2), 1772.15);
+        let in_jpy = [1234.56 * 149.5, 150_000.0];
+        assert_eq!(round_amount(sum_amounts(in_jpy), 0), 334567.0);
+    }
+
+    #[derive(serde::Serialize)]
+    struct Amount(#[serde(serialize_with = "serialize_amount")] f64);
+}
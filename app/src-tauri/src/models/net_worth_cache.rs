@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+/// What `refresh_snapshots` recomputed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotRefresh {
+    /// The first day recomputed; `None` when the snapshots were already current.
+    pub refreshed_from: Option<String>,
+    /// The last day the snapshots now cover, which is today.
+    pub valid_until: String,
+    /// Days given a snapshot; days without net worth have none.
+    pub snapshots: usize,
+}
@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(rename_all = "snake_case")]
+pub enum NoteHitKind {
+    /// `id` is a balance entry, matched on its notes.
+    Entry,
+    /// `id` is a milestone, matched on its label.
+    Milestone,
+}
+
+/// A note or label matching a `search_notes` query.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct NoteSearchHit {
+    pub kind: NoteHitKind,
+    pub id: String,
+    /// Unset for milestones not tied to an account.
+    pub account_id: Option<String>,
+    pub account_name: Option<String>,
+    pub date: String,
+    /// The matching stretch of text, with matches wrapped in `<mark>` tags.
+    pub snippet: String,
+}
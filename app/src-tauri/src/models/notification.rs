@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(rename_all = "snake_case")]
+pub enum NotificationKind {
+    /// A target milestone has been reached.
+    Achieved,
+    /// A milestone is dated within `Settings::notify_days_ahead` of today.
+    Upcoming,
+}
+
+/// A notification shown by `check_and_notify`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MilestoneNotification {
+    pub milestone_id: String,
+    pub kind: NotificationKind,
+    /// The achievement date, or the milestone's date when upcoming.
+    pub date: String,
+    pub title: String,
+    pub body: String,
+}
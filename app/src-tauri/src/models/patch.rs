@@ -0,0 +1,96 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// A change to an optional field. In JSON an omitted field is `Keep`, `null` is
+/// `Clear` and any other value is `Set`, so fields must be marked
+/// `#[serde(default, skip_serializing_if = "Patch::is_keep")]`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Patch<T> {
+    #[default]
+    Keep,
+    Clear,
+    Set(T),
+}
+
+impl<T> Patch<T> {
+    pub fn is_keep(&self) -> bool {
+        matches!(self, Patch::Keep)
+    }
+
+    /// Writes the change into `target`, leaving it alone for `Keep`.
+    pub fn apply(self, target: &mut Option<T>) {
+        match self {
+            Patch::Keep => {}
+            Patch::Clear => *target = None,
+            Patch::Set(value) => *target = Some(value),
+        }
+    }
+}
+
+impl<T> From<Option<T>> for Patch<T> {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Patch::Clear, Patch::Set)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Patch<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Option::<T>::deserialize(deserializer).map(Patch::from)
+    }
+}
+
+impl<T: Serialize> Serialize for Patch<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Patch::Set(value) => serializer.serialize_some(value),
+            Patch::Keep | Patch::Clear => serializer.serialize_none(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::Patch;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Input {
+        #[serde(default, skip_serializing_if = "Patch::is_keep")]
+        notes: Patch<String>,
+    }
+
+    fn round_trip(json: &str, patch: Patch<String>) {
+        let input: Input = serde_json::from_str(json).unwrap();
+        assert_eq!(input, Input { notes: patch });
+        assert_eq!(serde_json::to_string(&input).unwrap(), json);
+    }
+
+    #[test]
+    fn an_absent_field_keeps() {
+        round_trip("{}", Patch::Keep);
+    }
+
+    #[test]
+    fn null_clears() {
+        round_trip(r#"{"notes":null}"#, Patch::Clear);
+    }
+
+    #[test]
+    fn a_value_sets() {
+        round_trip(
+            r#"{"notes":"remortgaged"}"#,
+            Patch::Set("remortgaged".into()),
+        );
+    }
+
+    #[test]
+    fn apply_changes_only_what_the_patch_says() {
+        let mut notes = Some("old".to_string());
+        Patch::Keep.apply(&mut notes);
+        assert_eq!(notes.as_deref(), Some("old"));
+        Patch::Set("new".to_string()).apply(&mut notes);
+        assert_eq!(notes.as_deref(), Some("new"));
+        Patch::Clear.apply(&mut notes);
+        assert_eq!(notes, None);
+    }
+}
@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct PensionDetails {
+    pub account_id: String,
+    pub employer: Option<String>,
+    /// Percentages of `annual_salary` paid in each year.
+    pub employee_contribution_percent: f64,
+    pub employer_contribution_percent: f64,
+    pub annual_salary: Option<f64>,
+    pub retirement_date: Option<String>,
+    pub updated_at: String,
+}
+
+impl PensionDetails {
+    /// The combined employee and employer contribution paid in each month.
+    pub fn monthly_contribution(&self) -> f64 {
+        let percent = self.employee_contribution_percent + self.employer_contribution_percent;
+        self.annual_salary.unwrap_or(0.0) * percent / 100.0 / 12.0
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetPensionDetailsInput {
+    pub employer: Option<String>,
+    #[serde(default)]
+    pub employee_contribution_percent: f64,
+    #[serde(default)]
+    pub employer_contribution_percent: f64,
+    pub annual_salary: Option<f64>,
+    pub retirement_date: Option<String>,
+}
+
+impl SetPensionDetailsInput {
+    pub fn validate(&self) -> Result<()> {
+        for (field, percent) in [
+            (
+                "employee_contribution_percent",
+                self.employee_contribution_percent,
+            ),
+            (
+                "employer_contribution_percent",
+                self.employer_contribution_percent,
+            ),
+        ] {
+            if !(0.0..=100.0).contains(&percent) {
+                return Err(Error::validation(field, "must be between 0 and 100"));
+            }
+        }
+        if self
+            .annual_salary
+            .is_some_and(|salary| !salary.is_finite() || salary < 0.0)
+        {
+            return Err(Error::validation("annual_salary", "must not be negative"));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PensionProjectionPoint {
+    pub date: String,
+    pub balance: f64,
+    /// Running totals since the latest entry.
+    pub contributions: f64,
+    pub growth: f64,
+}
@@ -0,0 +1,77 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectedPoint {
+    pub date: String,
+    pub balance: f64,
+    /// Interest added since the latest entry, in the same sign as `balance`.
+    pub interest_accrued: f64,
+}
+
+/// One month of paying down a loan.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AmortisationRow {
+    /// Counting from 1.
+    pub month: u32,
+    pub date: String,
+    pub opening_balance: f64,
+    pub interest: f64,
+    pub principal: f64,
+    pub closing_balance: f64,
+}
+
+/// Paying a fixed amount each month until nothing is owed. The last payment
+/// is only what is left.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AmortisationPlan {
+    /// Including any overpayment.
+    pub monthly_payment: f64,
+    pub rows: Vec<AmortisationRow>,
+    pub total_interest: f64,
+    pub total_paid: f64,
+    pub payoff_date: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AmortisationSchedule {
+    pub account_id: String,
+    /// The latest balance entry, which the schedule starts from.
+    pub start_date: String,
+    /// What is owed, as a positive amount.
+    pub balance: f64,
+    /// Annual percentage rate used.
+    pub interest_rate: f64,
+    pub plan: AmortisationPlan,
+    /// The same with the overpayment added to every payment.
+    pub with_overpayment: Option<AmortisationPlan>,
+    pub interest_saved: Option<f64>,
+    pub months_saved: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PayoffStatus {
+    /// `payoff_date` is when the trend reaches zero.
+    Projected,
+    /// Nothing is owed any more.
+    PaidOff,
+    /// The trend is flat or the debt is growing.
+    NotReducing,
+    /// Fewer than three entries to fit a trend to.
+    InsufficientData,
+}
+
+/// When a liability is likely to be paid off, from the trend of its recent
+/// entries rather than from an interest rate.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PayoffProjection {
+    pub account_id: String,
+    pub status: PayoffStatus,
+    pub payoff_date: Option<String>,
+    /// The fitted fall in what is owed a month; `None` without a trend.
+    pub average_monthly_reduction: Option<f64>,
+    /// What is owed at the latest entry, as a positive amount; 0 without entries.
+    pub remaining: f64,
+    /// The entries the trend was fitted to.
+    pub data_points: u32,
+}
@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct PropertyDetails {
+    pub account_id: String,
+    pub purchase_price: f64,
+    pub purchase_date: String,
+    pub deposit_paid: Option<f64>,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetPropertyDetailsInput {
+    pub purchase_price: f64,
+    pub purchase_date: String,
+    pub deposit_paid: Option<f64>,
+}
+
+impl SetPropertyDetailsInput {
+    pub fn validate(&self) -> Result<()> {
+        if !self.purchase_price.is_finite() || self.purchase_price <= 0.0 {
+            return Err(Error::validation(
+                "purchase_price",
+                "must be greater than 0",
+            ));
+        }
+        if let Some(deposit) = self.deposit_paid {
+            if !deposit.is_finite() || deposit < 0.0 {
+                return Err(Error::validation("deposit_paid", "must not be negative"));
+            }
+            if deposit > self.purchase_price {
+                return Err(Error::validation(
+                    "deposit_paid",
+                    "must not exceed the purchase price",
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PropertyPerformance {
+    pub account_id: String,
+    pub purchase_price: f64,
+    pub purchase_date: String,
+    /// The latest balance entry, taken as the current valuation.
+    pub current_value: f64,
+    pub valuation_date: String,
+    pub appreciation: f64,
+    pub appreciation_percent: f64,
+    /// Compound annual growth since purchase; `None` until the valuation postdates it.
+    pub annualised_percent: Option<f64>,
+    /// Outstanding balance of open mortgages linked to the property, if any.
+    pub mortgage_balance: Option<f64>,
+    pub equity: Option<f64>,
+}
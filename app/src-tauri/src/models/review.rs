@@ -0,0 +1,65 @@
+use serde::{Deserialize, Serialize};
+
+use super::{Milestone, NetWorthPoint};
+
+/// Net worth on one end of a review's year.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewEndpoint {
+    pub date: String,
+    pub net_worth: f64,
+}
+
+/// How much one account added to net worth over the year, at its owned share
+/// in the base currency. For a liability the values are amounts owed and a
+/// fall is a gain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewAccountChange {
+    pub account_id: String,
+    pub name: String,
+    /// Zero when it wasn't held then.
+    pub start_value: f64,
+    pub end_value: f64,
+    pub change: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewAccountEvent {
+    pub account_id: String,
+    pub name: String,
+    pub date: String,
+}
+
+/// A financial year at a glance, as `get_year_review` builds it for the
+/// review screen and exports.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct YearReview {
+    /// The calendar year the financial year starts in.
+    pub year: i32,
+    pub start: String,
+    pub end: String,
+    /// True when the year hasn't ended or nothing was recorded by its start;
+    /// `notes` says which.
+    pub partial: bool,
+    pub notes: Vec<String>,
+    /// Net worth the day before the year starts or, failing that, on the first
+    /// month end with balances. `None` with nothing recorded in the year.
+    pub opening: Option<ReviewEndpoint>,
+    /// On the last day of the year, or today while it is still running.
+    pub closing: Option<ReviewEndpoint>,
+    pub change: Option<f64>,
+    /// Net flows into accounts between the endpoints; `None` without flows, in
+    /// which case the change isn't split.
+    pub contributions: Option<f64>,
+    pub growth: Option<f64>,
+    pub biggest_gain: Option<ReviewAccountChange>,
+    pub biggest_loss: Option<ReviewAccountChange>,
+    /// Targets reached in the year, and dated events without a target.
+    pub milestones: Vec<Milestone>,
+    /// Balance entries dated in the year.
+    pub balance_updates: u32,
+    /// Opened on its opening date, or its first entry without one.
+    pub accounts_opened: Vec<ReviewAccountEvent>,
+    pub accounts_closed: Vec<ReviewAccountEvent>,
+    /// Net worth at each month end of the year so far.
+    pub series: Vec<NetWorthPoint>,
+}
@@ -0,0 +1,93 @@
+use serde::{Deserialize, Serialize};
+
+use super::{AccountCategory, Patch};
+
+/// A hypothetical change layered over the real accounts. Amounts are paid
+/// into an account, or for a liability paid off; negative amounts take out or
+/// borrow more.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ScenarioOperation {
+    /// An account that doesn't exist, in the base currency and wholly owned,
+    /// from `start_date` on.
+    AddAccount {
+        name: String,
+        category: AccountCategory,
+        /// What is held, or for a liability owed, on `start_date`.
+        opening_balance: f64,
+        start_date: String,
+        /// Paid in, or off, at each month end after `start_date`.
+        #[serde(default)]
+        monthly_contribution: f64,
+        /// Annual percentage, compounded monthly.
+        interest_rate: Option<f64>,
+    },
+    /// Pays `amount` into an existing account each month on the day of
+    /// `start_date`, until `end_date` if given.
+    MonthlyFlow {
+        account_id: String,
+        amount: f64,
+        start_date: String,
+        end_date: Option<String>,
+    },
+    /// Pays `amount` into an existing account once.
+    LumpSum {
+        account_id: String,
+        amount: f64,
+        date: String,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ScenarioDefinition {
+    /// How many month ends after today the comparison runs to.
+    pub horizon_months: u32,
+    pub operations: Vec<ScenarioOperation>,
+}
+
+/// A saved what-if. Stored apart from the real data, which running it never
+/// changes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Scenario {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(flatten)]
+    pub definition: ScenarioDefinition,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateScenarioInput {
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(flatten)]
+    pub definition: ScenarioDefinition,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateScenarioInput {
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Patch::is_keep")]
+    pub description: Patch<String>,
+    pub horizon_months: Option<u32>,
+    pub operations: Option<Vec<ScenarioOperation>>,
+}
+
+/// Net worth on one date with and without the scenario.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScenarioPoint {
+    pub date: String,
+    pub baseline: f64,
+    pub scenario: f64,
+    /// `scenario` less `baseline`.
+    pub delta: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScenarioResult {
+    /// Today, then each month end to the horizon.
+    pub points: Vec<ScenarioPoint>,
+    pub delta_at_horizon: f64,
+}
@@ -0,0 +1,164 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use super::{validate_currency, DEFAULT_CURRENCY};
+use crate::error::{Error, Result};
+
+/// What happens to a negative balance entered for a liability, which is stored
+/// as the positive amount owed.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SignPolicy {
+    /// Saved as entered.
+    Allow,
+    /// Saved as entered, with a warning.
+    #[default]
+    Warn,
+    /// Saved as the positive amount, with a warning saying so.
+    Normalise,
+}
+
+/// App-wide preferences. Each field is stored as its own row in `settings`, so
+/// fields added later simply start at their default.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// How far ahead of today a balance entry may be dated; none by default.
+    pub max_future_days: u32,
+    /// A new balance this far from the previous entry, in percent of it, and
+    /// by at least `outlier_min_change`, is flagged as a likely typo.
+    pub outlier_percent: f64,
+    pub outlier_min_change: f64,
+    /// Whether flagged balances are refused until resent with `confirm_outlier`,
+    /// rather than saved with a warning.
+    pub confirm_outliers: bool,
+    /// The currency totals across accounts are rounded to.
+    pub base_currency: String,
+    pub sign_policy: SignPolicy,
+    /// Whether `check_and_notify` shows desktop notifications at all.
+    pub notifications_enabled: bool,
+    /// How many days before a milestone's date it is announced as upcoming.
+    pub notify_days_ahead: u32,
+    /// The day each financial year starts, as `MM-DD`; `01-01` for calendar years.
+    pub fiscal_year_start: String,
+    /// The percentage of savings drawn each year in `get_fire_projection`.
+    pub safe_withdrawal_rate: f64,
+    /// Yearly growth after inflation, in percent, that FIRE projections assume.
+    pub expected_real_growth: f64,
+    /// What a typical month costs, in the base currency, for
+    /// `get_emergency_coverage`; zero until set.
+    pub monthly_expenses: f64,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            max_future_days: 0,
+            outlier_percent: 50.0,
+            outlier_min_change: 100.0,
+            confirm_outliers: false,
+            base_currency: DEFAULT_CURRENCY.to_string(),
+            sign_policy: SignPolicy::default(),
+            notifications_enabled: true,
+            notify_days_ahead: 7,
+            fiscal_year_start: "01-01".to_string(),
+            safe_withdrawal_rate: 4.0,
+            expected_real_growth: 5.0,
+            monthly_expenses: 0.0,
+        }
+    }
+}
+
+/// The date `fiscal_year_start`, an `MM-DD`, falls on in `year`.
+pub fn fiscal_year_start_in(fiscal_year_start: &str, year: i32) -> Option<NaiveDate> {
+    let (month, day) = fiscal_year_start.split_once('-')?;
+    if month.len() != 2 || day.len() != 2 {
+        return None;
+    }
+    NaiveDate::from_ymd_opt(year, month.parse().ok()?, day.parse().ok()?)
+}
+
+/// Checks a withdrawal rate is above 0 and at most 100 percent, and a growth
+/// rate above -100 percent.
+pub fn check_fire_rates(safe_withdrawal_rate: Option<f64>, growth: Option<f64>) -> Result<()> {
+    if safe_withdrawal_rate.is_some_and(|rate| !rate.is_finite() || rate <= 0.0 || rate > 100.0) {
+        return Err(Error::validation(
+            "safe_withdrawal_rate",
+            "must be more than 0 and at most 100",
+        ));
+    }
+    if growth.is_some_and(|rate| !rate.is_finite() || rate <= -100.0) {
+        return Err(Error::validation(
+            "expected_real_growth",
+            "must be more than -100",
+        ));
+    }
+    Ok(())
+}
+
+/// Only the fields given are changed.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateSettingsInput {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_future_days: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outlier_percent: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outlier_min_change: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confirm_outliers: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_currency: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sign_policy: Option<SignPolicy>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notifications_enabled: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notify_days_ahead: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fiscal_year_start: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub safe_withdrawal_rate: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_real_growth: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub monthly_expenses: Option<f64>,
+}
+
+impl UpdateSettingsInput {
+    /// Also upper-cases `base_currency`.
+    pub fn validate(&mut self) -> Result<()> {
+        if let Some(code) = &mut self.base_currency {
+            *code = code.trim().to_ascii_uppercase();
+            validate_currency(code)?;
+        }
+        if let Some(start) = &mut self.fiscal_year_start {
+            *start = start.trim().to_string();
+            if fiscal_year_start_in(start, 2023).is_none() {
+                return Err(Error::validation(
+                    "fiscal_year_start",
+                    "must be a month and day as MM-DD, other than 02-29",
+                ));
+            }
+        }
+        for (field, value) in [
+            ("outlier_percent", self.outlier_percent),
+            ("outlier_min_change", self.outlier_min_change),
+            ("monthly_expenses", self.monthly_expenses),
+        ] {
+            if value.is_some_and(|value| !value.is_finite() || value < 0.0) {
+                return Err(Error::validation(field, "must be zero or more"));
+            }
+        }
+        check_fire_rates(self.safe_withdrawal_rate, self.expected_real_growth)?;
+        Ok(())
+    }
+}
+
+/// One row of `settings`, as held in backups. `value` is JSON.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Setting {
+    pub key: String,
+    pub value: String,
+    pub updated_at: String,
+}
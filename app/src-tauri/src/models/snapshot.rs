@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+
+use super::AccountCategory;
+
+/// How an account's existence changed between the two dates of a comparison.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SnapshotPresence {
+    /// Held on both dates.
+    Both,
+    /// Opened, or first recorded, after the first date.
+    Appeared,
+    /// Closed after the first date and on or before the second.
+    Closed,
+}
+
+/// One account's row in a snapshot comparison. Balances are the owned share,
+/// as in net worth, and what is owed counts as a positive amount.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountSnapshotChange {
+    pub account_id: String,
+    pub name: String,
+    pub category: AccountCategory,
+    pub currency: String,
+    pub presence: SnapshotPresence,
+    /// `None` when the account wasn't held on the date: not yet opened, without
+    /// entries so far, or closed before it.
+    pub balance_a: Option<f64>,
+    pub balance_b: Option<f64>,
+    /// Missing balances count as zero.
+    pub change: f64,
+    /// `None` when there was nothing to compare against on the first date.
+    pub change_percent: Option<f64>,
+    /// True when either balance was estimated rather than recorded on the day.
+    pub is_estimated: bool,
+    /// Listed, but left out of the totals.
+    pub exclude_from_net_worth: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategorySnapshotTotal {
+    pub category: AccountCategory,
+    pub total_a: f64,
+    pub total_b: f64,
+    pub change: f64,
+    pub change_percent: Option<f64>,
+}
+
+/// Every account held on either of two dates, side by side, with totals per
+/// category and for net worth in the base currency's precision.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotComparison {
+    pub date_a: String,
+    pub date_b: String,
+    pub accounts: Vec<AccountSnapshotChange>,
+    pub totals: Vec<CategorySnapshotTotal>,
+    pub net_worth_a: f64,
+    pub net_worth_b: f64,
+    pub net_worth_change: f64,
+}
@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+
+use super::Account;
+
+/// An account that hasn't been updated within its reminder frequency, or the
+/// caller's threshold when it has none.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StaleAccount {
+    #[serde(flatten)]
+    pub account: Account,
+    /// `None` when the account has never had a balance entered.
+    pub last_entry_date: Option<String>,
+    pub days_since: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DueUpdate {
+    #[serde(flatten)]
+    pub account: Account,
+    pub last_entry_date: Option<String>,
+    /// When the next entry was due; `None` when there has never been one.
+    pub due_date: Option<String>,
+    /// Whole periods of the account's frequency since its last entry.
+    pub periods_missed: Option<u32>,
+}
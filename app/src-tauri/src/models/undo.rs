@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(rename_all = "snake_case")]
+pub enum UndoOperation {
+    Create,
+    Update,
+    Delete,
+    Import,
+    Adjust,
+}
+
+/// One journalled change to balance entries, undone or redone as a whole.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct UndoStep {
+    pub id: i64,
+    pub operation: UndoOperation,
+    /// What the change did, for labelling undo and redo buttons.
+    pub description: String,
+    pub undone: bool,
+    pub created_at: String,
+}
+
+/// A step left in place because applying it would clobber later changes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedUndo {
+    pub step: UndoStep,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UndoReport {
+    /// Steps applied, in the order they were applied.
+    pub applied: Vec<UndoStep>,
+    pub skipped: Vec<SkippedUndo>,
+}
@@ -0,0 +1,165 @@
+//! Just enough of OFX to read statement balances. Handles both the SGML
+//! flavour (OFX 1.x, where leaf elements are never closed) and the XML one
+//! (OFX 2.x); everything outside bank and card statements is skipped.
+
+use chrono::NaiveDate;
+
+use crate::dates;
+use crate::models::OfxStatement;
+
+/// Where and why a file stopped making sense as OFX.
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("invalid OFX at byte {offset}: {message}")]
+pub struct ParseError {
+    /// Counted from the start of the file.
+    pub offset: usize,
+    pub message: String,
+}
+
+fn error(offset: usize, message: impl Into<String>) -> ParseError {
+    ParseError {
+        offset,
+        message: message.into(),
+    }
+}
+
+fn is_statement(aggregate: &str) -> bool {
+    matches!(aggregate, "STMTRS" | "CCSTMTRS")
+}
+
+/// Undoes the XML escapes that can turn up in values.
+fn unescape(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+fn parse_amount(value: &str, offset: usize) -> Result<f64, ParseError> {
+    // Some banks write a decimal comma.
+    let normalized = if value.contains('.') {
+        value.to_string()
+    } else {
+        value.replace(',', ".")
+    };
+    normalized
+        .parse::<f64>()
+        .ok()
+        .filter(|amount| amount.is_finite())
+        .ok_or_else(|| error(offset, format!("'{value}' is not an amount")))
+}
+
+/// The day of an OFX datetime such as `20240131120000.000[-5:EST]`.
+fn parse_day(value: &str, offset: usize) -> Result<String, ParseError> {
+    value
+        .get(..8)
+        .and_then(|day| NaiveDate::parse_from_str(day, "%Y%m%d").ok())
+        .map(dates::format)
+        .ok_or_else(|| error(offset, format!("'{value}' is not an OFX date")))
+}
+
+fn read_leaf(
+    statement: &mut OfxStatement,
+    parent: &str,
+    name: &str,
+    value: &str,
+    offset: usize,
+) -> Result<(), ParseError> {
+    match (parent, name) {
+        (_, "CURDEF") => statement.currency = Some(value.to_ascii_uppercase()),
+        ("BANKACCTFROM" | "CCACCTFROM", "ACCTID") => statement.account_id = value.to_string(),
+        ("BANKACCTFROM", "BANKID") => statement.bank_id = Some(value.to_string()),
+        ("BANKACCTFROM", "ACCTTYPE") => statement.account_type = Some(value.to_string()),
+        ("LEDGERBAL", "BALAMT") => statement.ledger_balance = Some(parse_amount(value, offset)?),
+        ("LEDGERBAL", "DTASOF") => statement.ledger_date = Some(parse_day(value, offset)?),
+        ("AVAILBAL", "BALAMT") => statement.available_balance = Some(parse_amount(value, offset)?),
+        ("AVAILBAL", "DTASOF") => statement.available_date = Some(parse_day(value, offset)?),
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Every bank or card statement in `text`, in file order.
+pub fn parse(text: &str) -> Result<Vec<OfxStatement>, ParseError> {
+    // Upper-casing ASCII keeps byte offsets the same.
+    let upper = text.to_ascii_uppercase();
+    let Some(start) = upper.find("<OFX>") else {
+        return Err(error(0, "there is no <OFX> element"));
+    };
+
+    let mut statements = Vec::new();
+    let mut current: Option<OfxStatement> = None;
+    let mut open: Vec<String> = Vec::new();
+    let mut last_leaf: Option<String> = None;
+    let mut pos = start;
+
+    while let Some(found) = text[pos..].find('<') {
+        let tag_start = pos + found;
+        let Some(length) = text[tag_start..].find('>') else {
+            return Err(error(tag_start, "the tag is never closed with '>'"));
+        };
+        let tag_end = tag_start + length;
+        let tag = upper[tag_start + 1..tag_end].trim();
+        pos = tag_end + 1;
+        if tag.starts_with('?') || tag.starts_with('!') {
+            continue;
+        }
+
+        if let Some(name) = tag.strip_prefix('/') {
+            let name = name.trim();
+            if open.last().map(String::as_str) == Some(name) {
+                open.pop();
+                if is_statement(name) {
+                    let statement = current.take().expect("opened with the statement");
+                    if statement.account_id.is_empty() {
+                        return Err(error(tag_start, "the statement has no ACCTID"));
+                    }
+                    if statement.ledger_balance.is_none() && statement.available_balance.is_none() {
+                        return Err(error(
+                            tag_start,
+                            "the statement has no LEDGERBAL or AVAILBAL",
+                        ));
+                    }
+                    statements.push(statement);
+                }
+            } else if last_leaf.as_deref() != Some(name) {
+                return Err(error(tag_start, format!("unexpected </{name}>")));
+            }
+            last_leaf = None;
+            continue;
+        }
+
+        if tag.is_empty() || tag.contains(char::is_whitespace) {
+            return Err(error(tag_start, format!("'<{tag}>' is not an OFX tag")));
+        }
+        let value_end = text[pos..].find('<').map_or(text.len(), |next| pos + next);
+        let raw = &text[pos..value_end];
+        let value = raw.trim();
+        let value_offset = pos + (raw.len() - raw.trim_start().len());
+        if value.is_empty() {
+            if is_statement(tag) {
+                if current.is_some() {
+                    return Err(error(tag_start, "a statement starts inside another"));
+                }
+                current = Some(OfxStatement {
+                    account_type: (tag == "CCSTMTRS").then(|| "CREDITCARD".to_string()),
+                    ..OfxStatement::default()
+                });
+            }
+            open.push(tag.to_string());
+            last_leaf = None;
+        } else {
+            if let (Some(statement), Some(parent)) = (current.as_mut(), open.last()) {
+                read_leaf(statement, parent, tag, &unescape(value), value_offset)?;
+            }
+            last_leaf = Some(tag.to_string());
+        }
+    }
+
+    if let Some(unclosed) = open.last() {
+        return Err(error(text.len(), format!("<{unclosed}> is never closed")));
+    }
+    Ok(statements)
+}
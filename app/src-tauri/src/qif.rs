@@ -0,0 +1,145 @@
+//! A reader for the transaction records of QIF files: one field per line, keyed
+//! by its first character (`D` date, `T` amount, `P` payee, `M` memo), with `^`
+//! ending each record. Dates are left as written, since whether `03/04/24` is
+//! day- or month-first can only be decided across the whole file.
+
+use chrono::NaiveDate;
+
+use crate::models::QifDateOrder;
+
+/// A transaction as written, and the line its record started on, counting from 1.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Transaction {
+    pub line: usize,
+    pub date: Option<String>,
+    pub amount: Option<String>,
+    pub payee: Option<String>,
+    pub memo: Option<String>,
+}
+
+/// The `!Type:` sections that hold plain transactions.
+const CASH_TYPES: &[&str] = &["bank", "cash", "ccard", "oth a", "oth l"];
+
+/// Every transaction in `text`. Account lists, categories and classes are
+/// skipped; other sections, such as investments whose records mean something
+/// else, are refused.
+pub fn parse(text: &str) -> Result<Vec<Transaction>, String> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let mut transactions = Vec::new();
+    let mut current = Transaction::default();
+    let mut in_transactions = false;
+    let mut started = false;
+
+    for (index, line) in text.lines().enumerate() {
+        let number = index + 1;
+        let line = line.trim_end();
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(header) = line.strip_prefix('!') {
+            let header = header.trim().to_ascii_lowercase();
+            if let Some(kind) = header.strip_prefix("type:") {
+                let kind = kind.trim();
+                if !CASH_TYPES.contains(&kind) && !matches!(kind, "cat" | "class" | "memorized") {
+                    return Err(format!(
+                        "line {number}: !{} sections aren't supported",
+                        &line[1..]
+                    ));
+                }
+                in_transactions = CASH_TYPES.contains(&kind);
+            } else if header == "account" {
+                in_transactions = false;
+            }
+            continue;
+        }
+        if !in_transactions {
+            continue;
+        }
+
+        let (code, value) = line.split_at(line.chars().next().map_or(0, char::len_utf8));
+        let value = value.trim();
+        if !started {
+            current.line = number;
+            started = true;
+        }
+        let field = || (!value.is_empty()).then(|| value.to_string());
+        match code {
+            "^" => {
+                transactions.push(std::mem::take(&mut current));
+                started = false;
+            }
+            "D" => current.date = field(),
+            // `U` repeats the amount in newer files.
+            "T" => current.amount = field(),
+            "U" if current.amount.is_none() => current.amount = field(),
+            "P" => current.payee = field(),
+            "M" => current.memo = field(),
+            _ => {}
+        }
+    }
+    if started {
+        return Err(format!(
+            "the record starting on line {} has no closing '^'",
+            current.line
+        ));
+    }
+    Ok(transactions)
+}
+
+/// The numbers of a date such as `31/01/2024`, `1/31'24` or `01-31-99`, as
+/// first, second and four-digit year. An apostrophe before the year marks the
+/// 2000s; two-digit years otherwise pivot at 70.
+fn date_parts(text: &str) -> Option<(u32, u32, i32)> {
+    let text: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    let apostrophe = text.contains('\'');
+    let mut parts = text.split(['/', '-', '.', '\'']);
+    let (first, second, year) = (parts.next()?, parts.next()?, parts.next()?);
+    if parts.next().is_some() {
+        return None;
+    }
+    let first = first.parse().ok()?;
+    let second = second.parse().ok()?;
+    let year = match (year.len(), year.parse::<i32>().ok()?) {
+        (4, year) => year,
+        (1 | 2, year) if apostrophe || year < 70 => 2000 + year,
+        (2, year) => 1900 + year,
+        _ => return None,
+    };
+    Some((first, second, year))
+}
+
+/// The order the file's dates must be in: `Some` when at least one date only
+/// makes sense one way round and none only the other, `None` when every date
+/// reads both ways. Fails when the dates contradict each other.
+pub fn detect_date_order<'a>(
+    dates: impl IntoIterator<Item = &'a str>,
+) -> Result<Option<QifDateOrder>, String> {
+    let (mut day_first, mut month_first) = (None, None);
+    for date in dates {
+        let Some((first, second, _)) = date_parts(date) else {
+            continue;
+        };
+        if first > 12 && second <= 12 {
+            day_first.get_or_insert(date);
+        } else if second > 12 && first <= 12 {
+            month_first.get_or_insert(date);
+        }
+    }
+    match (day_first, month_first) {
+        (Some(day), Some(month)) => {
+            Err(format!("'{day}' is day-first but '{month}' is month-first"))
+        }
+        (Some(_), None) => Ok(Some(QifDateOrder::DayFirst)),
+        (None, Some(_)) => Ok(Some(QifDateOrder::MonthFirst)),
+        (None, None) => Ok(None),
+    }
+}
+
+pub fn parse_date(text: &str, order: QifDateOrder) -> Option<NaiveDate> {
+    let (first, second, year) = date_parts(text)?;
+    let (day, month) = match order {
+        QifDateOrder::DayFirst => (first, second),
+        QifDateOrder::MonthFirst => (second, first),
+    };
+    NaiveDate::from_ymd_opt(year, month, day)
+}
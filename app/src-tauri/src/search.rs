@@ -0,0 +1,87 @@
+//! Pure helpers for loose text matching in account search, and for turning
+//! typed searches into full-text queries.
+
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+/// Lower-cases `text` and strips accents, so "Société" matches "societe".
+pub fn fold(text: &str) -> String {
+    text.nfd()
+        .filter(|c| !is_combining_mark(*c))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// How well folded `query` matches folded `text`, from 0 to 1, or `None` when it
+/// doesn't. Substrings score highest, earlier ones more so; otherwise the query's
+/// characters must appear in order, scoring higher the closer together they are.
+pub fn score(query: &str, text: &str) -> Option<f64> {
+    if query.is_empty() {
+        return Some(0.0);
+    }
+    let text: Vec<char> = text.chars().collect();
+    let query: Vec<char> = query.chars().collect();
+
+    if let Some(start) = text.windows(query.len()).position(|window| window == query) {
+        return Some(1.0 - 0.5 * start as f64 / text.len() as f64);
+    }
+
+    let mut matched = 0;
+    let mut first = None;
+    let mut last = 0;
+    for (i, c) in text.iter().enumerate() {
+        if *c == query[matched] {
+            first.get_or_insert(i);
+            last = i;
+            matched += 1;
+            if matched == query.len() {
+                break;
+            }
+        }
+    }
+    if matched < query.len() {
+        return None;
+    }
+    let span = (last - first.unwrap_or(0) + 1) as f64;
+    Some(0.5 * query.len() as f64 / span)
+}
+
+/// An FTS5 query for `input`: every word must match, `"quoted words"` as a
+/// phrase and `word*` as a prefix. Everything else is quoted, so operators and
+/// punctuation can't make the query invalid. `None` when nothing is searchable.
+pub fn fts_query(input: &str) -> Option<String> {
+    let mut terms = Vec::new();
+    let mut rest = input;
+    while let Some(start) = rest.find('"') {
+        terms.extend(words(&rest[..start]));
+        let after = &rest[start + 1..];
+        let (phrase, next) = after.split_once('"').unwrap_or((after, ""));
+        if !phrase.trim().is_empty() {
+            terms.push(quote(phrase.trim()));
+        }
+        rest = next;
+    }
+    terms.extend(words(rest));
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split_whitespace().filter_map(|word| {
+        let (word, prefix) = match word.strip_suffix('*') {
+            Some(word) => (word.trim_end_matches('*'), true),
+            None => (word, false),
+        };
+        if !word.chars().any(char::is_alphanumeric) {
+            return None;
+        }
+        Some(if prefix {
+            format!("{}*", quote(word))
+        } else {
+            quote(word)
+        })
+    })
+}
+
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('"', "\"\""))
+}
@@ -0,0 +1,301 @@
+//! Pure helpers for turning sparse balance entries into regular series.
+
+use chrono::{Datelike, Days, Months, NaiveDate};
+
+use crate::error::{Error, Result};
+use crate::models::{FillMethod, Granularity, Smoothed, SmoothingMethod, SmoothingSpec};
+
+/// A known balance on a date. Slices of points are expected in ascending date order.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Point {
+    pub date: NaiveDate,
+    pub balance: f64,
+}
+
+/// The balance on `date`, linearly interpolated between the entries either side
+/// and carried forward after the last one. `None` before the first entry.
+pub fn interpolate_linear(points: &[Point], date: NaiveDate) -> Option<f64> {
+    let after = points.partition_point(|p| p.date <= date);
+    if after == 0 {
+        return None;
+    }
+    let before = points[after - 1];
+    let Some(next) = points.get(after) else {
+        return Some(before.balance);
+    };
+    if before.date == date {
+        return Some(before.balance);
+    }
+
+    let span = (next.date - before.date).num_days() as f64;
+    let elapsed = (date - before.date).num_days() as f64;
+    Some(before.balance + (next.balance - before.balance) * elapsed / span)
+}
+
+/// Like [`interpolate_linear`], but `None` after `closed_at`: a closed account
+/// holds its final balance up to the closing date and nothing afterwards.
+pub fn interpolate_until(
+    points: &[Point],
+    date: NaiveDate,
+    closed_at: Option<NaiveDate>,
+) -> Option<f64> {
+    if closed_at.is_some_and(|closed| date > closed) {
+        return None;
+    }
+    interpolate_linear(points, date)
+}
+
+pub fn month_end(date: NaiveDate) -> NaiveDate {
+    let first = date.with_day(1).expect("every month has a first day");
+    first + Months::new(1) - Days::new(1)
+}
+
+/// The `day` of `date`'s month, or its last day when the month is too short.
+fn day_in_month(date: NaiveDate, day: u32) -> NaiveDate {
+    date.with_day(day.min(month_end(date).day()))
+        .expect("clamped to the month's last day")
+}
+
+/// The `day` of every month between `start` and `end` inclusive, taking the
+/// last day of months too short to have it.
+pub fn monthly_on_day(start: NaiveDate, end: NaiveDate, day: u32) -> Vec<NaiveDate> {
+    let mut dates = Vec::new();
+    let mut month_start = start.with_day(1).expect("every month has a first day");
+    while month_start <= end {
+        let date = day_in_month(month_start, day);
+        if date >= start && date <= end {
+            dates.push(date);
+        }
+        month_start = month_start + Months::new(1);
+    }
+    dates
+}
+
+/// `start`'s day and month in every year up to `end`, from `start` itself on. A
+/// 29 February falls on the 28th in other years.
+pub fn yearly_on_day(start: NaiveDate, end: NaiveDate) -> Vec<NaiveDate> {
+    (start.year()..=end.year())
+        .filter_map(|year| NaiveDate::from_ymd_opt(year, start.month(), 1))
+        .map(|month_start| day_in_month(month_start, start.day()))
+        .filter(|date| *date <= end)
+        .collect()
+}
+
+/// The monthly periods, as first and last day, that overlap `start` to `end`.
+/// Each closes on `closing_day` (31 for calendar months) and opens the day
+/// after the previous one closed.
+pub fn monthly_periods(
+    start: NaiveDate,
+    end: NaiveDate,
+    closing_day: u32,
+) -> Vec<(NaiveDate, NaiveDate)> {
+    let mut closings = monthly_on_day(start, end + Months::new(1), closing_day);
+    if let Some(last) = closings.iter().position(|closing| *closing >= end) {
+        closings.truncate(last + 1);
+    }
+    closings
+        .into_iter()
+        .map(|closing| {
+            let previous = day_in_month(closing - Months::new(1), closing_day);
+            (previous + Days::new(1), closing)
+        })
+        .collect()
+}
+
+/// Every month end from `start`'s month up to `end`, followed by `end` itself
+/// when it falls mid-month.
+pub fn month_ends(start: NaiveDate, end: NaiveDate) -> Vec<NaiveDate> {
+    let mut dates = Vec::new();
+    let mut current = month_end(start);
+    while current <= end {
+        dates.push(current);
+        current = month_end(current + Days::new(1));
+    }
+    if start <= end && dates.last() != Some(&end) {
+        dates.push(end);
+    }
+    dates
+}
+
+/// The dates from `start` to `end` at `granularity`. Weekly and monthly steps
+/// finish with `end` itself when it falls between them.
+pub fn period_dates(start: NaiveDate, end: NaiveDate, granularity: Granularity) -> Vec<NaiveDate> {
+    let step = match granularity {
+        Granularity::Month => return month_ends(start, end),
+        Granularity::Day => 1,
+        Granularity::Week => 7,
+    };
+    let mut dates: Vec<NaiveDate> = start
+        .iter_days()
+        .step_by(step)
+        .take_while(|date| *date <= end)
+        .collect();
+    if start <= end && dates.last() != Some(&end) {
+        dates.push(end);
+    }
+    dates
+}
+
+/// A balance on one of the requested dates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sample {
+    pub date: NaiveDate,
+    pub balance: f64,
+    /// False when a point falls exactly on the date.
+    pub estimated: bool,
+}
+
+/// The balance on each of `dates`, which must be ascending, with gaps filled by
+/// `method` and the last balance carried forward after the final point. Dates
+/// before the first point are left out. Walks both slices once, so long daily
+/// ranges stay cheap.
+pub fn resample(points: &[Point], dates: &[NaiveDate], method: FillMethod) -> Vec<Sample> {
+    let mut samples = Vec::with_capacity(dates.len());
+    let mut after = 0;
+    for &date in dates {
+        while after < points.len() && points[after].date <= date {
+            after += 1;
+        }
+        let Some(before) = after.checked_sub(1).map(|i| points[i]) else {
+            continue;
+        };
+        if before.date == date {
+            samples.push(Sample {
+                date,
+                balance: before.balance,
+                estimated: false,
+            });
+            continue;
+        }
+        let balance = match (method, points.get(after)) {
+            (FillMethod::Linear, Some(next)) => {
+                let span = (next.date - before.date).num_days() as f64;
+                let elapsed = (date - before.date).num_days() as f64;
+                before.balance + (next.balance - before.balance) * elapsed / span
+            }
+            _ => before.balance,
+        };
+        samples.push(Sample {
+            date,
+            balance,
+            estimated: true,
+        });
+    }
+    samples
+}
+
+pub const DAYS_PER_MONTH: f64 = 365.25 / 12.0;
+
+/// A least-squares line through values against days since the first date.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LinearFit {
+    /// Per day.
+    pub slope: f64,
+    /// The fitted value on the first date.
+    pub intercept: f64,
+    /// 1 for a perfect fit; 0 when the values are all the same.
+    pub r_squared: f64,
+}
+
+impl LinearFit {
+    pub fn value_after(&self, days: f64) -> f64 {
+        self.intercept + self.slope * days
+    }
+}
+
+/// The least-squares line through `values`, which are in date order. Flat when
+/// they are all on one date or there are none.
+pub fn linear_fit(values: &[(NaiveDate, f64)]) -> LinearFit {
+    let Some((first, _)) = values.first() else {
+        return LinearFit {
+            slope: 0.0,
+            intercept: 0.0,
+            r_squared: 0.0,
+        };
+    };
+    let xs: Vec<f64> = values
+        .iter()
+        .map(|(date, _)| (*date - *first).num_days() as f64)
+        .collect();
+    let n = values.len() as f64;
+    let mean_x = xs.iter().sum::<f64>() / n;
+    let mean_y = values.iter().map(|(_, value)| value).sum::<f64>() / n;
+    let (mut covariance, mut variance) = (0.0, 0.0);
+    for (x, (_, y)) in xs.iter().zip(values) {
+        covariance += (x - mean_x) * (y - mean_y);
+        variance += (x - mean_x) * (x - mean_x);
+    }
+    let slope = if variance == 0.0 {
+        0.0
+    } else {
+        covariance / variance
+    };
+    let fit = LinearFit {
+        slope,
+        intercept: mean_y - slope * mean_x,
+        r_squared: 0.0,
+    };
+    LinearFit {
+        r_squared: r_squared(
+            values
+                .iter()
+                .zip(&xs)
+                .map(|((_, actual), x)| (*actual, fit.value_after(*x))),
+        ),
+        ..fit
+    }
+}
+
+/// The share of the variance in the actual values that the fitted ones
+/// explain, from pairs of actual and fitted; 0 when the actual values don't vary.
+pub fn r_squared(pairs: impl IntoIterator<Item = (f64, f64)> + Clone) -> f64 {
+    let (sum, count) = pairs
+        .clone()
+        .into_iter()
+        .fold((0.0, 0.0), |(sum, count), (actual, _)| {
+            (sum + actual, count + 1.0)
+        });
+    if count == 0.0 {
+        return 0.0;
+    }
+    let mean = sum / count;
+    let (mut residual, mut total) = (0.0, 0.0);
+    for (actual, fitted) in pairs {
+        residual += (actual - fitted).powi(2);
+        total += (actual - mean).powi(2);
+    }
+    if total == 0.0 {
+        0.0
+    } else {
+        1.0 - residual / total
+    }
+}
+
+/// The moving average of `values` at each of them. The first `window - 1` take
+/// what there is so far and are marked partial, so the result is as long as
+/// `values`.
+pub fn smooth(values: &[f64], spec: SmoothingSpec) -> Result<Vec<Smoothed>> {
+    if spec.window == 0 {
+        return Err(Error::validation("window", "must be at least 1"));
+    }
+    let window = spec.window as usize;
+    let alpha = 2.0 / (spec.window as f64 + 1.0);
+    let mut smoothed: Vec<Smoothed> = Vec::with_capacity(values.len());
+    for (index, value) in values.iter().enumerate() {
+        let average = match spec.method {
+            SmoothingMethod::Simple => {
+                let recent = &values[(index + 1).saturating_sub(window)..=index];
+                recent.iter().sum::<f64>() / recent.len() as f64
+            }
+            SmoothingMethod::Exponential => match smoothed.last() {
+                Some(previous) => alpha * value + (1.0 - alpha) * previous.value,
+                None => *value,
+            },
+        };
+        smoothed.push(Smoothed {
+            value: average,
+            partial: index + 1 < window,
+        });
+    }
+    Ok(smoothed)
+}
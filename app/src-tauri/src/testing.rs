@@ -0,0 +1,159 @@
+//! A throwaway database for tests that go through the commands, behind a mock
+//! app so they can take the pool as `State` the way the frontend calls them.
+
+use std::path::PathBuf;
+
+use chrono::{Days, NaiveDate};
+use serde_json::json;
+use sqlx::SqlitePool;
+use tauri::test::{mock_app, MockRuntime};
+use tauri::{App, Manager, State};
+use uuid::Uuid;
+
+use crate::commands::accounts::{close_account, create_account};
+use crate::commands::balances::{create_balance_entry, insert_balance_entry, new_balance_entry};
+use crate::commands::exchange_rates::set_exchange_rate;
+use crate::models::Account;
+use crate::{dates, db};
+
+pub(crate) struct TestDb {
+    app: App<MockRuntime>,
+    path: PathBuf,
+}
+
+impl TestDb {
+    /// A new, fully migrated database in the temp directory, deleted on drop.
+    pub(crate) fn new() -> Self {
+        let path = std::env::temp_dir().join(format!("tally-test-{}.db", Uuid::new_v4()));
+        let (pool, _) = block_on(db::connect(&path)).expect("test database opens");
+        let app = mock_app();
+        app.manage(pool);
+        TestDb { app, path }
+    }
+
+    pub(crate) fn state(&self) -> State<'_, SqlitePool> {
+        self.app.state()
+    }
+
+    pub(crate) fn pool(&self) -> &SqlitePool {
+        self.state().inner()
+    }
+}
+
+impl Drop for TestDb {
+    fn drop(&mut self) {
+        block_on(self.pool().close());
+        for suffix in ["", "-wal", "-shm"] {
+            let mut path = self.path.clone().into_os_string();
+            path.push(suffix);
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+pub(crate) fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    tauri::async_runtime::block_on(future)
+}
+
+/// A command input written as JSON, the way the frontend sends it, so tests
+/// only spell out the fields they care about.
+pub(crate) fn input<T: serde::de::DeserializeOwned>(value: serde_json::Value) -> T {
+    serde_json::from_value(value).expect("test input deserializes")
+}
+
+/// A temp file path for a test to write to; the caller removes it.
+pub(crate) fn temp_path(extension: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("tally-test-{}.{extension}", Uuid::new_v4()))
+}
+
+/// The accounts of `seed_household`.
+pub(crate) struct Household {
+    pub current: Account,
+}
+
+/// Accounts of each sort net worth treats differently, with entries through
+/// the first five months of 2025 and the base currency left at GBP:
+///
+/// - `current`: 1,000 on 1 January rising to 1,600 on 31 March.
+/// - `brokerage`: in USD at 0.8 to the pound, opened on 15 March with 2,000 and
+///   at 2,500 on 31 May.
+/// - `mortgage`: half owned, 200,000 on 1 January down to 190,000 on 31 May.
+/// - `old_savings`: 500 from 1 January until it closed on 30 April.
+pub(crate) async fn seed_household(db: &TestDb) -> Household {
+    let account = |value: serde_json::Value| async {
+        create_account(db.state(), input(value)).await.unwrap()
+    };
+    let entry = |account: &Account, date: &str, balance: f64| {
+        let value = json!({"account_id": account.id, "date": date, "balance": balance});
+        async {
+            create_balance_entry(db.state(), input(value))
+                .await
+                .unwrap()
+        }
+    };
+
+    set_exchange_rate(
+        db.state(),
+        "2025-01-01".into(),
+        "USD".into(),
+        "GBP".into(),
+        0.8,
+    )
+    .await
+    .unwrap();
+    let current = account(json!({
+        "name": "Current",
+        "account_type": "savings",
+        "opening_balance": 1000,
+        "opened_at": "2025-01-01",
+    }))
+    .await;
+    entry(&current, "2025-03-31", 1600.0).await;
+    let brokerage = account(json!({
+        "name": "Brokerage",
+        "account_type": "investment",
+        "currency": "USD",
+        "opening_balance": 2000,
+        "opened_at": "2025-03-15",
+    }))
+    .await;
+    entry(&brokerage, "2025-05-31", 2500.0).await;
+    let mortgage = account(json!({
+        "name": "Mortgage",
+        "account_type": "mortgage",
+        "ownership_percent": 50,
+        "opening_balance": 200_000,
+        "opened_at": "2025-01-01",
+    }))
+    .await;
+    entry(&mortgage, "2025-05-31", 190_000.0).await;
+    let old_savings = account(json!({
+        "name": "Old savings",
+        "account_type": "savings",
+        "opening_balance": 500,
+        "opened_at": "2025-01-01",
+    }))
+    .await;
+    close_account(db.state(), old_savings.id, "2025-04-30".into(), Some(500.0))
+        .await
+        .unwrap();
+
+    Household { current }
+}
+
+/// `count` entries for `account`, one a day up to `last` and each a pound more
+/// than the one before, written straight to the table in one transaction.
+pub(crate) async fn seed_daily_entries(
+    db: &TestDb,
+    account: &Account,
+    last: NaiveDate,
+    count: u64,
+) {
+    let mut tx = db.pool().begin().await.unwrap();
+    for days_back in 0..count {
+        let date = dates::format(last - Days::new(days_back));
+        let entry = new_balance_entry(account, date, (count - days_back) as f64, None);
+        insert_balance_entry(&mut *tx, &entry).await.unwrap();
+    }
+    tx.commit().await.unwrap();
+}
@@ -1,154 +1,155 @@
-import { getDb } from './db';
+import { invoke } from '@tauri-apps/api/core';
 import type {
   Account,
+  AccountFieldChange,
+  AccountIssue,
+  AccountType,
   AccountWithBalance,
   CreateAccountInput,
+  CreditUtilisationReport,
+  Currency,
+  CurrencyFormat,
+  DueUpdate,
+  MergeStrategy,
+  MergeSummary,
+  StaleAccount,
   UpdateAccountInput,
 } from '$lib/types';
-import { getCategoryForType } from '$lib/types';
 
-function generateId(): string {
-  return crypto.randomUUID();
+export async function getAccounts(includeArchived = false): Promise<Account[]> {
+  return invoke<Account[]>('get_accounts', { includeArchived });
 }
 
-function now(): string {
-  return new Date().toISOString();
+export async function searchAccounts(
+  query: string,
+  includeArchived = false
+): Promise<AccountWithBalance[]> {
+  return invoke<AccountWithBalance[]>('search_accounts', { query, includeArchived });
 }
 
-export async function getAccounts(): Promise<Account[]> {
-  const db = await getDb();
-  const rows = await db.select<Account[]>(
-    'SELECT * FROM accounts WHERE is_active = 1 ORDER BY category, account_type, name'
-  );
-  return rows.map((row) => ({
-    ...row,
-    is_active: Boolean(row.is_active),
-  }));
+export async function getAccount(id: string, includeTags = false): Promise<Account> {
+  return invoke<Account>('get_account', { id, includeTags });
 }
 
-export async function getAccount(id: string): Promise<Account | null> {
-  const db = await getDb();
-  const rows = await db.select<Account[]>('SELECT * FROM accounts WHERE id = ?', [id]);
-  if (rows.length === 0) return null;
-  return {
-    ...rows[0],
-    is_active: Boolean(rows[0].is_active),
-  };
+export async function createAccount(input: CreateAccountInput): Promise<Account> {
+  return invoke<Account>('create_account', { input });
 }
 
-export async function createAccount(input: CreateAccountInput): Promise<Account> {
-  const db = await getDb();
-  const id = generateId();
-  const timestamp = now();
-  const category = getCategoryForType(input.account_type);
-  const currency = input.currency || 'GBP';
-
-  await db.execute(
-    `INSERT INTO accounts (id, name, account_type, category, institution, description, currency, is_active, created_at, updated_at)
-     VALUES (?, ?, ?, ?, ?, ?, ?, 1, ?, ?)`,
-    [
-      id,
-      input.name,
-      input.account_type,
-      category,
-      input.institution || null,
-      input.description || null,
-      currency,
-      timestamp,
-      timestamp,
-    ]
-  );
-
-  return {
-    id,
-    name: input.name,
-    account_type: input.account_type,
-    category,
-    institution: input.institution || null,
-    description: input.description || null,
-    currency,
-    is_active: true,
-    created_at: timestamp,
-    updated_at: timestamp,
-  };
-}
-
-export async function updateAccount(id: string, input: UpdateAccountInput): Promise<Account | null> {
-  const db = await getDb();
-  const existing = await getAccount(id);
-  if (!existing) return null;
-
-  const updates: string[] = [];
-  const values: (string | number | null)[] = [];
-
-  if (input.name !== undefined) {
-    updates.push('name = ?');
-    values.push(input.name);
-  }
-  if (input.institution !== undefined) {
-    updates.push('institution = ?');
-    values.push(input.institution);
-  }
-  if (input.description !== undefined) {
-    updates.push('description = ?');
-    values.push(input.description);
-  }
-  if (input.currency !== undefined) {
-    updates.push('currency = ?');
-    values.push(input.currency);
-  }
-  if (input.is_active !== undefined) {
-    updates.push('is_active = ?');
-    values.push(input.is_active ? 1 : 0);
-  }
-
-  if (updates.length === 0) return existing;
-
-  const timestamp = now();
-  updates.push('updated_at = ?');
-  values.push(timestamp);
-  values.push(id);
-
-  await db.execute(`UPDATE accounts SET ${updates.join(', ')} WHERE id = ?`, values);
-
-  return getAccount(id);
-}
-
-export async function deleteAccount(id: string): Promise<void> {
-  const db = await getDb();
-  // Delete balance entries first (SQLite foreign keys not enabled by default)
-  await db.execute('DELETE FROM balance_entries WHERE account_id = ?', [id]);
-  await db.execute('DELETE FROM accounts WHERE id = ?', [id]);
-}
-
-export async function getAccountsWithBalances(): Promise<AccountWithBalance[]> {
-  const db = await getDb();
-
-  const rows = await db.select<
-    (Account & { current_balance: number | null; balance_date: string | null })[]
-  >(
-    `SELECT
-      a.*,
-      b.balance as current_balance,
-      b.date as balance_date
-    FROM accounts a
-    LEFT JOIN (
-      SELECT account_id, balance, date
-      FROM balance_entries
-      WHERE (account_id, date) IN (
-        SELECT account_id, MAX(date)
-        FROM balance_entries
-        GROUP BY account_id
-      )
-    ) b ON a.id = b.account_id
-    WHERE a.is_active = 1
-    ORDER BY a.category, a.account_type, a.name`
-  );
-
-  return rows.map((row) => ({
-    ...row,
-    is_active: Boolean(row.is_active),
-    current_balance: row.current_balance ?? 0,
-    balance_date: row.balance_date,
-  }));
+// Copies type, institution, currency and description but no history; with
+// `copyLatestBalance` the new account opens today at the source's latest balance.
+export async function duplicateAccount(
+  accountId: string,
+  newName: string,
+  copyLatestBalance = false
+): Promise<Account> {
+  return invoke<Account>('duplicate_account', { accountId, newName, copyLatestBalance });
+}
+
+export async function updateAccount(id: string, input: UpdateAccountInput): Promise<Account> {
+  return invoke<Account>('update_account', { id, input });
+}
+
+// The percentage of a jointly held account that counts towards net worth, from 0 to 100.
+export async function setOwnership(accountId: string, ownershipPercent: number): Promise<Account> {
+  return invoke<Account>('set_ownership', { accountId, ownershipPercent });
+}
+
+// Clears the custom colour and icon so the account type's defaults apply again.
+export async function resetAccountAppearance(accountId: string): Promise<Account> {
+  return invoke<Account>('reset_account_appearance', { accountId });
+}
+
+export async function getAccountHistory(accountId: string): Promise<AccountFieldChange[]> {
+  return invoke<AccountFieldChange[]>('get_account_history', { accountId });
+}
+
+export async function getCreditUtilisation(): Promise<CreditUtilisationReport> {
+  return invoke<CreditUtilisationReport>('get_credit_utilisation');
+}
+
+// Accounts whose latest entry is older than `maxAgeDays`, or that have none.
+export async function getStaleAccounts(maxAgeDays: number): Promise<StaleAccount[]> {
+  return invoke<StaleAccount[]>('get_stale_accounts', { maxAgeDays });
+}
+
+// Switching between asset and liability types requires `negateBalances`.
+export async function changeAccountType(
+  accountId: string,
+  newType: AccountType,
+  negateBalances = false
+): Promise<Account> {
+  return invoke<Account>('change_account_type', { accountId, newType, negateBalances });
+}
+
+export async function findAccountByExternalRef(externalRef: string): Promise<Account | null> {
+  return invoke<Account | null>('find_account_by_external_ref', { externalRef });
+}
+
+// Pass null to clear; fails if another account already uses the reference.
+export async function setAccountExternalRef(
+  accountId: string,
+  externalRef: string | null
+): Promise<Account> {
+  return invoke<Account>('set_account_external_ref', { accountId, externalRef });
+}
+
+export async function getDueUpdates(asOf?: string): Promise<DueUpdate[]> {
+  return invoke<DueUpdate[]>('get_due_updates', { asOf });
+}
+
+export async function getSupportedCurrencies(): Promise<Currency[]> {
+  return invoke<Currency[]>('get_supported_currencies');
+}
+
+export async function getCurrencyFormat(currency: string): Promise<CurrencyFormat> {
+  return invoke<CurrencyFormat>('get_currency_format', { currency });
+}
+
+export async function validateAccounts(): Promise<AccountIssue[]> {
+  return invoke<AccountIssue[]>('validate_accounts');
+}
+
+export async function reorderAccounts(orderedIds: string[]): Promise<Account[]> {
+  return invoke<Account[]>('reorder_accounts', { orderedIds });
+}
+
+export async function archiveAccount(id: string): Promise<Account> {
+  return invoke<Account>('archive_account', { id });
+}
+
+export async function unarchiveAccount(id: string): Promise<Account> {
+  return invoke<Account>('unarchive_account', { id });
+}
+
+export async function closeAccount(
+  accountId: string,
+  closedAt: string,
+  finalBalance?: number
+): Promise<Account> {
+  return invoke<Account>('close_account', { accountId, closedAt, finalBalance });
+}
+
+export async function reopenAccount(accountId: string): Promise<Account> {
+  return invoke<Account>('reopen_account', { accountId });
+}
+
+// Refuses unless the account is archived, or `force` is set.
+export async function deleteAccount(id: string, force = false): Promise<void> {
+  await invoke('delete_account', { id, force });
+}
+
+// Moves the source's history onto the target and deletes the source. Accounts of
+// different categories are only merged with `force`; different currencies never are.
+export async function mergeAccounts(
+  sourceId: string,
+  targetId: string,
+  strategy: MergeStrategy,
+  force = false
+): Promise<MergeSummary> {
+  return invoke<MergeSummary>('merge_accounts', { sourceId, targetId, strategy, force });
+}
+
+// Balances are the latest entry on or before `asOf` (today by default).
+export async function getAccountsWithBalances(asOf?: string): Promise<AccountWithBalance[]> {
+  return invoke<AccountWithBalance[]>('get_accounts_with_balance', { asOf });
 }
@@ -0,0 +1,44 @@
+import { invoke } from '@tauri-apps/api/core';
+import type {
+  AppliedAdjustments,
+  BulkAdjustment,
+  BulkAdjustmentResult,
+  CreateAdjustmentInput,
+  RecurringAdjustment,
+  UpdateAdjustmentInput,
+} from '$lib/types';
+
+export async function createRecurringAdjustment(
+  input: CreateAdjustmentInput
+): Promise<RecurringAdjustment> {
+  return invoke<RecurringAdjustment>('create_recurring_adjustment', { input });
+}
+
+export async function getRecurringAdjustments(accountId?: string): Promise<RecurringAdjustment[]> {
+  return invoke<RecurringAdjustment[]>('get_recurring_adjustments', { accountId });
+}
+
+export async function updateRecurringAdjustment(
+  id: string,
+  input: UpdateAdjustmentInput
+): Promise<RecurringAdjustment> {
+  return invoke<RecurringAdjustment>('update_recurring_adjustment', { id, input });
+}
+
+export async function deleteRecurringAdjustment(id: string): Promise<void> {
+  await invoke('delete_recurring_adjustment', { id });
+}
+
+// Safe to call on every launch: dates already generated or covered by an entry are skipped.
+export async function applyDueAdjustments(asOf?: string): Promise<AppliedAdjustments> {
+  return invoke<AppliedAdjustments>('apply_due_adjustments', { asOf });
+}
+
+// Accounts whose latest balance is already on or after date are skipped.
+export async function applyBulkAdjustment(
+  accountIds: string[],
+  date: string,
+  adjustment: BulkAdjustment
+): Promise<BulkAdjustmentResult> {
+  return invoke<BulkAdjustmentResult>('apply_bulk_adjustment', { accountIds, date, adjustment });
+}
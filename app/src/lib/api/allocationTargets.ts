@@ -0,0 +1,33 @@
+import { invoke } from '@tauri-apps/api/core';
+import type {
+  AllocationDrift,
+  AllocationTarget,
+  CreateAllocationTargetInput,
+  UpdateAllocationTargetInput,
+} from '$lib/types';
+
+export async function getAllocationTargets(): Promise<AllocationTarget[]> {
+  return invoke<AllocationTarget[]>('get_allocation_targets');
+}
+
+export async function createAllocationTarget(
+  input: CreateAllocationTargetInput
+): Promise<AllocationTarget> {
+  return invoke<AllocationTarget>('create_allocation_target', { input });
+}
+
+export async function updateAllocationTarget(
+  id: string,
+  input: UpdateAllocationTargetInput
+): Promise<AllocationTarget> {
+  return invoke<AllocationTarget>('update_allocation_target', { id, input });
+}
+
+export async function deleteAllocationTarget(id: string): Promise<void> {
+  await invoke('delete_allocation_target', { id });
+}
+
+// Fails unless the targets add up to 100%.
+export async function getAllocationDrift(asOf?: string): Promise<AllocationDrift> {
+  return invoke<AllocationDrift>('get_allocation_drift', { asOf });
+}
@@ -0,0 +1,139 @@
+import { invoke } from '@tauri-apps/api/core';
+import type {
+  Allocation,
+  AllocationGroupBy,
+  BreakdownGroupBy,
+  Cagr,
+  CagrScope,
+  DateRange,
+  DebtRatioPoint,
+  DrawdownAnalysis,
+  FireParams,
+  FireProjection,
+  ForecastModel,
+  Granularity,
+  GrowthSummary,
+  InflationOptions,
+  LiquidityBreakdown,
+  LiquidityPoint,
+  MonteCarloParams,
+  MonteCarloProjection,
+  NetWorthBreakdownPoint,
+  NetWorthForecast,
+  NetWorthPoint,
+  NetWorthSeriesOptions,
+  PeriodStats,
+} from '$lib/types';
+
+// Dates before any account has a balance have no point; a missing exchange rate rejects the call.
+export async function getNetWorthSeries(
+  start: string,
+  end: string,
+  granularity: Granularity = 'month',
+  options?: NetWorthSeriesOptions
+): Promise<NetWorthPoint[]> {
+  return invoke<NetWorthPoint[]>('get_net_worth_series', { start, end, granularity, options });
+}
+
+// Groups that are zero throughout are left out; the rest appear on every date and add up to
+// that date's assets and liabilities in getNetWorthSeries.
+export async function getNetWorthBreakdown(
+  start: string,
+  end: string,
+  granularity: Granularity = 'month',
+  groupBy: BreakdownGroupBy = 'account_type',
+  options?: NetWorthSeriesOptions
+): Promise<NetWorthBreakdownPoint[]> {
+  return invoke<NetWorthBreakdownPoint[]>('get_net_worth_breakdown', {
+    start,
+    end,
+    granularity,
+    groupBy,
+    options,
+  });
+}
+
+// On the same dates and from the same figures as getNetWorthSeries.
+export async function getDebtRatioSeries(
+  start: string,
+  end: string,
+  granularity: Granularity = 'month'
+): Promise<DebtRatioPoint[]> {
+  return invoke<DebtRatioPoint[]>('get_debt_ratio_series', { start, end, granularity });
+}
+
+// Defaults to today; slices under otherThreshold percent are merged into one "Other" slice.
+export async function getAllocation(
+  groupBy: AllocationGroupBy = 'account_type',
+  asOf?: string,
+  otherThreshold?: number
+): Promise<Allocation> {
+  return invoke<Allocation>('get_allocation', { asOf, groupBy, otherThreshold });
+}
+
+// Defaults to today, valued like getAllocation.
+export async function getLiquidityBreakdown(asOf?: string): Promise<LiquidityBreakdown> {
+  return invoke<LiquidityBreakdown>('get_liquidity_breakdown', { asOf });
+}
+
+export async function getLiquiditySeries(
+  start: string,
+  end: string,
+  granularity: Granularity = 'month'
+): Promise<LiquidityPoint[]> {
+  return invoke<LiquidityPoint[]>('get_liquidity_series', { start, end, granularity });
+}
+
+// Defaults to today; year_to_date runs from the fiscal_year_start setting.
+export async function getGrowthSummary(
+  asOf?: string,
+  inflation?: InflationOptions
+): Promise<GrowthSummary> {
+  return invoke<GrowthSummary>('get_growth_summary', { asOf, inflation });
+}
+
+// Dates outside the span the scope has balances for are moved onto it; see start and end.
+export async function getCagr(
+  scope: CagrScope,
+  start: string,
+  end: string,
+  inflation?: InflationOptions
+): Promise<Cagr> {
+  return invoke<Cagr>('get_cagr', { scope, start, end, inflation });
+}
+
+export async function getFireProjection(params: FireParams): Promise<FireProjection> {
+  return invoke<FireProjection>('get_fire_projection', { params });
+}
+
+// One band per month ahead; rejects with no_data when there is too little history to estimate from.
+export async function runMonteCarlo(params: MonteCarloParams): Promise<MonteCarloProjection> {
+  return invoke<MonteCarloProjection>('run_monte_carlo', { params });
+}
+
+// Fitted to month-end net worth over the trailing windowMonths (24 by default).
+export async function forecastNetWorth(
+  horizonMonths: number,
+  model: ForecastModel = 'auto',
+  windowMonths?: number
+): Promise<NetWorthForecast> {
+  return invoke<NetWorthForecast>('forecast_net_worth', { horizonMonths, model, windowMonths });
+}
+
+// The range defaults to the first recorded balance through today.
+export async function getPeriodStats(
+  granularity: Granularity = 'month',
+  range?: DateRange
+): Promise<PeriodStats> {
+  return invoke<PeriodStats>('get_period_stats', { granularity, range });
+}
+
+// Monthly by default, listing drawdowns at least thresholdPercent (5 by default) deep.
+export async function getDrawdowns(
+  start: string,
+  end: string,
+  granularity: Granularity = 'month',
+  thresholdPercent?: number
+): Promise<DrawdownAnalysis> {
+  return invoke<DrawdownAnalysis>('get_drawdowns', { start, end, granularity, thresholdPercent });
+}
@@ -0,0 +1,23 @@
+import { invoke } from '@tauri-apps/api/core';
+import type { Attachment, OrphanedAttachments } from '$lib/types';
+
+// Copies the file into the app's attachments directory; the original can be moved afterwards.
+export async function addAttachment(entryId: string, sourcePath: string): Promise<Attachment> {
+  return invoke<Attachment>('add_attachment', { entryId, sourcePath });
+}
+
+export async function getAttachments(entryId: string): Promise<Attachment[]> {
+  return invoke<Attachment[]>('get_attachments', { entryId });
+}
+
+export async function openAttachment(id: string): Promise<void> {
+  await invoke('open_attachment', { id });
+}
+
+export async function removeAttachment(id: string): Promise<void> {
+  await invoke('remove_attachment', { id });
+}
+
+export async function findOrphanedAttachments(dryRun = true): Promise<OrphanedAttachments> {
+  return invoke<OrphanedAttachments>('find_orphaned_attachments', { dryRun });
+}
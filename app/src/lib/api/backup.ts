@@ -0,0 +1,16 @@
+import { invoke } from '@tauri-apps/api/core';
+import type { Backup, CsvImportReport, ImportSummary } from '$lib/types';
+
+export async function exportBackup(): Promise<Backup> {
+  return invoke<Backup>('export_backup');
+}
+
+// Replaces all existing data with the backup's contents.
+export async function importBackup(backup: Backup): Promise<ImportSummary> {
+  return invoke<ImportSummary>('import_backup', { backup });
+}
+
+// Columns: name, type, and optionally institution, currency, description.
+export async function importAccountsCsv(path: string, dryRun: boolean): Promise<CsvImportReport> {
+  return invoke<CsvImportReport>('import_accounts_csv', { path, dryRun });
+}
@@ -1,13 +1,40 @@
+import { invoke } from '@tauri-apps/api/core';
 import { getDb } from './db';
-import type { BalanceEntry, CreateBalanceInput, ChartDataPoint, NetWorthStats } from '$lib/types';
-
-function generateId(): string {
-  return crypto.randomUUID();
-}
-
-function now(): string {
-  return new Date().toISOString();
-}
+import type {
+  BalanceAt,
+  BalanceCsvExportOptions,
+  BalanceCsvMapping,
+  BalanceCsvReport,
+  BalanceEntry,
+  BalanceEntryChange,
+  BalanceEntryDeletion,
+  BalanceEntryFilter,
+  BalanceEntryPage,
+  BalanceEntryWithDelta,
+  CreateBalanceInput,
+  CreatedBalanceEntry,
+  ChartDataPoint,
+  DateRepairReport,
+  DatetimeRepairReport,
+  DuplicateEntryGroup,
+  DuplicateMergeReport,
+  DuplicateMergeStrategy,
+  DuplicatePolicy,
+  EntrySource,
+  FillMethod,
+  Granularity,
+  MonthlyBalance,
+  NetWorthStats,
+  OfxImportReport,
+  QifImportMode,
+  QifImportOptions,
+  ReconciliationStatus,
+  SeriesPoint,
+  SignNormalisationReport,
+  SmoothingSpec,
+  UpdateBalanceInput,
+  UpsertBalanceResult,
+} from '$lib/types';
 
 export async function getBalances(
   accountId: string,
@@ -33,6 +60,20 @@ export async function getBalances(
   return db.select<BalanceEntry[]>(query, params);
 }
 
+// Keyed by account id; accounts with no entry on or before asOf are absent.
+export async function getLatestBalances(asOf?: string): Promise<Record<string, BalanceEntry>> {
+  return invoke<Record<string, BalanceEntry>>('get_latest_balances', { asOf });
+}
+
+// Newest first, like getBalances.
+export async function getBalanceEntriesWithDelta(
+  accountId: string,
+  start?: string,
+  end?: string
+): Promise<BalanceEntryWithDelta[]> {
+  return invoke<BalanceEntryWithDelta[]>('get_balance_entries_with_delta', { accountId, start, end });
+}
+
 export async function getLatestBalance(accountId: string): Promise<BalanceEntry | null> {
   const db = await getDb();
   const rows = await db.select<BalanceEntry[]>(
@@ -42,33 +83,186 @@ export async function getLatestBalance(accountId: string): Promise<BalanceEntry
   return rows.length > 0 ? rows[0] : null;
 }
 
+// Creates the entry, or overwrites the one already on that date.
+export async function upsertBalanceEntry(input: CreateBalanceInput): Promise<UpsertBalanceResult> {
+  return invoke<UpsertBalanceResult>('upsert_balance_entry', { input });
+}
+
 export async function setBalance(input: CreateBalanceInput): Promise<BalanceEntry> {
-  const db = await getDb();
-  const id = generateId();
-  const timestamp = now();
-
-  // Use INSERT OR REPLACE to handle unique constraint on (account_id, date)
-  await db.execute(
-    `INSERT INTO balance_entries (id, account_id, date, balance, notes, created_at)
-     VALUES (?, ?, ?, ?, ?, ?)
-     ON CONFLICT(account_id, date) DO UPDATE SET
-       balance = excluded.balance,
-       notes = excluded.notes`,
-    [id, input.account_id, input.date, input.balance, input.notes || null, timestamp]
-  );
+  const { entry } = await upsertBalanceEntry(input);
+  return entry;
+}
 
-  // Fetch the actual record (might have different id if it was an update)
-  const rows = await db.select<BalanceEntry[]>(
-    'SELECT * FROM balance_entries WHERE account_id = ? AND date = ?',
-    [input.account_id, input.date]
-  );
+// Rejects a second entry for the same account and date.
+export async function createBalanceEntry(input: CreateBalanceInput): Promise<CreatedBalanceEntry> {
+  return invoke<CreatedBalanceEntry>('create_balance_entry', { input });
+}
+
+// All or nothing: an `invalid_batch` error lists every rejected entry by index.
+export async function createBalanceEntries(entries: CreateBalanceInput[]): Promise<BalanceEntry[]> {
+  return invoke<BalanceEntry[]>('create_balance_entries', { entries });
+}
+
+// Resolves to the number of rows written; refuses an existing file unless overwrite is set.
+export async function exportBalancesCsv(
+  accountId: string,
+  path: string,
+  options?: BalanceCsvExportOptions
+): Promise<number> {
+  return invoke<number>('export_balances_csv', { accountId, path, options });
+}
+
+// Writing (dryRun false) requires choosing what happens to dates that already have an entry.
+export async function importBalancesCsv(
+  accountId: string,
+  path: string,
+  mapping: BalanceCsvMapping,
+  dryRun: boolean,
+  onDuplicate?: DuplicatePolicy
+): Promise<BalanceCsvReport> {
+  return invoke<BalanceCsvReport>('import_balances_csv', {
+    accountId,
+    path,
+    mapping,
+    dryRun,
+    onDuplicate,
+  });
+}
+
+// Tab- or comma-separated date and balance columns as pasted from a spreadsheet.
+// Ambiguous dates such as 03/04/2024 come back with a message saying how they were read.
+export async function importBalancesFromText(
+  accountId: string,
+  text: string,
+  dryRun: boolean,
+  onDuplicate?: DuplicatePolicy
+): Promise<BalanceCsvReport> {
+  return invoke<BalanceCsvReport>('import_balances_from_text', {
+    accountId,
+    text,
+    dryRun,
+    onDuplicate,
+  });
+}
+
+// A malformed file fails with an `invalid_ofx` error carrying the byte `offset`.
+// Pass the statement's ACCTID as statementAccount when the file holds several.
+export async function importOfx(
+  accountId: string,
+  path: string,
+  dryRun: boolean,
+  statementAccount?: string
+): Promise<OfxImportReport> {
+  return invoke<OfxImportReport>('import_ofx', { accountId, path, dryRun, statementAccount });
+}
+
+// Generated balances are reported and deduplicated like CSV rows.
+export async function importQif(
+  accountId: string,
+  path: string,
+  mode: QifImportMode,
+  dryRun: boolean,
+  onDuplicate?: DuplicatePolicy,
+  options?: QifImportOptions
+): Promise<BalanceCsvReport> {
+  return invoke<BalanceCsvReport>('import_qif', {
+    accountId,
+    path,
+    mode,
+    dryRun,
+    onDuplicate,
+    options,
+  });
+}
+
+// Rewrites stored dates into YYYY-MM-DD where they can be read.
+export async function repairEntryDates(dryRun = true): Promise<DateRepairReport> {
+  return invoke<DateRepairReport>('repair_entry_dates', { dryRun });
+}
+
+// Strips times such as T00:00:00 from stored dates.
+export async function repairDatetimeDates(
+  dryRun = true,
+  strategy?: DuplicateMergeStrategy
+): Promise<DatetimeRepairReport> {
+  return invoke<DatetimeRepairReport>('repair_datetime_dates', { dryRun, strategy });
+}
+
+export async function findDuplicateEntries(accountId?: string): Promise<DuplicateEntryGroup[]> {
+  return invoke<DuplicateEntryGroup[]>('find_duplicate_entries', { accountId });
+}
+
+// Keeps one entry per duplicated day; the kept entry takes the others' attachments.
+export async function mergeDuplicateEntries(
+  strategy: DuplicateMergeStrategy
+): Promise<DuplicateMergeReport> {
+  return invoke<DuplicateMergeReport>('merge_duplicate_entries', { strategy });
+}
+
+// Makes negative liability balances positive, the way they are stored.
+export async function normaliseBalanceSigns(dryRun = true): Promise<SignNormalisationReport> {
+  return invoke<SignNormalisationReport>('normalise_balance_signs', { dryRun });
+}
+
+// A new balance drops any amount the entry was reported as in another currency
+// and makes an estimated entry a manual one. The sign policy and outlier check
+// apply to it as to a new balance.
+export async function updateBalanceEntry(
+  id: string,
+  input: UpdateBalanceInput,
+): Promise<CreatedBalanceEntry> {
+  return invoke<CreatedBalanceEntry>('update_balance_entry', { id, input });
+}
+
+export async function markEntryVerified(entryId: string): Promise<BalanceEntry> {
+  return invoke<BalanceEntry>('mark_entry_verified', { entryId });
+}
+
+export async function unmarkEntryVerified(entryId: string): Promise<BalanceEntry> {
+  return invoke<BalanceEntry>('unmark_entry_verified', { entryId });
+}
 
-  return rows[0];
+export async function getReconciliationStatus(accountId: string): Promise<ReconciliationStatus> {
+  return invoke<ReconciliationStatus>('get_reconciliation_status', { accountId });
 }
 
+// Also removes the entry's attachments.
 export async function deleteBalance(id: string): Promise<void> {
-  const db = await getDb();
-  await db.execute('DELETE FROM balance_entries WHERE id = ?', [id]);
+  await invoke('delete_balance_entry', { id });
+}
+
+// Kept after the entry is deleted, so this also works for removed entries.
+export async function getBalanceEntryHistory(entryId: string): Promise<BalanceEntryChange[]> {
+  return invoke<BalanceEntryChange[]>('get_balance_entry_history', { entryId });
+}
+
+export async function getRecentChanges(limit = 50): Promise<BalanceEntryChange[]> {
+  return invoke<BalanceEntryChange[]>('get_recent_changes', { limit });
+}
+
+// Newest first and 50 at a time unless the filter says otherwise.
+export async function listBalanceEntries(
+  accountId: string,
+  filter: BalanceEntryFilter = {}
+): Promise<BalanceEntryPage> {
+  return invoke<BalanceEntryPage>('list_balance_entries', { accountId, filter });
+}
+
+// Dates are inclusive. Nothing is deleted unless `dryRun` is false.
+export async function deleteBalanceEntries(
+  accountId: string,
+  startDate: string,
+  endDate: string,
+  source?: EntrySource,
+  dryRun = true
+): Promise<BalanceEntryDeletion> {
+  return invoke<BalanceEntryDeletion>('delete_balance_entries', {
+    accountId,
+    startDate,
+    endDate,
+    source,
+    dryRun,
+  });
 }
 
 export async function getAllBalancesForDateRange(
@@ -82,6 +276,73 @@ export async function getAllBalancesForDateRange(
   );
 }
 
+// Dates before the first entry have no point rather than a zero.
+export async function getAccountSeries(
+  accountId: string,
+  start: string,
+  end: string,
+  granularity: Granularity = 'month',
+  method: FillMethod = 'linear',
+  excludeEstimated = false,
+  smoothing?: SmoothingSpec
+): Promise<SeriesPoint[]> {
+  return invoke<SeriesPoint[]>('get_account_series', {
+    accountId,
+    start,
+    end,
+    granularity,
+    method,
+    excludeEstimated,
+    smoothing,
+  });
+}
+
+// Adds an estimated month-end entry to each month in the range that has none.
+export async function fillMissingMonths(
+  accountId: string,
+  start: string,
+  end: string,
+  method: FillMethod = 'linear'
+): Promise<BalanceEntry[]> {
+  return invoke<BalanceEntry[]>('fill_missing_months', { accountId, start, end, method });
+}
+
+// Removes only the entries fillMissingMonths created.
+export async function deleteEstimatedEntries(accountId: string): Promise<BalanceEntry[]> {
+  return invoke<BalanceEntry[]>('delete_estimated_entries', { accountId });
+}
+
+// Months without an entry carry the previous balance forward, flagged as estimated.
+export async function getMonthlyBalances(
+  accountId: string,
+  start: string,
+  end: string,
+  excludeEstimated = false
+): Promise<MonthlyBalance[]> {
+  return invoke<MonthlyBalance[]>('get_monthly_balances', {
+    accountId,
+    start,
+    end,
+    excludeEstimated,
+  });
+}
+
+export async function getBalanceAt(
+  accountId: string,
+  date: string,
+  method: FillMethod = 'carry_forward'
+): Promise<BalanceAt> {
+  return invoke<BalanceAt>('get_balance_at', { accountId, date, method });
+}
+
+export async function getBalancesAt(
+  accountIds: string[],
+  date: string,
+  method: FillMethod = 'carry_forward'
+): Promise<BalanceAt[]> {
+  return invoke<BalanceAt[]>('get_balances_at', { accountIds, date, method });
+}
+
 export async function getChartData(
   fromDate: string,
   toDate: string
@@ -94,11 +355,14 @@ export async function getChartData(
     date: string;
     balance: number;
     category: string;
+    closed_at: string | null;
   }>>(
-    `SELECT be.account_id, be.date, be.balance, a.category
+    // Closed accounts are inactive but still count up to their closing date
+    `SELECT be.account_id, be.date, be.balance * a.ownership_percent / 100.0 AS balance, a.category, a.closed_at
      FROM balance_entries be
      JOIN accounts a ON be.account_id = a.id
-     WHERE a.is_active = 1
+     WHERE (a.is_active = 1 OR a.closed_at IS NOT NULL) AND a.archived_at IS NULL
+       AND a.exclude_from_net_worth = 0
      ORDER BY be.account_id, be.date`,
     []
   );
@@ -111,7 +375,10 @@ export async function getChartData(
   if (allDates.length === 0) return [];
 
   // Build a map of account_id -> sorted balance entries
-  const accountBalances = new Map<string, Array<{ date: string; balance: number; category: string }>>();
+  const accountBalances = new Map<
+    string,
+    Array<{ date: string; balance: number; category: string; closed_at: string | null }>
+  >();
   for (const row of rows) {
     if (!accountBalances.has(row.account_id)) {
       accountBalances.set(row.account_id, []);
@@ -120,6 +387,7 @@ export async function getChartData(
       date: row.date,
       balance: row.balance,
       category: row.category,
+      closed_at: row.closed_at,
     });
   }
 
@@ -132,7 +400,8 @@ export async function getChartData(
 
     for (const [, entries] of accountBalances) {
       // Find the most recent entry on or before this date
-      let latestEntry: { balance: number; category: string } | null = null;
+      let latestEntry: { balance: number; category: string; closed_at: string | null } | null =
+        null;
       for (const entry of entries) {
         if (entry.date <= date) {
           latestEntry = entry;
@@ -141,7 +410,7 @@ export async function getChartData(
         }
       }
 
-      if (latestEntry) {
+      if (latestEntry && !(latestEntry.closed_at && date > latestEntry.closed_at)) {
         if (latestEntry.category === 'asset') {
           assets += latestEntry.balance;
         } else {
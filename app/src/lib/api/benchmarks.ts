@@ -0,0 +1,34 @@
+import { invoke } from '@tauri-apps/api/core';
+import type {
+  Benchmark,
+  BenchmarkPoint,
+  CreateBenchmarkInput,
+  Granularity,
+  UpdateBenchmarkInput,
+} from '$lib/types';
+
+export async function createBenchmark(input: CreateBenchmarkInput): Promise<Benchmark> {
+  return invoke<Benchmark>('create_benchmark', { input });
+}
+
+export async function getBenchmarks(): Promise<Benchmark[]> {
+  return invoke<Benchmark[]>('get_benchmarks');
+}
+
+export async function updateBenchmark(id: string, input: UpdateBenchmarkInput): Promise<Benchmark> {
+  return invoke<Benchmark>('update_benchmark', { id, input });
+}
+
+export async function deleteBenchmark(id: string): Promise<void> {
+  await invoke('delete_benchmark', { id });
+}
+
+// Starts from net worth on start, on the same dates as getNetWorthSeries; rejects with no_data without one.
+export async function getBenchmarkSeries(
+  benchmarkId: string,
+  start: string,
+  end: string,
+  granularity: Granularity = 'month'
+): Promise<BenchmarkPoint[]> {
+  return invoke<BenchmarkPoint[]>('get_benchmark_series', { benchmarkId, start, end, granularity });
+}
@@ -0,0 +1,7 @@
+import { invoke } from '@tauri-apps/api/core';
+import type { EmergencyCoverage } from '$lib/types';
+
+// Measured against the monthly_expenses setting; see EmergencyCoverage.status.
+export async function getEmergencyCoverage(asOf?: string): Promise<EmergencyCoverage> {
+  return invoke<EmergencyCoverage>('get_emergency_coverage', { asOf });
+}
@@ -0,0 +1,23 @@
+import { invoke } from '@tauri-apps/api/core';
+import type { ExchangeRate } from '$lib/types';
+
+// `rate` is the value of one `fromCurrency` in `toCurrency`; replaces any rate for the same day.
+export async function setExchangeRate(
+  date: string,
+  fromCurrency: string,
+  toCurrency: string,
+  rate: number
+): Promise<ExchangeRate> {
+  return invoke<ExchangeRate>('set_exchange_rate', { date, fromCurrency, toCurrency, rate });
+}
+
+export async function getExchangeRates(
+  fromCurrency?: string,
+  toCurrency?: string
+): Promise<ExchangeRate[]> {
+  return invoke<ExchangeRate[]>('get_exchange_rates', { fromCurrency, toCurrency });
+}
+
+export async function deleteExchangeRate(id: string): Promise<void> {
+  await invoke('delete_exchange_rate', { id });
+}
@@ -0,0 +1,52 @@
+import { invoke } from '@tauri-apps/api/core';
+import type {
+  AccountGrowth,
+  CreateFlowInput,
+  Flow,
+  NetWorthGrowth,
+  TimeWeightedReturn,
+  UpdateFlowInput,
+} from '$lib/types';
+
+export async function createFlow(input: CreateFlowInput): Promise<Flow> {
+  return invoke<Flow>('create_flow', { input });
+}
+
+export async function getFlows(accountId: string, start?: string, end?: string): Promise<Flow[]> {
+  return invoke<Flow[]>('get_flows', { accountId, start, end });
+}
+
+export async function updateFlow(id: string, input: UpdateFlowInput): Promise<Flow> {
+  return invoke<Flow>('update_flow', { id, input });
+}
+
+export async function deleteFlow(id: string): Promise<void> {
+  await invoke('delete_flow', { id });
+}
+
+// Flows dated after `start` up to and including `end` count towards the period.
+export async function getAccountGrowth(
+  accountId: string,
+  start: string,
+  end: string,
+  excludeEstimated = false
+): Promise<AccountGrowth> {
+  return invoke<AccountGrowth>('get_account_growth', { accountId, start, end, excludeEstimated });
+}
+
+export async function getNetWorthGrowth(
+  start: string,
+  end: string,
+  excludeEstimated = false
+): Promise<NetWorthGrowth> {
+  return invoke<NetWorthGrowth>('get_net_worth_growth', { start, end, excludeEstimated });
+}
+
+// Asset accounts only; xirr is the money-weighted rate for comparison.
+export async function getTimeWeightedReturn(
+  accountId: string,
+  start: string,
+  end: string
+): Promise<TimeWeightedReturn> {
+  return invoke<TimeWeightedReturn>('get_time_weighted_return', { accountId, start, end });
+}
@@ -0,0 +1,37 @@
+import { invoke } from '@tauri-apps/api/core';
+import type {
+  Account,
+  AccountGroup,
+  CreateGroupInput,
+  GroupedAccounts,
+  UpdateGroupInput,
+} from '$lib/types';
+
+export async function createGroup(input: CreateGroupInput): Promise<AccountGroup> {
+  return invoke<AccountGroup>('create_group', { input });
+}
+
+export async function renameGroup(id: string, input: UpdateGroupInput): Promise<AccountGroup> {
+  return invoke<AccountGroup>('rename_group', { id, input });
+}
+
+// Its accounts become ungrouped rather than being deleted.
+export async function deleteGroup(id: string): Promise<void> {
+  await invoke('delete_group', { id });
+}
+
+// Pass null to move the account out of any group.
+export async function assignAccountToGroup(
+  accountId: string,
+  groupId: string | null
+): Promise<Account> {
+  return invoke<Account>('assign_account_to_group', { accountId, groupId });
+}
+
+export async function reorderGroups(orderedIds: string[]): Promise<AccountGroup[]> {
+  return invoke<AccountGroup[]>('reorder_groups', { orderedIds });
+}
+
+export async function getAccountsGrouped(): Promise<GroupedAccounts> {
+  return invoke<GroupedAccounts>('get_accounts_grouped');
+}
@@ -0,0 +1,32 @@
+import { invoke } from '@tauri-apps/api/core';
+import type {
+  CreateIncomeRecordInput,
+  IncomeRecord,
+  SavingsRate,
+  UpdateIncomeRecordInput,
+} from '$lib/types';
+
+export async function createIncomeRecord(input: CreateIncomeRecordInput): Promise<IncomeRecord> {
+  return invoke<IncomeRecord>('create_income_record', { input });
+}
+
+// Months are YYYY-MM; both bounds are optional and inclusive.
+export async function getIncomeRecords(start?: string, end?: string): Promise<IncomeRecord[]> {
+  return invoke<IncomeRecord[]>('get_income_records', { start, end });
+}
+
+export async function updateIncomeRecord(
+  id: string,
+  input: UpdateIncomeRecordInput
+): Promise<IncomeRecord> {
+  return invoke<IncomeRecord>('update_income_record', { id, input });
+}
+
+export async function deleteIncomeRecord(id: string): Promise<void> {
+  await invoke('delete_income_record', { id });
+}
+
+// Months without flows fall back to their change in net worth; see source on each month.
+export async function getSavingsRate(start: string, end: string): Promise<SavingsRate> {
+  return invoke<SavingsRate>('get_savings_rate', { start, end });
+}
@@ -1,3 +1,29 @@
 export * from './accounts';
+export * from './adjustments';
+export * from './allocationTargets';
+export * from './analytics';
+export * from './attachments';
+export * from './backup';
 export * from './balances';
+export * from './benchmarks';
+export * from './emergencyFund';
+export * from './exchangeRates';
+export * from './flows';
+export * from './groups';
 export { getDb } from './db';
+export * from './income';
+export * from './inflation';
+export * from './links';
+export * from './milestones';
+export * from './netWorthCache';
+export * from './notifications';
+export * from './pensions';
+export * from './projections';
+export * from './properties';
+export * from './reviews';
+export * from './scenarios';
+export * from './search';
+export * from './settings';
+export * from './snapshots';
+export * from './tags';
+export * from './undo';
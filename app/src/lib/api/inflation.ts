@@ -0,0 +1,20 @@
+import { invoke } from '@tauri-apps/api/core';
+import type { CpiImportReport, CpiRate } from '$lib/types';
+
+// Replaces any value already stored for the month (YYYY-MM).
+export async function setCpiRate(month: string, indexValue: number): Promise<CpiRate> {
+  return invoke<CpiRate>('set_cpi_rate', { month, indexValue });
+}
+
+export async function getCpiRates(): Promise<CpiRate[]> {
+  return invoke<CpiRate[]>('get_cpi_rates');
+}
+
+export async function deleteCpiRate(month: string): Promise<void> {
+  await invoke('delete_cpi_rate', { month });
+}
+
+// Needs month and index_value columns; bad rows are reported and the rest saved.
+export async function importCpiCsv(path: string): Promise<CpiImportReport> {
+  return invoke<CpiImportReport>('import_cpi_csv', { path });
+}
@@ -0,0 +1,16 @@
+import { invoke } from '@tauri-apps/api/core';
+import type { Account, LtvReport } from '$lib/types';
+
+// Records that a liability (e.g. a mortgage) is secured on an asset; returns the liability.
+export async function linkAccounts(liabilityId: string, assetId: string): Promise<Account> {
+  return invoke<Account>('link_accounts', { liabilityId, assetId });
+}
+
+export async function unlinkAccount(accountId: string): Promise<Account> {
+  return invoke<Account>('unlink_account', { accountId });
+}
+
+// Loan-to-value of an asset against every liability linked to it.
+export async function getLtv(assetId: string): Promise<LtvReport> {
+  return invoke<LtvReport>('get_ltv', { assetId });
+}
@@ -0,0 +1,108 @@
+import { invoke } from '@tauri-apps/api/core';
+import type {
+  CreateMilestoneInput,
+  DateRange,
+  Milestone,
+  MilestoneCategory,
+  MilestoneEditScope,
+  MilestoneFilter,
+  MilestoneListItem,
+  MilestoneProjection,
+  MilestoneSuggestion,
+  MilestoneWithNetWorth,
+  TrendMethod,
+  UpdateMilestoneInput,
+} from '$lib/types';
+
+export async function createMilestone(input: CreateMilestoneInput): Promise<Milestone> {
+  return invoke<Milestone>('create_milestone', { input });
+}
+
+// Both dates are optional and inclusive.
+export async function getMilestones(start?: string, end?: string): Promise<Milestone[]> {
+  return invoke<Milestone[]>('get_milestones', { start, end });
+}
+
+// One page, oldest first; 50 at a time unless filter.limit says otherwise.
+export async function listMilestones(filter: MilestoneFilter = {}): Promise<MilestoneListItem[]> {
+  return invoke<MilestoneListItem[]>('list_milestones', { filter });
+}
+
+// Ignores limit and offset, for paging through listMilestones.
+export async function countMilestones(filter: MilestoneFilter = {}): Promise<number> {
+  return invoke<number>('count_milestones', { filter });
+}
+
+// Net worth and linked balances as they stood on each milestone's date; null before any data.
+export async function getMilestonesWithNetWorth(
+  start?: string,
+  end?: string
+): Promise<MilestoneWithNetWorth[]> {
+  return invoke<MilestoneWithNetWorth[]>('get_milestones_with_net_worth', { start, end });
+}
+
+export async function getMilestonesByCategory(
+  category: MilestoneCategory,
+  start?: string,
+  end?: string
+): Promise<Milestone[]> {
+  return invoke<Milestone[]>('get_milestones_by_category', { category, start, end });
+}
+
+// 'all_future' also changes the rest of a recurring series; dates only change one at a time.
+export async function updateMilestone(
+  id: string,
+  input: UpdateMilestoneInput,
+  scope: MilestoneEditScope = 'this_instance'
+): Promise<Milestone> {
+  return invoke<Milestone>('update_milestone', { id, input, scope });
+}
+
+// orderedIds must list every milestone on the date exactly once.
+export async function reorderMilestones(date: string, orderedIds: string[]): Promise<Milestone[]> {
+  return invoke<Milestone[]>('reorder_milestones', { date, orderedIds });
+}
+
+// 'all_future' also deletes the later instances of a recurring series and stops it recurring.
+export async function deleteMilestone(
+  id: string,
+  scope: MilestoneEditScope = 'this_instance'
+): Promise<void> {
+  await invoke('delete_milestone', { id, scope });
+}
+
+// Generates the instances of recurring milestones up to untilDate; returns those created.
+export async function materialiseRecurringMilestones(untilDate: string): Promise<Milestone[]> {
+  return invoke<Milestone[]>('materialise_recurring_milestones', { untilDate });
+}
+
+// Stamps target milestones with the first date their figure reached the target; returns those newly achieved.
+export async function checkMilestoneAchievements(): Promise<Milestone[]> {
+  return invoke<Milestone[]>('check_milestone_achievements');
+}
+
+// Fits the last 12 months of the target's figure; already achieved targets return their date.
+export async function projectMilestone(
+  milestoneId: string,
+  method: TrendMethod = 'linear'
+): Promise<MilestoneProjection> {
+  return invoke<MilestoneProjection>('project_milestone', { milestoneId, method });
+}
+
+// Replaces any file at `path`; resolves to the number of events written.
+export async function exportMilestonesIcs(path: string, range?: DateRange): Promise<number> {
+  return invoke<number>('export_milestones_ics', { path, range });
+}
+
+// Paid-off liabilities, new properties and net worth milestones not yet marked, accepted or dismissed.
+export async function suggestMilestones(): Promise<MilestoneSuggestion[]> {
+  return invoke<MilestoneSuggestion[]>('suggest_milestones');
+}
+
+export async function acceptMilestoneSuggestion(suggestion: MilestoneSuggestion): Promise<Milestone> {
+  return invoke<Milestone>('accept_milestone_suggestion', { suggestion });
+}
+
+export async function dismissMilestoneSuggestion(key: string): Promise<void> {
+  return invoke<void>('dismiss_milestone_suggestion', { key });
+}
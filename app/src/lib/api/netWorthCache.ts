@@ -0,0 +1,7 @@
+import { invoke } from '@tauri-apps/api/core';
+import type { SnapshotRefresh } from '$lib/types';
+
+// getNetWorthSeries refreshes on its own; this warms the snapshots ahead of it.
+export async function refreshSnapshots(): Promise<SnapshotRefresh> {
+  return invoke<SnapshotRefresh>('refresh_snapshots');
+}
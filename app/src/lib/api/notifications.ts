@@ -0,0 +1,7 @@
+import { invoke } from '@tauri-apps/api/core';
+import type { MilestoneNotification } from '$lib/types';
+
+// Call on start-up and on a timer; resolves to the notices shown, each only ever shown once.
+export async function checkAndNotify(): Promise<MilestoneNotification[]> {
+  return invoke<MilestoneNotification[]>('check_and_notify');
+}
@@ -0,0 +1,20 @@
+import { invoke } from '@tauri-apps/api/core';
+import type { PensionDetails, PensionProjectionPoint, SetPensionDetailsInput } from '$lib/types';
+
+export async function getPensionDetails(accountId: string): Promise<PensionDetails | null> {
+  return invoke<PensionDetails | null>('get_pension_details', { accountId });
+}
+
+export async function setPensionDetails(
+  accountId: string,
+  input: SetPensionDetailsInput
+): Promise<PensionDetails> {
+  return invoke<PensionDetails>('set_pension_details', { accountId, input });
+}
+
+export async function projectPension(
+  accountId: string,
+  annualGrowthPercent: number
+): Promise<PensionProjectionPoint[]> {
+  return invoke<PensionProjectionPoint[]>('project_pension', { accountId, annualGrowthPercent });
+}
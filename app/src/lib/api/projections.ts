@@ -0,0 +1,31 @@
+import { invoke } from '@tauri-apps/api/core';
+import type { AmortisationSchedule, PayoffProjection, ProjectedPoint } from '$lib/types';
+
+// Compounds the latest balance monthly at the account's interest rate; rejects
+// when the account has no rate.
+export async function projectInterest(
+  accountId: string,
+  months: number
+): Promise<ProjectedPoint[]> {
+  return invoke<ProjectedPoint[]>('project_interest', { accountId, months });
+}
+
+// Mortgages and loans only; a payment that doesn't cover the interest rejects with a validation error.
+export async function getAmortisationSchedule(
+  accountId: string,
+  payment: number,
+  overpayment?: number,
+  interestRate?: number
+): Promise<AmortisationSchedule> {
+  return invoke<AmortisationSchedule>('get_amortisation_schedule', {
+    accountId,
+    payment,
+    overpayment,
+    interestRate,
+  });
+}
+
+// Liabilities only; fewer than three entries give the 'insufficient_data' status.
+export async function projectPayoff(accountId: string): Promise<PayoffProjection> {
+  return invoke<PayoffProjection>('project_payoff', { accountId });
+}
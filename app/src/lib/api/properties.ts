@@ -0,0 +1,17 @@
+import { invoke } from '@tauri-apps/api/core';
+import type { PropertyDetails, PropertyPerformance, SetPropertyDetailsInput } from '$lib/types';
+
+export async function getPropertyDetails(accountId: string): Promise<PropertyDetails | null> {
+  return invoke<PropertyDetails | null>('get_property_details', { accountId });
+}
+
+export async function setPropertyDetails(
+  accountId: string,
+  input: SetPropertyDetailsInput
+): Promise<PropertyDetails> {
+  return invoke<PropertyDetails>('set_property_details', { accountId, input });
+}
+
+export async function getPropertyPerformance(accountId: string): Promise<PropertyPerformance> {
+  return invoke<PropertyPerformance>('get_property_performance', { accountId });
+}
@@ -0,0 +1,7 @@
+import { invoke } from '@tauri-apps/api/core';
+import type { YearReview } from '$lib/types';
+
+// Year boundaries follow the fiscal_year_start setting.
+export async function getYearReview(year: number): Promise<YearReview> {
+  return invoke<YearReview>('get_year_review', { year });
+}
@@ -0,0 +1,33 @@
+import { invoke } from '@tauri-apps/api/core';
+import type {
+  CreateScenarioInput,
+  Scenario,
+  ScenarioDefinition,
+  ScenarioResult,
+  UpdateScenarioInput,
+} from '$lib/types';
+
+export async function createScenario(input: CreateScenarioInput): Promise<Scenario> {
+  return invoke<Scenario>('create_scenario', { input });
+}
+
+export async function getScenarios(): Promise<Scenario[]> {
+  return invoke<Scenario[]>('get_scenarios');
+}
+
+export async function getScenario(id: string): Promise<Scenario> {
+  return invoke<Scenario>('get_scenario', { id });
+}
+
+export async function updateScenario(id: string, input: UpdateScenarioInput): Promise<Scenario> {
+  return invoke<Scenario>('update_scenario', { id, input });
+}
+
+export async function deleteScenario(id: string): Promise<void> {
+  await invoke('delete_scenario', { id });
+}
+
+// Accepts a saved Scenario as well as a bare definition; nothing it does is saved.
+export async function runScenario(scenario: ScenarioDefinition): Promise<ScenarioResult> {
+  return invoke<ScenarioResult>('run_scenario', { scenario });
+}
@@ -0,0 +1,7 @@
+import { invoke } from '@tauri-apps/api/core';
+import type { NoteSearchHit } from '$lib/types';
+
+// Searches entry notes and milestone labels. Supports "quoted phrases" and prefix* words.
+export async function searchNotes(query: string, limit?: number): Promise<NoteSearchHit[]> {
+  return invoke<NoteSearchHit[]>('search_notes', { query, limit });
+}
@@ -0,0 +1,11 @@
+import { invoke } from '@tauri-apps/api/core';
+import type { Settings, UpdateSettingsInput } from '$lib/types';
+
+export async function getSettings(): Promise<Settings> {
+  return invoke<Settings>('get_settings');
+}
+
+// Only the fields given are changed.
+export async function updateSettings(input: UpdateSettingsInput): Promise<Settings> {
+  return invoke<Settings>('update_settings', { input });
+}
@@ -0,0 +1,7 @@
+import { invoke } from '@tauri-apps/api/core';
+import type { SnapshotComparison } from '$lib/types';
+
+// Balances on each date are interpolated between entries; dateB must not be before dateA.
+export async function compareSnapshots(dateA: string, dateB: string): Promise<SnapshotComparison> {
+  return invoke<SnapshotComparison>('compare_snapshots', { dateA, dateB });
+}
@@ -0,0 +1,25 @@
+import { invoke } from '@tauri-apps/api/core';
+import type { AccountWithBalance, CreateTagInput, Tag } from '$lib/types';
+
+export async function getTags(): Promise<Tag[]> {
+  return invoke<Tag[]>('get_tags');
+}
+
+// Rejects a name already used by another tag, ignoring case.
+export async function createTag(input: CreateTagInput): Promise<Tag> {
+  return invoke<Tag>('create_tag', { input });
+}
+
+// Untags every account; the accounts themselves are kept.
+export async function deleteTag(id: string): Promise<void> {
+  await invoke('delete_tag', { id });
+}
+
+// Replaces the account's tags with `tagIds`, returning the new set.
+export async function setAccountTags(accountId: string, tagIds: string[]): Promise<Tag[]> {
+  return invoke<Tag[]>('set_account_tags', { accountId, tagIds });
+}
+
+export async function getAccountsByTag(tagId: string): Promise<AccountWithBalance[]> {
+  return invoke<AccountWithBalance[]>('get_accounts_by_tag', { tagId });
+}
@@ -0,0 +1,20 @@
+import { invoke } from '@tauri-apps/api/core';
+import type { UndoReport, UndoStep } from '$lib/types';
+
+// Steps whose account has since been deleted come back in `skipped` and are dropped.
+export async function undoLast(count = 1): Promise<UndoReport> {
+  return invoke<UndoReport>('undo_last', { count });
+}
+
+// Anything that changes an entry after an undo clears what can be redone.
+export async function redoLast(count = 1): Promise<UndoReport> {
+  return invoke<UndoReport>('redo_last', { count });
+}
+
+export async function getUndoLog(): Promise<UndoStep[]> {
+  return invoke<UndoStep[]>('get_undo_log');
+}
+
+export async function clearUndoLog(): Promise<void> {
+  await invoke('clear_undo_log');
+}
@@ -1,5 +1,5 @@
 // Account Types
-export type AccountType =
+export type BuiltinAccountType =
   | 'property'
   | 'pension'
   | 'investment'
@@ -8,70 +8,1623 @@ export type AccountType =
   | 'loan'
   | 'credit_card';
 
+// User-defined types arrive as { custom: slug }, keyed by the custom type's slug.
+export type AccountType = BuiltinAccountType | { custom: string };
+
+// What ACCOUNT_TYPE_LABELS and ACCOUNT_TYPE_COLORS are keyed by; every custom type shares 'custom'.
+export type AccountTypeKey = BuiltinAccountType | 'custom';
+
 export type AccountCategory = 'asset' | 'liability';
 
-export interface Account {
+export type ReminderFrequency = 'weekly' | 'monthly' | 'quarterly' | 'yearly' | 'never';
+
+// How quickly an account's money can be got at, or for a liability, how soon it falls due.
+export type Liquidity = 'liquid' | 'semi' | 'illiquid';
+
+export interface Account {
+  id: string;
+  name: string;
+  account_type: AccountType;
+  category: AccountCategory;
+  institution: string | null;
+  description: string | null;
+  currency: string;
+  is_active: boolean;
+  created_at: string;
+  updated_at: string;
+  archived_at: string | null;
+  group_id: string | null;
+  sort_order: number;
+  linked_account_id: string | null;
+  ownership_percent: number;
+  color: string | null;
+  icon: string | null;
+  interest_rate: number | null;
+  opened_at: string | null;
+  closed_at: string | null;
+  exclude_from_net_worth: boolean;
+  credit_limit: number | null;
+  reminder_frequency: ReminderFrequency | null;
+  external_ref: string | null;
+  // Day of the month statements close on; monthly figures use calendar months when null.
+  statement_day: number | null;
+  // Starts at the account type's default.
+  liquidity: Liquidity;
+  // Counted towards getEmergencyCoverage.
+  is_emergency_fund: boolean;
+  tags?: Tag[];
+}
+
+export interface AccountIssue {
+  account_id: string;
+  name: string;
+  field: 'account_type' | 'category' | 'currency';
+  value: string;
+  message: string;
+}
+
+export interface Tag {
+  id: string;
+  name: string;
+  created_at: string;
+}
+
+// Names are unique ignoring case.
+export interface CreateTagInput {
+  name: string;
+}
+
+export interface CreateAccountInput {
+  name: string;
+  account_type: AccountType;
+  institution?: string;
+  description?: string;
+  currency?: string;
+  ownership_percent?: number;
+  color?: string;
+  icon?: string;
+  interest_rate?: number;
+  opening_balance?: number;
+  opened_at?: string;
+  exclude_from_net_worth?: boolean;
+  credit_limit?: number;
+  reminder_frequency?: ReminderFrequency;
+  external_ref?: string;
+  statement_day?: number;
+  liquidity?: Liquidity;
+  is_emergency_fund?: boolean;
+}
+
+// For the nullable text fields, omit to keep the current value and send null to clear it.
+export interface UpdateAccountInput {
+  name?: string;
+  institution?: string | null;
+  description?: string | null;
+  currency?: string;
+  is_active?: boolean;
+  color?: string;
+  icon?: string;
+  interest_rate?: number;
+  exclude_from_net_worth?: boolean;
+  credit_limit?: number | null;
+  reminder_frequency?: ReminderFrequency | null;
+  external_ref?: string | null;
+  statement_day?: number | null;
+  liquidity?: Liquidity;
+  is_emergency_fund?: boolean;
+}
+
+// How mergeAccounts settles a source and target entry on the same date.
+export type MergeStrategy = 'keep_target' | 'keep_source' | 'keep_larger';
+
+export interface MergeSummary {
+  moved: number;
+  overwritten: number;
+  skipped: number;
+}
+
+export interface AccountWithBalance extends Account {
+  current_balance: number;
+  owned_balance: number;
+  balance_date: string | null;
+}
+
+export interface StaleAccount extends Account {
+  last_entry_date: string | null;
+  days_since: number | null;
+}
+
+export interface DueUpdate extends Account {
+  last_entry_date: string | null;
+  due_date: string | null;
+  periods_missed: number | null;
+}
+
+export interface Currency {
+  code: string;
+  minor_units: number;
+  name: string;
+  symbol: string;
+}
+
+// Separators are hints for the currency's usual style; example is 1234.5 written that way.
+export interface CurrencyFormat {
+  code: string;
+  symbol: string;
+  decimal_places: number;
+  decimal_separator: string;
+  thousands_separator: string;
+  example: string;
+}
+
+// Group Types
+export interface AccountGroup {
+  id: string;
+  name: string;
+  sort_order: number;
+  created_at: string;
+}
+
+export interface CreateGroupInput {
+  name: string;
+}
+
+export interface UpdateGroupInput {
+  name: string;
+}
+
+// subtotal nets the members' latest balances in the base currency, liabilities subtracted.
+export interface AccountGroupWithAccounts extends AccountGroup {
+  accounts: AccountWithBalance[];
+  subtotal: number;
+}
+
+export interface GroupedAccounts {
+  groups: AccountGroupWithAccounts[];
+  ungrouped: AccountWithBalance[];
+  ungrouped_subtotal: number;
+}
+
+// Balance Types
+export type EntrySource = 'manual' | 'import' | 'estimated' | 'scheduled';
+
+export interface BalanceEntry {
+  id: string;
+  account_id: string;
+  date: string;
+  balance: number;
+  // The balance in whole minor units (pence for GBP), at `minor_units` decimal places.
+  balance_minor: number;
+  minor_units: number;
+  notes: string | null;
+  created_at: string;
+  // Set when the balance was reported in another currency and converted.
+  original_currency: string | null;
+  original_amount: number | null;
+  source: EntrySource;
+  // Cleared whenever the balance changes.
+  verified_at: string | null;
+}
+
+// Deltas are null on the account's first entry; change_percent also when the previous balance was 0.
+export interface BalanceEntryWithDelta extends BalanceEntry {
+  previous_balance: number | null;
+  change_amount: number | null;
+  change_percent: number | null;
+  days_since_previous: number | null;
+}
+
+export interface CreateBalanceInput {
+  account_id: string;
+  date: string;
+  balance: number;
+  notes?: string;
+  // Converted into the account currency at the nearest stored rate; a
+  // `missing_exchange_rate` error means one must be entered with setExchangeRate.
+  original_currency?: string;
+  original_amount?: number;
+  // Needed to save a flagged balance when the confirm_outliers setting is on; an
+  // `unconfirmed_outlier` error carries the OutlierWarning otherwise.
+  confirm_outlier?: boolean;
+}
+
+export interface OutlierWarning {
+  previous_date: string;
+  previous_balance: number;
+  balance: number;
+  change: number;
+  change_percent: number | null;
+  sign_changed: boolean;
+}
+
+// A negative balance entered for a liability, which is stored as the positive amount owed.
+export interface SignWarning {
+  entered: number;
+  normalised: boolean;
+}
+
+export interface CreatedBalanceEntry extends BalanceEntry {
+  outlier?: OutlierWarning;
+  sign?: SignWarning;
+}
+
+export interface UpsertBalanceResult {
+  entry: BalanceEntry;
+  created: boolean;
+  outlier?: OutlierWarning;
+  sign?: SignWarning;
+}
+
+export interface AccountSignFix {
+  account_id: string;
+  name: string;
+  positive_entries: number;
+  dates: string[];
+}
+
+export interface SignNormalisationReport {
+  dry_run: boolean;
+  accounts: AccountSignFix[];
+  fixed: number;
+}
+
+// Carried in the `errors` of an `invalid_batch` error.
+export interface BatchEntryError {
+  index: number;
+  field: string;
+  message: string;
+}
+
+export interface BalanceEntryFilter {
+  start_date?: string;
+  end_date?: string;
+  search?: string;
+  sort?: 'asc' | 'desc';
+  limit?: number;
+  offset?: number;
+  // The previous page's next_cursor.
+  after?: string;
+  verified?: boolean;
+  source?: EntrySource;
+}
+
+// unverified_since counts entries after last_verified_date, or all of them when none is verified.
+export interface ReconciliationStatus {
+  account_id: string;
+  last_verified_date: string | null;
+  last_verified_at: string | null;
+  unverified_since: number;
+}
+
+export interface BalanceEntryPage {
+  entries: BalanceEntry[];
+  total: number;
+  limit: number;
+  offset: number;
+  next_cursor: string | null;
+}
+
+export interface BalanceEntryDeletion {
+  dry_run: boolean;
+  count: number;
+  dates: string[];
+  entries: BalanceEntry[];
+}
+
+export interface UpdateBalanceInput {
+  date?: string;
+  balance?: number;
+  notes?: string | null;
+  confirm_outlier?: boolean;
+}
+
+export interface DateRepair {
+  entry_id: string;
+  account_id: string;
+  date: string;
+  repaired_date: string | null;
+}
+
+export interface DateRepairReport {
+  dry_run: boolean;
+  scanned: number;
+  repaired: DateRepair[];
+  conflicts: DateRepair[];
+  unparseable: DateRepair[];
+}
+
+// Entries stored under different spellings of the same day, e.g. 2023-1-5 and 2023-01-05.
+export interface DuplicateEntryGroup {
+  account_id: string;
+  date: string;
+  entries: BalanceEntry[];
+}
+
+export type DuplicateMergeStrategy = 'latest_created' | 'largest' | 'manual_over_estimated';
+
+export interface MergedEntries {
+  account_id: string;
+  date: string;
+  kept: BalanceEntry;
+  deleted: BalanceEntry[];
+}
+
+export interface DuplicateMergeReport {
+  strategy: DuplicateMergeStrategy;
+  merged: MergedEntries[];
+  deleted: number;
+}
+
+// Collisions are left alone unless a merge strategy was given, in which case they appear in merged.
+export interface DatetimeRepairReport {
+  dry_run: boolean;
+  repaired: DateRepair[];
+  collisions: DuplicateEntryGroup[];
+  merged: MergedEntries[];
+}
+
+export type Granularity = 'day' | 'week' | 'month';
+
+export type FillMethod = 'carry_forward' | 'linear';
+
+export type SmoothingMethod = 'simple' | 'exponential';
+
+// window counts points.
+export interface SmoothingSpec {
+  window: number;
+  method: SmoothingMethod;
+}
+
+// partial marks the first window - 1 points, which average what came before them.
+export interface Smoothed {
+  value: number;
+  partial: boolean;
+}
+
+export interface SeriesPoint {
+  date: string;
+  balance: number;
+  is_estimated: boolean;
+  // Only present when smoothing was asked for.
+  smoothed?: Smoothed;
+}
+
+export interface MilestoneMarker {
+  milestone_id: string;
+  // The milestone's own date, which may fall before the point it is on.
+  date: string;
+  label: string;
+  category: MilestoneCategory;
+  icon: string | null;
+  achieved: boolean;
+}
+
+// reference_month is YYYY-MM, the latest month with a CPI value by default.
+export interface InflationOptions {
+  inflation_adjusted?: boolean;
+  reference_month?: string;
+}
+
+export interface NetWorthSeriesOptions extends InflationOptions {
+  // Linear by default.
+  method?: FillMethod;
+  exclude_estimated?: boolean;
+  include_milestones?: boolean;
+  // Smooths net.
+  smoothing?: SmoothingSpec;
+}
+
+export type BreakdownGroupBy = 'category' | 'account_type';
+
+export type AllocationGroupBy = 'account' | 'account_type' | 'institution';
+
+export interface AllocationSlice {
+  // Account id, account type or institution; empty without an institution and for "Other".
+  key: string;
+  name: string;
+  amount: number;
+  // Of total assets, or of total liabilities.
+  percent: number;
+  account_ids: string[];
+  is_other: boolean;
+}
+
+// Largest slice first; what is owed is positive.
+export interface Allocation {
+  as_of: string;
+  group_by: AllocationGroupBy;
+  total_assets: number;
+  total_liabilities: number;
+  assets: AllocationSlice[];
+  liabilities: AllocationSlice[];
+}
+
+export type CagrScope =
+  | { kind: 'net_worth' }
+  // What is owed is positive, so a shrinking debt has negative growth.
+  | { kind: 'category'; category: AccountCategory }
+  | { kind: 'account'; account_id: string };
+
+export type CagrUnavailable = 'no_data' | 'empty_window' | 'non_positive_start' | 'negative_end';
+
+export interface CagrEndpoint {
+  date: string;
+  value: number;
+  // Interpolated rather than read from entries on the date.
+  estimated: boolean;
+}
+
+// start and end are where the window was moved to fit the data; null with 'no_data'.
+export interface Cagr {
+  scope: CagrScope;
+  requested_start: string;
+  requested_end: string;
+  start: CagrEndpoint | null;
+  end: CagrEndpoint | null;
+  years: number;
+  // Under a year, so cagr extrapolates.
+  short_window: boolean;
+  // Percent per year; null with a reason in unavailable.
+  cagr: number | null;
+  unavailable: CagrUnavailable | null;
+  // Set when adjusted for inflation.
+  reference_month: string | null;
+}
+
+// 'investable' counts investment, pension and savings accounts only.
+export type FireBasis = 'all_assets' | 'investable';
+
+// Rates are percentages; those left out come from settings, and the contribution from the
+// last twelve months of flows.
+export interface FireParams {
+  annual_expenses: number;
+  safe_withdrawal_rate?: number;
+  real_growth?: number;
+  monthly_contribution?: number;
+  basis?: FireBasis;
+}
+
+export type FireStatus = 'reached' | 'projected' | 'never';
+
+// starting_value is the counted assets less every liability; fi_date is null when 'never'.
+export interface FireProjection {
+  fi_number: number;
+  starting_value: number;
+  assets: number;
+  liabilities: number;
+  progress_percent: number;
+  status: FireStatus;
+  fi_date: string | null;
+  months_to_fi: number | null;
+  safe_withdrawal_rate: number;
+  real_growth: number;
+  monthly_contribution: number;
+  basis: FireBasis;
+}
+
+// Rates are yearly percentages; those left out are estimated from month-end history, and the
+// contribution from the last twelve months of flows. account_id must be an asset.
+export interface MonteCarloParams {
+  account_id?: string;
+  months: number;
+  paths?: number;
+  annual_return?: number;
+  annual_volatility?: number;
+  monthly_contribution?: number;
+  seed?: number;
+}
+
+export interface MonteCarloBand {
+  date: string;
+  p10: number;
+  p25: number;
+  p50: number;
+  p75: number;
+  p90: number;
+}
+
+// Pass seed back in to reproduce the bands.
+export interface MonteCarloProjection {
+  start_date: string;
+  start_value: number;
+  annual_return: number;
+  annual_volatility: number;
+  estimated: boolean;
+  monthly_contribution: number;
+  paths: number;
+  seed: number;
+  bands: MonteCarloBand[];
+}
+
+// 'auto' picks whichever of the two fits the window better by R².
+export type ForecastModel = 'linear' | 'exponential' | 'auto';
+
+// monthly_rate is an amount for a linear trend and a percentage for an exponential one.
+export interface ForecastFit {
+  method: TrendMethod;
+  intercept: number;
+  monthly_rate: number;
+  r_squared: number;
+}
+
+export interface ForecastPoint {
+  date: string;
+  net_worth: number;
+}
+
+// notes say why the window shrank or a model was picked or left out.
+export interface NetWorthForecast {
+  requested_model: ForecastModel;
+  fit: ForecastFit;
+  candidates: ForecastFit[];
+  window_start: string;
+  window_end: string;
+  requested_window_months: number;
+  window_months: number;
+  data_points: number;
+  notes: string[];
+  points: ForecastPoint[];
+}
+
+export type GrowthWindowKind = 'one_month' | 'three_months' | 'twelve_months' | 'year_to_date';
+
+// Unavailable when the window starts before any balance was recorded; its figures are then null.
+export interface GrowthWindow {
+  window: GrowthWindowKind;
+  start: string;
+  available: boolean;
+  start_net_worth: number | null;
+  change: number | null;
+  // Also null when net worth was zero at the start.
+  change_percent: number | null;
+}
+
+export interface GrowthSummary {
+  as_of: string;
+  net_worth: number | null;
+  windows: GrowthWindow[];
+  // Set when adjusted for inflation.
+  reference_month: string | null;
+  cpi_estimated: boolean;
+}
+
+// Ratios rather than percentages; debt_to_assets is null without assets, and debt_to_net_worth
+// when net worth is zero or below.
+export interface DebtRatioPoint {
+  date: string;
+  assets: number;
+  liabilities: number;
+  net: number;
+  debt_to_assets: number | null;
+  debt_to_net_worth: number | null;
+  is_estimated: boolean;
+}
+
+export interface LiquidityTotals {
+  liquid: number;
+  semi: number;
+  illiquid: number;
+  total: number;
+}
+
+// Liabilities are positive; accessible_cash is liquid assets less liquid liabilities.
+export interface LiquidityPoint {
+  date: string;
+  assets: LiquidityTotals;
+  liabilities: LiquidityTotals;
+  accessible_cash: number;
+  is_estimated: boolean;
+}
+
+export interface LiquidityAccount {
+  account_id: string;
+  name: string;
+  category: AccountCategory;
+  liquidity: Liquidity;
+  amount: number;
+}
+
+// Accounts are assets first, largest first within each category.
+export interface LiquidityBreakdown extends LiquidityPoint {
+  accounts: LiquidityAccount[];
+}
+
+// recovery_date is null while still below the peak; depth is positive.
+export interface Drawdown {
+  peak_date: string;
+  peak: number;
+  trough_date: string;
+  trough: number;
+  recovery_date: string | null;
+  depth: number;
+  depth_percent: number | null;
+}
+
+export interface CurrentDrawdown {
+  high_date: string;
+  high: number;
+  value: number;
+  depth: number;
+  depth_percent: number | null;
+}
+
+// drawdowns holds those at least threshold_percent deep, oldest first.
+export interface DrawdownAnalysis {
+  start: string;
+  end: string;
+  max_drawdown: Drawdown | null;
+  current: CurrentDrawdown | null;
+  drawdowns: Drawdown[];
+  threshold_percent: number;
+}
+
+// mostly_estimated: over half of what was held or owed at the end was estimated.
+export interface PeriodChange {
+  start: string;
+  end: string;
+  start_net_worth: number;
+  end_net_worth: number;
+  change: number;
+  change_percent: number | null;
+  mostly_estimated: boolean;
+}
+
+export type StreakDirection = 'up' | 'down' | 'flat';
+
+export interface Streak {
+  direction: StreakDirection;
+  start: string;
+  end: string;
+  periods: number;
+}
+
+// Ties go to the more recent period; the first and last years are cut short by the range.
+export interface PeriodStats {
+  granularity: Granularity;
+  start: string;
+  end: string;
+  changes: PeriodChange[];
+  best_period: PeriodChange | null;
+  worst_period: PeriodChange | null;
+  best_year: PeriodChange | null;
+  worst_year: PeriodChange | null;
+  longest_rise: Streak | null;
+  current_streak: Streak | null;
+  average_change: number | null;
+  change_std_dev: number | null;
+}
+
+// Keyed by category ('asset', 'liability') or account type; what is owed is positive.
+export interface NetWorthBreakdownPoint {
+  date: string;
+  groups: Record<string, number>;
+  is_estimated: boolean;
+}
+
+// In the base currency, at each account's owned share.
+export interface NetWorthPoint {
+  date: string;
+  assets: number;
+  // What is owed, as a positive amount.
+  liabilities: number;
+  net: number;
+  is_estimated: boolean;
+  // Of net; only present when smoothing was asked for.
+  smoothed?: Smoothed;
+  // Only present when milestones were asked for and some fall in the point's period.
+  milestones?: MilestoneMarker[];
+}
+
+// One per month, or statement period when the account has a statement_day.
+export interface MonthlyBalance {
+  month: string;
+  period_start: string;
+  period_end: string;
+  balance: number;
+  entry_date: string;
+  is_estimated: boolean;
+  change: number | null;
+  change_percent: number | null;
+}
+
+export type BalanceAtStatus = 'exact' | 'estimated' | 'no_data';
+
+export interface BalanceAt {
+  account_id: string;
+  date: string;
+  status: BalanceAtStatus;
+  balance: number | null;
+  derived_from: { id: string; date: string }[];
+}
+
+export interface ExchangeRate {
+  id: string;
+  date: string;
+  from_currency: string;
+  to_currency: string;
+  rate: number;
+  created_at: string;
+}
+
+// Flow Types
+export type FlowKind = 'contribution' | 'withdrawal';
+
+export interface Flow {
+  id: string;
+  account_id: string;
+  date: string;
+  amount: number;
+  kind: FlowKind;
+  notes: string | null;
+  created_at: string;
+}
+
+export interface CreateFlowInput {
+  account_id: string;
+  date: string;
+  amount: number;
+  kind: FlowKind;
+  notes?: string;
+}
+
+export interface UpdateFlowInput {
+  date?: string;
+  amount?: number;
+  kind?: FlowKind;
+  notes?: string | null;
+}
+
+// For a liability the balances are amounts owed and contributions are repayments.
+export interface AccountGrowth {
+  account_id: string;
+  start: string;
+  end: string;
+  start_balance: number;
+  end_balance: number;
+  change: number;
+  contributions: number;
+  withdrawals: number;
+  net_flow: number;
+  growth: number;
+  has_flows: boolean;
+}
+
+export interface NetWorthGrowth {
+  start: string;
+  end: string;
+  start_net_worth: number;
+  end_net_worth: number;
+  change: number;
+  saved: number;
+  grown: number;
+  accounts: AccountGrowth[];
+}
+
+export type ReturnUnavailable = 'no_data' | 'non_positive_value' | 'no_convergence';
+
+// end_value includes the flows dated end; net_flow has withdrawals negative.
+export interface SubPeriodReturn {
+  start: string;
+  end: string;
+  start_value: number;
+  end_value: number;
+  net_flow: number;
+  return_percent: number | null;
+  estimated: boolean;
+}
+
+// Percentages; twr is over the whole period, twr_annualised and xirr are per year.
+export interface TimeWeightedReturn {
+  account_id: string;
+  start: string;
+  end: string;
+  periods: SubPeriodReturn[];
+  twr: number | null;
+  twr_annualised: number | null;
+  twr_unavailable: ReturnUnavailable | null;
+  xirr: number | null;
+  xirr_unavailable: ReturnUnavailable | null;
+  has_flows: boolean;
+}
+
+// Loan-to-value Types
+// The entry a figure was taken from.
+export interface BalanceUsed {
+  account_id: string;
+  date: string;
+  balance: number;
+}
+
+export interface LtvPoint {
+  date: string;
+  asset_balance: number;
+  liability_balance: number;
+  ltv_percent: number;
+}
+
+// current_ltv_percent is null until both sides have entries, or while the asset is valued at zero.
+// series is month-end LTV over the period both sides have data.
+export interface LtvReport {
+  asset_id: string;
+  current_ltv_percent: number | null;
+  asset_balance: BalanceUsed | null;
+  liability_balances: BalanceUsed[];
+  series: LtvPoint[];
+}
+
+// Interest Types
+// interest_accrued is the interest added since the latest entry, in the same sign as balance.
+export interface ProjectedPoint {
+  date: string;
+  balance: number;
+  interest_accrued: number;
+}
+
+// Amortisation Types
+export interface AmortisationRow {
+  month: number;
+  date: string;
+  opening_balance: number;
+  interest: number;
+  principal: number;
+  closing_balance: number;
+}
+
+// monthly_payment includes any overpayment; the last payment is only what is left.
+export interface AmortisationPlan {
+  monthly_payment: number;
+  rows: AmortisationRow[];
+  total_interest: number;
+  total_paid: number;
+  payoff_date: string;
+}
+
+export interface AmortisationSchedule {
+  account_id: string;
+  start_date: string;
+  balance: number;
+  interest_rate: number;
+  plan: AmortisationPlan;
+  with_overpayment: AmortisationPlan | null;
+  interest_saved: number | null;
+  months_saved: number | null;
+}
+
+export type PayoffStatus = 'projected' | 'paid_off' | 'not_reducing' | 'insufficient_data';
+
+// From the trend of the latest entries; remaining is what is owed at the latest one.
+export interface PayoffProjection {
+  account_id: string;
+  status: PayoffStatus;
+  payoff_date: string | null;
+  average_monthly_reduction: number | null;
+  remaining: number;
+  data_points: number;
+}
+
+// Income Types
+// net_income is in the base currency; one record per month.
+export interface IncomeRecord {
+  id: string;
+  month: string;
+  net_income: number;
+  notes: string | null;
+  created_at: string;
+}
+
+export interface CreateIncomeRecordInput {
+  month: string;
+  net_income: number;
+  notes?: string;
+}
+
+export interface UpdateIncomeRecordInput {
+  month?: string;
+  net_income?: number;
+  notes?: string | null;
+}
+
+export interface ReviewEndpoint {
+  date: string;
+  net_worth: number;
+}
+
+// For a liability the values are amounts owed and a fall is a gain.
+export interface ReviewAccountChange {
+  account_id: string;
+  name: string;
+  start_value: number;
+  end_value: number;
+  change: number;
+}
+
+export interface ReviewAccountEvent {
+  account_id: string;
+  name: string;
+  date: string;
+}
+
+// year is the calendar year the financial year starts in; notes explain a partial year.
+export interface YearReview {
+  year: number;
+  start: string;
+  end: string;
+  partial: boolean;
+  notes: string[];
+  opening: ReviewEndpoint | null;
+  closing: ReviewEndpoint | null;
+  change: number | null;
+  // Null without flows, in which case the change isn't split.
+  contributions: number | null;
+  growth: number | null;
+  biggest_gain: ReviewAccountChange | null;
+  biggest_loss: ReviewAccountChange | null;
+  milestones: Milestone[];
+  balance_updates: number;
+  accounts_opened: ReviewAccountEvent[];
+  accounts_closed: ReviewAccountEvent[];
+  series: NetWorthPoint[];
+}
+
+// Amounts are paid in, or for a liability paid off; negative amounts take out or borrow more.
+export type ScenarioOperation =
+  | {
+      kind: 'add_account';
+      name: string;
+      category: AccountCategory;
+      opening_balance: number;
+      start_date: string;
+      monthly_contribution?: number;
+      interest_rate?: number | null;
+    }
+  | {
+      kind: 'monthly_flow';
+      account_id: string;
+      amount: number;
+      start_date: string;
+      end_date?: string | null;
+    }
+  | { kind: 'lump_sum'; account_id: string; amount: number; date: string };
+
+export interface ScenarioDefinition {
+  horizon_months: number;
+  operations: ScenarioOperation[];
+}
+
+export interface Scenario extends ScenarioDefinition {
   id: string;
   name: string;
-  account_type: AccountType;
-  category: AccountCategory;
-  institution: string | null;
   description: string | null;
-  currency: string;
-  is_active: boolean;
   created_at: string;
   updated_at: string;
 }
 
-export interface CreateAccountInput {
+export interface CreateScenarioInput extends ScenarioDefinition {
   name: string;
-  account_type: AccountType;
-  institution?: string;
   description?: string;
-  currency?: string;
 }
 
-export interface UpdateAccountInput {
+export interface UpdateScenarioInput {
   name?: string;
-  institution?: string;
-  description?: string;
-  currency?: string;
-  is_active?: boolean;
+  description?: string | null;
+  horizon_months?: number;
+  operations?: ScenarioOperation[];
 }
 
-export interface AccountWithBalance extends Account {
-  current_balance: number;
-  balance_date: string | null;
+export interface ScenarioPoint {
+  date: string;
+  baseline: number;
+  scenario: number;
+  delta: number;
 }
 
-// Balance Types
-export interface BalanceEntry {
+// Points run from today to each month end up to the horizon.
+export interface ScenarioResult {
+  points: ScenarioPoint[];
+  delta_at_horizon: number;
+}
+
+// month is YYYY-MM; only ratios between index values matter.
+export interface CpiRate {
+  month: string;
+  index_value: number;
+  created_at: string;
+}
+
+export interface CpiRowError {
+  line: number;
+  message: string;
+}
+
+export interface CpiImportReport {
+  imported: number;
+  errors: CpiRowError[];
+}
+
+export interface BenchmarkValue {
+  date: string;
+  value: number;
+}
+
+// fixed_rate compounds a twelfth of annual_rate (a percentage) monthly; custom points are interpolated.
+export type BenchmarkSpec =
+  | { kind: 'fixed_rate'; annual_rate: number }
+  | { kind: 'custom_points'; points: BenchmarkValue[] };
+
+export type Benchmark = BenchmarkSpec & {
   id: string;
-  account_id: string;
+  name: string;
+  created_at: string;
+};
+
+export type CreateBenchmarkInput = BenchmarkSpec & { name: string };
+
+export interface UpdateBenchmarkInput {
+  name?: string;
+  spec?: BenchmarkSpec;
+}
+
+// value is null outside a custom benchmark's points, net_worth before any balance.
+export interface BenchmarkPoint {
   date: string;
-  balance: number;
-  notes: string | null;
+  value: number | null;
+  net_worth: number | null;
+}
+
+export type SavingsSource = 'flows' | 'net_worth_change';
+
+// Rates are percentages; months missing income are flagged and left out of the pooled rates.
+export interface SavingsRateMonth {
+  month: string;
+  income: number | null;
+  saved: number | null;
+  source: SavingsSource;
+  rate: number | null;
+  missing_income: boolean;
+  trailing_12_month_rate: number | null;
+}
+
+export interface SavingsRate {
+  start: string;
+  end: string;
+  months: SavingsRateMonth[];
+  average_rate: number | null;
+}
+
+// Snapshot Comparison Types
+export type SnapshotPresence = 'both' | 'appeared' | 'closed';
+
+// Balances are the owned share; what is owed counts as positive. null means not held on that date.
+export interface AccountSnapshotChange {
+  account_id: string;
+  name: string;
+  category: AccountCategory;
+  currency: string;
+  presence: SnapshotPresence;
+  balance_a: number | null;
+  balance_b: number | null;
+  change: number;
+  change_percent: number | null;
+  is_estimated: boolean;
+  exclude_from_net_worth: boolean;
+}
+
+export interface CategorySnapshotTotal {
+  category: AccountCategory;
+  total_a: number;
+  total_b: number;
+  change: number;
+  change_percent: number | null;
+}
+
+export interface SnapshotComparison {
+  date_a: string;
+  date_b: string;
+  accounts: AccountSnapshotChange[];
+  totals: CategorySnapshotTotal[];
+  net_worth_a: number;
+  net_worth_b: number;
+  net_worth_change: number;
+}
+
+// Recurring Adjustment Types
+// A delta is added to the previous balance; an absolute amount is the balance itself.
+export type AdjustmentKind = 'delta' | 'absolute';
+
+export interface RecurringAdjustment {
+  id: string;
+  account_id: string;
+  amount: number;
+  day_of_month: number;
+  start_date: string;
+  end_date: string | null;
+  kind: AdjustmentKind;
+  last_applied: string | null;
   created_at: string;
 }
 
-export interface CreateBalanceInput {
+export interface CreateAdjustmentInput {
   account_id: string;
-  date: string;
-  balance: number;
-  notes?: string;
+  amount: number;
+  day_of_month: number;
+  start_date: string;
+  end_date?: string;
+  kind: AdjustmentKind;
+}
+
+export interface UpdateAdjustmentInput {
+  amount?: number;
+  day_of_month?: number;
+  start_date?: string;
+  end_date?: string | null;
+  kind?: AdjustmentKind;
+}
+
+export interface AppliedAdjustments {
+  created: BalanceEntry[];
+  skipped: { adjustment_id: string; account_id: string; date: string; reason: string }[];
+}
+
+// A one-off change to several accounts: { percent: 2.3 } or { delta: -100 }.
+export type BulkAdjustment = { percent: number } | { delta: number };
+
+export interface BulkAdjustmentResult {
+  created: BalanceEntry[];
+  skipped: { account_id: string; latest_date: string | null; reason: string }[];
 }
 
 // Milestone Types
+// An account target sums the linked accounts; when all are liabilities it is the amount owed,
+// reached by falling to it.
+export type MilestoneTargetScope = 'net_worth' | 'account';
+
+export type MilestoneRecurrence = 'none' | 'monthly' | 'yearly';
+
+// 'custom' milestones are drawn with their own icon.
+export type MilestoneCategory = 'general' | 'purchase' | 'payoff' | 'life_event' | 'custom';
+
+// Which milestones of a recurring series an edit or deletion applies to.
+export type MilestoneEditScope = 'this_instance' | 'all_future';
+
 export interface Milestone {
   id: string;
   date: string;
   label: string;
+  account_ids: string[];
+  created_at: string;
+  target_amount: number | null;
+  target_scope: MilestoneTargetScope | null;
+  achieved_at: string | null;
+  // Set on templates; instances link back through parent_id.
+  recurrence: MilestoneRecurrence;
+  parent_id: string | null;
+  materialised_until: string | null;
+  category: MilestoneCategory;
+  icon: string | null;
+  // Its place among the milestones on the same date.
+  sort_order: number;
+}
+
+// Every field is optional and those given must all match; dates are inclusive.
+export interface MilestoneFilter {
+  start?: string;
+  end?: string;
+  category?: MilestoneCategory;
+  // true: achieved targets; false: targets not yet reached.
+  achieved?: boolean;
+  account_id?: string;
+  search?: string;
+  limit?: number;
+  offset?: number;
+}
+
+export interface MilestoneListItem extends Milestone {
+  // In the order of account_ids.
+  account_names: string[];
+}
+
+export type MilestoneSuggestionKind = 'paid_off' | 'bought' | 'net_worth_crossed';
+
+// Offered by suggestMilestones; saved only once accepted.
+export interface MilestoneSuggestion {
+  key: string;
+  kind: MilestoneSuggestionKind;
+  date: string;
+  label: string;
+  account_ids: string[];
+  target_amount: number | null;
+  target_scope: MilestoneTargetScope | null;
+  category: MilestoneCategory;
+}
+
+export interface MilestoneAccountBalance {
+  account_id: string;
+  // The owned share, with what is owed as a positive amount; null when not held then.
+  balance: number | null;
+  is_estimated: boolean;
+}
+
+export interface MilestoneWithNetWorth extends Milestone {
+  net_worth: number | null;
+  net_worth_estimated: boolean;
+  account_balances: MilestoneAccountBalance[];
+}
+
+export interface CreateMilestoneInput {
+  date: string;
+  label: string;
+  account_ids?: string[];
+  target_amount?: number;
+  target_scope?: MilestoneTargetScope;
+  recurrence?: MilestoneRecurrence;
+  category?: MilestoneCategory;
+  icon?: string;
+}
+
+// null clears a field; account_ids replaces every link. Changing the target clears achieved_at.
+export interface UpdateMilestoneInput {
+  date?: string;
+  label?: string;
+  account_ids?: string[];
+  target_amount?: number | null;
+  target_scope?: MilestoneTargetScope | null;
+  recurrence?: MilestoneRecurrence;
+  category?: MilestoneCategory;
+  icon?: string | null;
+}
+
+export type TrendMethod = 'linear' | 'exponential';
+
+export type MilestoneProjectionStatus = 'achieved' | 'on_track' | 'not_on_track';
+
+// monthly_rate is an amount for a linear trend and a percentage for an exponential one.
+export interface MilestoneProjection {
+  milestone_id: string;
+  method: TrendMethod;
+  status: MilestoneProjectionStatus;
+  date: string | null;
+  monthly_rate: number | null;
+  data_points: number;
+  note: string | null;
+}
+
+// A balance entry note or milestone label matching a search; snippet wraps matches in <mark>.
+export interface NoteSearchHit {
+  kind: 'entry' | 'milestone';
+  id: string;
+  account_id: string | null;
+  account_name: string | null;
+  date: string;
+  snippet: string;
+}
+
+// Credit Types
+export interface CardUtilisation {
+  account_id: string;
+  name: string;
+  balance: number;
+  balance_date: string | null;
+  credit_limit: number;
+  utilisation_percent: number;
+}
+
+export interface CardWithoutLimit {
+  account_id: string;
+  name: string;
+  balance: number;
+  balance_date: string | null;
+}
+
+export interface UtilisationPoint {
+  date: string;
+  balance: number;
+  credit_limit: number;
+  utilisation_percent: number;
+}
+
+export interface CreditUtilisationReport {
+  cards: CardUtilisation[];
+  cards_without_limit: CardWithoutLimit[];
+  total_balance: number;
+  total_limit: number;
+  utilisation_percent: number | null;
+  series: UtilisationPoint[];
+}
+
+// Pension Types
+export interface PensionDetails {
+  account_id: string;
+  employer: string | null;
+  employee_contribution_percent: number;
+  employer_contribution_percent: number;
+  annual_salary: number | null;
+  retirement_date: string | null;
+  updated_at: string;
+}
+
+export interface SetPensionDetailsInput {
+  employer?: string;
+  employee_contribution_percent?: number;
+  employer_contribution_percent?: number;
+  annual_salary?: number;
+  retirement_date?: string;
+}
+
+export interface PensionProjectionPoint {
+  date: string;
+  balance: number;
+  contributions: number;
+  growth: number;
+}
+
+// Property Types
+export interface PropertyDetails {
+  account_id: string;
+  purchase_price: number;
+  purchase_date: string;
+  deposit_paid: number | null;
+  updated_at: string;
+}
+
+export interface SetPropertyDetailsInput {
+  purchase_price: number;
+  purchase_date: string;
+  deposit_paid?: number;
+}
+
+export interface PropertyPerformance {
+  account_id: string;
+  purchase_price: number;
+  purchase_date: string;
+  current_value: number;
+  valuation_date: string;
+  appreciation: number;
+  appreciation_percent: number;
+  annualised_percent: number | null;
+  mortgage_balance: number | null;
+  equity: number | null;
+}
+
+// Import Types
+export type CsvRowStatus = 'created' | 'skipped_duplicate' | 'error';
+
+export interface CsvRowReport {
+  line: number;
+  name: string;
+  status: CsvRowStatus;
+  message: string | null;
   account_id: string | null;
+}
+
+export interface CsvImportReport {
+  dry_run: boolean;
+  created: number;
+  skipped: number;
+  errors: number;
+  rows: CsvRowReport[];
+}
+
+// date_format is a strftime pattern; a ',' decimal separator switches the delimiter to ';'.
+export interface BalanceCsvExportOptions {
+  date_format?: string;
+  decimal_separator?: '.' | ',';
+  include_interpolated?: boolean;
+  overwrite?: boolean;
+}
+
+// Both dates are optional and inclusive.
+export interface DateRange {
+  start?: string;
+  end?: string;
+}
+
+export interface BalanceCsvMapping {
+  date_column: string;
+  balance_column: string;
+  notes_column?: string;
+  // A chrono format such as '%d/%m/%Y'; ISO dates when omitted.
+  date_format?: string;
+  decimal_separator?: '.' | ',';
+  flip_sign?: boolean;
+}
+
+export type DuplicatePolicy = 'skip' | 'overwrite';
+
+export type BalanceCsvRowStatus =
+  | 'created'
+  | 'duplicate'
+  | 'overwritten'
+  | 'skipped_duplicate'
+  | 'error';
+
+export interface BalanceCsvRow {
+  line: number;
+  date: string | null;
+  balance: number | null;
+  notes: string | null;
+  status: BalanceCsvRowStatus;
+  message: string | null;
+}
+
+export interface BalanceCsvReport {
+  dry_run: boolean;
+  created: number;
+  overwritten: number;
+  skipped: number;
+  duplicates: number;
+  errors: number;
+  rows: BalanceCsvRow[];
+}
+
+export type QifImportMode = 'closing_balance_per_month' | 'balance_records_only';
+export type QifDateOrder = 'day_first' | 'month_first';
+
+export interface QifImportOptions {
+  // Only used by closing_balance_per_month; 0 when omitted.
+  opening_balance?: number;
+  // Needed when every date in the file reads both ways round.
+  date_order?: QifDateOrder;
+}
+
+export interface OfxStatement {
+  account_id: string;
+  bank_id: string | null;
+  account_type: string | null;
+  currency: string | null;
+  ledger_balance: number | null;
+  ledger_date: string | null;
+  available_balance: number | null;
+  available_date: string | null;
+}
+
+// With several statements and none picked, only `statements` is filled in.
+export interface OfxImportReport {
+  dry_run: boolean;
+  statements: OfxStatement[];
+  selected: string | null;
+  balance: number | null;
+  date: string | null;
+  currency_mismatch: string | null;
+  result: UpsertBalanceResult | null;
+}
+
+// Attachment Types
+export interface Attachment {
+  id: string;
+  balance_entry_id: string;
+  filename: string;
+  stored_name: string;
+  mime_type: string;
+  size_bytes: number;
+  added_at: string;
+}
+
+export interface OrphanedAttachments {
+  dry_run: boolean;
+  orphaned_files: string[];
+  missing_files: Attachment[];
+}
+
+// History Types
+export interface AccountFieldChange {
+  id: string;
+  account_id: string;
+  field: 'name' | 'institution' | 'account_type';
+  old_value: string | null;
+  new_value: string | null;
+  changed_at: string;
+}
+
+// The new_* fields are null for deletions.
+export interface BalanceEntryChange {
+  id: string;
+  entry_id: string;
+  account_id: string;
+  operation: 'update' | 'delete';
+  old_date: string;
+  old_balance: number;
+  old_notes: string | null;
+  new_date: string | null;
+  new_balance: number | null;
+  new_notes: string | null;
+  old_verified_at: string | null;
+  new_verified_at: string | null;
+  changed_at: string;
+}
+
+// Undo Types
+export type UndoOperation = 'create' | 'update' | 'delete' | 'import' | 'adjust';
+
+export interface UndoStep {
+  id: number;
+  operation: UndoOperation;
+  description: string;
+  undone: boolean;
   created_at: string;
 }
 
-// Utility function to get category from account type
-export function getCategoryForType(type: AccountType): AccountCategory {
+export interface SkippedUndo {
+  step: UndoStep;
+  reason: string;
+}
+
+export interface UndoReport {
+  applied: UndoStep[];
+  skipped: SkippedUndo[];
+}
+
+// Settings Types
+export type SignPolicy = 'allow' | 'warn' | 'normalise';
+
+export type NotificationKind = 'achieved' | 'upcoming';
+
+export interface MilestoneNotification {
+  milestone_id: string;
+  kind: NotificationKind;
+  // The achievement date, or the milestone's date when upcoming.
+  date: string;
+  title: string;
+  body: string;
+}
+
+export interface Settings {
+  max_future_days: number;
+  // Balances at least outlier_percent and outlier_min_change from the previous entry are flagged.
+  outlier_percent: number;
+  outlier_min_change: number;
+  confirm_outliers: boolean;
+  // Totals across accounts are rounded to this currency's minor units.
+  base_currency: string;
+  sign_policy: SignPolicy;
+  notifications_enabled: boolean;
+  // Milestones are announced as upcoming this many days before their date.
+  notify_days_ahead: number;
+  // MM-DD; year_to_date growth is measured from this day.
+  fiscal_year_start: string;
+  // Percentages assumed by getFireProjection when not given.
+  safe_withdrawal_rate: number;
+  expected_real_growth: number;
+  // In the base currency; 0 until set, which leaves emergency coverage unmeasured.
+  monthly_expenses: number;
+}
+
+export type UpdateSettingsInput = Partial<Settings>;
+
+// Backup Types
+export interface Backup {
+  version: number;
+  exported_at: string;
+  custom_account_types: unknown[];
+  groups: unknown[];
+  accounts: Account[];
+  balance_entries: BalanceEntry[];
+  milestones: Milestone[];
+  tags: Tag[];
+  account_tags: { account_id: string; tag_id: string }[];
+  account_field_history: AccountFieldChange[];
+  pension_details: PensionDetails[];
+  property_details: PropertyDetails[];
+  settings: { key: string; value: string; updated_at: string }[];
+  balance_entry_history: BalanceEntryChange[];
+  exchange_rates: ExchangeRate[];
+  flows: Flow[];
+  recurring_adjustments: RecurringAdjustment[];
+  income_records: IncomeRecord[];
+  benchmarks: Benchmark[];
+  cpi_rates: CpiRate[];
+  scenarios: Scenario[];
+  allocation_targets: AllocationTarget[];
+  attachments: Attachment[];
+}
+
+export interface ImportSummary {
+  accounts: number;
+  balance_entries: number;
+  milestones: number;
+}
+
+// Utility function to get category from account type; a custom type's is on the account itself.
+export function getCategoryForType(type: BuiltinAccountType): AccountCategory {
   switch (type) {
     case 'property':
     case 'pension':
@@ -85,8 +1638,12 @@ export function getCategoryForType(type: AccountType): AccountCategory {
   }
 }
 
+export function accountTypeKey(type: AccountType): AccountTypeKey {
+  return typeof type === 'string' ? type : 'custom';
+}
+
 // Account type display labels
-export const ACCOUNT_TYPE_LABELS: Record<AccountType, string> = {
+export const ACCOUNT_TYPE_LABELS: Record<AccountTypeKey, string> = {
   property: 'Property',
   pension: 'Pension',
   investment: 'Investment',
@@ -94,6 +1651,7 @@ export const ACCOUNT_TYPE_LABELS: Record<AccountType, string> = {
   mortgage: 'Mortgage',
   loan: 'Loan',
   credit_card: 'Credit Card',
+  custom: 'Custom',
 };
 
 // Chart Types
@@ -116,7 +1674,7 @@ export interface NetWorthStats {
 }
 
 // Account type Tailwind background classes
-export const ACCOUNT_TYPE_COLORS: Record<AccountType, string> = {
+export const ACCOUNT_TYPE_COLORS: Record<AccountTypeKey, string> = {
   property: 'bg-account-property',
   pension: 'bg-account-pension',
   investment: 'bg-account-investment',
@@ -124,4 +1682,80 @@ export const ACCOUNT_TYPE_COLORS: Record<AccountType, string> = {
   mortgage: 'bg-account-mortgage',
   loan: 'bg-account-loan',
   credit_card: 'bg-account-credit-card',
+  custom: 'bg-account-custom',
+};
+
+// refreshed_from is null when the snapshots were already current.
+export interface SnapshotRefresh {
+  refreshed_from: string | null;
+  valid_until: string;
+  snapshots: number;
+}
+
+// The targets in force are all by account type or all by tag.
+export type AllocationBucket =
+  | { kind: 'account_type'; account_type: AccountType }
+  | { kind: 'tag'; tag_id: string };
+
+export type AllocationTarget = AllocationBucket & {
+  id: string;
+  target_percent: number;
+  created_at: string;
+  updated_at: string;
+};
+
+export type CreateAllocationTargetInput = AllocationBucket & {
+  target_percent: number;
 };
+
+export interface UpdateAllocationTargetInput {
+  target_percent: number;
+}
+
+// Drifts are actual less target; to_move is what to pay in (negative: take out).
+export type AllocationDriftBucket = AllocationBucket & {
+  target_id: string;
+  name: string;
+  account_ids: string[];
+  actual_amount: number;
+  actual_percent: number;
+  target_amount: number;
+  target_percent: number;
+  drift_percent: number;
+  drift_amount: number;
+  to_move: number;
+};
+
+export interface UnallocatedHolding {
+  key: string;
+  name: string;
+  account_ids: string[];
+  amount: number;
+  percent: number;
+}
+
+export interface AllocationDrift {
+  as_of: string;
+  total_assets: number;
+  buckets: AllocationDriftBucket[];
+  unallocated: UnallocatedHolding[];
+}
+
+export type EmergencyCoverageStatus = 'available' | 'configure_expenses' | 'no_accounts';
+
+export interface EmergencyCoveragePoint {
+  date: string;
+  total: number;
+  months_covered: number | null;
+}
+
+// months_covered is null unless status is 'available'; series is month ends over two years.
+export interface EmergencyCoverage {
+  as_of: string;
+  status: EmergencyCoverageStatus;
+  monthly_expenses: number;
+  account_ids: string[];
+  total: number;
+  months_covered: number | null;
+  series: EmergencyCoveragePoint[];
+}